@@ -33,16 +33,23 @@
 //! ```
 
 use anyhow::{Context, Result};
+use providers::{ModelList, TagList};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 pub mod error;
 pub mod providers;
+mod sse;
+pub mod truncate;
 
 pub use error::AiError;
+pub use truncate::{truncate, Truncation};
 
 /// AI provider type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -53,6 +60,17 @@ pub enum AiProvider {
     Local,
 }
 
+impl AiProvider {
+    /// Lowercase name, as in the config
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OpenAI => "openai",
+            Self::Anthropic => "anthropic",
+            Self::Local => "local",
+        }
+    }
+}
+
 /// AI client configuration
 #[derive(Debug, Clone)]
 pub struct AiConfig {
@@ -79,6 +97,13 @@ pub struct AiConfig {
 
     /// Enable caching
     pub enable_cache: bool,
+
+    /// Longest text sent to the provider, in bytes; longer text is cut
+    /// with `truncation` first
+    pub max_input_bytes: Option<usize>,
+
+    /// How text over `max_input_bytes` is cut
+    pub truncation: Truncation,
 }
 
 impl Default for AiConfig {
@@ -88,19 +113,213 @@ impl Default for AiConfig {
             api_key: None,
             model: "gpt-4".to_string(),
             endpoint: None,
-            system_prompt: "You are a helpful assistant that creates concise summaries.".to_string(),
+            system_prompt: "You are a helpful assistant that creates concise summaries."
+                .to_string(),
             max_tokens: 500,
             temperature: 0.7,
             enable_cache: true,
+            max_input_bytes: None,
+            truncation: Truncation::default(),
+        }
+    }
+}
+
+/// How a summary is written, on top of the system prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SummaryStyle {
+    /// A few bullet points
+    Bullets,
+    /// One or two sentences
+    Tldr,
+    /// Several paragraphs covering arguments and details
+    DeepDive,
+    /// A research paper's problem, method, results, and limitations, from
+    /// its abstract (for arXiv and OAI-PMH feeds)
+    Paper,
+    /// An overview of many articles' summaries (for digests)
+    Overview,
+    /// Recurring themes across a period's topic clusters (for reports)
+    Trends,
+}
+
+impl SummaryStyle {
+    /// Styles for a single article, in cycling order
+    pub const ALL: [SummaryStyle; 3] = [Self::Bullets, Self::Tldr, Self::DeepDive];
+
+    /// Instruction appended to the system prompt
+    pub fn instruction(self) -> &'static str {
+        match self {
+            Self::Bullets => "Summarize as 3 to 5 short bullet points, one line each.",
+            Self::Tldr => "Summarize in one or two sentences (a TL;DR).",
+            Self::DeepDive => {
+                "Write a detailed summary of several paragraphs covering the main \
+                 argument, supporting evidence, and notable details."
+            }
+            Self::Paper => {
+                "The text is a research paper's abstract, possibly with its authors \
+                 and comments. Summarize it for a researcher in the field as 3 to 5 \
+                 short bullet points: the problem addressed, the method or approach, \
+                 the main results (with their numbers, datasets, or benchmarks when \
+                 given), and any stated limitations. Skip the motivation and claims of \
+                 significance, and don't invent details the abstract doesn't give."
+            }
+            Self::Overview => {
+                "The text holds summaries of several articles, each under its title. \
+                 Write an overview of one or two short paragraphs: the main themes \
+                 across them and the items most worth reading."
+            }
+            Self::Trends => {
+                "The text groups a period's articles into topics, each with how many \
+                 articles and sources covered it, followed by their titles and summaries. \
+                 Write a short trends report: the three most recurring themes, how \
+                 coverage of each developed, and which sources drove it."
+            }
         }
     }
+
+    /// Display name
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Bullets => "bullets",
+            Self::Tldr => "TL;DR",
+            Self::DeepDive => "deep dive",
+            Self::Paper => "paper",
+            Self::Overview => "overview",
+            Self::Trends => "trends",
+        }
+    }
+}
+
+/// Per-request summarization options
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SummaryOptions {
+    /// Style instruction; `None` uses the system prompt alone
+    pub style: Option<SummaryStyle>,
+
+    /// Generate a new summary even if one is cached
+    pub skip_cache: bool,
+}
+
+/// How long a health check or model listing waits for the provider
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Models named in a [`AiError::ModelNotAvailable`] error
+const MODELS_SHOWN: usize = 5;
+
+/// Whether a provider error is the HTTP client's timeout
+fn is_timeout(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<AiError>() {
+        Some(AiError::HttpError(e)) => e.is_timeout(),
+        _ => error
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout),
+    }
+}
+
+/// Error for a request the provider refused
+fn status_error(provider: AiProvider, status: StatusCode, body: &str) -> AiError {
+    let body: String = body.trim().chars().take(200).collect();
+    let detail = match body.is_empty() {
+        true => format!("{} answered HTTP {}", provider.name(), status.as_u16()),
+        false => format!(
+            "{} answered HTTP {}: {}",
+            provider.name(),
+            status.as_u16(),
+            body
+        ),
+    };
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => AiError::AuthError(detail),
+        StatusCode::TOO_MANY_REQUESTS => AiError::RateLimitError(detail),
+        _ => AiError::ApiError(detail),
+    }
+}
+
+/// Error the provider reported in the middle of a stream
+fn stream_error(provider: AiProvider, message: &str) -> anyhow::Error {
+    AiError::ApiError(format!(
+        "{} stopped answering: {}",
+        provider.name(),
+        message
+    ))
+    .into()
+}
+
+/// Generated text, which must not be empty
+fn answer(text: String) -> Result<String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(AiError::InvalidResponse("the answer is empty".into()).into());
+    }
+    Ok(text)
+}
+
+/// The answer to a request to `url`, which must exist
+fn found<T>(url: &str, answer: Option<T>) -> Result<T> {
+    answer.ok_or_else(|| {
+        AiError::ApiError(format!("{} not found (HTTP 404); check the endpoint", url)).into()
+    })
+}
+
+/// Whether `model` is among `models`: as named, as Ollama's `:latest` tag,
+/// or, for an alias ending in `-latest`, as any dated version of it
+fn offers(models: &[String], model: &str) -> bool {
+    let tagged = format!("{}:latest", model);
+    let alias = model
+        .strip_suffix("-latest")
+        .map(|base| format!("{}-", base));
+    models.iter().any(|m| {
+        *m == model || *m == tagged || alias.as_deref().is_some_and(|alias| m.starts_with(alias))
+    })
+}
+
+/// Models of a local provider without an endpoint: its model file, if
+/// there is one
+fn local_model_files(model: &str) -> Result<Vec<String>> {
+    #[cfg(feature = "local-llm")]
+    {
+        Ok(std::path::Path::new(model)
+            .exists()
+            .then(|| model.to_string())
+            .into_iter()
+            .collect())
+    }
+
+    #[cfg(not(feature = "local-llm"))]
+    {
+        let _ = model;
+        anyhow::bail!("Local LLM support not enabled. Compile with --features local-llm")
+    }
 }
 
+/// Hash identifying the content a summary was generated from
+///
+/// Stored alongside summaries so unchanged content is not summarized twice.
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// One finished request to the provider (cache hits make none)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AiRequest {
+    pub provider: AiProvider,
+    pub model: String,
+    pub duration: Duration,
+    /// The request hit the client's timeout
+    pub timed_out: bool,
+    pub failed: bool,
+}
+
+/// Called after each provider request (see [`AiClient::with_request_observer`])
+pub type AiRequestObserver = Arc<dyn Fn(&AiRequest) + Send + Sync>;
+
 /// AI client for summarization
 pub struct AiClient {
     config: AiConfig,
     client: reqwest::Client,
     cache: Arc<RwLock<HashMap<String, String>>>,
+    observer: Option<AiRequestObserver>,
 }
 
 /// Summary response from AI
@@ -131,9 +350,26 @@ impl AiClient {
             config,
             client,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            observer: None,
         })
     }
 
+    /// Report every provider request to `observer`
+    pub fn with_request_observer(mut self, observer: AiRequestObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// The configured provider
+    pub fn provider(&self) -> AiProvider {
+        self.config.provider
+    }
+
+    /// The configured model
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
     /// Summarize the given content
     ///
     /// # Arguments
@@ -144,13 +380,93 @@ impl AiClient {
     ///
     /// A `Summary` containing the generated summary and metadata
     pub async fn summarize(&self, content: &str) -> Result<Summary> {
+        self.summarize_stream(content, SummaryOptions::default(), |_| {})
+            .await
+    }
+
+    /// Summarize `content` with `options`, passing the text to `on_text` as
+    /// it is generated
+    ///
+    /// Providers that stream call `on_text` once per chunk; cached summaries
+    /// and providers that answer all at once call it once with the whole text.
+    pub async fn summarize_stream(
+        &self,
+        content: &str,
+        options: SummaryOptions,
+        mut on_text: impl FnMut(&str) + Send,
+    ) -> Result<Summary> {
+        let system_prompt = match options.style {
+            Some(style) => format!("{}\n\n{}", self.config.system_prompt, style.instruction()),
+            None => self.config.system_prompt.clone(),
+        };
+        self.complete(content, &system_prompt, options.skip_cache, &mut on_text)
+            .await
+    }
+
+    /// Translate `content` into `language` (a language name, e.g. "English"),
+    /// keeping its paragraphs
+    ///
+    /// The configured system prompt is for summaries, so translations use
+    /// their own.
+    pub async fn translate(&self, content: &str, language: &str) -> Result<Summary> {
+        let system_prompt = format!(
+            "Translate the text into {}. Keep its paragraphs, names, numbers, and \
+             links as they are, and reply with the translation only.",
+            language
+        );
+        self.complete(content, &system_prompt, false, &mut |_| {})
+            .await
+    }
+
+    /// Describe in a sentence or two what a correction or edit changed,
+    /// given a line diff of the article (`-` removed, `+` added)
+    pub async fn describe_changes(&self, diff: &str) -> Result<Summary> {
+        let system_prompt = "The text is a line diff of a news article that was edited after \
+             publication: lines starting with - were removed and lines starting with + \
+             were added. In one or two sentences, say what changed in substance (a \
+             correction, new facts, an update to the story). Ignore wording and \
+             formatting changes. Reply with the description only.";
+        self.complete(diff, system_prompt, false, &mut |_| {}).await
+    }
+
+    /// Answer a question from the numbered sources that follow it,
+    /// citing them as `[n]`, passing the text to `on_text` as it is generated
+    pub async fn answer(
+        &self,
+        question_and_sources: &str,
+        mut on_text: impl FnMut(&str) + Send,
+    ) -> Result<Summary> {
+        let system_prompt = "The text is a question followed by numbered sources: news articles \
+             or their summaries. Answer the question from the sources alone, citing the \
+             sources each statement rests on by number in square brackets, e.g. [2]. If \
+             the sources don't answer the question, say so. Keep the answer short: a \
+             paragraph or a few bullet points.";
+        self.complete(question_and_sources, system_prompt, false, &mut on_text)
+            .await
+    }
+
+    /// Answer `content` under `system_prompt`, from the cache when allowed
+    async fn complete(
+        &self,
+        content: &str,
+        system_prompt: &str,
+        skip_cache: bool,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<Summary> {
+        let content = match self.config.max_input_bytes {
+            Some(max_bytes) => truncate(content, max_bytes, self.config.truncation),
+            None => std::borrow::Cow::Borrowed(content),
+        };
+        let content = content.as_ref();
+        let cache_key = self.cache_key(content, system_prompt);
+
         // Check cache first if enabled
-        if self.config.enable_cache {
-            let cache_key = self.cache_key(content);
+        if self.config.enable_cache && !skip_cache {
             let cache = self.cache.read().await;
 
             if let Some(cached_summary) = cache.get(&cache_key) {
                 tracing::debug!("Cache hit for content");
+                on_text(cached_summary);
                 return Ok(Summary {
                     text: cached_summary.clone(),
                     cached: true,
@@ -161,15 +477,28 @@ impl AiClient {
         }
 
         // Generate summary using the configured provider
-        let summary = match self.config.provider {
-            AiProvider::OpenAI => self.summarize_openai(content).await?,
-            AiProvider::Anthropic => self.summarize_anthropic(content).await?,
-            AiProvider::Local => self.summarize_local(content).await?,
+        let started = Instant::now();
+        let result = match self.config.provider {
+            AiProvider::OpenAI => self.summarize_openai(content, system_prompt, on_text).await,
+            AiProvider::Anthropic => {
+                self.summarize_anthropic(content, system_prompt, on_text)
+                    .await
+            }
+            AiProvider::Local => self.summarize_local(content, system_prompt, on_text).await,
         };
+        if let Some(observer) = &self.observer {
+            observer(&AiRequest {
+                provider: self.config.provider,
+                model: self.config.model.clone(),
+                duration: started.elapsed(),
+                timed_out: result.as_ref().err().is_some_and(is_timeout),
+                failed: result.is_err(),
+            });
+        }
+        let summary = result?;
 
         // Cache the result if enabled
         if self.config.enable_cache {
-            let cache_key = self.cache_key(content);
             let mut cache = self.cache.write().await;
             cache.insert(cache_key, summary.text.clone());
         }
@@ -183,38 +512,137 @@ impl AiClient {
     }
 
     /// Summarize using OpenAI API
-    async fn summarize_openai(&self, content: &str) -> Result<Summary> {
+    async fn summarize_openai(
+        &self,
+        content: &str,
+        system_prompt: &str,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<Summary> {
         tracing::debug!("Generating summary using OpenAI");
 
-        // TODO: Implement OpenAI API call
-        // 1. Prepare request with system prompt and content
-        // 2. Make API call to OpenAI
-        // 3. Parse response and extract summary
-        // 4. Return Summary with token count
-
-        todo!("Implement OpenAI summarization")
+        let endpoint = self
+            .config
+            .endpoint
+            .as_deref()
+            .map(|e| e.trim_end_matches('/'));
+        // Custom endpoints may not need a key
+        if self.config.api_key.is_none() && endpoint.is_none() {
+            return Err(AiError::AuthError("no API key configured".into()).into());
+        }
+        let base = endpoint.unwrap_or(providers::openai::API_BASE);
+        let url = format!("{}{}", base, providers::openai::CHAT_COMPLETIONS_ENDPOINT);
+        self.chat_completion(&url, content, system_prompt, endpoint.is_none(), on_text)
+            .await
     }
 
     /// Summarize using Anthropic API
-    async fn summarize_anthropic(&self, content: &str) -> Result<Summary> {
+    async fn summarize_anthropic(
+        &self,
+        content: &str,
+        system_prompt: &str,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<Summary> {
         tracing::debug!("Generating summary using Anthropic");
 
-        // TODO: Implement Anthropic API call
-        // Similar to OpenAI but using Anthropic's API format
+        let key = self
+            .config
+            .api_key
+            .as_deref()
+            .ok_or_else(|| AiError::AuthError("no API key configured".into()))?;
+        let base = self
+            .config
+            .endpoint
+            .as_deref()
+            .map_or(providers::anthropic::API_BASE, |e| e.trim_end_matches('/'));
+        let body = providers::anthropic::MessagesRequest {
+            model: &self.config.model,
+            system: system_prompt,
+            messages: vec![providers::Message {
+                role: "user",
+                content,
+            }],
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+            stream: true,
+        };
+        let request = self
+            .client
+            .post(format!(
+                "{}{}",
+                base,
+                providers::anthropic::MESSAGES_ENDPOINT
+            ))
+            .header("x-api-key", key)
+            .header("anthropic-version", providers::anthropic::API_VERSION)
+            .json(&body);
+
+        let mut text = String::new();
+        let mut usage = providers::anthropic::Usage::default();
+        self.stream_events(request, |data| {
+            use providers::anthropic::StreamEvent;
+            let event: StreamEvent =
+                serde_json::from_str(data).map_err(|e| AiError::InvalidResponse(e.to_string()))?;
+            match event {
+                StreamEvent::MessageStart { message } => usage = message.usage,
+                StreamEvent::ContentBlockDelta { delta } => {
+                    if let Some(delta) = delta.text {
+                        on_text(&delta);
+                        text.push_str(&delta);
+                    }
+                }
+                // Output tokens so far
+                StreamEvent::MessageDelta { usage: delta } => {
+                    usage.output_tokens = delta.output_tokens
+                }
+                StreamEvent::MessageStop => return Ok(false),
+                StreamEvent::Error { error } => {
+                    return Err(stream_error(self.config.provider, &error.message))
+                }
+                StreamEvent::Other => {}
+            }
+            Ok(true)
+        })
+        .await?;
 
-        todo!("Implement Anthropic summarization")
+        Ok(Summary {
+            text: answer(text)?,
+            cached: false,
+            model: self.config.model.clone(),
+            tokens: Some(usage.input_tokens + usage.output_tokens),
+        })
     }
 
     /// Summarize using local LLM
-    async fn summarize_local(&self, content: &str) -> Result<Summary> {
+    ///
+    /// With an `endpoint`, the model runs on an OpenAI-compatible server
+    /// (Ollama, llama.cpp); without one, it's a model file for llama.cpp.
+    async fn summarize_local(
+        &self,
+        content: &str,
+        system_prompt: &str,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<Summary> {
         tracing::debug!("Generating summary using local LLM");
 
-        // TODO: Implement local LLM inference
-        // This will use llama-cpp-rs when the feature is enabled
+        if let Some(endpoint) = &self.config.endpoint {
+            let base = endpoint.trim_end_matches('/').trim_end_matches("/v1");
+            let url = format!(
+                "{}/v1{}",
+                base,
+                providers::openai::CHAT_COMPLETIONS_ENDPOINT
+            );
+            return self
+                .chat_completion(&url, content, system_prompt, false, on_text)
+                .await;
+        }
 
         #[cfg(feature = "local-llm")]
         {
-            todo!("Implement local LLM summarization")
+            Err(AiError::LocalLlmError(format!(
+                "running {} in-process isn't supported yet; serve it with Ollama or llama.cpp and set the endpoint",
+                self.config.model
+            ))
+            .into())
         }
 
         #[cfg(not(feature = "local-llm"))]
@@ -223,11 +651,234 @@ impl AiClient {
         }
     }
 
+    /// Summary from an OpenAI-style chat completion endpoint at `url`;
+    /// `include_usage` asks for the token count, which not every server
+    /// accepts
+    async fn chat_completion(
+        &self,
+        url: &str,
+        content: &str,
+        system_prompt: &str,
+        include_usage: bool,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<Summary> {
+        let body = providers::openai::ChatRequest {
+            model: &self.config.model,
+            messages: vec![
+                providers::Message {
+                    role: "system",
+                    content: system_prompt,
+                },
+                providers::Message {
+                    role: "user",
+                    content,
+                },
+            ],
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+            stream: true,
+            stream_options: include_usage
+                .then_some(providers::openai::StreamOptions { include_usage }),
+        };
+        let mut request = self.client.post(url).json(&body);
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let mut text = String::new();
+        let mut tokens = None;
+        self.stream_events(request, |data| {
+            if data == "[DONE]" {
+                return Ok(false);
+            }
+            let chunk: providers::openai::ChatChunk =
+                serde_json::from_str(data).map_err(|e| AiError::InvalidResponse(e.to_string()))?;
+            if let Some(error) = chunk.error {
+                return Err(stream_error(self.config.provider, &error.message));
+            }
+            for delta in chunk
+                .choices
+                .into_iter()
+                .filter_map(|choice| choice.delta.content)
+            {
+                on_text(&delta);
+                text.push_str(&delta);
+            }
+            if let Some(usage) = chunk.usage {
+                tokens = Some(usage.total_tokens);
+            }
+            Ok(true)
+        })
+        .await?;
+
+        Ok(Summary {
+            text: answer(text)?,
+            cached: false,
+            model: self.config.model.clone(),
+            tokens,
+        })
+    }
+
+    /// Send a streaming request, handing the data of each event to
+    /// `on_event` until it returns `false` or the stream ends
+    async fn stream_events(
+        &self,
+        request: RequestBuilder,
+        mut on_event: impl FnMut(&str) -> Result<bool> + Send,
+    ) -> Result<()> {
+        let mut response = request.send().await.map_err(AiError::from)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_error(self.config.provider, status, &body).into());
+        }
+
+        let mut decoder = sse::Decoder::new();
+        while let Some(bytes) = response.chunk().await.map_err(AiError::from)? {
+            for data in decoder.push(&bytes) {
+                if !on_event(&data)? {
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(data) = decoder.finish() {
+            on_event(&data)?;
+        }
+        Ok(())
+    }
+
+    /// Check, without generating anything, that the provider answers,
+    /// accepts the API key, and offers the configured model; returns how
+    /// long it took to answer
+    ///
+    /// Failures are [`AiError`]s: `AuthError` for a missing or refused key,
+    /// `ModelNotAvailable` (naming some models it does offer) for a model it
+    /// doesn't have.
+    pub async fn health_check(&self) -> Result<Duration> {
+        let started = Instant::now();
+        let models = self.list_models().await?;
+        let latency = started.elapsed();
+        if !offers(&models, &self.config.model) {
+            let offered = match models.len() {
+                0 => "none".to_string(),
+                n if n > MODELS_SHOWN => {
+                    format!(
+                        "{}, and {} more",
+                        models[..MODELS_SHOWN].join(", "),
+                        n - MODELS_SHOWN
+                    )
+                }
+                _ => models.join(", "),
+            };
+            return Err(AiError::ModelNotAvailable(format!(
+                "{} doesn't offer {} (it has {})",
+                self.config.provider.name(),
+                self.config.model,
+                offered
+            ))
+            .into());
+        }
+        Ok(latency)
+    }
+
+    /// Models the provider offers, sorted
+    ///
+    /// A local provider with an `endpoint` is asked as an Ollama server,
+    /// then as an OpenAI-compatible one (like llama.cpp's); without one, its
+    /// model is a file, offered if it exists.
+    pub async fn list_models(&self) -> Result<Vec<String>> {
+        let endpoint = self
+            .config
+            .endpoint
+            .as_deref()
+            .map(|e| e.trim_end_matches('/'));
+        let api_key = self.config.api_key.as_deref();
+        let mut models = match self.config.provider {
+            AiProvider::OpenAI => {
+                // Custom endpoints may not need a key
+                if api_key.is_none() && endpoint.is_none() {
+                    return Err(AiError::AuthError("no API key configured".into()).into());
+                }
+                let base = endpoint.unwrap_or(providers::openai::API_BASE);
+                self.openai_models(&format!("{}{}", base, providers::openai::MODELS_ENDPOINT))
+                    .await?
+            }
+            AiProvider::Anthropic => {
+                let key =
+                    api_key.ok_or_else(|| AiError::AuthError("no API key configured".into()))?;
+                let base = endpoint.unwrap_or(providers::anthropic::API_BASE);
+                let url = format!("{}{}", base, providers::anthropic::MODELS_ENDPOINT);
+                let request = self
+                    .client
+                    .get(&url)
+                    .header("x-api-key", key)
+                    .header("anthropic-version", providers::anthropic::API_VERSION);
+                let list: ModelList = found(&url, self.get_json(request).await?)?;
+                list.data.into_iter().map(|m| m.id).collect()
+            }
+            AiProvider::Local => match endpoint {
+                Some(endpoint) => {
+                    let base = endpoint.trim_end_matches("/v1");
+                    let tags =
+                        self.client
+                            .get(format!("{}{}", base, providers::ollama::TAGS_ENDPOINT));
+                    match self.get_json::<TagList>(tags).await? {
+                        Some(tags) => tags.models.into_iter().map(|m| m.name).collect(),
+                        None => {
+                            self.openai_models(&format!(
+                                "{}/v1{}",
+                                base,
+                                providers::openai::MODELS_ENDPOINT
+                            ))
+                            .await?
+                        }
+                    }
+                }
+                None => local_model_files(&self.config.model)?,
+            },
+        };
+        models.sort();
+        Ok(models)
+    }
+
+    /// Models listed at an OpenAI-style `url`
+    async fn openai_models(&self, url: &str) -> Result<Vec<String>> {
+        let mut request = self.client.get(url);
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+        let list: ModelList = found(url, self.get_json(request).await?)?;
+        Ok(list.data.into_iter().map(|m| m.id).collect())
+    }
+
+    /// Answer to a check request; `None` if the provider has no such
+    /// endpoint (404)
+    async fn get_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<Option<T>> {
+        let response = request
+            .timeout(CHECK_TIMEOUT)
+            .send()
+            .await
+            .map_err(AiError::from)?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_error(self.config.provider, status, &body).into());
+        }
+        let value = response
+            .json()
+            .await
+            .map_err(|e| AiError::InvalidResponse(e.to_string()))?;
+        Ok(Some(value))
+    }
+
     /// Generate a cache key for content
-    fn cache_key(&self, content: &str) -> String {
+    fn cache_key(&self, content: &str, system_prompt: &str) -> String {
         let mut hasher = Sha256::new();
         hasher.update(content.as_bytes());
-        hasher.update(self.config.system_prompt.as_bytes());
+        hasher.update(system_prompt.as_bytes());
         hasher.update(self.config.model.as_bytes());
         format!("{:x}", hasher.finalize())
     }
@@ -264,5 +915,283 @@ mod tests {
         assert!(client.is_ok());
     }
 
-    // TODO: Add more tests with mock API responses
+    #[tokio::test]
+    async fn test_summarize_stream_from_cache() {
+        let client = AiClient::new(AiConfig::default()).unwrap();
+        let bullets = SummaryOptions {
+            style: Some(SummaryStyle::Bullets),
+            skip_cache: false,
+        };
+        let prompt = format!(
+            "{}\n\n{}",
+            client.config.system_prompt,
+            SummaryStyle::Bullets.instruction()
+        );
+        client
+            .cache
+            .write()
+            .await
+            .insert(client.cache_key("text", &prompt), "- point".into());
+
+        let mut streamed = String::new();
+        let summary = client
+            .summarize_stream("text", bullets, |chunk| streamed.push_str(chunk))
+            .await
+            .unwrap();
+        assert!(summary.cached);
+        assert_eq!(streamed, "- point");
+        // Each style has its own cache entry
+        assert_ne!(
+            client.cache_key("text", &prompt),
+            client.cache_key("text", &client.config.system_prompt)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_observer() {
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let observer: AiRequestObserver = {
+            let requests = requests.clone();
+            Arc::new(move |request: &AiRequest| requests.lock().unwrap().push(request.clone()))
+        };
+        let config = AiConfig {
+            provider: AiProvider::Local,
+            ..Default::default()
+        };
+        let client = AiClient::new(config)
+            .unwrap()
+            .with_request_observer(observer);
+        client.cache.write().await.insert(
+            client.cache_key("cached", &client.config.system_prompt),
+            "summary".into(),
+        );
+
+        client.summarize("cached").await.unwrap();
+        assert!(requests.lock().unwrap().is_empty());
+        #[cfg(not(feature = "local-llm"))]
+        {
+            assert!(client.summarize("text").await.is_err());
+            let requests = requests.lock().unwrap();
+            assert_eq!(requests.len(), 1);
+            assert_eq!(requests[0].provider.name(), "local");
+            assert!(requests[0].failed && !requests[0].timed_out);
+        }
+    }
+
+    fn client_for(provider: AiProvider, model: &str, endpoint: String) -> AiClient {
+        AiClient::new(AiConfig {
+            provider,
+            api_key: Some("sk-test".into()),
+            model: model.into(),
+            endpoint: Some(endpoint),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/models")
+            .match_header("authorization", "Bearer sk-test")
+            .with_body(r#"{"object": "list", "data": [{"id": "gpt-4o"}, {"id": "gpt-4"}]}"#)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let client = client_for(AiProvider::OpenAI, "gpt-4", server.url());
+        assert_eq!(client.list_models().await.unwrap(), ["gpt-4", "gpt-4o"]);
+        assert!(client.health_check().await.is_ok());
+        let client = client_for(AiProvider::OpenAI, "gpt-5", server.url());
+        let error = client.health_check().await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(AiError::ModelNotAvailable(_))
+        ));
+        assert!(error
+            .to_string()
+            .contains("openai doesn't offer gpt-5 (it has gpt-4, gpt-4o)"));
+        mock.assert_async().await;
+
+        server
+            .mock("GET", "/models")
+            .match_query(mockito::Matcher::UrlEncoded("limit".into(), "1000".into()))
+            .match_header("x-api-key", "sk-test")
+            .with_status(401)
+            .with_body(r#"{"type": "error", "error": {"type": "authentication_error"}}"#)
+            .create_async()
+            .await;
+        let client = client_for(
+            AiProvider::Anthropic,
+            "claude-3-haiku-20240307",
+            server.url(),
+        );
+        let error = client.health_check().await.unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(AiError::AuthError(_))));
+        assert!(error.to_string().contains("anthropic answered HTTP 401"));
+
+        // No key, nothing to ask
+        let client = AiClient::new(AiConfig::default()).unwrap();
+        assert!(matches!(
+            client.health_check().await.unwrap_err().downcast_ref(),
+            Some(AiError::AuthError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_local_models() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/tags")
+            .with_body(r#"{"models": [{"name": "mistral:7b"}, {"name": "llama3:latest"}]}"#)
+            .create_async()
+            .await;
+        let client = client_for(AiProvider::Local, "llama3", server.url());
+        assert_eq!(
+            client.list_models().await.unwrap(),
+            ["llama3:latest", "mistral:7b"]
+        );
+        assert!(client.health_check().await.is_ok());
+
+        // Not Ollama: an OpenAI-compatible server
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/tags")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/models")
+            .with_body(r#"{"data": [{"id": "qwen2.5-7b-instruct"}]}"#)
+            .create_async()
+            .await;
+        let client = client_for(AiProvider::Local, "local", format!("{}/v1", server.url()));
+        assert_eq!(client.list_models().await.unwrap(), ["qwen2.5-7b-instruct"]);
+    }
+
+    #[test]
+    fn test_offers() {
+        let models = vec![
+            "claude-3-5-sonnet-20241022".to_string(),
+            "llama3:latest".to_string(),
+        ];
+        assert!(offers(&models, "claude-3-5-sonnet-20241022"));
+        assert!(offers(&models, "claude-3-5-sonnet-latest"));
+        assert!(offers(&models, "llama3"));
+        assert!(!offers(&models, "claude-3-5"));
+        assert!(!offers(&models, "llama3:8b"));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_openai() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .match_header("authorization", "Bearer sk-test")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::PartialJsonString(r#"{"model": "gpt-4", "stream": true}"#.into()),
+                mockito::Matcher::Regex(r#"\{"role":"user","content":"text"\}"#.into()),
+            ]))
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "data: {\"choices\": [{\"delta\": {\"role\": \"assistant\"}}]}\n\n",
+                "data: {\"choices\": [{\"delta\": {\"content\": \"A sum\"}}]}\n\n",
+                "data: {\"choices\": [{\"delta\": {\"content\": \"mary.\"}}]}\n\n",
+                "data: {\"choices\": [], \"usage\": {\"total_tokens\": 42}}\n\n",
+                "data: [DONE]\n\n",
+            ))
+            .create_async()
+            .await;
+
+        let client = client_for(AiProvider::OpenAI, "gpt-4", server.url());
+        let mut streamed = Vec::new();
+        let summary = client
+            .summarize_stream("text", SummaryOptions::default(), |chunk| {
+                streamed.push(chunk.to_string())
+            })
+            .await
+            .unwrap();
+        assert_eq!(summary.text, "A summary.");
+        assert_eq!(streamed, ["A sum", "mary."]);
+        assert_eq!(summary.tokens, Some(42));
+        assert!(!summary.cached);
+        mock.assert_async().await;
+
+        server
+            .mock("POST", "/chat/completions")
+            .with_status(429)
+            .create_async()
+            .await;
+        let error = client.summarize("other text").await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(AiError::RateLimitError(_))
+        ));
+
+        // Failing after the answer started
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/chat/completions")
+            .with_body("data: {\"choices\": [{\"delta\": {\"content\": \"A\"}}]}\n\ndata: {\"error\": {\"message\": \"overloaded\"}}\n\n")
+            .create_async()
+            .await;
+        let client = client_for(AiProvider::OpenAI, "gpt-4", server.url());
+        let error = client.summarize("text").await.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("openai stopped answering: overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_anthropic() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/messages")
+            .match_header("x-api-key", "sk-test")
+            .match_header("anthropic-version", providers::anthropic::API_VERSION)
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"system": "Summarize.", "stream": true}"#.into()))
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "event: message_start\n",
+                "data: {\"type\": \"message_start\", \"message\": {\"usage\": {\"input_tokens\": 30, \"output_tokens\": 1}}}\n\n",
+                "event: ping\ndata: {\"type\": \"ping\"}\n\n",
+                "event: content_block_delta\n",
+                "data: {\"type\": \"content_block_delta\", \"index\": 0, \"delta\": {\"type\": \"text_delta\", \"text\": \"A summary.\"}}\n\n",
+                "event: message_delta\n",
+                "data: {\"type\": \"message_delta\", \"delta\": {\"stop_reason\": \"end_turn\"}, \"usage\": {\"output_tokens\": 12}}\n\n",
+                "event: message_stop\ndata: {\"type\": \"message_stop\"}\n\n",
+            ))
+            .create_async()
+            .await;
+
+        let mut client = client_for(
+            AiProvider::Anthropic,
+            "claude-3-haiku-20240307",
+            server.url(),
+        );
+        client.config.system_prompt = "Summarize.".into();
+        let summary = client.summarize("text").await.unwrap();
+        assert_eq!(summary.text, "A summary.");
+        assert_eq!(summary.tokens, Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_local_server() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/chat/completions")
+            .with_body(
+                "data: {\"choices\": [{\"delta\": {\"content\": \" \"}}]}\n\ndata: [DONE]\n\n",
+            )
+            .create_async()
+            .await;
+
+        let client = client_for(AiProvider::Local, "llama3", format!("{}/v1", server.url()));
+        let error = client.summarize("text").await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(AiError::InvalidResponse(_))
+        ));
+    }
 }
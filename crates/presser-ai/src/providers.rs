@@ -1,26 +1,177 @@
 //! AI provider-specific implementations
 
+use serde::{Deserialize, Serialize};
+
+/// One message of a conversation
+#[derive(Debug, Serialize)]
+pub struct Message<'a> {
+    pub role: &'a str,
+    pub content: &'a str,
+}
+
 /// OpenAI API models and constants
 pub mod openai {
     pub const API_BASE: &str = "https://api.openai.com/v1";
     pub const CHAT_COMPLETIONS_ENDPOINT: &str = "/chat/completions";
+    pub const MODELS_ENDPOINT: &str = "/models";
 
     /// Common OpenAI models
     pub const GPT_4: &str = "gpt-4";
     pub const GPT_4_TURBO: &str = "gpt-4-turbo-preview";
     pub const GPT_35_TURBO: &str = "gpt-3.5-turbo";
+
+    use serde::{Deserialize, Serialize};
+
+    use super::Message;
+
+    /// Chat completion request, also understood by Ollama and llama.cpp
+    #[derive(Debug, Serialize)]
+    pub struct ChatRequest<'a> {
+        pub model: &'a str,
+        pub messages: Vec<Message<'a>>,
+        pub max_tokens: u32,
+        pub temperature: f32,
+        pub stream: bool,
+        /// Only OpenAI itself is sure to accept it
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub stream_options: Option<StreamOptions>,
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct StreamOptions {
+        /// Send token usage in a last chunk
+        pub include_usage: bool,
+    }
+
+    /// One event of a streamed chat completion
+    #[derive(Debug, Deserialize)]
+    pub struct ChatChunk {
+        #[serde(default)]
+        pub choices: Vec<Choice>,
+        pub usage: Option<Usage>,
+        pub error: Option<StreamError>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Choice {
+        pub delta: Delta,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Delta {
+        pub content: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Usage {
+        pub total_tokens: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct StreamError {
+        pub message: String,
+    }
 }
 
 /// Anthropic API models and constants
 pub mod anthropic {
     pub const API_BASE: &str = "https://api.anthropic.com/v1";
     pub const MESSAGES_ENDPOINT: &str = "/messages";
+    /// Listed up to 1000 at a time
+    pub const MODELS_ENDPOINT: &str = "/models?limit=1000";
+    /// Sent as `anthropic-version`
+    pub const API_VERSION: &str = "2023-06-01";
 
     /// Common Anthropic models
     pub const CLAUDE_3_OPUS: &str = "claude-3-opus-20240229";
     pub const CLAUDE_3_SONNET: &str = "claude-3-sonnet-20240229";
     pub const CLAUDE_3_HAIKU: &str = "claude-3-haiku-20240307";
+
+    use serde::{Deserialize, Serialize};
+
+    use super::Message;
+
+    #[derive(Debug, Serialize)]
+    pub struct MessagesRequest<'a> {
+        pub model: &'a str,
+        pub system: &'a str,
+        pub messages: Vec<Message<'a>>,
+        pub max_tokens: u32,
+        pub temperature: f32,
+        pub stream: bool,
+    }
+
+    /// One event of a streamed message
+    #[derive(Debug, Deserialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    pub enum StreamEvent {
+        MessageStart {
+            message: MessageStart,
+        },
+        ContentBlockDelta {
+            delta: TextDelta,
+        },
+        MessageDelta {
+            usage: Usage,
+        },
+        MessageStop,
+        Error {
+            error: StreamError,
+        },
+        /// Pings and block boundaries
+        #[serde(other)]
+        Other,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct MessageStart {
+        pub usage: Usage,
+    }
+
+    /// Only text deltas have `text`
+    #[derive(Debug, Deserialize)]
+    pub struct TextDelta {
+        pub text: Option<String>,
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    pub struct Usage {
+        #[serde(default)]
+        pub input_tokens: u32,
+        #[serde(default)]
+        pub output_tokens: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct StreamError {
+        pub message: String,
+    }
+}
+
+/// Ollama API constants
+pub mod ollama {
+    /// Models pulled to the server
+    pub const TAGS_ENDPOINT: &str = "/api/tags";
 }
 
-// TODO: Add request/response types for each provider
-// TODO: Implement provider-specific API clients
+/// Model list of OpenAI, Anthropic, and OpenAI-compatible servers
+#[derive(Debug, Deserialize)]
+pub struct ModelList {
+    pub data: Vec<ModelInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+}
+
+/// Model list of an Ollama server
+#[derive(Debug, Deserialize)]
+pub struct TagList {
+    pub models: Vec<TagInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagInfo {
+    pub name: String,
+}
@@ -0,0 +1,81 @@
+//! Server-sent events, as streamed by the providers
+//!
+//! Only `data` fields matter here: OpenAI and Anthropic both put the whole
+//! event, type included, in its JSON data.
+
+/// Splits a byte stream into the data of its events
+#[derive(Debug, Default)]
+pub struct Decoder {
+    /// Bytes after the last complete line
+    buffer: Vec<u8>,
+    /// Data of the event being read
+    data: Option<String>,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Data of the events completed by `bytes`
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            self.line(line.trim_end_matches(['\n', '\r']), &mut events);
+        }
+        events
+    }
+
+    /// Data of an event the stream ended without a blank line after
+    pub fn finish(mut self) -> Option<String> {
+        let mut events = Vec::new();
+        let rest = std::mem::take(&mut self.buffer);
+        self.line(
+            String::from_utf8_lossy(&rest).trim_end_matches('\r'),
+            &mut events,
+        );
+        events.pop().or(self.data)
+    }
+
+    fn line(&mut self, line: &str, events: &mut Vec<String>) {
+        if line.is_empty() {
+            events.extend(self.data.take());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            }
+        }
+        // Comments and other fields are ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder() {
+        let mut decoder = Decoder::new();
+        assert!(decoder
+            .push(b"event: message_start\r\ndata: {\"a\":")
+            .is_empty());
+        assert_eq!(
+            decoder.push(b" 1}\r\n\r\n: ping\n\ndata:[DONE]\n"),
+            ["{\"a\": 1}"]
+        );
+        assert_eq!(
+            decoder.push(b"\ndata: one\ndata: two\n\n"),
+            ["[DONE]", "one\ntwo"]
+        );
+        decoder.push(b"data: last");
+        assert_eq!(decoder.finish().as_deref(), Some("last"));
+    }
+}
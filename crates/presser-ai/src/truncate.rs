@@ -0,0 +1,131 @@
+//! Cutting oversized text (a scraped page, a long article) down to a size
+//! limit, for storage and for prompts
+//!
+//! Whatever is cut is replaced by a `[…]` marker, so readers and models can
+//! tell the text doesn't end there. Limits are in bytes and counted with the
+//! marker; text is never split inside a character.
+
+use std::borrow::Cow;
+
+pub use presser_config::Truncation;
+
+/// Marks where text was cut
+const MARKER: &str = "[…]";
+
+/// Ends of paragraphs and blocks, for [`Truncation::Smart`]: blank lines and
+/// closing tags of block elements
+const PARAGRAPH_ENDS: &[&str] = &[
+    "\n\n",
+    "</p>",
+    "</li>",
+    "</ul>",
+    "</ol>",
+    "</blockquote>",
+    "</pre>",
+    "</div>",
+    "</section>",
+];
+
+/// Ends of sentences, when no paragraph ends late enough
+const SENTENCE_ENDS: &[&str] = &[". ", "! ", "? ", ".\n", "!\n", "?\n"];
+
+/// `text` cut to at most `max_bytes` with `strategy`; as it is if it fits
+pub fn truncate(text: &str, max_bytes: usize, strategy: Truncation) -> Cow<'_, str> {
+    if text.len() <= max_bytes {
+        return Cow::Borrowed(text);
+    }
+    let end_marker = format!("\n\n{}", MARKER);
+    if max_bytes <= end_marker.len() {
+        return Cow::Owned(head(text, max_bytes).to_string());
+    }
+    let budget = max_bytes - end_marker.len();
+    Cow::Owned(match strategy {
+        Truncation::Head => format!("{}{}", head(text, budget), end_marker),
+        Truncation::Smart => format!("{}{}", smart_head(text, budget), end_marker),
+        Truncation::HeadTail => {
+            let middle_marker = format!("\n\n{}\n\n", MARKER);
+            let budget = max_bytes.saturating_sub(middle_marker.len());
+            let head_len = budget * 2 / 3;
+            let tail = tail(text, budget - head_len);
+            format!("{}{}{}", head(text, head_len), middle_marker, tail)
+        }
+    })
+}
+
+/// The longest start of `text` within `max_bytes`
+fn head(text: &str, max_bytes: usize) -> &str {
+    let mut end = max_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// The longest end of `text` within `max_bytes`
+fn tail(text: &str, max_bytes: usize) -> &str {
+    let mut start = text.len().saturating_sub(max_bytes);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// The start of `text` within `max_bytes`, ending with a paragraph,
+/// sentence, or word if one ends in its second half
+fn smart_head(text: &str, max_bytes: usize) -> &str {
+    let head = head(text, max_bytes);
+    let late_enough = head.len() / 2;
+    let last_end = |ends: &[&str]| {
+        ends.iter()
+            .filter_map(|end| head.rfind(end).map(|i| i + end.trim_end().len().max(1)))
+            .max()
+            .filter(|&i| i >= late_enough)
+    };
+    let end = last_end(PARAGRAPH_ENDS)
+        .or_else(|| last_end(SENTENCE_ENDS))
+        .or_else(|| {
+            head.rfind(char::is_whitespace)
+                .filter(|&i| i >= late_enough)
+        })
+        .unwrap_or(head.len());
+    head[..end].trim_end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        let text =
+            "First paragraph, short.\n\nSecond paragraph. It runs longer than the first.\n\nThird.";
+        assert_eq!(truncate(text, 1000, Truncation::Smart), text);
+
+        let smart = truncate(text, 70, Truncation::Smart);
+        assert_eq!(smart, "First paragraph, short.\n\nSecond paragraph.\n\n[…]");
+        let head = truncate(text, 40, Truncation::Head);
+        assert_eq!(head, "First paragraph, short.\n\nSecond p\n\n[…]");
+        let head_tail = truncate(text, 50, Truncation::HeadTail);
+        assert_eq!(
+            head_tail,
+            "First paragraph, short.\n\nSe\n\n[…]\n\nfirst.\n\nThird."
+        );
+        for cut in [smart, head, head_tail] {
+            assert!(cut.len() <= 70);
+        }
+    }
+
+    #[test]
+    fn test_truncate_html() {
+        let html = format!("<p>{}</p><p>{}</p>", "a".repeat(40), "b".repeat(40));
+        assert_eq!(
+            truncate(&html, 80, Truncation::Smart),
+            format!("<p>{}</p>\n\n[…]", "a".repeat(40))
+        );
+        // Never inside a character
+        let accents = "é".repeat(20);
+        let cut = truncate(&accents, 15, Truncation::Head);
+        assert_eq!(cut, format!("{}\n\n[…]", "é".repeat(4)));
+        assert_eq!(truncate(&accents, 5, Truncation::HeadTail), "éé");
+    }
+}
@@ -0,0 +1,47 @@
+//! Page archive settings (`[archive]` section)
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Keeping the HTML page of every new entry (see `presser archive`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveConfig {
+    /// Fetch and store the page of each new entry during updates
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Directory of the compressed pages (relative to the config directory;
+    /// default: `archive` next to the database)
+    pub path: Option<PathBuf>,
+}
+
+impl ArchiveConfig {
+    /// Directory pages are stored in, given the database path
+    pub fn dir(&self, database: &Path) -> PathBuf {
+        match &self.path {
+            Some(path) => path.clone(),
+            None => database.with_file_name("archive"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_dir() {
+        let config: ArchiveConfig = toml::from_str("enabled = true").unwrap();
+        assert!(config.enabled);
+        assert_eq!(
+            config.dir(Path::new("/data/presser/presser.db")),
+            Path::new("/data/presser/archive")
+        );
+        let config = ArchiveConfig {
+            path: Some("/mnt/pages".into()),
+            ..config
+        };
+        assert_eq!(config.dir(Path::new("presser.db")), Path::new("/mnt/pages"));
+    }
+}
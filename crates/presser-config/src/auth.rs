@@ -0,0 +1,158 @@
+//! Feed credentials (`[feed.auth]` table of a feed)
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::ConfigError;
+
+/// Token sent with a feed's requests, for feeds behind an API (a private
+/// Discourse, GitHub notifications)
+///
+/// When the feed answers 401, a new token is obtained with `token_command`,
+/// or from `token_url` with the refresh token, and the fetch retried once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedAuth {
+    /// Header the token is sent in
+    #[serde(default = "default_header")]
+    pub header: String,
+
+    /// Written before the token in the header ("" for the token alone)
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+
+    /// Token to start with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Shell command printing a fresh token, run when there is none yet and
+    /// whenever the feed answers 401
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<String>,
+
+    /// OAuth token endpoint a refresh token is exchanged at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<String>,
+
+    /// Refresh token to start with (the endpoint's rotated ones are kept in
+    /// the database)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+impl FeedAuth {
+    /// Value of the header carrying `token`
+    pub fn header_value(&self, token: &str) -> String {
+        match self.scheme.as_str() {
+            "" => token.to_string(),
+            scheme => format!("{} {}", scheme, token),
+        }
+    }
+
+    /// Whether a new token can be obtained when the feed turns one down
+    pub fn refreshes(&self) -> bool {
+        self.token_command.is_some() || self.token_url.is_some()
+    }
+
+    /// Check that there is a way to get a token, and only one way to
+    /// refresh it
+    pub fn validate(&self, feed_id: &str) -> Result<(), ConfigError> {
+        if self.token_command.is_some() && self.token_url.is_some() {
+            return Err(ConfigError::InvalidConfig(format!(
+                "Feed '{}' auth has both token_command and token_url; pick one",
+                feed_id
+            )));
+        }
+        if let Some(token_url) = &self.token_url {
+            Url::parse(token_url).map_err(|_| ConfigError::InvalidUrl(token_url.clone()))?;
+            if self.refresh_token.is_none() {
+                return Err(ConfigError::MissingField(format!(
+                    "Feed '{}' auth has a token_url but no refresh_token",
+                    feed_id
+                )));
+            }
+        }
+        if self.token.is_none() && !self.refreshes() {
+            return Err(ConfigError::MissingField(format!(
+                "Feed '{}' auth needs a token, token_command, or token_url",
+                feed_id
+            )));
+        }
+        if self.header.is_empty()
+            || !self
+                .header
+                .bytes()
+                .all(|b| b.is_ascii_graphic() && b != b':')
+        {
+            return Err(ConfigError::InvalidConfig(format!(
+                "Feed '{}' auth header '{}' is not a header name",
+                feed_id, self.header
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn default_header() -> String {
+    "Authorization".to_string()
+}
+
+fn default_scheme() -> String {
+    "Bearer".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_auth() {
+        let auth: FeedAuth = toml::from_str(r#"token_command = "pass show discourse""#).unwrap();
+        assert_eq!(auth.header_value("abc"), "Bearer abc");
+        assert!(auth.refreshes());
+        assert!(auth.validate("forum").is_ok());
+
+        let auth: FeedAuth = toml::from_str(
+            r#"
+            header = "X-Api-Key"
+            scheme = ""
+            token = "abc"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(auth.header_value("abc"), "abc");
+        assert!(!auth.refreshes());
+        assert!(auth.validate("forum").is_ok());
+
+        let refresh = FeedAuth {
+            token_url: Some("https://forum.example.com/oauth/token".into()),
+            ..auth.clone()
+        };
+        assert!(refresh.validate("forum").is_err());
+        let refresh = FeedAuth {
+            refresh_token: Some("r1".into()),
+            ..refresh
+        };
+        assert!(refresh.validate("forum").is_ok());
+        let both = FeedAuth {
+            token_command: Some("cat token".into()),
+            ..refresh
+        };
+        assert!(both.validate("forum").is_err());
+        let nothing = FeedAuth {
+            token: None,
+            ..auth.clone()
+        };
+        assert!(nothing.validate("forum").is_err());
+        let bad_header = FeedAuth {
+            header: "X Api Key".into(),
+            ..auth
+        };
+        assert!(bad_header.validate("forum").is_err());
+    }
+}
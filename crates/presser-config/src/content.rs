@@ -0,0 +1,95 @@
+//! Entry content size limits (`[content]` section)
+
+use serde::{Deserialize, Serialize};
+
+/// How text over its limit is cut
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Truncation {
+    /// Keep the start, cut anywhere
+    Head,
+    /// Keep the start and the end (where conclusions are), cutting the middle
+    HeadTail,
+    /// Keep the start, cut after the last whole paragraph (else sentence,
+    /// else word) that fits
+    #[default]
+    Smart,
+}
+
+/// Size limits of entry content, so one huge scraped page bloats neither
+/// the database nor a prompt (0 turns a limit off)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentConfig {
+    /// Largest stored HTML of an entry, in bytes
+    #[serde(default = "default_max_html_bytes")]
+    pub max_html_bytes: usize,
+
+    /// Largest stored text of an entry, in bytes
+    #[serde(default = "default_max_text_bytes")]
+    pub max_text_bytes: usize,
+
+    /// Largest text sent to the AI provider in one request, in bytes
+    #[serde(default = "default_max_prompt_bytes")]
+    pub max_prompt_bytes: usize,
+
+    /// How content over a limit is cut
+    #[serde(default)]
+    pub truncation: Truncation,
+}
+
+impl Default for ContentConfig {
+    fn default() -> Self {
+        Self {
+            max_html_bytes: default_max_html_bytes(),
+            max_text_bytes: default_max_text_bytes(),
+            max_prompt_bytes: default_max_prompt_bytes(),
+            truncation: Truncation::default(),
+        }
+    }
+}
+
+impl ContentConfig {
+    /// Limit of stored HTML, if any
+    pub fn html_limit(&self) -> Option<usize> {
+        limit(self.max_html_bytes)
+    }
+
+    /// Limit of stored text, if any
+    pub fn text_limit(&self) -> Option<usize> {
+        limit(self.max_text_bytes)
+    }
+
+    /// Limit of prompt text, if any
+    pub fn prompt_limit(&self) -> Option<usize> {
+        limit(self.max_prompt_bytes)
+    }
+}
+
+fn limit(bytes: usize) -> Option<usize> {
+    (bytes > 0).then_some(bytes)
+}
+
+fn default_max_html_bytes() -> usize {
+    1024 * 1024
+}
+fn default_max_text_bytes() -> usize {
+    256 * 1024
+}
+fn default_max_prompt_bytes() -> usize {
+    48 * 1024
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_limits() {
+        let config: ContentConfig =
+            toml::from_str("max_text_bytes = 0\ntruncation = \"head-tail\"").unwrap();
+        assert_eq!(config.html_limit(), Some(1024 * 1024));
+        assert_eq!(config.text_limit(), None);
+        assert_eq!(config.prompt_limit(), Some(48 * 1024));
+        assert_eq!(config.truncation, Truncation::HeadTail);
+    }
+}
@@ -0,0 +1,57 @@
+//! Cookie jar settings (`[cookies]` section)
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Keeping the cookies sites set, for sources that let a logged-in session
+/// through
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookiesConfig {
+    /// Send cookies sites set back with later feed and page requests, and
+    /// save them across restarts
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// File the cookies are saved to (relative to the config directory;
+    /// default: `cookies.json` next to the database)
+    pub path: Option<PathBuf>,
+}
+
+impl CookiesConfig {
+    /// File cookies are saved to, given the database path; `None` if the
+    /// jar is off
+    pub fn file(&self, database: &Path) -> Option<PathBuf> {
+        if !self.enabled {
+            return None;
+        }
+        Some(match &self.path {
+            Some(path) => path.clone(),
+            None => database.with_file_name("cookies.json"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie_file() {
+        let database = Path::new("/data/presser/presser.db");
+        assert_eq!(CookiesConfig::default().file(database), None);
+        let config: CookiesConfig = toml::from_str("enabled = true").unwrap();
+        assert_eq!(
+            config.file(database).unwrap(),
+            Path::new("/data/presser/cookies.json")
+        );
+        let config = CookiesConfig {
+            path: Some("/secrets/cookies.json".into()),
+            ..config
+        };
+        assert_eq!(
+            config.file(database).unwrap(),
+            Path::new("/secrets/cookies.json")
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Entry filter rules for groups of feeds (`[[filters]]` sections)
+
+use serde::{Deserialize, Serialize};
+
+use crate::FeedConfig;
+
+/// A filter expression applied to the feeds of some folders or tags
+///
+/// Entries of those feeds are stored only if the expression holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterRule {
+    /// Names the rule in logs and `presser filters test`
+    pub name: String,
+
+    /// Expression an entry must satisfy to be kept
+    pub rule: String,
+
+    /// Feeds in any of these folders
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<String>,
+
+    /// Feeds with any of these tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl FilterRule {
+    /// Whether the rule covers a feed: one in a listed folder or with a
+    /// listed tag, or any feed when neither is listed
+    pub fn applies_to(&self, feed: Option<&FeedConfig>) -> bool {
+        if self.folders.is_empty() && self.tags.is_empty() {
+            return true;
+        }
+        let Some(feed) = feed else {
+            return false;
+        };
+        let in_folder = feed
+            .folder
+            .as_ref()
+            .is_some_and(|folder| self.folders.contains(folder));
+        in_folder || feed.tags.iter().any(|tag| self.tags.contains(tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applies_to() {
+        let rule: FilterRule = toml::from_str(
+            r#"
+            name = "no-sponsored"
+            rule = '!tags.contains("sponsored")'
+            folders = ["News"]
+            tags = ["tech"]
+            "#,
+        )
+        .unwrap();
+        let mut feed = FeedConfig::new("https://example.com/feed", "Example");
+        assert!(!rule.applies_to(Some(&feed)));
+        assert!(!rule.applies_to(None));
+        feed.folder = Some("News".into());
+        assert!(rule.applies_to(Some(&feed)));
+        feed.folder = None;
+        feed.tags = vec!["tech".into()];
+        assert!(rule.applies_to(Some(&feed)));
+
+        let everywhere = FilterRule {
+            folders: Vec::new(),
+            tags: Vec::new(),
+            ..rule
+        };
+        assert!(everywhere.applies_to(None));
+    }
+}
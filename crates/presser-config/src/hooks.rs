@@ -0,0 +1,31 @@
+//! Shell hooks (`[hooks]` section)
+
+use serde::{Deserialize, Serialize};
+
+/// Shell commands run on engine events, with the event as JSON on stdin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Run once for every new entry stored by an update
+    pub on_new_entry: Option<String>,
+
+    /// Run after a digest with at least one entry is generated
+    pub on_digest: Option<String>,
+
+    /// Seconds a hook may run before it is killed
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_new_entry: None,
+            on_digest: None,
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
@@ -0,0 +1,103 @@
+//! Read-it-later services (`[integrations]` section)
+
+use serde::{Deserialize, Serialize};
+
+/// Services entries can be saved to with `presser save`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrationsConfig {
+    /// Service used when none is named (defaults to the only configured one)
+    pub default: Option<String>,
+
+    /// Wallabag instance
+    pub wallabag: Option<WallabagConfig>,
+
+    /// Pocket account
+    pub pocket: Option<PocketConfig>,
+
+    /// Instapaper account
+    pub instapaper: Option<InstapaperConfig>,
+}
+
+impl IntegrationsConfig {
+    /// Names of the configured services
+    pub fn configured(&self) -> Vec<&'static str> {
+        [
+            ("wallabag", self.wallabag.is_some()),
+            ("pocket", self.pocket.is_some()),
+            ("instapaper", self.instapaper.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| name)
+        .collect()
+    }
+
+    /// Service to use when none is named: `default`, or the only configured one
+    pub fn default_service(&self) -> Option<String> {
+        match (&self.default, self.configured().as_slice()) {
+            (Some(name), _) => Some(name.clone()),
+            (None, [only]) => Some(only.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Wallabag API client credentials (Developer > Create a new client)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WallabagConfig {
+    /// Instance URL, e.g. `https://app.wallabag.it`
+    pub url: String,
+    /// API client ID
+    pub client_id: String,
+    /// API client secret
+    pub client_secret: String,
+    /// Account user name
+    pub username: String,
+    /// Account password
+    pub password: String,
+}
+
+/// Pocket application key and user token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PocketConfig {
+    /// Consumer key of your Pocket application
+    pub consumer_key: String,
+    /// Access token authorized for that application
+    pub access_token: String,
+}
+
+/// Instapaper account (Simple API)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstapaperConfig {
+    /// Account email or user name
+    pub username: String,
+    /// Account password (accounts without one leave it unset)
+    pub password: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_service() {
+        let mut config: IntegrationsConfig = toml::from_str(
+            r#"
+[pocket]
+consumer_key = "1234-abcd"
+access_token = "token"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.configured(), ["pocket"]);
+        assert_eq!(config.default_service().as_deref(), Some("pocket"));
+
+        config.instapaper = Some(InstapaperConfig {
+            username: "me@example.com".into(),
+            password: None,
+        });
+        assert_eq!(config.default_service(), None);
+        config.default = Some("instapaper".into());
+        assert_eq!(config.default_service().as_deref(), Some("instapaper"));
+    }
+}
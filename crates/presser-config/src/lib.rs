@@ -25,12 +25,39 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 
+pub mod archive;
+pub mod auth;
+pub mod content;
+pub mod cookies;
 pub mod error;
+pub mod filters;
+pub mod hooks;
+pub mod integrations;
+pub mod logging;
+pub mod notifications;
+pub mod podcasts;
+pub mod scoring;
+pub mod ui;
 pub mod validation;
 
+pub use archive::ArchiveConfig;
+pub use auth::FeedAuth;
+pub use content::{ContentConfig, Truncation};
+pub use cookies::CookiesConfig;
 pub use error::ConfigError;
+pub use filters::FilterRule;
+pub use hooks::HooksConfig;
+pub use integrations::{InstapaperConfig, IntegrationsConfig, PocketConfig, WallabagConfig};
+pub use logging::{LogFormat, LoggingConfig};
+pub use notifications::{
+    NotificationKind, NotificationsConfig, NotifierBackend, NotifierConfig, QuietHours,
+};
+pub use podcasts::PodcastsConfig;
+pub use scoring::ScoringConfig;
+pub use ui::{ImageMode, KeyList, ThemeBase, ThemeConfig, UiConfig};
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +74,62 @@ pub struct Config {
     /// Scheduler configuration
     pub scheduler: SchedulerConfig,
 
+    /// Entry retention and maintenance
+    #[serde(default)]
+    pub retention: RetentionConfig,
+
+    /// Digest output
+    #[serde(default)]
+    pub digest: DigestConfig,
+
+    /// Notification backends and triggers
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    /// Daemon control interface
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+
+    /// Log file
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    /// Read-it-later services
+    #[serde(default)]
+    pub integrations: IntegrationsConfig,
+
+    /// Shell commands run on events
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Terminal UI
+    #[serde(default)]
+    pub ui: UiConfig,
+
+    /// Entry filter rules for groups of feeds (`[[filters]]`)
+    #[serde(default)]
+    pub filters: Vec<FilterRule>,
+
+    /// Entry ranking weights
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+
+    /// Page archive
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+
+    /// Podcast player and downloads
+    #[serde(default)]
+    pub podcasts: PodcastsConfig,
+
+    /// Cookie jar
+    #[serde(default)]
+    pub cookies: CookiesConfig,
+
+    /// Entry content size limits
+    #[serde(default)]
+    pub content: ContentConfig,
+
     /// Feed-specific configurations
     pub feeds: HashMap<String, FeedConfig>,
 }
@@ -62,13 +145,56 @@ pub struct GlobalConfig {
     #[serde(default = "default_fetch_timeout")]
     pub fetch_timeout_secs: u64,
 
-    /// User agent string for HTTP requests
+    /// User agent sent with feed requests (`browser` for a browser-like one)
     #[serde(default = "default_user_agent")]
     pub user_agent: String,
 
+    /// Retry a feed answering 403 Forbidden once with the alternate user
+    /// agent (a browser's, or Presser's own if the browser's was refused)
+    #[serde(default = "default_true")]
+    pub retry_forbidden: bool,
+
     /// Enable content extraction (readability)
     #[serde(default = "default_true")]
     pub extract_content: bool,
+
+    /// Strip cookie banners, subscription nags, and related-article boxes
+    /// from article pages before extraction
+    #[serde(default = "default_true")]
+    pub strip_boilerplate: bool,
+
+    /// CSS selectors of further elements to strip from article pages
+    #[serde(default)]
+    pub strip_selectors: Vec<String>,
+
+    /// Nitter-style bridge used to follow X/Twitter accounts
+    #[serde(default = "default_nitter_instance")]
+    pub nitter_instance: String,
+
+    /// Follow permanent redirects by rewriting feed URLs in `feeds/*.toml`
+    /// and the database (otherwise only reported by `presser fix-urls`)
+    #[serde(default)]
+    pub auto_fix_urls: bool,
+
+    /// Never fetch: updates are deferred and everything reads from the
+    /// database (`--offline` sets this for one run)
+    #[serde(default)]
+    pub offline: bool,
+
+    /// `host:port` connected to when fetches fail, to tell whether the
+    /// network is down (empty turns offline detection off)
+    #[serde(default = "default_connectivity_check")]
+    pub connectivity_check: String,
+
+    /// Requests (feeds, pages, AI) taking at least this many seconds are
+    /// logged with their URL or provider (0 logs none)
+    #[serde(default = "default_slow_request_secs")]
+    pub slow_request_secs: u64,
+
+    /// What becomes of a new entry another feed already stored (the same
+    /// address without tracking parameters, or the same text)
+    #[serde(default)]
+    pub duplicates: DuplicateHandling,
 }
 
 impl Default for GlobalConfig {
@@ -77,11 +203,33 @@ impl Default for GlobalConfig {
             max_concurrent_fetches: default_max_concurrent_fetches(),
             fetch_timeout_secs: default_fetch_timeout(),
             user_agent: default_user_agent(),
+            retry_forbidden: true,
             extract_content: default_true(),
+            strip_boilerplate: default_true(),
+            strip_selectors: Vec::new(),
+            nitter_instance: default_nitter_instance(),
+            auto_fix_urls: false,
+            offline: false,
+            connectivity_check: default_connectivity_check(),
+            slow_request_secs: default_slow_request_secs(),
+            duplicates: DuplicateHandling::default(),
         }
     }
 }
 
+impl GlobalConfig {
+    /// User agent configured for a feed: its own, or the global one
+    pub fn user_agent_for<'a>(&'a self, feed: Option<&'a FeedConfig>) -> &'a str {
+        feed.and_then(|f| f.user_agent.as_deref())
+            .unwrap_or(&self.user_agent)
+    }
+
+    /// Threshold for logging slow requests (zero logs none)
+    pub fn slow_request(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.slow_request_secs)
+    }
+}
+
 /// AI provider configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiConfig {
@@ -112,6 +260,21 @@ pub struct AiConfig {
     /// Enable caching based on content hash
     #[serde(default = "default_true")]
     pub enable_cache: bool,
+
+    /// Tokens to spend per UTC day; entries past the budget stay queued for the next day
+    pub daily_token_budget: Option<u64>,
+
+    /// Maximum summary requests per minute
+    pub requests_per_minute: Option<u32>,
+
+    /// AI requests in flight at once, across all feeds being updated
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+
+    /// Ask the AI what changed when an entry's text is edited after it
+    /// was stored (corrections, major edits)
+    #[serde(default)]
+    pub describe_changes: bool,
 }
 
 /// AI provider type
@@ -123,6 +286,44 @@ pub enum AiProvider {
     Local,
 }
 
+/// Handling of entries that mirror feeds (planets, aggregators) repeat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateHandling {
+    /// Group the duplicate with the original as one story
+    #[default]
+    Link,
+    /// Group it and hide it, without summarizing or announcing it
+    Hide,
+    /// Store it like any other entry
+    Keep,
+}
+
+/// Content extraction backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtractionBackend {
+    /// Readability over the served HTML
+    #[default]
+    Readability,
+    /// Render in headless Chromium first (for JavaScript-rendered pages)
+    Headless,
+}
+
+/// Summary style a feed's entries are summarized in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SummaryStyle {
+    /// A few bullet points
+    Bullets,
+    /// One or two sentences
+    Tldr,
+    /// Several paragraphs covering arguments and details
+    DeepDive,
+    /// A research paper's problem, method, results, and limitations
+    Paper,
+}
+
 /// Database configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
@@ -133,6 +334,51 @@ pub struct DatabaseConfig {
     /// Maximum number of database connections
     #[serde(default = "default_max_connections")]
     pub max_connections: u32,
+
+    /// SQLCipher key (requires the `encryption` build feature)
+    #[serde(default)]
+    pub encryption_key: Option<String>,
+
+    /// Shell command printing the SQLCipher key, e.g. a keyring lookup
+    #[serde(default)]
+    pub encryption_key_command: Option<String>,
+}
+
+impl DatabaseConfig {
+    /// Resolve the encryption key, if any
+    ///
+    /// `PRESSER_DB_KEY` wins over `encryption_key`, which wins over
+    /// `encryption_key_command`. Returns None for an unencrypted database.
+    pub fn encryption_key(&self) -> Result<Option<String>> {
+        if let Ok(key) = std::env::var("PRESSER_DB_KEY") {
+            return Ok(Some(key));
+        }
+        if let Some(key) = &self.encryption_key {
+            return Ok(Some(key.clone()));
+        }
+        let Some(command) = &self.encryption_key_command else {
+            return Ok(None);
+        };
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()
+            .with_context(|| format!("Failed to run encryption_key_command `{}`", command))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "encryption_key_command `{}` failed: {}",
+                command,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let key = String::from_utf8(output.stdout)
+            .context("encryption_key_command printed invalid UTF-8")?;
+        let key = key.trim_end_matches(['\r', '\n']);
+        if key.is_empty() {
+            anyhow::bail!("encryption_key_command `{}` printed an empty key", command);
+        }
+        Ok(Some(key.to_string()))
+    }
 }
 
 /// Scheduler configuration
@@ -145,6 +391,90 @@ pub struct SchedulerConfig {
     /// Enable automatic updates
     #[serde(default = "default_true")]
     pub auto_update: bool,
+
+    /// How many tasks of each group may run at once (`fetches`, `ai-heavy`,
+    /// `maintenance`), independently of the other groups
+    #[serde(default)]
+    pub groups: HashMap<String, usize>,
+}
+
+impl SchedulerConfig {
+    /// Concurrency limit configured for `group`, or `default`
+    pub fn group_limit(&self, group: &str, default: usize) -> usize {
+        self.groups.get(group).copied().unwrap_or(default)
+    }
+}
+
+/// Entry retention configuration (nothing is pruned by default)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Prune entries older than this many days
+    pub max_age_days: Option<u32>,
+
+    /// Keep at most this many entries per feed
+    pub max_entries_per_feed: Option<u32>,
+
+    /// Move pruned entries to the archive table instead of deleting them
+    #[serde(default)]
+    pub archive: bool,
+
+    /// Forget `presser log` records of updates older than this many days
+    pub run_log_days: Option<u32>,
+
+    /// Cron expression for the maintenance task (prune, VACUUM, indices, ANALYZE, WAL checkpoint)
+    pub maintenance_schedule: Option<String>,
+}
+
+/// Digest configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestConfig {
+    /// Template file used instead of the builtin formats (relative to the config directory)
+    pub template: Option<PathBuf>,
+
+    /// Open `presser digest` with an AI overview written from the entries'
+    /// stored summaries (one request per digest)
+    #[serde(default)]
+    pub synthesis: bool,
+
+    /// Digests with fewer entries get no overview (default 5)
+    pub synthesis_min_entries: Option<usize>,
+}
+
+impl DigestConfig {
+    /// Fewest entries a digest needs for an overview
+    pub fn synthesis_threshold(&self) -> usize {
+        self.synthesis_min_entries.unwrap_or(5)
+    }
+}
+
+/// Daemon configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    /// Unix socket for `presser ctl` (defaults to `presser.sock` next to the default database)
+    pub control_socket: Option<PathBuf>,
+
+    /// Loopback TCP address to listen on instead of the Unix socket
+    pub control_addr: Option<SocketAddr>,
+
+    /// Minutes between metrics summaries in the log (default 60, 0 disables)
+    pub metrics_log_minutes: Option<u64>,
+}
+
+impl DaemonConfig {
+    /// How often the daemon logs metrics, if at all
+    pub fn metrics_log_interval(&self) -> Option<std::time::Duration> {
+        match self.metrics_log_minutes.unwrap_or(60) {
+            0 => None,
+            minutes => Some(std::time::Duration::from_secs(minutes * 60)),
+        }
+    }
+
+    /// Path of the control socket, with the default applied
+    pub fn control_socket_path(&self) -> PathBuf {
+        self.control_socket
+            .clone()
+            .unwrap_or_else(default_control_socket)
+    }
 }
 
 /// Feed-specific configuration
@@ -169,15 +499,113 @@ pub struct FeedConfig {
     /// Whether to extract full content
     pub extract_content: Option<bool>,
 
-    /// Tags for categorization
+    /// Extraction backend for article pages
     #[serde(default)]
+    pub extractor: ExtractionBackend,
+
+    /// Style of this feed's summaries; arXiv and OAI-PMH feeds default to
+    /// `paper`, others to the plain summary prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_style: Option<SummaryStyle>,
+
+    /// Tags for categorization
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 
+    /// Folder shown in the TUI sidebar
+    pub folder: Option<String>,
+
+    /// Keep only entries mentioning at least one of these (case-insensitive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_keywords: Vec<String>,
+
+    /// Drop entries mentioning any of these (case-insensitive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_keywords: Vec<String>,
+
+    /// Filter expression an entry must satisfy to be stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+
+    /// Multiplies the ranks of this feed's entries (default 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<f64>,
+
+    /// Whether new entries of this feed can trigger high-priority
+    /// notifications
+    #[serde(default = "default_true")]
+    pub notify: bool,
+
+    /// Count the comment feeds of recent entries on each update, following
+    /// their comment counts over time
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub track_comments: bool,
+
+    /// Translate entries written in another language into this one (an
+    /// ISO 639-1 code, e.g. "en") before summarizing them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translate_to: Option<String>,
+
+    /// User agent sent when fetching this feed, overriding the global one
+    /// (`browser` for a browser-like one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// Token sent with this feed's requests, and how to get a new one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<FeedAuth>,
+
     /// Whether this feed is enabled
     #[serde(default = "default_true")]
     pub enabled: bool,
 }
 
+impl FeedConfig {
+    /// Config for `url` with every optional setting at its default
+    pub fn new(url: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            name: name.into(),
+            update_interval: None,
+            custom_prompt: None,
+            enable_ai: true,
+            extract_content: None,
+            extractor: ExtractionBackend::default(),
+            summary_style: None,
+            tags: Vec::new(),
+            folder: None,
+            include_keywords: Vec::new(),
+            exclude_keywords: Vec::new(),
+            filter: None,
+            priority: None,
+            notify: true,
+            track_comments: false,
+            translate_to: None,
+            user_agent: None,
+            auth: None,
+            enabled: true,
+        }
+    }
+
+    /// Check the URL, name, and update interval
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        validation::validate_feed(&self.url, self)
+    }
+
+    /// Render as the contents of a `feeds/*.toml` file
+    pub fn to_toml(&self) -> Result<String> {
+        Self::list_to_toml(std::slice::from_ref(self))
+    }
+
+    /// Render several feeds as one `feeds/*.toml` file
+    pub fn list_to_toml(feeds: &[FeedConfig]) -> Result<String> {
+        let file = FeedToml {
+            feed: feeds.to_vec(),
+        };
+        toml::to_string(&file).context("Failed to serialize feed config")
+    }
+}
+
 /// Intermediate struct for parsing global.toml
 #[derive(Debug, Clone, Deserialize, Default)]
 struct GlobalToml {
@@ -189,10 +617,38 @@ struct GlobalToml {
     database: Option<DatabaseConfig>,
     #[serde(default)]
     scheduler: Option<SchedulerConfig>,
+    #[serde(default)]
+    retention: RetentionConfig,
+    #[serde(default)]
+    digest: DigestConfig,
+    #[serde(default)]
+    notifications: NotificationsConfig,
+    #[serde(default)]
+    daemon: DaemonConfig,
+    #[serde(default)]
+    logging: LoggingConfig,
+    #[serde(default)]
+    integrations: IntegrationsConfig,
+    #[serde(default)]
+    hooks: HooksConfig,
+    #[serde(default)]
+    ui: UiConfig,
+    #[serde(default)]
+    filters: Vec<FilterRule>,
+    #[serde(default)]
+    scoring: ScoringConfig,
+    #[serde(default)]
+    archive: ArchiveConfig,
+    #[serde(default)]
+    podcasts: PodcastsConfig,
+    #[serde(default)]
+    cookies: CookiesConfig,
+    #[serde(default)]
+    content: ContentConfig,
 }
 
 /// Intermediate struct for parsing feed TOML files
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct FeedToml {
     #[serde(default)]
     feed: Vec<FeedConfig>,
@@ -254,6 +710,10 @@ impl Config {
             max_tokens: default_max_tokens(),
             temperature: default_temperature(),
             enable_cache: true,
+            daily_token_budget: None,
+            requests_per_minute: None,
+            max_concurrent_requests: default_max_concurrent_requests(),
+            describe_changes: false,
         });
 
         let config = Config {
@@ -262,11 +722,48 @@ impl Config {
             database: global_toml.database.unwrap_or_else(|| DatabaseConfig {
                 path: default_db_path(),
                 max_connections: default_max_connections(),
+                encryption_key: None,
+                encryption_key_command: None,
             }),
             scheduler: global_toml.scheduler.unwrap_or_else(|| SchedulerConfig {
                 default_interval: default_update_interval(),
                 auto_update: default_true(),
+                groups: HashMap::new(),
             }),
+            retention: global_toml.retention,
+            digest: DigestConfig {
+                template: global_toml
+                    .digest
+                    .template
+                    .as_ref()
+                    .map(|path| dir.join(path)),
+                ..global_toml.digest
+            },
+            notifications: global_toml.notifications,
+            daemon: global_toml.daemon,
+            logging: global_toml.logging,
+            integrations: global_toml.integrations,
+            hooks: global_toml.hooks,
+            ui: global_toml.ui,
+            filters: global_toml.filters,
+            scoring: global_toml.scoring,
+            archive: ArchiveConfig {
+                path: global_toml.archive.path.as_ref().map(|path| dir.join(path)),
+                ..global_toml.archive
+            },
+            podcasts: PodcastsConfig {
+                path: global_toml
+                    .podcasts
+                    .path
+                    .as_ref()
+                    .map(|path| dir.join(path)),
+                ..global_toml.podcasts
+            },
+            cookies: CookiesConfig {
+                path: global_toml.cookies.path.as_ref().map(|path| dir.join(path)),
+                ..global_toml.cookies
+            },
+            content: global_toml.content,
             feeds,
         };
 
@@ -287,6 +784,130 @@ impl Config {
         Ok(Self::config_dir()?.join("feeds"))
     }
 
+    /// Replace a feed's URL in whichever `feeds/*.toml` file under `dir`
+    /// defines it, returning the rewritten file (`None` if no file has `old`)
+    ///
+    /// The URL is swapped in place so comments and formatting survive; a file
+    /// where that doesn't take (an escaped URL, say) is re-serialized instead.
+    pub fn rewrite_feed_url(dir: &Path, old: &str, new: &str) -> Result<Option<PathBuf>> {
+        let Some((path, content, mut file)) = Self::find_feed_file(dir, old)? else {
+            return Ok(None);
+        };
+        let swapped = content
+            .replace(&format!("\"{}\"", old), &format!("\"{}\"", new))
+            .replace(&format!("'{}'", old), &format!("'{}'", new));
+        let rewritten = match toml::from_str::<FeedToml>(&swapped) {
+            Ok(parsed) if parsed.feed.iter().all(|f| f.url != old) => swapped,
+            _ => {
+                for feed in file.feed.iter_mut().filter(|f| f.url == old) {
+                    feed.url = new.to_string();
+                }
+                FeedConfig::list_to_toml(&file.feed)?
+            }
+        };
+        std::fs::write(&path, rewritten)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(Some(path))
+    }
+
+    /// Write `feed` to a new `feeds/<id>.toml` under `dir`, returning its path
+    pub fn add_feed(dir: &Path, id: &str, feed: &FeedConfig) -> Result<PathBuf> {
+        feed.validate()?;
+        let feeds_dir = dir.join("feeds");
+        std::fs::create_dir_all(&feeds_dir)
+            .with_context(|| format!("Failed to create {}", feeds_dir.display()))?;
+        let path = feeds_dir.join(format!("{}.toml", id));
+        if path.exists() {
+            anyhow::bail!("{} already exists", path.display());
+        }
+        std::fs::write(&path, feed.to_toml()?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Replace the settings of the feed at `url` (`feed` may change the URL
+    /// too) in whichever `feeds/*.toml` file under `dir` defines it,
+    /// returning that file (`None` if no file has `url`)
+    ///
+    /// The file is re-serialized, so its comments are lost.
+    pub fn update_feed(dir: &Path, url: &str, feed: &FeedConfig) -> Result<Option<PathBuf>> {
+        feed.validate()?;
+        let Some((path, _, mut file)) = Self::find_feed_file(dir, url)? else {
+            return Ok(None);
+        };
+        for slot in file.feed.iter_mut().filter(|f| f.url == url) {
+            *slot = feed.clone();
+        }
+        std::fs::write(&path, FeedConfig::list_to_toml(&file.feed)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(Some(path))
+    }
+
+    /// Remove the feed at `url` from whichever `feeds/*.toml` file under
+    /// `dir` defines it, deleting a file left without feeds, and return that
+    /// file (`None` if no file has `url`)
+    pub fn remove_feed(dir: &Path, url: &str) -> Result<Option<PathBuf>> {
+        let Some((path, _, mut file)) = Self::find_feed_file(dir, url)? else {
+            return Ok(None);
+        };
+        file.feed.retain(|f| f.url != url);
+        if file.feed.is_empty() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        } else {
+            std::fs::write(&path, FeedConfig::list_to_toml(&file.feed)?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        Ok(Some(path))
+    }
+
+    /// The `feeds/*.toml` file under `dir` defining the feed at `url`, with
+    /// its content and parsed feeds
+    fn find_feed_file(dir: &Path, url: &str) -> Result<Option<(PathBuf, String, FeedToml)>> {
+        let feeds_dir = dir.join("feeds");
+        if !feeds_dir.is_dir() {
+            return Ok(None);
+        }
+        for entry in std::fs::read_dir(&feeds_dir)
+            .with_context(|| format!("Failed to read {}", feeds_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let file: FeedToml = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            if file.feed.iter().any(|f| f.url == url) {
+                return Ok(Some((path, content, file)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Extraction backend configured for a feed URL (readability if unset)
+    pub fn extraction_backend_for(&self, url: &str) -> ExtractionBackend {
+        self.feeds.get(url).map(|f| f.extractor).unwrap_or_default()
+    }
+
+    /// Whether a feed URL's entries get their article pages extracted
+    /// (the feed's `extract_content`, else the global one)
+    pub fn extracts_content_for(&self, url: &str) -> bool {
+        self.feeds
+            .get(url)
+            .and_then(|f| f.extract_content)
+            .unwrap_or(self.global.extract_content)
+    }
+
+    /// Folder assignment for every configured feed, keyed by URL
+    pub fn folder_assignments(&self) -> HashMap<String, Option<String>> {
+        self.feeds
+            .iter()
+            .map(|(url, feed)| (url.clone(), feed.folder.clone()))
+            .collect()
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<(), ConfigError> {
         validation::validate_config(self)
@@ -294,26 +915,56 @@ impl Config {
 }
 
 // Default value functions
-fn default_max_concurrent_fetches() -> usize { 10 }
-fn default_fetch_timeout() -> u64 { 30 }
+fn default_max_concurrent_fetches() -> usize {
+    10
+}
+fn default_fetch_timeout() -> u64 {
+    30
+}
 fn default_user_agent() -> String {
     format!("Presser/{}", env!("CARGO_PKG_VERSION"))
 }
-fn default_true() -> bool { true }
+pub(crate) fn default_true() -> bool {
+    true
+}
+fn default_nitter_instance() -> String {
+    "nitter.net".to_string()
+}
+fn default_connectivity_check() -> String {
+    "1.1.1.1:443".to_string()
+}
+fn default_slow_request_secs() -> u64 {
+    10
+}
 fn default_system_prompt() -> String {
     "You are a helpful assistant that creates concise summaries of articles. \
-     Focus on key points and insights.".to_string()
+     Focus on key points and insights."
+        .to_string()
+}
+fn default_max_tokens() -> u32 {
+    500
+}
+fn default_temperature() -> f32 {
+    0.7
+}
+fn default_max_concurrent_requests() -> usize {
+    4
 }
-fn default_max_tokens() -> u32 { 500 }
-fn default_temperature() -> f32 { 0.7 }
 fn default_db_path() -> PathBuf {
     dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("presser")
         .join("presser.db")
 }
-fn default_max_connections() -> u32 { 5 }
-fn default_update_interval() -> String { "0 0 */6 * * *".to_string() } // Every 6 hours (sec min hour day month weekday)
+fn default_max_connections() -> u32 {
+    5
+}
+fn default_control_socket() -> PathBuf {
+    default_db_path().with_file_name("presser.sock")
+}
+fn default_update_interval() -> String {
+    "0 0 */6 * * *".to_string()
+} // Every 6 hours (sec min hour day month weekday)
 
 #[cfg(test)]
 mod tests {
@@ -327,6 +978,25 @@ mod tests {
         assert!(default_true());
     }
 
+    #[test]
+    fn test_encryption_key_command() {
+        let mut db = DatabaseConfig {
+            path: PathBuf::from("presser.db"),
+            max_connections: 1,
+            encryption_key: None,
+            encryption_key_command: Some("printf 'hunter2\\n'".to_string()),
+        };
+        if std::env::var("PRESSER_DB_KEY").is_ok() {
+            return;
+        }
+        assert_eq!(db.encryption_key().unwrap().as_deref(), Some("hunter2"));
+        db.encryption_key = Some("inline".to_string());
+        assert_eq!(db.encryption_key().unwrap().as_deref(), Some("inline"));
+        db.encryption_key = None;
+        db.encryption_key_command = Some("exit 3".to_string());
+        assert!(db.encryption_key().is_err());
+    }
+
     #[test]
     fn test_load_from_dir_empty() {
         let temp_dir = TempDir::new().unwrap();
@@ -354,6 +1024,38 @@ endpoint = "http://localhost:8080"
         let config = Config::load_from_dir(temp_dir.path()).unwrap();
         assert_eq!(config.ai.model, "test-model");
         assert!(config.feeds.is_empty());
+        assert!(config.digest.template.is_none());
+    }
+
+    #[test]
+    fn test_digest_template_relative_to_config_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("global.toml"),
+            "[digest]\ntemplate = \"templates/newsletter.html\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from_dir(temp_dir.path()).unwrap();
+        assert_eq!(
+            config.digest.template,
+            Some(temp_dir.path().join("templates/newsletter.html"))
+        );
+    }
+
+    #[test]
+    fn test_scheduler_groups() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("global.toml"),
+            "[scheduler]\n[scheduler.groups]\nai-heavy = 1\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from_dir(temp_dir.path()).unwrap();
+        assert_eq!(config.scheduler.group_limit("ai-heavy", 2), 1);
+        assert_eq!(config.scheduler.group_limit("maintenance", 1), 1);
+        assert!(config.scheduler.auto_update);
     }
 
     #[test]
@@ -383,8 +1085,120 @@ name = "Test Feed"
         )
         .unwrap();
 
-        let config = Config::load_from_dir(temp_dir.path()).unwrap();
+        let mut config = Config::load_from_dir(temp_dir.path()).unwrap();
         assert_eq!(config.feeds.len(), 1);
         assert!(config.feeds.contains_key("https://example.com/feed"));
+        assert_eq!(
+            config.extraction_backend_for("https://example.com/feed"),
+            ExtractionBackend::Readability
+        );
+        assert!(config.extracts_content_for("https://example.com/feed"));
+        config.global.extract_content = false;
+        assert!(!config.extracts_content_for("https://example.com/feed"));
+    }
+
+    #[test]
+    fn test_summary_style() {
+        let feed: FeedConfig = toml::from_str(
+            "url = \"arxiv:cs.LG\"\nname = \"Machine learning\"\nsummary_style = \"deep-dive\"\n",
+        )
+        .unwrap();
+        assert_eq!(feed.summary_style, Some(SummaryStyle::DeepDive));
+        assert!(toml::from_str::<FeedConfig>(
+            "url = \"a\"\nname = \"b\"\nsummary_style = \"essay\"\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_rewrite_feed_url() {
+        let temp_dir = TempDir::new().unwrap();
+        let feeds_dir = temp_dir.path().join("feeds");
+        std::fs::create_dir(&feeds_dir).unwrap();
+        std::fs::write(
+            feeds_dir.join("blog.toml"),
+            "# My blog\n[[feed]]\nurl = \"http://www.example.com/feed\"\nname = \"Blog\"\n",
+        )
+        .unwrap();
+
+        let old = "http://www.example.com/feed";
+        let new = "https://example.com/feed";
+        let path = Config::rewrite_feed_url(temp_dir.path(), old, new).unwrap();
+        assert_eq!(path, Some(feeds_dir.join("blog.toml")));
+        let content = std::fs::read_to_string(feeds_dir.join("blog.toml")).unwrap();
+        assert!(content.starts_with("# My blog\n"), "{}", content);
+
+        let config = Config::load_from_dir(temp_dir.path()).unwrap();
+        assert_eq!(config.feeds[new].name, "Blog");
+        assert!(!config.feeds.contains_key(old));
+        assert_eq!(
+            Config::rewrite_feed_url(temp_dir.path(), old, new).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_add_update_remove_feed() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let blog = FeedConfig::new("https://example.com/feed", "Blog");
+        let path = Config::add_feed(dir, "blog", &blog).unwrap();
+        assert_eq!(path, dir.join("feeds").join("blog.toml"));
+        assert!(Config::add_feed(dir, "blog", &blog).is_err());
+        let mut news = FeedConfig::new("https://news.example.com/rss", "News");
+        std::fs::write(
+            dir.join("feeds").join("more.toml"),
+            FeedConfig::list_to_toml(&[
+                news.clone(),
+                FeedConfig::new("https://other.example.com/", "Other"),
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+
+        news.tags = vec!["daily".into()];
+        news.enable_ai = false;
+        let path = Config::update_feed(dir, &news.url, &news).unwrap();
+        assert_eq!(path, Some(dir.join("feeds").join("more.toml")));
+        let config = Config::load_from_dir(dir).unwrap();
+        assert_eq!(config.feeds[&news.url].tags, ["daily"]);
+        assert!(!config.feeds[&news.url].enable_ai);
+        assert_eq!(config.feeds.len(), 3);
+        news.update_interval = Some("not cron".into());
+        assert!(Config::update_feed(dir, &news.url, &news).is_err());
+
+        assert!(Config::remove_feed(dir, &blog.url).unwrap().is_some());
+        assert!(!dir.join("feeds").join("blog.toml").exists());
+        Config::remove_feed(dir, &news.url).unwrap();
+        let config = Config::load_from_dir(dir).unwrap();
+        assert_eq!(
+            config.feeds.keys().collect::<Vec<_>>(),
+            ["https://other.example.com/"]
+        );
+        assert_eq!(Config::remove_feed(dir, &news.url).unwrap(), None);
+        news.update_interval = None;
+        assert_eq!(Config::update_feed(dir, &news.url, &news).unwrap(), None);
+    }
+
+    #[test]
+    fn test_feed_config_to_toml_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let feeds_dir = temp_dir.path().join("feeds");
+        std::fs::create_dir(&feeds_dir).unwrap();
+
+        let mut feed = FeedConfig::new("https://example.com/feed", "Example");
+        feed.tags = vec!["tech".into()];
+        feed.enable_ai = false;
+        let toml = feed.to_toml().unwrap();
+        assert!(toml.starts_with("[[feed]]"));
+        assert!(!toml.contains("include_keywords"));
+        std::fs::write(feeds_dir.join("example.toml"), toml).unwrap();
+
+        let config = Config::load_from_dir(temp_dir.path()).unwrap();
+        let loaded = &config.feeds["https://example.com/feed"];
+        assert_eq!(loaded.name, "Example");
+        assert_eq!(loaded.tags, ["tech"]);
+        assert!(!loaded.enable_ai);
+        assert!(loaded.enabled);
     }
 }
@@ -0,0 +1,86 @@
+//! Log file configuration (`[logging]` section)
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::default_true;
+
+/// Line format of the log file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable, one event per line
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Log file settings (console logging is controlled by `-v`/`-d` only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Write logs to a file
+    #[serde(default)]
+    pub file: bool,
+
+    /// Log file path (defaults to `logs/presser.log` next to the default database)
+    pub path: Option<PathBuf>,
+
+    /// Line format
+    #[serde(default)]
+    pub format: LogFormat,
+
+    /// Level filter with optional per-module levels, e.g. `info,presser_feeds=debug,sqlx=warn`
+    #[serde(default = "default_filter")]
+    pub filter: String,
+
+    /// Start a new file once the current one exceeds this many megabytes (0 disables)
+    #[serde(default = "default_max_size_mb")]
+    pub max_size_mb: u64,
+
+    /// Start a new file every day (UTC)
+    #[serde(default = "default_true")]
+    pub daily: bool,
+
+    /// Rotated files to keep
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: false,
+            path: None,
+            format: LogFormat::default(),
+            filter: default_filter(),
+            max_size_mb: default_max_size_mb(),
+            daily: true,
+            keep: default_keep(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Path of the log file, with the default applied
+    pub fn file_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            crate::default_db_path()
+                .with_file_name("logs")
+                .join("presser.log")
+        })
+    }
+}
+
+fn default_filter() -> String {
+    "info".to_string()
+}
+
+fn default_max_size_mb() -> u64 {
+    10
+}
+
+fn default_keep() -> usize {
+    7
+}
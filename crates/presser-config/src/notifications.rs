@@ -0,0 +1,223 @@
+//! Notification configuration (`[notifications]` section)
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+/// Events that can trigger a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// A new entry matched the priority rules
+    HighPriority,
+    /// A digest was generated
+    DigestReady,
+    /// A feed reached `failure_threshold` consecutive failed fetches
+    FeedFailing,
+}
+
+/// Notification settings (nothing is sent until a backend is configured)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// New entries whose title or description contains one of these (case-insensitive) are high priority
+    #[serde(default)]
+    pub priority_keywords: Vec<String>,
+
+    /// New entries scored at least this high are high priority
+    pub min_score: Option<f64>,
+
+    /// Consecutive failed fetches before a feed counts as failing
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: i64,
+
+    /// Where notifications are sent (`[[notifications.backend]]` tables)
+    #[serde(default, rename = "backend")]
+    pub backends: Vec<NotifierConfig>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            priority_keywords: Vec::new(),
+            min_score: None,
+            failure_threshold: default_failure_threshold(),
+            backends: Vec::new(),
+        }
+    }
+}
+
+/// One notification backend and the events routed to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotifierConfig {
+    /// Backend type and its settings
+    #[serde(flatten)]
+    pub backend: NotifierBackend,
+
+    /// Events sent to this backend (all if empty)
+    #[serde(default)]
+    pub events: Vec<NotificationKind>,
+
+    /// Local times when nothing is sent to this backend (`"22:00-07:00"`)
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl NotifierConfig {
+    /// Whether `kind` is routed to this backend
+    pub fn wants(&self, kind: NotificationKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// A daily window of local time, which may span midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Whether `time` falls in the window (its end is not part of it)
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Invalid quiet hours '{}' (expected HH:MM-HH:MM)", s);
+        let (start, end) = s.split_once('-').ok_or_else(error)?;
+        let time = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M").map_err(|_| error());
+        Ok(Self {
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+}
+
+impl TryFrom<String> for QuietHours {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<QuietHours> for String {
+    fn from(hours: QuietHours) -> Self {
+        hours.to_string()
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (start, end) = (self.start.format("%H:%M"), self.end.format("%H:%M"));
+        write!(f, "{}-{}", start, end)
+    }
+}
+
+/// Notification backend settings, selected by `type`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum NotifierBackend {
+    /// POST the notification as JSON to a URL
+    Webhook {
+        /// Endpoint URL
+        url: String,
+    },
+    /// Publish to an ntfy topic
+    Ntfy {
+        /// ntfy server
+        #[serde(default = "default_ntfy_server")]
+        server: String,
+        /// Topic name
+        topic: String,
+        /// Access token for protected topics
+        token: Option<String>,
+    },
+    /// Send a message through a Telegram bot
+    Telegram {
+        /// Bot token from @BotFather
+        bot_token: String,
+        /// Chat to post to
+        chat_id: String,
+    },
+    /// Post an embed to a Discord channel webhook
+    Discord {
+        /// Channel webhook URL
+        webhook_url: String,
+    },
+    /// Show a native desktop notification (needs the
+    /// `desktop-notifications` feature)
+    Desktop,
+}
+
+fn default_failure_threshold() -> i64 {
+    3
+}
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backends() {
+        let config: NotificationsConfig = toml::from_str(
+            r#"
+priority_keywords = ["outage"]
+
+[[backend]]
+type = "ntfy"
+topic = "presser"
+
+[[backend]]
+type = "discord"
+webhook_url = "https://discord.com/api/webhooks/1/abc"
+events = ["feed_failing"]
+
+[[backend]]
+type = "desktop"
+quiet_hours = "22:30-07:00"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.failure_threshold, 3);
+        assert_eq!(
+            config.backends[0].backend,
+            NotifierBackend::Ntfy {
+                server: "https://ntfy.sh".into(),
+                topic: "presser".into(),
+                token: None,
+            }
+        );
+        assert!(config.backends[0].wants(NotificationKind::DigestReady));
+        assert!(!config.backends[1].wants(NotificationKind::DigestReady));
+        assert!(config.backends[1].wants(NotificationKind::FeedFailing));
+
+        assert_eq!(config.backends[2].backend, NotifierBackend::Desktop);
+        let quiet = config.backends[2].quiet_hours.unwrap();
+        assert_eq!(quiet.to_string(), "22:30-07:00");
+        let at = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        assert!(quiet.contains(at("23:00")));
+        assert!(quiet.contains(at("06:59")));
+        assert!(!quiet.contains(at("07:00")));
+        assert!(!quiet.contains(at("12:00")));
+        let daytime: QuietHours = "09:00-17:00".parse().unwrap();
+        assert!(daytime.contains(at("12:00")));
+        assert!(!daytime.contains(at("23:00")));
+        let late = "type = \"desktop\"\nquiet_hours = \"late\"";
+        assert!(toml::from_str::<NotifierConfig>(late).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Podcast settings (`[podcasts]` section)
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Playing and downloading the audio enclosures of entries from the TUI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PodcastsConfig {
+    /// Player command, split on whitespace: `{file}` is replaced by the
+    /// downloaded file (or the enclosure's address when it isn't
+    /// downloaded) and `{start}` by the seconds to resume from
+    #[serde(default = "default_player")]
+    pub player: String,
+
+    /// Directory of downloaded enclosures (relative to the config
+    /// directory; default: `podcasts` next to the database)
+    pub path: Option<PathBuf>,
+}
+
+impl Default for PodcastsConfig {
+    fn default() -> Self {
+        Self {
+            player: default_player(),
+            path: None,
+        }
+    }
+}
+
+impl PodcastsConfig {
+    /// Directory downloads are stored in, given the database path
+    pub fn dir(&self, database: &Path) -> PathBuf {
+        match &self.path {
+            Some(path) => path.clone(),
+            None => database.with_file_name("podcasts"),
+        }
+    }
+
+    /// Program and arguments that play `file` from `start_secs` on
+    ///
+    /// Returns None if no player is configured.
+    pub fn player_command(&self, file: &str, start_secs: i64) -> Option<Vec<String>> {
+        let start = start_secs.max(0).to_string();
+        let command: Vec<String> = self
+            .player
+            .split_whitespace()
+            .map(|word| word.replace("{file}", file).replace("{start}", &start))
+            .collect();
+        (!command.is_empty()).then_some(command)
+    }
+}
+
+fn default_player() -> String {
+    "mpv --no-video --start={start} {file}".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_command() {
+        let config = PodcastsConfig::default();
+        assert_eq!(
+            config.player_command("/data/My Show/ep 1.mp3", 95).unwrap(),
+            ["mpv", "--no-video", "--start=95", "/data/My Show/ep 1.mp3"]
+        );
+        let config: PodcastsConfig =
+            toml::from_str(r#"player = "vlc --intf dummy {file}""#).unwrap();
+        assert_eq!(
+            config
+                .player_command("https://cdn.example.com/ep.mp3", 0)
+                .unwrap(),
+            ["vlc", "--intf", "dummy", "https://cdn.example.com/ep.mp3"]
+        );
+        let config = PodcastsConfig {
+            player: " ".into(),
+            ..config
+        };
+        assert_eq!(config.player_command("ep.mp3", 0), None);
+    }
+
+    #[test]
+    fn test_podcasts_dir() {
+        let config = PodcastsConfig::default();
+        assert_eq!(
+            config.dir(Path::new("/data/presser/presser.db")),
+            Path::new("/data/presser/podcasts")
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Entry ranking weights (`[scoring]` section)
+
+use serde::{Deserialize, Serialize};
+
+/// How the signals behind an entry's rank are weighed
+///
+/// A feed's `priority` multiplies the ranks of its entries on top of these.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    /// Weight of the entry's AI relevance score
+    #[serde(default = "default_weight")]
+    pub relevance_weight: f64,
+
+    /// Weight of social signals (Hacker News points, Reddit score)
+    #[serde(default = "default_weight")]
+    pub social_weight: f64,
+
+    /// Points (or score) counted as the strongest social signal; more
+    /// doesn't rank higher
+    #[serde(default = "default_social_saturation")]
+    pub social_saturation: u32,
+
+    /// Hours after which an entry's rank has halved
+    #[serde(default = "default_half_life_hours")]
+    pub half_life_hours: f64,
+
+    /// Only entries published in this many days are ranked
+    #[serde(default = "default_window_days")]
+    pub window_days: u32,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            relevance_weight: default_weight(),
+            social_weight: default_weight(),
+            social_saturation: default_social_saturation(),
+            half_life_hours: default_half_life_hours(),
+            window_days: default_window_days(),
+        }
+    }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_social_saturation() -> u32 {
+    500
+}
+
+fn default_half_life_hours() -> f64 {
+    24.0
+}
+
+fn default_window_days() -> u32 {
+    14
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let scoring: ScoringConfig = toml::from_str("social_weight = 0.5").unwrap();
+        assert_eq!(
+            scoring,
+            ScoringConfig {
+                social_weight: 0.5,
+                ..ScoringConfig::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Terminal UI settings (`[ui]` section)
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// TUI settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiConfig {
+    /// Key bindings by action name, replacing that action's defaults,
+    /// e.g. `down = ["j", "Down"]` or `palette = ";"`
+    #[serde(default)]
+    pub keys: HashMap<String, KeyList>,
+
+    /// How the reader shows images in articles
+    #[serde(default)]
+    pub images: ImageMode,
+
+    /// Colors (`[ui.theme]`)
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    /// Entries listed in the reader's related panel, closest by embedding
+    /// first (0 turns the panel off)
+    #[serde(default = "default_related_entries")]
+    pub related_entries: usize,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            keys: HashMap::new(),
+            images: ImageMode::default(),
+            theme: ThemeConfig::default(),
+            related_entries: default_related_entries(),
+        }
+    }
+}
+
+fn default_related_entries() -> usize {
+    5
+}
+
+/// TUI colors: a builtin palette with any of its colors replaced
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// Palette the colors start from
+    #[serde(default)]
+    pub base: ThemeBase,
+
+    /// Colors by element name, e.g. `accent = "#fabd2f"` or `dim = "8"`:
+    /// a color name, `#rrggbb`, or a 256-color index
+    #[serde(flatten)]
+    pub colors: HashMap<String, String>,
+}
+
+/// Builtin TUI palettes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeBase {
+    #[default]
+    Dark,
+    Light,
+    Solarized,
+    Gruvbox,
+}
+
+/// Terminal graphics protocol for inline images
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageMode {
+    /// Detect the terminal's protocol, else show placeholders
+    #[default]
+    Auto,
+    Kitty,
+    Iterm,
+    Sixel,
+    /// Always show placeholders
+    Off,
+}
+
+/// One key or several for the same action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    /// The keys as written in the config
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            KeyList::One(key) => vec![key.as_str()],
+            KeyList::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
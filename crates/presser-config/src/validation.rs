@@ -7,9 +7,8 @@ use url::Url;
 
 /// Validate a cron expression
 fn validate_cron_expression(expr: &str, context: &str) -> Result<(), ConfigError> {
-    Schedule::from_str(expr).map_err(|e| {
-        ConfigError::InvalidCron(format!("{}: '{}' - {}", context, expr, e))
-    })?;
+    Schedule::from_str(expr)
+        .map_err(|e| ConfigError::InvalidCron(format!("{}: '{}' - {}", context, expr, e)))?;
     Ok(())
 }
 
@@ -24,6 +23,30 @@ pub fn validate_config(config: &Config) -> Result<(), ConfigError> {
     // Validate scheduler settings
     validate_scheduler(&config.scheduler)?;
 
+    // Validate retention settings
+    validate_retention(&config.retention)?;
+
+    // Validate notification settings
+    validate_notifications(&config.notifications)?;
+
+    validate_scoring(&config.scoring)?;
+
+    if config.hooks.timeout_secs == 0 {
+        return Err(ConfigError::InvalidConfig(
+            "hooks.timeout_secs must be greater than 0".to_string(),
+        ));
+    }
+
+    // The control interface has no authentication, so keep it off the network
+    if let Some(addr) = config.daemon.control_addr {
+        if !addr.ip().is_loopback() {
+            return Err(ConfigError::InvalidConfig(format!(
+                "daemon.control_addr must be a loopback address, got {}",
+                addr
+            )));
+        }
+    }
+
     // Validate each feed
     for (feed_id, feed) in &config.feeds {
         validate_feed(feed_id, feed)?;
@@ -55,9 +78,10 @@ fn validate_ai(ai: &crate::AiConfig) -> Result<(), ConfigError> {
     match ai.provider {
         crate::AiProvider::OpenAI | crate::AiProvider::Anthropic => {
             if ai.api_key.is_none() {
-                return Err(ConfigError::InvalidConfig(
-                    format!("{:?} provider requires an API key", ai.provider),
-                ));
+                return Err(ConfigError::InvalidConfig(format!(
+                    "{:?} provider requires an API key",
+                    ai.provider
+                )));
             }
         }
         crate::AiProvider::Local => {
@@ -72,8 +96,19 @@ fn validate_ai(ai: &crate::AiConfig) -> Result<(), ConfigError> {
 
     // Validate endpoint URL if provided
     if let Some(endpoint) = &ai.endpoint {
-        Url::parse(endpoint)
-            .map_err(|_| ConfigError::InvalidUrl(endpoint.clone()))?;
+        Url::parse(endpoint).map_err(|_| ConfigError::InvalidUrl(endpoint.clone()))?;
+    }
+
+    if ai.requests_per_minute == Some(0) {
+        return Err(ConfigError::InvalidConfig(
+            "requests_per_minute must be greater than 0 (omit it for no limit)".to_string(),
+        ));
+    }
+
+    if ai.max_concurrent_requests == 0 {
+        return Err(ConfigError::InvalidConfig(
+            "max_concurrent_requests must be greater than 0".to_string(),
+        ));
     }
 
     // Validate temperature range
@@ -97,29 +132,120 @@ fn validate_scheduler(scheduler: &crate::SchedulerConfig) -> Result<(), ConfigEr
     Ok(())
 }
 
+/// Validate retention configuration
+fn validate_retention(retention: &crate::RetentionConfig) -> Result<(), ConfigError> {
+    if retention.max_age_days == Some(0)
+        || retention.max_entries_per_feed == Some(0)
+        || retention.run_log_days == Some(0)
+    {
+        return Err(ConfigError::InvalidConfig(
+            "retention limits must be greater than 0 (omit them to keep everything)".to_string(),
+        ));
+    }
+    if let Some(schedule) = &retention.maintenance_schedule {
+        validate_cron_expression(schedule, "retention.maintenance_schedule")?;
+    }
+    Ok(())
+}
+
+/// Validate notification configuration
+fn validate_notifications(notifications: &crate::NotificationsConfig) -> Result<(), ConfigError> {
+    if notifications.failure_threshold <= 0 {
+        return Err(ConfigError::InvalidConfig(
+            "notifications.failure_threshold must be greater than 0".to_string(),
+        ));
+    }
+    for notifier in &notifications.backends {
+        match &notifier.backend {
+            crate::NotifierBackend::Webhook { url } => {
+                Url::parse(url).map_err(|_| ConfigError::InvalidUrl(url.clone()))?;
+            }
+            crate::NotifierBackend::Ntfy { server, topic, .. } => {
+                Url::parse(server).map_err(|_| ConfigError::InvalidUrl(server.clone()))?;
+                if topic.is_empty() {
+                    return Err(ConfigError::MissingField(
+                        "ntfy notifier needs a topic".to_string(),
+                    ));
+                }
+            }
+            crate::NotifierBackend::Telegram { bot_token, chat_id } => {
+                if bot_token.is_empty() || chat_id.is_empty() {
+                    return Err(ConfigError::MissingField(
+                        "telegram notifier needs bot_token and chat_id".to_string(),
+                    ));
+                }
+            }
+            crate::NotifierBackend::Discord { webhook_url } => {
+                Url::parse(webhook_url)
+                    .map_err(|_| ConfigError::InvalidUrl(webhook_url.clone()))?;
+            }
+            crate::NotifierBackend::Desktop => {}
+        }
+    }
+    Ok(())
+}
+
+/// Validate ranking weights
+fn validate_scoring(scoring: &crate::ScoringConfig) -> Result<(), ConfigError> {
+    if scoring.relevance_weight < 0.0 || scoring.social_weight < 0.0 {
+        return Err(ConfigError::InvalidConfig(
+            "scoring weights must not be negative".to_string(),
+        ));
+    }
+    if scoring.half_life_hours <= 0.0 || scoring.social_saturation == 0 || scoring.window_days == 0
+    {
+        return Err(ConfigError::InvalidConfig(
+            "scoring.half_life_hours, social_saturation, and window_days must be greater than 0"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
 /// Validate feed configuration
-fn validate_feed(feed_id: &str, feed: &crate::FeedConfig) -> Result<(), ConfigError> {
+pub(crate) fn validate_feed(feed_id: &str, feed: &crate::FeedConfig) -> Result<(), ConfigError> {
     // Validate URL
-    Url::parse(&feed.url)
-        .map_err(|_| ConfigError::InvalidUrl(feed.url.clone()))?;
+    Url::parse(&feed.url).map_err(|_| ConfigError::InvalidUrl(feed.url.clone()))?;
 
     // Validate name is not empty
     if feed.name.is_empty() {
-        return Err(ConfigError::MissingField(
-            format!("Feed '{}' must have a name", feed_id),
-        ));
+        return Err(ConfigError::MissingField(format!(
+            "Feed '{}' must have a name",
+            feed_id
+        )));
     }
 
     // Validate custom interval if provided
     if let Some(interval) = &feed.update_interval {
         if interval.is_empty() {
-            return Err(ConfigError::InvalidCron(
-                format!("Feed '{}' has empty update_interval", feed_id),
-            ));
+            return Err(ConfigError::InvalidCron(format!(
+                "Feed '{}' has empty update_interval",
+                feed_id
+            )));
         }
         validate_cron_expression(interval, &format!("feed '{}' update_interval", feed_id))?;
     }
 
+    if feed.priority.is_some_and(|priority| priority < 0.0) {
+        return Err(ConfigError::InvalidConfig(format!(
+            "Feed '{}' has a negative priority",
+            feed_id
+        )));
+    }
+
+    if let Some(language) = &feed.translate_to {
+        if language.len() != 2 || !language.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(ConfigError::InvalidConfig(format!(
+                "Feed '{}' has translate_to = '{}'; expected a two-letter language code like \"en\"",
+                feed_id, language
+            )));
+        }
+    }
+
+    if let Some(auth) = &feed.auth {
+        auth.validate(feed_id)?;
+    }
+
     Ok(())
 }
 
@@ -138,6 +264,32 @@ mod tests {
         assert!(validate_global(&global).is_err());
     }
 
+    #[test]
+    fn test_validate_retention() {
+        assert!(validate_retention(&RetentionConfig::default()).is_ok());
+        let zero = RetentionConfig {
+            max_entries_per_feed: Some(0),
+            ..Default::default()
+        };
+        assert!(validate_retention(&zero).is_err());
+        let bad_cron = RetentionConfig {
+            maintenance_schedule: Some("nightly".into()),
+            ..Default::default()
+        };
+        assert!(validate_retention(&bad_cron).is_err());
+    }
+
+    #[test]
+    fn test_validate_feed_translate_to() {
+        let mut feed = FeedConfig::new("https://example.de/feed.xml", "Example");
+        feed.translate_to = Some("en".into());
+        assert!(feed.validate().is_ok());
+        for language in ["english", "EN", "e"] {
+            feed.translate_to = Some(language.into());
+            assert!(feed.validate().is_err(), "{}", language);
+        }
+    }
+
     #[test]
     fn test_validate_cron_valid() {
         // cron crate uses 6-field format: sec min hour day month weekday
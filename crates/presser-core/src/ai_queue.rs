@@ -0,0 +1,270 @@
+//! The engine's one queue for AI requests
+//!
+//! Every summary and translation, from any number of feeds updating at
+//! once, takes a slot here before it calls the provider. When all slots are
+//! busy, requests wait in priority order rather than first come, first
+//! served: what a user is waiting on goes first, then entries with a higher
+//! score, then newer ones. A feed with a hundred new entries thus no longer
+//! holds up the handful another feed just published.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use presser_db::{Database, Entry};
+use tokio::sync::oneshot;
+
+/// Where a request goes in the queue; greater goes first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Priority {
+    /// Someone is waiting for the result (the TUI, `presser summarize`)
+    interactive: bool,
+
+    /// Entry score, if it has one
+    score: Option<f64>,
+
+    /// Publication time (or when first seen), in seconds
+    published: i64,
+}
+
+impl Priority {
+    /// Ahead of every background request
+    pub(crate) fn interactive() -> Self {
+        Self {
+            interactive: true,
+            score: None,
+            published: i64::MAX,
+        }
+    }
+
+    /// Background priority of an entry, by score and then age
+    pub(crate) fn of(entry: &Entry) -> Self {
+        Self {
+            interactive: false,
+            score: entry.score,
+            published: entry.published.unwrap_or(entry.created_at).timestamp(),
+        }
+    }
+
+    /// Priority of an entry that isn't stored (last of all)
+    pub(crate) fn lowest() -> Self {
+        Self {
+            interactive: false,
+            score: None,
+            published: i64::MIN,
+        }
+    }
+}
+
+impl Eq for Priority {}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let score = |p: &Self| p.score.unwrap_or(f64::NEG_INFINITY);
+        self.interactive
+            .cmp(&other.interactive)
+            .then_with(|| score(self).total_cmp(&score(other)))
+            .then_with(|| self.published.cmp(&other.published))
+    }
+}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Background priorities of stored entries, by ID
+pub(crate) async fn entry_priorities(
+    db: &Database,
+    ids: &[String],
+) -> Result<HashMap<String, Priority>> {
+    Ok(db
+        .get_entries_by_ids(ids)
+        .await?
+        .iter()
+        .map(|entry| (entry.id.clone(), Priority::of(entry)))
+        .collect())
+}
+
+/// A request waiting for a slot
+struct Waiter {
+    priority: Priority,
+    /// Arrival order, so equal priorities are served first come, first served
+    seq: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    waiting: BinaryHeap<Waiter>,
+    next_seq: u64,
+}
+
+/// A fixed number of slots for AI requests, handed out by priority
+pub(crate) struct SlotQueue {
+    slots: usize,
+    state: Mutex<State>,
+}
+
+impl SlotQueue {
+    /// Queue with `slots` requests in flight at most (at least one)
+    pub(crate) fn new(slots: usize) -> Self {
+        Self {
+            slots: slots.max(1),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Wait for a slot, behind every waiting request of higher priority
+    pub(crate) async fn acquire(&self, priority: Priority) -> Slot<'_> {
+        let wake = {
+            let mut state = self.state.lock().expect("slot queue poisoned");
+            if state.running < self.slots && state.waiting.is_empty() {
+                state.running += 1;
+                return Slot { queue: self };
+            }
+            let (wake, woken) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter {
+                priority,
+                seq,
+                wake,
+            });
+            woken
+        };
+        let mut waiting = Waiting {
+            queue: self,
+            woken: Some(wake),
+        };
+        if let Some(woken) = waiting.woken.as_mut() {
+            // The sender lives in the queue until it hands over a slot
+            let _ = woken.await;
+        }
+        waiting.woken = None;
+        Slot { queue: self }
+    }
+
+    /// Hand a finished request's slot to the first waiter still waiting
+    fn release(&self) {
+        let mut state = self.state.lock().expect("slot queue poisoned");
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        state.running -= 1;
+    }
+}
+
+/// A slot in the queue, given back when dropped
+pub(crate) struct Slot<'a> {
+    queue: &'a SlotQueue,
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+/// A request in line; if it is abandoned after being handed a slot, the
+/// slot is passed on
+struct Waiting<'a> {
+    queue: &'a SlotQueue,
+    woken: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(mut woken) = self.woken.take() {
+            woken.close();
+            if woken.try_recv().is_ok() {
+                self.queue.release();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_slots_by_priority() {
+        let queue = Arc::new(SlotQueue::new(1));
+        let first = queue.acquire(Priority::lowest()).await;
+
+        let background = |score: Option<f64>, published: i64| Priority {
+            interactive: false,
+            score,
+            published,
+        };
+        let (done, mut order) = tokio::sync::mpsc::unbounded_channel();
+        let requests = [
+            ("old", background(None, 100)),
+            ("new", background(None, 200)),
+            ("scored", background(Some(0.9), 50)),
+            ("user", Priority::interactive()),
+            ("new again", background(None, 200)),
+        ];
+        let count = requests.len();
+        for (queued, (name, priority)) in requests.into_iter().enumerate() {
+            let waiting = Arc::clone(&queue);
+            let done = done.clone();
+            tokio::spawn(async move {
+                let _slot = waiting.acquire(priority).await;
+                done.send(name).unwrap();
+            });
+            // Queued in the order listed
+            while queue.state.lock().unwrap().waiting.len() <= queued {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        // An abandoned request doesn't keep its place
+        let abandoned = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            queue.acquire(Priority::interactive()),
+        )
+        .await;
+        assert!(abandoned.is_err());
+
+        drop(first);
+        let mut served = Vec::new();
+        for _ in 0..count {
+            served.push(order.recv().await.unwrap());
+        }
+        assert_eq!(served, ["user", "scored", "new", "new again", "old"]);
+        while queue.state.lock().unwrap().running > 0 {
+            tokio::task::yield_now().await;
+        }
+        assert!(queue.state.lock().unwrap().waiting.is_empty());
+    }
+}
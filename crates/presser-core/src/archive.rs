@@ -0,0 +1,91 @@
+//! Page archive (`[archive]`): the HTML of entries' pages, kept so articles
+//! stay readable after their site goes away or puts them behind a paywall
+//!
+//! Pages are gzip-compressed and stored under the SHA-256 of their HTML
+//! (`ab/ab12….html.gz`), so a page served again unchanged, or shared by
+//! several entries, is written once. The database maps entries to hashes.
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+
+/// Directory of compressed pages named by hash
+#[derive(Debug, Clone)]
+pub struct PageArchive {
+    dir: PathBuf,
+}
+
+impl PageArchive {
+    /// Archive stored in `dir` (created on the first write)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// File holding the page with `hash`
+    fn path(&self, hash: &str) -> PathBuf {
+        let prefix = hash.get(..2).unwrap_or(hash);
+        self.dir.join(prefix).join(format!("{}.html.gz", hash))
+    }
+
+    /// Store `html`, returning its hash; a page already stored is not
+    /// written again
+    pub fn put(&self, html: &[u8]) -> Result<String> {
+        let hash = format!("{:x}", Sha256::digest(html));
+        let path = self.path(&hash);
+        if path.exists() {
+            return Ok(hash);
+        }
+        let parent = path.parent().unwrap_or(&self.dir);
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(html)?;
+        let compressed = encoder.finish()?;
+        // Write beside the final name first, so a crash never leaves half a page
+        let partial = path.with_extension("gz.partial");
+        std::fs::write(&partial, compressed)
+            .with_context(|| format!("Failed to write {}", partial.display()))?;
+        std::fs::rename(&partial, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(hash)
+    }
+
+    /// The HTML stored under `hash`
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        let path = self.path(hash);
+        let file = std::fs::File::open(&path)
+            .with_context(|| format!("Archived page missing: {}", path.display()))?;
+        let mut html = Vec::new();
+        GzDecoder::new(file)
+            .read_to_end(&mut html)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_and_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = PageArchive::new(dir.path().join("archive"));
+        let html = b"<html><body><p>Still here</p></body></html>";
+
+        let hash = archive.put(html).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert!(archive
+            .path(&hash)
+            .starts_with(dir.path().join("archive").join(&hash[..2])));
+        assert_eq!(archive.get(&hash).unwrap(), html);
+        // The same page again is the same file
+        assert_eq!(archive.put(html).unwrap(), hash);
+        assert!(archive.get("0000").is_err());
+    }
+}
@@ -0,0 +1,410 @@
+//! Questions answered from stored entries (`presser ask`)
+//!
+//! The question's keywords are searched in full text, the best hits are
+//! joined by the entries nearest to them by embedding, and the summaries of
+//! what was found go to the AI as numbered sources for it to answer from
+//! and cite as `[n]`.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use presser_db::{Database, Entry, EntryFilter, SearchOptions};
+
+/// Full-text hits, best first, whose nearest entries by embedding are added
+const EXPANDED_HITS: usize = 3;
+
+/// Nearest entries looked up for each of those hits
+const NEIGHBOURS: usize = 3;
+
+/// Characters of each source's text given to the AI
+const SOURCE_CHARS: usize = 1200;
+
+/// Words that say nothing about what a question is about
+const STOPWORDS: &[&str] = &[
+    "a",
+    "about",
+    "after",
+    "all",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "been",
+    "by",
+    "can",
+    "could",
+    "did",
+    "do",
+    "does",
+    "for",
+    "from",
+    "go",
+    "going",
+    "had",
+    "happen",
+    "happened",
+    "happening",
+    "has",
+    "have",
+    "how",
+    "i",
+    "in",
+    "is",
+    "it",
+    "its",
+    "last",
+    "latest",
+    "me",
+    "month",
+    "my",
+    "new",
+    "news",
+    "of",
+    "on",
+    "or",
+    "past",
+    "say",
+    "said",
+    "should",
+    "so",
+    "tell",
+    "that",
+    "the",
+    "their",
+    "there",
+    "these",
+    "this",
+    "to",
+    "today",
+    "up",
+    "was",
+    "we",
+    "week",
+    "were",
+    "what",
+    "whats",
+    "when",
+    "where",
+    "which",
+    "who",
+    "why",
+    "will",
+    "with",
+    "would",
+    "year",
+    "yesterday",
+    "you",
+];
+
+/// What `presser ask` searches
+#[derive(Debug, Clone)]
+pub struct AskOptions {
+    /// Most entries given to the AI
+    pub limit: usize,
+    /// Only entries published since then (default: the period the question
+    /// names, see [`period`])
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries of this feed
+    pub feed: Option<String>,
+}
+
+impl Default for AskOptions {
+    fn default() -> Self {
+        Self {
+            limit: 12,
+            since: None,
+            feed: None,
+        }
+    }
+}
+
+/// An entry given to the AI as a numbered source
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub entry: Entry,
+    pub feed_title: String,
+    /// Its AI summary, or else its text, shortened
+    pub text: String,
+}
+
+/// The AI's answer and the sources it was given, numbered from 1
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub text: String,
+    pub sources: Vec<Source>,
+    pub model: String,
+    pub tokens: u64,
+}
+
+impl Answer {
+    /// Sources the answer cites, with their numbers, in number order
+    pub fn cited(&self) -> Vec<(usize, &Source)> {
+        let numbers = citations(&self.text);
+        self.sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| (i + 1, source))
+            .filter(|(n, _)| numbers.contains(n))
+            .collect()
+    }
+}
+
+/// Numbers cited as `[n]` (or `[n, m]`) in `text`
+fn citations(text: &str) -> HashSet<usize> {
+    let mut numbers = HashSet::new();
+    for part in text.split('[').skip(1) {
+        let Some((inside, _)) = part.split_once(']') else {
+            continue;
+        };
+        numbers.extend(
+            inside
+                .split(',')
+                .filter_map(|n| n.trim().parse::<usize>().ok()),
+        );
+    }
+    numbers
+}
+
+/// Full-text query for a question: its keywords, any of which may match
+///
+/// Returns None if the question has no keywords.
+pub fn keywords(question: &str) -> Option<String> {
+    let mut seen = HashSet::new();
+    let words: Vec<String> = question
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.chars().count() > 1 && !STOPWORDS.contains(&word.as_str()))
+        .filter(|word| seen.insert(word.clone()))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" OR "))
+}
+
+/// Start of the period a question names ("today", "this week", "last
+/// month", ...), if any
+pub fn period(question: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let question = question.to_lowercase();
+    let midnight = |date: chrono::NaiveDate| date.and_time(NaiveTime::MIN).and_utc();
+    let today = now.date_naive();
+    let periods: [(&str, DateTime<Utc>); 9] = [
+        ("yesterday", midnight(today - Duration::days(1))),
+        ("today", midnight(today)),
+        (
+            "this week",
+            midnight(today - Duration::days(i64::from(today.weekday().num_days_from_monday()))),
+        ),
+        ("last week", now - Duration::days(7)),
+        ("past week", now - Duration::days(7)),
+        ("this month", midnight(today.with_day(1)?)),
+        ("last month", now - Duration::days(30)),
+        ("past month", now - Duration::days(30)),
+        ("this year", midnight(today.with_ordinal(1)?)),
+    ];
+    periods
+        .into_iter()
+        .find(|(phrase, _)| question.contains(phrase))
+        .map(|(_, since)| since)
+}
+
+/// Entries relevant to `question`: full-text hits, each of the best few
+/// followed by its nearest entries by embedding, at most `options.limit`
+pub async fn retrieve(db: &Database, question: &str, options: &AskOptions) -> Result<Vec<Entry>> {
+    let Some(query) = keywords(question) else {
+        return Ok(Vec::new());
+    };
+    let since = options.since.or_else(|| period(question, Utc::now()));
+    let mut filter = EntryFilter::new();
+    if let Some(since) = since {
+        filter = filter.since(since);
+    }
+    if let Some(feed) = &options.feed {
+        filter = filter.feed(feed.clone());
+    }
+    let search = SearchOptions::new(query)
+        .filter(filter)
+        .limit(options.limit as i64);
+    let hits = db.search(&search).await?;
+
+    let mut seen: HashSet<String> = hits.iter().map(|hit| hit.entry.id.clone()).collect();
+    let mut entries = Vec::new();
+    for (i, hit) in hits.into_iter().enumerate() {
+        let id = hit.entry.id.clone();
+        entries.push(hit.entry);
+        if i >= EXPANDED_HITS {
+            continue;
+        }
+        for near in db
+            .similar_entries(&id, NEIGHBOURS)
+            .await?
+            .unwrap_or_default()
+        {
+            if !seen.insert(near.entry_id.clone()) {
+                continue;
+            }
+            let Some(entry) = db.get_entry(&near.entry_id).await? else {
+                continue;
+            };
+            let published = entry.published.unwrap_or(entry.created_at);
+            if entry.hidden
+                || since.is_some_and(|since| published < since)
+                || options
+                    .feed
+                    .as_ref()
+                    .is_some_and(|feed| *feed != entry.feed_id)
+            {
+                continue;
+            }
+            entries.push(entry);
+        }
+    }
+    entries.truncate(options.limit);
+    Ok(entries)
+}
+
+/// Sources for `entries`: each with its feed's title and its AI summary,
+/// extracted text, or feed description
+pub async fn sources(db: &Database, entries: Vec<Entry>) -> Result<Vec<Source>> {
+    let mut feed_titles: HashMap<String, String> = HashMap::new();
+    let mut sources = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if !feed_titles.contains_key(&entry.feed_id) {
+            let title = db
+                .get_feed(&entry.feed_id)
+                .await?
+                .map_or_else(|| entry.feed_id.clone(), |feed| feed.title);
+            feed_titles.insert(entry.feed_id.clone(), title);
+        }
+        let summary = db.get_summary(&entry.id).await?.map(|s| s.summary_text);
+        let text = summary
+            .or_else(|| entry.content_text.clone())
+            .or_else(|| entry.summary.clone())
+            .unwrap_or_default();
+        sources.push(Source {
+            feed_title: feed_titles[&entry.feed_id].clone(),
+            text: shorten(text.trim(), SOURCE_CHARS),
+            entry,
+        });
+    }
+    Ok(sources)
+}
+
+/// The question followed by its numbered sources, as sent to the AI
+pub fn prompt(question: &str, sources: &[Source]) -> String {
+    let mut prompt = format!("Question: {}\n\nSources:\n", question.trim());
+    for (i, source) in sources.iter().enumerate() {
+        let published = source.entry.published.unwrap_or(source.entry.created_at);
+        let _ = write!(
+            prompt,
+            "\n[{}] {} ({}, {})\n{}\n",
+            i + 1,
+            source.entry.title,
+            source.feed_title,
+            published.format("%Y-%m-%d"),
+            source.text
+        );
+    }
+    prompt
+}
+
+fn shorten(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_db::fixtures;
+
+    #[test]
+    fn test_keywords() {
+        assert_eq!(
+            keywords("What happened with the EU AI act this month?").as_deref(),
+            Some("eu OR ai OR act")
+        );
+        assert_eq!(keywords("Rust, rust and RUST").as_deref(), Some("rust"));
+        assert_eq!(keywords("what is this?"), None);
+    }
+
+    #[test]
+    fn test_period() {
+        let now: DateTime<Utc> = "2024-05-16T15:00:00Z".parse().unwrap();
+        let at = |s: &str| Some(s.parse::<DateTime<Utc>>().unwrap());
+        assert_eq!(
+            period("EU AI act this month?", now),
+            at("2024-05-01T00:00:00Z")
+        );
+        assert_eq!(
+            period("Anything new today", now),
+            at("2024-05-16T00:00:00Z")
+        );
+        // A Thursday
+        assert_eq!(period("This week in Rust", now), at("2024-05-13T00:00:00Z"));
+        assert_eq!(period("the past week", now), Some(now - Duration::days(7)));
+        assert_eq!(period("EU AI act", now), None);
+    }
+
+    #[test]
+    fn test_citations() {
+        let answer = Answer {
+            text: "Results are in [2]. Turnout was high [1, 2]; see [x] and [7].".into(),
+            sources: vec![
+                Source {
+                    entry: Entry::default(),
+                    feed_title: "A".into(),
+                    text: String::new(),
+                },
+                Source {
+                    entry: Entry::default(),
+                    feed_title: "B".into(),
+                    text: String::new(),
+                },
+                Source {
+                    entry: Entry::default(),
+                    feed_title: "C".into(),
+                    text: String::new(),
+                },
+            ],
+            model: "gpt-4".into(),
+            tokens: 0,
+        };
+        let cited: Vec<(usize, &str)> = answer
+            .cited()
+            .into_iter()
+            .map(|(n, source)| (n, source.feed_title.as_str()))
+            .collect();
+        assert_eq!(cited, [(1, "A"), (2, "B")]);
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_and_prompt() {
+        let (db, _) = fixtures::seeded().await.unwrap();
+        let entries = retrieve(&db, "How did the election go?", &AskOptions::default())
+            .await
+            .unwrap();
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["news-election"]);
+
+        // Entries published before the window are left out
+        let options = AskOptions {
+            since: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(retrieve(&db, "election", &options)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let sources = sources(&db, entries).await.unwrap();
+        assert_eq!(sources[0].feed_title, "Example News");
+        let prompt = prompt(" How did the election go? ", &sources);
+        assert!(prompt.starts_with("Question: How did the election go?\n\nSources:\n\n[1] Election results are in (Example News, "));
+        assert!(prompt.ends_with(&format!("\n{}\n", sources[0].text)));
+    }
+}
@@ -0,0 +1,160 @@
+//! Feed audits (`presser feeds audit`): feeds that look dead, because they
+//! stopped publishing or their URL has been gone for several fetches in a
+//! row, with the feeds their sites advertise now as possible replacements
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use presser_db::{Feed, FeedHealth};
+
+/// Failed fetches in a row, the last answered 404 or 410, before a feed
+/// counts as gone rather than briefly broken
+pub const GONE_AFTER_FAILURES: i64 = 3;
+
+/// Why a feed looks dead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeadReason {
+    /// The URL answers 404 Not Found or 410 Gone
+    Gone { status: i64, failures: i64 },
+
+    /// Nothing published since `since` (never, when None)
+    Silent { since: Option<DateTime<Utc>> },
+}
+
+impl fmt::Display for DeadReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gone { status, failures } => {
+                write!(f, "HTTP {} on the last {} fetches", status, failures)
+            }
+            Self::Silent { since: Some(since) } => {
+                write!(f, "nothing published since {}", since.format("%Y-%m-%d"))
+            }
+            Self::Silent { since: None } => write!(f, "nothing ever published"),
+        }
+    }
+}
+
+/// A feed that looks dead
+#[derive(Debug, Clone)]
+pub struct FeedAudit {
+    /// Feed audited
+    pub feed: Feed,
+
+    /// Why it looks dead
+    pub reason: DeadReason,
+
+    /// Feeds its site advertises that aren't subscribed yet
+    pub replacements: Vec<String>,
+}
+
+/// Why `feed` looks dead, if it does, given that live feeds publish after
+/// `cutoff`
+pub fn assess(feed: &Feed, health: &FeedHealth, cutoff: DateTime<Utc>) -> Option<DeadReason> {
+    if let Some(status @ (404 | 410)) = health.last_status {
+        if health.consecutive_failures >= GONE_AFTER_FAILURES {
+            return Some(DeadReason::Gone {
+                status,
+                failures: health.consecutive_failures,
+            });
+        }
+    }
+    match health.last_active() {
+        Some(since) if since < cutoff => Some(DeadReason::Silent { since: Some(since) }),
+        // Only once the feed has had time to publish
+        None if feed.created_at < cutoff => Some(DeadReason::Silent { since: None }),
+        _ => None,
+    }
+}
+
+/// Page to look for a feed's replacements on: its site, or the root of the
+/// feed's own host
+pub fn discovery_page(feed: &Feed) -> Option<String> {
+    if let Some(site) = feed.site_url.as_deref().filter(|s| !s.is_empty()) {
+        return Some(site.to_string());
+    }
+    let url = url::Url::parse(&feed.url).ok().filter(|u| u.has_host())?;
+    Some(url.join("/").ok()?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_assess() {
+        let now = Utc::now();
+        let cutoff = now - Duration::days(180);
+        let feed = Feed {
+            id: "blog".into(),
+            url: "https://blog.example.com/feed.xml".into(),
+            created_at: now - Duration::days(400),
+            ..Default::default()
+        };
+        let healthy = FeedHealth {
+            feed_id: "blog".into(),
+            last_status: Some(200),
+            consecutive_failures: 0,
+            last_published: Some(now - Duration::days(10)),
+            last_stored: Some(now - Duration::days(300)),
+        };
+        assert_eq!(assess(&feed, &healthy, cutoff), None);
+
+        let silent = FeedHealth {
+            last_published: Some(now - Duration::days(200)),
+            ..healthy.clone()
+        };
+        assert!(matches!(
+            assess(&feed, &silent, cutoff),
+            Some(DeadReason::Silent { since: Some(_) })
+        ));
+        let never = FeedHealth {
+            last_published: None,
+            last_stored: None,
+            ..healthy.clone()
+        };
+        assert_eq!(
+            assess(&feed, &never, cutoff),
+            Some(DeadReason::Silent { since: None })
+        );
+        // Too new to have published anything
+        let added = Feed {
+            created_at: now,
+            ..feed.clone()
+        };
+        assert_eq!(assess(&added, &never, cutoff), None);
+
+        // Gone only once the 404s persist
+        let missing = FeedHealth {
+            last_status: Some(404),
+            consecutive_failures: 2,
+            ..healthy.clone()
+        };
+        assert_eq!(assess(&feed, &missing, cutoff), None);
+        let gone = FeedHealth {
+            consecutive_failures: 3,
+            ..missing
+        };
+        assert_eq!(
+            assess(&feed, &gone, cutoff),
+            Some(DeadReason::Gone {
+                status: 404,
+                failures: 3
+            })
+        );
+
+        assert_eq!(
+            discovery_page(&feed).as_deref(),
+            Some("https://blog.example.com/")
+        );
+        let with_site = Feed {
+            site_url: Some("https://example.com/blog".into()),
+            ..feed
+        };
+        assert_eq!(
+            discovery_page(&with_site).as_deref(),
+            Some("https://example.com/blog")
+        );
+    }
+}
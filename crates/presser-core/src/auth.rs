@@ -0,0 +1,348 @@
+//! Tokens of feeds behind an API (`[feed.auth]`)
+//!
+//! The token sent with a feed's requests is the last one obtained for it
+//! (kept in the database, so it outlives restarts), else the configured
+//! `token`. A feed with neither gets one before its first fetch, and one
+//! whose obtained token has expired gets a new one. When the feed answers
+//! 401, the engine asks for a new token and retries once.
+//!
+//! New tokens come from `token_command`, which prints one, or from
+//! `token_url` with a refresh token (OAuth 2 `refresh_token` grant), the
+//! endpoint's rotated refresh token replacing the configured one. Refreshes
+//! run one at a time, so a rotated refresh token is never spent twice.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use presser_config::FeedAuth;
+use presser_db::{Database, FeedToken};
+use presser_feeds::FeedError;
+use serde::Deserialize;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+/// Seconds a `token_command` may run before it is killed
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tokens this close to expiring (in seconds) are refreshed before use
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// Whether a fetch failed because the feed turned its credentials down
+pub fn is_unauthorized(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<FeedError>(),
+        Some(FeedError::HttpStatus { status: 401, .. })
+    )
+}
+
+/// A token endpoint's answer
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+/// Obtains and keeps the tokens of feeds with `[feed.auth]`
+pub struct FeedTokens {
+    db: Database,
+    client: reqwest::Client,
+    /// Held while a token is being obtained
+    refreshing: Mutex<()>,
+}
+
+impl FeedTokens {
+    /// Tokens kept in `db`, obtained from token endpoints with `client`
+    pub fn new(db: Database, client: reqwest::Client) -> Self {
+        Self {
+            db,
+            client,
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Token to send with the feed's requests, obtained first if it has
+    /// none yet or its obtained one expired
+    pub async fn token(&self, feed_id: &str, auth: &FeedAuth) -> Result<String> {
+        match (self.db.get_feed_token(feed_id).await?, &auth.token) {
+            (Some(stored), _) if !expired(&stored) || !auth.refreshes() => {
+                return Ok(stored.access_token)
+            }
+            (None, Some(token)) => return Ok(token.clone()),
+            _ => {}
+        }
+        let _refreshing = self.refreshing.lock().await;
+        // Another fetch of the feed may have obtained one in the meantime
+        let stored = self.db.get_feed_token(feed_id).await?;
+        match stored {
+            Some(stored) if !expired(&stored) => Ok(stored.access_token),
+            stored => self.obtain(feed_id, auth, stored).await,
+        }
+    }
+
+    /// A new token for the feed, which turned `rejected` down
+    pub async fn refresh(&self, feed_id: &str, auth: &FeedAuth, rejected: &str) -> Result<String> {
+        let _refreshing = self.refreshing.lock().await;
+        let stored = self.db.get_feed_token(feed_id).await?;
+        match stored {
+            // Refreshed by another fetch of the feed while this one waited
+            Some(stored) if stored.access_token != rejected && !expired(&stored) => {
+                Ok(stored.access_token)
+            }
+            stored => self.obtain(feed_id, auth, stored).await,
+        }
+    }
+
+    /// Obtain and store a new token; `stored` is the feed's current one
+    async fn obtain(
+        &self,
+        feed_id: &str,
+        auth: &FeedAuth,
+        stored: Option<FeedToken>,
+    ) -> Result<String> {
+        let token = if let Some(command) = &auth.token_command {
+            FeedToken {
+                feed_id: feed_id.to_string(),
+                access_token: run_token_command(command, feed_id).await?,
+                refresh_token: None,
+                expires_at: None,
+                refreshed_at: Utc::now(),
+            }
+        } else if let Some(token_url) = &auth.token_url {
+            let rotated = stored.and_then(|t| t.refresh_token);
+            let configured = auth.refresh_token.as_deref();
+            match rotated.as_deref() {
+                // A configured refresh token replacing a revoked one is tried too
+                Some(rotated) if Some(rotated) != configured => {
+                    match self.exchange(feed_id, auth, token_url, rotated).await {
+                        Err(e) if configured.is_some() => {
+                            tracing::warn!("Stored refresh token of {} failed ({:#}), trying the configured one", feed_id, e);
+                            self.exchange(feed_id, auth, token_url, configured.unwrap_or_default())
+                                .await?
+                        }
+                        result => result?,
+                    }
+                }
+                _ => {
+                    let refresh_token = configured.with_context(|| {
+                        format!("Feed {} has a token_url but no refresh_token", feed_id)
+                    })?;
+                    self.exchange(feed_id, auth, token_url, refresh_token)
+                        .await?
+                }
+            }
+        } else {
+            anyhow::bail!(
+                "Feed {} has no token_command or token_url to get a new token with",
+                feed_id
+            );
+        };
+        self.db.set_feed_token(&token).await?;
+        tracing::info!("Got a new token for {}", feed_id);
+        Ok(token.access_token)
+    }
+
+    /// Exchange `refresh_token` for a new token at `token_url`
+    async fn exchange(
+        &self,
+        feed_id: &str,
+        auth: &FeedAuth,
+        token_url: &str,
+        refresh_token: &str,
+    ) -> Result<FeedToken> {
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+        if let Some(client_id) = &auth.client_id {
+            form.push(("client_id", client_id));
+        }
+        if let Some(client_secret) = &auth.client_secret {
+            form.push(("client_secret", client_secret));
+        }
+        let response: TokenResponse = self
+            .client
+            .post(token_url)
+            .form(&form)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Token refresh at {} failed", token_url))?
+            .json()
+            .await
+            .with_context(|| format!("Unexpected token response from {}", token_url))?;
+        let now = Utc::now();
+        Ok(FeedToken {
+            feed_id: feed_id.to_string(),
+            access_token: response.access_token,
+            // Endpoints that don't rotate refresh tokens leave them out
+            refresh_token: Some(
+                response
+                    .refresh_token
+                    .unwrap_or_else(|| refresh_token.to_string()),
+            ),
+            expires_at: response
+                .expires_in
+                .map(|secs| now + chrono::Duration::seconds(secs)),
+            refreshed_at: now,
+        })
+    }
+}
+
+/// Whether `token` has run out, or is about to
+fn expired(token: &FeedToken) -> bool {
+    token
+        .expires_at
+        .is_some_and(|at| at <= Utc::now() + chrono::Duration::seconds(EXPIRY_MARGIN_SECS))
+}
+
+/// Run `command` with `sh -c`, returning the token it prints
+async fn run_token_command(command: &str, feed_id: &str) -> Result<String> {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("PRESSER_FEED_ID", feed_id)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run token_command `{}`", command))?;
+    let output = tokio::time::timeout(COMMAND_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "token_command `{}` timed out after {:?}",
+                command,
+                COMMAND_TIMEOUT
+            )
+        })?
+        .with_context(|| format!("Failed to wait for token_command `{}`", command))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "token_command `{}` exited with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let token = String::from_utf8(output.stdout).context("token_command printed invalid UTF-8")?;
+    let token = token.trim();
+    if token.is_empty() {
+        anyhow::bail!("token_command `{}` printed no token", command);
+    }
+    Ok(token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_db::fixtures::seeded;
+
+    fn auth() -> FeedAuth {
+        toml::from_str("").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_token_command() {
+        let (db, _) = seeded().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let count = dir.path().join("count");
+        let auth = FeedAuth {
+            token_command: Some(format!(
+                "echo x >> {0}; echo \"$PRESSER_FEED_ID-$(wc -l < {0} | tr -d ' ')\"",
+                count.display()
+            )),
+            ..auth()
+        };
+        let tokens = FeedTokens::new(db.clone(), reqwest::Client::new());
+        // Obtained before the first fetch, then kept
+        assert_eq!(tokens.token("news", &auth).await.unwrap(), "news-1");
+        assert_eq!(tokens.token("news", &auth).await.unwrap(), "news-1");
+        assert_eq!(
+            tokens.refresh("news", &auth, "news-1").await.unwrap(),
+            "news-2"
+        );
+        // Already replaced: not refreshed again
+        assert_eq!(
+            tokens.refresh("news", &auth, "news-1").await.unwrap(),
+            "news-2"
+        );
+        let restarted = FeedTokens::new(db, reqwest::Client::new());
+        assert_eq!(restarted.token("news", &auth).await.unwrap(), "news-2");
+
+        let failing = FeedAuth {
+            token_command: Some("echo denied >&2; exit 1".into()),
+            ..auth
+        };
+        let error = tokens
+            .refresh("news", &failing, "news-2")
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("denied"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mut refreshes = Vec::new();
+        for (refresh_token, body) in [
+            (
+                "r1",
+                r#"{"access_token":"a2","refresh_token":"r2","expires_in":3600}"#,
+            ),
+            ("r2", r#"{"access_token":"a3","expires_in":0}"#),
+        ] {
+            let form = format!(
+                "grant_type=refresh_token&refresh_token={}&client_id=presser",
+                refresh_token
+            );
+            let mock = server
+                .mock("POST", "/oauth/token")
+                .match_body(mockito::Matcher::Regex(regex::escape(&form)))
+                .with_header("content-type", "application/json")
+                .with_body(body)
+                .create_async()
+                .await;
+            refreshes.push(mock);
+        }
+        let token_url = format!("{}/oauth/token", server.url());
+
+        let (db, _) = seeded().await.unwrap();
+        let auth = FeedAuth {
+            token: Some("a1".into()),
+            token_url: Some(token_url),
+            refresh_token: Some("r1".into()),
+            client_id: Some("presser".into()),
+            ..auth()
+        };
+        let tokens = FeedTokens::new(db.clone(), reqwest::Client::new());
+        assert_eq!(tokens.token("news", &auth).await.unwrap(), "a1");
+        assert_eq!(tokens.refresh("news", &auth, "a1").await.unwrap(), "a2");
+        assert_eq!(tokens.token("news", &auth).await.unwrap(), "a2");
+        // The rotated refresh token is used next, and kept when not rotated
+        assert_eq!(tokens.refresh("news", &auth, "a2").await.unwrap(), "a3");
+        let stored = db.get_feed_token("news").await.unwrap().unwrap();
+        assert_eq!(stored.refresh_token.as_deref(), Some("r2"));
+        assert!(expired(&stored));
+
+        for refresh in refreshes {
+            refresh.assert_async().await;
+        }
+    }
+
+    #[test]
+    fn test_is_unauthorized() {
+        let status = |status| -> anyhow::Error {
+            FeedError::HttpStatus {
+                url: "https://forum.example.com/latest.rss".into(),
+                status,
+            }
+            .into()
+        };
+        assert!(is_unauthorized(&status(401)));
+        assert!(!is_unauthorized(&status(403)));
+    }
+}
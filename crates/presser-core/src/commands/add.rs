@@ -0,0 +1,225 @@
+//! Adding feeds (`presser add`)
+//!
+//! A feed is recorded twice: as a `feeds/<id>.toml` file holding the user's
+//! settings, and as a database row holding fetch state.
+
+use std::collections::HashSet;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use presser_config::{Config, FeedConfig};
+use presser_db::Feed;
+use presser_feeds::{FeedMetadata, FeedProbe};
+
+use crate::reload::ConfigDiff;
+
+/// Options for `presser add`
+#[derive(Debug, Default)]
+pub struct AddOptions {
+    /// Title to use instead of the feed's own
+    pub name: Option<String>,
+    /// Tags written to the feed config
+    pub tags: Vec<String>,
+    /// Update schedule (cron expression) overriding the global one
+    pub interval: Option<String>,
+    /// Turn off AI summaries for the feed
+    pub no_ai: bool,
+    /// Fetch the feed right after adding it
+    pub fetch_now: bool,
+}
+
+/// Lowercase, dash-separated identifier derived from a title
+pub(super) fn slugify(s: &str) -> String {
+    s.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// `slugify(title)`, suffixed with `-2`, `-3`, ... if that ID is already taken
+pub(super) fn unique_feed_id(title: &str, taken: &HashSet<String>) -> String {
+    let base = match slugify(title) {
+        slug if slug.is_empty() => "feed".to_string(),
+        slug => slug,
+    };
+    (1..)
+        .map(|n| match n {
+            1 => base.clone(),
+            n => format!("{}-{}", base, n),
+        })
+        .find(|id| !taken.contains(id))
+        .expect("unbounded range")
+}
+
+/// Probe `url` (following autodiscovery from an HTML page), then write the feed config and database row
+pub async fn add_feed(engine: &crate::Engine, url: &str, options: AddOptions) -> Result<()> {
+    let (url, probe) = resolve_feed(engine, url).await?;
+    let Some(metadata) = probe.metadata.clone() else {
+        anyhow::bail!("Not a usable feed:\n{}", probe);
+    };
+    if let Some(target) = &probe.redirect_target {
+        println!("Note: {} redirects to {}", url, target);
+    }
+    ensure_new(engine, &url).await?;
+
+    let title = match options.name {
+        Some(name) => name,
+        None => confirm_title(&metadata.title)?,
+    };
+    let mut feed_config = FeedConfig::new(&url, &title);
+    feed_config.tags = options.tags;
+    feed_config.update_interval = options.interval;
+    feed_config.enable_ai = !options.no_ai;
+    let (id, path) = create_feed(engine, &feed_config, &metadata).await?;
+    println!("Added feed: {} ({})", title, id);
+    println!("  Config: {}", path.display());
+
+    if options.fetch_now {
+        let engine = reload_after_add(engine).await?;
+        let report = engine.update_feed(&id).await?;
+        println!(
+            "Fetched {} new entries ({} summarized)",
+            report.new, report.summarized
+        );
+        for error in &report.errors {
+            println!("  warning: {}", error);
+        }
+    } else {
+        println!("Fetch it now with 'presser update {}'", id);
+    }
+    Ok(())
+}
+
+/// Fail if a feed with `url` is already added, else return every feed
+async fn ensure_new(engine: &crate::Engine, url: &str) -> Result<Vec<Feed>> {
+    let feeds = engine.database().get_all_feeds().await?;
+    if let Some(existing) = feeds.iter().find(|f| f.url == url) {
+        anyhow::bail!("{} is already added as '{}'", url, existing.id);
+    }
+    if engine.config().feeds.contains_key(url) {
+        anyhow::bail!(
+            "{} is already configured in {}",
+            url,
+            Config::feeds_dir()?.display()
+        );
+    }
+    Ok(feeds)
+}
+
+/// Write the config file and database row of a new feed, returning its ID
+/// and config file
+pub(crate) async fn create_feed(
+    engine: &crate::Engine,
+    feed_config: &FeedConfig,
+    metadata: &FeedMetadata,
+) -> Result<(String, PathBuf)> {
+    let url = &feed_config.url;
+    let db = engine.database();
+    let feeds = ensure_new(engine, url).await?;
+    feed_config.validate()?;
+
+    let config_dir = Config::config_dir()?;
+    let mut taken: HashSet<String> = feeds.into_iter().map(|f| f.id).collect();
+    taken.extend(existing_file_stems(&config_dir.join("feeds"))?);
+    let id = unique_feed_id(&feed_config.name, &taken);
+    let path = Config::add_feed(&config_dir, &id, feed_config)?;
+
+    let stored = db
+        .upsert_feed(&Feed {
+            id: id.clone(),
+            url: url.clone(),
+            title: feed_config.name.clone(),
+            description: metadata.description.clone(),
+            site_url: metadata.site_url.clone(),
+            ..Default::default()
+        })
+        .await;
+    if let Err(e) = stored {
+        // Without its row the feed would only be half added
+        let _ = std::fs::remove_file(&path);
+        return Err(e.into());
+    }
+    Ok((id, path))
+}
+
+/// `engine` reconfigured with the feeds just added, so updates see their
+/// settings (e.g. `--no-ai`); the database and AI client are kept
+pub(crate) async fn reload_after_add(engine: &crate::Engine) -> Result<crate::Engine> {
+    let config = Config::load()?;
+    let diff = ConfigDiff::between(engine.config(), &config);
+    engine.reconfigured(config, &diff).await
+}
+
+/// Probe `url`; for a web page, probe the feeds it advertises and return the first that works
+async fn resolve_feed(engine: &crate::Engine, url: &str) -> Result<(String, FeedProbe)> {
+    println!("Probing feed: {}", url);
+    let fetcher = engine.fetcher();
+    let probe = fetcher.probe(url).await?;
+    if probe.is_valid_feed() || !probe.is_html() {
+        return Ok((url.to_string(), probe));
+    }
+
+    let candidates = fetcher.discover(url).await?;
+    if candidates.is_empty() {
+        anyhow::bail!("{} is a web page that does not advertise any feeds", url);
+    }
+    for candidate in candidates {
+        println!("Found feed: {}", candidate);
+        let candidate_probe = fetcher.probe(&candidate).await?;
+        if candidate_probe.is_valid_feed() {
+            return Ok((candidate, candidate_probe));
+        }
+        tracing::debug!("Skipping {}: {:?}", candidate, candidate_probe.error);
+    }
+    anyhow::bail!("None of the feeds advertised by {} could be read", url)
+}
+
+/// Ask for a title on a terminal, keeping `detected` on empty input
+fn confirm_title(detected: &str) -> Result<String> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(detected.to_string());
+    }
+    print!("Title [{}]: ", detected);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().lock().read_line(&mut input)?;
+    Ok(match input.trim() {
+        "" => detected.to_string(),
+        title => title.to_string(),
+    })
+}
+
+/// Names of existing feed files, so a new file never overwrites one
+pub(super) fn existing_file_stems(feeds_dir: &std::path::Path) -> Result<Vec<String>> {
+    if !feeds_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut stems = Vec::new();
+    for entry in std::fs::read_dir(feeds_dir)
+        .with_context(|| format!("Failed to read {}", feeds_dir.display()))?
+    {
+        let path = entry?.path();
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            stems.push(stem.to_string());
+        }
+    }
+    Ok(stems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_feed_id() {
+        let taken: HashSet<String> = ["rust-blog".to_string(), "rust-blog-2".to_string()].into();
+        assert_eq!(unique_feed_id("Example News!", &taken), "example-news");
+        assert_eq!(unique_feed_id("Rust Blog", &taken), "rust-blog-3");
+        assert_eq!(unique_feed_id("???", &taken), "feed");
+    }
+}
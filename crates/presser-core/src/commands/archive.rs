@@ -0,0 +1,40 @@
+//! Archived pages (`presser archive`)
+
+use std::io::Write;
+
+use anyhow::{Context, Result};
+
+/// Open an entry's archived page in the browser, or print its HTML
+pub async fn open_archived(engine: &crate::Engine, entry_id: &str, print: bool) -> Result<()> {
+    let Some((page, html)) = engine.archived_page(entry_id).await? else {
+        anyhow::bail!(
+            "No archived page for {} (save one with `presser archive save`)",
+            entry_id
+        );
+    };
+    if print {
+        std::io::stdout().write_all(&html)?;
+        return Ok(());
+    }
+    let path = std::env::temp_dir().join(format!("presser-{}.html", &page.hash[..12]));
+    std::fs::write(&path, &html).with_context(|| format!("Failed to write {}", path.display()))?;
+    open::that(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+    println!(
+        "Opened the copy of {} archived {}",
+        page.url,
+        page.archived_at.format("%Y-%m-%d %H:%M")
+    );
+    Ok(())
+}
+
+/// Archive an entry's page now
+pub async fn archive_entry(engine: &crate::Engine, entry_id: &str) -> Result<()> {
+    let page = engine.archive_entry(entry_id).await?;
+    println!(
+        "Archived {} ({} KB, {})",
+        page.url,
+        (page.size + 1023) / 1024,
+        &page.hash[..12]
+    );
+    Ok(())
+}
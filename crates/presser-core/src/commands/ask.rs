@@ -0,0 +1,75 @@
+//! Questions answered from stored entries (`presser ask`)
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::ask::{Answer, AskOptions};
+
+/// Print the answer to `question` as it is generated, then the entries it cites
+pub async fn ask(engine: &crate::Engine, question: &str, options: &AskOptions) -> Result<()> {
+    let answer = engine
+        .ask(question, options, |text| {
+            print!("{}", text);
+            let _ = std::io::stdout().flush();
+        })
+        .await?;
+    println!();
+    println!();
+    print!("{}", render_sources(&answer));
+    Ok(())
+}
+
+/// The cited sources, or every source when the answer cites none
+fn render_sources(answer: &Answer) -> String {
+    let mut cited = answer.cited();
+    if cited.is_empty() {
+        cited = answer
+            .sources
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i + 1, s))
+            .collect();
+    }
+    let mut out = String::from("Sources:\n");
+    for (n, source) in cited {
+        out.push_str(&format!(
+            "  [{}] {} ({})\n      {}\n",
+            n, source.entry.title, source.feed_title, source.entry.url
+        ));
+    }
+    out.push_str(&format!("({}, {} tokens)\n", answer.model, answer.tokens));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ask::Source;
+    use presser_db::Entry;
+
+    #[test]
+    fn test_render_sources() {
+        let source = |title: &str| Source {
+            entry: Entry {
+                title: title.into(),
+                url: format!("https://example.com/{}", title.to_lowercase()),
+                ..Default::default()
+            },
+            feed_title: "Example News".into(),
+            text: String::new(),
+        };
+        let mut answer = Answer {
+            text: "The act passed [2].".into(),
+            sources: vec![source("Vote"), source("Passed")],
+            model: "gpt-4".into(),
+            tokens: 310,
+        };
+        assert_eq!(
+            render_sources(&answer),
+            "Sources:\n  [2] Passed (Example News)\n      https://example.com/passed\n(gpt-4, 310 tokens)\n"
+        );
+        answer.text = "Nothing in the sources says.".into();
+        assert!(render_sources(&answer).starts_with("Sources:\n  [1] Vote"));
+    }
+}
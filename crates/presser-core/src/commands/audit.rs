@@ -0,0 +1,57 @@
+//! Dead-feed audits (`presser feeds audit`)
+
+use anyhow::Result;
+use presser_config::Config;
+
+/// List feeds that look dead with their possible replacements, and with
+/// `disable` turn them off in `feeds/*.toml` after one confirmation
+pub async fn audit_feeds(engine: &crate::Engine, months: u32, disable: bool) -> Result<()> {
+    let audits = engine.audit_feeds(months).await?;
+    if audits.is_empty() {
+        println!("No dead feeds.");
+        return Ok(());
+    }
+    for audit in &audits {
+        println!("{} ({})", audit.feed.title, audit.feed.url);
+        println!("  {}", audit.reason);
+        for url in &audit.replacements {
+            println!("  replacement: {}", url);
+        }
+    }
+    println!("{} feed(s) look dead", audits.len());
+    if !disable {
+        println!("Disable them with `presser feeds audit --disable`");
+        return Ok(());
+    }
+    if !super::confirm(&format!("Disable {} feed(s)?", audits.len())) {
+        println!("Left them enabled");
+        return Ok(());
+    }
+    let config_dir = Config::config_dir()?;
+    let db = engine.database();
+    for audit in audits {
+        let url = audit.feed.url.clone();
+        match engine.config().feeds.get(&url) {
+            Some(feed_config) => {
+                let feed_config = presser_config::FeedConfig {
+                    enabled: false,
+                    ..feed_config.clone()
+                };
+                if let Some(path) = Config::update_feed(&config_dir, &url, &feed_config)? {
+                    println!("Disabled {} in {}", url, path.display());
+                }
+            }
+            None => println!(
+                "{} is not in any feeds/*.toml file; disabled in the database only",
+                url
+            ),
+        }
+        db.upsert_feed(&presser_db::Feed {
+            enabled: false,
+            ..audit.feed
+        })
+        .await?;
+    }
+    println!("If the daemon is running, apply the change with `presser ctl reload-config`");
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Shell completions and man pages (`presser completions`, `presser manpages`)
+//!
+//! Completion is dynamic: the script printed by `presser completions` calls
+//! back into `presser` (with `COMPLETE=<shell>` set) on every TAB, so feed IDs
+//! come from the database the config points at.
+
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap_complete::env::Shells;
+use clap_complete::{CompletionCandidate, Shell};
+use presser_config::Config;
+use presser_db::Database;
+
+/// Environment variable the completion script sets when calling back
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Print the script that registers `presser` completions with `shell`
+pub fn print_completions(shell: Shell) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .with_context(|| format!("No completion support for {}", shell))?;
+    let mut out = std::io::stdout().lock();
+    completer
+        .write_registration(COMPLETE_VAR, "presser", "presser", "presser", &mut out)
+        .context("Failed to write completion script")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Write a man page for `cmd` and each of its subcommands into `dir`
+pub fn write_manpages(cmd: clap::Command, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    clap_mangen::generate_to(cmd, dir)
+        .with_context(|| format!("Failed to write man pages to {}", dir.display()))?;
+    println!("Wrote man pages to {}", dir.display());
+    Ok(())
+}
+
+/// Feed IDs (with titles) for completing feed arguments; empty when the
+/// config or database can't be read
+pub fn feed_id_candidates() -> Vec<CompletionCandidate> {
+    // Completion runs before the main runtime starts
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(_) => return Vec::new(),
+    };
+    runtime
+        .block_on(feed_ids())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(id, title)| CompletionCandidate::new(id).help(Some(title.into())))
+        .collect()
+}
+
+async fn feed_ids() -> Result<Vec<(String, String)>> {
+    let config = Config::load()?;
+    let path = &config.database.path;
+    // Never create a database just to complete a command line
+    anyhow::ensure!(path.exists(), "No database at {}", path.display());
+    let key = config.database.encryption_key()?;
+    let db = Database::open_with_key(path, key.as_deref()).await?;
+    Ok(db
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .map(|f| (f.id, f.title))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_manpages() {
+        let dir = tempfile::tempdir().unwrap();
+        let cmd = clap::Command::new("presser")
+            .about("Feed reader")
+            .subcommand(clap::Command::new("update").about("Update feeds"));
+        write_manpages(cmd, dir.path()).unwrap();
+
+        let page = std::fs::read_to_string(dir.path().join("presser.1")).unwrap();
+        assert!(page.contains("Feed reader"));
+        let page = std::fs::read_to_string(dir.path().join("presser-update.1")).unwrap();
+        assert!(page.contains("Update feeds"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Dry runs of filter rules (`presser filters test`)
+
+use std::fmt::Write;
+
+use anyhow::{bail, Context, Result};
+
+use super::truncate;
+use crate::filter::{self, Fields, Rule};
+
+/// Run each feed's filter rules (or `rule` instead) over its `limit` most
+/// recent stored entries, printing which would be kept
+pub async fn test_filters(
+    engine: &crate::Engine,
+    feed_id: Option<&str>,
+    rule: Option<&str>,
+    limit: i64,
+) -> Result<()> {
+    let custom = rule
+        .map(|source| Rule::parse("--rule", source))
+        .transpose()
+        .context("Invalid rule")?;
+    let db = engine.database();
+    let feeds = match feed_id {
+        Some(id) => match db.get_feed(id).await? {
+            Some(feed) => vec![feed],
+            None => bail!("Feed not found: {}", id),
+        },
+        None => db.get_all_feeds().await?,
+    };
+
+    let mut tested = 0;
+    for feed in feeds.iter().filter(|f| f.enabled || feed_id.is_some()) {
+        let config = engine.config().feeds.get(&feed.url);
+        let rules = match &custom {
+            Some(rule) => vec![rule],
+            None => engine.filters().for_feed(&feed.url, config),
+        };
+        if rules.is_empty() {
+            continue;
+        }
+        let results: Vec<(String, Option<String>)> = db
+            .get_entries_for_feed(&feed.id, limit)
+            .await?
+            .iter()
+            .map(|entry| {
+                let dropped = filter::dropped_by(&rules, &Fields::of_stored(entry, config));
+                (entry.title.clone(), dropped.map(|rule| rule.name.clone()))
+            })
+            .collect();
+        let names: Vec<&str> = rules.iter().map(|r| r.name.as_str()).collect();
+        print!("{}", render_results(&feed.id, &names, &results));
+        tested += 1;
+    }
+    if tested == 0 {
+        println!("No filter rules apply to these feeds.");
+    }
+    Ok(())
+}
+
+/// A feed's header, a row per entry (kept, or dropped and by which rule),
+/// and a total
+fn render_results(feed_id: &str, rules: &[&str], results: &[(String, Option<String>)]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{} (rules: {})", feed_id, rules.join(", "));
+    for (title, dropped) in results {
+        let _ = match dropped {
+            Some(rule) => writeln!(out, "  drop  {}  [{}]", truncate(title, 60), rule),
+            None => writeln!(out, "  keep  {}", truncate(title, 60)),
+        };
+    }
+    let dropped = results.iter().filter(|(_, d)| d.is_some()).count();
+    let _ = writeln!(
+        out,
+        "  {} of {} recent entries would be dropped",
+        dropped,
+        results.len()
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_results() {
+        let results = [
+            ("Rust 1.80 released".to_string(), None),
+            (
+                "Sponsored: try our IDE".to_string(),
+                Some("no-sponsored".to_string()),
+            ),
+        ];
+        assert_eq!(
+            render_results("rust-blog", &["feed", "no-sponsored"], &results),
+            "rust-blog (rules: feed, no-sponsored)\n\
+             \x20 keep  Rust 1.80 released\n\
+             \x20 drop  Sponsored: try our IDE  [no-sponsored]\n\
+             \x20 1 of 2 recent entries would be dropped\n"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Update run history (`presser log`)
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use presser_db::RunRecord;
+
+use super::{format_bytes, truncate};
+
+/// Print recorded feed updates, newest run first
+pub async fn show_log(
+    engine: &crate::Engine,
+    feed: Option<&str>,
+    since: Option<Duration>,
+    limit: i64,
+) -> Result<()> {
+    let since = since.map(|age| Utc::now() - age);
+    let runs = engine.database().get_runs(feed, since, limit).await?;
+    if runs.is_empty() {
+        println!("No updates recorded.");
+    } else {
+        print!("{}", render_log(&runs));
+    }
+    Ok(())
+}
+
+/// One block per run: a header with totals, then a row per feed and its errors
+fn render_log(runs: &[RunRecord]) -> String {
+    let mut groups: Vec<(&str, Vec<&RunRecord>)> = Vec::new();
+    for run in runs {
+        match groups.iter_mut().find(|(id, _)| *id == run.run_id) {
+            Some((_, feeds)) => feeds.push(run),
+            None => groups.push((&run.run_id, vec![run])),
+        }
+    }
+
+    let mut out = String::new();
+    for (run_id, feeds) in groups {
+        let started = feeds.iter().map(|r| r.started_at).min().unwrap_or_default();
+        let failed = feeds.iter().filter(|r| r.failed).count();
+        let _ = writeln!(
+            out,
+            "{}  run {}: {} feeds, {} new, {} tokens{}",
+            started.format("%Y-%m-%d %H:%M:%S"),
+            run_id,
+            feeds.len(),
+            feeds.iter().map(|r| r.new_entries).sum::<i64>(),
+            feeds.iter().map(|r| r.tokens).sum::<i64>(),
+            if failed > 0 {
+                format!(", {} failed", failed)
+            } else {
+                String::new()
+            }
+        );
+        for run in feeds {
+            let errors = run.error_list();
+            let status = match (run.failed, run.not_modified, errors.len()) {
+                (true, _, _) => "failed".to_string(),
+                (_, true, _) => "not modified".to_string(),
+                (_, _, 0) => "ok".to_string(),
+                (_, _, n) => format!("{} warnings", n),
+            };
+            let _ = writeln!(
+                out,
+                "  {:<28} {:>4} new {:>4} summarized {:>6} tokens {:>8} {:>7}  {}",
+                truncate(&run.feed_id, 28),
+                run.new_entries,
+                run.summarized,
+                run.tokens,
+                format_bytes(run.bytes),
+                format_duration(run.duration_ms),
+                status
+            );
+            for error in &errors {
+                let _ = writeln!(out, "      {}", truncate(error, 100));
+            }
+        }
+    }
+    out
+}
+
+fn format_duration(ms: i64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_log() {
+        let started_at = "2024-05-01T08:00:00Z".parse().unwrap();
+        let run = |run_id: &str, feed_id: &str| RunRecord {
+            run_id: run_id.into(),
+            feed_id: feed_id.into(),
+            started_at,
+            duration_ms: 1340,
+            new_entries: 3,
+            summarized: 2,
+            tokens: 400,
+            bytes: 1_500_000,
+            ..Default::default()
+        };
+        let runs = [
+            run("r2", "blog"),
+            RunRecord {
+                failed: true,
+                duration_ms: 80,
+                new_entries: 0,
+                summarized: 0,
+                tokens: 0,
+                bytes: 0,
+                errors: Some(r#"["HTTP 503 from https://example.com/feed"]"#.into()),
+                ..run("r1", "news")
+            },
+            run("r2", "news"),
+        ];
+
+        let text = render_log(&runs);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "2024-05-01 08:00:00  run r2: 2 feeds, 6 new, 800 tokens"
+        );
+        assert!(lines[1].starts_with("  blog "), "{}", lines[1]);
+        assert!(
+            lines[1].ends_with("   3 new    2 summarized    400 tokens   1.4 MB    1.3s  ok"),
+            "{}",
+            lines[1]
+        );
+        assert!(lines[2].starts_with("  news "));
+        assert_eq!(
+            lines[3],
+            "2024-05-01 08:00:00  run r1: 1 feeds, 0 new, 0 tokens, 1 failed"
+        );
+        assert!(
+            lines[4].ends_with("0 tokens      0 B    80ms  failed"),
+            "{}",
+            lines[4]
+        );
+        assert_eq!(lines[5], "      HTTP 503 from https://example.com/feed");
+    }
+}
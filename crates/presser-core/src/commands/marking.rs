@@ -0,0 +1,97 @@
+//! Bulk read-state changes (`presser mark-read`, `presser mark-unread`)
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use presser_db::EntryFilter;
+
+/// Which entries `mark-read`/`mark-unread` change
+#[derive(Debug, Default, clap::Args)]
+#[group(id = "scope", required = true, multiple = true)]
+pub struct MarkScope {
+    /// Entry IDs
+    #[arg(conflicts_with_all = ["all", "feed", "tag", "older_than"])]
+    pub ids: Vec<String>,
+
+    /// Every entry
+    #[arg(long, conflicts_with_all = ["feed", "tag", "older_than"])]
+    pub all: bool,
+
+    /// Entries from this feed
+    #[arg(long, add = clap_complete::ArgValueCandidates::new(super::feed_id_candidates))]
+    pub feed: Option<String>,
+
+    /// Entries with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Entries published longer ago than this (e.g. 12h, 7d, 2w)
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    pub older_than: Option<Duration>,
+}
+
+/// Parse an age such as `12h`, `7d`, or `2w`
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.len() - s.chars().last().map_or(0, char::len_utf8);
+    let (count, unit) = s.split_at(split);
+    let count: i64 = count
+        .parse()
+        .map_err(|_| format!("expected a number followed by h, d, or w, got '{}'", s))?;
+    match unit {
+        "h" => Ok(Duration::hours(count)),
+        "d" => Ok(Duration::days(count)),
+        "w" => Ok(Duration::weeks(count)),
+        _ => Err(format!("unknown unit in '{}' (use h, d, or w)", s)),
+    }
+}
+
+/// Mark the entries in `scope` as read (or unread)
+pub async fn mark_entries(engine: &crate::Engine, scope: MarkScope, read: bool) -> Result<()> {
+    let db = engine.database();
+    let state = if read { "read" } else { "unread" };
+
+    if !scope.ids.is_empty() {
+        for id in &scope.ids {
+            if read {
+                db.mark_read(id).await?;
+            } else {
+                db.mark_unread(id).await?;
+            }
+        }
+        println!("Marked {} entries as {}", scope.ids.len(), state);
+        return Ok(());
+    }
+
+    let mut filter = EntryFilter::new();
+    if let Some(feed) = scope.feed {
+        if db.get_feed(&feed).await?.is_none() {
+            anyhow::bail!("Feed not found: {}", feed);
+        }
+        filter = filter.feed(feed);
+    }
+    if let Some(tag) = scope.tag {
+        filter = filter.tag(tag);
+    }
+    if let Some(age) = scope.older_than {
+        filter = filter.until(Utc::now() - age);
+    }
+
+    let changed = db.set_read(&filter, read).await?;
+    println!("Marked {} entries as {}", changed, state);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("12h"), Ok(Duration::hours(12)));
+        assert_eq!(parse_age("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_age(" 2w "), Ok(Duration::weeks(2)));
+        assert!(parse_age("7").is_err());
+        assert!(parse_age("7y").is_err());
+        assert!(parse_age("").is_err());
+    }
+}
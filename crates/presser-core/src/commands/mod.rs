@@ -0,0 +1,913 @@
+//! CLI command implementations
+
+use crate::control::{self, ControlEndpoint, ControlListener, ControlRequest, ControlResponse};
+use crate::daemon::{Daemon, InstanceLock};
+use crate::digest::report::ReportPeriod;
+use crate::digest::{DigestFormat, DigestStyle, SynthesisCost};
+use crate::text::truncate;
+use crate::update::UpdateSummary;
+use anyhow::{Context, Result};
+use presser_config::Config;
+use presser_db::{EntryFilter, ExportFormat};
+use std::path::{Path, PathBuf};
+
+mod add;
+mod archive;
+mod ask;
+mod audit;
+mod completions;
+mod filters;
+mod log;
+mod marking;
+mod mute;
+mod notes;
+mod opml;
+mod publish;
+mod readers;
+mod reading;
+mod summarize;
+mod users;
+
+pub use add::*;
+pub use archive::*;
+pub use ask::*;
+pub use audit::*;
+pub use completions::*;
+pub use filters::*;
+pub use log::*;
+pub use marking::*;
+pub use mute::*;
+pub use notes::*;
+pub use opml::*;
+pub use publish::*;
+pub use readers::*;
+pub use reading::*;
+pub use summarize::*;
+pub use users::*;
+
+/// Send an entry to a read-it-later service
+pub async fn save_entry(engine: &crate::Engine, id: &str, to: Option<&str>) -> Result<()> {
+    let entry = engine.database().get_entry(id).await?.ok_or_else(|| {
+        anyhow::anyhow!("Entry not found: {} (list entries with 'presser read')", id)
+    })?;
+    let service = crate::integrations::save_entry(engine, &entry, to).await?;
+    println!("Saved to {}: {}", service, entry.title);
+    Ok(())
+}
+
+pub async fn remove_feed(engine: &crate::Engine, id: &str) -> Result<()> {
+    engine.database().delete_feed(id).await?;
+    println!("Removed feed: {}", id);
+    Ok(())
+}
+
+pub async fn list_feeds(engine: &crate::Engine) -> Result<()> {
+    let feeds = engine.database().get_all_feeds().await?;
+    if feeds.is_empty() {
+        println!("No feeds configured. Use 'presser add <url>' to add one.");
+    } else {
+        for feed in feeds {
+            let status = if feed.enabled { "" } else { " [disabled]" };
+            println!(
+                "{}: {} ({} entries){}",
+                feed.id, feed.title, feed.entry_count, status
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Update feeds
+pub async fn update_feeds(engine: &crate::Engine, feed_id: Option<&str>) -> Result<()> {
+    let moved = match feed_id {
+        Some(id) => {
+            println!("Updating feed: {}", id);
+            let report = engine.update_feed(id).await?;
+            if report.busy {
+                println!("Feed is already being updated by another process");
+            } else if report.offline {
+                println!("Offline: update deferred");
+            } else if report.not_modified {
+                println!("Feed not modified");
+            } else {
+                println!(
+                    "Feed updated: {} new, {} summarized, {} skipped",
+                    report.new, report.summarized, report.skipped
+                );
+                if report.translated > 0 {
+                    println!("{} entries translated", report.translated);
+                }
+                if report.revised > 0 {
+                    println!("{} stored entries changed", report.revised);
+                }
+                if report.deferred > 0 {
+                    println!(
+                        "{} summaries deferred by the daily token budget",
+                        report.deferred
+                    );
+                }
+            }
+            if let Some(url) = &report.moved_to {
+                println!("Feed moved permanently to {}", url);
+            }
+            for error in &report.errors {
+                println!("  warning: {}", error);
+            }
+            usize::from(report.moved_to.is_some())
+        }
+        None => {
+            println!("Updating all feeds...");
+            let summary = engine.update_all_feeds().await?;
+            print_update_summary(&summary);
+            summary.moved()
+        }
+    };
+    if moved > 0 {
+        if engine.config().global.auto_fix_urls {
+            fix_urls(engine, false).await?;
+        } else {
+            println!(
+                "{} feeds moved permanently; run `presser fix-urls` to follow them",
+                moved
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Point feeds that moved permanently at their new URLs, in `feeds/*.toml`
+/// and the database (or just list them with `dry_run`)
+pub async fn fix_urls(engine: &crate::Engine, dry_run: bool) -> Result<()> {
+    let moves = engine
+        .apply_url_moves(&Config::config_dir()?, dry_run)
+        .await?;
+    if moves.is_empty() {
+        println!("No moved feeds.");
+        return Ok(());
+    }
+    if dry_run {
+        println!("Dry run: the config and database are left alone");
+    }
+    for url_move in &moves {
+        println!("{}: {} -> {}", url_move.feed_id, url_move.from, url_move.to);
+        match (&url_move.skipped, &url_move.config_file) {
+            (Some(reason), _) => println!("  skipped: {}", reason),
+            (None, Some(path)) => println!("  updated {}", path.display()),
+            (None, None) if !dry_run => {
+                println!("  not in any feeds/*.toml file; updated the database only")
+            }
+            (None, None) => {}
+        }
+    }
+    if !dry_run && moves.iter().any(|m| m.skipped.is_none()) {
+        println!("If the daemon is running, apply the new URLs with `presser ctl reload-config`");
+    }
+    Ok(())
+}
+
+/// Fetch feeds and print what an update would store and summarize, writing nothing
+pub async fn preview_update(engine: &crate::Engine, feed_id: Option<&str>) -> Result<()> {
+    let plans = match feed_id {
+        Some(id) => vec![engine.plan_feed_update(id).await?],
+        None => engine.plan_all_feeds().await?,
+    };
+    println!("Dry run: nothing is stored and no summaries are requested");
+    if plans.is_empty() {
+        println!("No enabled feeds.");
+        return Ok(());
+    }
+    println!(
+        "{:<28} {:>5} {:>12} {:>7}  Status",
+        "Feed", "New", "To summarize", "Skipped"
+    );
+    for plan in &plans {
+        let status = match (&plan.error, plan.not_modified) {
+            (Some(error), _) => format!("failed: {}", truncate(error, 60)),
+            (None, true) => "not modified".to_string(),
+            (None, false) => "ok".to_string(),
+        };
+        println!(
+            "{:<28} {:>5} {:>12} {:>7}  {}",
+            truncate(&plan.feed_id, 28),
+            plan.new.len(),
+            plan.to_summarize,
+            plan.skipped,
+            status
+        );
+        for title in &plan.new {
+            println!("  + {}", truncate(title, 76));
+        }
+    }
+    println!(
+        "{:<28} {:>5} {:>12} {:>7}",
+        "Total",
+        plans.iter().map(|p| p.new.len()).sum::<usize>(),
+        plans.iter().map(|p| p.to_summarize).sum::<usize>(),
+        plans.iter().map(|p| p.skipped).sum::<usize>()
+    );
+    Ok(())
+}
+
+/// Print one row per feed plus totals
+fn print_update_summary(summary: &crate::update::UpdateSummary) {
+    if summary.reports.is_empty() {
+        println!("No enabled feeds.");
+        return;
+    }
+    println!(
+        "{:<28} {:>5} {:>10} {:>7}  Status",
+        "Feed", "New", "Summarized", "Skipped"
+    );
+    for report in &summary.reports {
+        let status = match (report.failed, report.not_modified, report.errors.first()) {
+            _ if report.busy => "busy (updating elsewhere)".to_string(),
+            _ if report.offline => "offline (deferred)".to_string(),
+            (true, _, Some(error)) => format!("failed: {}", truncate(error, 60)),
+            (_, true, _) => "not modified".to_string(),
+            (_, _, Some(_)) => format!("{} warnings", report.errors.len()),
+            _ => "ok".to_string(),
+        };
+        println!(
+            "{:<28} {:>5} {:>10} {:>7}  {}",
+            truncate(&report.feed_id, 28),
+            report.new,
+            report.summarized,
+            report.skipped,
+            status
+        );
+    }
+    println!(
+        "{:<28} {:>5} {:>10} {:>7}  {} of {} feeds failed",
+        "Total",
+        summary.new_entries(),
+        summary.summarized(),
+        summary.skipped(),
+        summary.failed(),
+        summary.reports.len()
+    );
+    if let Some(backlog) = summary.backlog.as_ref().filter(|b| b.summarized > 0) {
+        println!(
+            "Summarized {} entries queued by earlier runs",
+            backlog.summarized
+        );
+    }
+    if summary.deferred() > 0 {
+        println!(
+            "{} summaries deferred by the daily token budget; they stay queued for the next run",
+            summary.deferred()
+        );
+    }
+}
+
+/// What `presser digest` writes
+#[derive(Debug)]
+pub struct DigestOptions {
+    /// Days of unread entries to cover
+    pub days: u32,
+
+    /// Builtin format (default: [digest].template if set, else text)
+    pub format: Option<String>,
+
+    /// Template file to render with instead of a builtin format
+    pub template: Option<PathBuf>,
+
+    /// Group by feed, tag, or day
+    pub group_by: String,
+
+    /// File to write instead of stdout
+    pub output: Option<PathBuf>,
+
+    /// Only list what the digest would include
+    pub dry_run: bool,
+
+    /// Open with an AI overview even if [digest].synthesis is off
+    pub synthesize: bool,
+
+    /// Show the overview's estimated cost and ask before writing it
+    pub confirm_cost: bool,
+}
+
+/// Generate a digest and print it or write it to `options.output`
+pub async fn generate_digest(engine: &crate::Engine, options: DigestOptions) -> Result<()> {
+    let style = digest_style(engine, options.template, options.format.as_deref())?;
+    let synthesize = options.synthesize || engine.config().digest.synthesis;
+    if options.dry_run {
+        let digest =
+            crate::digest::build(engine.database(), options.days, options.group_by.parse()?)
+                .await?;
+        println!(
+            "Dry run: the digest would cover {} entries from {} feeds ({} summarized); \
+             nothing written, no notification sent",
+            digest.entry_count, digest.feed_count, digest.summarized_count
+        );
+        if synthesize {
+            match engine.synthesis_cost(&digest).await? {
+                Some(cost) => println!("The overview would take {}", cost),
+                None => println!("Too few entries for an overview"),
+            }
+        }
+        for section in &digest.sections {
+            println!("{} ({})", section.heading, section.items.len());
+            for item in &section.items {
+                println!("  {}", truncate(&item.title, 76));
+            }
+        }
+        return Ok(());
+    }
+    let confirm_cost = options.confirm_cost;
+    let approve = move |cost: &SynthesisCost| !confirm_cost || confirm_synthesis("overview", cost);
+    let digest = engine
+        .generate_digest(
+            options.days,
+            &style,
+            options.group_by.parse()?,
+            synthesize.then_some(&approve as &(dyn Fn(&SynthesisCost) -> bool + Sync)),
+        )
+        .await?;
+    match options.output.as_deref() {
+        Some(path) => {
+            std::fs::write(path, digest)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Digest written to {}", path.display());
+        }
+        None => print!("{}", digest),
+    }
+    Ok(())
+}
+
+/// What `presser report` writes
+#[derive(Debug)]
+pub struct ReportOptions {
+    /// Day, week, or month
+    pub period: String,
+
+    /// Builtin format (default: [digest].template if set, else text)
+    pub format: Option<String>,
+
+    /// Template file to render with instead of a builtin format
+    pub template: Option<PathBuf>,
+
+    /// File to write instead of stdout
+    pub output: Option<PathBuf>,
+
+    /// Only list the topics the report would cover
+    pub dry_run: bool,
+
+    /// Leave out the AI trends narrative
+    pub no_narrative: bool,
+
+    /// Show the narrative's estimated cost and ask before writing it
+    pub confirm_cost: bool,
+}
+
+/// Write a trend report of the period's entries by topic
+pub async fn generate_report(engine: &crate::Engine, options: ReportOptions) -> Result<()> {
+    let period: ReportPeriod = options.period.parse()?;
+    let style = digest_style(engine, options.template, options.format.as_deref())?;
+    if options.dry_run {
+        let report = crate::digest::report::build(engine.database(), period).await?;
+        println!(
+            "Dry run: the report would cover {} entries from {} feeds in {} sections; nothing written",
+            report.entry_count,
+            report.feed_count,
+            report.sections.len()
+        );
+        if !options.no_narrative {
+            match engine.narrative_cost(&report).await? {
+                Some(cost) => println!("The narrative would take {}", cost),
+                None => println!("No entries for a narrative"),
+            }
+        }
+        for section in &report.sections {
+            println!("  {}", section.heading);
+        }
+        return Ok(());
+    }
+    let confirm_cost = options.confirm_cost;
+    let approve = move |cost: &SynthesisCost| !confirm_cost || confirm_synthesis("narrative", cost);
+    let report = engine
+        .generate_report(
+            period,
+            &style,
+            (!options.no_narrative).then_some(&approve as &(dyn Fn(&SynthesisCost) -> bool + Sync)),
+        )
+        .await?;
+    match options.output.as_deref() {
+        Some(path) => {
+            std::fs::write(path, report)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Report written to {}", path.display());
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
+/// How to render a digest or report: an explicit --template or --format
+/// wins over [digest].template
+fn digest_style(
+    engine: &crate::Engine,
+    template: Option<PathBuf>,
+    format: Option<&str>,
+) -> Result<DigestStyle> {
+    Ok(match (template, format) {
+        (Some(path), _) => DigestStyle::Template(path),
+        (None, Some(format)) => DigestStyle::Builtin(format.parse()?),
+        (None, None) => match &engine.config().digest.template {
+            Some(path) => DigestStyle::Template(path.clone()),
+            None => DigestStyle::Builtin(DigestFormat::default()),
+        },
+    })
+}
+
+/// Show what an AI overview or narrative would cost and ask whether to
+/// write it; on stderr, so the output itself can be piped
+fn confirm_synthesis(what: &str, cost: &SynthesisCost) -> bool {
+    let yes = confirm(&format!("The {} takes {}. Write it?", what, cost));
+    if !yes {
+        eprintln!("Leaving the {} out", what);
+    }
+    yes
+}
+
+/// Ask a yes/no question on stderr; anything but "y" or "yes" is no
+fn confirm(question: &str) -> bool {
+    use std::io::Write;
+    eprint!("{} [y/N] ", question);
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Start the scheduler daemon and its control interface; runs until Ctrl-C
+pub async fn start_daemon() -> Result<()> {
+    let config_dir = Config::config_dir()?;
+    let config = Config::load_from_dir(&config_dir)?;
+    let endpoint = ControlEndpoint::from_config(&config.daemon);
+    // Bind first so a second daemon fails before touching the database
+    let listener = ControlListener::bind(&endpoint).await?;
+    let _lock = InstanceLock::acquire(&config.database.path)?;
+    let engine = crate::daemon::load_engine(&config_dir).await?;
+    let daemon = Daemon::new(engine, config_dir).await?;
+    println!(
+        "Daemon started; control it with `presser ctl` ({})",
+        endpoint
+    );
+    daemon.run(listener).await
+}
+
+/// Hand `request` to the daemon if one is running; false if there is none
+///
+/// Used by commands that would otherwise duplicate the daemon's work.
+pub async fn delegate_to_daemon(request: ControlRequest) -> Result<bool> {
+    let config = Config::load()?;
+    let endpoint = ControlEndpoint::from_config(&config.daemon);
+    if !control::is_running(&endpoint).await {
+        return Ok(false);
+    }
+    println!("Daemon is running; handing the request to it (use --local to run here)");
+    ctl(request).await?;
+    Ok(true)
+}
+
+/// Send a control request to the running daemon and print the answer
+pub async fn ctl(request: ControlRequest) -> Result<()> {
+    let config = Config::load()?;
+    let endpoint = ControlEndpoint::from_config(&config.daemon);
+    match control::send(&endpoint, &request).await? {
+        ControlResponse::Status(status) => {
+            let uptime = chrono::Utc::now() - status.started_at;
+            println!(
+                "Daemon running (pid {}, up {}h {}m)",
+                status.pid,
+                uptime.num_hours(),
+                uptime.num_minutes() % 60
+            );
+            println!();
+            println!(
+                "  {:<30} {:<20} {:<20} State",
+                "Task", "Next run", "Last run"
+            );
+            for task in &status.tasks {
+                println!(
+                    "  {:<30} {:<20} {:<20} {}",
+                    truncate(&task.id, 30),
+                    task.next_run.format("%Y-%m-%d %H:%M:%S"),
+                    task.last_run.map_or("-".to_string(), |t| t
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()),
+                    if task.paused { "paused" } else { "active" }
+                );
+            }
+        }
+        ControlResponse::Update(report) => print_update_summary(&UpdateSummary {
+            reports: vec![report],
+            backlog: None,
+        }),
+        ControlResponse::Summary(summary) => print_update_summary(&summary),
+        ControlResponse::Stats(stats) => {
+            println!("Feeds:     {}", stats.total_feeds);
+            println!(
+                "Entries:   {} ({} unread)",
+                stats.total_entries, stats.unread_entries
+            );
+            println!("Summaries: {}", stats.total_summaries);
+        }
+        ControlResponse::Done { message } => println!("{}", message),
+        ControlResponse::Error { message } => anyhow::bail!("Daemon error: {}", message),
+    }
+    Ok(())
+}
+
+/// Show database statistics
+pub async fn show_stats(engine: &crate::Engine, days: u32) -> Result<()> {
+    let db = engine.database();
+    let stats = db.get_stats().await?;
+    println!("Database Statistics:");
+    println!("  Feeds:     {}", stats.total_feeds);
+    println!(
+        "  Entries:   {} ({} unread)",
+        stats.total_entries, stats.unread_entries
+    );
+    println!("  Summaries: {}", stats.total_summaries);
+
+    let feeds = db.get_feed_stats().await?;
+    println!(
+        "  Downloaded: {}",
+        format_bytes(feeds.iter().map(|f| f.bytes).sum())
+    );
+    if !feeds.is_empty() {
+        println!();
+        println!(
+            "  {:<30} {:>7} {:>7} {:>7} {:>8} {:>6} {:>10}",
+            "Feed", "Entries", "Unread", "/week", "Tokens", "Fail%", "Downloaded"
+        );
+        for feed in &feeds {
+            let tokens = feed
+                .avg_summary_tokens
+                .map_or("-".to_string(), |t| format!("{:.0}", t));
+            let failures = feed
+                .failure_rate()
+                .map_or("-".to_string(), |r| format!("{:.0}", r * 100.0));
+            println!(
+                "  {:<30} {:>7} {:>7} {:>7.1} {:>8} {:>6} {:>10}",
+                truncate(&feed.title, 30),
+                feed.entries,
+                feed.unread,
+                feed.entries_per_week,
+                tokens,
+                failures,
+                format_bytes(feed.bytes)
+            );
+        }
+    }
+
+    let daily = db.get_daily_counts(days).await?;
+    let peak = daily.iter().map(|d| d.entries).max().unwrap_or(0).max(1);
+    println!();
+    println!("  Last {} days (new entries / summaries):", daily.len());
+    for day in &daily {
+        let bar = "#".repeat((day.entries * 40 / peak) as usize);
+        println!(
+            "  {} {:>5} {:>5} {}",
+            day.day, day.entries, day.summaries, bar
+        );
+    }
+    Ok(())
+}
+
+/// Byte count for display, in binary units (`512 B`, `1.5 KB`, `3.2 MB`)
+pub(crate) fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Prune entries per the retention policy, optionally reclaiming disk space
+pub async fn prune(engine: &crate::Engine, vacuum: bool, dry_run: bool) -> Result<()> {
+    if dry_run {
+        return preview_prune(engine, vacuum).await;
+    }
+    if engine.retention_policy().is_noop() {
+        println!("No retention limits configured; nothing to prune.");
+    } else {
+        let report = engine.prune().await?;
+        println!(
+            "Pruned {} entries ({} archived)",
+            report.deleted, report.archived
+        );
+    }
+    let runs = engine.prune_run_log().await?;
+    if runs > 0 {
+        println!("Forgot {} update log records", runs);
+    }
+    if vacuum {
+        let db = engine.database();
+        db.vacuum().await?;
+        let report = db.maintenance().await?;
+        println!("Database vacuumed and analyzed");
+        if !report.indices_created.is_empty() {
+            println!(
+                "Recreated missing indices: {}",
+                report.indices_created.join(", ")
+            );
+        }
+        if report.wal_busy {
+            println!("WAL still in use by another process; not truncated");
+        }
+    }
+    Ok(())
+}
+
+/// Print how many entries per feed `prune` would delete, deleting nothing
+async fn preview_prune(engine: &crate::Engine, vacuum: bool) -> Result<()> {
+    let policy = engine.retention_policy();
+    if policy.is_noop() {
+        println!("No retention limits configured; nothing to prune.");
+        return Ok(());
+    }
+    let candidates = engine.database().prune_candidates(&policy).await?;
+    let mut per_feed: Vec<(&str, usize)> = Vec::new();
+    for entry in &candidates {
+        match per_feed.last_mut() {
+            Some((feed, count)) if *feed == entry.feed_id => *count += 1,
+            _ => per_feed.push((&entry.feed_id, 1)),
+        }
+    }
+    println!(
+        "Dry run: would prune {} entries{}",
+        candidates.len(),
+        if policy.archive {
+            " (archiving them first)"
+        } else {
+            ""
+        }
+    );
+    for (feed, count) in per_feed {
+        println!("  {:<28} {:>6}", truncate(feed, 28), count);
+    }
+    if vacuum {
+        println!("Skipping --vacuum in a dry run");
+    }
+    Ok(())
+}
+
+/// Back up the database to a new file
+pub async fn backup(engine: &crate::Engine, path: &Path) -> Result<()> {
+    engine.database().backup(path).await?;
+    println!("Backed up database to {} (verified)", path.display());
+    Ok(())
+}
+
+/// Restore the configured database from a backup file
+///
+/// Runs without an engine so the current database is not held open.
+pub async fn restore(path: &Path) -> Result<()> {
+    let config = presser_config::Config::load()?;
+    let target = &config.database.path;
+    let key = config.database.encryption_key()?;
+    let saved = presser_db::Database::restore(path, target, key.as_deref()).await?;
+    println!("Restored {} from {}", target.display(), path.display());
+    if let Some(saved) = saved {
+        println!("Previous database kept at {}", saved.display());
+    }
+    Ok(())
+}
+
+/// Options for `presser export`
+#[derive(Debug, Default)]
+pub struct ExportOptions {
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+    pub feed: Option<String>,
+    pub days: Option<u32>,
+    pub starred: bool,
+}
+
+/// Resolve an explicit format name, falling back to the file extension
+fn resolve_format(format: Option<&str>, path: Option<&Path>) -> Result<ExportFormat> {
+    match (format, path.and_then(ExportFormat::from_path)) {
+        (Some(format), _) => format.parse(),
+        (None, Some(format)) => Ok(format),
+        (None, None) if path.is_none() => Ok(ExportFormat::Json),
+        (None, None) => anyhow::bail!("Cannot infer format from file name; pass --format"),
+    }
+}
+
+/// Export entries to a file or stdout
+pub async fn export_entries(engine: &crate::Engine, options: ExportOptions) -> Result<()> {
+    let format = resolve_format(options.format.as_deref(), options.output.as_deref())?;
+
+    let mut filter = EntryFilter::new();
+    if let Some(feed) = options.feed {
+        filter = filter.feed(feed);
+    }
+    if let Some(days) = options.days {
+        filter = filter.since(chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+    }
+    if options.starred {
+        filter = filter.starred(true);
+    }
+
+    match &options.output {
+        Some(path) => {
+            let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            let count = engine
+                .database()
+                .export_entries(&filter, format, file)
+                .await?;
+            println!(
+                "Exported {} entries to {} ({})",
+                count,
+                path.display(),
+                format
+            );
+        }
+        None => {
+            engine
+                .database()
+                .export_entries(&filter, format, std::io::stdout().lock())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Import entries from an export file
+pub async fn import_entries(
+    engine: &crate::Engine,
+    path: &Path,
+    format: Option<&str>,
+) -> Result<()> {
+    let format = resolve_format(format, Some(path))?;
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let report = engine.database().import_entries(format, file).await?;
+    println!(
+        "Imported {} entries ({} already present, {} feeds created)",
+        report.imported, report.duplicates, report.feeds_created
+    );
+    Ok(())
+}
+
+/// Diagnose feed health for a single URL or every stored feed
+pub async fn doctor(engine: &crate::Engine, url: Option<&str>) -> Result<()> {
+    let urls: Vec<String> = match url {
+        Some(url) => vec![url.to_string()],
+        None => engine
+            .database()
+            .get_all_feeds()
+            .await?
+            .into_iter()
+            .filter(|f| !crate::engine::is_local_feed(&f.id))
+            .map(|f| f.url)
+            .collect(),
+    };
+
+    // Before the feeds: the provider fails every summary if it fails
+    let ai_healthy = url.is_some() || check_ai(engine.ai()).await;
+
+    if urls.is_empty() {
+        println!("No feeds to check. Use 'presser add <url>' to add one.");
+        return Ok(());
+    }
+
+    let mut problems = 0;
+    for url in &urls {
+        let probe = engine.fetcher().probe(url).await?;
+        if probe.error.is_some() || !probe.is_valid_feed() {
+            problems += 1;
+        }
+        println!("{}", probe);
+    }
+
+    println!(
+        "Checked {} feed(s): {} healthy, {} with problems",
+        urls.len(),
+        urls.len() - problems,
+        problems
+    );
+    if !ai_healthy {
+        println!("The AI provider has a problem: summaries fail until it is fixed");
+    }
+
+    if url.is_none() {
+        let user_agents = engine.database().feed_user_agents().await?;
+        if !user_agents.is_empty() {
+            println!("\nFeeds that refused the configured user agent (403) and needed another:");
+            for (feed_id, user_agent) in user_agents {
+                println!("  {}: {}", feed_id, user_agent);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Start interactive TUI
+pub async fn run_tui(engine: std::sync::Arc<crate::Engine>) -> Result<()> {
+    let mut app = crate::ui::App::new(engine).await?;
+    app.run().await
+}
+
+/// Check the AI provider, printing what was found; whether it is usable
+async fn check_ai(ai: &presser_ai::AiClient) -> bool {
+    println!("Provider:   {} ({})", ai.provider().name(), ai.model());
+    match ai.health_check().await {
+        Ok(latency) => {
+            println!("Reachable:  yes ({} ms)\n", latency.as_millis());
+            true
+        }
+        Err(e) => {
+            println!("Problem:    {:#}\n", e);
+            false
+        }
+    }
+}
+
+/// Initialize configuration: create the config directories, create (and
+/// migrate) the database, and check the AI provider
+///
+/// Settings left out of `global.toml` keep their defaults, so none is
+/// written.
+pub async fn init_config() -> Result<()> {
+    println!("Initializing configuration...");
+    let dir = Config::config_dir()?;
+    let feeds_dir = dir.join("feeds");
+    std::fs::create_dir_all(&feeds_dir)
+        .with_context(|| format!("Failed to create {}", feeds_dir.display()))?;
+    println!("Configuration directory: {}", dir.display());
+
+    let engine = crate::Engine::new().await?;
+    println!("Database: {}\n", engine.config().database.path.display());
+
+    if !check_ai(engine.ai()).await {
+        if let Ok(models) = engine.ai().list_models().await {
+            if !models.is_empty() {
+                println!("Models offered: {}", models.join(", "));
+            }
+        }
+        println!(
+            "Set [ai] in {} and run 'presser doctor' to check again",
+            dir.join("global.toml").display()
+        );
+    }
+    println!("Use 'presser add <url>' to add a feed.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::test_config;
+    use crate::Engine;
+
+    #[tokio::test]
+    async fn test_prune_dry_run_deletes_nothing() {
+        let mut config = test_config();
+        config.retention.max_entries_per_feed = Some(1);
+        let engine = Engine::with_config(config).await.unwrap();
+        let fixtures = presser_db::fixtures::seed(engine.database()).await.unwrap();
+        let total = || async { engine.database().get_stats().await.unwrap().total_entries };
+        assert!(!engine
+            .database()
+            .prune_candidates(&engine.retention_policy())
+            .await
+            .unwrap()
+            .is_empty());
+
+        prune(&engine, false, true).await.unwrap();
+        assert_eq!(total().await, fixtures.entries.len() as i64);
+        prune(&engine, false, false).await.unwrap();
+        assert!(total().await < fixtures.entries.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_digest_dry_run_writes_nothing() {
+        let engine = Engine::with_config(test_config()).await.unwrap();
+        presser_db::fixtures::seed(engine.database()).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let options = |dry_run| DigestOptions {
+            days: 30,
+            format: Some("markdown".into()),
+            template: None,
+            group_by: "feed".into(),
+            output: Some(dir.path().join("digest.md")),
+            dry_run,
+            synthesize: false,
+            confirm_cost: false,
+        };
+
+        generate_digest(&engine, options(true)).await.unwrap();
+        assert!(!dir.path().join("digest.md").exists());
+        generate_digest(&engine, options(false)).await.unwrap();
+        assert!(dir.path().join("digest.md").exists());
+    }
+}
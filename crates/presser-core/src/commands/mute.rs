@@ -0,0 +1,147 @@
+//! Mute rules (`presser mute`, `presser unmute`)
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use presser_db::{MuteKind, MuteRule};
+
+use super::truncate;
+
+/// Mute entries matching `pattern` for `duration`, or until unmuted
+pub async fn mute(
+    engine: &crate::Engine,
+    kind: MuteKind,
+    pattern: &str,
+    duration: Option<Duration>,
+) -> Result<()> {
+    let id = engine.mute(kind, pattern, duration).await?;
+    let until = match duration {
+        Some(duration) => format!(
+            " until {}",
+            (Utc::now() + duration).format("%Y-%m-%d %H:%M")
+        ),
+        None => " until unmuted".to_string(),
+    };
+    println!(
+        "Muted {} '{}'{} (rule {})",
+        kind_name(kind),
+        pattern.trim(),
+        until,
+        id
+    );
+    Ok(())
+}
+
+/// Print the rules in force
+pub async fn list_mutes(engine: &crate::Engine) -> Result<()> {
+    let rules = engine.mute_rules().await?;
+    if rules.is_empty() {
+        println!("No mute rules.");
+    } else {
+        print!("{}", render_mutes(&rules, Utc::now()));
+    }
+    Ok(())
+}
+
+/// Remove a mute rule
+pub async fn unmute(engine: &crate::Engine, id: i64) -> Result<()> {
+    anyhow::ensure!(
+        engine.database().delete_mute_rule(id).await?,
+        "No mute rule {} (see presser mute --list)",
+        id
+    );
+    println!("Removed mute rule {}", id);
+    Ok(())
+}
+
+fn kind_name(kind: MuteKind) -> &'static str {
+    match kind {
+        MuteKind::Keyword => "keyword",
+        MuteKind::Regex => "regex",
+        MuteKind::Tag => "tag",
+    }
+}
+
+/// A row per rule: ID, kind, pattern, and time left
+fn render_mutes(rules: &[MuteRule], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{:>4}  {:<8} {:<40} EXPIRES", "ID", "KIND", "PATTERN");
+    for rule in rules {
+        let expires = match rule.expires_at {
+            Some(at) => format!(
+                "{} (in {})",
+                at.format("%Y-%m-%d %H:%M"),
+                time_left(at - now)
+            ),
+            None => "never".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "{:>4}  {:<8} {:<40} {}",
+            rule.id,
+            kind_name(rule.kind),
+            truncate(&rule.pattern, 40),
+            expires
+        );
+    }
+    out
+}
+
+/// `left` in whole days, or hours under a day
+fn time_left(left: Duration) -> String {
+    match left.num_days() {
+        0 => format!("{}h", left.num_hours().max(1)),
+        days => format!("{}d", days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_mutes() {
+        let now = DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let rule = |id, kind, pattern: &str, expires_at| MuteRule {
+            id,
+            kind,
+            pattern: pattern.into(),
+            expires_at,
+            created_at: now,
+        };
+        let rules = [
+            rule(
+                1,
+                MuteKind::Keyword,
+                "world cup",
+                Some(now + Duration::days(30)),
+            ),
+            rule(2, MuteKind::Tag, "crypto", Some(now + Duration::hours(5))),
+            rule(3, MuteKind::Regex, r"\bnfts?\b", None),
+        ];
+        assert_eq!(
+            render_mutes(&rules, now),
+            format!(
+                "{:>4}  {:<8} {:<40} EXPIRES\n\
+                 {:>4}  {:<8} {:<40} 2024-07-01 12:00 (in 30d)\n\
+                 {:>4}  {:<8} {:<40} 2024-06-01 17:00 (in 5h)\n\
+                 {:>4}  {:<8} {:<40} never\n",
+                "ID",
+                "KIND",
+                "PATTERN",
+                1,
+                "keyword",
+                "world cup",
+                2,
+                "tag",
+                "crypto",
+                3,
+                "regex",
+                r"\bnfts?\b"
+            )
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Markdown notes export for Obsidian and similar tools (`presser export notes`)
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use presser_db::{Entry, Note};
+
+use super::add::unique_feed_id;
+
+/// Title characters used in file names
+const FILE_TITLE_LEN: usize = 60;
+
+/// Write one Markdown file per starred or annotated entry into `dir`
+///
+/// File names come from the publication date and title, so re-running the
+/// export only adds files for new entries; existing files are left alone
+/// (edits made in the vault survive) unless `overwrite` is set.
+pub async fn export_notes(engine: &crate::Engine, dir: &Path, overwrite: bool) -> Result<()> {
+    let db = engine.database();
+    let mut notes: HashMap<String, Vec<Note>> = HashMap::new();
+    for note in db.get_all_notes().await? {
+        notes.entry(note.entry_id.clone()).or_default().push(note);
+    }
+
+    let mut entries: BTreeMap<String, Entry> = db
+        .get_starred_entries(i64::MAX)
+        .await?
+        .into_iter()
+        .map(|e| (e.id.clone(), e))
+        .collect();
+    for id in notes.keys() {
+        if !entries.contains_key(id) {
+            if let Some(entry) = db.get_entry(id).await? {
+                entries.insert(id.clone(), entry);
+            }
+        }
+    }
+    // Oldest first, so colliding titles get stable suffixes from run to run
+    let mut entries: Vec<Entry> = entries.into_values().collect();
+    entries.sort_by(|a, b| (a.published, &a.id).cmp(&(b.published, &b.id)));
+
+    let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+    let summaries = db.get_summaries_for_entries(&ids).await?;
+    let tags = db.get_tags_for_entries(&ids).await?;
+    let feed_titles: HashMap<String, String> = db
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .map(|f| (f.id, f.title))
+        .collect();
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let mut taken = HashSet::new();
+    let (mut written, mut skipped) = (0, 0);
+    for entry in &entries {
+        let stem = file_stem(entry, &taken);
+        taken.insert(stem.clone());
+        let path = dir.join(format!("{}.md", stem));
+        if path.exists() && !overwrite {
+            skipped += 1;
+            continue;
+        }
+        let note = NoteFile {
+            entry,
+            feed_title: feed_titles.get(&entry.feed_id).map(String::as_str),
+            summary: summaries.get(&entry.id).map(|s| s.summary_text.as_str()),
+            tags: tags.get(&entry.id).map(Vec::as_slice).unwrap_or_default(),
+            notes: notes.get(&entry.id).map(Vec::as_slice).unwrap_or_default(),
+        };
+        std::fs::write(&path, note.render())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        written += 1;
+    }
+
+    println!(
+        "Wrote {} notes to {} ({} already exported)",
+        written,
+        dir.display(),
+        skipped
+    );
+    Ok(())
+}
+
+/// `<date>-<title-slug>`, unique among `taken`
+fn file_stem(entry: &Entry, taken: &HashSet<String>) -> String {
+    let date = entry
+        .published
+        .unwrap_or(entry.created_at)
+        .format("%Y-%m-%d");
+    let title: String = entry.title.chars().take(FILE_TITLE_LEN).collect();
+    unique_feed_id(&format!("{} {}", date, title), taken)
+}
+
+/// One entry's Markdown file
+struct NoteFile<'a> {
+    entry: &'a Entry,
+    feed_title: Option<&'a str>,
+    summary: Option<&'a str>,
+    tags: &'a [String],
+    notes: &'a [Note],
+}
+
+impl NoteFile<'_> {
+    /// YAML frontmatter, then the summary, highlights, and notes
+    fn render(&self) -> String {
+        // JSON strings are valid YAML scalars and take care of quoting
+        let quote = |s: &str| serde_json::to_string(s).unwrap_or_default();
+        let entry = self.entry;
+
+        let mut out = String::from("---\n");
+        let _ = writeln!(out, "title: {}", quote(&entry.title));
+        let _ = writeln!(out, "url: {}", quote(&entry.url));
+        if let Some(feed) = self.feed_title {
+            let _ = writeln!(out, "source: {}", quote(feed));
+        }
+        if let Some(author) = &entry.author {
+            let _ = writeln!(out, "author: {}", quote(author));
+        }
+        if let Some(published) = entry.published {
+            let _ = writeln!(out, "published: {}", published.format("%Y-%m-%d"));
+        }
+        if let Some(starred_at) = entry.starred_at {
+            let _ = writeln!(out, "starred: {}", starred_at.format("%Y-%m-%d"));
+        }
+        let tags: Vec<String> = self.tags.iter().map(|t| quote(t)).collect();
+        let _ = writeln!(out, "tags: [{}]", tags.join(", "));
+        let _ = writeln!(out, "presser_id: {}", quote(&entry.id));
+        out.push_str("---\n\n");
+
+        let _ = writeln!(out, "# [{}]({})\n", entry.title, entry.url);
+        if let Some(summary) = self.summary {
+            let _ = writeln!(out, "## Summary\n\n{}\n", summary.trim());
+        }
+
+        let highlights: Vec<&Note> = self
+            .notes
+            .iter()
+            .filter(|n| n.selection_text.is_some())
+            .collect();
+        if !highlights.is_empty() {
+            out.push_str("## Highlights\n\n");
+            for note in highlights {
+                for line in note.selection_text.as_deref().unwrap_or_default().lines() {
+                    let _ = writeln!(out, "> {}", line);
+                }
+                if let Some(text) = &note.note_text {
+                    let _ = writeln!(out, "\n{}", text.trim());
+                }
+                out.push('\n');
+            }
+        }
+
+        let comments: Vec<&str> = self
+            .notes
+            .iter()
+            .filter(|n| n.selection_text.is_none())
+            .filter_map(|n| n.note_text.as_deref())
+            .collect();
+        if !comments.is_empty() {
+            out.push_str("## Notes\n\n");
+            for text in comments {
+                let _ = writeln!(out, "{}\n", text.trim());
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_render_note_file() {
+        let entry = Entry {
+            id: "guid-1".into(),
+            title: "Async \"closures\" are here".into(),
+            url: "https://blog.rust-lang.org/async-closures".into(),
+            published: Some(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap()),
+            starred: true,
+            ..Default::default()
+        };
+        let note = |selection: Option<&str>, text: Option<&str>| Note {
+            id: 1,
+            entry_id: entry.id.clone(),
+            selection_text: selection.map(Into::into),
+            note_text: text.map(Into::into),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let notes = [
+            note(Some("Closures can now\nbe async"), Some("Finally")),
+            note(None, Some("Try this in the parser")),
+        ];
+        let tags = ["rust".to_string()];
+        let file = NoteFile {
+            entry: &entry,
+            feed_title: Some("Rust Blog"),
+            summary: Some("Async closures are stable."),
+            tags: &tags,
+            notes: &notes,
+        }
+        .render();
+
+        assert!(file.starts_with("---\ntitle: \"Async \\\"closures\\\" are here\"\n"));
+        assert!(file.contains("published: 2024-03-15\n"));
+        assert!(file.contains("tags: [\"rust\"]\n"));
+        assert!(file.contains("## Summary\n\nAsync closures are stable.\n"));
+        assert!(file.contains("> Closures can now\n> be async\n\nFinally\n"));
+        assert!(file.contains("## Notes\n\nTry this in the parser\n"));
+
+        let taken = HashSet::from(["2024-03-15-async-closures-are-here".to_string()]);
+        assert_eq!(
+            file_stem(&entry, &taken),
+            "2024-03-15-async-closures-are-here-2"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Subscription lists (`presser import opml`, `presser export opml`)
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use presser_config::{Config, FeedConfig};
+use presser_db::Feed;
+use presser_feeds::opml::{self, OpmlFeed};
+
+use super::add::{existing_file_stems, unique_feed_id};
+
+/// Add the feeds listed in an OPML file, skipping ones already subscribed
+///
+/// All new feeds go into a single `feeds/<name>.toml` file named after the
+/// OPML title (or file name). Folder outlines become feed folders.
+pub async fn import_opml(engine: &crate::Engine, path: &Path) -> Result<()> {
+    let xml = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let document = opml::parse(&xml)?;
+    let name = document
+        .title
+        .as_deref()
+        .or_else(|| path.file_stem().and_then(|s| s.to_str()))
+        .unwrap_or("opml");
+    add_listed_feeds(engine, document.feeds, name).await?;
+    Ok(())
+}
+
+/// Subscribe to `feeds`, skipping ones already subscribed, and return the new feed IDs
+///
+/// All new feeds go into a single `feeds/<name>.toml` file.
+pub(super) async fn add_listed_feeds(
+    engine: &crate::Engine,
+    feeds: Vec<OpmlFeed>,
+    name: &str,
+) -> Result<Vec<String>> {
+    let db = engine.database();
+    let existing: HashMap<String, String> = db
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .map(|f| (f.url, f.id))
+        .collect();
+    let (added, skipped) = plan_import(feeds, &existing, engine.config());
+
+    if !added.is_empty() {
+        let feeds_dir = Config::feeds_dir()?;
+        let stems: HashSet<String> = existing_file_stems(&feeds_dir)?.into_iter().collect();
+        let file = feeds_dir.join(format!("{}.toml", unique_feed_id(name, &stems)));
+
+        let configs: Vec<FeedConfig> = added.iter().map(|(_, config, _)| config.clone()).collect();
+        std::fs::create_dir_all(&feeds_dir)
+            .with_context(|| format!("Failed to create {}", feeds_dir.display()))?;
+        std::fs::write(&file, FeedConfig::list_to_toml(&configs)?)
+            .with_context(|| format!("Failed to write {}", file.display()))?;
+
+        for (n, (id, config, feed)) in added.iter().enumerate() {
+            let stored = db
+                .upsert_feed(&Feed {
+                    id: id.clone(),
+                    url: config.url.clone(),
+                    title: config.name.clone(),
+                    site_url: feed.site_url.clone(),
+                    ..Default::default()
+                })
+                .await;
+            if let Err(e) = stored {
+                // Undo the whole batch so a retry adds it again
+                for (id, _, _) in &added[..n] {
+                    let _ = db.delete_feed(id).await;
+                }
+                let _ = std::fs::remove_file(&file);
+                return Err(e.into());
+            }
+        }
+        println!("Added {} feeds (config: {})", added.len(), file.display());
+        for (id, config, _) in &added {
+            println!("  + {} ({})", config.name, id);
+        }
+    } else {
+        println!("No new feeds to add");
+    }
+
+    if !skipped.is_empty() {
+        println!("Skipped {} feeds:", skipped.len());
+        for reason in &skipped {
+            println!("  - {}", reason);
+        }
+    }
+    Ok(added.into_iter().map(|(id, _, _)| id).collect())
+}
+
+/// New feeds as (ID, config, listing), plus a reason for each skipped listing
+///
+/// `existing` maps stored feed URLs to their IDs.
+fn plan_import(
+    feeds: Vec<OpmlFeed>,
+    existing: &HashMap<String, String>,
+    config: &Config,
+) -> (Vec<(String, FeedConfig, OpmlFeed)>, Vec<String>) {
+    let mut taken: HashSet<String> = existing.values().cloned().collect();
+    let mut seen = HashSet::new();
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+    for feed in feeds {
+        if let Some(id) = existing.get(&feed.url) {
+            skipped.push(format!("{}: already added as '{}'", feed.url, id));
+            continue;
+        }
+        if config.feeds.contains_key(&feed.url) {
+            skipped.push(format!("{}: already in the feed config", feed.url));
+            continue;
+        }
+        if !seen.insert(feed.url.clone()) {
+            skipped.push(format!("{}: listed more than once", feed.url));
+            continue;
+        }
+        let title = feed.title.clone().unwrap_or_else(|| feed.url.clone());
+        let mut feed_config = FeedConfig::new(&feed.url, &title);
+        feed_config.folder = feed.folder.clone();
+        feed_config.tags = feed.tags.clone();
+        if let Err(e) = feed_config.validate() {
+            skipped.push(format!("{}: {}", feed.url, e));
+            continue;
+        }
+        let id = unique_feed_id(&title, &taken);
+        taken.insert(id.clone());
+        added.push((id, feed_config, feed));
+    }
+    (added, skipped)
+}
+
+/// Write all feeds as OPML, filed under their folders
+pub async fn export_opml(engine: &crate::Engine, output: Option<&Path>) -> Result<()> {
+    let feeds: Vec<OpmlFeed> = engine
+        .database()
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .filter(|feed| !crate::engine::is_local_feed(&feed.id))
+        .map(|feed| {
+            let config = engine.config().feeds.get(&feed.url);
+            OpmlFeed {
+                folder: config.and_then(|c| c.folder.clone()),
+                tags: config.map(|c| c.tags.clone()).unwrap_or_default(),
+                url: feed.url,
+                title: Some(feed.title),
+                site_url: feed.site_url,
+            }
+        })
+        .collect();
+    let document = opml::write("Presser subscriptions", &feeds);
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, document)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Exported {} feeds to {}", feeds.len(), path.display());
+        }
+        None => print!("{}", document),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::test_config;
+
+    #[test]
+    fn test_plan_import() {
+        let listing = |url: &str, title: &str| OpmlFeed {
+            url: url.into(),
+            title: Some(title.into()),
+            folder: Some("Tech".into()),
+            tags: vec!["rust".into()],
+            ..Default::default()
+        };
+        let existing = HashMap::from([(
+            "https://blog.rust-lang.org/feed.xml".to_string(),
+            "rust-blog".to_string(),
+        )]);
+
+        let (added, skipped) = plan_import(
+            vec![
+                listing("https://blog.rust-lang.org/feed.xml", "Rust Blog"),
+                listing("https://example.org/rust.xml", "Rust Blog"),
+                listing("https://example.org/rust.xml", "Rust Blog again"),
+                listing("not a url", "Broken"),
+            ],
+            &existing,
+            &test_config(),
+        );
+
+        assert_eq!(added.len(), 1);
+        let (id, config, _) = &added[0];
+        assert_eq!(id, "rust-blog-2");
+        assert_eq!(config.folder.as_deref(), Some("Tech"));
+        assert_eq!(config.tags, ["rust"]);
+        assert_eq!(skipped.len(), 3);
+        assert!(skipped[0].contains("already added as 'rust-blog'"));
+        assert!(skipped[1].contains("more than once"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Static digest site (`presser publish`)
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, Utc};
+use presser_db::EntryFilter;
+
+use crate::digest::{
+    self,
+    site::{self, Period, SitePage},
+};
+
+/// What `presser publish` renders
+#[derive(Debug)]
+pub struct PublishOptions {
+    /// Site directory (created if missing)
+    pub dir: PathBuf,
+
+    /// Days of entries to (re)render pages for
+    pub days: u32,
+
+    /// Site title
+    pub title: String,
+
+    /// URL the site is hosted at, for absolute links in the RSS feed
+    pub base_url: Option<String>,
+}
+
+/// Render daily and weekly digest pages, the index, and the RSS feed into `options.dir`
+///
+/// Pages are rebuilt for every week overlapping the last `days` days (so no
+/// page is written with only part of its entries); older pages are left in
+/// place and stay in the index.
+pub async fn publish(engine: &crate::Engine, options: PublishOptions) -> Result<()> {
+    let generated_at = Utc::now();
+    let since = generated_at - Duration::days(i64::from(options.days));
+    let week_start =
+        since.date_naive() - Duration::days(i64::from(since.weekday().num_days_from_monday()));
+    let filter = EntryFilter::new().since(week_start.and_time(Default::default()).and_utc());
+    let items = digest::collect(engine.database(), filter).await?;
+
+    let mut written = 0;
+    for period in Period::ALL {
+        let period_dir = options.dir.join(period.dir());
+        std::fs::create_dir_all(&period_dir)
+            .with_context(|| format!("Failed to create {}", period_dir.display()))?;
+        for (page, digest) in site::pages(&items, period, generated_at) {
+            write(
+                &options.dir.join(page.path()),
+                &site::render_page(&options.title, &digest),
+            )?;
+            written += 1;
+        }
+    }
+
+    let pages = existing_pages(&options.dir)?;
+    write(
+        &options.dir.join("index.html"),
+        &site::render_index(&options.title, &pages, generated_at),
+    )?;
+    write(&options.dir.join("style.css"), &site::stylesheet())?;
+    write(
+        &options.dir.join("feed.xml"),
+        &site::render_rss(
+            &options.title,
+            options.base_url.as_deref(),
+            &pages,
+            generated_at,
+        ),
+    )?;
+
+    println!(
+        "Published {} pages ({} in total) to {}",
+        written,
+        pages.len(),
+        options.dir.display()
+    );
+    if options.base_url.is_none() {
+        println!("Pass --base-url so feed.xml links work in feed readers");
+    }
+    Ok(())
+}
+
+/// Every page in the site directory, from earlier runs included
+fn existing_pages(dir: &Path) -> Result<Vec<SitePage>> {
+    let mut pages = Vec::new();
+    for period in Period::ALL {
+        let period_dir = dir.join(period.dir());
+        let files = std::fs::read_dir(&period_dir)
+            .with_context(|| format!("Failed to read {}", period_dir.display()))?;
+        for file in files {
+            let path = file?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("html") {
+                continue;
+            }
+            if let Some(page) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| SitePage::parse(period, s))
+            {
+                pages.push(page);
+            }
+        }
+    }
+    pages.sort();
+    Ok(pages)
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,191 @@
+//! Switching from another reader (`presser import miniflux|freshrss|feedly`)
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use presser_db::Entry;
+use presser_feeds::{ReaderItem, ReaderService};
+
+use super::add::reload_after_add;
+use super::opml::add_listed_feeds;
+
+/// Subscribe to a reader's feeds, fetch them, and carry over read and starred state
+///
+/// Entries are matched by URL once Presser has fetched the feeds itself.
+/// Starred entries no longer in their feed are stored from the reader's copy.
+pub async fn import_reader(
+    engine: &crate::Engine,
+    service: &ReaderService,
+    limit: usize,
+) -> Result<()> {
+    println!(
+        "Reading subscriptions and entries from {}...",
+        service.name()
+    );
+    let export = service
+        .export(engine.fetcher().client(), limit)
+        .await
+        .with_context(|| format!("Failed to read from {}", service.name()))?;
+    println!(
+        "Found {} subscriptions, {} entries ({} starred)",
+        export.feeds.len(),
+        export.items.len(),
+        export.items.iter().filter(|i| i.starred).count()
+    );
+
+    let added = add_listed_feeds(engine, export.feeds, service.name()).await?;
+
+    let engine = reload_after_add(engine).await?;
+    let db = engine.database();
+    let feed_ids: HashMap<String, String> = db
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .map(|f| (f.url, f.id))
+        .collect();
+    let touched: HashSet<&String> = export
+        .items
+        .iter()
+        .filter_map(|item| feed_ids.get(&item.feed_url))
+        .chain(&added)
+        .collect();
+
+    if !touched.is_empty() {
+        println!("Fetching {} feeds...", touched.len());
+    }
+    let limit_fetches = engine.config().global.max_concurrent_fetches.max(1);
+    let engine_ref = &engine;
+    stream::iter(touched.iter().copied())
+        .for_each_concurrent(limit_fetches, |id| async move {
+            if let Err(e) = engine_ref.update_feed(id).await {
+                println!("  warning: {}: {:#}", id, e);
+            }
+        })
+        .await;
+
+    let mut known = HashMap::new();
+    for id in &touched {
+        for entry in db.get_entries_for_feed(id, i64::MAX).await? {
+            known.insert(entry.url.clone(), entry);
+        }
+    }
+    let changes = plan_state(&export.items, &feed_ids, &known);
+    for id in &changes.read {
+        db.mark_read(id).await?;
+    }
+    for id in &changes.starred {
+        db.star_entry(id).await?;
+    }
+    for entry in &changes.saved {
+        db.upsert_entry(entry).await?;
+    }
+    println!(
+        "Marked {} entries read and {} starred; kept {} starred entries no longer in their feeds",
+        changes.read.len(),
+        changes.starred.len(),
+        changes.saved.len()
+    );
+    Ok(())
+}
+
+/// State to apply after fetching
+#[derive(Debug, Default)]
+struct StateChanges {
+    /// Stored entries to mark read
+    read: Vec<String>,
+    /// Stored entries to star
+    starred: Vec<String>,
+    /// Starred entries missing from their feeds, to store as they are
+    saved: Vec<Entry>,
+}
+
+/// Match reader items to stored entries by URL
+///
+/// `feed_ids` maps feed URLs to feed IDs and `known` maps entry URLs to
+/// stored entries. Items from unsubscribed feeds, and unstarred items that
+/// were not fetched, are ignored.
+fn plan_state(
+    items: &[ReaderItem],
+    feed_ids: &HashMap<String, String>,
+    known: &HashMap<String, Entry>,
+) -> StateChanges {
+    let mut changes = StateChanges::default();
+    for item in items {
+        let Some(feed_id) = feed_ids.get(&item.feed_url) else {
+            continue;
+        };
+        match known.get(&item.url) {
+            Some(entry) => {
+                if item.read && !entry.read {
+                    changes.read.push(entry.id.clone());
+                }
+                if item.starred && !entry.starred {
+                    changes.starred.push(entry.id.clone());
+                }
+            }
+            None if item.starred => changes.saved.push(Entry {
+                id: item.url.clone(),
+                feed_id: feed_id.clone(),
+                title: item.title.clone(),
+                url: item.url.clone(),
+                published: item.published,
+                content_html: item.content_html.clone(),
+                read: item.read,
+                starred: true,
+                starred_at: Some(chrono::Utc::now()),
+                ..Default::default()
+            }),
+            None => {}
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_state() {
+        let item = |url: &str, read: bool, starred: bool| ReaderItem {
+            feed_url: "https://blog.rust-lang.org/feed.xml".into(),
+            url: url.into(),
+            title: url.into(),
+            read,
+            starred,
+            ..Default::default()
+        };
+        let feed_ids = HashMap::from([(
+            "https://blog.rust-lang.org/feed.xml".to_string(),
+            "rust-blog".to_string(),
+        )]);
+        let known = HashMap::from([(
+            "https://blog.rust-lang.org/a".to_string(),
+            Entry {
+                id: "guid-a".into(),
+                url: "https://blog.rust-lang.org/a".into(),
+                ..Default::default()
+            },
+        )]);
+
+        let mut other_feed = item("https://example.org/x", true, true);
+        other_feed.feed_url = "https://example.org/feed.xml".into();
+        let changes = plan_state(
+            &[
+                item("https://blog.rust-lang.org/a", true, true),
+                item("https://blog.rust-lang.org/old", true, true),
+                item("https://blog.rust-lang.org/gone", true, false),
+                other_feed,
+            ],
+            &feed_ids,
+            &known,
+        );
+
+        assert_eq!(changes.read, ["guid-a"]);
+        assert_eq!(changes.starred, ["guid-a"]);
+        assert_eq!(changes.saved.len(), 1);
+        assert_eq!(changes.saved[0].feed_id, "rust-blog");
+        assert!(changes.saved[0].starred && changes.saved[0].read);
+    }
+}
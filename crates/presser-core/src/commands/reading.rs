@@ -0,0 +1,231 @@
+//! Reading entries without the TUI (`presser read`, `presser show`)
+
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, Utc};
+use presser_db::{Entry, EntryFilter};
+use presser_feeds::ContentExtractor;
+
+use super::truncate;
+
+/// Widest text `presser show` wraps to, however wide the terminal
+const MAX_WIDTH: usize = 100;
+
+/// Pager used when `$PAGER` is unset
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// Options for `presser read`
+#[derive(Debug, Default)]
+pub struct ReadOptions {
+    pub feed: Option<String>,
+    pub tag: Option<String>,
+    pub days: Option<u32>,
+    pub since: Option<NaiveDate>,
+    pub limit: i64,
+    pub all: bool,
+}
+
+/// List unread (or all) entries, newest first
+pub async fn read_entries(engine: &crate::Engine, options: ReadOptions) -> Result<()> {
+    let db = engine.database();
+    let mut filter = EntryFilter::new();
+    if !options.all {
+        filter = filter.unread();
+    }
+    if let Some(feed) = options.feed {
+        filter = filter.feed(feed);
+    }
+    if let Some(tag) = options.tag {
+        filter = filter.tag(tag);
+    }
+    if let Some(days) = options.days {
+        filter = filter.since(Utc::now() - Duration::days(i64::from(days)));
+    }
+    if let Some(date) = options.since {
+        filter = filter.since(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    let total = db.count_entries(&filter).await?;
+    let entries = db.query_entries(&filter.limit(options.limit)).await?;
+    if entries.is_empty() {
+        println!("No entries match.");
+        return Ok(());
+    }
+
+    let feed_titles: HashMap<String, String> = db
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .map(|f| (f.id, f.title))
+        .collect();
+    for entry in &entries {
+        let feed = feed_titles.get(&entry.feed_id).unwrap_or(&entry.feed_id);
+        println!(
+            "{} {}  {:<20}  {}",
+            if entry.read { ' ' } else { '*' },
+            entry
+                .published
+                .unwrap_or(entry.created_at)
+                .format("%Y-%m-%d %H:%M"),
+            truncate(feed, 20),
+            entry.title
+        );
+        println!("    {}", entry.id);
+    }
+    println!();
+    println!(
+        "Showing {} of {} entries. Read one with 'presser show <id>'.",
+        entries.len(),
+        total
+    );
+    Ok(())
+}
+
+/// Print an entry's summary and text (through `$PAGER` on a terminal), then mark it read
+pub async fn show_entry(
+    engine: &crate::Engine,
+    id: &str,
+    pager: bool,
+    keep_unread: bool,
+) -> Result<()> {
+    let db = engine.database();
+    let entry = db.get_entry(id).await?.ok_or_else(|| {
+        anyhow::anyhow!("Entry not found: {} (list entries with 'presser read')", id)
+    })?;
+    let feed_title = db
+        .get_feed(&entry.feed_id)
+        .await?
+        .map_or_else(|| entry.feed_id.clone(), |f| f.title);
+    let summary = db.get_summary(id).await?.map(|s| s.summary_text);
+    let tags = db.get_tags_for_entry(id).await?;
+
+    let width = crossterm::terminal::size()
+        .map_or(80, |(columns, _)| usize::from(columns))
+        .min(MAX_WIDTH);
+    let text = render_entry(&entry, &feed_title, summary.as_deref(), &tags, width);
+    if pager && std::io::stdout().is_terminal() {
+        page(&text)?;
+    } else {
+        print!("{}", text);
+    }
+
+    if !keep_unread && !entry.read {
+        db.mark_read(id).await?;
+    }
+    Ok(())
+}
+
+/// Lay out an entry as plain text wrapped to `width`
+fn render_entry(
+    entry: &Entry,
+    feed_title: &str,
+    summary: Option<&str>,
+    tags: &[String],
+    width: usize,
+) -> String {
+    let mut out = format!(
+        "{}\n{}\n",
+        entry.title,
+        "=".repeat(entry.title.chars().count().min(width))
+    );
+    let mut byline = format!(
+        "{} | {}",
+        feed_title,
+        entry
+            .published
+            .unwrap_or(entry.created_at)
+            .format("%Y-%m-%d %H:%M")
+    );
+    if let Some(author) = &entry.author {
+        byline.push_str(&format!(" | {}", author));
+    }
+    out.push_str(&format!("{}\n{}\n", byline, entry.url));
+    if !tags.is_empty() {
+        out.push_str(&format!("Tags: {}\n", tags.join(", ")));
+    }
+
+    if let Some(summary) = summary {
+        out.push_str("\nSummary\n-------\n");
+        out.push_str(&textwrap::fill(summary, width));
+        out.push('\n');
+    }
+
+    let body = match (
+        &entry.content_text,
+        entry.content_html.as_ref().or(entry.summary.as_ref()),
+    ) {
+        (Some(text), _) => text
+            .split("\n\n")
+            .map(|paragraph| textwrap::fill(paragraph.trim(), width))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        (None, Some(html)) => ContentExtractor::new().html_to_text(html, width),
+        (None, None) => String::new(),
+    };
+    if !body.trim().is_empty() {
+        out.push('\n');
+        out.push_str(body.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Show `text` in the user's pager, falling back to stdout if it cannot start
+fn page(text: &str) -> Result<()> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut args = pager.split_whitespace();
+    let Some(program) = args.next() else {
+        print!("{}", text);
+        return Ok(());
+    };
+    match Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager closes its input when the user quits early
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait()?;
+        }
+        Err(_) => print!("{}", text),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_entry() {
+        let entry = Entry {
+            id: "rust-async".into(),
+            title: "Async closures stabilized".into(),
+            url: "https://example.com/rust-async".into(),
+            author: Some("Ferris".into()),
+            content_text: Some("First paragraph of the article.\n\nSecond one.".into()),
+            summary: Some("<p>Ignored when there is extracted text</p>".into()),
+            ..Default::default()
+        };
+        let text = render_entry(
+            &entry,
+            "Rust Blog",
+            Some("Async closures are now stable."),
+            &["rust".to_string()],
+            20,
+        );
+
+        assert!(text.starts_with("Async closures stabilized\n===================="));
+        assert!(text.contains("Rust Blog | "));
+        assert!(text.contains(" | Ferris\nhttps://example.com/rust-async\nTags: rust\n"));
+        assert!(text.contains("Summary\n-------\nAsync closures are\nnow stable.\n"));
+        assert!(text.ends_with("First paragraph of\nthe article.\n\nSecond one.\n"));
+        assert!(!text.contains("Ignored"));
+    }
+}
@@ -0,0 +1,67 @@
+//! Summarizing on demand (`presser summarize`)
+
+use anyhow::Result;
+use presser_db::{Entry, Summary};
+
+/// What `presser summarize` works on
+#[derive(Debug)]
+pub enum SummarizeTarget {
+    /// An article URL, stored in the saved feed
+    Url(String),
+    /// A stored entry
+    Entry(String),
+    /// Every stored entry without a summary (at most `limit`)
+    Missing { limit: Option<i64> },
+    /// Entries in the summary queue, oldest first (at most `limit`)
+    Queued { limit: Option<i64> },
+}
+
+/// Summarize, store, and print the result
+pub async fn summarize(engine: &crate::Engine, target: SummarizeTarget) -> Result<()> {
+    match target {
+        SummarizeTarget::Url(url) => {
+            let (entry, summary) = engine.summarize_url(&url).await?;
+            print_summary(&entry, &summary);
+            println!("Saved as entry {}", entry.id);
+        }
+        SummarizeTarget::Entry(id) => {
+            let (entry, summary) = engine.summarize_entry(&id).await?;
+            print_summary(&entry, &summary);
+        }
+        SummarizeTarget::Missing { limit } => {
+            let report = engine.summarize_missing(limit).await?;
+            print_report(&report);
+        }
+        SummarizeTarget::Queued { limit } => {
+            let report = engine.drain_summary_queue(limit).await?;
+            print_report(&report);
+            let queued = engine.database().summary_queue_len().await?;
+            if queued > 0 {
+                println!("{} entries still queued", queued);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_report(report: &crate::update::UpdateReport) {
+    println!("Summarized {} entries", report.summarized);
+    if report.deferred > 0 {
+        println!(
+            "{} deferred by the daily token budget (they stay queued)",
+            report.deferred
+        );
+    }
+    for error in &report.errors {
+        println!("  warning: {}", error);
+    }
+}
+
+fn print_summary(entry: &Entry, summary: &Summary) {
+    println!("{}", entry.title);
+    println!("{}", entry.url);
+    println!();
+    println!("{}", textwrap::fill(&summary.summary_text, 80));
+    println!();
+    println!("({})", summary.model);
+}
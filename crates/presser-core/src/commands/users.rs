@@ -0,0 +1,39 @@
+//! API users (`presser users`)
+
+use anyhow::Result;
+
+use crate::server::{hash_token, new_token};
+
+/// Create a user and print their token, which is not stored
+pub async fn add_user(engine: &crate::Engine, name: &str) -> Result<()> {
+    let token = new_token();
+    let user = engine
+        .database()
+        .create_user(name, &hash_token(&token))
+        .await?;
+    println!("Created user {}", user.name);
+    println!("Token (shown only once): {}", token);
+    Ok(())
+}
+
+pub async fn list_users(engine: &crate::Engine) -> Result<()> {
+    let users = engine.database().list_users().await?;
+    if users.is_empty() {
+        println!("No users (add one with `presser users add NAME`)");
+        return Ok(());
+    }
+    for user in users {
+        println!(
+            "{}  (since {})",
+            user.name,
+            user.created_at.format("%Y-%m-%d")
+        );
+    }
+    Ok(())
+}
+
+pub async fn remove_user(engine: &crate::Engine, name: &str) -> Result<()> {
+    engine.database().delete_user(name).await?;
+    println!("Removed user: {}", name);
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! Control interface between `presser ctl` and a running daemon
+//!
+//! The daemon listens on a Unix socket (or a loopback TCP address). Each
+//! request is one line of JSON and is answered by one line of JSON.
+
+use std::fmt;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use presser_config::DaemonConfig;
+use presser_db::DatabaseStats;
+use presser_scheduler::TaskStatus;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::update::{UpdateReport, UpdateSummary};
+
+/// Port used when Unix sockets are unavailable and no address is configured
+#[cfg(not(unix))]
+const DEFAULT_CONTROL_PORT: u16 = 7373;
+
+/// Command sent to the daemon
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, clap::Subcommand)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum ControlRequest {
+    /// Show scheduled tasks and daemon uptime
+    Status,
+    /// Update one feed now
+    Refresh {
+        /// Feed ID
+        #[arg(add = clap_complete::ArgValueCandidates::new(crate::commands::feed_id_candidates))]
+        feed_id: String,
+    },
+    /// Update all enabled feeds now
+    RefreshAll,
+    /// Stop updating a feed on schedule
+    Pause {
+        /// Feed ID
+        #[arg(add = clap_complete::ArgValueCandidates::new(crate::commands::feed_id_candidates))]
+        feed_id: String,
+    },
+    /// Update a paused feed on schedule again
+    Resume {
+        /// Feed ID
+        #[arg(add = clap_complete::ArgValueCandidates::new(crate::commands::feed_id_candidates))]
+        feed_id: String,
+    },
+    /// Reload the configuration and reschedule all tasks
+    ReloadConfig,
+    /// Show database statistics
+    Stats,
+}
+
+/// Daemon's answer to a [`ControlRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ControlResponse {
+    /// Answer to `status`
+    Status(DaemonStatus),
+    /// Answer to `refresh`
+    Update(UpdateReport),
+    /// Answer to `refresh-all`
+    Summary(UpdateSummary),
+    /// Answer to `stats`
+    Stats(DatabaseStats),
+    /// Command carried out
+    Done {
+        /// What happened
+        message: String,
+    },
+    /// Command failed
+    Error {
+        /// Why it failed
+        message: String,
+    },
+}
+
+/// Daemon process and scheduler state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Daemon process ID
+    pub pid: u32,
+
+    /// When the daemon started
+    pub started_at: DateTime<Utc>,
+
+    /// Scheduled tasks, ordered by ID
+    pub tasks: Vec<TaskStatus>,
+}
+
+/// Where the daemon listens for control requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlEndpoint {
+    /// Unix domain socket
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// Loopback TCP address
+    Tcp(SocketAddr),
+}
+
+impl ControlEndpoint {
+    /// Endpoint from the `[daemon]` config section
+    pub fn from_config(config: &DaemonConfig) -> Self {
+        match config.control_addr {
+            Some(addr) => Self::Tcp(addr),
+            #[cfg(unix)]
+            None => Self::Unix(config.control_socket_path()),
+            #[cfg(not(unix))]
+            None => Self::Tcp(SocketAddr::from(([127, 0, 0, 1], DEFAULT_CONTROL_PORT))),
+        }
+    }
+}
+
+impl fmt::Display for ControlEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "{}", path.display()),
+            Self::Tcp(addr) => write!(f, "tcp://{}", addr),
+        }
+    }
+}
+
+/// Bidirectional byte stream a control connection runs over
+pub trait ControlStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> ControlStream for T {}
+
+/// Bound control endpoint; the socket file is removed on drop
+pub enum ControlListener {
+    /// Listening on a Unix socket
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+    /// Listening on a TCP port
+    Tcp(TcpListener),
+}
+
+impl ControlListener {
+    /// Start listening, replacing a stale socket left by a crashed daemon
+    pub async fn bind(endpoint: &ControlEndpoint) -> Result<Self> {
+        match endpoint {
+            #[cfg(unix)]
+            ControlEndpoint::Unix(path) => {
+                use std::os::unix::fs::PermissionsExt;
+
+                if path.exists() {
+                    if tokio::net::UnixStream::connect(path).await.is_ok() {
+                        anyhow::bail!("A daemon is already listening on {}", path.display());
+                    }
+                    std::fs::remove_file(path).with_context(|| {
+                        format!("Failed to remove stale socket {}", path.display())
+                    })?;
+                }
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let listener = tokio::net::UnixListener::bind(path)
+                    .with_context(|| format!("Failed to listen on {}", path.display()))?;
+                // Anyone who can connect can control the daemon
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+                Ok(Self::Unix(listener, path.clone()))
+            }
+            ControlEndpoint::Tcp(addr) => {
+                Ok(Self::Tcp(TcpListener::bind(addr).await.with_context(
+                    || format!("Failed to listen on {}", addr),
+                )?))
+            }
+        }
+    }
+
+    /// Wait for the next connection
+    pub async fn accept(&self) -> Result<Box<dyn ControlStream>> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(listener, _) => Ok(Box::new(listener.accept().await?.0)),
+            Self::Tcp(listener) => Ok(Box::new(listener.accept().await?.0)),
+        }
+    }
+}
+
+impl Drop for ControlListener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Self::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Whether a daemon is accepting connections at `endpoint`
+pub async fn is_running(endpoint: &ControlEndpoint) -> bool {
+    match endpoint {
+        #[cfg(unix)]
+        ControlEndpoint::Unix(path) => tokio::net::UnixStream::connect(path).await.is_ok(),
+        ControlEndpoint::Tcp(addr) => TcpStream::connect(addr).await.is_ok(),
+    }
+}
+
+/// Send one request to the daemon at `endpoint` and wait for the answer
+pub async fn send(endpoint: &ControlEndpoint, request: &ControlRequest) -> Result<ControlResponse> {
+    let connect_error = || {
+        format!(
+            "Failed to connect to the daemon at {} (is it running?)",
+            endpoint
+        )
+    };
+    match endpoint {
+        #[cfg(unix)]
+        ControlEndpoint::Unix(path) => {
+            let stream = tokio::net::UnixStream::connect(path)
+                .await
+                .with_context(connect_error)?;
+            exchange(stream, request).await
+        }
+        ControlEndpoint::Tcp(addr) => {
+            let stream = TcpStream::connect(addr).await.with_context(connect_error)?;
+            exchange(stream, request).await
+        }
+    }
+}
+
+/// Write a request line and read the response line
+pub async fn exchange<S: ControlStream>(
+    stream: S,
+    request: &ControlRequest,
+) -> Result<ControlResponse> {
+    let mut stream = BufReader::new(stream);
+    write_line(stream.get_mut(), request).await?;
+    let mut line = String::new();
+    if stream.read_line(&mut line).await? == 0 {
+        anyhow::bail!("The daemon closed the connection without answering");
+    }
+    serde_json::from_str(&line).context("Invalid response from the daemon")
+}
+
+/// Write `value` as one line of JSON
+pub(crate) async fn write_line<W: AsyncWrite + Unpin, T: Serialize>(
+    writer: &mut W,
+    value: &T,
+) -> Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    writer.flush().await?;
+    Ok(())
+}
@@ -0,0 +1,614 @@
+//! Long-running daemon: scheduled updates plus the control interface
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use presser_config::Config;
+use presser_db::Feed;
+use presser_scheduler::{Scheduler, Task, TaskStatus};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::RwLock;
+
+use crate::control::{
+    self, ControlListener, ControlRequest, ControlResponse, ControlStream, DaemonStatus,
+};
+use crate::metrics::METRICS;
+use crate::reload::ConfigDiff;
+use crate::tasks::{
+    FeedUpdateTask, MaintenanceTask, SummaryQueueTask, AI_GROUP, FETCH_GROUP, MAINTENANCE_GROUP,
+};
+use crate::Engine;
+
+/// Scheduler ID of the maintenance task (cannot clash with a feed ID slug)
+const MAINTENANCE_TASK: &str = "presser:maintenance";
+
+/// Scheduler ID of the summary queue task
+const SUMMARY_QUEUE_TASK: &str = "presser:summary-queue";
+
+/// How often the summary queue is worked through
+const SUMMARY_QUEUE_SCHEDULE: &str = "0 */15 * * * *";
+
+/// AI-heavy tasks run at once unless `[scheduler.groups]` says otherwise
+const DEFAULT_AI_TASKS: usize = 2;
+
+/// How often the network is checked while offline
+const NETWORK_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// What a scheduled task does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Job {
+    Feed,
+    SummaryQueue,
+    Maintenance,
+}
+
+/// Tasks the daemon schedules for `config`: scheduler ID, job, and cron
+/// expression
+fn plan(config: &Config, feeds: &[Feed]) -> Vec<(String, Job, String)> {
+    let mut tasks = Vec::new();
+    if config.scheduler.auto_update {
+        for feed in feeds.iter().filter(|f| f.enabled) {
+            let interval = config
+                .feeds
+                .get(&feed.url)
+                .and_then(|f| f.update_interval.as_deref())
+                .unwrap_or(&config.scheduler.default_interval);
+            tasks.push((feed.id.clone(), Job::Feed, interval.to_string()));
+        }
+        tasks.push((
+            SUMMARY_QUEUE_TASK.to_string(),
+            Job::SummaryQueue,
+            SUMMARY_QUEUE_SCHEDULE.to_string(),
+        ));
+    }
+    if let Some(schedule) = &config.retention.maintenance_schedule {
+        tasks.push((
+            MAINTENANCE_TASK.to_string(),
+            Job::Maintenance,
+            schedule.clone(),
+        ));
+    }
+    tasks
+}
+
+/// The tasks a daemon started now would schedule, with their next runs,
+/// for showing the schedule when no daemon is running
+pub fn preview(config: &Config, feeds: &[Feed]) -> Result<Vec<TaskStatus>> {
+    let mut tasks = Vec::new();
+    for (id, _, schedule) in plan(config, feeds) {
+        let next_run = presser_scheduler::next_run(&schedule)
+            .with_context(|| format!("Invalid schedule for {}", id))?;
+        if let Some(next_run) = next_run {
+            tasks.push(TaskStatus {
+                id,
+                last_run: None,
+                next_run,
+                paused: false,
+                deferred: false,
+            });
+        }
+    }
+    tasks.sort_by_key(|t| t.next_run);
+    Ok(tasks)
+}
+
+/// Scheduler with a concurrency limit per task group, so slow AI work and
+/// maintenance never hold the slots of feed updates, nor they theirs
+fn build_scheduler(config: &Config) -> Result<Scheduler> {
+    let fetches = config.global.max_concurrent_fetches.max(1);
+    let mut scheduler = Scheduler::new(fetches)?;
+    for (group, default) in [
+        (FETCH_GROUP, fetches),
+        (AI_GROUP, DEFAULT_AI_TASKS),
+        (MAINTENANCE_GROUP, 1),
+    ] {
+        scheduler = scheduler.with_group(group, config.scheduler.group_limit(group, default))?;
+    }
+    for group in config.scheduler.groups.keys() {
+        if ![FETCH_GROUP, AI_GROUP, MAINTENANCE_GROUP].contains(&group.as_str()) {
+            tracing::warn!("Unknown scheduler group in [scheduler.groups]: {}", group);
+        }
+    }
+    Ok(scheduler)
+}
+
+/// Scheduler and engine shared by scheduled tasks and control connections
+pub struct Daemon {
+    engine: RwLock<Arc<Engine>>,
+    scheduler: Arc<Scheduler>,
+    config_dir: PathBuf,
+    started_at: DateTime<Utc>,
+}
+
+impl Daemon {
+    /// Schedule updates for every enabled feed; `config_dir` is re-read on `reload-config`
+    pub async fn new(engine: Engine, config_dir: PathBuf) -> Result<Arc<Self>> {
+        let engine = Arc::new(engine);
+        let scheduler = build_scheduler(engine.config())?;
+        let daemon = Self {
+            engine: RwLock::new(engine.clone()),
+            scheduler: Arc::new(scheduler),
+            config_dir,
+            started_at: Utc::now(),
+        };
+        daemon.schedule_all(&engine).await?;
+        Ok(Arc::new(daemon))
+    }
+
+    /// Schedule feed updates and the summary queue (unless `auto_update` is
+    /// off) and maintenance
+    async fn schedule_all(&self, engine: &Arc<Engine>) -> Result<()> {
+        let feeds = engine.database().get_all_feeds().await?;
+        for (id, job, schedule) in plan(engine.config(), &feeds) {
+            let task = task(engine, &id, job);
+            self.scheduler.schedule(id, &schedule, task).await?;
+        }
+        Ok(())
+    }
+
+    /// Carry out one control request
+    pub async fn handle(&self, request: ControlRequest) -> ControlResponse {
+        let engine = self.engine.read().await.clone();
+        let result = match request {
+            ControlRequest::Status => Ok(ControlResponse::Status(DaemonStatus {
+                pid: std::process::id(),
+                started_at: self.started_at,
+                tasks: self.scheduler.tasks().await,
+            })),
+            ControlRequest::Refresh { feed_id } => engine
+                .update_feed(&feed_id)
+                .await
+                .map(ControlResponse::Update),
+            ControlRequest::RefreshAll => engine
+                .update_all_feeds()
+                .await
+                .map(ControlResponse::Summary),
+            ControlRequest::Pause { feed_id } => self
+                .scheduler
+                .pause(&feed_id)
+                .await
+                .map(|_| done(format!("Paused {}", feed_id)))
+                .map_err(Into::into),
+            ControlRequest::Resume { feed_id } => self
+                .scheduler
+                .resume(&feed_id)
+                .await
+                .map(|_| done(format!("Resumed {}", feed_id)))
+                .map_err(Into::into),
+            ControlRequest::ReloadConfig => self.reload().await,
+            ControlRequest::Stats => engine
+                .database()
+                .get_stats()
+                .await
+                .map(ControlResponse::Stats)
+                .map_err(Into::into),
+        };
+        result.unwrap_or_else(|e| ControlResponse::Error {
+            message: format!("{:#}", e),
+        })
+    }
+
+    /// Re-read the config directory and apply what changed
+    ///
+    /// The engine is rebuilt around the parts the changes leave alone (see
+    /// [`Engine::reconfigured`]), and only tasks whose feeds or schedules
+    /// changed are added, removed, or rescheduled; the others keep their
+    /// last and next runs and move to the new engine. Paused tasks stay
+    /// paused. Tasks already running finish on the old engine.
+    async fn reload(&self) -> Result<ControlResponse> {
+        let current = self.engine.read().await.clone();
+        let config = Config::load_from_dir(&self.config_dir)?;
+        let diff = ConfigDiff::between(current.config(), &config);
+        let (engine, diff) = reconfigure(&current, &self.config_dir, config, diff).await?;
+        let changes = diff.describe();
+        for change in &changes {
+            tracing::info!("Configuration: {}", change);
+        }
+        if diff.changes_concurrency() {
+            tracing::warn!("Scheduler concurrency limits change when the daemon restarts");
+        }
+
+        // Feeds added or removed since, by `presser add` and the like, count too
+        let feeds = engine.database().get_all_feeds().await?;
+        let previous: HashMap<String, String> = plan(current.config(), &feeds)
+            .into_iter()
+            .map(|(id, _, schedule)| (id, schedule))
+            .collect();
+        let scheduled: HashSet<String> = self
+            .scheduler
+            .tasks()
+            .await
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        let planned = plan(engine.config(), &feeds);
+        let (mut added, mut rescheduled) = (0, 0);
+        for (id, job, schedule) in &planned {
+            let task = task(&engine, id, *job);
+            if !scheduled.contains(id) {
+                self.scheduler.schedule(id.clone(), schedule, task).await?;
+                added += 1;
+            } else if previous.get(id) != Some(schedule) {
+                self.scheduler.replace(id, Some(schedule), task).await?;
+                rescheduled += 1;
+            } else if !Arc::ptr_eq(&engine, &current) {
+                self.scheduler.replace(id, None, task).await?;
+            }
+        }
+        let removed: Vec<&String> = scheduled
+            .iter()
+            .filter(|id| !planned.iter().any(|(planned, _, _)| planned == *id))
+            .collect();
+        for id in &removed {
+            self.scheduler.unschedule(id).await?;
+        }
+        *self.engine.write().await = engine;
+
+        let tasks = format!(
+            "{} tasks scheduled ({} added, {} removed, {} rescheduled)",
+            planned.len(),
+            added,
+            removed.len(),
+            rescheduled
+        );
+        tracing::info!("Reloaded configuration; {}", tasks);
+        let message = match changes.is_empty() {
+            true => format!("Configuration unchanged; {}", tasks),
+            false => format!("Reloaded configuration; {}\n{}", tasks, changes.join("\n")),
+        };
+        Ok(done(message))
+    }
+
+    /// Answer requests on one connection until the client hangs up
+    pub async fn serve<S: ControlStream>(&self, stream: S) -> Result<()> {
+        let mut stream = BufReader::new(stream);
+        let mut line = String::new();
+        while stream.read_line(&mut line).await? > 0 {
+            let response = match serde_json::from_str(&line) {
+                Ok(request) => self.handle(request).await,
+                Err(e) => ControlResponse::Error {
+                    message: format!("Invalid request: {}", e),
+                },
+            };
+            control::write_line(stream.get_mut(), &response).await?;
+            line.clear();
+        }
+        Ok(())
+    }
+
+    /// Run the scheduler and accept control connections until Ctrl-C
+    pub async fn run(self: Arc<Self>, listener: ControlListener) -> Result<()> {
+        let scheduler = self.scheduler.clone();
+        let scheduler_handle = tokio::spawn(async move { scheduler.start().await });
+        let metrics_interval = self
+            .engine
+            .read()
+            .await
+            .config()
+            .daemon
+            .metrics_log_interval();
+        let metrics_handle = metrics_interval.map(|period| {
+            tokio::spawn(async move {
+                let mut ticks = tokio::time::interval(period);
+                ticks.tick().await;
+                loop {
+                    ticks.tick().await;
+                    tracing::info!("Metrics: {}", METRICS.summary());
+                }
+            })
+        });
+
+        // Feed updates wait while the engine finds the network down, and
+        // catch up once it is back
+        let daemon = self.clone();
+        let network_handle = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(NETWORK_CHECK_INTERVAL);
+            loop {
+                ticks.tick().await;
+                let engine = daemon.engine.read().await.clone();
+                if engine.is_offline() {
+                    engine.check_network().await;
+                }
+                daemon.scheduler.set_offline(engine.is_offline());
+            }
+        });
+
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(stream) => {
+                        let daemon = self.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = daemon.serve(stream).await {
+                                tracing::warn!("Control connection failed: {:#}", e);
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!("Failed to accept control connection: {:#}", e),
+                },
+            }
+        }
+
+        tracing::info!("Shutting down daemon");
+        if let Some(handle) = metrics_handle {
+            handle.abort();
+        }
+        network_handle.abort();
+        tracing::info!("Metrics: {}", METRICS.summary());
+        self.scheduler.stop().await?;
+        scheduler_handle.await??;
+        Ok(())
+    }
+}
+
+/// One daemon per database: an exclusive lock on `<database>.lock`, held until dropped
+///
+/// The lock is released by the OS if the daemon dies, so no stale lock file
+/// has to be cleaned up. The file holds the owner's process ID.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Lock the database at `db_path`, failing if another daemon holds it
+    pub fn acquire(db_path: &Path) -> Result<Self> {
+        let mut name = db_path.as_os_str().to_owned();
+        name.push(".lock");
+        let path = PathBuf::from(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut owner = String::new();
+                let _ = file.read_to_string(&mut owner);
+                anyhow::bail!(
+                    "Another daemon (pid {}) is using {}",
+                    owner.trim(),
+                    db_path.display()
+                );
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", path.display()))
+            }
+        }
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// Engine for the config in `config_dir`, after following recorded feed
+/// moves when `global.auto_fix_urls` is on
+pub(crate) async fn load_engine(config_dir: &Path) -> Result<Engine> {
+    let engine = Engine::with_config(Config::load_from_dir(config_dir)?).await?;
+    if !engine.config().global.auto_fix_urls
+        || engine.apply_url_moves(config_dir, false).await?.is_empty()
+    {
+        return Ok(engine);
+    }
+    // The feed files changed under the engine
+    Engine::with_config(Config::load_from_dir(config_dir)?).await
+}
+
+/// `current` reconfigured for `config`, which differs from its config by
+/// `diff` (`current` itself if nothing did), after following recorded feed
+/// moves like [`load_engine`]; with the difference from the config finally
+/// loaded
+async fn reconfigure(
+    current: &Arc<Engine>,
+    config_dir: &Path,
+    config: Config,
+    diff: ConfigDiff,
+) -> Result<(Arc<Engine>, ConfigDiff)> {
+    let engine = match diff.is_empty() {
+        true => current.clone(),
+        false => Arc::new(current.reconfigured(config, &diff).await?),
+    };
+    if !engine.config().global.auto_fix_urls
+        || engine.apply_url_moves(config_dir, false).await?.is_empty()
+    {
+        return Ok((engine, diff));
+    }
+    let config = Config::load_from_dir(config_dir)?;
+    let diff = ConfigDiff::between(current.config(), &config);
+    Ok((Arc::new(current.reconfigured(config, &diff).await?), diff))
+}
+
+/// Scheduled task doing `job` (for feed `id`) on `engine`
+fn task(engine: &Arc<Engine>, id: &str, job: Job) -> Arc<dyn Task> {
+    match job {
+        Job::Feed => Arc::new(FeedUpdateTask::new(engine.clone(), id.to_string())),
+        Job::SummaryQueue => Arc::new(SummaryQueueTask::new(engine.clone())),
+        Job::Maintenance => Arc::new(MaintenanceTask::new(engine.clone())),
+    }
+}
+
+fn done(message: String) -> ControlResponse {
+    ControlResponse::Done { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::{refused_endpoint, test_config};
+
+    #[test]
+    fn test_instance_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("presser.db");
+        let lock = InstanceLock::acquire(&db_path).unwrap();
+        let err = InstanceLock::acquire(&db_path).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("pid {}", std::process::id())),
+            "{err}"
+        );
+        drop(lock);
+        assert!(InstanceLock::acquire(&db_path).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_control_requests() {
+        let engine = Engine::with_config(test_config()).await.unwrap();
+        presser_db::fixtures::seed(engine.database()).await.unwrap();
+        let daemon = Daemon::new(engine, PathBuf::from(".")).await.unwrap();
+
+        let (client, server) = tokio::io::duplex(4096);
+        let serving = daemon.clone();
+        tokio::spawn(async move { serving.serve(server).await });
+
+        let response = control::exchange(
+            client,
+            &ControlRequest::Pause {
+                feed_id: "rust-blog".into(),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(
+            matches!(response, ControlResponse::Done { .. }),
+            "{:?}",
+            response
+        );
+
+        match daemon.handle(ControlRequest::Status).await {
+            ControlResponse::Status(status) => {
+                let tasks: Vec<_> = status
+                    .tasks
+                    .iter()
+                    .map(|t| (t.id.as_str(), t.paused))
+                    .collect();
+                assert_eq!(
+                    tasks,
+                    [
+                        ("news", false),
+                        (SUMMARY_QUEUE_TASK, false),
+                        ("rust-blog", true)
+                    ]
+                );
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+        let engine = daemon.engine.read().await.clone();
+        let feeds = engine.database().get_all_feeds().await.unwrap();
+        let mut planned: Vec<_> = preview(engine.config(), &feeds)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        planned.sort();
+        assert_eq!(planned, ["news", SUMMARY_QUEUE_TASK, "rust-blog"]);
+
+        match daemon.handle(ControlRequest::Stats).await {
+            ControlResponse::Stats(stats) => assert_eq!(stats.total_feeds, 2),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(matches!(
+            daemon
+                .handle(ControlRequest::Resume {
+                    feed_id: "missing".into()
+                })
+                .await,
+            ControlResponse::Error { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reload_applies_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        // The test config, with the fixtures' in-memory database
+        std::fs::write(
+            dir.path().join("global.toml"),
+            format!(
+                r#"
+            [global]
+            connectivity_check = ""
+            extract_content = false
+
+            [ai]
+            provider = "local"
+            model = "test-model"
+            endpoint = "{}"
+            system_prompt = "test prompt"
+            max_tokens = 100
+            temperature = 0.7
+
+            [database]
+            path = ":memory:"
+            max_connections = 5
+
+            [scheduler]
+            default_interval = "0 0 */6 * * *"
+            "#,
+                refused_endpoint()
+            ),
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("feeds")).unwrap();
+        std::fs::write(
+            dir.path().join("feeds/rust.toml"),
+            r#"
+            [[feed]]
+            url = "https://blog.rust-lang.org/feed.xml"
+            name = "Rust Blog"
+            update_interval = "0 0 * * * *"
+            "#,
+        )
+        .unwrap();
+
+        let engine = Engine::with_config(test_config()).await.unwrap();
+        presser_db::fixtures::seed(engine.database()).await.unwrap();
+        let daemon = Daemon::new(engine, dir.path().to_path_buf()).await.unwrap();
+        daemon.scheduler.pause("rust-blog").await.unwrap();
+        let before = daemon.engine.read().await.clone();
+
+        let message = match daemon.handle(ControlRequest::ReloadConfig).await {
+            ControlResponse::Done { message } => message,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert!(
+            message.starts_with(
+                "Reloaded configuration; 3 tasks scheduled (0 added, 0 removed, 1 rescheduled)"
+            ),
+            "{message}"
+        );
+        assert!(
+            message.contains("feed added: https://blog.rust-lang.org/feed.xml"),
+            "{message}"
+        );
+        assert!(!message.contains("[ai]"), "{message}");
+
+        // A new engine on the same database; the rescheduled feed stays paused
+        let after = daemon.engine.read().await.clone();
+        assert!(!Arc::ptr_eq(&before, &after));
+        assert_eq!(after.database().get_stats().await.unwrap().total_feeds, 2);
+        let tasks = daemon.scheduler.tasks().await;
+        let rust_blog = tasks.iter().find(|t| t.id == "rust-blog").unwrap();
+        assert!(rust_blog.paused);
+
+        match daemon.handle(ControlRequest::ReloadConfig).await {
+            ControlResponse::Done { message } => assert_eq!(
+                message,
+                "Configuration unchanged; 3 tasks scheduled (0 added, 0 removed, 0 rescheduled)"
+            ),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(Arc::ptr_eq(&after, &daemon.engine.read().await.clone()));
+    }
+}
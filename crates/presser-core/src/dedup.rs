@@ -0,0 +1,125 @@
+//! Fingerprints that find the same post in several feeds (planets and other
+//! aggregators mirror their members' posts), handled as
+//! `global.duplicates` says
+//!
+//! A mirror that repeats the original's entry IDs gets IDs of its own (see
+//! [`scoped_id`]) instead of taking the original feed's entries over. Two
+//! entries are the same post if their addresses match once scheme,
+//! `www.`, fragment, trailing slash, and tracking parameters are set aside,
+//! or if their texts match ignoring case and spacing.
+
+use presser_db::{Entry, EntryFingerprint};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// Texts shorter than this (in characters, once normalized) are not
+/// compared: short descriptions like "Read more" say nothing of the post
+const MIN_TEXT_CHARS: usize = 200;
+
+/// Query parameters that only say where a click came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_hsenc",
+    "_hsmi", "ref", "ref_src", "rss",
+];
+
+/// ID of an entry whose feed repeats the ID of another feed's entry, as
+/// mirrors do with the original's GUID
+pub fn scoped_id(feed_id: &str, entry_id: &str) -> String {
+    format!("{}:{}", feed_id, entry_id)
+}
+
+/// Fingerprint of a stored entry
+pub fn fingerprint(entry: &Entry) -> EntryFingerprint {
+    let text = entry.content_text.as_deref().or(entry.summary.as_deref());
+    EntryFingerprint {
+        entry_id: entry.id.clone(),
+        canonical_url: canonical_url(&entry.url),
+        text_hash: text.and_then(text_hash),
+    }
+}
+
+/// `url` without scheme, `www.`, fragment, trailing slash, or tracking
+/// parameters, its remaining parameters sorted; trimmed and lowercased if
+/// it isn't an absolute URL
+pub fn canonical_url(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url.trim()) else {
+        return url.trim().to_lowercase();
+    };
+    let host = parsed.host_str().unwrap_or_default();
+    let mut canonical = host.strip_prefix("www.").unwrap_or(host).to_string();
+    if let Some(port) = parsed.port() {
+        canonical.push_str(&format!(":{}", port));
+    }
+    canonical.push_str(parsed.path().trim_end_matches('/'));
+    let mut params: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| {
+            let name = name.to_lowercase();
+            !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name.as_str())
+        })
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if !params.is_empty() {
+        params.sort();
+        let query: Vec<String> = params
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        canonical.push('?');
+        canonical.push_str(&query.join("&"));
+    }
+    canonical
+}
+
+/// SHA-256 of `text` lowercased with its whitespace collapsed, if it is
+/// long enough to compare
+pub fn text_hash(text: &str) -> Option<String> {
+    let normalized = text
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ");
+    (normalized.chars().count() >= MIN_TEXT_CHARS)
+        .then(|| format!("{:x}", Sha256::digest(normalized.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_url() {
+        let canonical = "blog.example.com/2024/post?id=3&page=2";
+        for url in [
+            "https://blog.example.com/2024/post/?page=2&id=3",
+            "http://www.blog.example.com/2024/post?id=3&utm_source=planet&page=2#comments",
+            "https://blog.example.com/2024/post?page=2&fbclid=abc&id=3&ref=rss",
+        ] {
+            assert_eq!(canonical_url(url), canonical, "{url}");
+        }
+        assert_eq!(
+            canonical_url("https://example.com:8443/"),
+            "example.com:8443"
+        );
+        assert_eq!(canonical_url(" Not a URL "), "not a url");
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let text = "Async closures are stable. ".repeat(10);
+        let entry = Entry {
+            id: "planet-async".into(),
+            url: "https://www.blog.rust-lang.org/async/?utm_medium=feed".into(),
+            summary: Some("Short".into()),
+            content_text: Some(text.clone()),
+            ..Default::default()
+        };
+        let fingerprint = fingerprint(&entry);
+        assert_eq!(fingerprint.canonical_url, "blog.rust-lang.org/async");
+        // Case and spacing aside
+        let reflowed = text.to_uppercase().replace(". ", ".\n\n");
+        assert_eq!(fingerprint.text_hash, text_hash(&reflowed));
+        assert!(fingerprint.text_hash.is_some());
+        assert_eq!(text_hash("Read more"), None);
+    }
+}
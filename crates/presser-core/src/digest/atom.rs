@@ -0,0 +1,81 @@
+//! Atom digest renderer
+//!
+//! Turns a digest into a feed whose entries carry the AI summaries and link
+//! back to the original articles, so the digested stream can be read in any
+//! feed reader. Sections are ignored: each entry appears once.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use super::html::escape;
+use super::{Digest, DigestItem, DigestRenderer};
+
+/// Atom 1.0 feed
+#[derive(Debug, Clone, Default)]
+pub struct AtomRenderer {
+    /// URL the feed is served from (`rel="self"` link)
+    pub self_url: Option<String>,
+}
+
+impl DigestRenderer for AtomRenderer {
+    fn render(&self, digest: &Digest) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        let _ = writeln!(out, "  <title>{}</title>", escape(&digest.title));
+        let _ = writeln!(out, "  <id>urn:presser:digest</id>");
+        let _ = writeln!(
+            out,
+            "  <updated>{}</updated>",
+            digest.generated_at.to_rfc3339()
+        );
+        let _ = writeln!(out, "  <generator>Presser</generator>");
+        if let Some(synthesis) = &digest.synthesis {
+            let _ = writeln!(out, "  <subtitle>{}</subtitle>", escape(synthesis.trim()));
+        }
+        if let Some(url) = &self.self_url {
+            let _ = writeln!(out, "  <link rel=\"self\" href=\"{}\"/>", escape(url));
+        }
+
+        let mut items: Vec<&DigestItem> = Vec::new();
+        let mut seen = HashSet::new();
+        for item in digest.sections.iter().flat_map(|s| &s.items) {
+            if seen.insert(&item.id) {
+                items.push(item);
+            }
+        }
+        items.sort_by_key(|item| std::cmp::Reverse(item.published));
+
+        for item in items {
+            out.push_str("  <entry>\n");
+            let _ = writeln!(out, "    <title>{}</title>", escape(&item.title));
+            let _ = writeln!(out, "    <id>{}</id>", escape(&item.url));
+            let _ = writeln!(out, "    <link href=\"{}\"/>", escape(&item.url));
+            if let Some(url) = &item.discussion {
+                let _ = writeln!(
+                    out,
+                    "    <link rel=\"replies\" type=\"text/html\" href=\"{}\"/>",
+                    escape(url)
+                );
+            }
+            let _ = writeln!(
+                out,
+                "    <updated>{}</updated>",
+                item.published.to_rfc3339()
+            );
+            let _ = writeln!(
+                out,
+                "    <author><name>{}</name></author>",
+                escape(&item.feed_title)
+            );
+            for tag in &item.tags {
+                let _ = writeln!(out, "    <category term=\"{}\"/>", escape(tag));
+            }
+            if let Some(text) = item.summary.as_ref().or(item.description.as_ref()) {
+                let _ = writeln!(out, "    <summary>{}</summary>", escape(text.trim()));
+            }
+            out.push_str("  </entry>\n");
+        }
+        out.push_str("</feed>\n");
+        out
+    }
+}
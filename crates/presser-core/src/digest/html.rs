@@ -0,0 +1,156 @@
+//! HTML digest renderer
+//!
+//! Produces one self-contained page with inline CSS and no external assets,
+//! so the file can be attached to or pasted into an email as is.
+
+use std::fmt::Write;
+
+use super::{web_link, Digest, DigestRenderer};
+
+pub(super) const STYLE: &str = "\
+body{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;max-width:680px;margin:0 auto;padding:24px;color:#222;line-height:1.5}\
+h1{font-size:24px;margin-bottom:4px}\
+h2{font-size:18px;border-bottom:1px solid #ddd;padding-bottom:4px;margin-top:32px}\
+h3{font-size:16px;margin:20px 0 2px}\
+a{color:#1a5fb4;text-decoration:none}\
+.meta{color:#777;font-size:13px;margin:0 0 6px}\
+.summary{margin:0}\
+.synthesis{font-size:17px}\
+.updated{margin:0 0 6px;color:#a15c00;font-size:14px}\
+.description{margin:0;color:#555;border-left:3px solid #ddd;padding-left:8px}";
+
+/// Self-contained HTML page
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HtmlRenderer;
+
+impl DigestRenderer for HtmlRenderer {
+    fn render(&self, digest: &Digest) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+            title = escape(&digest.title)
+        );
+        let _ = writeln!(
+            out,
+            "<p class=\"meta\">{} entries from {} feeds in the last {} day(s)</p>",
+            digest.entry_count, digest.feed_count, digest.days
+        );
+        if let Some(synthesis) = &digest.synthesis {
+            for paragraph in synthesis.trim().split("\n\n") {
+                let _ = writeln!(
+                    out,
+                    "<p class=\"synthesis\">{}</p>",
+                    escape(paragraph.trim())
+                );
+            }
+        }
+        if !digest.coverage.is_empty() {
+            out.push_str("<p class=\"meta\">Coverage by source:</p>\n<ul class=\"meta\">\n");
+            for source in &digest.coverage {
+                let _ = writeln!(
+                    out,
+                    "<li>{}: {} entries in {} topic(s)</li>",
+                    escape(&source.source),
+                    source.entries,
+                    source.topics
+                );
+            }
+            out.push_str("</ul>\n");
+        }
+        write_sections(&mut out, digest);
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Section headings and entries, summaries preferred over feed descriptions
+pub(super) fn write_sections(out: &mut String, digest: &Digest) {
+    for section in &digest.sections {
+        let _ = writeln!(out, "<h2>{}</h2>", escape(&section.heading));
+        for item in &section.items {
+            let _ = writeln!(out, "<h3>{}</h3>", link(&item.url, &item.title));
+            let discussion = match (item.discussion_label(), &item.discussion) {
+                (Some(label), Some(url)) => format!(" &middot; {}", link(url, &label)),
+                (Some(label), None) => format!(" &middot; {}", escape(&label)),
+                _ => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "<p class=\"meta\">{} &middot; {}{}</p>",
+                escape(&item.feed_title),
+                item.published.format("%Y-%m-%d %H:%M"),
+                discussion
+            );
+            if let Some(changes) = &item.changes {
+                let _ = writeln!(
+                    out,
+                    "<p class=\"updated\"><strong>Updated:</strong> {}</p>",
+                    escape(changes)
+                );
+            }
+            if let Some(summary) = &item.summary {
+                let _ = writeln!(out, "<p class=\"summary\">{}</p>", paragraphs(summary));
+            } else if let Some(description) = &item.description {
+                let _ = writeln!(
+                    out,
+                    "<p class=\"description\">{}</p>",
+                    paragraphs(description)
+                );
+            }
+        }
+    }
+}
+
+/// `text` linking to `url`, or just `text` when `url` is not an http(s) link
+fn link(url: &str, text: &str) -> String {
+    match web_link(url) {
+        Some(url) => format!("<a href=\"{}\">{}</a>", escape(&url), escape(text)),
+        None => escape(text),
+    }
+}
+
+/// Escape text for use in HTML content and attribute values
+pub(crate) fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escaped text with line breaks kept
+fn paragraphs(s: &str) -> String {
+    escape(s.trim()).replace('\n', "<br>\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_link() {
+        assert_eq!(
+            link("https://example.com/?a=1&b=\"2\"", "A & B"),
+            "<a href=\"https://example.com/?a=1&amp;b=%222%22\">A &amp; B</a>"
+        );
+        assert_eq!(link("javascript:alert(1)", "<b>"), "&lt;b&gt;");
+    }
+}
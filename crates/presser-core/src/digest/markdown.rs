@@ -0,0 +1,105 @@
+//! Markdown digest renderer
+
+use std::fmt::Write;
+
+use super::{web_link, Digest, DigestRenderer};
+
+/// Markdown with one `##` section per group
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownRenderer;
+
+impl DigestRenderer for MarkdownRenderer {
+    fn render(&self, digest: &Digest) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", digest.title);
+        let _ = writeln!(
+            out,
+            "_{} entries from {} feeds in the last {} day(s)_\n",
+            digest.entry_count, digest.feed_count, digest.days
+        );
+        if let Some(synthesis) = &digest.synthesis {
+            let _ = writeln!(out, "{}\n", synthesis.trim());
+        }
+        if !digest.coverage.is_empty() {
+            let _ = writeln!(out, "**Coverage by source**\n");
+            for source in &digest.coverage {
+                let _ = writeln!(
+                    out,
+                    "- {}: {} entries in {} topic(s)",
+                    escape(&source.source),
+                    source.entries,
+                    source.topics
+                );
+            }
+            out.push('\n');
+        }
+
+        for section in &digest.sections {
+            let _ = writeln!(out, "## {}\n", section.heading);
+            for item in &section.items {
+                let _ = writeln!(out, "### {}\n", link(&item.url, &item.title));
+                let discussion = match (item.discussion_label(), &item.discussion) {
+                    (Some(label), Some(url)) => format!(" · {}", link(url, &label)),
+                    (Some(label), None) => format!(" · {}", label),
+                    _ => String::new(),
+                };
+                let _ = writeln!(
+                    out,
+                    "*{} · {}{}*\n",
+                    escape(&item.feed_title),
+                    item.published.format("%Y-%m-%d %H:%M"),
+                    discussion
+                );
+                if let Some(changes) = &item.changes {
+                    let _ = writeln!(out, "**Updated:** {}\n", escape(changes));
+                }
+                if let Some(summary) = &item.summary {
+                    let _ = writeln!(out, "{}\n", summary.trim());
+                } else if let Some(description) = &item.description {
+                    let _ = writeln!(out, "> {}\n", description.trim().replace('\n', "\n> "));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// `[text](url)`, or just `text` when `url` is not an http(s) link
+fn link(url: &str, text: &str) -> String {
+    match web_link(url) {
+        // Parsing already percent-encoded spaces and angle brackets; parens
+        // would end the destination early
+        Some(url) => format!(
+            "[{}]({})",
+            escape(text),
+            url.replace('(', "%28").replace(')', "%29")
+        ),
+        None => escape(text),
+    }
+}
+
+/// Escape characters that would change the meaning of inline text
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '[' | ']' | '*' | '_' | '`' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link() {
+        assert_eq!(
+            link("https://en.wikipedia.org/wiki/Rust_(language) x", "[Rust]"),
+            "[\\[Rust\\]](https://en.wikipedia.org/wiki/Rust_%28language%29%20x)"
+        );
+        assert_eq!(link("javascript:alert(1)", "Click"), "Click");
+    }
+}
@@ -0,0 +1,589 @@
+//! Digest generation
+//!
+//! A [`Digest`] is built from the database once and handed to a
+//! [`DigestRenderer`] for output, so new formats only need a renderer.
+
+mod atom;
+mod html;
+mod markdown;
+pub mod report;
+pub mod site;
+mod template;
+mod text;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use presser_db::{Database, EntryFilter, EntryOrder};
+use presser_feeds::ContentExtractor;
+use serde::{Deserialize, Serialize};
+
+pub use atom::AtomRenderer;
+pub use html::HtmlRenderer;
+pub use markdown::MarkdownRenderer;
+pub use template::TemplateRenderer;
+pub use text::TextRenderer;
+
+/// Heading used for entries without tags when grouping by tag
+const UNTAGGED: &str = "Untagged";
+
+/// Feed descriptions are flattened to text on one line per paragraph
+const DESCRIPTION_WIDTH: usize = 10_000;
+
+/// Characters of a feed description sent for an entry without a summary
+const SYNTHESIS_DESCRIPTION_CHARS: usize = 300;
+
+/// Output format of a digest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DigestFormat {
+    /// Plain text, wrapped for the terminal
+    #[default]
+    Text,
+    /// Markdown
+    Markdown,
+    /// Self-contained HTML page (inline CSS, no external assets)
+    Html,
+    /// Atom feed of summaries linking to the original articles
+    Atom,
+}
+
+impl FromStr for DigestFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(Self::Text),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "atom" => Ok(Self::Atom),
+            other => anyhow::bail!(
+                "Unknown digest format '{}' (expected text, markdown, html, or atom)",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DigestFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Markdown => "markdown",
+            Self::Html => "html",
+            Self::Atom => "atom",
+        })
+    }
+}
+
+/// Where digest output comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DigestStyle {
+    /// One of the builtin renderers
+    Builtin(DigestFormat),
+    /// A user template file (see [`TemplateRenderer`])
+    Template(PathBuf),
+}
+
+/// How digest entries are grouped into sections
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DigestGrouping {
+    /// One section per feed, by feed title
+    #[default]
+    Feed,
+    /// One section per tag (entries may appear under several tags)
+    Tag,
+    /// One section per publication day, newest first
+    Day,
+}
+
+impl FromStr for DigestGrouping {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "feed" => Ok(Self::Feed),
+            "tag" => Ok(Self::Tag),
+            "day" | "date" => Ok(Self::Day),
+            other => anyhow::bail!(
+                "Unknown digest grouping '{}' (expected feed, tag, or day)",
+                other
+            ),
+        }
+    }
+}
+
+/// Entries to cover, grouped for display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    /// Digest title
+    pub title: String,
+
+    /// When the digest was generated
+    pub generated_at: DateTime<Utc>,
+
+    /// Days covered, counting back from `generated_at`
+    pub days: u32,
+
+    /// Distinct entries in the digest
+    pub entry_count: usize,
+
+    /// Distinct feeds those entries come from
+    pub feed_count: usize,
+
+    /// Entries that have an AI summary
+    pub summarized_count: usize,
+
+    /// AI overview of the entries, written from their stored summaries
+    #[serde(default)]
+    pub synthesis: Option<String>,
+
+    /// Entries per source, most first (filled in by trend reports)
+    #[serde(default)]
+    pub coverage: Vec<SourceCoverage>,
+
+    /// Grouped entries
+    pub sections: Vec<DigestSection>,
+}
+
+/// One group of entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSection {
+    /// Feed title, tag name, or day
+    pub heading: String,
+
+    /// Entries, highest ranked (or newest) first
+    pub items: Vec<DigestItem>,
+}
+
+/// One entry as shown in a digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestItem {
+    /// Entry ID
+    pub id: String,
+
+    /// Entry title
+    pub title: String,
+
+    /// Link to the article
+    pub url: String,
+
+    /// Title of the entry's feed
+    pub feed_title: String,
+
+    /// Publication date (falls back to when the entry was first seen)
+    pub published: DateTime<Utc>,
+
+    /// AI summary, if one was generated
+    pub summary: Option<String>,
+
+    /// Description provided by the feed, as plain text
+    pub description: Option<String>,
+
+    /// Tags on the entry
+    pub tags: Vec<String>,
+
+    /// Page with the entry's comments, if its feed gives one
+    #[serde(default)]
+    pub discussion: Option<String>,
+
+    /// Latest comment count
+    #[serde(default)]
+    pub comments: Option<i64>,
+
+    /// What changed, if the entry's text was edited after it was first
+    /// stored (see [`presser_db::EntryRevision::change_label`])
+    #[serde(default)]
+    pub changes: Option<String>,
+}
+
+impl DigestItem {
+    /// "342 comments", or "Discussion" for a discussion page with no count
+    pub fn discussion_label(&self) -> Option<String> {
+        match (self.comments, &self.discussion) {
+            (Some(n), _) => Some(comments_label(n)),
+            (None, Some(_)) => Some("Discussion".to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// `url` normalized, if it is an http(s) link and safe to render as one
+///
+/// Feeds control entry URLs, and a `javascript:` link in a digest runs when
+/// clicked.
+pub(crate) fn web_link(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .map(String::from)
+}
+
+/// "1 comment", "342 comments"
+pub fn comments_label(n: i64) -> String {
+    format!("{} {}", n, if n == 1 { "comment" } else { "comments" })
+}
+
+/// How much of a report one source contributed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceCoverage {
+    /// Feed title
+    pub source: String,
+
+    /// Entries from the source
+    pub entries: usize,
+
+    /// Topics those entries fall under
+    pub topics: usize,
+}
+
+/// What writing a digest's overview would cost, in tokens (estimated at
+/// four characters a token)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SynthesisCost {
+    /// Entries whose summaries (or feed descriptions) are sent
+    pub entries: usize,
+    /// Tokens sent
+    pub input_tokens: u64,
+    /// Most tokens the reply may take (`[ai].max_tokens`)
+    pub max_output_tokens: u64,
+    /// Tokens left in today's budget, if there is one
+    pub budget_left: Option<u64>,
+}
+
+impl SynthesisCost {
+    /// Most tokens the request may use
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.max_output_tokens
+    }
+}
+
+impl fmt::Display for SynthesisCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "one AI request with {} entries' summaries: ~{} tokens in, up to {} out (~{} in all)",
+            self.entries,
+            self.input_tokens,
+            self.max_output_tokens,
+            self.total()
+        )?;
+        if let Some(left) = self.budget_left {
+            write!(f, "; {} tokens left in today's budget", left)?;
+        }
+        Ok(())
+    }
+}
+
+/// The text an overview is written from: each entry once, its title and
+/// feed over its AI summary (or feed description, cut short)
+pub fn synthesis_input(digest: &Digest) -> (usize, String) {
+    let mut seen = HashSet::new();
+    let mut entries = 0;
+    let mut out = String::new();
+    for item in digest.sections.iter().flat_map(|s| &s.items) {
+        if !seen.insert(&item.id) {
+            continue;
+        }
+        let text = match (&item.summary, &item.description) {
+            (Some(summary), _) => summary.trim().to_string(),
+            (None, Some(description)) => description
+                .chars()
+                .take(SYNTHESIS_DESCRIPTION_CHARS)
+                .collect(),
+            (None, None) => String::new(),
+        };
+        entries += 1;
+        let _ = write!(
+            out,
+            "## {} ({})\n{}\n\n",
+            item.title,
+            item.feed_title,
+            text.trim()
+        );
+    }
+    (entries, out)
+}
+
+/// Tokens `text` is likely to take
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Turns a digest into output text
+pub trait DigestRenderer {
+    /// Render the whole digest
+    fn render(&self, digest: &Digest) -> String;
+}
+
+/// Builtin renderer for a format
+pub fn renderer(format: DigestFormat) -> Box<dyn DigestRenderer> {
+    match format {
+        DigestFormat::Text => Box::new(TextRenderer::default()),
+        DigestFormat::Markdown => Box::new(MarkdownRenderer),
+        DigestFormat::Html => Box::new(HtmlRenderer),
+        DigestFormat::Atom => Box::new(AtomRenderer::default()),
+    }
+}
+
+/// Collect unread entries from the last `days` days into a digest, highest
+/// ranked first
+pub async fn build(db: &Database, days: u32, grouping: DigestGrouping) -> Result<Digest> {
+    let filter = EntryFilter::new().unread().order(EntryOrder::Rank);
+    build_filtered(db, filter, days, grouping).await
+}
+
+/// Collect entries matching `filter` from the last `days` days into a digest
+pub async fn build_filtered(
+    db: &Database,
+    filter: EntryFilter,
+    days: u32,
+    grouping: DigestGrouping,
+) -> Result<Digest> {
+    let generated_at = Utc::now();
+    let items = collect(
+        db,
+        filter.since(generated_at - Duration::days(i64::from(days))),
+    )
+    .await?;
+    let title = format!(
+        "Presser digest for {}",
+        generated_at.format("%A, %B %-d, %Y")
+    );
+    Ok(assemble(title, generated_at, days, items, grouping))
+}
+
+/// Entries matching `filter` as digest items, in the filter's order
+pub async fn collect(db: &Database, filter: EntryFilter) -> Result<Vec<DigestItem>> {
+    let entries = db.query_entries(&filter).await?;
+    let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+    let mut summaries = db.get_summaries_for_entries(&ids).await?;
+    let mut tags = db.get_tags_for_entries(&ids).await?;
+    let mut discussions = db.get_discussions_for_entries(&ids).await?;
+    let mut revisions = db.get_latest_revisions(&ids).await?;
+    let feed_titles: HashMap<String, String> = db
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .map(|f| (f.id, f.title))
+        .collect();
+
+    let extractor = ContentExtractor::new();
+    let items: Vec<DigestItem> = entries
+        .into_iter()
+        .map(|e| {
+            let discussion = discussions.remove(&e.id);
+            DigestItem {
+                feed_title: feed_titles
+                    .get(&e.feed_id)
+                    .cloned()
+                    .unwrap_or_else(|| e.feed_id.clone()),
+                published: e.published.unwrap_or(e.created_at),
+                summary: summaries.remove(&e.id).map(|s| s.summary_text),
+                tags: tags.remove(&e.id).unwrap_or_default(),
+                description: e
+                    .summary
+                    .map(|d| {
+                        extractor
+                            .html_to_text(&d, DESCRIPTION_WIDTH)
+                            .trim()
+                            .to_string()
+                    })
+                    .filter(|d| !d.is_empty()),
+                discussion: discussion.as_ref().and_then(|d| d.url.clone()),
+                comments: discussion.and_then(|d| d.comments),
+                changes: revisions.remove(&e.id).map(|r| r.change_label()),
+                id: e.id,
+                title: e.title,
+                url: e.url,
+            }
+        })
+        .collect();
+    Ok(items)
+}
+
+/// Group `items` into a digest with counts filled in
+fn assemble(
+    title: String,
+    generated_at: DateTime<Utc>,
+    days: u32,
+    items: Vec<DigestItem>,
+    grouping: DigestGrouping,
+) -> Digest {
+    let mut feeds: Vec<&str> = items.iter().map(|i| i.feed_title.as_str()).collect();
+    feeds.sort_unstable();
+    feeds.dedup();
+    Digest {
+        title,
+        generated_at,
+        days,
+        entry_count: items.len(),
+        feed_count: feeds.len(),
+        summarized_count: items.iter().filter(|i| i.summary.is_some()).count(),
+        synthesis: None,
+        coverage: Vec::new(),
+        sections: group(items, grouping),
+    }
+}
+
+/// Split items into sections; items keep their order
+fn group(items: Vec<DigestItem>, grouping: DigestGrouping) -> Vec<DigestSection> {
+    let mut groups: BTreeMap<String, Vec<DigestItem>> = BTreeMap::new();
+    for item in items {
+        match grouping {
+            DigestGrouping::Feed => groups
+                .entry(item.feed_title.clone())
+                .or_default()
+                .push(item),
+            DigestGrouping::Day => groups
+                .entry(item.published.format("%Y-%m-%d").to_string())
+                .or_default()
+                .push(item),
+            DigestGrouping::Tag if item.tags.is_empty() => {
+                groups.entry(UNTAGGED.to_string()).or_default().push(item)
+            }
+            DigestGrouping::Tag => {
+                for tag in &item.tags {
+                    groups.entry(tag.clone()).or_default().push(item.clone());
+                }
+            }
+        }
+    }
+
+    let mut sections: Vec<DigestSection> = groups
+        .into_iter()
+        .map(|(heading, items)| DigestSection { heading, items })
+        .collect();
+    match grouping {
+        DigestGrouping::Day => sections.reverse(),
+        DigestGrouping::Tag => {
+            // Keep "Untagged" last
+            sections.sort_by_key(|s| s.heading == UNTAGGED);
+        }
+        DigestGrouping::Feed => {}
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_db::fixtures;
+
+    #[tokio::test]
+    async fn test_build_and_render() {
+        let (db, _) = fixtures::seeded().await.unwrap();
+        db.set_discussion(&presser_db::EntryDiscussion {
+            entry_id: "rust-async".into(),
+            url: Some("https://news.ycombinator.com/item?id=42".into()),
+            comment_feed: None,
+            comments: Some(342),
+            checked_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+        db.add_revision(&presser_db::EntryRevision {
+            entry_id: "rust-async".into(),
+            previous_text: "Async closures are coming.".into(),
+            diff: "-Async closures are coming.\n+Async closures are now stable.\n".into(),
+            lines_added: 1,
+            lines_removed: 1,
+            change_summary: Some("Stabilization confirmed".into()),
+            created_at: Utc::now(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let digest = build(&db, 7, DigestGrouping::Feed).await.unwrap();
+        assert_eq!(
+            (
+                digest.entry_count,
+                digest.feed_count,
+                digest.summarized_count
+            ),
+            (3, 2, 1)
+        );
+        let headings: Vec<_> = digest.sections.iter().map(|s| s.heading.as_str()).collect();
+        assert_eq!(headings, ["Example News", "Rust Blog"]);
+        assert_eq!(digest.sections[0].items[0].id, "news-election");
+
+        let by_tag = build(&db, 7, DigestGrouping::Tag).await.unwrap();
+        let headings: Vec<_> = by_tag.sections.iter().map(|s| s.heading.as_str()).collect();
+        assert_eq!(headings, ["politics", "rust", UNTAGGED]);
+
+        let by_day = build(&db, 7, DigestGrouping::Day).await.unwrap();
+        assert!(by_day
+            .sections
+            .windows(2)
+            .all(|w| w[0].heading > w[1].heading));
+
+        for format in [
+            DigestFormat::Text,
+            DigestFormat::Markdown,
+            DigestFormat::Html,
+            DigestFormat::Atom,
+        ] {
+            let output = renderer(format).render(&digest);
+            assert!(output.contains("Async closures stabilized"), "{format}");
+            assert!(
+                output.contains("Async closures are now stable."),
+                "{format}"
+            );
+            assert!(
+                output.contains("https://example.com/rust-async"),
+                "{format}"
+            );
+            assert!(
+                output.contains("news.ycombinator.com/item?id=42"),
+                "{format}"
+            );
+            if format != DigestFormat::Atom {
+                assert!(output.contains("342 comments"), "{format}");
+                assert!(output.contains("Stabilization confirmed"), "{format}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_synthesis() {
+        let (db, _) = fixtures::seeded().await.unwrap();
+        let mut digest = build(&db, 7, DigestGrouping::Tag).await.unwrap();
+
+        // Each entry once, even under several tags
+        let (entries, input) = synthesis_input(&digest);
+        assert_eq!(entries, 3);
+        assert_eq!(input.matches("## ").count(), 3);
+        assert!(input.contains(
+            "## Async closures stabilized (Rust Blog)\nAsync closures are now stable.\n\n"
+        ));
+        assert_eq!(estimate_tokens("abcdefghi"), 3);
+
+        digest.synthesis = Some("Rust had a busy week.\n\nElections too.".into());
+        for format in [
+            DigestFormat::Text,
+            DigestFormat::Markdown,
+            DigestFormat::Html,
+            DigestFormat::Atom,
+        ] {
+            let output = renderer(format).render(&digest);
+            assert!(output.contains("Rust had a busy week."), "{format}");
+        }
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            "MD".parse::<DigestFormat>().unwrap(),
+            DigestFormat::Markdown
+        );
+        assert!("pdf".parse::<DigestFormat>().is_err());
+        assert_eq!(
+            "day".parse::<DigestGrouping>().unwrap(),
+            DigestGrouping::Day
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Trend reports (`presser report`)
+//!
+//! A report is a [`Digest`] of a period's entries, read or not, grouped by
+//! topic instead of feed. Topics are an entry's tags and the topics of its
+//! title ("World Cup", "compiler"); the topic shared by the most entries
+//! takes them all, then the next among those left, until no topic is shared.
+//! The report's synthesis is an AI narrative of the recurring themes.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use presser_db::{Database, EntryFilter, EntryOrder};
+
+use super::{collect, Digest, DigestItem, DigestSection, SourceCoverage};
+
+/// Most topic sections in a report, not counting [`OTHER`]
+const MAX_TOPICS: usize = 10;
+
+/// Fewest entries that make a topic
+const MIN_TOPIC_ENTRIES: usize = 2;
+
+/// Heading for entries sharing no topic
+const OTHER: &str = "Other";
+
+/// Entries per topic sent for the narrative
+const TRENDS_ENTRIES_PER_TOPIC: usize = 8;
+
+/// Characters of each entry's summary (or feed description) sent
+const TRENDS_SUMMARY_CHARS: usize = 200;
+
+/// Span of time a report covers, ending now
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportPeriod {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl ReportPeriod {
+    /// Days covered
+    pub fn days(self) -> u32 {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+
+    fn adjective(self) -> &'static str {
+        match self {
+            Self::Day => "daily",
+            Self::Week => "weekly",
+            Self::Month => "monthly",
+        }
+    }
+}
+
+impl FromStr for ReportPeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" => Ok(Self::Day),
+            "week" | "weekly" => Ok(Self::Week),
+            "month" | "monthly" => Ok(Self::Month),
+            other => anyhow::bail!(
+                "Unknown report period '{}' (expected day, week, or month)",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ReportPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        })
+    }
+}
+
+/// Collect the period's entries into a report, by topic, highest ranked
+/// first within each
+pub async fn build(db: &Database, period: ReportPeriod) -> Result<Digest> {
+    let generated_at = Utc::now();
+    let start = generated_at - Duration::days(i64::from(period.days()));
+    let filter = EntryFilter::new().since(start).order(EntryOrder::Rank);
+    let items = collect(db, filter).await?;
+    let title = format!(
+        "Presser {} report, {} to {}",
+        period.adjective(),
+        start.format("%b %-d"),
+        generated_at.format("%b %-d, %Y")
+    );
+    Ok(assemble(title, generated_at, period.days(), items))
+}
+
+/// Cluster `items` into topic sections with counts and coverage filled in
+fn assemble(
+    title: String,
+    generated_at: DateTime<Utc>,
+    days: u32,
+    items: Vec<DigestItem>,
+) -> Digest {
+    let feeds: HashSet<&str> = items.iter().map(|i| i.feed_title.as_str()).collect();
+    let (entry_count, feed_count) = (items.len(), feeds.len());
+    let summarized_count = items.iter().filter(|i| i.summary.is_some()).count();
+    let (sections, topics) = cluster(items);
+    Digest {
+        title,
+        generated_at,
+        days,
+        entry_count,
+        feed_count,
+        summarized_count,
+        synthesis: None,
+        coverage: coverage(&sections[..topics], &sections[topics..]),
+        sections,
+    }
+}
+
+/// An item's topics as (lowercased key, label) pairs, each once
+fn topics_of(item: &DigestItem) -> Vec<(String, String)> {
+    let mut topics: Vec<(String, String)> = Vec::new();
+    for label in item
+        .tags
+        .iter()
+        .cloned()
+        .chain(crate::mute::title_topics(&item.title))
+    {
+        let key = label.to_lowercase();
+        if !topics.iter().any(|(k, _)| *k == key) {
+            topics.push((key, label));
+        }
+    }
+    topics
+}
+
+/// Sections of items under the topics most of them share (see module
+/// docs), largest first, then one for the rest; also how many are topics
+fn cluster(items: Vec<DigestItem>) -> (Vec<DigestSection>, usize) {
+    let topics: Vec<Vec<(String, String)>> = items.iter().map(topics_of).collect();
+    let mut left: Vec<usize> = (0..items.len()).collect();
+    let mut clusters: Vec<(String, Vec<usize>)> = Vec::new();
+    while clusters.len() < MAX_TOPICS {
+        let mut counts: HashMap<&str, (usize, &str)> = HashMap::new();
+        for &i in &left {
+            for (key, label) in &topics[i] {
+                counts.entry(key).or_insert((0, label)).0 += 1;
+            }
+        }
+        // Most entries, then the longer topic ("world cup" over "world"),
+        // then alphabetically for a stable choice
+        let best = counts.into_iter().max_by(|(a, (x, _)), (b, (y, _))| {
+            x.cmp(y)
+                .then_with(|| a.split(' ').count().cmp(&b.split(' ').count()))
+                .then_with(|| b.cmp(a))
+        });
+        let Some((key, (count, label))) = best else {
+            break;
+        };
+        if count < MIN_TOPIC_ENTRIES {
+            break;
+        }
+        let (members, rest) = left
+            .into_iter()
+            .partition(|&i| topics[i].iter().any(|(k, _)| k == key));
+        left = rest;
+        clusters.push((label.to_string(), members));
+    }
+    let topic_count = clusters.len();
+    if !left.is_empty() {
+        clusters.push((OTHER.to_string(), left));
+    }
+
+    let mut items: Vec<Option<DigestItem>> = items.into_iter().map(Some).collect();
+    let sections = clusters
+        .into_iter()
+        .map(|(label, members)| {
+            let items: Vec<DigestItem> = members
+                .into_iter()
+                .filter_map(|i| items[i].take())
+                .collect();
+            let sources: HashSet<&str> = items.iter().map(|i| i.feed_title.as_str()).collect();
+            DigestSection {
+                heading: format!(
+                    "{} ({}, {})",
+                    label,
+                    plural(items.len(), "entry", "entries"),
+                    plural(sources.len(), "source", "sources")
+                ),
+                items,
+            }
+        })
+        .collect();
+    (sections, topic_count)
+}
+
+/// "1 entry", "2 entries"
+fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+/// Entries per source and the topics they fall under, most entries first
+fn coverage(topics: &[DigestSection], other: &[DigestSection]) -> Vec<SourceCoverage> {
+    let mut sources: BTreeMap<&str, (usize, HashSet<usize>)> = BTreeMap::new();
+    for (i, section) in topics.iter().chain(other).enumerate() {
+        let topic = i < topics.len();
+        for item in &section.items {
+            let (entries, covered) = sources.entry(&item.feed_title).or_default();
+            *entries += 1;
+            if topic {
+                covered.insert(i);
+            }
+        }
+    }
+    let mut coverage: Vec<SourceCoverage> = sources
+        .into_iter()
+        .map(|(source, (entries, topics))| SourceCoverage {
+            source: source.to_string(),
+            entries,
+            topics: topics.len(),
+        })
+        .collect();
+    coverage.sort_by_key(|c| Reverse(c.entries));
+    coverage
+}
+
+/// The text a report's narrative is written from: each topic with its
+/// sources, over its top entries' titles and summaries (cut short)
+pub fn trends_input(report: &Digest) -> (usize, String) {
+    let mut entries = 0;
+    let mut out = String::new();
+    for section in &report.sections {
+        let mut sources: BTreeMap<&str, usize> = BTreeMap::new();
+        for item in &section.items {
+            *sources.entry(&item.feed_title).or_default() += 1;
+        }
+        let mut sources: Vec<(&str, usize)> = sources.into_iter().collect();
+        sources.sort_by_key(|(_, n)| Reverse(*n));
+        let sources: Vec<String> = sources
+            .iter()
+            .map(|(source, n)| format!("{} {}", source, n))
+            .collect();
+        let _ = writeln!(
+            out,
+            "## {}\nSources: {}",
+            section.heading,
+            sources.join(", ")
+        );
+        for item in section.items.iter().take(TRENDS_ENTRIES_PER_TOPIC) {
+            let text: String = item
+                .summary
+                .as_ref()
+                .or(item.description.as_ref())
+                .map(|text| text.trim().chars().take(TRENDS_SUMMARY_CHARS).collect())
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "- {} ({}): {}",
+                item.title,
+                item.feed_title,
+                text.replace('\n', " ")
+            );
+            entries += 1;
+        }
+        if let Some(more) = section.items.len().checked_sub(TRENDS_ENTRIES_PER_TOPIC) {
+            if more > 0 {
+                let _ = writeln!(out, "- and {} more", more);
+            }
+        }
+        out.push('\n');
+    }
+    (entries, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assemble_report() {
+        let item = |id: &str, title: &str, feed: &str, tags: &[&str]| DigestItem {
+            id: id.into(),
+            title: title.into(),
+            url: format!("https://example.com/{}", id),
+            feed_title: feed.into(),
+            published: Utc::now(),
+            summary: Some(format!("Summary of {}", id)),
+            description: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            discussion: None,
+            comments: None,
+            changes: None,
+        };
+        let items = vec![
+            item("a", "Argentina wins the World Cup", "BBC", &[]),
+            item("b", "Rust 1.80 released", "Rust Blog", &["rust"]),
+            item("c", "World Cup final draws record audience", "ESPN", &[]),
+            item("d", "Async closures in Rust", "Hacker News", &["rust"]),
+            item("e", "World Cup hosts named", "BBC", &[]),
+            item("f", "Local bakery reopens", "Gazette", &[]),
+        ];
+        let report = assemble("Report".into(), Utc::now(), 7, items);
+        assert_eq!((report.entry_count, report.feed_count), (6, 5));
+
+        let sections: Vec<(&str, Vec<&str>)> = report
+            .sections
+            .iter()
+            .map(|s| {
+                let ids = s.items.iter().map(|i| i.id.as_str()).collect();
+                (s.heading.as_str(), ids)
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                ("World Cup (3 entries, 2 sources)", vec!["a", "c", "e"]),
+                ("rust (2 entries, 2 sources)", vec!["b", "d"]),
+                ("Other (1 entry, 1 source)", vec!["f"]),
+            ]
+        );
+        assert_eq!(
+            report.coverage[0],
+            SourceCoverage {
+                source: "BBC".into(),
+                entries: 2,
+                topics: 1
+            }
+        );
+        let gazette = report.coverage.iter().find(|c| c.source == "Gazette");
+        assert_eq!(gazette.map(|c| (c.entries, c.topics)), Some((1, 0)));
+
+        let (entries, input) = trends_input(&report);
+        assert_eq!(entries, 6);
+        assert!(input.starts_with(
+            "## World Cup (3 entries, 2 sources)\nSources: BBC 2, ESPN 1\n\
+             - Argentina wins the World Cup (BBC): Summary of a\n"
+        ));
+
+        assert_eq!(
+            "Weekly".parse::<ReportPeriod>().unwrap(),
+            ReportPeriod::Week
+        );
+        assert!("year".parse::<ReportPeriod>().is_err());
+    }
+}
@@ -0,0 +1,331 @@
+//! Static site of digest pages (`presser publish`)
+//!
+//! The site is a directory of plain files: one page per day under `daily/`,
+//! one per ISO week under `weekly/`, plus `index.html`, `style.css`, and an
+//! RSS feed (`feed.xml`) announcing the pages. The index and feed are built
+//! from the page file names, so pages from earlier runs stay listed after
+//! their entries have been pruned from the database.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, NaiveDate, Utc, Weekday};
+
+use super::html::{escape, write_sections, STYLE};
+use super::{assemble, Digest, DigestGrouping, DigestItem};
+
+/// Pages listed in the RSS feed
+const FEED_PAGES: usize = 50;
+
+/// Rules added to the digest style for navigation and the index
+const SITE_STYLE: &str = "\
+nav{font-size:14px;margin-bottom:16px}\
+footer{color:#777;font-size:13px;margin-top:40px;border-top:1px solid #ddd;padding-top:8px}\
+ul.pages{list-style:none;padding:0}\
+ul.pages li{margin:4px 0}";
+
+/// How much a page covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Period {
+    /// One UTC day, slug `2024-03-15`
+    Daily,
+    /// One ISO week, slug `2024-W11`
+    Weekly,
+}
+
+impl Period {
+    /// Both periods, in index order
+    pub const ALL: [Period; 2] = [Period::Daily, Period::Weekly];
+
+    /// Site subdirectory holding this period's pages
+    pub fn dir(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    fn slug(self, time: DateTime<Utc>) -> String {
+        match self {
+            Self::Daily => time.format("%Y-%m-%d").to_string(),
+            Self::Weekly => time.format("%G-W%V").to_string(),
+        }
+    }
+
+    fn days(self) -> u32 {
+        match self {
+            Self::Daily => 1,
+            Self::Weekly => 7,
+        }
+    }
+}
+
+/// A page of the site, identified by its file name
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SitePage {
+    /// Period the page covers
+    pub period: Period,
+
+    /// File stem (see [`Period`])
+    pub slug: String,
+}
+
+impl SitePage {
+    /// Page for a file stem, if it is a valid slug for `period`
+    pub fn parse(period: Period, slug: &str) -> Option<Self> {
+        let page = Self {
+            period,
+            slug: slug.to_string(),
+        };
+        page.start().map(|_| page)
+    }
+
+    /// Path relative to the site root
+    pub fn path(&self) -> String {
+        format!("{}/{}.html", self.period.dir(), self.slug)
+    }
+
+    /// First day the page covers
+    fn start(&self) -> Option<NaiveDate> {
+        match self.period {
+            Period::Daily => NaiveDate::parse_from_str(&self.slug, "%Y-%m-%d").ok(),
+            Period::Weekly => {
+                let (year, week) = self.slug.split_once("-W")?;
+                NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)
+            }
+        }
+    }
+
+    /// Human-readable title, e.g. "Friday, March 15, 2024" or "Week 11, 2024"
+    pub fn title(&self) -> String {
+        match (self.period, self.start()) {
+            (Period::Daily, Some(day)) => day.format("%A, %B %-d, %Y").to_string(),
+            (Period::Weekly, Some(monday)) => monday.format("Week %V, %G").to_string(),
+            (_, None) => self.slug.clone(),
+        }
+    }
+}
+
+/// Split items into one digest per page, grouped by feed
+pub fn pages(
+    items: &[DigestItem],
+    period: Period,
+    generated_at: DateTime<Utc>,
+) -> Vec<(SitePage, Digest)> {
+    let mut by_slug: BTreeMap<String, Vec<DigestItem>> = BTreeMap::new();
+    for item in items {
+        by_slug
+            .entry(period.slug(item.published))
+            .or_default()
+            .push(item.clone());
+    }
+    by_slug
+        .into_iter()
+        .rev()
+        .map(|(slug, items)| {
+            let page = SitePage { period, slug };
+            let digest = assemble(
+                page.title(),
+                generated_at,
+                period.days(),
+                items,
+                DigestGrouping::Feed,
+            );
+            (page, digest)
+        })
+        .collect()
+}
+
+/// Shared stylesheet
+pub fn stylesheet() -> String {
+    format!("{}{}\n", STYLE, SITE_STYLE)
+}
+
+/// One digest page; links are relative to the page's subdirectory
+pub fn render_page(site_title: &str, digest: &Digest) -> String {
+    let mut out = head(&format!("{} - {}", digest.title, site_title), "../");
+    let _ = writeln!(
+        out,
+        "<nav><a href=\"../index.html\">&larr; {}</a></nav>\n<h1>{}</h1>",
+        escape(site_title),
+        escape(&digest.title)
+    );
+    let _ = writeln!(
+        out,
+        "<p class=\"meta\">{} entries from {} feeds, {} summarized</p>",
+        digest.entry_count, digest.feed_count, digest.summarized_count
+    );
+    write_sections(&mut out, digest);
+    out.push_str(&footer(digest.generated_at));
+    out
+}
+
+/// Index listing every page, newest first, one list per period
+pub fn render_index(site_title: &str, pages: &[SitePage], generated_at: DateTime<Utc>) -> String {
+    let mut out = head(site_title, "");
+    let _ = writeln!(out, "<h1>{}</h1>", escape(site_title));
+    if pages.is_empty() {
+        out.push_str("<p class=\"meta\">No digests yet.</p>\n");
+    }
+    for period in Period::ALL {
+        let mut listed: Vec<&SitePage> = pages.iter().filter(|p| p.period == period).collect();
+        if listed.is_empty() {
+            continue;
+        }
+        listed.sort_by(|a, b| b.slug.cmp(&a.slug));
+        let heading = match period {
+            Period::Daily => "Daily digests",
+            Period::Weekly => "Weekly digests",
+        };
+        let _ = writeln!(out, "<h2>{}</h2>\n<ul class=\"pages\">", heading);
+        for page in listed {
+            let _ = writeln!(
+                out,
+                "<li><a href=\"{}\">{}</a></li>",
+                escape(&page.path()),
+                escape(&page.title())
+            );
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str(&footer(generated_at));
+    out
+}
+
+/// RSS 2.0 feed of the newest pages
+///
+/// Feed readers need absolute links, so links are only usable when
+/// `base_url` (where the site is hosted) is given.
+pub fn render_rss(
+    site_title: &str,
+    base_url: Option<&str>,
+    pages: &[SitePage],
+    generated_at: DateTime<Utc>,
+) -> String {
+    let base = base_url.map(|b| format!("{}/", b.trim_end_matches('/')));
+    let base = base.as_deref().unwrap_or("");
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<rss version=\"2.0\">\n<channel>\n");
+    let _ = writeln!(out, "  <title>{}</title>", escape(site_title));
+    let _ = writeln!(out, "  <link>{}index.html</link>", escape(base));
+    let _ = writeln!(
+        out,
+        "  <description>Reading digests published by Presser</description>"
+    );
+    let _ = writeln!(
+        out,
+        "  <lastBuildDate>{}</lastBuildDate>",
+        generated_at.to_rfc2822()
+    );
+
+    let mut dated: Vec<(NaiveDate, &SitePage)> = pages
+        .iter()
+        .filter_map(|page| Some((page.start()?, page)))
+        .collect();
+    dated.sort_by(|a, b| b.cmp(a));
+    for (start, page) in dated.into_iter().take(FEED_PAGES) {
+        let link = format!("{}{}", base, page.path());
+        let kind = match page.period {
+            Period::Daily => "Daily digest",
+            Period::Weekly => "Weekly digest",
+        };
+        out.push_str("  <item>\n");
+        let _ = writeln!(
+            out,
+            "    <title>{}: {}</title>",
+            kind,
+            escape(&page.title())
+        );
+        let _ = writeln!(out, "    <link>{}</link>", escape(&link));
+        let _ = writeln!(out, "    <guid>{}</guid>", escape(&link));
+        let _ = writeln!(
+            out,
+            "    <pubDate>{}</pubDate>",
+            start.and_time(Default::default()).and_utc().to_rfc2822()
+        );
+        out.push_str("  </item>\n");
+    }
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+/// Document start up to the opening body tag; `root` is the relative path to the site root
+fn head(title: &str, root: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n\
+         <link rel=\"alternate\" type=\"application/rss+xml\" href=\"{root}feed.xml\">\n\
+         </head>\n<body>\n",
+        title = escape(title),
+        root = root
+    )
+}
+
+fn footer(generated_at: DateTime<Utc>) -> String {
+    format!(
+        "<footer>Generated by Presser on {}</footer>\n</body>\n</html>\n",
+        generated_at.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_pages_and_index() {
+        let item = |id: &str, day: u32| DigestItem {
+            id: id.into(),
+            title: format!("Entry {}", id),
+            url: format!("https://example.com/{}", id),
+            feed_title: "Example".into(),
+            published: Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap(),
+            summary: Some("Summary".into()),
+            description: None,
+            tags: vec![],
+            discussion: None,
+            comments: None,
+            changes: None,
+        };
+        // Friday the 15th and Sunday the 17th are in week 11, Monday the 18th in week 12
+        let items = [item("a", 18), item("b", 17), item("c", 15)];
+        let now = Utc.with_ymd_and_hms(2024, 3, 18, 13, 0, 0).unwrap();
+
+        let daily = pages(&items, Period::Daily, now);
+        let slugs: Vec<_> = daily.iter().map(|(p, _)| p.slug.as_str()).collect();
+        assert_eq!(slugs, ["2024-03-18", "2024-03-17", "2024-03-15"]);
+        let weekly = pages(&items, Period::Weekly, now);
+        let counts: Vec<_> = weekly
+            .iter()
+            .map(|(p, d)| (p.slug.as_str(), d.entry_count))
+            .collect();
+        assert_eq!(counts, [("2024-W12", 1), ("2024-W11", 2)]);
+        assert_eq!(weekly[1].0.title(), "Week 11, 2024");
+
+        let page = render_page("My reading", &daily[2].1);
+        assert!(page.contains("<h1>Friday, March 15, 2024</h1>"));
+        assert!(page.contains("href=\"../style.css\""));
+        assert!(page.contains("https://example.com/c"));
+
+        let all: Vec<SitePage> = daily.into_iter().chain(weekly).map(|(p, _)| p).collect();
+        let index = render_index("My reading", &all, now);
+        assert!(index.contains("<a href=\"weekly/2024-W11.html\">Week 11, 2024</a>"));
+        let rss = render_rss(
+            "My reading",
+            Some("https://me.github.io/reading/"),
+            &all,
+            now,
+        );
+        assert!(rss.contains("<link>https://me.github.io/reading/daily/2024-03-18.html</link>"));
+        assert_eq!(rss.matches("<item>").count(), 5);
+
+        assert_eq!(
+            SitePage::parse(Period::Weekly, "2024-W11"),
+            Some(all[4].clone())
+        );
+        assert_eq!(SitePage::parse(Period::Daily, "notes"), None);
+    }
+}
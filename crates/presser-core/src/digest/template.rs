@@ -0,0 +1,154 @@
+//! User-supplied digest templates (Jinja syntax via minijinja)
+//!
+//! Templates see the digest fields at the top level (`title`,
+//! `generated_at`, `days`, `entry_count`, `feed_count`, `summarized_count`,
+//! `synthesis` (the AI overview, or none), `coverage` (entries per source,
+//! in trend reports), `sections`) plus:
+//!
+//! - `entries`: every digest item once, newest first
+//! - `summaries`: AI summary text keyed by entry ID
+//! - `feeds`: all subscribed feeds
+//! - `stats`: database totals (`total_feeds`, `total_entries`, ...)
+//!
+//! Output is HTML-escaped when the template file name ends in `.html`,
+//! `.htm`, or `.xml`. Dates can be formatted with the `date` filter, e.g.
+//! `{{ item.published | date("%b %-d") }}`.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use minijinja::{Environment, ErrorKind};
+use presser_db::{DatabaseStats, Feed};
+use serde::Serialize;
+
+use super::{Digest, DigestItem};
+
+/// Format used by the `date` filter when none is given
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Digest renderer backed by a template file
+#[derive(Debug, Clone)]
+pub struct TemplateRenderer {
+    name: String,
+    source: String,
+}
+
+/// Everything a template can refer to
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    #[serde(flatten)]
+    digest: &'a Digest,
+    entries: Vec<&'a DigestItem>,
+    summaries: HashMap<&'a str, &'a str>,
+    feeds: &'a [Feed],
+    stats: &'a DatabaseStats,
+}
+
+impl TemplateRenderer {
+    /// Compile a template; `name` decides auto-escaping (see module docs)
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Result<Self> {
+        let renderer = Self {
+            name: name.into(),
+            source: source.into(),
+        };
+        environment()
+            .template_from_named_str(&renderer.name, &renderer.source)
+            .with_context(|| format!("Invalid digest template {}", renderer.name))?;
+        Ok(renderer)
+    }
+
+    /// Load and compile a template file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read digest template {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::new(name, source)
+    }
+
+    /// Render a digest together with the subscribed feeds and database totals
+    pub fn render(&self, digest: &Digest, feeds: &[Feed], stats: &DatabaseStats) -> Result<String> {
+        let mut seen = HashSet::new();
+        let mut entries: Vec<&DigestItem> = digest
+            .sections
+            .iter()
+            .flat_map(|s| &s.items)
+            .filter(|item| seen.insert(item.id.as_str()))
+            .collect();
+        entries.sort_by_key(|item| Reverse(item.published));
+        let summaries = entries
+            .iter()
+            .filter_map(|item| Some((item.id.as_str(), item.summary.as_deref()?)))
+            .collect();
+
+        let context = TemplateContext {
+            digest,
+            entries,
+            summaries,
+            feeds,
+            stats,
+        };
+        let env = environment();
+        let template = env.template_from_named_str(&self.name, &self.source)?;
+        template
+            .render(&context)
+            .with_context(|| format!("Failed to render digest template {}", self.name))
+    }
+}
+
+/// Template environment with Presser's filters registered
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.add_filter("date", date);
+    env
+}
+
+/// `date` filter: format an RFC 3339 timestamp with a strftime pattern
+fn date(value: String, format: Option<String>) -> Result<String, minijinja::Error> {
+    let parsed: DateTime<Utc> = value.parse().map_err(|e| {
+        minijinja::Error::new(
+            ErrorKind::InvalidOperation,
+            format!("'{}' is not a timestamp: {}", value, e),
+        )
+    })?;
+    Ok(parsed
+        .format(format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT))
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::{build, DigestGrouping};
+    use presser_db::fixtures;
+
+    #[tokio::test]
+    async fn test_render_template() {
+        let (db, _) = fixtures::seeded().await.unwrap();
+        let digest = build(&db, 7, DigestGrouping::Tag).await.unwrap();
+        let feeds = db.get_all_feeds().await.unwrap();
+        let stats = db.get_stats().await.unwrap();
+
+        let renderer = TemplateRenderer::new(
+            "newsletter.html",
+            "{{ entries | length }}/{{ stats.total_entries }} from {{ feeds | length }} feeds\n\
+             {% for item in entries %}<{{ item.title }}|{{ item.published | date('%Y') }}>{% endfor %}\n\
+             {{ summaries['rust-async'] }}",
+        )
+        .unwrap();
+        let output = renderer.render(&digest, &feeds, &stats).unwrap();
+
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("3/5 from 2 feeds"));
+        // Entries appear once even when they sit under several tags
+        assert_eq!(lines.next().unwrap().matches('<').count(), 3);
+        assert_eq!(lines.next(), Some("Async closures are now stable."));
+
+        assert!(TemplateRenderer::new("broken.txt", "{% for %}").is_err());
+    }
+}
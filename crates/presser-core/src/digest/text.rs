@@ -0,0 +1,88 @@
+//! Plain-text digest renderer
+
+use std::fmt::Write;
+
+use super::{Digest, DigestRenderer};
+
+/// Plain text wrapped to a fixed width
+#[derive(Debug, Clone)]
+pub struct TextRenderer {
+    /// Column to wrap summaries at
+    pub width: usize,
+}
+
+impl Default for TextRenderer {
+    fn default() -> Self {
+        Self { width: 78 }
+    }
+}
+
+impl DigestRenderer for TextRenderer {
+    fn render(&self, digest: &Digest) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", digest.title);
+        let _ = writeln!(out, "{}", "=".repeat(digest.title.chars().count()));
+        let _ = writeln!(
+            out,
+            "{} entries from {} feeds in the last {} day(s)\n",
+            digest.entry_count, digest.feed_count, digest.days
+        );
+        if let Some(synthesis) = &digest.synthesis {
+            for paragraph in synthesis.trim().split("\n\n") {
+                for line in textwrap::wrap(paragraph.trim(), self.width) {
+                    let _ = writeln!(out, "{}", line);
+                }
+                out.push('\n');
+            }
+        }
+        if !digest.coverage.is_empty() {
+            let _ = writeln!(out, "Coverage by source:");
+            for source in &digest.coverage {
+                let _ = writeln!(
+                    out,
+                    "  {}: {} entries in {} topic(s)",
+                    source.source, source.entries, source.topics
+                );
+            }
+            out.push('\n');
+        }
+
+        for section in &digest.sections {
+            let _ = writeln!(out, "{}", section.heading);
+            let _ = writeln!(out, "{}", "-".repeat(section.heading.chars().count()));
+            for item in &section.items {
+                let _ = writeln!(out, "* {}", item.title);
+                let _ = writeln!(
+                    out,
+                    "  {} | {}",
+                    item.feed_title,
+                    item.published.format("%Y-%m-%d %H:%M")
+                );
+                let _ = writeln!(out, "  {}", item.url);
+                if let Some(label) = item.discussion_label() {
+                    match &item.discussion {
+                        Some(url) => {
+                            let _ = writeln!(out, "  {}: {}", label, url);
+                        }
+                        None => {
+                            let _ = writeln!(out, "  {}", label);
+                        }
+                    }
+                }
+                if let Some(changes) = &item.changes {
+                    let _ = writeln!(out, "  Updated: {}", changes);
+                }
+                if let Some(text) = item.summary.as_ref().or(item.description.as_ref()) {
+                    let options = textwrap::Options::new(self.width)
+                        .initial_indent("  ")
+                        .subsequent_indent("  ");
+                    for line in textwrap::wrap(text, options) {
+                        let _ = writeln!(out, "{}", line);
+                    }
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,278 @@
+//! Digests, reports, and the AI requests written from them
+
+use anyhow::Result;
+use presser_ai::{SummaryOptions, SummaryStyle};
+use presser_config::NotificationKind;
+use presser_db::EntryFilter;
+
+use super::Engine;
+use crate::ai_queue::Priority;
+use crate::ask::{self, Answer, AskOptions};
+use crate::digest::report::{self, ReportPeriod};
+use crate::digest::{
+    self, AtomRenderer, Digest, DigestGrouping, DigestRenderer, DigestStyle, SynthesisCost,
+    TemplateRenderer,
+};
+use crate::metrics::{self, METRICS};
+use crate::notify::Notification;
+
+impl Engine {
+    /// Render a digest of unread entries from the last `days` days, highest
+    /// ranked first
+    ///
+    /// With `approve_synthesis`, a digest of at least
+    /// `[digest].synthesis_min_entries` entries opens with an AI overview if
+    /// it approves the estimated cost; a failed overview is logged and left
+    /// out. A non-empty digest is announced through the configured notifiers.
+    pub async fn generate_digest(
+        &self,
+        days: u32,
+        style: &DigestStyle,
+        grouping: DigestGrouping,
+        approve_synthesis: Option<&(dyn Fn(&SynthesisCost) -> bool + Sync)>,
+    ) -> Result<String> {
+        self.rank_entries().await?;
+        let (digest, output) = match style {
+            DigestStyle::Builtin(format) => {
+                let mut digest = digest::build(&self.db, days, grouping).await?;
+                self.add_synthesis(&mut digest, SummaryStyle::Overview, approve_synthesis)
+                    .await?;
+                let output = digest::renderer(*format).render(&digest);
+                (digest, output)
+            }
+            DigestStyle::Template(path) => {
+                // Load first so a broken template fails before any queries
+                let renderer = TemplateRenderer::from_file(path)?;
+                let mut digest = digest::build(&self.db, days, grouping).await?;
+                self.add_synthesis(&mut digest, SummaryStyle::Overview, approve_synthesis)
+                    .await?;
+                let feeds = self.db.get_all_feeds().await?;
+                let stats = self.db.get_stats().await?;
+                let output = renderer.render(&digest, &feeds, &stats)?;
+                (digest, output)
+            }
+        };
+        if digest.entry_count > 0 {
+            self.notifications
+                .notify(&Notification {
+                    event: NotificationKind::DigestReady,
+                    title: digest.title.clone(),
+                    body: format!(
+                        "{} unread entries from {} feeds ({} summarized)",
+                        digest.entry_count, digest.feed_count, digest.summarized_count
+                    ),
+                    url: None,
+                })
+                .await;
+            self.hooks.digest(&digest).await;
+        }
+        Ok(output)
+    }
+
+    /// Render a trend report of the last `period`: its entries by topic,
+    /// with the sources covering each
+    ///
+    /// With `approve_narrative`, the report opens with an AI narrative of the
+    /// recurring themes if it approves the estimated cost; a failed narrative
+    /// is logged and left out.
+    pub async fn generate_report(
+        &self,
+        period: ReportPeriod,
+        style: &DigestStyle,
+        approve_narrative: Option<&(dyn Fn(&SynthesisCost) -> bool + Sync)>,
+    ) -> Result<String> {
+        self.rank_entries().await?;
+        match style {
+            DigestStyle::Builtin(format) => {
+                let mut report = report::build(&self.db, period).await?;
+                self.add_synthesis(&mut report, SummaryStyle::Trends, approve_narrative)
+                    .await?;
+                Ok(digest::renderer(*format).render(&report))
+            }
+            DigestStyle::Template(path) => {
+                let renderer = TemplateRenderer::from_file(path)?;
+                let mut report = report::build(&self.db, period).await?;
+                self.add_synthesis(&mut report, SummaryStyle::Trends, approve_narrative)
+                    .await?;
+                let feeds = self.db.get_all_feeds().await?;
+                let stats = self.db.get_stats().await?;
+                renderer.render(&report, &feeds, &stats)
+            }
+        }
+    }
+
+    /// What an AI overview of `digest` would cost, or None when it has fewer
+    /// entries than `[digest].synthesis_min_entries`
+    pub async fn synthesis_cost(&self, digest: &Digest) -> Result<Option<SynthesisCost>> {
+        if digest.entry_count == 0 || digest.entry_count < self.config.digest.synthesis_threshold()
+        {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.request_cost(digest, SummaryStyle::Overview).await?,
+        ))
+    }
+
+    /// What a report's trends narrative would cost, or None for an empty report
+    pub async fn narrative_cost(&self, report: &Digest) -> Result<Option<SynthesisCost>> {
+        if report.entry_count == 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.request_cost(report, SummaryStyle::Trends).await?))
+    }
+
+    /// Estimated cost of one AI request of `style` over `digest`
+    async fn request_cost(&self, digest: &Digest, style: SummaryStyle) -> Result<SynthesisCost> {
+        let (entries, input) = synthesis_input(digest, style);
+        let prompt = digest::estimate_tokens(&self.config.ai.system_prompt)
+            + digest::estimate_tokens(style.instruction());
+        Ok(SynthesisCost {
+            entries,
+            input_tokens: prompt + digest::estimate_tokens(&input),
+            max_output_tokens: u64::from(self.config.ai.max_tokens),
+            budget_left: self.tokens_left_today().await?,
+        })
+    }
+
+    /// Write `digest.synthesis` in `style` (an overview, or a report's trends
+    /// narrative) if `approve` accepts the cost (see `generate_digest`)
+    async fn add_synthesis(
+        &self,
+        digest: &mut Digest,
+        style: SummaryStyle,
+        approve: Option<&(dyn Fn(&SynthesisCost) -> bool + Sync)>,
+    ) -> Result<()> {
+        let Some(approve) = approve else {
+            return Ok(());
+        };
+        let cost = match style {
+            SummaryStyle::Trends => self.narrative_cost(digest).await?,
+            _ => self.synthesis_cost(digest).await?,
+        };
+        let Some(cost) = cost else {
+            tracing::info!(
+                "{} entries are too few for an AI {}",
+                digest.entry_count,
+                style.label()
+            );
+            return Ok(());
+        };
+        if !approve(&cost) {
+            return Ok(());
+        }
+        if let Err(e) = self.synthesize(digest, style).await {
+            tracing::warn!("AI {} left out: {:#}", style.label(), e);
+        }
+        Ok(())
+    }
+
+    /// Run one interactive AI request (an overview, an answer) in its turn:
+    /// timed, and its tokens counted in the metrics and the day's usage
+    async fn interactive_request(
+        &self,
+        request: impl std::future::Future<Output = Result<presser_ai::Summary>>,
+    ) -> Result<presser_ai::Summary> {
+        let _slot = self.ai_budget.acquire(Priority::interactive()).await;
+        self.ai_budget.pace().await;
+        let summary = metrics::time_ai(request).await?;
+        let tokens = summary.tokens.map_or(0, u64::from);
+        METRICS.tokens.add(tokens);
+        if !summary.cached {
+            self.db
+                .record_ai_usage(chrono::Utc::now().date_naive(), tokens as i64)
+                .await?;
+        }
+        Ok(summary)
+    }
+
+    /// One AI request writing `digest.synthesis` from its stored summaries
+    async fn synthesize(&self, digest: &mut Digest, style: SummaryStyle) -> Result<()> {
+        if self.tokens_left_today().await? == Some(0) {
+            anyhow::bail!("the daily token budget is used up");
+        }
+        let (_, input) = synthesis_input(digest, style);
+        let options = SummaryOptions {
+            style: Some(style),
+            skip_cache: false,
+        };
+        let summary = self
+            .interactive_request(self.ai.summarize_stream(&input, options, |_| {}))
+            .await?;
+        tracing::info!(
+            "Wrote the AI {} ({} tokens)",
+            style.label(),
+            summary.tokens.unwrap_or(0)
+        );
+        digest.synthesis = Some(summary.text);
+        Ok(())
+    }
+
+    /// Answer `question` from the stored entries most relevant to it (see
+    /// [`ask`]), passing the answer to `on_text` as it is generated
+    pub async fn ask(
+        &self,
+        question: &str,
+        options: &AskOptions,
+        on_text: impl FnMut(&str) + Send,
+    ) -> Result<Answer> {
+        let entries = ask::retrieve(&self.db, question, options).await?;
+        if entries.is_empty() {
+            anyhow::bail!("No stored entries match the question");
+        }
+        if self.tokens_left_today().await? == Some(0) {
+            anyhow::bail!("The daily token budget is used up");
+        }
+        let sources = ask::sources(&self.db, entries).await?;
+        let prompt = ask::prompt(question, &sources);
+        let summary = self
+            .interactive_request(self.ai.answer(&prompt, on_text))
+            .await?;
+        Ok(Answer {
+            text: summary.text,
+            sources,
+            model: summary.model,
+            tokens: summary.tokens.map_or(0, u64::from),
+        })
+    }
+
+    /// Tokens left in today's AI budget, if there is one
+    async fn tokens_left_today(&self) -> Result<Option<u64>> {
+        let spent = self
+            .db
+            .get_ai_usage(chrono::Utc::now().date_naive())
+            .await?
+            .tokens
+            .max(0) as u64;
+        Ok(self.ai_budget.remaining(spent))
+    }
+
+    /// Atom feed of summarized entries from the last `days` days, read or not
+    ///
+    /// `self_url` is where the feed is served from, if anywhere.
+    pub async fn summaries_feed(
+        &self,
+        days: u32,
+        tag: Option<&str>,
+        self_url: Option<&str>,
+    ) -> Result<String> {
+        let mut filter = EntryFilter::new().has_summary(true);
+        if let Some(tag) = tag {
+            filter = filter.tag(tag);
+        }
+        let mut digest =
+            digest::build_filtered(&self.db, filter, days, DigestGrouping::Feed).await?;
+        digest.title = "Presser summaries".to_string();
+        let renderer = AtomRenderer {
+            self_url: self_url.map(String::from),
+        };
+        Ok(renderer.render(&digest))
+    }
+}
+
+/// The entries and text an AI request of `style` over `digest` is written from
+fn synthesis_input(digest: &Digest, style: SummaryStyle) -> (usize, String) {
+    match style {
+        SummaryStyle::Trends => report::trends_input(digest),
+        _ => digest::synthesis_input(digest),
+    }
+}
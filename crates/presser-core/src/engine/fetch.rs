@@ -0,0 +1,1097 @@
+//! Updating feeds: fetching, storing new entries, and summarizing them
+
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use presser_config::{DuplicateHandling, NotificationKind};
+use presser_db::{Enclosure, Entry, EntryDiscussion, FetchState, RunCheckpoint};
+use presser_feeds::{network, user_agent, ByteMeter, ConditionalFetch, FeedError, Validators};
+use tokio::sync::mpsc;
+
+use super::plan::SelectedEntry;
+use super::{fit_content, Engine};
+use crate::auth;
+use crate::dedup;
+use crate::metrics::METRICS;
+use crate::notify::Notification;
+use crate::revisions;
+use crate::scoring;
+use crate::translate;
+use crate::update::{self, UpdateProgress, UpdateReport, UpdateSummary};
+
+/// How long a feed lease lasts; a crashed updater blocks the feed at most this long
+const LEASE_TTL: chrono::Duration = chrono::Duration::minutes(10);
+
+/// Entries whose comment feeds `track_comments` counts, by age in days
+const COMMENT_TRACKING_DAYS: i64 = 7;
+
+impl Engine {
+    /// Update a single feed
+    ///
+    /// Fetches changes, drops entries rejected by the feed's keyword filters,
+    /// stores the rest, and summarizes new entries. Fetch failures are recorded
+    /// on the feed and returned as errors; failed summaries only end up in the
+    /// report. High-priority new entries and feeds that start failing are
+    /// announced through the configured notifiers.
+    ///
+    /// A feed that another process (or engine) is already updating is left
+    /// alone and reported as `busy`. Offline, nothing is fetched and the
+    /// update is reported as `offline` instead of failing: in offline mode
+    /// right away, otherwise once a fetch fails and the network turns out to
+    /// be down.
+    ///
+    /// Every update that isn't `busy` or `offline` is recorded in the run log
+    /// (`presser log`).
+    pub async fn update_feed(&self, feed_id: &str) -> Result<UpdateReport> {
+        self.update_feed_in_run(feed_id, &update::new_run_id())
+            .await
+    }
+
+    /// `update_feed`, logged as part of run `run_id`
+    async fn update_feed_in_run(&self, feed_id: &str, run_id: &str) -> Result<UpdateReport> {
+        let feed = self
+            .db
+            .get_feed(feed_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Feed not found: {}", feed_id))?;
+        if self.forced_offline {
+            return Ok(UpdateReport::offline(feed_id));
+        }
+
+        let started_at = chrono::Utc::now();
+        let started = std::time::Instant::now();
+        let (mut result, bytes) = self
+            .metered(feed_id, self.lease_and_update(feed, run_id, started_at))
+            .await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let logged = match &mut result {
+            Ok(report) if report.busy || report.offline => None,
+            Ok(report) => {
+                report.duration_ms = duration_ms;
+                report.bytes = bytes;
+                Some(report.clone())
+            }
+            Err(e) => Some(UpdateReport {
+                duration_ms,
+                bytes,
+                ..UpdateReport::failure(feed_id, e)
+            }),
+        };
+        if let Some(report) = logged {
+            if let Err(e) = self
+                .db
+                .record_run(&report.run_record(run_id, started_at))
+                .await
+            {
+                tracing::warn!("Failed to log update of {}: {:#}", feed_id, e);
+            }
+            // Logged, so no longer in progress
+            if let Err(e) = self.db.take_run_checkpoint(feed_id).await {
+                tracing::warn!("Failed to clear checkpoint of {}: {:#}", feed_id, e);
+            }
+        }
+        result
+    }
+
+    /// Run `future`, adding the bytes the fetcher downloads meanwhile to
+    /// `feed_id`'s total, and return its output with those bytes
+    pub(super) async fn metered<F: std::future::Future>(
+        &self,
+        feed_id: &str,
+        future: F,
+    ) -> (F::Output, u64) {
+        let meter = ByteMeter::new();
+        let output = meter.measure(future).await;
+        let bytes = meter.bytes();
+        if bytes > 0 {
+            if let Err(e) = self.db.add_feed_bytes(feed_id, bytes).await {
+                tracing::warn!("Failed to count bytes downloaded for {}: {:#}", feed_id, e);
+            }
+        }
+        (output, bytes)
+    }
+
+    /// Update `feed` unless another holder has leased it
+    async fn lease_and_update(
+        &self,
+        feed: presser_db::Feed,
+        run_id: &str,
+        started_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<UpdateReport> {
+        let feed_id = feed.id.clone();
+        let feed_id = feed_id.as_str();
+        if !self
+            .db
+            .acquire_lease(feed_id, &self.lease_holder, LEASE_TTL)
+            .await?
+        {
+            tracing::info!("Feed {} is already being updated elsewhere", feed_id);
+            return Ok(UpdateReport {
+                feed_id: feed_id.to_string(),
+                busy: true,
+                ..Default::default()
+            });
+        }
+        let result = self.update_leased_feed(feed, run_id, started_at).await;
+        if let Err(e) = self.db.release_lease(feed_id, &self.lease_holder).await {
+            tracing::warn!("Failed to release lease on {}: {:#}", feed_id, e);
+        }
+        result
+    }
+
+    /// Finish an update of `feed_id` that was interrupted past its checkpoint
+    ///
+    /// The interrupted update is logged with what it had stored, and the
+    /// summaries it left queued are requested now. Its entries and fetch
+    /// state were saved at the checkpoint, so they aren't fetched again.
+    async fn resume_interrupted(
+        &self,
+        feed: &presser_db::Feed,
+        report: &mut UpdateReport,
+    ) -> Result<()> {
+        let feed_id = feed.id.as_str();
+        let Some(checkpoint) = self.db.take_run_checkpoint(feed_id).await? else {
+            return Ok(());
+        };
+        tracing::info!(
+            "Resuming update of {} interrupted in run {}",
+            feed_id,
+            checkpoint.run_id
+        );
+        let interrupted = presser_db::RunRecord {
+            run_id: checkpoint.run_id,
+            feed_id: feed_id.to_string(),
+            started_at: checkpoint.started_at,
+            duration_ms: (checkpoint.checkpointed_at - checkpoint.started_at).num_milliseconds(),
+            new_entries: checkpoint.new_entries,
+            errors: serde_json::to_string(&["Interrupted; summaries resumed by a later run"]).ok(),
+            ..Default::default()
+        };
+        if let Err(e) = self.db.record_run(&interrupted).await {
+            tracing::warn!("Failed to log interrupted update of {}: {:#}", feed_id, e);
+        }
+
+        if self
+            .config
+            .feeds
+            .get(&feed.url)
+            .is_some_and(|c| !c.enable_ai)
+        {
+            return Ok(());
+        }
+        let mut pending = Vec::new();
+        for queued in self.db.feed_queued_summaries(feed_id).await? {
+            if let Some(content) = self
+                .db
+                .get_entry(&queued.entry_id)
+                .await?
+                .and_then(|e| e.content_text.or(e.summary))
+            {
+                pending.push((queued.entry_id, content));
+            }
+        }
+        let style = self.summary_style(&feed.url);
+        update::summarize_entries(
+            &self.ai,
+            &self.db,
+            &self.pipelines,
+            &self.ai_budget,
+            pending,
+            style,
+            report,
+        )
+        .await
+    }
+
+    /// Fetch the feed at `url` with its configured user agent (or `remembered`,
+    /// the one its last fetch needed), retrying once with the other on 403
+    /// when `retry_forbidden` is on
+    ///
+    /// Also returns the user agent to remember for the feed: the one that
+    /// worked if it isn't the configured one.
+    pub(super) async fn fetch_feed(
+        &self,
+        feed_id: &str,
+        url: &str,
+        validators: &Validators,
+        remembered: Option<&str>,
+    ) -> (Result<ConditionalFetch>, Option<String>) {
+        let configured = user_agent::resolve(
+            self.config
+                .global
+                .user_agent_for(self.config.feeds.get(url)),
+        );
+        let first = remembered.map_or_else(|| configured.clone(), str::to_string);
+        let result = self
+            .fetch_authorized(feed_id, url, validators, &first)
+            .await;
+        let (result, sent) = match result {
+            Err(e) if self.config.global.retry_forbidden && user_agent::is_forbidden(&e) => {
+                let retry = if first == configured {
+                    user_agent::alternate(&first)
+                } else {
+                    configured.clone()
+                };
+                tracing::info!(
+                    "{} refused with 403, retrying with user agent {:?}",
+                    url,
+                    retry
+                );
+                let result = self
+                    .fetch_authorized(feed_id, url, validators, &retry)
+                    .await;
+                match &result {
+                    Ok(_) if retry != configured => {
+                        tracing::warn!(
+                            "{} needs user agent {:?}, using it from now on",
+                            url,
+                            retry
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(_) => tracing::warn!("{} refused both user agents", url),
+                }
+                (result, retry)
+            }
+            result => (result, first),
+        };
+        let remember = (result.is_ok() && sent != configured).then_some(sent);
+        (result, remember)
+    }
+
+    /// Fetch the feed at `url` as `user_agent`, sending its `[feed.auth]`
+    /// token if it has one; on 401, gets a new token and retries once
+    async fn fetch_authorized(
+        &self,
+        feed_id: &str,
+        url: &str,
+        validators: &Validators,
+        user_agent: &str,
+    ) -> Result<ConditionalFetch> {
+        let Some(feed_auth) = self.config.feeds.get(url).and_then(|c| c.auth.as_ref()) else {
+            return self
+                .fetcher
+                .fetch_conditional_as(url, validators, user_agent, None)
+                .await;
+        };
+        let token = self
+            .tokens
+            .token(feed_id, feed_auth)
+            .await
+            .with_context(|| format!("Failed to get a token for {}", url))?;
+        let header = feed_auth.header_value(&token);
+        let result = self
+            .fetcher
+            .fetch_conditional_as(
+                url,
+                validators,
+                user_agent,
+                Some((feed_auth.header.as_str(), header.as_str())),
+            )
+            .await;
+        match result {
+            Err(e) if feed_auth.refreshes() && auth::is_unauthorized(&e) => {
+                tracing::info!("{} refused its token with 401, getting a new one", url);
+                let token = self
+                    .tokens
+                    .refresh(feed_id, feed_auth, &token)
+                    .await
+                    .with_context(|| format!("Failed to refresh the token for {}", url))?;
+                let header = feed_auth.header_value(&token);
+                self.fetcher
+                    .fetch_conditional_as(
+                        url,
+                        validators,
+                        user_agent,
+                        Some((feed_auth.header.as_str(), header.as_str())),
+                    )
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    /// `update_feed` once the lease is held
+    async fn update_leased_feed(
+        &self,
+        feed: presser_db::Feed,
+        run_id: &str,
+        started_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<UpdateReport> {
+        let feed_id = feed.id.clone();
+        let feed_id = feed_id.as_str();
+        tracing::info!("Updating feed: {}", feed_id);
+        let mut report = UpdateReport {
+            feed_id: feed_id.to_string(),
+            ..Default::default()
+        };
+        self.resume_interrupted(&feed, &mut report).await?;
+
+        let state = self.db.get_fetch_state(feed_id).await?.unwrap_or_default();
+        let validators = Validators {
+            etag: state.etag,
+            last_modified: state.last_modified,
+        };
+        let started = std::time::Instant::now();
+        let (fetch_result, alternate_user_agent) = self
+            .fetch_feed(
+                feed_id,
+                &feed.url,
+                &validators,
+                state.alternate_user_agent.as_deref(),
+            )
+            .await;
+        METRICS.fetches.inc();
+        METRICS.fetch_seconds.observe(started.elapsed());
+        if let Err(e) = &fetch_result {
+            // A feed isn't failing when the whole network is down
+            if network::is_connection_error(e) && !self.check_network().await {
+                tracing::info!("Network unreachable, deferring update of {}", feed_id);
+                return Ok(UpdateReport::offline(feed_id));
+            }
+        } else {
+            self.offline.store(false, Ordering::Relaxed);
+        }
+
+        let moved_to = fetch_result
+            .as_ref()
+            .ok()
+            .and_then(|fetch| fetch.moved_to())
+            .filter(|url| *url != feed.url)
+            .map(str::to_string);
+        if moved_to.is_some() && moved_to != state.moved_to {
+            tracing::info!(
+                "Feed {} moved permanently to {}",
+                feed_id,
+                moved_to.as_deref().unwrap_or_default()
+            );
+        }
+        report.moved_to = moved_to.clone();
+
+        let unchanged = fetch_result
+            .as_ref()
+            .is_ok_and(|fetch| fetch.is_unchanged(state.content_hash.as_deref()));
+        let track_comments = self
+            .config
+            .feeds
+            .get(&feed.url)
+            .is_some_and(|c| c.track_comments);
+
+        match fetch_result {
+            Ok(ConditionalFetch::Modified {
+                status,
+                metadata,
+                entries,
+                validators,
+                content_hash,
+                ..
+            }) if !unchanged => {
+                let updated_feed = presser_db::Feed {
+                    title: metadata.title,
+                    description: metadata.description,
+                    site_url: metadata.site_url,
+                    last_fetched: Some(chrono::Utc::now()),
+                    last_successful_fetch: Some(chrono::Utc::now()),
+                    last_error: None,
+                    entry_count: entries.len() as i64,
+                    ..feed
+                };
+                self.db.upsert_feed(&updated_feed).await?;
+                // Before filters drop any: a feed is alive if it publishes at all
+                let last_published = entries
+                    .iter()
+                    .filter_map(|e| e.published)
+                    .max()
+                    .map(|published| published.min(chrono::Utc::now()));
+
+                let feed_config = self.config.feeds.get(&updated_feed.url);
+                let extract = self.config.extracts_content_for(&updated_feed.url);
+                let backend = self.config.extraction_backend_for(&updated_feed.url);
+                let (selected, known) = self
+                    .select_entries(&updated_feed, entries, &mut report)
+                    .await?;
+
+                let mut db_entries = Vec::with_capacity(selected.len());
+                let mut entry_tags = Vec::with_capacity(selected.len());
+                let mut entry_discussions = Vec::new();
+                let mut entry_enclosures = Vec::new();
+                for SelectedEntry {
+                    entry: mut db_entry,
+                    discussion,
+                    enclosures,
+                    categories,
+                } in selected
+                {
+                    // New entries the feed gives no text for get their page's
+                    if extract
+                        && db_entry.content_text.is_none()
+                        && !db_entry.url.is_empty()
+                        && !known.contains(&db_entry.id)
+                    {
+                        match self
+                            .fetcher
+                            .extract_content_with(&db_entry.url, backend)
+                            .await
+                        {
+                            Ok(text) => db_entry.content_text = Some(text),
+                            Err(e) => tracing::warn!(
+                                "Failed to extract content for {}: {:#}",
+                                db_entry.url,
+                                e
+                            ),
+                        }
+                    }
+                    fit_content(&mut db_entry, &self.config.content);
+                    if let Some(discussion) = discussion {
+                        entry_discussions.push(EntryDiscussion {
+                            entry_id: db_entry.id.clone(),
+                            ..discussion.into()
+                        });
+                    }
+                    entry_enclosures.extend(enclosures.into_iter().map(|enclosure| Enclosure {
+                        entry_id: db_entry.id.clone(),
+                        ..enclosure.into()
+                    }));
+                    entry_tags.push((db_entry.id.clone(), categories));
+                    db_entries.push(db_entry);
+                }
+                // Entries already stored whose text changed, before the new
+                // text replaces theirs
+                let stored_ids: Vec<String> = db_entries
+                    .iter()
+                    .filter(|e| known.contains(&e.id))
+                    .map(|e| e.id.clone())
+                    .collect();
+                let stored = self.db.get_entries_by_ids(&stored_ids).await?;
+                let changed: Vec<(String, String, String)> = db_entries
+                    .iter()
+                    .filter_map(|e| {
+                        let stored = stored.iter().find(|s| s.id == e.id)?;
+                        let (old, new) = revisions::changed_text(stored, e)?;
+                        Some((e.id.clone(), old, new))
+                    })
+                    .collect();
+                self.db.upsert_entries(&db_entries).await?;
+                let describe = self.config.ai.describe_changes
+                    && feed_config.map(|c| c.enable_ai).unwrap_or(true);
+                revisions::record_revisions(
+                    &self.ai,
+                    &self.db,
+                    &self.ai_budget,
+                    changed,
+                    describe,
+                    &mut report,
+                )
+                .await?;
+                for (entry_id, categories) in &entry_tags {
+                    self.db.set_feed_tags(entry_id, categories).await?;
+                }
+                for discussion in &entry_discussions {
+                    self.db.set_discussion(discussion).await?;
+                }
+                for enclosure in &entry_enclosures {
+                    self.db.upsert_enclosure(enclosure).await?;
+                }
+
+                let mut new_entries: Vec<_> = db_entries
+                    .iter()
+                    .filter(|e| !known.contains(&e.id))
+                    .collect();
+                report.new = new_entries.len();
+                // Hidden duplicates are neither summarized nor announced
+                let hidden = self.handle_duplicates(&new_entries, &mut report).await?;
+                new_entries.retain(|e| !hidden.contains(&e.id));
+                let mut pending: Vec<(String, String)> = new_entries
+                    .iter()
+                    .filter_map(|e| {
+                        let content = e.content_text.as_ref().or(e.summary.as_ref())?;
+                        Some((e.id.clone(), content.clone()))
+                    })
+                    .collect();
+                let enable_ai = feed_config.map(|c| c.enable_ai).unwrap_or(true);
+
+                // Checkpoint: from here on the work left is in the summary
+                // queue, so an interrupted update neither fetches these
+                // entries again nor summarizes them twice
+                if enable_ai {
+                    let ids: Vec<String> = pending.iter().map(|(id, _)| id.clone()).collect();
+                    self.db.enqueue_summaries(&ids).await?;
+                }
+                self.db
+                    .set_fetch_state(&FetchState {
+                        feed_id: feed_id.to_string(),
+                        etag: validators.etag,
+                        last_modified: validators.last_modified,
+                        last_status: Some(i64::from(status)),
+                        consecutive_failures: 0,
+                        moved_to,
+                        content_hash: Some(content_hash),
+                        alternate_user_agent,
+                    })
+                    .await?;
+                if let Some(published) = last_published {
+                    self.db.record_published(feed_id, published).await?;
+                }
+                self.db
+                    .set_run_checkpoint(&RunCheckpoint {
+                        feed_id: feed_id.to_string(),
+                        run_id: run_id.to_string(),
+                        started_at,
+                        new_entries: report.new as i64,
+                        checkpointed_at: chrono::Utc::now(),
+                    })
+                    .await?;
+
+                if self.config.archive.enabled {
+                    for entry in &new_entries {
+                        if let Err(e) = self.archive_page(&entry.id, &entry.url).await {
+                            tracing::warn!("Failed to archive {}: {:#}", entry.url, e);
+                        }
+                    }
+                }
+                // Summaries are written from the translation
+                if let Some(language) = feed_config.and_then(|c| c.translate_to.as_deref()) {
+                    pending = translate::translate_entries(
+                        &self.ai,
+                        &self.db,
+                        &self.ai_budget,
+                        pending,
+                        language,
+                        &mut report,
+                    )
+                    .await?;
+                }
+                if enable_ai {
+                    let style = self.summary_style(&updated_feed.url);
+                    update::summarize_entries(
+                        &self.ai,
+                        &self.db,
+                        &self.pipelines,
+                        &self.ai_budget,
+                        pending,
+                        style,
+                        &mut report,
+                    )
+                    .await?;
+                }
+                if self.hooks.wants_new_entries() {
+                    for entry in &new_entries {
+                        let summary = self.db.get_summary(&entry.id).await?;
+                        self.hooks
+                            .new_entry(&updated_feed.title, entry, summary.as_ref())
+                            .await;
+                    }
+                }
+                let notify = feed_config.map(|c| c.notify).unwrap_or(true);
+                for entry in new_entries
+                    .into_iter()
+                    .filter(|e| notify && self.notifications.is_high_priority(e))
+                {
+                    let summary = self.db.get_summary(&entry.id).await?;
+                    self.notifications
+                        .notify(&Notification {
+                            event: NotificationKind::HighPriority,
+                            title: entry.title.clone(),
+                            body: summary
+                                .map(|s| s.summary_text)
+                                .unwrap_or_else(|| format!("New in {}", updated_feed.title)),
+                            url: Some(entry.url.clone()),
+                        })
+                        .await;
+                }
+
+                tracing::info!(
+                    "Feed {} updated: {} new, {} summarized, {} skipped, {} duplicates",
+                    feed_id,
+                    report.new,
+                    report.summarized,
+                    report.skipped,
+                    report.duplicates
+                );
+            }
+            // Nothing new: skip parsing results, extraction, and summaries
+            Ok(fetch) => {
+                let (status, validators) = match fetch {
+                    ConditionalFetch::NotModified { .. } => {
+                        METRICS.fetches_not_modified.inc();
+                        (304, validators)
+                    }
+                    ConditionalFetch::Modified {
+                        status, validators, ..
+                    } => {
+                        METRICS.fetches_unchanged.inc();
+                        (status, validators)
+                    }
+                };
+                let now = chrono::Utc::now();
+                self.db
+                    .upsert_feed(&presser_db::Feed {
+                        last_fetched: Some(now),
+                        last_successful_fetch: Some(now),
+                        last_error: None,
+                        ..feed
+                    })
+                    .await?;
+                self.db
+                    .set_fetch_state(&FetchState {
+                        feed_id: feed_id.to_string(),
+                        etag: validators.etag,
+                        last_modified: validators.last_modified,
+                        last_status: Some(i64::from(status)),
+                        consecutive_failures: 0,
+                        moved_to,
+                        content_hash: state.content_hash,
+                        alternate_user_agent,
+                    })
+                    .await?;
+                tracing::info!("Feed {} not modified", feed_id);
+                report.not_modified = true;
+            }
+            Err(e) => {
+                METRICS.fetch_errors.inc();
+                let status = match e.downcast_ref::<FeedError>() {
+                    Some(FeedError::HttpStatus { status, .. }) => Some(i64::from(*status)),
+                    Some(FeedError::ParseError(_)) => {
+                        METRICS.parse_errors.inc();
+                        None
+                    }
+                    _ => None,
+                };
+                let failures = self.db.record_fetch_failure(feed_id, status).await?;
+                if self.notifications.is_newly_failing(failures) {
+                    self.notifications
+                        .notify(&Notification {
+                            event: NotificationKind::FeedFailing,
+                            title: format!("Feed failing: {}", feed.title),
+                            body: format!("{} failed fetches in a row: {:#}", failures, e),
+                            url: Some(feed.url.clone()),
+                        })
+                        .await;
+                }
+                let updated_feed = presser_db::Feed {
+                    last_fetched: Some(chrono::Utc::now()),
+                    last_error: Some(e.to_string()),
+                    ..feed
+                };
+                self.db.upsert_feed(&updated_feed).await?;
+                return Err(e);
+            }
+        }
+
+        // Comments keep coming after the feed stops changing
+        if track_comments {
+            if let Err(e) = self.track_comments(feed_id).await {
+                tracing::warn!("Failed to track comments of {}: {:#}", feed_id, e);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Fingerprint entries stored for the first time, and group those
+    /// another feed already stored with the original (hiding them too under
+    /// `duplicates = "hide"`), returning the IDs of the hidden ones
+    ///
+    /// Fingerprints are recorded under `duplicates = "keep"` as well, so
+    /// switching it later finds the copies of entries stored meanwhile.
+    async fn handle_duplicates(
+        &self,
+        entries: &[&Entry],
+        report: &mut UpdateReport,
+    ) -> Result<HashSet<String>> {
+        let handling = self.config.global.duplicates;
+        let mut hidden = HashSet::new();
+        for entry in entries {
+            let fingerprint = dedup::fingerprint(entry);
+            self.db.set_fingerprint(&fingerprint).await?;
+            if handling == DuplicateHandling::Keep {
+                continue;
+            }
+            let Some(original) = self.db.find_original(&fingerprint).await? else {
+                continue;
+            };
+            tracing::debug!("Entry {} duplicates {}", entry.id, original);
+            report.duplicates += 1;
+            self.db
+                .merge_duplicates(&original, std::slice::from_ref(&entry.id))
+                .await?;
+            if handling == DuplicateHandling::Hide {
+                self.db.hide_entry(&entry.id).await?;
+                hidden.insert(entry.id.clone());
+            }
+        }
+        Ok(hidden)
+    }
+
+    /// Count the comment feeds of a feed's entries from the last
+    /// `COMMENT_TRACKING_DAYS`, recording the counts that changed
+    ///
+    /// A count never drops below the stored one, since comment feeds often
+    /// list only the latest comments. A failing comment feed is skipped.
+    async fn track_comments(&self, feed_id: &str) -> Result<()> {
+        let since = chrono::Utc::now() - chrono::Duration::days(COMMENT_TRACKING_DAYS);
+        for discussion in self.db.get_comment_feeds(feed_id, since).await? {
+            let Some(url) = discussion.comment_feed.as_deref() else {
+                continue;
+            };
+            let counted = match self.fetcher.count_comments(url).await {
+                Ok(counted) => counted,
+                Err(e) => {
+                    tracing::debug!("Failed to count comments at {}: {:#}", url, e);
+                    continue;
+                }
+            };
+            self.db
+                .set_discussion(&EntryDiscussion {
+                    comments: Some(discussion.comments.map_or(counted, |c| c.max(counted))),
+                    checked_at: chrono::Utc::now(),
+                    ..discussion
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Update all enabled feeds, at most `global.max_concurrent_fetches` at a time
+    ///
+    /// A failing feed is reported and does not stop the others. Entries left in
+    /// the summary queue by earlier runs are summarized first.
+    pub async fn update_all_feeds(&self) -> Result<UpdateSummary> {
+        self.update_all_feeds_with_progress(None).await
+    }
+
+    /// `update_all_feeds`, announcing each feed on `progress` as its update
+    /// starts and ends
+    pub async fn update_all_feeds_with_progress(
+        &self,
+        progress: Option<mpsc::UnboundedSender<UpdateProgress>>,
+    ) -> Result<UpdateSummary> {
+        let backlog = if !self.is_offline() && self.db.summary_queue_len().await? > 0 {
+            Some(self.drain_summary_queue(None).await?)
+        } else {
+            None
+        };
+        let feeds = self.db.get_all_feeds().await?;
+        let limit = self.config.global.max_concurrent_fetches.max(1);
+        let run_id = update::new_run_id();
+        let run_id = run_id.as_str();
+        let mut reports: Vec<UpdateReport> = stream::iter(feeds.into_iter().filter(|f| f.enabled))
+            .map(|feed| {
+                let progress = progress.clone();
+                async move {
+                    let send = |event| {
+                        if let Some(progress) = &progress {
+                            let _ = progress.send(event);
+                        }
+                    };
+                    send(UpdateProgress::Started(feed.id.clone()));
+                    let report = self
+                        .update_feed_in_run(&feed.id, run_id)
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::warn!("Failed to update feed {}: {:#}", feed.id, e);
+                            UpdateReport::failure(&feed.id, &e)
+                        });
+                    send(UpdateProgress::Finished(report.clone()));
+                    report
+                }
+            })
+            .buffer_unordered(limit)
+            .collect()
+            .await;
+        reports.sort_by(|a, b| a.feed_id.cmp(&b.feed_id));
+        if let Err(e) = self.rank_entries().await {
+            tracing::warn!("Failed to rank entries: {:#}", e);
+        }
+        Ok(UpdateSummary { reports, backlog })
+    }
+
+    /// Rank recent entries for digests and the TUI's top view (see
+    /// `scoring`), returning how many were ranked
+    pub async fn rank_entries(&self) -> Result<usize> {
+        scoring::rank_entries(&self.db, &self.config, chrono::Utc::now()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::{create_test_engine, serve_rss, test_config};
+    use presser_db::MuteKind;
+
+    #[tokio::test]
+    async fn test_update_all_feeds_empty() {
+        let engine = create_test_engine().await;
+        let summary = engine.update_all_feeds().await.unwrap();
+        assert!(summary.reports.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_feed_not_found() {
+        let engine = create_test_engine().await;
+        let result = engine.update_feed("nonexistent").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mirror_duplicates() {
+        // A planet repeating a blog's post, GUID and all
+        let (_planet, planet) = serve_rss(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Planet</title><link>https://planet.example.com</link>
+            <item><guid>post-1</guid><title>Async closures</title><link>https://blog.example.com/post-1?utm_source=planet</link>
+            <description>Async closures are stable now, after years of design work on how they capture their environment and how they interact with the borrow checker and async traits.</description></item>
+            </channel></rss>"#,
+        )
+        .await;
+        let (_blog, blog) = serve_rss(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Blog</title><link>https://blog.example.com</link>
+            <item><guid>post-1</guid><title>Async closures</title><link>https://blog.example.com/post-1/</link>
+            <description>Short teaser</description></item>
+            </channel></rss>"#,
+        )
+        .await;
+        let mut config = test_config();
+        config.global.duplicates = DuplicateHandling::Hide;
+        let engine = Engine::with_config(config).await.unwrap();
+        for (id, url) in [("planet", planet), ("blog", blog)] {
+            engine
+                .database()
+                .upsert_feed(&presser_db::Feed {
+                    id: id.into(),
+                    url,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let report = engine.update_feed("planet").await.unwrap();
+        assert_eq!((report.new, report.duplicates), (1, 0));
+        // The blog's post is new under its own ID, dry run or not
+        assert_eq!(
+            engine.plan_feed_update("blog").await.unwrap().new,
+            ["Async closures"]
+        );
+        let report = engine.update_feed("blog").await.unwrap();
+        assert_eq!((report.new, report.duplicates), (1, 1));
+
+        // The planet's copy stays its own; the blog's gets an ID of its own
+        let db = engine.database();
+        assert_eq!(
+            db.get_entry("post-1").await.unwrap().unwrap().feed_id,
+            "planet"
+        );
+        let copy = db.get_entry("blog:post-1").await.unwrap().unwrap();
+        assert!(copy.hidden);
+        let cluster = db
+            .get_cluster_for_entry("blog:post-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cluster.representative_id, "post-1");
+
+        // Known from now on: not new, and not a duplicate again
+        let report = engine.update_feed("blog").await.unwrap();
+        assert_eq!((report.new, report.duplicates), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_update_feed_pipeline() {
+        let (_server, url) = serve_rss(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Test</title><link>https://example.com</link>
+            <item><guid>a</guid><title>Rust news</title><link>https://example.com/a</link><description>Rust</description></item>
+            <item><guid>b</guid><title>Rust jobs</title><link>https://example.com/b</link><description>Sponsored</description></item>
+            <item><guid>c</guid><title>Rust tips</title><link>https://example.com/c</link></item>
+            </channel></rss>"#,
+        )
+        .await;
+        let mut config = test_config();
+        let feed_config: presser_config::FeedConfig = toml::from_str(&format!(
+            "url = \"{}\"\nname = \"Test\"\nexclude_keywords = [\"sponsored\"]",
+            url
+        ))
+        .unwrap();
+        config.feeds.insert(url.clone(), feed_config);
+        let engine = Engine::with_config(config).await.unwrap();
+        engine
+            .database()
+            .upsert_feed(&presser_db::Feed {
+                id: "test".into(),
+                url,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        engine
+            .mute(MuteKind::Keyword, "TIPS", Some(chrono::Duration::days(1)))
+            .await
+            .unwrap();
+        assert!(engine.mute(MuteKind::Regex, "(", None).await.is_err());
+
+        // A dry run sees the same entries but stores nothing
+        let plan = engine.plan_feed_update("test").await.unwrap();
+        assert_eq!(plan.new, ["Rust news"]);
+        assert_eq!((plan.to_summarize, plan.skipped), (1, 2));
+        assert!(engine
+            .database()
+            .get_entries_for_feed("test", 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let report = engine.update_feed("test").await.unwrap();
+        assert_eq!((report.new, report.skipped, report.summarized), (1, 2, 0));
+        // Nothing listens at the provider's endpoint, so the one entry with content fails
+        assert_eq!(report.errors.len(), 1, "{:?}", report.errors);
+        assert_eq!(
+            engine
+                .database()
+                .get_entries_for_feed("test", 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // The same document again is a cheap no-change run
+        let report = engine.update_feed("test").await.unwrap();
+        assert!(report.not_modified);
+        assert_eq!((report.new, report.skipped), (0, 0));
+        assert!(report.errors.is_empty());
+        assert!(engine.plan_feed_update("test").await.unwrap().not_modified);
+        let runs = engine
+            .database()
+            .get_runs(Some("test"), None, 10)
+            .await
+            .unwrap();
+        assert!(runs[0].not_modified);
+
+        // Another process is updating the feed
+        let db = engine.database();
+        assert!(db.acquire_lease("test", "other", LEASE_TTL).await.unwrap());
+        let report = engine.update_feed("test").await.unwrap();
+        assert!(report.busy);
+        assert_eq!(report.skipped, 0);
+    }
+
+    #[tokio::test]
+    async fn test_update_extracts_content() {
+        let mut server = mockito::Server::new_async().await;
+        let rss = format!(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Test</title><link>https://example.com</link>
+            <item><guid>a</guid><title>Async closures</title><link>{}/a.html</link></item>
+            </channel></rss>"#,
+            server.url()
+        );
+        server
+            .mock("GET", "/feed.xml")
+            .with_body(rss)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/a.html")
+            .with_header("content-type", "text/html")
+            .with_body(
+                "<html><head><title>Async closures</title></head><body><article>\
+                 <p>Async closures are stable now, after years of design work on how they capture \
+                 their environment and how they interact with the borrow checker.</p></article></body></html>",
+            )
+            .create_async()
+            .await;
+        let url = format!("{}/feed.xml", server.url());
+
+        let mut config = test_config();
+        config.global.extract_content = true;
+        let engine = Engine::with_config(config).await.unwrap();
+        let db = engine.database();
+        db.upsert_feed(&presser_db::Feed {
+            id: "test".into(),
+            url,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(engine.update_feed("test").await.unwrap().new, 1);
+        let entry = db.get_entry("a").await.unwrap().unwrap();
+        assert!(entry.content_text.unwrap().contains("borrow checker"));
+    }
+
+    #[tokio::test]
+    async fn test_update_all_feeds_continues_past_failures() {
+        let (_server, url) = serve_rss(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Up</title>
+            <item><guid>x</guid><title>X</title><link>https://example.com/x</link></item>
+            </channel></rss>"#,
+        )
+        .await;
+        // Bind and drop a listener to get a port nothing listens on
+        let dead = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead_url = format!("http://{}/feed.xml", dead.local_addr().unwrap());
+        drop(dead);
+
+        let engine = create_test_engine().await;
+        for (id, url, enabled) in [
+            ("up", url, true),
+            ("down", dead_url, true),
+            ("off", String::new(), false),
+        ] {
+            let feed = presser_db::Feed {
+                id: id.into(),
+                url: if url.is_empty() {
+                    "https://off.example.com".into()
+                } else {
+                    url
+                },
+                enabled,
+                ..Default::default()
+            };
+            engine.database().upsert_feed(&feed).await.unwrap();
+        }
+
+        let (progress, mut events) = tokio::sync::mpsc::unbounded_channel();
+        let summary = engine
+            .update_all_feeds_with_progress(Some(progress))
+            .await
+            .unwrap();
+        let ids: Vec<_> = summary.reports.iter().map(|r| r.feed_id.as_str()).collect();
+        assert_eq!(ids, ["down", "up"]);
+        let mut started = 0;
+        while let Ok(event) = events.try_recv() {
+            match event {
+                UpdateProgress::Started(_) => started += 1,
+                UpdateProgress::Finished(report) => assert!(summary.reports.contains(&report)),
+            }
+        }
+        assert_eq!(started, 2);
+        assert!(summary.reports[0].failed);
+        assert_eq!((summary.failed(), summary.new_entries()), (1, 1));
+        assert!(engine
+            .database()
+            .get_feed("down")
+            .await
+            .unwrap()
+            .unwrap()
+            .last_error
+            .is_some());
+
+        // Both attempts are logged as one run
+        let runs = engine.database().get_runs(None, None, 10).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].run_id, runs[1].run_id);
+        let down = runs.iter().find(|r| r.feed_id == "down").unwrap();
+        assert!(down.failed);
+        assert_eq!(down.error_list().len(), 1);
+        assert_eq!(
+            runs.iter().find(|r| r.feed_id == "up").unwrap().new_entries,
+            1
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Entries presser keeps itself, archived pages, and downloads
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+use presser_db::{ArchivedPage, Entry, Summary};
+use presser_feeds::ContentExtractor;
+
+use super::{fit_content, Engine, SAVED_FEED_ID};
+use crate::ingest::{Ingested, INBOX_FEED_ID};
+use crate::podcasts;
+
+impl Engine {
+    /// Extract and summarize an article that is not in any feed
+    ///
+    /// The article is stored (under its URL as ID) as an entry of the saved
+    /// feed, which is never fetched; if a feed already has it, its entry there
+    /// is updated instead.
+    pub async fn summarize_url(&self, url: &str) -> Result<(Entry, Summary)> {
+        let article = self
+            .fetcher
+            .extract_article_with(url, self.config.extraction_backend_for(url))
+            .await?;
+        let entry = self
+            .store_local_entry(SAVED_FEED_ID, url, Some(article.title), None, article.text)
+            .await?;
+        let summary = self.summarize(&entry).await?;
+        Ok((entry, summary))
+    }
+
+    /// Store an article pushed to `/ingest` in the inbox feed and queue it
+    /// for summarization
+    ///
+    /// Stored under its URL as ID, so pushing it again updates it (as it does
+    /// the entry of a feed that already has it). Content left out is fetched
+    /// and extracted from the URL.
+    pub async fn ingest(&self, item: &Ingested) -> Result<Entry> {
+        item.check()?;
+        let url = item.url.trim();
+        let extractor = ContentExtractor::new().with_boilerplate(
+            self.config.global.strip_boilerplate,
+            &self.config.global.strip_selectors,
+        )?;
+        let (content_html, text, page_title) = match item.content(&extractor) {
+            Some((html, text)) => (html, text, None),
+            None => {
+                let article = self
+                    .fetcher
+                    .extract_article_with(url, self.config.extraction_backend_for(url))
+                    .await?;
+                (None, article.text, Some(article.title))
+            }
+        };
+
+        let entry = self
+            .store_local_entry(
+                INBOX_FEED_ID,
+                url,
+                item.title().or(page_title),
+                content_html,
+                text,
+            )
+            .await?;
+        self.db
+            .enqueue_summaries(std::slice::from_ref(&entry.id))
+            .await?;
+        tracing::info!("Ingested {}", entry.url);
+        Ok(entry)
+    }
+
+    /// Store an article that came from outside the feeds in the local feed
+    /// `feed_id` (saved articles or the inbox), created when first needed
+    ///
+    /// An entry that already has the URL, in any feed, is updated in place.
+    /// Without a title or HTML the stored ones are kept (the URL if there is
+    /// no title).
+    async fn store_local_entry(
+        &self,
+        feed_id: &str,
+        url: &str,
+        title: Option<String>,
+        content_html: Option<String>,
+        text: String,
+    ) -> Result<Entry> {
+        let existing = self.db.get_entry_by_url(url).await?;
+        if existing.is_none() && self.db.get_feed(feed_id).await?.is_none() {
+            self.db
+                .upsert_feed(&presser_db::Feed {
+                    id: feed_id.to_string(),
+                    url: feed_id.to_string(),
+                    title: local_feed_title(feed_id).to_string(),
+                    enabled: false,
+                    ..Default::default()
+                })
+                .await?;
+        }
+        let base = existing.unwrap_or_else(|| Entry {
+            id: url.to_string(),
+            feed_id: feed_id.to_string(),
+            url: url.to_string(),
+            title: url.to_string(),
+            published: Some(chrono::Utc::now()),
+            ..Default::default()
+        });
+        let mut entry = Entry {
+            title: title
+                .filter(|t| !t.trim().is_empty())
+                .unwrap_or_else(|| base.title.clone()),
+            content_html: content_html.or_else(|| base.content_html.clone()),
+            content_text: Some(text),
+            ..base
+        };
+        fit_content(&mut entry, &self.config.content);
+        self.db.upsert_entry(&entry).await?;
+        Ok(entry)
+    }
+
+    /// Fetch `url` and save it to the archive as the page of entry `entry_id`
+    pub async fn archive_page(&self, entry_id: &str, url: &str) -> Result<ArchivedPage> {
+        let html = self.fetcher.fetch_page(url).await?;
+        let page = ArchivedPage {
+            entry_id: entry_id.to_string(),
+            url: url.to_string(),
+            hash: self.archive.put(&html)?,
+            size: html.len() as i64,
+            archived_at: chrono::Utc::now(),
+        };
+        self.db.set_archived_page(&page).await?;
+        Ok(page)
+    }
+
+    /// Archive a stored entry's page now (replacing any earlier snapshot)
+    pub async fn archive_entry(&self, entry_id: &str) -> Result<ArchivedPage> {
+        let entry = self
+            .db
+            .get_entry(entry_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", entry_id))?;
+        self.metered(&entry.feed_id, self.archive_page(&entry.id, &entry.url))
+            .await
+            .0
+    }
+
+    /// The archived page of an entry and its HTML, if one was saved
+    ///
+    /// Works for entries pruned since.
+    pub async fn archived_page(&self, entry_id: &str) -> Result<Option<(ArchivedPage, Vec<u8>)>> {
+        let Some(page) = self.db.get_archived_page(entry_id).await? else {
+            return Ok(None);
+        };
+        let html = self.archive.get(&page.hash)?;
+        Ok(Some((page, html)))
+    }
+
+    /// Download an enclosure into the `[podcasts]` directory, returning
+    /// where it was saved
+    ///
+    /// Progress is written to the enclosures table as the download goes
+    /// (the TUI's downloads view reads it), and a failed download leaves its
+    /// error there.
+    pub async fn download_enclosure(&self, id: i64) -> Result<PathBuf> {
+        let enclosure = self
+            .db
+            .get_enclosure(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Enclosure not found: {}", id))?;
+        let entry = self
+            .db
+            .get_entry(&enclosure.entry_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", enclosure.entry_id))?;
+        let dir = self.config.podcasts.dir(&self.config.database.path);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(podcasts::file_name(&enclosure));
+        let partial = podcasts::partial_path(&path);
+
+        self.db.start_download(id).await?;
+        let progress = AtomicU64::new(0);
+        let download = self.metered(
+            &entry.feed_id,
+            self.fetcher.download(&enclosure.url, &partial, &progress),
+        );
+        tokio::pin!(download);
+        let mut ticks = tokio::time::interval(podcasts::PROGRESS_INTERVAL);
+        let downloaded = loop {
+            tokio::select! {
+                (result, _) = &mut download => break result,
+                _ = ticks.tick() => {
+                    self.db.set_download_progress(id, progress.load(Ordering::Relaxed) as i64).await?;
+                }
+            }
+        };
+        let saved = match downloaded {
+            Ok(bytes) => tokio::fs::rename(&partial, &path)
+                .await
+                .with_context(|| format!("Failed to move the download to {}", path.display()))
+                .map(|_| bytes),
+            Err(e) => Err(e),
+        };
+        match saved {
+            Ok(bytes) => {
+                self.db.set_download_progress(id, bytes as i64).await?;
+                self.db.mark_downloaded(id, &path.to_string_lossy()).await?;
+                Ok(path)
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                self.db.fail_download(id, &format!("{:#}", e)).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Download an image shown with an entry of `feed_id`, counting it
+    /// toward the feed's downloaded bytes
+    pub async fn fetch_image(&self, feed_id: &str, url: &str, max_bytes: usize) -> Result<Vec<u8>> {
+        self.metered(feed_id, self.fetcher.fetch_image(url, max_bytes))
+            .await
+            .0
+    }
+}
+
+/// Title of a local feed, shown where feeds are listed
+fn local_feed_title(feed_id: &str) -> &'static str {
+    if feed_id == INBOX_FEED_ID {
+        "Inbox"
+    } else {
+        "Saved articles"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::create_test_engine;
+
+    #[tokio::test]
+    async fn test_ingest_reuses_entry_of_url() {
+        let engine = create_test_engine().await;
+        let fixtures = presser_db::fixtures::seed(engine.database()).await.unwrap();
+        let stored = &fixtures.entries[0];
+
+        // A URL a feed already has updates that feed's entry
+        let item = Ingested {
+            url: stored.url.clone(),
+            content: Some("Pushed text".into()),
+            ..Default::default()
+        };
+        let entry = engine.ingest(&item).await.unwrap();
+        assert_eq!(
+            (entry.id.as_str(), entry.feed_id.as_str()),
+            (stored.id.as_str(), stored.feed_id.as_str())
+        );
+        assert_eq!(entry.title, stored.title);
+        assert_eq!(entry.content_text.as_deref(), Some("Pushed text"));
+        assert!(engine
+            .database()
+            .get_feed(INBOX_FEED_ID)
+            .await
+            .unwrap()
+            .is_none());
+
+        // A new one goes to the inbox
+        let item = Ingested {
+            url: "https://example.com/pushed".into(),
+            content: Some("New text".into()),
+            ..Default::default()
+        };
+        let entry = engine.ingest(&item).await.unwrap();
+        assert_eq!(
+            (entry.feed_id.as_str(), entry.title.as_str()),
+            (INBOX_FEED_ID, "https://example.com/pushed")
+        );
+        let inbox = engine
+            .database()
+            .get_feed(INBOX_FEED_ID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(inbox.title, "Inbox");
+    }
+}
@@ -0,0 +1,515 @@
+//! Core engine that orchestrates all components
+
+mod digests;
+mod fetch;
+mod local;
+mod plan;
+mod summaries;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use presser_ai::AiClient;
+use presser_config::{Config, ContentConfig};
+use presser_db::{Database, Entry, PruneReport, RetentionPolicy};
+use presser_feeds::{ContentExtractor, CookieJar, FeedFetcher};
+use presser_scheduler::Scheduler;
+
+use crate::archive::PageArchive;
+use crate::auth::FeedTokens;
+use crate::filter::Filters;
+use crate::hooks::ScriptHooks;
+use crate::ingest::INBOX_FEED_ID;
+use crate::metrics;
+use crate::notify::Notifications;
+use crate::pipeline::{Pipeline, Pipelines};
+use crate::reload::ConfigDiff;
+use crate::update::AiBudget;
+
+/// Feed that holds articles summarized by URL (cannot clash with a feed ID slug)
+pub const SAVED_FEED_ID: &str = "presser:saved";
+
+/// Whether a feed is one presser keeps itself (saved or pushed articles)
+/// rather than one that is fetched
+pub fn is_local_feed(feed_id: &str) -> bool {
+    feed_id == SAVED_FEED_ID || feed_id == INBOX_FEED_ID
+}
+
+/// Set by `--offline`: engines created afterwards never fetch
+static FORCE_OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Keep every engine created from now on offline, as if `global.offline` were set
+pub fn force_offline() {
+    FORCE_OFFLINE.store(true, Ordering::Relaxed);
+}
+
+/// Main application engine
+pub struct Engine {
+    config: Config,
+    db: Database,
+    fetcher: FeedFetcher,
+    ai: Arc<AiClient>,
+    /// Rate limit and daily token budget for `ai`
+    ai_budget: Arc<AiBudget>,
+    /// Tokens of feeds with `[feed.auth]`
+    tokens: Arc<FeedTokens>,
+    notifications: Notifications,
+    hooks: ScriptHooks,
+    scheduler: Option<Scheduler>,
+    /// Hooks run on entries during updates
+    pipelines: Pipelines,
+    /// Filter expressions deciding which fetched entries are stored
+    filters: Filters,
+    /// Saved pages of entries (`[archive]`)
+    archive: PageArchive,
+    /// Identifies this engine's feed leases (process ID plus a random suffix)
+    lease_holder: String,
+    /// `global.offline` or `--offline`: updates are deferred without fetching
+    forced_offline: bool,
+    /// Whether the network looked down at the last failed fetch or check
+    offline: AtomicBool,
+}
+
+impl Engine {
+    /// Create a new engine instance
+    pub async fn new() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .map(|d| d.join("presser"))
+            .unwrap_or_else(|| std::path::PathBuf::from("."));
+
+        let config = Config::load_from_dir(&config_dir)?;
+        Self::with_config(config).await
+    }
+
+    /// Initialize from custom config
+    pub async fn with_config(config: Config) -> Result<Self> {
+        let db_path = config.database.path.clone();
+
+        let key = config.database.encryption_key()?;
+        let db = Database::open_with_key(&db_path, key.as_deref()).await?;
+        db.migrate().await?;
+        db.sync_folders(&config.folder_assignments()).await?;
+
+        let fetcher = build_fetcher(&config)?;
+        let forced_offline = config.global.offline || FORCE_OFFLINE.load(Ordering::Relaxed);
+        let ai = build_ai(&config)?;
+        let ai_budget = Arc::new(AiBudget::from_config(&config.ai));
+        let tokens = Arc::new(FeedTokens::new(db.clone(), fetcher.client().clone()));
+        let notifications = Notifications::from_config(&config.notifications)?;
+        let hooks = ScriptHooks::from_config(&config.hooks);
+        let filters = Filters::from_config(&config)?;
+        let archive = PageArchive::new(config.archive.dir(&config.database.path));
+
+        Ok(Self {
+            config,
+            db,
+            fetcher,
+            ai,
+            ai_budget,
+            tokens,
+            notifications,
+            hooks,
+            scheduler: None,
+            pipelines: Pipelines::default(),
+            filters,
+            archive,
+            lease_holder: format!("{}-{:08x}", std::process::id(), rand::random::<u32>()),
+            forced_offline,
+            offline: AtomicBool::new(forced_offline),
+        })
+    }
+
+    /// An engine for `config`, which differs from this engine's by `diff`,
+    /// sharing what the difference leaves alone
+    ///
+    /// The database is reopened only if `[database]` changed, and the AI
+    /// client rebuilt only if its settings did (see
+    /// [`ConfigDiff::rebuilds_ai`]); registered pipelines carry over, while
+    /// the fetcher and everything else is built from `config` as
+    /// [`with_config`](Self::with_config) does.
+    pub async fn reconfigured(&self, config: Config, diff: &ConfigDiff) -> Result<Self> {
+        if diff.reopens_database() {
+            let mut engine = Self::with_config(config).await?;
+            engine.pipelines = self.pipelines.clone();
+            return Ok(engine);
+        }
+        let db = self.db.clone();
+        db.sync_folders(&config.folder_assignments()).await?;
+
+        let fetcher = build_fetcher(&config)?;
+        let forced_offline = config.global.offline || FORCE_OFFLINE.load(Ordering::Relaxed);
+        let ai = match diff.rebuilds_ai() {
+            true => build_ai(&config)?,
+            false => self.ai.clone(),
+        };
+        // Keeps today's token count unless the limits changed
+        let ai_budget = match diff.section_changed("ai") {
+            true => Arc::new(AiBudget::from_config(&config.ai)),
+            false => self.ai_budget.clone(),
+        };
+        let tokens = self.tokens.clone();
+        let notifications = Notifications::from_config(&config.notifications)?;
+        let hooks = ScriptHooks::from_config(&config.hooks);
+        let filters = Filters::from_config(&config)?;
+        let archive = PageArchive::new(config.archive.dir(&config.database.path));
+        // Still offline if the network was down, unless only offline mode made it so
+        let offline = forced_offline || (self.is_offline() && !self.forced_offline);
+
+        Ok(Self {
+            config,
+            db,
+            fetcher,
+            ai,
+            ai_budget,
+            tokens,
+            notifications,
+            hooks,
+            scheduler: None,
+            pipelines: self.pipelines.clone(),
+            filters,
+            archive,
+            lease_holder: format!("{}-{:08x}", std::process::id(), rand::random::<u32>()),
+            forced_offline,
+            offline: AtomicBool::new(offline),
+        })
+    }
+
+    /// Whether updates are being deferred: offline mode is on, or the last
+    /// fetch failed because the network is down
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    /// Check whether the network is reachable again, updating `is_offline`
+    ///
+    /// Always offline in offline mode.
+    pub async fn check_network(&self) -> bool {
+        let online = !self.forced_offline && self.fetcher.is_online().await;
+        self.offline.store(!online, Ordering::Relaxed);
+        online
+    }
+
+    /// Run `pipeline` on entries from now on, after the hooks registered earlier
+    ///
+    /// Hooks carry over to the engine `reload-config` creates in the daemon
+    /// (see [`reconfigured`](Self::reconfigured)).
+    #[allow(dead_code)] // the binary registers none; this is for library users
+    pub fn register_pipeline(&mut self, pipeline: impl Pipeline + 'static) {
+        self.pipelines.push(Arc::new(pipeline));
+    }
+
+    /// Fetch feeds whose addresses match any of `patterns` through `adapter`
+    /// (see [`presser_feeds::AdapterRegistry`]), ahead of the builtin adapters
+    ///
+    /// Unlike pipelines, adapters belong to this engine's fetcher: an engine
+    /// created by `reload-config` starts without any.
+    #[allow(dead_code)] // the binary registers none; this is for library users
+    pub fn register_adapter(
+        &mut self,
+        patterns: &[&str],
+        adapter: Arc<dyn presser_feeds::SourceAdapter>,
+    ) {
+        self.fetcher.register_adapter(patterns, adapter);
+    }
+
+    /// Retention policy from the `[retention]` config section
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        let retention = &self.config.retention;
+        RetentionPolicy {
+            max_age_days: retention.max_age_days,
+            max_entries_per_feed: retention.max_entries_per_feed,
+            archive: retention.archive,
+        }
+    }
+
+    /// Prune entries according to the configured retention policy
+    pub async fn prune(&self) -> Result<PruneReport> {
+        let report = self.db.prune(&self.retention_policy()).await?;
+        tracing::info!(
+            "Pruned {} entries ({} archived)",
+            report.deleted,
+            report.archived
+        );
+        Ok(report)
+    }
+
+    /// Forget update runs older than `retention.run_log_days`, returning how many
+    pub async fn prune_run_log(&self) -> Result<u64> {
+        let Some(days) = self.config.retention.run_log_days else {
+            return Ok(0);
+        };
+        let pruned = self
+            .db
+            .prune_runs(chrono::Utc::now() - chrono::Duration::days(i64::from(days)))
+            .await?;
+        tracing::info!("Pruned {} update log records", pruned);
+        Ok(pruned)
+    }
+
+    /// Get database reference
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    /// Get config reference
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Get fetcher reference
+    pub fn fetcher(&self) -> &FeedFetcher {
+        &self.fetcher
+    }
+
+    /// Get the AI client
+    pub fn ai(&self) -> &AiClient {
+        &self.ai
+    }
+
+    /// Get the compiled filter rules
+    pub fn filters(&self) -> &Filters {
+        &self.filters
+    }
+}
+
+/// Fetcher with the `[global]` fetch settings of `config`
+fn build_fetcher(config: &Config) -> Result<FeedFetcher> {
+    let extractor = ContentExtractor::new()
+        .with_boilerplate(
+            config.global.strip_boilerplate,
+            &config.global.strip_selectors,
+        )
+        .context("Invalid global.strip_selectors")?;
+    let fetcher = FeedFetcher::new()?
+        .with_extractor(extractor)
+        .with_nitter_instance(config.global.nitter_instance.clone())
+        .with_connectivity_check(&config.global.connectivity_check)
+        .with_user_agent(&config.global.user_agent)
+        .with_request_observer(metrics::feeds_observer(config.global.slow_request()));
+    Ok(match config.cookies.file(&config.database.path) {
+        Some(path) => fetcher.with_cookie_jar(Arc::new(CookieJar::open(path)?)),
+        None => fetcher,
+    })
+}
+
+/// AI client for the `[ai]` settings of `config`
+fn build_ai(config: &Config) -> Result<Arc<AiClient>> {
+    let ai_config = presser_ai::AiConfig {
+        provider: match config.ai.provider {
+            presser_config::AiProvider::OpenAI => presser_ai::AiProvider::OpenAI,
+            presser_config::AiProvider::Anthropic => presser_ai::AiProvider::Anthropic,
+            presser_config::AiProvider::Local => presser_ai::AiProvider::Local,
+        },
+        api_key: config.ai.api_key.clone(),
+        model: config.ai.model.clone(),
+        endpoint: config.ai.endpoint.clone(),
+        system_prompt: config.ai.system_prompt.clone(),
+        max_tokens: config.ai.max_tokens,
+        temperature: config.ai.temperature,
+        enable_cache: config.ai.enable_cache,
+        max_input_bytes: config.content.prompt_limit(),
+        truncation: config.content.truncation,
+    };
+    Ok(Arc::new(AiClient::new(ai_config)?.with_request_observer(
+        metrics::ai_observer(config.global.slow_request()),
+    )))
+}
+
+/// Cut an entry's HTML and text to the `[content]` limits
+fn fit_content(entry: &mut Entry, limits: &ContentConfig) {
+    let fields = [
+        (&mut entry.content_html, limits.html_limit()),
+        (&mut entry.content_text, limits.text_limit()),
+    ];
+    for (content, limit) in fields {
+        if let (Some(text), Some(max_bytes)) = (content.as_mut(), limit) {
+            if text.len() > max_bytes {
+                *text = presser_ai::truncate(text, max_bytes, limits.truncation).into_owned();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use presser_config::{AiConfig, AiProvider, DatabaseConfig, GlobalConfig, SchedulerConfig};
+    use std::collections::HashMap;
+
+    pub(crate) async fn create_test_engine() -> Engine {
+        Engine::with_config(test_config()).await.unwrap()
+    }
+
+    /// Address of a port that was just free, so every request to the AI
+    /// provider fails to connect; the same for every config, so configs compare equal
+    pub(crate) fn refused_endpoint() -> String {
+        static ENDPOINT: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+        ENDPOINT
+            .get_or_init(|| {
+                let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+                format!("http://{}", listener.local_addr().unwrap())
+            })
+            .clone()
+    }
+
+    pub(crate) fn test_config() -> Config {
+        Config {
+            global: GlobalConfig {
+                // Failed fetches are feed failures, whatever the sandbox's network
+                connectivity_check: String::new(),
+                // Nothing fetches the fixtures' article pages
+                extract_content: false,
+                ..Default::default()
+            },
+            ai: AiConfig {
+                provider: AiProvider::Local,
+                api_key: None,
+                model: "test-model".to_string(),
+                endpoint: Some(refused_endpoint()),
+                system_prompt: "test prompt".to_string(),
+                max_tokens: 100,
+                temperature: 0.7,
+                enable_cache: true,
+                daily_token_budget: None,
+                requests_per_minute: None,
+                max_concurrent_requests: 4,
+                describe_changes: false,
+            },
+            database: DatabaseConfig {
+                path: presser_db::MEMORY_PATH.into(),
+                max_connections: 5,
+                encryption_key: None,
+                encryption_key_command: None,
+            },
+            scheduler: SchedulerConfig {
+                default_interval: "0 0 */6 * * *".to_string(),
+                auto_update: true,
+                groups: Default::default(),
+            },
+            retention: Default::default(),
+            digest: Default::default(),
+            notifications: Default::default(),
+            daemon: Default::default(),
+            logging: Default::default(),
+            integrations: Default::default(),
+            hooks: Default::default(),
+            ui: Default::default(),
+            filters: Vec::new(),
+            scoring: Default::default(),
+            archive: Default::default(),
+            podcasts: Default::default(),
+            cookies: Default::default(),
+            content: Default::default(),
+            feeds: HashMap::new(),
+        }
+    }
+
+    /// Serve `body` as RSS at `/feed.xml` of a mock server, returning the
+    /// server (which serves only while kept) and the feed URL
+    pub(crate) async fn serve_rss(body: &str) -> (mockito::ServerGuard, String) {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/feed.xml")
+            .with_header("content-type", "application/rss+xml")
+            .with_body(body)
+            .create_async()
+            .await;
+        let url = format!("{}/feed.xml", server.url());
+        (server, url)
+    }
+
+    #[tokio::test]
+    async fn test_engine_creation() {
+        let _engine = create_test_engine().await;
+    }
+
+    #[tokio::test]
+    async fn test_reconfigured() {
+        let engine = create_test_engine().await;
+        let fixtures = presser_db::fixtures::seed(engine.database()).await.unwrap();
+
+        let mut config = test_config();
+        config.ai.daily_token_budget = Some(10_000);
+        let diff = ConfigDiff::between(engine.config(), &config);
+        let budgeted = engine.reconfigured(config.clone(), &diff).await.unwrap();
+        // Same database and AI client, new budget
+        let stats = budgeted.database().get_stats().await.unwrap();
+        assert_eq!(stats.total_entries, fixtures.entries.len() as i64);
+        assert!(Arc::ptr_eq(&engine.ai, &budgeted.ai));
+        assert!(!Arc::ptr_eq(&engine.ai_budget, &budgeted.ai_budget));
+
+        config.ai.model = "other-model".into();
+        let diff = ConfigDiff::between(budgeted.config(), &config);
+        let remodelled = budgeted.reconfigured(config, &diff).await.unwrap();
+        assert!(!Arc::ptr_eq(&budgeted.ai, &remodelled.ai));
+        assert_eq!(remodelled.config().ai.model, "other-model");
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_fixtures_without_policy() {
+        let engine = create_test_engine().await;
+        let fixtures = presser_db::fixtures::seed(engine.database()).await.unwrap();
+        assert!(engine.retention_policy().is_noop());
+        assert_eq!(engine.prune().await.unwrap(), Default::default());
+        let stats = engine.database().get_stats().await.unwrap();
+        assert_eq!(stats.total_entries, fixtures.entries.len() as i64);
+    }
+
+    #[test]
+    fn test_fit_content() {
+        let limits = ContentConfig {
+            max_html_bytes: 64,
+            max_text_bytes: 0,
+            ..Default::default()
+        };
+        let paragraph = format!("<p>{}</p>", "word ".repeat(8));
+        let mut entry = Entry {
+            content_html: Some(paragraph.repeat(20)),
+            content_text: Some("word ".repeat(1000)),
+            ..Default::default()
+        };
+        fit_content(&mut entry, &limits);
+        assert_eq!(entry.content_html.unwrap(), format!("{}\n\n[…]", paragraph));
+        // No limit
+        assert_eq!(entry.content_text.unwrap().len(), 5000);
+    }
+
+    #[tokio::test]
+    async fn test_offline() {
+        let dead = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead_addr = dead.local_addr().unwrap().to_string();
+        drop(dead);
+        let feed = presser_db::Feed {
+            id: "down".into(),
+            url: format!("http://{}/feed.xml", dead_addr),
+            ..Default::default()
+        };
+
+        // The connectivity check fails too, so the network is down
+        let mut config = test_config();
+        config.global.connectivity_check = dead_addr;
+        let engine = Engine::with_config(config.clone()).await.unwrap();
+        engine.database().upsert_feed(&feed).await.unwrap();
+        assert!(!engine.is_offline());
+        let report = engine.update_feed("down").await.unwrap();
+        assert!(report.offline && !report.failed);
+        assert!(engine.is_offline());
+        let stored = engine.database().get_feed("down").await.unwrap().unwrap();
+        assert!(stored.last_error.is_none());
+        assert!(engine
+            .database()
+            .get_runs(None, None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Offline mode doesn't even try
+        config.global.offline = true;
+        let engine = Engine::with_config(config).await.unwrap();
+        assert!(engine.is_offline());
+        assert!(!engine.check_network().await);
+        engine.database().upsert_feed(&feed).await.unwrap();
+        assert!(engine.update_feed("down").await.unwrap().offline);
+    }
+}
@@ -0,0 +1,406 @@
+//! Which fetched entries updates keep, dry runs of updates, and feed upkeep
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use presser_config::Config;
+use presser_db::{Entry, MuteKind, MuteRule};
+use presser_feeds::{ConditionalFetch, Validators};
+
+use super::{is_local_feed, Engine};
+use crate::audit::{self, FeedAudit};
+use crate::dedup;
+use crate::filter::{self, Fields};
+use crate::mute::{self, Mutes};
+use crate::pipeline::Verdict;
+use crate::update::{KeywordFilter, UpdatePlan, UpdateReport, UrlMove};
+
+/// An entry of a fetch that an update keeps (see `Engine::select_entries`),
+/// with what is stored apart from it
+pub(super) struct SelectedEntry {
+    pub(super) entry: Entry,
+    pub(super) discussion: Option<presser_feeds::Discussion>,
+    pub(super) enclosures: Vec<presser_feeds::FeedEnclosure>,
+    pub(super) categories: Vec<String>,
+}
+
+impl Engine {
+    /// Fetch a feed and work out what `update_feed` would store and summarize,
+    /// without writing to the database or calling the AI provider
+    ///
+    /// Entries are selected as an update selects them, pipeline hooks
+    /// included; no article pages are fetched.
+    pub async fn plan_feed_update(&self, feed_id: &str) -> Result<UpdatePlan> {
+        let feed = self
+            .db
+            .get_feed(feed_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Feed not found: {}", feed_id))?;
+        let mut plan = UpdatePlan {
+            feed_id: feed_id.to_string(),
+            ..Default::default()
+        };
+
+        let state = self.db.get_fetch_state(feed_id).await?.unwrap_or_default();
+        let validators = Validators {
+            etag: state.etag,
+            last_modified: state.last_modified,
+        };
+        let (fetch, _) = self
+            .fetch_feed(
+                feed_id,
+                &feed.url,
+                &validators,
+                state.alternate_user_agent.as_deref(),
+            )
+            .await;
+        let fetch = fetch?;
+        let unchanged = fetch.is_unchanged(state.content_hash.as_deref());
+        let entries = match fetch {
+            ConditionalFetch::Modified { entries, .. } if !unchanged => entries,
+            _ => {
+                plan.not_modified = true;
+                return Ok(plan);
+            }
+        };
+
+        let summarize = self
+            .config
+            .feeds
+            .get(&feed.url)
+            .map(|c| c.enable_ai)
+            .unwrap_or(true);
+        let extract = self.config.extracts_content_for(&feed.url);
+        // Hooks report into a throwaway report; only their verdicts count here
+        let mut report = UpdateReport::default();
+        let (selected, known) = self.select_entries(&feed, entries, &mut report).await?;
+        plan.skipped = report.skipped;
+        for SelectedEntry { entry, .. } in selected {
+            if known.contains(&entry.id) {
+                continue;
+            }
+            let has_text = entry.content_text.is_some()
+                || entry.summary.is_some()
+                || (extract && !entry.url.is_empty());
+            if summarize && has_text {
+                plan.to_summarize += 1;
+            }
+            plan.new.push(entry.title);
+        }
+        Ok(plan)
+    }
+
+    /// The entries of a fetch of `feed` that an update keeps, with the IDs
+    /// of those already stored
+    ///
+    /// A mirror's entries whose IDs another feed has get IDs of their own;
+    /// entries the keyword filters, filter rules, mutes, or pipeline hooks
+    /// drop are counted in `report.skipped`.
+    pub(super) async fn select_entries(
+        &self,
+        feed: &presser_db::Feed,
+        mut entries: Vec<presser_feeds::FeedEntry>,
+        report: &mut UpdateReport,
+    ) -> Result<(Vec<SelectedEntry>, HashSet<String>)> {
+        let feed_id = feed.id.as_str();
+        let feed_config = self.config.feeds.get(&feed.url);
+        let filter = KeywordFilter::for_feed(feed_config);
+        let rules = self.filters.for_feed(&feed.url, feed_config);
+        let mutes = self.mutes().await?;
+        // A mirror repeating another feed's entry IDs gets its own
+        let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+        let owners = self.db.get_entry_feed_ids(&ids).await?;
+        for entry in &mut entries {
+            if owners.get(&entry.id).is_some_and(|owner| owner != feed_id) {
+                entry.id = dedup::scoped_id(feed_id, &entry.id);
+            }
+        }
+        let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+        let known = self.db.get_known_entry_ids(&ids).await?;
+
+        let mut selected = Vec::with_capacity(entries.len());
+        for mut entry in entries {
+            if !filter.matches(&entry)
+                || self.dropped_by_rules(&rules, &entry, feed_id, feed_config)
+                || muted(&mutes, &entry, feed_id)
+                || self.pipelines.entry_fetched(feed, &mut entry, report).await == Verdict::Drop
+            {
+                report.skipped += 1;
+                continue;
+            }
+            // Stored apart from the entry
+            let discussion = entry.discussion.take();
+            let enclosures = std::mem::take(&mut entry.enclosures);
+            let categories = entry.categories.clone();
+            let mut entry = Entry {
+                feed_id: feed_id.to_string(),
+                ..Entry::from(entry)
+            };
+            if self.pipelines.before_store(&mut entry, report).await == Verdict::Drop {
+                report.skipped += 1;
+                continue;
+            }
+            selected.push(SelectedEntry {
+                entry,
+                discussion,
+                enclosures,
+                categories,
+            });
+        }
+        Ok((selected, known))
+    }
+
+    /// `plan_feed_update` for every enabled feed, ordered by feed ID
+    pub async fn plan_all_feeds(&self) -> Result<Vec<UpdatePlan>> {
+        let feeds = self.db.get_all_feeds().await?;
+        let limit = self.config.global.max_concurrent_fetches.max(1);
+        let mut plans: Vec<UpdatePlan> = stream::iter(feeds.into_iter().filter(|f| f.enabled))
+            .map(|feed| async move {
+                self.plan_feed_update(&feed.id)
+                    .await
+                    .unwrap_or_else(|e| UpdatePlan {
+                        feed_id: feed.id.clone(),
+                        error: Some(format!("{:#}", e)),
+                        ..Default::default()
+                    })
+            })
+            .buffer_unordered(limit)
+            .collect()
+            .await;
+        plans.sort_by(|a, b| a.feed_id.cmp(&b.feed_id));
+        Ok(plans)
+    }
+
+    /// Whether one of `rules` drops a fetched entry (logged with the rule)
+    fn dropped_by_rules(
+        &self,
+        rules: &[&filter::Rule],
+        entry: &presser_feeds::FeedEntry,
+        feed_id: &str,
+        feed_config: Option<&presser_config::FeedConfig>,
+    ) -> bool {
+        if rules.is_empty() {
+            return false;
+        }
+        let fields = Fields::of_fetched(entry, feed_id, feed_config);
+        match filter::dropped_by(rules, &fields) {
+            Some(rule) => {
+                tracing::debug!(
+                    "Filter '{}' dropped {} from {}",
+                    rule.name,
+                    entry.id,
+                    feed_id
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Mute entries matching `pattern` for `duration`, or until unmuted,
+    /// returning the rule's ID
+    pub async fn mute(
+        &self,
+        kind: MuteKind,
+        pattern: &str,
+        duration: Option<chrono::Duration>,
+    ) -> Result<i64> {
+        mute::check(kind, pattern)?;
+        let expires_at = duration.map(|d| chrono::Utc::now() + d);
+        Ok(self
+            .db
+            .add_mute_rule(kind, pattern.trim(), expires_at)
+            .await?)
+    }
+
+    /// Mute rules in force, after forgetting expired ones
+    pub async fn mute_rules(&self) -> Result<Vec<MuteRule>> {
+        let now = chrono::Utc::now();
+        self.db.delete_expired_mute_rules(now).await?;
+        Ok(self.db.get_active_mute_rules(now).await?)
+    }
+
+    /// Mute rules in force, ready to match fetched entries
+    async fn mutes(&self) -> Result<Mutes> {
+        Ok(Mutes::new(
+            self.db.get_active_mute_rules(chrono::Utc::now()).await?,
+        ))
+    }
+
+    /// Follow the permanent redirects recorded by updates: point each moved
+    /// feed at its new URL in `feeds/*.toml` under `config_dir` and in the
+    /// database, keeping the feed ID and so its entries
+    ///
+    /// A move onto a URL another feed already uses is skipped. With `dry_run`
+    /// nothing is written. This engine's config still has the old URLs, so
+    /// callers that keep running should reload it afterwards.
+    pub async fn apply_url_moves(
+        &self,
+        config_dir: &std::path::Path,
+        dry_run: bool,
+    ) -> Result<Vec<UrlMove>> {
+        let feeds = self.db.get_all_feeds().await?;
+        let mut moves = Vec::new();
+        for (feed_id, to) in self.db.feed_moves().await? {
+            let Some(feed) = feeds.iter().find(|f| f.id == feed_id) else {
+                continue;
+            };
+            let mut url_move = UrlMove {
+                feed_id,
+                from: feed.url.clone(),
+                to,
+                config_file: None,
+                skipped: None,
+            };
+            if let Some(other) = feeds
+                .iter()
+                .find(|f| f.url == url_move.to && f.id != feed.id)
+            {
+                url_move.skipped = Some(format!(
+                    "{} is already used by feed {}",
+                    url_move.to, other.id
+                ));
+            } else if !dry_run {
+                url_move.config_file =
+                    Config::rewrite_feed_url(config_dir, &url_move.from, &url_move.to)?;
+                self.db
+                    .move_feed_url(&url_move.feed_id, &url_move.to)
+                    .await?;
+                tracing::info!(
+                    "Moved feed {} from {} to {}",
+                    url_move.feed_id,
+                    url_move.from,
+                    url_move.to
+                );
+            }
+            moves.push(url_move);
+        }
+        Ok(moves)
+    }
+
+    /// Enabled feeds that look dead: gone for several fetches in a row
+    /// (404/410) or silent for `months`, each with the unsubscribed feeds
+    /// its site advertises now
+    pub async fn audit_feeds(&self, months: u32) -> Result<Vec<FeedAudit>> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(30 * i64::from(months));
+        let feeds = self.db.get_all_feeds().await?;
+        let health = self.db.get_feed_health().await?;
+        let mut audits = Vec::new();
+        for feed in feeds.iter().filter(|f| f.enabled && !is_local_feed(&f.id)) {
+            let Some(reason) = health
+                .iter()
+                .find(|h| h.feed_id == feed.id)
+                .and_then(|h| audit::assess(feed, h, cutoff))
+            else {
+                continue;
+            };
+            let mut replacements = Vec::new();
+            if let Some(page) = audit::discovery_page(feed) {
+                match self.fetcher.discover(&page).await {
+                    Ok(found) => replacements = found,
+                    Err(e) => tracing::warn!("Failed to look for feeds on {}: {:#}", page, e),
+                }
+            }
+            replacements.retain(|url| !feeds.iter().any(|f| f.url == *url));
+            audits.push(FeedAudit {
+                feed: feed.clone(),
+                reason,
+                replacements,
+            });
+        }
+        Ok(audits)
+    }
+}
+
+/// Whether a mute rule drops a fetched entry (logged with the rule)
+fn muted(mutes: &Mutes, entry: &presser_feeds::FeedEntry, feed_id: &str) -> bool {
+    match mutes.matching(entry) {
+        Some(rule) => {
+            tracing::debug!(
+                "Mute rule {} ('{}') dropped {} from {}",
+                rule.id,
+                rule.pattern,
+                entry.id,
+                feed_id
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::{serve_rss, test_config};
+
+    #[tokio::test]
+    async fn test_apply_url_moves() {
+        // `/old.xml` moved permanently to the feed at `url`
+        let (mut server, url) = serve_rss(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Test</title><link>https://example.com</link>
+            <item><guid>a</guid><title>Rust news</title><link>https://example.com/a</link></item>
+            </channel></rss>"#,
+        )
+        .await;
+        server
+            .mock("GET", "/old.xml")
+            .with_status(301)
+            .with_header("location", &url)
+            .create_async()
+            .await;
+        let old = format!("{}/old.xml", server.url());
+
+        let dir = tempfile::tempdir().unwrap();
+        let feed_config = presser_config::FeedConfig::new(old.clone(), "Test");
+        std::fs::create_dir(dir.path().join("feeds")).unwrap();
+        std::fs::write(
+            dir.path().join("feeds/test.toml"),
+            feed_config.to_toml().unwrap(),
+        )
+        .unwrap();
+        let mut config = test_config();
+        config.feeds.insert(old.clone(), feed_config);
+        let engine = Engine::with_config(config).await.unwrap();
+        let db = engine.database();
+        db.upsert_feed(&presser_db::Feed {
+            id: "test".into(),
+            url: old.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let report = engine.update_feed("test").await.unwrap();
+        assert_eq!(report.new, 1);
+        assert_eq!(report.moved_to.as_deref(), Some(url.as_str()));
+
+        let moves = engine.apply_url_moves(dir.path(), true).await.unwrap();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(
+            (moves[0].from.as_str(), moves[0].to.as_str()),
+            (old.as_str(), url.as_str())
+        );
+        assert_eq!(moves[0].config_file, None);
+        assert_eq!(db.get_feed("test").await.unwrap().unwrap().url, old);
+
+        let moves = engine.apply_url_moves(dir.path(), false).await.unwrap();
+        assert_eq!(
+            moves[0].config_file,
+            Some(dir.path().join("feeds/test.toml"))
+        );
+        assert_eq!(db.get_feed("test").await.unwrap().unwrap().url, url);
+        assert_eq!(db.get_entries_for_feed("test", 10).await.unwrap().len(), 1);
+        assert!(Config::load_from_dir(dir.path())
+            .unwrap()
+            .feeds
+            .contains_key(&url));
+        assert!(engine
+            .apply_url_moves(dir.path(), false)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
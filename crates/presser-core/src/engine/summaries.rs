@@ -0,0 +1,307 @@
+//! Summarizing stored entries
+
+use anyhow::Result;
+use presser_ai::{SummaryOptions, SummaryStyle};
+use presser_db::{Entry, EntryFilter, Summary};
+use tokio::sync::mpsc;
+
+use super::{fit_content, Engine};
+use crate::update::{self, UpdateReport};
+
+/// Entries summarized in one style, as `(entry ID, content)` pairs
+type StyleGroup = (Option<SummaryStyle>, Vec<(String, String)>);
+
+impl Engine {
+    /// Summarize a stored entry (replacing any current summary)
+    ///
+    /// Entries with neither extracted text nor a feed description are extracted first.
+    pub async fn summarize_entry(&self, entry_id: &str) -> Result<(Entry, Summary)> {
+        self.summarize_entry_streaming(entry_id, SummaryOptions::default(), None)
+            .await
+    }
+
+    /// Summarize a stored entry with `options`, sending the summary text to
+    /// `stream` as the provider generates it
+    pub async fn summarize_entry_streaming(
+        &self,
+        entry_id: &str,
+        options: SummaryOptions,
+        stream: Option<mpsc::UnboundedSender<String>>,
+    ) -> Result<(Entry, Summary)> {
+        let mut entry = self
+            .db
+            .get_entry(entry_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", entry_id))?;
+        let feed_url = self
+            .db
+            .get_feed(&entry.feed_id)
+            .await?
+            .map(|f| f.url)
+            .unwrap_or_default();
+        if entry.content_text.is_none() && entry.summary.is_none() {
+            let backend = self.config.extraction_backend_for(&feed_url);
+            entry.content_text = Some(
+                self.fetcher
+                    .extract_content_with(&entry.url, backend)
+                    .await?,
+            );
+            fit_content(&mut entry, &self.config.content);
+            self.db.upsert_entry(&entry).await?;
+        }
+        let options = SummaryOptions {
+            style: options.style.or(self.summary_style(&feed_url)),
+            ..options
+        };
+        let summary = self.summarize_with(&entry, options, stream).await?;
+        Ok((entry, summary))
+    }
+
+    /// Summarize stored entries that have no summary yet, newest first
+    ///
+    /// Entries of feeds with AI turned off are left alone. Failures end up in the report.
+    pub async fn summarize_missing(&self, limit: Option<i64>) -> Result<UpdateReport> {
+        let ai_disabled = self.ai_disabled_feeds().await?;
+        let mut filter = EntryFilter::new().has_summary(false);
+        if let Some(limit) = limit {
+            filter = filter.limit(limit);
+        }
+        let pending = self
+            .db
+            .query_entries(&filter)
+            .await?
+            .into_iter()
+            .filter(|e| !ai_disabled.contains(&e.feed_id))
+            .filter_map(|e| {
+                let content = e.content_text.or(e.summary)?;
+                Some((e.feed_id, e.id, content))
+            })
+            .collect();
+
+        let mut report = UpdateReport::default();
+        self.summarize_in_feed_styles(pending, &mut report).await?;
+        Ok(report)
+    }
+
+    /// Summarize entries waiting in the summary queue, oldest first, as far as
+    /// the daily token budget allows
+    ///
+    /// Entries that are gone, have no text, or belong to feeds with AI turned
+    /// off are taken off the queue.
+    pub async fn drain_summary_queue(&self, limit: Option<i64>) -> Result<UpdateReport> {
+        let ai_disabled = self.ai_disabled_feeds().await?;
+        let mut pending = Vec::new();
+        let mut stale = Vec::new();
+        for queued in self.db.queued_summaries(limit.unwrap_or(i64::MAX)).await? {
+            let content = self
+                .db
+                .get_entry(&queued.entry_id)
+                .await?
+                .filter(|e| !ai_disabled.contains(&e.feed_id))
+                .and_then(|e| Some((e.feed_id, e.content_text.or(e.summary)?)));
+            match content {
+                Some((feed_id, content)) => pending.push((feed_id, queued.entry_id, content)),
+                None => stale.push(queued.entry_id),
+            }
+        }
+        self.db.dequeue_summaries(&stale).await?;
+
+        let mut report = UpdateReport::default();
+        self.summarize_in_feed_styles(pending, &mut report).await?;
+        Ok(report)
+    }
+
+    /// Summarize `(feed_id, entry_id, content)` triples of any feeds, each
+    /// entry in its feed's style
+    async fn summarize_in_feed_styles(
+        &self,
+        pending: Vec<(String, String, String)>,
+        report: &mut UpdateReport,
+    ) -> Result<()> {
+        let feeds = self.db.get_all_feeds().await?;
+        let mut by_style: Vec<StyleGroup> = Vec::new();
+        for (feed_id, entry_id, content) in pending {
+            let style = feeds
+                .iter()
+                .find(|f| f.id == feed_id)
+                .and_then(|f| self.summary_style(&f.url));
+            match by_style.iter_mut().find(|(s, _)| *s == style) {
+                Some((_, entries)) => entries.push((entry_id, content)),
+                None => by_style.push((style, vec![(entry_id, content)])),
+            }
+        }
+        for (style, pending) in by_style {
+            update::summarize_entries(
+                &self.ai,
+                &self.db,
+                &self.pipelines,
+                &self.ai_budget,
+                pending,
+                style,
+                report,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// IDs of feeds whose config turns AI off
+    async fn ai_disabled_feeds(&self) -> Result<Vec<String>> {
+        Ok(self
+            .db
+            .get_all_feeds()
+            .await?
+            .into_iter()
+            .filter(|f| self.config.feeds.get(&f.url).is_some_and(|c| !c.enable_ai))
+            .map(|f| f.id)
+            .collect())
+    }
+
+    /// Summary style of a feed's entries (see [`summary_style`])
+    pub(super) fn summary_style(&self, feed_url: &str) -> Option<SummaryStyle> {
+        summary_style(
+            self.config
+                .feeds
+                .get(feed_url)
+                .and_then(|c| c.summary_style),
+            feed_url,
+        )
+    }
+
+    /// Summarize one entry's text (or feed description) and store the result
+    pub(super) async fn summarize(&self, entry: &Entry) -> Result<Summary> {
+        self.summarize_with(entry, SummaryOptions::default(), None)
+            .await
+    }
+
+    async fn summarize_with(
+        &self,
+        entry: &Entry,
+        options: SummaryOptions,
+        stream: Option<mpsc::UnboundedSender<String>>,
+    ) -> Result<Summary> {
+        let content = entry
+            .content_text
+            .as_ref()
+            .or(entry.summary.as_ref())
+            .ok_or_else(|| anyhow::anyhow!("Entry {} has no text to summarize", entry.id))?;
+        let mut report = UpdateReport::default();
+        let pending = vec![(entry.id.clone(), content.clone())];
+        update::summarize_entries_with(
+            &self.ai,
+            &self.db,
+            &self.pipelines,
+            &self.ai_budget,
+            pending,
+            options,
+            stream,
+            true,
+            &mut report,
+        )
+        .await?;
+        if let Some(error) = report.errors.pop() {
+            anyhow::bail!(error);
+        }
+        if report.deferred > 0 {
+            anyhow::bail!(
+                "Daily token budget reached; {} stays queued for tomorrow",
+                entry.id
+            );
+        }
+        self.db
+            .get_summary(&entry.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Summary of {} was not stored", entry.id))
+    }
+}
+
+/// The configured summary style, else the paper style for arXiv and
+/// OAI-PMH feeds and the plain summary prompt for others
+fn summary_style(
+    configured: Option<presser_config::SummaryStyle>,
+    feed_url: &str,
+) -> Option<SummaryStyle> {
+    match configured {
+        Some(presser_config::SummaryStyle::Bullets) => Some(SummaryStyle::Bullets),
+        Some(presser_config::SummaryStyle::Tldr) => Some(SummaryStyle::Tldr),
+        Some(presser_config::SummaryStyle::DeepDive) => Some(SummaryStyle::DeepDive),
+        Some(presser_config::SummaryStyle::Paper) => Some(SummaryStyle::Paper),
+        None => presser_feeds::adapters::is_paper_feed(feed_url).then_some(SummaryStyle::Paper),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::test_config;
+
+    #[test]
+    fn test_summary_style() {
+        assert_eq!(
+            summary_style(None, "arxiv:cs.LG"),
+            Some(SummaryStyle::Paper)
+        );
+        assert_eq!(
+            summary_style(None, "oai:https://repo.example.org/oai"),
+            Some(SummaryStyle::Paper)
+        );
+        assert_eq!(summary_style(None, "https://example.com/feed"), None);
+        assert_eq!(
+            summary_style(Some(presser_config::SummaryStyle::Tldr), "arxiv:cs.LG"),
+            Some(SummaryStyle::Tldr)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_summarize_missing_skips_feeds_without_ai() {
+        let mut config = test_config();
+        let mut news = presser_config::FeedConfig::new("https://news.example.com/rss", "News");
+        news.enable_ai = false;
+        config.feeds.insert(news.url.clone(), news);
+        let engine = Engine::with_config(config).await.unwrap();
+        presser_db::fixtures::seed(engine.database()).await.unwrap();
+
+        // Only rust-old is unsummarized outside the news feed; the unreachable provider fails it
+        let report = engine.summarize_missing(None).await.unwrap();
+        assert_eq!(report.summarized, 0);
+        assert_eq!(report.errors.len(), 1, "{:?}", report.errors);
+        assert!(report.errors[0].contains("rust-old"));
+
+        assert!(engine.summarize_entry("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_defers_to_queue() {
+        let mut config = test_config();
+        config.ai.daily_token_budget = Some(100);
+        let engine = Engine::with_config(config).await.unwrap();
+        let db = engine.database();
+        presser_db::fixtures::seed(db).await.unwrap();
+        db.record_ai_usage(chrono::Utc::now().date_naive(), 150)
+            .await
+            .unwrap();
+
+        // Three entries lack summaries; none reaches the provider
+        let report = engine.summarize_missing(None).await.unwrap();
+        assert_eq!((report.summarized, report.deferred), (0, 3));
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(db.summary_queue_len().await.unwrap(), 3);
+        assert!(engine.summarize_entry("rust-old").await.is_err());
+
+        // With room in the budget the queue is worked off; the unreachable provider
+        // fails every attempt, and entries leave the queue after the last one
+        db.record_ai_usage(chrono::Utc::now().date_naive(), -150)
+            .await
+            .unwrap();
+        for attempt in 1..=update::MAX_SUMMARY_ATTEMPTS {
+            let report = engine.drain_summary_queue(None).await.unwrap();
+            assert_eq!(
+                (report.deferred, report.errors.len()),
+                (0, 3),
+                "attempt {}",
+                attempt
+            );
+        }
+        assert_eq!(db.summary_queue_len().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,658 @@
+//! Filter expressions: rules like
+//! `title.contains("rust") && word_count > 500 && !tags.contains("sponsored")`
+//! deciding which fetched entries are stored (and so summarized)
+//!
+//! Rules come from a feed's `filter` and the `[[filters]]` sections covering
+//! it. They are type-checked when the engine starts, so evaluating one can't
+//! fail.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use presser_config::{Config, FeedConfig};
+use presser_feeds::FeedEntry;
+
+/// What an expression sees of an entry
+#[derive(Debug, Clone, Default)]
+pub struct Fields {
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    /// Full text, or the feed's summary when there is none
+    pub content: String,
+    /// The feed's own summary of the entry
+    pub summary: String,
+    /// Feed ID
+    pub feed: String,
+    /// The feed's folder (empty when unfiled)
+    pub folder: String,
+    /// The entry's categories
+    pub tags: Vec<String>,
+    /// The feed's tags from its config
+    pub feed_tags: Vec<String>,
+    /// Words in `content`
+    pub word_count: f64,
+    /// Days since the entry was published (0 when unknown)
+    pub age_days: f64,
+}
+
+impl Fields {
+    /// Fields of an entry as fetched from `feed_id`
+    pub fn of_fetched(entry: &FeedEntry, feed_id: &str, config: Option<&FeedConfig>) -> Fields {
+        Fields::new(
+            (&entry.title, &entry.url, entry.author.as_deref()),
+            (entry.content_text.as_deref(), entry.summary.as_deref()),
+            entry.published,
+            entry.categories.clone(),
+            feed_id,
+            config,
+        )
+    }
+
+    /// Fields of a stored entry
+    pub fn of_stored(entry: &presser_db::Entry, config: Option<&FeedConfig>) -> Fields {
+        Fields::new(
+            (&entry.title, &entry.url, entry.author.as_deref()),
+            (entry.content_text.as_deref(), entry.summary.as_deref()),
+            entry.published,
+            entry.category_list(),
+            &entry.feed_id,
+            config,
+        )
+    }
+
+    fn new(
+        (title, url, author): (&str, &str, Option<&str>),
+        (content, summary): (Option<&str>, Option<&str>),
+        published: Option<DateTime<Utc>>,
+        tags: Vec<String>,
+        feed_id: &str,
+        config: Option<&FeedConfig>,
+    ) -> Fields {
+        let content = content.or(summary).unwrap_or_default();
+        Fields {
+            title: title.to_string(),
+            url: url.to_string(),
+            author: author.unwrap_or_default().to_string(),
+            content: content.to_string(),
+            summary: summary.unwrap_or_default().to_string(),
+            feed: feed_id.to_string(),
+            folder: config.and_then(|c| c.folder.clone()).unwrap_or_default(),
+            tags,
+            feed_tags: config.map(|c| c.tags.clone()).unwrap_or_default(),
+            word_count: content.split_whitespace().count() as f64,
+            age_days: published.map_or(0.0, |at| {
+                (Utc::now() - at).num_seconds().max(0) as f64 / 86_400.0
+            }),
+        }
+    }
+}
+
+/// A named, checked filter expression
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// `[[filters]]` name, or `feed` for a feed's own filter
+    pub name: String,
+    expr: Expr,
+}
+
+impl Rule {
+    pub fn parse(name: &str, source: &str) -> Result<Rule> {
+        Ok(Rule {
+            name: name.to_string(),
+            expr: parse(source)?,
+        })
+    }
+
+    /// Whether the entry is kept
+    pub fn keeps(&self, fields: &Fields) -> bool {
+        self.expr.eval(fields).truthy()
+    }
+}
+
+/// Every configured rule, checked
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    /// Feeds' own rules by URL
+    feeds: HashMap<String, Rule>,
+    /// `[[filters]]` rules, with the sections they came from
+    groups: Vec<(presser_config::FilterRule, Rule)>,
+}
+
+impl Filters {
+    pub fn from_config(config: &Config) -> Result<Filters> {
+        let mut filters = Filters::default();
+        for (url, feed) in &config.feeds {
+            if let Some(source) = &feed.filter {
+                let rule = Rule::parse("feed", source)
+                    .with_context(|| format!("Invalid filter for feed {}", url))?;
+                filters.feeds.insert(url.clone(), rule);
+            }
+        }
+        for section in &config.filters {
+            let rule = Rule::parse(&section.name, &section.rule)
+                .with_context(|| format!("Invalid [[filters]] rule '{}'", section.name))?;
+            filters.groups.push((section.clone(), rule));
+        }
+        Ok(filters)
+    }
+
+    /// Rules covering the feed at `url`: its own, then the groups'
+    pub fn for_feed(&self, url: &str, config: Option<&FeedConfig>) -> Vec<&Rule> {
+        self.feeds
+            .get(url)
+            .into_iter()
+            .chain(
+                self.groups
+                    .iter()
+                    .filter(|(section, _)| section.applies_to(config))
+                    .map(|(_, rule)| rule),
+            )
+            .collect()
+    }
+}
+
+/// The first of `rules` to drop an entry
+pub fn dropped_by<'a>(rules: &[&'a Rule], fields: &Fields) -> Option<&'a Rule> {
+    rules.iter().copied().find(|rule| !rule.keeps(fields))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Bool,
+    Num,
+    Str,
+    List,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Type::Bool => "a boolean",
+            Type::Num => "a number",
+            Type::Str => "a string",
+            Type::List => "a list",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Var {
+    Title,
+    Url,
+    Author,
+    Content,
+    Summary,
+    Feed,
+    Folder,
+    Tags,
+    FeedTags,
+    WordCount,
+    AgeDays,
+}
+
+/// Variables by name, with their types
+const VARS: &[(&str, Var, Type)] = &[
+    ("title", Var::Title, Type::Str),
+    ("url", Var::Url, Type::Str),
+    ("author", Var::Author, Type::Str),
+    ("content", Var::Content, Type::Str),
+    ("summary", Var::Summary, Type::Str),
+    ("feed", Var::Feed, Type::Str),
+    ("folder", Var::Folder, Type::Str),
+    ("tags", Var::Tags, Type::List),
+    ("feed_tags", Var::FeedTags, Type::List),
+    ("word_count", Var::WordCount, Type::Num),
+    ("age_days", Var::AgeDays, Type::Num),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    Contains,
+    StartsWith,
+    EndsWith,
+    Len,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Var(Var),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Op, Box<Expr>, Box<Expr>),
+    Call(Method, Box<Expr>, Option<Box<Expr>>),
+}
+
+/// A value during evaluation; strings compare ignoring case
+#[derive(Debug, Clone, PartialEq)]
+enum Value<'a> {
+    Bool(bool),
+    Num(f64),
+    Str(&'a str),
+    List(&'a [String]),
+}
+
+impl Value<'_> {
+    fn truthy(&self) -> bool {
+        matches!(self, Value::Bool(true))
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Value::Str(s) => s.to_lowercase(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Expr {
+    fn eval<'a>(&'a self, fields: &'a Fields) -> Value<'a> {
+        match self {
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Num(n) => Value::Num(*n),
+            Expr::Str(s) => Value::Str(s),
+            Expr::Var(var) => match var {
+                Var::Title => Value::Str(&fields.title),
+                Var::Url => Value::Str(&fields.url),
+                Var::Author => Value::Str(&fields.author),
+                Var::Content => Value::Str(&fields.content),
+                Var::Summary => Value::Str(&fields.summary),
+                Var::Feed => Value::Str(&fields.feed),
+                Var::Folder => Value::Str(&fields.folder),
+                Var::Tags => Value::List(&fields.tags),
+                Var::FeedTags => Value::List(&fields.feed_tags),
+                Var::WordCount => Value::Num(fields.word_count),
+                Var::AgeDays => Value::Num(fields.age_days),
+            },
+            Expr::Not(inner) => Value::Bool(!inner.eval(fields).truthy()),
+            Expr::And(a, b) => Value::Bool(a.eval(fields).truthy() && b.eval(fields).truthy()),
+            Expr::Or(a, b) => Value::Bool(a.eval(fields).truthy() || b.eval(fields).truthy()),
+            Expr::Compare(op, a, b) => {
+                let (a, b) = (a.eval(fields), b.eval(fields));
+                let ordering = match (&a, &b) {
+                    (Value::Num(x), Value::Num(y)) => x.partial_cmp(y),
+                    (Value::Str(_), Value::Str(_)) => Some(a.text().cmp(&b.text())),
+                    _ => (a == b).then_some(std::cmp::Ordering::Equal),
+                };
+                Value::Bool(match op {
+                    Op::Eq => ordering == Some(std::cmp::Ordering::Equal),
+                    Op::Ne => ordering != Some(std::cmp::Ordering::Equal),
+                    Op::Lt => ordering.is_some_and(|o| o.is_lt()),
+                    Op::Le => ordering.is_some_and(|o| o.is_le()),
+                    Op::Gt => ordering.is_some_and(|o| o.is_gt()),
+                    Op::Ge => ordering.is_some_and(|o| o.is_ge()),
+                })
+            }
+            Expr::Call(method, target, arg) => {
+                let target = target.eval(fields);
+                let arg = arg
+                    .as_ref()
+                    .map(|a| a.eval(fields).text())
+                    .unwrap_or_default();
+                match (method, &target) {
+                    (Method::Len, Value::Str(s)) => Value::Num(s.chars().count() as f64),
+                    (Method::Len, Value::List(items)) => Value::Num(items.len() as f64),
+                    (Method::Contains, Value::List(items)) => {
+                        Value::Bool(items.iter().any(|item| item.to_lowercase() == arg))
+                    }
+                    (Method::Contains, _) => Value::Bool(target.text().contains(&arg)),
+                    (Method::StartsWith, _) => Value::Bool(target.text().starts_with(&arg)),
+                    (Method::EndsWith, _) => Value::Bool(target.text().ends_with(&arg)),
+                    (Method::Len, _) => Value::Num(0.0),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Str(String),
+    Ident(String),
+    /// Operators and punctuation
+    Sym(&'static str),
+}
+
+const SYMBOLS: &[&str] = &[
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")", ".", ",",
+];
+
+/// Split `source` into tokens, each with the column it starts at
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => bail!("Unterminated string at column {}", start),
+                    Some(&q) if q == c => break,
+                    Some('\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some(&escaped) => text.push(escaped),
+                            None => bail!("Unterminated string at column {}", start),
+                        }
+                    }
+                    Some(&other) => text.push(other),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(text), start));
+        } else if c.is_ascii_digit() {
+            let end = (i..chars.len())
+                .find(|&j| !(chars[j].is_ascii_digit() || chars[j] == '.' || chars[j] == '_'))
+                .unwrap_or(chars.len());
+            let text: String = chars[i..end].iter().filter(|&&c| c != '_').collect();
+            let number = text
+                .parse()
+                .with_context(|| format!("Invalid number '{}' at column {}", text, start))?;
+            tokens.push((Token::Num(number), start));
+            i = end;
+        } else if c.is_alphabetic() || c == '_' {
+            let end = (i..chars.len())
+                .find(|&j| !(chars[j].is_alphanumeric() || chars[j] == '_'))
+                .unwrap_or(chars.len());
+            tokens.push((Token::Ident(chars[i..end].iter().collect()), start));
+            i = end;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) else {
+                bail!("Unexpected '{}' at column {}", c, start);
+            };
+            tokens.push((Token::Sym(symbol), start));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens, checking types as it goes
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Column just past the end, for errors at the end of the input
+    end: usize,
+}
+
+/// Parse and type-check an expression, which must be boolean
+fn parse(source: &str) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        end: source.chars().count() + 1,
+    };
+    let (expr, ty) = parser.or()?;
+    if let Some((token, column)) = parser.tokens.get(parser.pos) {
+        bail!("Unexpected {} at column {}", describe(token), column);
+    }
+    if ty != Type::Bool {
+        bail!("The rule is {}, not true or false", ty);
+    }
+    Ok(expr)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Num(n) => format!("number {}", n),
+        Token::Str(s) => format!("string \"{}\"", s),
+        Token::Ident(name) => format!("'{}'", name),
+        Token::Sym(symbol) => format!("'{}'", symbol),
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |(_, column)| *column)
+    }
+
+    fn eat(&mut self, symbol: &'static str) -> bool {
+        if self.peek() == Some(&Token::Sym(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<()> {
+        if !self.eat(symbol) {
+            bail!("Expected '{}' at column {}", symbol, self.column());
+        }
+        Ok(())
+    }
+
+    fn boolean(&self, ty: Type, column: usize, what: &str) -> Result<()> {
+        if ty != Type::Bool {
+            bail!(
+                "{} needs true or false, got {} at column {}",
+                what,
+                ty,
+                column
+            );
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<(Expr, Type)> {
+        let column = self.column();
+        let (mut expr, mut ty) = self.and()?;
+        while self.eat("||") {
+            self.boolean(ty, column, "'||'")?;
+            let column = self.column();
+            let (right, right_ty) = self.and()?;
+            self.boolean(right_ty, column, "'||'")?;
+            expr = Expr::Or(Box::new(expr), Box::new(right));
+            ty = Type::Bool;
+        }
+        Ok((expr, ty))
+    }
+
+    fn and(&mut self) -> Result<(Expr, Type)> {
+        let column = self.column();
+        let (mut expr, mut ty) = self.unary()?;
+        while self.eat("&&") {
+            self.boolean(ty, column, "'&&'")?;
+            let column = self.column();
+            let (right, right_ty) = self.unary()?;
+            self.boolean(right_ty, column, "'&&'")?;
+            expr = Expr::And(Box::new(expr), Box::new(right));
+            ty = Type::Bool;
+        }
+        Ok((expr, ty))
+    }
+
+    fn unary(&mut self) -> Result<(Expr, Type)> {
+        if self.eat("!") {
+            let column = self.column();
+            let (inner, ty) = self.unary()?;
+            self.boolean(ty, column, "'!'")?;
+            return Ok((Expr::Not(Box::new(inner)), Type::Bool));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<(Expr, Type)> {
+        let (left, left_ty) = self.postfix()?;
+        let op = match self.peek() {
+            Some(Token::Sym("==")) => Op::Eq,
+            Some(Token::Sym("!=")) => Op::Ne,
+            Some(Token::Sym("<")) => Op::Lt,
+            Some(Token::Sym("<=")) => Op::Le,
+            Some(Token::Sym(">")) => Op::Gt,
+            Some(Token::Sym(">=")) => Op::Ge,
+            _ => return Ok((left, left_ty)),
+        };
+        let column = self.column();
+        self.pos += 1;
+        let (right, right_ty) = self.postfix()?;
+        let ordered = matches!(op, Op::Lt | Op::Le | Op::Gt | Op::Ge);
+        if left_ty != right_ty || left_ty == Type::List || (ordered && left_ty == Type::Bool) {
+            bail!(
+                "Can't compare {} with {} at column {}",
+                left_ty,
+                right_ty,
+                column
+            );
+        }
+        Ok((
+            Expr::Compare(op, Box::new(left), Box::new(right)),
+            Type::Bool,
+        ))
+    }
+
+    fn postfix(&mut self) -> Result<(Expr, Type)> {
+        let (mut expr, mut ty) = self.primary()?;
+        while self.eat(".") {
+            let column = self.column();
+            let Some(Token::Ident(name)) = self.peek().cloned() else {
+                bail!("Expected a method name at column {}", column);
+            };
+            self.pos += 1;
+            let (method, takes_arg, result) = match (name.as_str(), ty) {
+                ("contains", Type::Str | Type::List) => (Method::Contains, true, Type::Bool),
+                ("starts_with", Type::Str) => (Method::StartsWith, true, Type::Bool),
+                ("ends_with", Type::Str) => (Method::EndsWith, true, Type::Bool),
+                ("len", Type::Str | Type::List) => (Method::Len, false, Type::Num),
+                _ => bail!("{} has no method '{}' (column {})", ty, name, column),
+            };
+            self.expect("(")?;
+            let arg = if takes_arg {
+                let column = self.column();
+                let (arg, arg_ty) = self.or()?;
+                if arg_ty != Type::Str {
+                    bail!(
+                        "'{}' takes a string, got {} at column {}",
+                        name,
+                        arg_ty,
+                        column
+                    );
+                }
+                Some(Box::new(arg))
+            } else {
+                None
+            };
+            self.expect(")")?;
+            expr = Expr::Call(method, Box::new(expr), arg);
+            ty = result;
+        }
+        Ok((expr, ty))
+    }
+
+    fn primary(&mut self) -> Result<(Expr, Type)> {
+        let column = self.column();
+        let Some((token, _)) = self.tokens.get(self.pos).cloned() else {
+            bail!("Unexpected end of rule at column {}", column);
+        };
+        self.pos += 1;
+        Ok(match token {
+            Token::Num(n) => (Expr::Num(n), Type::Num),
+            Token::Str(s) => (Expr::Str(s), Type::Str),
+            Token::Ident(name) if name == "true" => (Expr::Bool(true), Type::Bool),
+            Token::Ident(name) if name == "false" => (Expr::Bool(false), Type::Bool),
+            Token::Ident(name) => match VARS.iter().find(|(n, ..)| *n == name) {
+                Some((_, var, ty)) => (Expr::Var(*var), *ty),
+                None => bail!(
+                    "Unknown name '{}' at column {} (one of {})",
+                    name,
+                    column,
+                    VARS.iter().map(|(n, ..)| *n).collect::<Vec<_>>().join(", ")
+                ),
+            },
+            Token::Sym("(") => {
+                let inner = self.or()?;
+                self.expect(")")?;
+                inner
+            }
+            token => bail!("Unexpected {} at column {}", describe(&token), column),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules() {
+        let fields = Fields {
+            title: "Rust 1.80 released".into(),
+            content: "word ".repeat(600),
+            feed: "rust-blog".into(),
+            tags: vec!["Release".into()],
+            word_count: 600.0,
+            age_days: 2.5,
+            ..Default::default()
+        };
+        let keeps = |source: &str| Rule::parse("test", source).unwrap().keeps(&fields);
+
+        assert!(keeps(
+            r#"title.contains("rust") && word_count > 500 && !tags.contains("sponsored")"#
+        ));
+        assert!(keeps("tags.contains('release') && tags.len() == 1"));
+        assert!(keeps(r#"feed == "Rust-Blog" || false"#));
+        assert!(keeps("!(age_days >= 3) && title.starts_with(\"rust 1\")"));
+        assert!(!keeps(r#"author != "" || summary.len() > 0"#));
+        assert!(!keeps("word_count < 1_000 && folder.ends_with(\"news\")"));
+
+        let error = |source: &str| Rule::parse("test", source).unwrap_err().to_string();
+        assert_eq!(
+            error("word_count"),
+            "The rule is a number, not true or false"
+        );
+        assert_eq!(
+            error("title > 3"),
+            "Can't compare a string with a number at column 7"
+        );
+        assert_eq!(
+            error("word_count.contains(\"x\")"),
+            "a number has no method 'contains' (column 12)"
+        );
+        assert_eq!(error("title.contains(\"a\""), "Expected ')' at column 19");
+        assert_eq!(error("title == 'x' )"), "Unexpected ')' at column 14");
+        assert!(error("wordcount > 3").starts_with("Unknown name 'wordcount' at column 1"));
+        assert_eq!(
+            error("tags.contains(3)"),
+            "'contains' takes a string, got a number at column 15"
+        );
+        assert_eq!(
+            error("title.contains(\"a)"),
+            "Unterminated string at column 16"
+        );
+        assert_eq!(
+            error("word_count > 3 && title"),
+            "'&&' needs true or false, got a string at column 19"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Shell commands run on engine events (`[hooks]`)
+//!
+//! Each hook is run with `sh -c`, gets the event as one JSON object on stdin
+//! and its name in `PRESSER_EVENT`. Like notifications, hooks never fail the
+//! operation that triggered them: failures, non-zero exits, and timeouts are
+//! logged.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use presser_config::HooksConfig;
+use presser_db::{Entry, Summary};
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::digest::Digest;
+
+/// Configured hook commands
+#[derive(Debug, Clone)]
+pub struct ScriptHooks {
+    config: HooksConfig,
+}
+
+impl ScriptHooks {
+    /// Hooks from the `[hooks]` config section
+    pub fn from_config(config: &HooksConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Whether an `on_new_entry` command is configured
+    pub fn wants_new_entries(&self) -> bool {
+        self.config.on_new_entry.is_some()
+    }
+
+    /// Run `on_new_entry` for an entry just stored by an update
+    pub async fn new_entry(&self, feed_title: &str, entry: &Entry, summary: Option<&Summary>) {
+        if let Some(command) = &self.config.on_new_entry {
+            let payload = json!({
+                "event": "new_entry",
+                "feed_title": feed_title,
+                "entry": entry,
+                "summary": summary.map(|s| &s.summary_text),
+            });
+            self.run_logged(command, "new_entry", &payload).await;
+        }
+    }
+
+    /// Run `on_digest` for a generated digest
+    pub async fn digest(&self, digest: &Digest) {
+        if let Some(command) = &self.config.on_digest {
+            let payload = json!({
+                "event": "digest",
+                "digest": digest,
+            });
+            self.run_logged(command, "digest", &payload).await;
+        }
+    }
+
+    async fn run_logged(&self, command: &str, event: &str, payload: &serde_json::Value) {
+        if let Err(e) = self.run(command, event, payload).await {
+            tracing::warn!("Hook on_{} failed: {:#}", event, e);
+        }
+    }
+
+    async fn run(&self, command: &str, event: &str, payload: &serde_json::Value) -> Result<()> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("PRESSER_EVENT", event)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to run `{}`", command))?;
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let output = tokio::time::timeout(timeout, async {
+            if let Some(mut stdin) = child.stdin.take() {
+                // A hook that ignores its input may exit before reading it
+                let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+            }
+            child.wait_with_output().await
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("`{}` timed out after {:?}", command, timeout))?
+        .with_context(|| format!("Failed to wait for `{}`", command))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "`{}` exited with {}: {}",
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("event.json");
+        let hooks = ScriptHooks::from_config(&HooksConfig {
+            on_new_entry: Some(format!(
+                "echo $PRESSER_EVENT > {0}; cat >> {0}",
+                out.display()
+            )),
+            on_digest: None,
+            timeout_secs: 1,
+        });
+        let entry = Entry {
+            id: "a".into(),
+            title: "Rust news".into(),
+            ..Default::default()
+        };
+        hooks.new_entry("Blog", &entry, None).await;
+        let written = std::fs::read_to_string(&out).unwrap();
+        let (event, payload) = written.split_once('\n').unwrap();
+        assert_eq!(event, "new_entry");
+        let payload: serde_json::Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(payload["entry"]["title"], "Rust news");
+        assert_eq!(payload["feed_title"], "Blog");
+
+        let value = json!({});
+        let error = hooks.run("exit 3", "digest", &value).await.unwrap_err();
+        assert!(
+            error.to_string().contains("exited with exit status: 3"),
+            "{}",
+            error
+        );
+        let error = hooks.run("sleep 5", "digest", &value).await.unwrap_err();
+        assert!(error.to_string().contains("timed out"), "{}", error);
+    }
+}
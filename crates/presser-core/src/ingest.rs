@@ -0,0 +1,124 @@
+//! Articles pushed in from outside (`POST /ingest` on `presser serve
+//! --ingest`), stored in the inbox feed and queued for summarization
+//!
+//! The pushed content may be a whole page (as a browser extension sends
+//! it), a fragment of HTML, or plain text; without any, the article is
+//! fetched and extracted like a saved URL.
+
+use anyhow::Result;
+use presser_feeds::ContentExtractor;
+use serde::Deserialize;
+
+/// Feed that holds pushed articles (cannot clash with a feed ID slug)
+pub const INBOX_FEED_ID: &str = "presser:inbox";
+
+/// Width plain text from pushed HTML is wrapped at
+const TEXT_WIDTH: usize = 100;
+
+/// An article pushed to `/ingest`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ingested {
+    pub url: String,
+
+    /// Title; taken from the page when left out
+    pub title: Option<String>,
+
+    /// HTML or plain text; fetched from `url` when left out
+    pub content: Option<String>,
+}
+
+impl Ingested {
+    /// Fail when the URL is not an absolute http(s) URL
+    pub fn check(&self) -> Result<()> {
+        let url = url::Url::parse(self.url.trim())
+            .map_err(|e| anyhow::anyhow!("Invalid url '{}': {}", self.url, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            anyhow::bail!("Invalid url '{}': not http(s)", self.url);
+        }
+        Ok(())
+    }
+
+    /// Title, if one was given
+    pub(crate) fn title(&self) -> Option<String> {
+        self.title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(String::from)
+    }
+
+    /// The content as HTML (if it is HTML) and as text, if any was given
+    pub(crate) fn content(&self, extractor: &ContentExtractor) -> Option<(Option<String>, String)> {
+        let content = self
+            .content
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())?;
+        if !looks_like_html(content) {
+            return Some((None, content.to_string()));
+        }
+        // Readability for whole pages, plain conversion for fragments it passes through
+        let text = extractor
+            .extract(content, self.url.trim())
+            .ok()
+            .filter(|text| !text.trim().is_empty() && !looks_like_html(text))
+            .unwrap_or_else(|| extractor.html_to_text(content, TEXT_WIDTH));
+        Some((Some(content.to_string()), text.trim().to_string()))
+    }
+}
+
+/// Whether text has HTML tags in it
+fn looks_like_html(text: &str) -> bool {
+    let Some(open) = text.find('<') else {
+        return false;
+    };
+    text[open + 1..]
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!')
+        && text[open..].contains('>')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingested_content() {
+        let extractor = ContentExtractor::new();
+        let pushed = |content: &str| Ingested {
+            url: "https://example.com/post".into(),
+            title: Some("  ".into()),
+            content: Some(content.into()),
+        };
+
+        let plain = pushed("Rates rose by 0.25 points, and x < y still holds.");
+        assert_eq!(
+            plain.content(&extractor),
+            Some((
+                None,
+                "Rates rose by 0.25 points, and x < y still holds.".into()
+            ))
+        );
+        assert_eq!(plain.title(), None);
+
+        let (html, text) = pushed("<p>Hello <b>world</b></p>")
+            .content(&extractor)
+            .unwrap();
+        assert_eq!(html.as_deref(), Some("<p>Hello <b>world</b></p>"));
+        assert!(
+            text.contains("Hello") && text.contains("world") && !text.contains("<b>"),
+            "{:?}",
+            text
+        );
+
+        assert_eq!(pushed(" ").content(&extractor), None);
+        assert!(plain.check().is_ok());
+        let bad = |url: &str| Ingested {
+            url: url.into(),
+            ..Default::default()
+        };
+        assert!(bad("ftp://example.com/file").check().is_err());
+        assert!(bad("not a url").check().is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Instapaper (Simple API, `POST /api/add` with basic auth)
+
+use anyhow::Result;
+use async_trait::async_trait;
+use presser_config::InstapaperConfig;
+
+use super::{Article, SaveService};
+
+const ADD_URL: &str = "https://www.instapaper.com/api/add";
+
+/// Saves to an Instapaper account
+pub struct InstapaperService {
+    client: reqwest::Client,
+    config: InstapaperConfig,
+}
+
+impl InstapaperService {
+    /// Service for the configured account
+    pub fn new(client: reqwest::Client, config: InstapaperConfig) -> Self {
+        Self { client, config }
+    }
+}
+
+#[async_trait]
+impl SaveService for InstapaperService {
+    fn name(&self) -> &'static str {
+        "instapaper"
+    }
+
+    async fn save(&self, article: &Article) -> Result<()> {
+        let response = self
+            .client
+            .post(ADD_URL)
+            .basic_auth(&self.config.username, self.config.password.as_ref())
+            .form(&[("url", &article.url), ("title", &article.title)])
+            .send()
+            .await?;
+        match response.status().as_u16() {
+            200 | 201 => Ok(()),
+            403 => anyhow::bail!("Instapaper rejected the username or password"),
+            status => anyhow::bail!("Instapaper returned HTTP {}", status),
+        }
+    }
+}
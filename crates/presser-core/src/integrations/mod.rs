@@ -0,0 +1,143 @@
+//! Read-it-later services
+//!
+//! Each configured service is a [`SaveService`]; `presser save` and the TUI
+//! push an entry's link, title, and tags to one of them.
+
+mod instapaper;
+mod pocket;
+mod wallabag;
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use presser_config::IntegrationsConfig;
+use presser_db::Entry;
+
+pub use instapaper::InstapaperService;
+pub use pocket::PocketService;
+pub use wallabag::WallabagService;
+
+/// Time allowed for a single save (Wallabag needs two requests)
+const SAVE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// What gets sent to a service
+#[derive(Debug, Clone, PartialEq)]
+pub struct Article {
+    /// Article URL
+    pub url: String,
+
+    /// Title, for services that do not fetch one themselves
+    pub title: String,
+
+    /// Entry tags
+    pub tags: Vec<String>,
+}
+
+/// Saves articles to one read-it-later account
+#[async_trait]
+pub trait SaveService: Send + Sync {
+    /// Service name for messages
+    fn name(&self) -> &'static str;
+
+    /// Add one article
+    async fn save(&self, article: &Article) -> Result<()>;
+}
+
+/// Service `name` (or the default one) from the `[integrations]` config section
+pub fn service(config: &IntegrationsConfig, name: Option<&str>) -> Result<Box<dyn SaveService>> {
+    let name = resolve(config, name)?;
+    let client = reqwest::Client::builder().timeout(SAVE_TIMEOUT).build()?;
+    let missing = || {
+        anyhow::anyhow!(
+            "{} is not configured; add an [integrations.{}] section to global.toml",
+            name,
+            name
+        )
+    };
+    Ok(match name.as_str() {
+        "wallabag" => Box::new(WallabagService::new(
+            client,
+            config.wallabag.clone().ok_or_else(missing)?,
+        )),
+        "pocket" => Box::new(PocketService::new(
+            client,
+            config.pocket.clone().ok_or_else(missing)?,
+        )),
+        "instapaper" => Box::new(InstapaperService::new(
+            client,
+            config.instapaper.clone().ok_or_else(missing)?,
+        )),
+        other => anyhow::bail!(
+            "Unknown service '{}' (expected wallabag, pocket, or instapaper)",
+            other
+        ),
+    })
+}
+
+/// The service to use: `name`, or the configured default
+fn resolve(config: &IntegrationsConfig, name: Option<&str>) -> Result<String> {
+    if let Some(name) = name {
+        return Ok(name.to_lowercase());
+    }
+    if let Some(name) = config.default_service() {
+        return Ok(name);
+    }
+    match config.configured().as_slice() {
+        [] => anyhow::bail!(
+            "No read-it-later service configured; add [integrations.wallabag], \
+             [integrations.pocket], or [integrations.instapaper] to global.toml"
+        ),
+        several => anyhow::bail!(
+            "Several services are configured ({}); pick one with --to or set [integrations].default",
+            several.join(", ")
+        ),
+    }
+}
+
+/// Save a stored entry; returns the service's name
+pub async fn save_entry(
+    engine: &crate::Engine,
+    entry: &Entry,
+    to: Option<&str>,
+) -> Result<&'static str> {
+    let service = service(&engine.config().integrations, to)?;
+    let article = Article {
+        url: entry.url.clone(),
+        title: entry.title.clone(),
+        tags: engine.database().get_tags_for_entry(&entry.id).await?,
+    };
+    service
+        .save(&article)
+        .await
+        .with_context(|| format!("Failed to save to {}", service.name()))?;
+    Ok(service.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_config::PocketConfig;
+
+    #[test]
+    fn test_resolve() {
+        let mut config = IntegrationsConfig::default();
+        assert!(resolve(&config, None)
+            .unwrap_err()
+            .to_string()
+            .contains("No read-it-later service"));
+        assert_eq!(resolve(&config, Some("Pocket")).unwrap(), "pocket");
+        assert!(service(&config, Some("pocket"))
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("[integrations.pocket]"));
+
+        config.pocket = Some(PocketConfig {
+            consumer_key: "key".into(),
+            access_token: "token".into(),
+        });
+        assert_eq!(service(&config, None).unwrap().name(), "pocket");
+        assert!(service(&config, Some("delicious")).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Pocket (`POST /v3/add`)
+
+use anyhow::Result;
+use async_trait::async_trait;
+use presser_config::PocketConfig;
+use serde_json::json;
+
+use super::{Article, SaveService};
+
+const ADD_URL: &str = "https://getpocket.com/v3/add";
+
+/// Saves to a Pocket account
+pub struct PocketService {
+    client: reqwest::Client,
+    config: PocketConfig,
+}
+
+impl PocketService {
+    /// Service for the configured application key and user token
+    pub fn new(client: reqwest::Client, config: PocketConfig) -> Self {
+        Self { client, config }
+    }
+}
+
+#[async_trait]
+impl SaveService for PocketService {
+    fn name(&self) -> &'static str {
+        "pocket"
+    }
+
+    async fn save(&self, article: &Article) -> Result<()> {
+        let response = self
+            .client
+            .post(ADD_URL)
+            .header("X-Accept", "application/json")
+            .json(&json!({
+                "url": article.url,
+                "title": article.title,
+                "tags": article.tags.join(","),
+                "consumer_key": self.config.consumer_key,
+                "access_token": self.config.access_token,
+            }))
+            .send()
+            .await?;
+        // Pocket explains failures in a header rather than the body
+        if let Some(error) = response.headers().get("X-Error") {
+            anyhow::bail!(
+                "Pocket returned {}: {}",
+                response.status(),
+                error.to_str().unwrap_or_default()
+            );
+        }
+        response.error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+//! Wallabag (OAuth password grant, then `POST /api/entries.json`)
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use presser_config::WallabagConfig;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{Article, SaveService};
+
+/// Saves to a Wallabag instance
+pub struct WallabagService {
+    client: reqwest::Client,
+    config: WallabagConfig,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    access_token: String,
+}
+
+impl WallabagService {
+    /// Service for the configured instance and account
+    pub fn new(client: reqwest::Client, config: WallabagConfig) -> Self {
+        Self { client, config }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.config.url.trim_end_matches('/'), path)
+    }
+
+    async fn token(&self) -> Result<String> {
+        let token: Token = self
+            .client
+            .post(self.endpoint("/oauth/v2/token"))
+            .form(&[
+                ("grant_type", "password"),
+                ("client_id", &self.config.client_id),
+                ("client_secret", &self.config.client_secret),
+                ("username", &self.config.username),
+                ("password", &self.config.password),
+            ])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .context("Wallabag login failed")?
+            .json()
+            .await
+            .context("Unexpected Wallabag token response")?;
+        Ok(token.access_token)
+    }
+}
+
+#[async_trait]
+impl SaveService for WallabagService {
+    fn name(&self) -> &'static str {
+        "wallabag"
+    }
+
+    async fn save(&self, article: &Article) -> Result<()> {
+        let token = self.token().await?;
+        self.client
+            .post(self.endpoint("/api/entries.json"))
+            .bearer_auth(token)
+            .json(&json!({
+                "url": article.url,
+                "title": article.title,
+                "tags": article.tags.join(","),
+            }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())?;
+        Ok(())
+    }
+}
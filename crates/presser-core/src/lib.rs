@@ -3,10 +3,38 @@
 //! This crate ties together all the other Presser crates and provides
 //! the main application logic.
 
+pub mod ai_queue;
+pub mod archive;
+pub mod ask;
+pub mod audit;
+pub mod auth;
 pub mod commands;
+pub mod control;
+pub mod daemon;
+pub mod dedup;
+pub mod digest;
 pub mod engine;
+pub mod filter;
+pub mod hooks;
+pub mod ingest;
+pub mod integrations;
+pub mod logging;
+pub mod metrics;
+pub mod mute;
+pub mod notify;
+pub mod pipeline;
+pub mod podcasts;
+pub mod reload;
+pub mod revisions;
+pub mod scoring;
+pub mod server;
 pub mod tasks;
+pub mod text;
+pub mod translate;
 pub mod ui;
+pub mod update;
 
 pub use commands::*;
 pub use engine::Engine;
+pub use pipeline::{Pipeline, Verdict};
+pub use update::{UpdateProgress, UpdateReport, UpdateSummary};
@@ -0,0 +1,193 @@
+//! Logging setup: console output plus the optional `[logging]` file sink
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use presser_config::{LogFormat, LoggingConfig};
+use tracing::Level;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, Layer, Registry};
+
+/// Install the global subscriber: the console at `console_level`, plus the
+/// log file when `config.file` is set
+pub fn init(console_level: Level, config: Option<&LoggingConfig>) -> Result<()> {
+    let console = fmt::layer()
+        .with_target(false)
+        .with_filter(LevelFilter::from_level(console_level));
+
+    let file = match config.filter(|c| c.file) {
+        Some(config) => Some(file_layer(config)?),
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(file)
+        .with(console)
+        .try_init()
+        .context("Failed to set tracing subscriber")
+}
+
+fn file_layer(config: &LoggingConfig) -> Result<Box<dyn Layer<Registry> + Send + Sync>> {
+    let filter = EnvFilter::try_new(&config.filter)
+        .with_context(|| format!("Invalid logging.filter '{}'", config.filter))?;
+    let writer = Arc::new(RotatingFile::open(
+        config.file_path(),
+        config.max_size_mb * 1024 * 1024,
+        config.daily,
+        config.keep,
+    )?);
+    let layer = fmt::layer().with_writer(writer).with_ansi(false);
+    Ok(match config.format {
+        LogFormat::Text => layer.with_filter(filter).boxed(),
+        LogFormat::Json => layer.json().with_filter(filter).boxed(),
+    })
+}
+
+/// Append-only log file that moves itself aside when it grows past a size or
+/// a day ends, keeping the newest `keep` old files as `<name>.1`, `<name>.2`, ...
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    daily: bool,
+    keep: usize,
+    state: Mutex<FileState>,
+}
+
+struct FileState {
+    file: File,
+    size: u64,
+    day: NaiveDate,
+}
+
+impl RotatingFile {
+    /// Open (or create) `path`; a `max_bytes` of 0 disables size-based rotation
+    pub fn open(path: PathBuf, max_bytes: u64, daily: bool, keep: usize) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let state = open_state(&path)?;
+        let rotating = Self {
+            path,
+            max_bytes,
+            daily,
+            keep,
+            state: Mutex::new(state),
+        };
+        // A file left over from an earlier day is rotated before the first write
+        let mut state = rotating.state.lock().unwrap_or_else(|e| e.into_inner());
+        if rotating.due(&state, 0, Utc::now()) {
+            rotating.rotate(&mut state)?;
+        }
+        drop(state);
+        Ok(rotating)
+    }
+
+    /// Whether writing `len` more bytes at `now` should start a new file
+    fn due(&self, state: &FileState, len: u64, now: DateTime<Utc>) -> bool {
+        let full = self.max_bytes > 0 && state.size > 0 && state.size + len > self.max_bytes;
+        let new_day = self.daily && state.size > 0 && now.date_naive() != state.day;
+        full || new_day
+    }
+
+    fn rotate(&self, state: &mut FileState) -> io::Result<()> {
+        let rotated = |n: usize| {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+        if self.keep == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let from = rotated(n);
+                if from.exists() {
+                    std::fs::rename(&from, rotated(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, rotated(1))?;
+        }
+        *state = open_state(&self.path).map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+fn open_state(path: &Path) -> Result<FileState> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))?;
+    let metadata = file.metadata()?;
+    let day = metadata
+        .modified()
+        .map(|t| DateTime::<Utc>::from(t).date_naive())
+        .unwrap_or_else(|_| Utc::now().date_naive());
+    Ok(FileState {
+        file,
+        size: metadata.len(),
+        day,
+    })
+}
+
+impl Write for &RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Utc::now();
+        if self.due(&state, buf.len() as u64, now) {
+            self.rotate(&mut state)?;
+        }
+        state.file.write_all(buf)?;
+        state.size += buf.len() as u64;
+        state.day = now.date_naive();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .file
+            .flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("presser.log");
+        let log = RotatingFile::open(path.clone(), 10, false, 1).unwrap();
+        let read = |suffix: &str| {
+            std::fs::read_to_string(format!("{}{}", path.display(), suffix)).unwrap_or_default()
+        };
+
+        for line in ["one\n", "two\n", "three\n", "four\n", "five\n"] {
+            (&log).write_all(line.as_bytes()).unwrap();
+        }
+        // Lines never straddle files; only the newest old file is kept
+        assert_eq!(read(""), "four\nfive\n");
+        assert_eq!(read(".1"), "three\n");
+        assert_eq!(read(".2"), "");
+
+        // Size-only rotation ignores the date
+        let tomorrow = Utc::now() + chrono::Duration::days(1);
+        assert!(!log.due(&log.state.lock().unwrap(), 0, tomorrow));
+
+        let daily = RotatingFile::open(dir.path().join("daily.log"), 0, true, 1).unwrap();
+        (&daily).write_all(b"x\n").unwrap();
+        let daily_state = daily.state.lock().unwrap();
+        assert!(daily.due(&daily_state, 1, tomorrow));
+        assert!(!daily.due(&daily_state, 1, Utc::now()));
+    }
+}
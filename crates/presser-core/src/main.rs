@@ -5,20 +5,49 @@
 //! local LLMs) and provides both a TUI and CLI interface.
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, CompleteEnv};
+use std::path::PathBuf;
 use tracing::Level;
-use tracing_subscriber::FmtSubscriber;
 
+mod ai_queue;
+mod archive;
+mod ask;
+mod audit;
+mod auth;
 mod commands;
+mod control;
+mod daemon;
+mod dedup;
+mod digest;
 mod engine;
+mod filter;
+mod hooks;
+mod ingest;
+mod integrations;
+mod logging;
+mod metrics;
+mod mute;
+mod notify;
+mod pipeline;
+mod podcasts;
+mod reload;
+mod revisions;
+mod scoring;
+mod server;
+mod tasks;
+mod text;
+mod translate;
 mod ui;
+mod update;
 
 use commands::*;
 use engine::Engine;
+use presser_feeds::ReaderService;
 
 /// Presser - AI-powered RSS feed processor
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(name = "presser", author, version, about, long_about = None)]
 struct Cli {
     /// Enable verbose logging
     #[arg(short, long, global = true)]
@@ -28,6 +57,11 @@ struct Cli {
     #[arg(short, long, global = true)]
     debug: bool,
 
+    /// Don't touch the network: updates are deferred and everything reads
+    /// from the local database
+    #[arg(long, global = true)]
+    offline: bool,
+
     /// Subcommand to execute
     #[command(subcommand)]
     command: Commands,
@@ -40,14 +74,31 @@ enum Commands {
         /// Feed URL
         url: String,
 
-        /// Feed name/title
+        /// Feed name/title (asked for on a terminal if omitted)
         #[arg(short, long)]
         name: Option<String>,
+
+        /// Tag the feed (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+
+        /// Update schedule as a cron expression (default: scheduler.default_interval)
+        #[arg(long)]
+        interval: Option<String>,
+
+        /// Don't summarize this feed's entries with AI
+        #[arg(long)]
+        no_ai: bool,
+
+        /// Fetch the feed right after adding it
+        #[arg(long)]
+        fetch_now: bool,
     },
 
     /// Remove a feed
     Remove {
         /// Feed ID
+        #[arg(add = ArgValueCandidates::new(commands::feed_id_candidates))]
         id: String,
     },
 
@@ -57,18 +108,221 @@ enum Commands {
     /// Update feeds (fetch new entries)
     Update {
         /// Update a specific feed (omit to update all)
+        #[arg(add = ArgValueCandidates::new(commands::feed_id_candidates))]
         feed_id: Option<String>,
+
+        /// Update in this process even if the daemon is running
+        #[arg(long)]
+        local: bool,
+
+        /// Fetch and show what would be added and summarized without storing
+        /// anything (always in this process)
+        #[arg(long, conflicts_with = "local")]
+        dry_run: bool,
+    },
+
+    /// Point feeds that moved permanently (301/308 redirects) at their new URLs
+    FixUrls {
+        /// List the moves without changing the config or database
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Feed maintenance
+    Feeds {
+        #[command(subcommand)]
+        command: FeedsCommand,
+    },
+
+    /// List unread entries (newest first)
+    Read {
+        /// Only entries from this feed
+        #[arg(long, add = ArgValueCandidates::new(commands::feed_id_candidates))]
+        feed: Option<String>,
+
+        /// Only entries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only entries published in the last N days
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Only entries published on or after this date (YYYY-MM-DD)
+        #[arg(long, conflicts_with = "days")]
+        since: Option<chrono::NaiveDate>,
+
+        /// Maximum number of entries to list
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: i64,
+
+        /// Include entries already read
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Print an entry's summary and text, then mark it read
+    Show {
+        /// Entry ID (as listed by `presser read`)
+        id: String,
+
+        /// Print directly instead of through $PAGER
+        #[arg(long)]
+        no_pager: bool,
+
+        /// Leave the entry unread
+        #[arg(long)]
+        keep_unread: bool,
+    },
+
+    /// Send an entry to a read-it-later service ([integrations] in global.toml)
+    Save {
+        /// Entry ID (as listed by `presser read`)
+        id: String,
+
+        /// Service: wallabag, pocket, or instapaper (defaults to [integrations].default)
+        #[arg(long)]
+        to: Option<String>,
+    },
+
+    /// Mark entries as read
+    MarkRead {
+        #[command(flatten)]
+        scope: MarkScope,
+    },
+
+    /// Mark entries as unread
+    MarkUnread {
+        #[command(flatten)]
+        scope: MarkScope,
+    },
+
+    /// Summarize an article URL, a stored entry, all entries missing a summary, or the summary queue
+    #[command(group(clap::ArgGroup::new("target").required(true).args(["url", "entry", "missing", "queued"])))]
+    #[command(group(clap::ArgGroup::new("limited").multiple(true).args(["missing", "queued"])))]
+    Summarize {
+        /// Article URL (stored under "Saved articles")
+        url: Option<String>,
+
+        /// Stored entry ID
+        #[arg(long)]
+        entry: Option<String>,
+
+        /// Backfill every stored entry without a summary
+        #[arg(long)]
+        missing: bool,
+
+        /// Work through entries queued by updates (deferred by the token budget or interrupted)
+        #[arg(long)]
+        queued: bool,
+
+        /// With --missing or --queued, summarize at most this many entries
+        #[arg(short = 'n', long, requires = "limited")]
+        limit: Option<i64>,
+    },
+
+    /// Answer a question from stored entries, citing them
+    Ask {
+        /// The question, e.g. "what happened with the EU AI act this month?"
+        question: String,
+
+        /// Most entries given to the AI as sources
+        #[arg(short = 'n', long, default_value = "12")]
+        limit: usize,
+
+        /// Only entries from the last N days (default: a period the question names, e.g. "this week")
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Only entries from this feed
+        #[arg(long, add = ArgValueCandidates::new(commands::feed_id_candidates))]
+        feed: Option<String>,
     },
 
     /// Generate digest
     Digest {
         /// Number of days to include
-        #[arg(short, long, default_value = "1")]
+        #[arg(long, default_value = "1")]
+        days: u32,
+
+        /// Output format (text, html, markdown, atom; default: [digest].template if set, else text)
+        #[arg(short, long)]
+        format: Option<String>,
+
+        /// Render with this template file instead of a builtin format
+        #[arg(short, long, conflicts_with = "format")]
+        template: Option<PathBuf>,
+
+        /// Group entries by feed, tag, or day
+        #[arg(short, long, default_value = "feed")]
+        group_by: String,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// List what the digest would include without writing it or sending notifications
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Open with an AI overview written from the entries' stored summaries
+        /// (on by default with [digest].synthesis)
+        #[arg(long)]
+        synthesize: bool,
+
+        /// Show the overview's estimated token cost and ask before writing it
+        #[arg(long)]
+        confirm_cost: bool,
+    },
+
+    /// Write a trend report: the period's entries by topic, with the sources
+    /// covering each and an AI narrative of the recurring themes
+    Report {
+        /// Period to cover (day, week, month)
+        #[arg(short, long, default_value = "week")]
+        period: String,
+
+        /// Output format (text, html, markdown, atom; default: [digest].template if set, else text)
+        #[arg(short, long)]
+        format: Option<String>,
+
+        /// Render with this template file instead of a builtin format
+        #[arg(short, long, conflicts_with = "format")]
+        template: Option<PathBuf>,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// List the topics the report would cover without writing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Leave out the AI narrative
+        #[arg(long)]
+        no_narrative: bool,
+
+        /// Show the narrative's estimated token cost and ask before writing it
+        #[arg(long)]
+        confirm_cost: bool,
+    },
+
+    /// Publish daily and weekly digests as a static website
+    Publish {
+        /// Site directory
+        dir: PathBuf,
+
+        /// Days of entries to render pages for (older pages are kept)
+        #[arg(long, default_value = "14")]
         days: u32,
 
-        /// Output format (text, html, markdown)
-        #[arg(short, long, default_value = "text")]
-        format: String,
+        /// Site title
+        #[arg(long, default_value = "Reading digest")]
+        title: String,
+
+        /// URL the site is hosted at (for links in feed.xml)
+        #[arg(long)]
+        base_url: Option<String>,
     },
 
     /// Start the interactive TUI
@@ -77,15 +331,343 @@ enum Commands {
     /// Start the scheduler daemon
     Daemon,
 
-    /// Show database statistics
-    Stats,
+    /// Serve Presser over HTTP
+    Serve {
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:8080")]
+        listen: std::net::SocketAddr,
+
+        /// Publish an Atom feed of AI summaries at /feed.atom
+        #[arg(long)]
+        feeds_out: bool,
+
+        /// Days of entries in the summaries feed (override per request with ?days=)
+        #[arg(long, default_value = "7")]
+        days: u32,
+
+        /// Serve the per-user API at /api (users from `presser users add`)
+        #[arg(long)]
+        api: bool,
+
+        /// Accept articles pushed to POST /ingest (with a user's token) into the inbox feed
+        #[arg(long)]
+        ingest: bool,
+    },
+
+    /// Users of the `presser serve --api` API, each with their own read state
+    Users {
+        #[command(subcommand)]
+        command: UsersCommand,
+    },
+
+    /// Control the running daemon
+    Ctl {
+        #[command(subcommand)]
+        command: control::ControlRequest,
+    },
+
+    /// Show recent update runs: per-feed new entries, tokens, duration, and errors
+    Log {
+        /// Only updates of this feed
+        #[arg(long, add = ArgValueCandidates::new(commands::feed_id_candidates))]
+        feed: Option<String>,
+
+        /// Only updates started within this long ago (e.g. 12h, 7d, 2w)
+        #[arg(long, value_name = "AGE", value_parser = commands::parse_age)]
+        since: Option<chrono::Duration>,
+
+        /// Maximum number of feed updates to show
+        #[arg(short = 'n', long, default_value = "100")]
+        limit: i64,
+    },
+
+    /// Mute a topic: fetched entries mentioning it are dropped, for a while or until unmuted
+    #[command(group(clap::ArgGroup::new("what").required(true).args(["pattern", "list"])))]
+    Mute {
+        /// Keyword to mute (matched in titles, descriptions, and content, ignoring case)
+        pattern: Option<String>,
+
+        /// Mute for this long (e.g. 12h, 30d, 2w) instead of until unmuted
+        #[arg(long = "for", value_name = "DURATION", value_parser = commands::parse_age)]
+        duration: Option<chrono::Duration>,
+
+        /// The pattern is a regular expression (ignoring case)
+        #[arg(long, conflicts_with = "tag")]
+        regex: bool,
+
+        /// The pattern is a tag (an entry category)
+        #[arg(long)]
+        tag: bool,
+
+        /// List the rules in force instead
+        #[arg(long, conflicts_with_all = ["pattern", "duration", "regex", "tag"])]
+        list: bool,
+    },
+
+    /// Remove a mute rule (IDs are shown by `presser mute --list`)
+    Unmute {
+        /// Rule ID
+        id: i64,
+    },
+
+    /// Archived copies of entries' pages ([archive] in global.toml)
+    Archive {
+        #[command(subcommand)]
+        command: ArchiveCommand,
+    },
+
+    /// Entry filter rules (a feed's `filter` and `[[filters]]` sections)
+    Filters {
+        #[command(subcommand)]
+        command: FiltersCommand,
+    },
+
+    /// Show database statistics with per-feed and daily breakdowns
+    Stats {
+        /// Days of daily activity to show
+        #[arg(long, default_value = "14")]
+        days: u32,
+    },
+
+    /// Prune old entries according to the [retention] config
+    Prune {
+        /// Also VACUUM the database and run maintenance (indices, ANALYZE, WAL checkpoint)
+        #[arg(long)]
+        vacuum: bool,
+
+        /// Show what would be pruned without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Write a consistent snapshot of the database (safe while the daemon runs)
+    Backup {
+        /// Destination file (must not exist)
+        path: PathBuf,
+    },
+
+    /// Replace the database with a backup (stop the daemon first)
+    Restore {
+        /// Backup file to restore
+        path: PathBuf,
+    },
+
+    /// Export entries (with AI summaries) to JSON, CSV, or NDJSON, feeds to OPML, or notes to Markdown
+    #[command(args_conflicts_with_subcommands = true)]
+    Export {
+        #[command(subcommand)]
+        target: Option<ExportTarget>,
+
+        /// Output format (json, csv, ndjson); inferred from --output if omitted
+        #[arg(short, long)]
+        format: Option<String>,
+
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only entries from this feed
+        #[arg(long, add = ArgValueCandidates::new(commands::feed_id_candidates))]
+        feed: Option<String>,
+
+        /// Only entries published in the last N days
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Only starred entries
+        #[arg(long)]
+        starred: bool,
+    },
+
+    /// Import entries from an export file (existing entries are skipped), or feeds from OPML or another reader
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Import {
+        #[command(subcommand)]
+        source: Option<ImportSource>,
+
+        /// File to import
+        #[arg(required = true)]
+        path: Option<PathBuf>,
+
+        /// Input format (json, csv, ndjson); inferred from the extension if omitted
+        #[arg(short, long)]
+        format: Option<String>,
+    },
+
+    /// Diagnose feed health (all feeds, or a single URL)
+    Doctor {
+        /// Feed URL to probe (omit to check all stored feeds)
+        url: Option<String>,
+    },
+
+    /// Print a shell completion script, e.g. `source <(presser completions bash)`
+    Completions {
+        /// Shell to complete for
+        shell: clap_complete::Shell,
+    },
+
+    /// Write man pages for presser and every subcommand
+    Manpages {
+        /// Directory to write them to (created if missing)
+        dir: PathBuf,
+    },
 
     /// Initialize configuration
     Init,
 }
 
+#[derive(Subcommand, Debug)]
+enum ArchiveCommand {
+    /// Open an entry's archived page in the browser
+    Open {
+        /// Entry ID (as listed by `presser read`)
+        entry: String,
+
+        /// Print the HTML to stdout instead
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Archive an entry's page now, replacing any earlier copy
+    Save {
+        /// Entry ID (as listed by `presser read`)
+        entry: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum UsersCommand {
+    /// Create a user and print their API token (shown only once)
+    Add { name: String },
+
+    /// List users
+    List,
+
+    /// Delete a user and their read, starred, and tag state
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum FeedsCommand {
+    /// Flag dead feeds (silent for months, or 404/410 on every recent fetch)
+    /// and suggest replacements their sites advertise
+    Audit {
+        /// Months without a new entry before a feed counts as dead
+        #[arg(long, default_value = "6")]
+        months: u32,
+
+        /// Disable the dead feeds in the config (asks once first)
+        #[arg(long)]
+        disable: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum FiltersCommand {
+    /// Show which recent stored entries each feed's rules would keep or drop
+    Test {
+        /// Only this feed
+        #[arg(long, add = ArgValueCandidates::new(commands::feed_id_candidates))]
+        feed: Option<String>,
+
+        /// Try this expression instead of the configured rules
+        #[arg(long)]
+        rule: Option<String>,
+
+        /// Most recent entries to test per feed
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: i64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ExportTarget {
+    /// Export the feed list as OPML, filed under each feed's folder
+    Opml {
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// One Markdown file per starred or annotated entry (e.g. into an Obsidian vault)
+    Notes {
+        /// Directory to write to (files already there are skipped)
+        #[arg(long)]
+        dir: PathBuf,
+
+        /// Rewrite files that were exported before
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ImportSource {
+    /// Subscribe to the feeds in an OPML file, keeping their folders
+    Opml {
+        /// OPML file
+        path: PathBuf,
+    },
+
+    /// Subscriptions, folders, starred entries, and read state from Miniflux
+    Miniflux {
+        /// Miniflux URL, e.g. https://reader.example.com
+        #[arg(long)]
+        url: String,
+
+        /// API token (Settings > API Keys)
+        #[arg(long)]
+        token: String,
+
+        /// Most recent entries to copy read state from (starred entries are capped too)
+        #[arg(long, default_value = "1000")]
+        limit: usize,
+    },
+
+    /// Subscriptions, folders, starred entries, and read state from FreshRSS
+    #[command(name = "freshrss")]
+    FreshRss {
+        /// FreshRSS URL, e.g. https://rss.example.com
+        #[arg(long)]
+        url: String,
+
+        /// User name
+        #[arg(long)]
+        user: String,
+
+        /// API password (Profile > API management)
+        #[arg(long)]
+        password: String,
+
+        /// Most recent entries to copy read state from (starred entries are capped too)
+        #[arg(long, default_value = "1000")]
+        limit: usize,
+    },
+
+    /// Subscriptions, categories, saved entries, and read state from Feedly
+    Feedly {
+        /// Developer access token
+        #[arg(long)]
+        token: String,
+
+        /// API endpoint
+        #[arg(long, default_value = presser_feeds::readers::feedly::DEFAULT_URL)]
+        url: String,
+
+        /// Most recent entries to copy read state from (saved entries are capped too)
+        #[arg(long, default_value = "1000")]
+        limit: usize,
+    },
+}
+
+fn main() -> Result<()> {
+    // Answer the completion script's callback (`COMPLETE=<shell> presser ...`) and exit
+    CompleteEnv::with_factory(Cli::command).complete();
+    run()
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn run() -> Result<()> {
     let cli = Cli::parse();
 
     // Setup logging
@@ -97,19 +679,32 @@ async fn main() -> Result<()> {
         Level::WARN
     };
 
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(log_level)
-        .with_target(false)
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber)
-        .context("Failed to set tracing subscriber")?;
+    // A broken config is reported by the command itself; log to the console meanwhile
+    let config = presser_config::Config::load().ok();
+    logging::init(log_level, config.as_ref().map(|c| &c.logging))?;
+    if cli.offline {
+        engine::force_offline();
+    }
 
     // Execute command
     match cli.command {
-        Commands::Add { url, name } => {
+        Commands::Add {
+            url,
+            name,
+            tags,
+            interval,
+            no_ai,
+            fetch_now,
+        } => {
             let engine = Engine::new().await?;
-            commands::add_feed(&engine, &url, name.as_deref()).await?;
+            let options = AddOptions {
+                name,
+                tags,
+                interval,
+                no_ai,
+                fetch_now,
+            };
+            commands::add_feed(&engine, &url, options).await?;
         }
         Commands::Remove { id } => {
             let engine = Engine::new().await?;
@@ -119,12 +714,165 @@ async fn main() -> Result<()> {
             let engine = Engine::new().await?;
             commands::list_feeds(&engine).await?;
         }
-        Commands::Update { feed_id } => {
+        Commands::Update {
+            feed_id,
+            dry_run: true,
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            commands::preview_update(&engine, feed_id.as_deref()).await?;
+        }
+        Commands::Update { feed_id, local, .. } => {
+            let request = match &feed_id {
+                Some(id) => control::ControlRequest::Refresh {
+                    feed_id: id.clone(),
+                },
+                None => control::ControlRequest::RefreshAll,
+            };
+            if local || !commands::delegate_to_daemon(request).await? {
+                let engine = Engine::new().await?;
+                commands::update_feeds(&engine, feed_id.as_deref()).await?;
+            }
+        }
+        Commands::FixUrls { dry_run } => {
             let engine = Engine::new().await?;
-            commands::update_feeds(&engine, feed_id.as_deref()).await?;
+            commands::fix_urls(&engine, dry_run).await?;
         }
-        Commands::Digest { days, format } => {
-            generate_digest(days, &format).await?;
+        Commands::Feeds {
+            command: FeedsCommand::Audit { months, disable },
+        } => {
+            let engine = Engine::new().await?;
+            commands::audit_feeds(&engine, months, disable).await?;
+        }
+        Commands::Read {
+            feed,
+            tag,
+            days,
+            since,
+            limit,
+            all,
+        } => {
+            let engine = Engine::new().await?;
+            let options = ReadOptions {
+                feed,
+                tag,
+                days,
+                since,
+                limit,
+                all,
+            };
+            commands::read_entries(&engine, options).await?;
+        }
+        Commands::Show {
+            id,
+            no_pager,
+            keep_unread,
+        } => {
+            let engine = Engine::new().await?;
+            commands::show_entry(&engine, &id, !no_pager, keep_unread).await?;
+        }
+        Commands::Save { id, to } => {
+            let engine = Engine::new().await?;
+            commands::save_entry(&engine, &id, to.as_deref()).await?;
+        }
+        Commands::MarkRead { scope } => {
+            let engine = Engine::new().await?;
+            commands::mark_entries(&engine, scope, true).await?;
+        }
+        Commands::MarkUnread { scope } => {
+            let engine = Engine::new().await?;
+            commands::mark_entries(&engine, scope, false).await?;
+        }
+        Commands::Summarize {
+            url,
+            entry,
+            queued,
+            limit,
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            // The argument group guarantees --missing when nothing else is given
+            let target = match (url, entry) {
+                (Some(url), _) => SummarizeTarget::Url(url),
+                (None, Some(id)) => SummarizeTarget::Entry(id),
+                (None, None) if queued => SummarizeTarget::Queued { limit },
+                (None, None) => SummarizeTarget::Missing { limit },
+            };
+            commands::summarize(&engine, target).await?;
+        }
+        Commands::Ask {
+            question,
+            limit,
+            days,
+            feed,
+        } => {
+            let engine = Engine::new().await?;
+            let options = ask::AskOptions {
+                limit,
+                since: days
+                    .map(|days| chrono::Utc::now() - chrono::Duration::days(i64::from(days))),
+                feed,
+            };
+            commands::ask(&engine, &question, &options).await?;
+        }
+        Commands::Digest {
+            days,
+            format,
+            template,
+            group_by,
+            output,
+            dry_run,
+            synthesize,
+            confirm_cost,
+        } => {
+            let engine = Engine::new().await?;
+            let options = DigestOptions {
+                days,
+                format,
+                template,
+                group_by,
+                output,
+                dry_run,
+                synthesize,
+                confirm_cost,
+            };
+            commands::generate_digest(&engine, options).await?;
+        }
+        Commands::Report {
+            period,
+            format,
+            template,
+            output,
+            dry_run,
+            no_narrative,
+            confirm_cost,
+        } => {
+            let engine = Engine::new().await?;
+            let options = ReportOptions {
+                period,
+                format,
+                template,
+                output,
+                dry_run,
+                no_narrative,
+                confirm_cost,
+            };
+            commands::generate_report(&engine, options).await?;
+        }
+        Commands::Publish {
+            dir,
+            days,
+            title,
+            base_url,
+        } => {
+            let engine = Engine::new().await?;
+            let options = PublishOptions {
+                dir,
+                days,
+                title,
+                base_url,
+            };
+            commands::publish(&engine, options).await?;
         }
         Commands::Tui => {
             let engine = std::sync::Arc::new(Engine::new().await?);
@@ -133,14 +881,210 @@ async fn main() -> Result<()> {
         Commands::Daemon => {
             start_daemon().await?;
         }
-        Commands::Stats => {
+        Commands::Serve {
+            listen,
+            feeds_out,
+            days,
+            api,
+            ingest,
+        } => {
+            let engine = Engine::new().await?;
+            let options = server::ServerOptions {
+                listen,
+                feeds_out,
+                days,
+                api,
+                ingest,
+            };
+            server::serve(engine, options).await?;
+        }
+        Commands::Users { command } => {
+            let engine = Engine::new().await?;
+            match command {
+                UsersCommand::Add { name } => commands::add_user(&engine, &name).await?,
+                UsersCommand::List => commands::list_users(&engine).await?,
+                UsersCommand::Remove { name } => commands::remove_user(&engine, &name).await?,
+            }
+        }
+        Commands::Ctl { command } => {
+            commands::ctl(command).await?;
+        }
+        Commands::Log { feed, since, limit } => {
+            let engine = Engine::new().await?;
+            commands::show_log(&engine, feed.as_deref(), since, limit).await?;
+        }
+        Commands::Mute { list: true, .. } => {
+            let engine = Engine::new().await?;
+            commands::list_mutes(&engine).await?;
+        }
+        Commands::Mute {
+            pattern,
+            duration,
+            regex,
+            tag,
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            let kind = match (regex, tag) {
+                (true, _) => presser_db::MuteKind::Regex,
+                (_, true) => presser_db::MuteKind::Tag,
+                _ => presser_db::MuteKind::Keyword,
+            };
+            // The argument group guarantees a pattern without --list
+            let pattern = pattern.unwrap_or_default();
+            commands::mute(&engine, kind, &pattern, duration).await?;
+        }
+        Commands::Unmute { id } => {
+            let engine = Engine::new().await?;
+            commands::unmute(&engine, id).await?;
+        }
+        Commands::Archive { command } => {
+            let engine = Engine::new().await?;
+            match command {
+                ArchiveCommand::Open { entry, print } => {
+                    commands::open_archived(&engine, &entry, print).await?
+                }
+                ArchiveCommand::Save { entry } => commands::archive_entry(&engine, &entry).await?,
+            }
+        }
+        Commands::Filters {
+            command: FiltersCommand::Test { feed, rule, limit },
+        } => {
+            let engine = Engine::new().await?;
+            commands::test_filters(&engine, feed.as_deref(), rule.as_deref(), limit).await?;
+        }
+        Commands::Stats { days } => {
             let engine = Engine::new().await?;
-            commands::show_stats(&engine).await?;
+            commands::show_stats(&engine, days).await?;
+        }
+        Commands::Prune { vacuum, dry_run } => {
+            let engine = Engine::new().await?;
+            commands::prune(&engine, vacuum, dry_run).await?;
+        }
+        Commands::Backup { path } => {
+            let engine = Engine::new().await?;
+            commands::backup(&engine, &path).await?;
+        }
+        Commands::Restore { path } => {
+            commands::restore(&path).await?;
+        }
+        Commands::Export {
+            target: Some(ExportTarget::Opml { output }),
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            commands::export_opml(&engine, output.as_deref()).await?;
+        }
+        Commands::Export {
+            target: Some(ExportTarget::Notes { dir, overwrite }),
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            commands::export_notes(&engine, &dir, overwrite).await?;
+        }
+        Commands::Export {
+            target: None,
+            format,
+            output,
+            feed,
+            days,
+            starred,
+        } => {
+            let engine = Engine::new().await?;
+            let options = ExportOptions {
+                format,
+                output,
+                feed,
+                days,
+                starred,
+            };
+            commands::export_entries(&engine, options).await?;
+        }
+        Commands::Import {
+            source: Some(ImportSource::Opml { path }),
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            commands::import_opml(&engine, &path).await?;
+        }
+        Commands::Import {
+            source: Some(ImportSource::Miniflux { url, token, limit }),
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            commands::import_reader(&engine, &ReaderService::Miniflux { url, token }, limit)
+                .await?;
+        }
+        Commands::Import {
+            source:
+                Some(ImportSource::FreshRss {
+                    url,
+                    user,
+                    password,
+                    limit,
+                }),
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            let service = ReaderService::FreshRss {
+                url,
+                user,
+                password,
+            };
+            commands::import_reader(&engine, &service, limit).await?;
+        }
+        Commands::Import {
+            source: Some(ImportSource::Feedly { token, url, limit }),
+            ..
+        } => {
+            let engine = Engine::new().await?;
+            commands::import_reader(&engine, &ReaderService::Feedly { url, token }, limit).await?;
+        }
+        Commands::Import {
+            source: None,
+            path,
+            format,
+        } => {
+            let engine = Engine::new().await?;
+            let path = path.context("No file to import")?;
+            commands::import_entries(&engine, &path, format.as_deref()).await?;
+        }
+        Commands::Doctor { url } => {
+            let engine = Engine::new().await?;
+            commands::doctor(&engine, url.as_deref()).await?;
         }
         Commands::Init => {
             init_config().await?;
         }
+        Commands::Completions { shell } => {
+            commands::print_completions(shell)?;
+        }
+        Commands::Manpages { dir } => {
+            commands::write_manpages(Cli::command(), &dir)?;
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        // Completions and man pages build every subcommand, so clashes like a
+        // subcommand's `-d` shadowing the global `--debug` must not slip in
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_summarize_limit_requires_batch() {
+        assert!(Cli::try_parse_from(["presser", "summarize", "--missing", "-n", "5"]).is_ok());
+        assert!(Cli::try_parse_from(["presser", "summarize", "--queued", "-n", "5"]).is_ok());
+        assert!(
+            Cli::try_parse_from(["presser", "summarize", "https://example.com/a", "-n", "5"])
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,527 @@
+//! Process-wide metrics
+//!
+//! Counters and histograms live in one static registry ([`METRICS`]) so the
+//! update pipeline can record into it without threading a handle through
+//! every call, and so values survive the daemon swapping its `Engine` on
+//! `reload-config`. `presser serve` exposes them at `/metrics` in the
+//! Prometheus text format; the daemon logs a one-line summary periodically.
+//!
+//! Every outbound HTTP request (feeds, pages, icons, and AI providers) is
+//! also timed per source through the fetcher's and AI client's observers
+//! ([`feeds_observer`], [`ai_observer`]), which log requests slower than
+//! `[global].slow_request_secs`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use presser_ai::{AiRequest, AiRequestObserver};
+use presser_feeds::{RequestObserver, RequestOutcome, RequestRecord};
+
+/// Upper bounds (seconds) of the fetch latency buckets
+const FETCH_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Upper bounds (seconds) of the AI latency buckets
+const AI_BUCKETS: &[f64] = &[0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 40.0, 80.0];
+
+/// Requests per source kept for latency percentiles
+const RECENT_REQUESTS: usize = 1000;
+
+/// Percentiles reported for request latency
+const QUANTILES: &[f64] = &[0.5, 0.9, 0.99];
+
+/// Source every request is also counted under
+const ALL_SOURCES: &str = "all";
+
+/// The registry
+pub static METRICS: Metrics = Metrics::new();
+
+/// Monotonic counter
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    /// Add one
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    /// Add `n`
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Current value
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Distribution of durations over fixed buckets
+#[derive(Debug)]
+pub struct Histogram {
+    buckets: &'static [f64],
+    state: Mutex<HistogramState>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct HistogramState {
+    /// Observations per bucket (not cumulative); one extra for +Inf
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    const fn new(buckets: &'static [f64]) -> Self {
+        Self {
+            buckets,
+            state: Mutex::new(HistogramState {
+                counts: Vec::new(),
+                sum: 0.0,
+                count: 0,
+            }),
+        }
+    }
+
+    /// Record one duration
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = self
+            .buckets
+            .iter()
+            .position(|&bound| seconds <= bound)
+            .unwrap_or(self.buckets.len());
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.counts.resize(self.buckets.len() + 1, 0);
+        state.counts[bucket] += 1;
+        state.sum += seconds;
+        state.count += 1;
+    }
+
+    fn snapshot(&self) -> HistogramState {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        state.counts.resize(self.buckets.len() + 1, 0);
+        state
+    }
+}
+
+/// How a timed request went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestFlags {
+    pub timed_out: bool,
+    pub failed: bool,
+    /// Slower than the slow-request threshold
+    pub slow: bool,
+}
+
+/// Latency of outbound requests per source (`feeds`, or the AI provider),
+/// with percentiles over each source's most recent requests
+#[derive(Debug)]
+pub struct RequestLatencies(Mutex<BTreeMap<String, RequestStats>>);
+
+#[derive(Debug, Default, Clone)]
+struct RequestStats {
+    /// Seconds of the most recent requests, overwritten oldest first
+    recent: Vec<f64>,
+    next: usize,
+    count: u64,
+    sum: f64,
+    timeouts: u64,
+    failures: u64,
+    slow: u64,
+}
+
+impl RequestStats {
+    fn record(&mut self, seconds: f64, flags: RequestFlags) {
+        if self.recent.len() < RECENT_REQUESTS {
+            self.recent.push(seconds);
+        } else {
+            self.recent[self.next] = seconds;
+        }
+        self.next = (self.next + 1) % RECENT_REQUESTS;
+        self.count += 1;
+        self.sum += seconds;
+        self.timeouts += u64::from(flags.timed_out);
+        self.failures += u64::from(flags.failed);
+        self.slow += u64::from(flags.slow);
+    }
+
+    /// Nearest-rank percentile of the recent requests
+    fn percentile(&self, q: f64) -> f64 {
+        let mut sorted = self.recent.clone();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (q * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied().unwrap_or(0.0)
+    }
+}
+
+impl RequestLatencies {
+    const fn new() -> Self {
+        Self(Mutex::new(BTreeMap::new()))
+    }
+
+    /// Record one request under `source` and under all sources
+    pub fn record(&self, source: &str, duration: Duration, flags: RequestFlags) {
+        let seconds = duration.as_secs_f64();
+        let mut stats = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        stats
+            .entry(source.to_string())
+            .or_default()
+            .record(seconds, flags);
+        stats
+            .entry(ALL_SOURCES.to_string())
+            .or_default()
+            .record(seconds, flags);
+    }
+
+    fn snapshot(&self) -> BTreeMap<String, RequestStats> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Await one AI request, observing how long it took in `ai_seconds`
+pub async fn time_ai<T>(request: impl std::future::Future<Output = T>) -> T {
+    let started = std::time::Instant::now();
+    let result = request.await;
+    METRICS.ai_seconds.observe(started.elapsed());
+    result
+}
+
+/// Observer timing the fetcher's requests, logging those that take at least
+/// `slow` (zero logs none)
+pub fn feeds_observer(slow: Duration) -> RequestObserver {
+    Arc::new(move |request: &RequestRecord| {
+        let flags = RequestFlags {
+            timed_out: request.outcome == RequestOutcome::TimedOut,
+            failed: !matches!(request.outcome, RequestOutcome::Status(status) if status < 400),
+            slow: is_slow(request.duration, slow),
+        };
+        if flags.slow {
+            tracing::warn!(
+                "Slow request: {} took {:.1}s ({})",
+                request.url,
+                request.duration.as_secs_f64(),
+                match request.outcome {
+                    RequestOutcome::Status(status) => format!("HTTP {}", status),
+                    RequestOutcome::TimedOut => "timed out".to_string(),
+                    RequestOutcome::Failed => "failed".to_string(),
+                }
+            );
+        }
+        METRICS.requests.record("feeds", request.duration, flags);
+    })
+}
+
+/// Observer timing the AI client's requests, logging those that take at
+/// least `slow` (zero logs none)
+pub fn ai_observer(slow: Duration) -> AiRequestObserver {
+    Arc::new(move |request: &AiRequest| {
+        let flags = RequestFlags {
+            timed_out: request.timed_out,
+            failed: request.failed,
+            slow: is_slow(request.duration, slow),
+        };
+        if flags.slow {
+            tracing::warn!(
+                "Slow AI request: {} ({}) took {:.1}s{}",
+                request.provider.name(),
+                request.model,
+                request.duration.as_secs_f64(),
+                if request.timed_out {
+                    " and timed out"
+                } else {
+                    ""
+                }
+            );
+        }
+        METRICS
+            .requests
+            .record(request.provider.name(), request.duration, flags);
+    })
+}
+
+fn is_slow(duration: Duration, threshold: Duration) -> bool {
+    !threshold.is_zero() && duration >= threshold
+}
+
+/// Everything Presser measures
+#[derive(Debug)]
+pub struct Metrics {
+    /// Feed fetches attempted
+    pub fetches: Counter,
+    /// Fetches answered with 304 Not Modified
+    pub fetches_not_modified: Counter,
+    /// Fetches that served the same body as the last one
+    pub fetches_unchanged: Counter,
+    /// Fetches that failed (network, HTTP status, or parsing)
+    pub fetch_errors: Counter,
+    /// Fetches whose body could not be parsed as a feed
+    pub parse_errors: Counter,
+    /// Summaries generated (cache hits included)
+    pub summaries: Counter,
+    /// Summary requests that failed
+    pub summary_errors: Counter,
+    /// Tokens reported by the AI provider
+    pub tokens: Counter,
+    /// Summaries served from the AI cache
+    pub cache_hits: Counter,
+    /// Fetch latency
+    pub fetch_seconds: Histogram,
+    /// Summary latency
+    pub ai_seconds: Histogram,
+    /// Latency of every outbound request, per source
+    pub requests: RequestLatencies,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            fetches: Counter::new(),
+            fetches_not_modified: Counter::new(),
+            fetches_unchanged: Counter::new(),
+            fetch_errors: Counter::new(),
+            parse_errors: Counter::new(),
+            summaries: Counter::new(),
+            summary_errors: Counter::new(),
+            tokens: Counter::new(),
+            cache_hits: Counter::new(),
+            fetch_seconds: Histogram::new(FETCH_BUCKETS),
+            ai_seconds: Histogram::new(AI_BUCKETS),
+            requests: RequestLatencies::new(),
+        }
+    }
+
+    /// Prometheus text exposition format
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        for (name, help, counter) in [
+            (
+                "presser_fetches_total",
+                "Feed fetches attempted",
+                &self.fetches,
+            ),
+            (
+                "presser_fetches_not_modified_total",
+                "Feed fetches answered with 304 Not Modified",
+                &self.fetches_not_modified,
+            ),
+            (
+                "presser_fetches_unchanged_total",
+                "Feed fetches that served the same body as the last one",
+                &self.fetches_unchanged,
+            ),
+            (
+                "presser_fetch_errors_total",
+                "Feed fetches that failed",
+                &self.fetch_errors,
+            ),
+            (
+                "presser_parse_errors_total",
+                "Fetched documents that could not be parsed as feeds",
+                &self.parse_errors,
+            ),
+            (
+                "presser_summaries_total",
+                "AI summaries generated",
+                &self.summaries,
+            ),
+            (
+                "presser_summary_errors_total",
+                "AI summary requests that failed",
+                &self.summary_errors,
+            ),
+            (
+                "presser_ai_tokens_total",
+                "Tokens reported by the AI provider",
+                &self.tokens,
+            ),
+            (
+                "presser_ai_cache_hits_total",
+                "Summaries served from the AI cache",
+                &self.cache_hits,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, counter.get());
+        }
+        for (name, help, histogram) in [
+            (
+                "presser_fetch_duration_seconds",
+                "Feed fetch latency",
+                &self.fetch_seconds,
+            ),
+            (
+                "presser_ai_duration_seconds",
+                "AI summary latency",
+                &self.ai_seconds,
+            ),
+        ] {
+            let state = histogram.snapshot();
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} histogram", name);
+            let mut cumulative = 0;
+            for (bound, count) in histogram.buckets.iter().zip(&state.counts) {
+                cumulative += count;
+                let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+            }
+            let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, state.count);
+            let _ = writeln!(out, "{}_sum {}", name, state.sum);
+            let _ = writeln!(out, "{}_count {}", name, state.count);
+        }
+
+        let requests = self.requests.snapshot();
+        let name = "presser_http_request_duration_seconds";
+        let _ = writeln!(
+            out,
+            "# HELP {} Outbound request latency per source (quantiles over the last {} requests)",
+            name, RECENT_REQUESTS
+        );
+        let _ = writeln!(out, "# TYPE {} summary", name);
+        for (source, stats) in &requests {
+            for q in QUANTILES {
+                let _ = writeln!(
+                    out,
+                    "{}{{source=\"{}\",quantile=\"{}\"}} {}",
+                    name,
+                    source,
+                    q,
+                    stats.percentile(*q)
+                );
+            }
+            let _ = writeln!(out, "{}_sum{{source=\"{}\"}} {}", name, source, stats.sum);
+            let _ = writeln!(
+                out,
+                "{}_count{{source=\"{}\"}} {}",
+                name, source, stats.count
+            );
+        }
+        for (name, help, count) in [
+            (
+                "presser_http_request_timeouts_total",
+                "Outbound requests that timed out",
+                (|s: &RequestStats| s.timeouts) as fn(&RequestStats) -> u64,
+            ),
+            (
+                "presser_http_request_failures_total",
+                "Outbound requests that failed or got an error status",
+                |s| s.failures,
+            ),
+            (
+                "presser_http_slow_requests_total",
+                "Outbound requests slower than slow_request_secs",
+                |s| s.slow,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (source, stats) in &requests {
+                let _ = writeln!(out, "{}{{source=\"{}\"}} {}", name, source, count(stats));
+            }
+        }
+        out
+    }
+
+    /// One-line summary for the daemon log
+    pub fn summary(&self) -> String {
+        let mean = |histogram: &Histogram| {
+            let state = histogram.snapshot();
+            if state.count == 0 {
+                0.0
+            } else {
+                state.sum / state.count as f64
+            }
+        };
+        let requests = match self.requests.snapshot().get(ALL_SOURCES) {
+            Some(all) => format!(
+                "; {} requests (p50 {:.2}s, p90 {:.2}s, p99 {:.2}s, {} slow, {} timed out)",
+                all.count,
+                all.percentile(0.5),
+                all.percentile(0.9),
+                all.percentile(0.99),
+                all.slow,
+                all.timeouts
+            ),
+            None => String::new(),
+        };
+        format!(
+            "{} fetches ({} not modified, {} unchanged, {} failed, {} parse errors, mean {:.2}s); \
+             {} summaries ({} cached, {} failed, {} tokens, mean {:.2}s){}",
+            self.fetches.get(),
+            self.fetches_not_modified.get(),
+            self.fetches_unchanged.get(),
+            self.fetch_errors.get(),
+            self.parse_errors.get(),
+            mean(&self.fetch_seconds),
+            self.summaries.get(),
+            self.cache_hits.get(),
+            self.summary_errors.get(),
+            self.tokens.get(),
+            mean(&self.ai_seconds),
+            requests,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus() {
+        let metrics = Metrics::new();
+        metrics.fetches.add(3);
+        metrics.tokens.add(120);
+        metrics.fetch_seconds.observe(Duration::from_millis(200));
+        metrics.fetch_seconds.observe(Duration::from_secs(60));
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains("# TYPE presser_fetches_total counter\npresser_fetches_total 3\n"));
+        assert!(text.contains("presser_ai_tokens_total 120\n"));
+        assert!(text.contains("presser_fetch_duration_seconds_bucket{le=\"0.1\"} 0\n"));
+        assert!(text.contains("presser_fetch_duration_seconds_bucket{le=\"0.25\"} 1\n"));
+        assert!(text.contains("presser_fetch_duration_seconds_bucket{le=\"30\"} 1\n"));
+        assert!(text.contains("presser_fetch_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("presser_fetch_duration_seconds_count 2\n"));
+        assert!(text.contains("presser_ai_duration_seconds_count 0\n"));
+        assert!(metrics.summary().starts_with("3 fetches"));
+        assert!(!metrics.summary().contains("requests"));
+
+        let slow = RequestFlags {
+            slow: true,
+            ..Default::default()
+        };
+        for ms in 1..=100 {
+            metrics.requests.record(
+                "feeds",
+                Duration::from_millis(ms * 10),
+                RequestFlags::default(),
+            );
+        }
+        metrics
+            .requests
+            .record("openai", Duration::from_secs(30), slow);
+        let requests = metrics.requests.snapshot();
+        assert_eq!(requests["feeds"].percentile(0.5), 0.5);
+        assert_eq!(requests["feeds"].percentile(0.99), 0.99);
+        assert_eq!(requests["all"].percentile(0.99), 1.0);
+        assert!(!requests.contains_key("anthropic"));
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains(
+            "presser_http_request_duration_seconds{source=\"feeds\",quantile=\"0.9\"} 0.9\n"
+        ));
+        assert!(text.contains("presser_http_request_duration_seconds_count{source=\"all\"} 101\n"));
+        assert!(text.contains("presser_http_slow_requests_total{source=\"openai\"} 1\n"));
+        assert!(metrics
+            .summary()
+            .ends_with("; 101 requests (p50 0.51s, p90 0.91s, p99 1.00s, 1 slow, 0 timed out)"));
+    }
+}
@@ -0,0 +1,222 @@
+//! Mute rules: fetched entries matching a rule in force are dropped like
+//! filtered ones, until the rule expires
+
+use anyhow::{Context, Result};
+use presser_db::{Entry, MuteKind, MuteRule};
+use presser_feeds::FeedEntry;
+use regex::{Regex, RegexBuilder};
+
+/// Most keywords suggested for muting an entry
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Title words too common to be worth muting
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "again", "also", "because", "been", "before", "being", "could", "does",
+    "from", "have", "here", "into", "just", "more", "most", "over", "said", "says", "should",
+    "some", "than", "that", "their", "them", "then", "there", "these", "they", "this", "what",
+    "when", "where", "which", "while", "will", "with", "would", "your",
+];
+
+/// How a rule's pattern is matched
+enum Matcher {
+    /// Lowercased keyword
+    Keyword(String),
+    Regex(Regex),
+    /// Lowercased tag
+    Tag(String),
+}
+
+impl Matcher {
+    fn new(kind: MuteKind, pattern: &str) -> Result<Self> {
+        Ok(match kind {
+            MuteKind::Keyword => Matcher::Keyword(pattern.to_lowercase()),
+            MuteKind::Regex => Matcher::Regex(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid regex '{}'", pattern))?,
+            ),
+            MuteKind::Tag => Matcher::Tag(pattern.to_lowercase()),
+        })
+    }
+}
+
+/// Check that a pattern can be muted (not empty, and a valid regex for
+/// [`MuteKind::Regex`])
+pub fn check(kind: MuteKind, pattern: &str) -> Result<()> {
+    anyhow::ensure!(!pattern.trim().is_empty(), "Nothing to mute");
+    Matcher::new(kind, pattern).map(drop)
+}
+
+/// The rules in force, ready to match fetched entries
+#[derive(Default)]
+pub struct Mutes {
+    rules: Vec<(MuteRule, Matcher)>,
+}
+
+impl Mutes {
+    /// Compile `rules`; one whose regex no longer compiles is logged and
+    /// skipped
+    pub fn new(rules: Vec<MuteRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|rule| match Matcher::new(rule.kind, &rule.pattern) {
+                Ok(matcher) => Some((rule, matcher)),
+                Err(e) => {
+                    tracing::warn!("Skipping mute rule {}: {:#}", rule.id, e);
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// The first rule muting `entry`: a keyword or regex found in its title,
+    /// description, or content, or one of its categories
+    pub fn matching(&self, entry: &FeedEntry) -> Option<&MuteRule> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let text = [
+            Some(&entry.title),
+            entry.summary.as_ref(),
+            entry.content_text.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+        let lower = text.to_lowercase();
+        self.rules
+            .iter()
+            .find(|(_, matcher)| match matcher {
+                Matcher::Keyword(keyword) => lower.contains(keyword.as_str()),
+                Matcher::Regex(regex) => regex.is_match(&text),
+                Matcher::Tag(tag) => entry.categories.iter().any(|c| c.to_lowercase() == *tag),
+            })
+            .map(|(rule, _)| rule)
+    }
+}
+
+/// Topics of a stored entry worth offering to mute: its categories, then
+/// runs of capitalized title words ("World Cup"), then other longer title
+/// words
+pub fn suggestions(entry: &Entry) -> Vec<(MuteKind, String)> {
+    let mut topics: Vec<(MuteKind, String)> = entry
+        .category_list()
+        .into_iter()
+        .map(|c| (MuteKind::Tag, c))
+        .collect();
+
+    topics.extend(
+        title_topics(&entry.title)
+            .into_iter()
+            .map(|topic| (MuteKind::Keyword, topic)),
+    );
+
+    let mut seen = Vec::new();
+    topics.retain(|(_, topic)| {
+        let key = topic.to_lowercase();
+        let new = !seen.contains(&key);
+        seen.push(key);
+        new
+    });
+    topics.truncate(MAX_SUGGESTIONS);
+    topics
+}
+
+/// Topics in a title: runs of capitalized words ("World Cup"), then other
+/// longer words (lowercased), possibly repeating each other
+pub(crate) fn title_topics(title: &str) -> Vec<String> {
+    let words: Vec<&str> = title
+        .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '\'')
+        .map(|w| w.trim_matches(|c| c == '-' || c == '\''))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let capitalized = |w: &&str| w.chars().next().is_some_and(char::is_uppercase);
+    let mut topics = Vec::new();
+    let mut run: Vec<&str> = Vec::new();
+    for word in words.iter().chain([&""]) {
+        if capitalized(word) {
+            run.push(word);
+            continue;
+        }
+        if run.len() > 1 {
+            topics.push(run.join(" "));
+        }
+        run.clear();
+    }
+    topics.extend(
+        words
+            .iter()
+            .filter(|w| w.chars().count() >= 4 && !STOP_WORDS.contains(&w.to_lowercase().as_str()))
+            .map(|w| w.to_lowercase()),
+    );
+    topics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_mutes() {
+        let rule = |id, kind, pattern: &str| MuteRule {
+            id,
+            kind,
+            pattern: pattern.into(),
+            expires_at: None,
+            created_at: Utc::now(),
+        };
+        let mutes = Mutes::new(vec![
+            rule(1, MuteKind::Keyword, "World Cup"),
+            rule(2, MuteKind::Regex, r"\bnfts?\b"),
+            rule(3, MuteKind::Tag, "Crypto"),
+            rule(4, MuteKind::Regex, "(unclosed"),
+        ]);
+        let entry = |title: &str, categories: &[&str]| FeedEntry {
+            id: "1".into(),
+            title: title.into(),
+            url: "https://example.com/1".into(),
+            published: None,
+            updated: None,
+            summary: None,
+            content_html: None,
+            content_text: None,
+            author: None,
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            discussion: None,
+            enclosures: Vec::new(),
+        };
+        let id = |title: &str, categories: &[&str]| {
+            mutes.matching(&entry(title, categories)).map(|r| r.id)
+        };
+        assert_eq!(id("Argentina wins the world cup", &[]), Some(1));
+        assert_eq!(id("New NFT marketplace", &[]), Some(2));
+        assert_eq!(id("Unfts", &[]), None);
+        assert_eq!(id("Markets today", &["crypto"]), Some(3));
+        assert_eq!(id("Rust 1.80 released", &["rust"]), None);
+        assert!(check(MuteKind::Regex, "(unclosed").is_err());
+        assert!(check(MuteKind::Keyword, "  ").is_err());
+
+        let stored = Entry {
+            title: "Argentina wins the World Cup after penalties".into(),
+            categories: Some(r#"["Sports"]"#.into()),
+            ..Default::default()
+        };
+        let topics: Vec<String> = suggestions(&stored).into_iter().map(|(_, t)| t).collect();
+        assert_eq!(
+            topics,
+            [
+                "Sports",
+                "World Cup",
+                "argentina",
+                "wins",
+                "world",
+                "penalties"
+            ]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Native desktop notifications (D-Bus on Linux and the BSDs, Notification
+//! Center on macOS, toasts on Windows)
+//!
+//! Shown only when built with the `desktop-notifications` feature; without
+//! it every send fails with a hint to rebuild.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{Notification, Notifier};
+
+/// Longest body shown, in characters (summaries can run long)
+#[cfg(feature = "desktop-notifications")]
+const MAX_BODY: usize = 300;
+
+/// Shows notifications on the desktop presser runs on
+#[derive(Debug, Default)]
+pub struct DesktopNotifier;
+
+impl DesktopNotifier {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    #[cfg(feature = "desktop-notifications")]
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        use anyhow::Context;
+
+        let body = crate::text::truncate(&notification.body, MAX_BODY);
+        let mut desktop = notify_rust::Notification::new();
+        desktop
+            .appname("presser")
+            .summary(&notification.title)
+            .body(&body);
+        #[cfg(all(unix, not(target_os = "macos")))]
+        desktop.urgency(if notification.is_urgent() {
+            notify_rust::Urgency::Normal
+        } else {
+            notify_rust::Urgency::Low
+        });
+        // The platform APIs block
+        tokio::task::spawn_blocking(move || desktop.show().map(drop))
+            .await?
+            .context("Failed to show desktop notification")
+    }
+
+    #[cfg(not(feature = "desktop-notifications"))]
+    async fn send(&self, _notification: &Notification) -> Result<()> {
+        anyhow::bail!(
+            "Desktop notifications require building with the `desktop-notifications` feature"
+        )
+    }
+}
@@ -0,0 +1,80 @@
+//! Discord channel webhooks
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Notification, Notifier};
+use crate::text::truncate;
+
+/// Discord's limits on embed titles and descriptions
+const MAX_TITLE: usize = 256;
+const MAX_DESCRIPTION: usize = 4096;
+
+/// Posts each notification as an embed
+pub struct DiscordNotifier {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl DiscordNotifier {
+    /// Notifier posting to a channel webhook
+    pub fn new(client: reqwest::Client, webhook_url: &str) -> Self {
+        Self {
+            client,
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+}
+
+/// Webhook payload with the notification as one embed
+fn payload(notification: &Notification) -> Value {
+    let mut embed = json!({
+        "title": truncate(&notification.title, MAX_TITLE),
+        "description": truncate(&notification.body, MAX_DESCRIPTION),
+    });
+    if let Some(url) = &notification.url {
+        embed["url"] = json!(url);
+    }
+    json!({ "username": "Presser", "embeds": [embed] })
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        // The webhook URL is a secret, so keep it out of error messages
+        self.client
+            .post(&self.webhook_url)
+            .json(&payload(notification))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| anyhow::anyhow!("{}", e.without_url()))
+            .context("Failed to post to Discord webhook")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_config::NotificationKind;
+
+    #[test]
+    fn test_payload_truncates() {
+        let value = payload(&Notification {
+            event: NotificationKind::HighPriority,
+            title: "x".repeat(300),
+            body: "Details".into(),
+            url: Some("https://example.com/a".into()),
+        });
+        let embed = &value["embeds"][0];
+        assert_eq!(embed["title"].as_str().unwrap().chars().count(), MAX_TITLE);
+        assert!(embed["title"].as_str().unwrap().ends_with('…'));
+        assert_eq!(embed["url"], "https://example.com/a");
+    }
+}
@@ -0,0 +1,271 @@
+//! Notifications about engine events
+//!
+//! Each configured backend is a [`Notifier`]; [`Notifications`] routes events
+//! to the backends that want them, outside their quiet hours. Delivery
+//! failures are logged and never fail the operation that triggered them.
+
+mod desktop;
+mod discord;
+mod ntfy;
+mod telegram;
+mod webhook;
+
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Local, NaiveTime};
+use futures::future::join_all;
+use presser_config::{NotificationKind, NotificationsConfig, NotifierBackend, NotifierConfig};
+use presser_db::Entry;
+use serde::Serialize;
+
+pub use desktop::DesktopNotifier;
+pub use discord::DiscordNotifier;
+pub use ntfy::NtfyNotifier;
+pub use telegram::TelegramNotifier;
+pub use webhook::WebhookNotifier;
+
+/// Time allowed for a single delivery
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Something worth telling the user about
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    /// Event that triggered the notification
+    pub event: NotificationKind,
+
+    /// Short headline
+    pub title: String,
+
+    /// Details
+    pub body: String,
+
+    /// Link to open (article, feed, ...)
+    pub url: Option<String>,
+}
+
+impl Notification {
+    /// Whether the event is urgent enough to interrupt the user
+    pub fn is_urgent(&self) -> bool {
+        self.event != NotificationKind::DigestReady
+    }
+
+    /// Title, body, and link as plain text lines
+    pub(crate) fn to_text(&self) -> String {
+        let mut text = format!("{}\n{}", self.title, self.body);
+        if let Some(url) = &self.url {
+            text.push('\n');
+            text.push_str(url);
+        }
+        text
+    }
+}
+
+/// Delivers notifications to one destination
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Backend name for logs
+    fn name(&self) -> &'static str;
+
+    /// Deliver one notification
+    async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Notifier for a configured backend
+pub fn notifier(backend: &NotifierBackend, client: reqwest::Client) -> Box<dyn Notifier> {
+    match backend {
+        NotifierBackend::Webhook { url } => Box::new(WebhookNotifier::new(client, url)),
+        NotifierBackend::Ntfy {
+            server,
+            topic,
+            token,
+        } => Box::new(NtfyNotifier::new(client, server, topic, token.clone())),
+        NotifierBackend::Telegram { bot_token, chat_id } => {
+            Box::new(TelegramNotifier::new(client, bot_token, chat_id))
+        }
+        NotifierBackend::Discord { webhook_url } => {
+            Box::new(DiscordNotifier::new(client, webhook_url))
+        }
+        NotifierBackend::Desktop => Box::new(DesktopNotifier::new()),
+    }
+}
+
+/// A notifier with its config: the events it wants and when it must stay quiet
+struct Route {
+    notifier: Box<dyn Notifier>,
+    config: NotifierConfig,
+}
+
+/// Configured notifiers plus the rules that decide when to notify
+#[derive(Default)]
+pub struct Notifications {
+    routes: Vec<Route>,
+    priority_keywords: Vec<String>,
+    min_score: Option<f64>,
+    failure_threshold: i64,
+}
+
+impl Notifications {
+    /// Build notifiers from the `[notifications]` config section
+    pub fn from_config(config: &NotificationsConfig) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(SEND_TIMEOUT).build()?;
+        let mut notifications = Self {
+            priority_keywords: config
+                .priority_keywords
+                .iter()
+                .map(|k| k.to_lowercase())
+                .collect(),
+            min_score: config.min_score,
+            failure_threshold: config.failure_threshold,
+            ..Default::default()
+        };
+        for backend in &config.backends {
+            notifications = notifications
+                .with_notifier(notifier(&backend.backend, client.clone()), backend.clone());
+        }
+        Ok(notifications)
+    }
+
+    /// Add a notifier for the events and outside the quiet hours of
+    /// `config` (whose backend settings `notifier` was built from)
+    pub fn with_notifier(mut self, notifier: Box<dyn Notifier>, config: NotifierConfig) -> Self {
+        self.routes.push(Route { notifier, config });
+        self
+    }
+
+    /// Whether a new entry matches the priority rules
+    pub fn is_high_priority(&self, entry: &Entry) -> bool {
+        if matches!((self.min_score, entry.score), (Some(min), Some(score)) if score >= min) {
+            return true;
+        }
+        if self.priority_keywords.is_empty() {
+            return false;
+        }
+        let text = format!(
+            "{}\n{}",
+            entry.title,
+            entry.summary.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+        self.priority_keywords.iter().any(|k| text.contains(k))
+    }
+
+    /// Whether a feed with this many consecutive failures just started failing
+    ///
+    /// Only the fetch that reaches the threshold notifies, not every one after it.
+    pub fn is_newly_failing(&self, consecutive_failures: i64) -> bool {
+        self.failure_threshold > 0 && consecutive_failures == self.failure_threshold
+    }
+
+    /// Send to every notifier that wants this event and isn't in its quiet
+    /// hours (a notification during them is dropped, not delayed)
+    pub async fn notify(&self, notification: &Notification) {
+        self.notify_at(notification, Local::now().time()).await;
+    }
+
+    async fn notify_at(&self, notification: &Notification, time: NaiveTime) {
+        let sends = self
+            .routes
+            .iter()
+            .filter(|route| route.config.wants(notification.event))
+            .filter(|route| {
+                route
+                    .config
+                    .quiet_hours
+                    .is_none_or(|quiet| !quiet.contains(time))
+            })
+            .map(|route| async move {
+                if let Err(e) = route.notifier.send(notification).await {
+                    tracing::warn!("{} notification failed: {:#}", route.notifier.name(), e);
+                }
+            });
+        join_all(sends).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl Notifier for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        async fn send(&self, notification: &Notification) -> Result<()> {
+            self.0.lock().unwrap().push(notification.title.clone());
+            Ok(())
+        }
+    }
+
+    /// Routing of a test notifier (its backend is not used)
+    fn route(
+        events: Vec<NotificationKind>,
+        quiet_hours: Option<presser_config::QuietHours>,
+    ) -> NotifierConfig {
+        NotifierConfig {
+            backend: NotifierBackend::Desktop,
+            events,
+            quiet_hours,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routing_and_rules() {
+        let all = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let notifications = Notifications::from_config(&NotificationsConfig {
+            priority_keywords: vec!["Outage".into()],
+            min_score: Some(0.9),
+            ..Default::default()
+        })
+        .unwrap()
+        .with_notifier(Box::new(Recorder(all.clone())), route(vec![], None))
+        .with_notifier(
+            Box::new(Recorder(failures.clone())),
+            route(
+                vec![NotificationKind::FeedFailing],
+                Some("22:00-07:00".parse().unwrap()),
+            ),
+        );
+
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let night = NaiveTime::from_hms_opt(23, 30, 0).unwrap();
+        for (event, title, time) in [
+            (NotificationKind::DigestReady, "digest", noon),
+            (NotificationKind::FeedFailing, "failing", noon),
+            (NotificationKind::FeedFailing, "failing at night", night),
+        ] {
+            let notification = Notification {
+                event,
+                title: title.into(),
+                body: String::new(),
+                url: None,
+            };
+            notifications.notify_at(&notification, time).await;
+        }
+        assert_eq!(
+            *all.lock().unwrap(),
+            ["digest", "failing", "failing at night"]
+        );
+        assert_eq!(*failures.lock().unwrap(), ["failing"]);
+
+        let entry = |title: &str, score| Entry {
+            title: title.into(),
+            score,
+            ..Default::default()
+        };
+        assert!(notifications.is_high_priority(&entry("Major OUTAGE at provider", None)));
+        assert!(notifications.is_high_priority(&entry("Quiet day", Some(0.95))));
+        assert!(!notifications.is_high_priority(&entry("Quiet day", Some(0.5))));
+
+        assert!(!notifications.is_newly_failing(2));
+        assert!(notifications.is_newly_failing(3));
+        assert!(!notifications.is_newly_failing(4));
+    }
+}
@@ -0,0 +1,57 @@
+//! ntfy.sh (or self-hosted ntfy) topics
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{Notification, Notifier};
+
+/// Publishes to an ntfy topic using its JSON API
+pub struct NtfyNotifier {
+    client: reqwest::Client,
+    server: String,
+    topic: String,
+    token: Option<String>,
+}
+
+impl NtfyNotifier {
+    /// Notifier for `topic` on `server`, authenticating with `token` if set
+    pub fn new(client: reqwest::Client, server: &str, topic: &str, token: Option<String>) -> Self {
+        Self {
+            client,
+            server: server.trim_end_matches('/').to_string(),
+            topic: topic.to_string(),
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        // Publishing as JSON avoids ntfy's ASCII-only title header
+        let mut message = json!({
+            "topic": self.topic,
+            "title": notification.title,
+            "message": notification.body,
+            "priority": if notification.is_urgent() { 4 } else { 3 },
+        });
+        if let Some(url) = &notification.url {
+            message["click"] = json!(url);
+        }
+        let mut request = self.client.post(&self.server).json(&message);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Failed to publish to ntfy topic {}", self.topic))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+//! Telegram bot messages
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{Notification, Notifier};
+
+/// Bot API endpoint
+const API_URL: &str = "https://api.telegram.org";
+
+/// Sends notifications as plain-text messages from a bot
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    /// Notifier posting to `chat_id` as the bot identified by `bot_token`
+    pub fn new(client: reqwest::Client, bot_token: &str, chat_id: &str) -> Self {
+        Self {
+            client,
+            bot_token: bot_token.to_string(),
+            chat_id: chat_id.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        // The URL embeds the bot token, so keep it out of error messages
+        let url = format!("{}/bot{}/sendMessage", API_URL, self.bot_token);
+        self.client
+            .post(url)
+            .json(&json!({
+                "chat_id": self.chat_id,
+                "text": notification.to_text(),
+                "disable_notification": !notification.is_urgent(),
+            }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| anyhow::anyhow!("{}", e.without_url()))
+            .with_context(|| format!("Failed to message Telegram chat {}", self.chat_id))?;
+        Ok(())
+    }
+}
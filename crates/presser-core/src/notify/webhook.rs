@@ -0,0 +1,74 @@
+//! Generic JSON webhook
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use super::{Notification, Notifier};
+
+/// POSTs each notification as JSON (`event`, `title`, `body`, `url`)
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    /// Notifier posting to `url`
+    pub fn new(client: reqwest::Client, url: &str) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(notification)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Failed to post to {}", self.url))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_config::NotificationKind;
+
+    #[tokio::test]
+    async fn test_posts_json() {
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "event": "feed_failing",
+                "title": "Feed failing: Rust Blog",
+            })))
+            .with_status(204)
+            .create_async()
+            .await;
+        let url = format!("{}/hook", server.url());
+
+        let notifier = WebhookNotifier::new(reqwest::Client::new(), &url);
+        notifier
+            .send(&Notification {
+                event: NotificationKind::FeedFailing,
+                title: "Feed failing: Rust Blog".into(),
+                body: "3 failed fetches in a row".into(),
+                url: Some("https://blog.rust-lang.org/feed.xml".into()),
+            })
+            .await
+            .unwrap();
+
+        hook.assert_async().await;
+    }
+}
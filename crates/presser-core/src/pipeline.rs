@@ -0,0 +1,262 @@
+//! Plugin points in the feed update pipeline
+//!
+//! A [`Pipeline`] registered with [`Engine::register_pipeline`] sees every
+//! entry as it moves through an update: right after fetching, just before it
+//! is stored, and around summarization. Hooks can rewrite what they are given
+//! or drop it, which is how custom filtering and enrichment (scoring, extra
+//! tags, ...) plug in without changing the engine.
+//!
+//! Hooks run in registration order and the first to drop an entry wins. A
+//! failing hook is recorded in the update report and the entry carries on as
+//! if the hook had kept it, so a broken plugin never loses entries.
+//!
+//! [`Engine::register_pipeline`]: crate::Engine::register_pipeline
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use presser_db::{Entry, Feed, Summary};
+use presser_feeds::FeedEntry;
+
+use crate::update::UpdateReport;
+
+/// What a hook decided about an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Let the entry continue
+    Keep,
+    /// Stop here: not stored (fetch and store hooks) or not summarized
+    Drop,
+}
+
+/// Custom processing of entries during feed updates
+///
+/// Every hook defaults to keeping its input unchanged, so implementations
+/// only override the stages they care about.
+#[async_trait]
+pub trait Pipeline: Send + Sync {
+    /// Hook name for logs and reports
+    fn name(&self) -> &str;
+
+    /// A fetched entry that passed the feed's keyword filters; `categories`
+    /// become the entry's feed tags
+    async fn on_entry_fetched(&self, _feed: &Feed, _entry: &mut FeedEntry) -> Result<Verdict> {
+        Ok(Verdict::Keep)
+    }
+
+    /// An entry about to be written to the database
+    async fn before_store(&self, _entry: &mut Entry) -> Result<Verdict> {
+        Ok(Verdict::Keep)
+    }
+
+    /// Text about to be sent to the AI provider for `entry_id`
+    async fn before_summarize(&self, _entry_id: &str, _content: &mut String) -> Result<Verdict> {
+        Ok(Verdict::Keep)
+    }
+
+    /// A generated summary, before it is stored
+    async fn after_summarize(&self, _summary: &mut Summary) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Registered hooks, run in order
+#[derive(Clone, Default)]
+pub struct Pipelines(Vec<Arc<dyn Pipeline>>);
+
+impl Pipelines {
+    /// Add a hook after the existing ones
+    pub fn push(&mut self, pipeline: Arc<dyn Pipeline>) {
+        self.0.push(pipeline);
+    }
+
+    /// Run `on_entry_fetched` hooks
+    pub(crate) async fn entry_fetched(
+        &self,
+        feed: &Feed,
+        entry: &mut FeedEntry,
+        report: &mut UpdateReport,
+    ) -> Verdict {
+        for pipeline in &self.0 {
+            let result = pipeline.on_entry_fetched(feed, entry).await;
+            if verdict(
+                pipeline.as_ref(),
+                "on_entry_fetched",
+                &entry.id,
+                result,
+                report,
+            ) == Verdict::Drop
+            {
+                return Verdict::Drop;
+            }
+        }
+        Verdict::Keep
+    }
+
+    /// Run `before_store` hooks
+    pub(crate) async fn before_store(
+        &self,
+        entry: &mut Entry,
+        report: &mut UpdateReport,
+    ) -> Verdict {
+        for pipeline in &self.0 {
+            let result = pipeline.before_store(entry).await;
+            if verdict(pipeline.as_ref(), "before_store", &entry.id, result, report)
+                == Verdict::Drop
+            {
+                return Verdict::Drop;
+            }
+        }
+        Verdict::Keep
+    }
+
+    /// Run `before_summarize` hooks
+    pub(crate) async fn before_summarize(
+        &self,
+        entry_id: &str,
+        content: &mut String,
+        report: &mut UpdateReport,
+    ) -> Verdict {
+        for pipeline in &self.0 {
+            let result = pipeline.before_summarize(entry_id, content).await;
+            if verdict(
+                pipeline.as_ref(),
+                "before_summarize",
+                entry_id,
+                result,
+                report,
+            ) == Verdict::Drop
+            {
+                return Verdict::Drop;
+            }
+        }
+        Verdict::Keep
+    }
+
+    /// Run `after_summarize` hooks
+    pub(crate) async fn after_summarize(&self, summary: &mut Summary, report: &mut UpdateReport) {
+        for pipeline in &self.0 {
+            let result = pipeline
+                .after_summarize(summary)
+                .await
+                .map(|()| Verdict::Keep);
+            verdict(
+                pipeline.as_ref(),
+                "after_summarize",
+                &summary.entry_id,
+                result,
+                report,
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for Pipelines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|p| p.name()))
+            .finish()
+    }
+}
+
+/// A hook's verdict, with failures recorded and treated as `Keep`
+fn verdict(
+    pipeline: &dyn Pipeline,
+    stage: &str,
+    entry_id: &str,
+    result: Result<Verdict>,
+    report: &mut UpdateReport,
+) -> Verdict {
+    result.unwrap_or_else(|e| {
+        let error = format!(
+            "Pipeline {} failed in {} for {}: {:#}",
+            pipeline.name(),
+            stage,
+            entry_id,
+            e
+        );
+        tracing::warn!("{}", error);
+        report.errors.push(error);
+        Verdict::Keep
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::{serve_rss, test_config};
+    use crate::Engine;
+
+    /// Drops sponsored entries, tags the rest, and keeps short entries from the AI
+    struct Curator;
+
+    #[async_trait]
+    impl Pipeline for Curator {
+        fn name(&self) -> &str {
+            "curator"
+        }
+
+        async fn on_entry_fetched(&self, _feed: &Feed, entry: &mut FeedEntry) -> Result<Verdict> {
+            if entry.title.contains("Sponsored") {
+                return Ok(Verdict::Drop);
+            }
+            entry.categories.push("curated".into());
+            Ok(Verdict::Keep)
+        }
+
+        async fn before_store(&self, entry: &mut Entry) -> Result<Verdict> {
+            anyhow::ensure!(!entry.url.is_empty(), "no link");
+            entry.title = entry.title.to_uppercase();
+            Ok(Verdict::Keep)
+        }
+
+        async fn before_summarize(&self, _entry_id: &str, content: &mut String) -> Result<Verdict> {
+            Ok(if content.len() < 20 {
+                Verdict::Drop
+            } else {
+                Verdict::Keep
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_hooks() {
+        let (_server, url) = serve_rss(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Test</title><link>https://example.com</link>
+            <item><guid>a</guid><title>Rust news</title><link>https://example.com/a</link><description>Short</description></item>
+            <item><guid>b</guid><title>Sponsored</title><link>https://example.com/b</link></item>
+            <item><guid>c</guid><title>Rust tips</title><description>No link</description></item>
+            </channel></rss>"#,
+        )
+        .await;
+        let mut engine = Engine::with_config(test_config()).await.unwrap();
+        engine.register_pipeline(Curator);
+        let db = engine.database();
+        db.upsert_feed(&Feed {
+            id: "test".into(),
+            url,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        // A dry run sees what the hooks make of the entries
+        let plan = engine.plan_feed_update("test").await.unwrap();
+        assert_eq!(plan.new, ["RUST NEWS", "Rust tips"]);
+        assert_eq!(plan.skipped, 1);
+
+        let report = engine.update_feed("test").await.unwrap();
+        assert_eq!((report.new, report.skipped, report.summarized), (2, 1, 0));
+        // The failed hook is reported, but no entry reached the (failing) AI provider
+        assert_eq!(report.errors.len(), 1, "{:?}", report.errors);
+        assert!(report.errors[0].contains("curator failed in before_store for c"));
+
+        let stored = db.get_entry("a").await.unwrap().unwrap();
+        assert_eq!(stored.title, "RUST NEWS");
+        assert_eq!(db.get_tags_for_entry("a").await.unwrap(), ["curated"]);
+        assert_eq!(db.get_entry("c").await.unwrap().unwrap().title, "Rust tips");
+        assert!(db.get_entry("b").await.unwrap().is_none());
+    }
+}
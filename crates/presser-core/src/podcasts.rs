@@ -0,0 +1,176 @@
+//! Podcast episodes: the audio enclosures of entries, downloaded into the
+//! `[podcasts]` directory and played by its player from where they were
+//! left
+//!
+//! Playback position is estimated from how long the player ran, so time
+//! spent paused counts as listened.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use presser_db::Enclosure;
+
+use crate::commands::format_bytes;
+
+/// How often a download in progress writes its progress to the database
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A player resumed past the start that exits sooner than this had
+/// nothing left to play: the episode starts over next time
+const FINISHED_RUN: Duration = Duration::from_secs(5);
+
+/// First audio enclosure of an entry, if it has one
+pub fn audio_enclosure(enclosures: &[Enclosure]) -> Option<&Enclosure> {
+    enclosures
+        .iter()
+        .find(|e| presser_feeds::is_audio(e.mime_type.as_deref(), &e.url))
+}
+
+/// Name an enclosure's download is saved under: its ID and the last
+/// segment of its address, without characters file systems object to
+pub fn file_name(enclosure: &Enclosure) -> String {
+    let path = enclosure.url.split(['?', '#']).next().unwrap_or_default();
+    let name: String = path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') => c,
+            _ => '_',
+        })
+        .collect();
+    match name.trim_matches('.') {
+        "" => format!("{}.audio", enclosure.id),
+        name => format!("{}-{}", enclosure.id, name),
+    }
+}
+
+/// Where a download is written until it finishes
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    PathBuf::from(partial)
+}
+
+/// What the player is given: the downloaded file if it is still there,
+/// else the enclosure's address, streamed
+pub fn playable(enclosure: &Enclosure) -> String {
+    match enclosure.local_path.as_deref() {
+        Some(path) if Path::new(path).exists() => path.to_string(),
+        _ => enclosure.url.clone(),
+    }
+}
+
+/// Position playback stopped at, given where it started and how long the
+/// player ran
+pub fn stopped_at(start_secs: i64, ran: Duration) -> i64 {
+    if start_secs > 0 && ran < FINISHED_RUN {
+        return 0;
+    }
+    start_secs.max(0) + ran.as_secs() as i64
+}
+
+/// A position as `m:ss`, or `h:mm:ss` past an hour
+pub fn format_position(secs: i64) -> String {
+    let secs = secs.max(0);
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// State of an enclosure's download, as the downloads view shows it
+pub fn download_status(enclosure: &Enclosure) -> String {
+    if enclosure.local_path.is_some() {
+        return match enclosure
+            .length
+            .or(Some(enclosure.downloaded_bytes).filter(|&b| b > 0))
+        {
+            Some(bytes) => format!("done, {}", format_bytes(bytes)),
+            None => "done".to_string(),
+        };
+    }
+    if let Some(error) = &enclosure.download_error {
+        return format!("failed: {}", error);
+    }
+    let downloaded = format_bytes(enclosure.downloaded_bytes);
+    match (enclosure.progress(), enclosure.length) {
+        (Some(progress), Some(length)) => format!(
+            "{:.0}% ({} of {})",
+            progress * 100.0,
+            downloaded,
+            format_bytes(length)
+        ),
+        _ => downloaded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn episode(url: &str) -> Enclosure {
+        Enclosure {
+            id: 7,
+            entry_id: "ep".into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_audio_enclosure() {
+        let enclosures = vec![
+            Enclosure {
+                mime_type: Some("image/jpeg".into()),
+                ..episode("https://cdn.example.com/cover.jpg")
+            },
+            episode("https://cdn.example.com/ep.mp3?source=rss"),
+        ];
+        assert_eq!(
+            audio_enclosure(&enclosures).map(|e| e.url.as_str()),
+            Some("https://cdn.example.com/ep.mp3?source=rss")
+        );
+        assert!(audio_enclosure(&enclosures[..1]).is_none());
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name(&episode("https://cdn.example.com/show/ep 12.mp3?x=1")),
+            "7-ep_12.mp3"
+        );
+        assert_eq!(file_name(&episode("https://cdn.example.com/")), "7.audio");
+        assert_eq!(
+            partial_path(Path::new("/podcasts/7-ep.mp3")),
+            Path::new("/podcasts/7-ep.mp3.part")
+        );
+    }
+
+    #[test]
+    fn test_stopped_at() {
+        assert_eq!(stopped_at(0, Duration::from_secs(95)), 95);
+        assert_eq!(stopped_at(600, Duration::from_secs(60)), 660);
+        // Resumed at the end: the player exits at once
+        assert_eq!(stopped_at(3000, Duration::from_secs(1)), 0);
+        assert_eq!(format_position(95), "1:35");
+        assert_eq!(format_position(3723), "1:02:03");
+    }
+
+    #[test]
+    fn test_download_status() {
+        let mut enclosure = Enclosure {
+            length: Some(4 * 1024 * 1024),
+            downloaded_bytes: 1024 * 1024,
+            ..episode("https://cdn.example.com/ep.mp3")
+        };
+        assert_eq!(download_status(&enclosure), "25% (1.0 MB of 4.0 MB)");
+        enclosure.download_error = Some("HTTP 404".into());
+        assert_eq!(download_status(&enclosure), "failed: HTTP 404");
+        enclosure.local_path = Some("/podcasts/7-ep.mp3".into());
+        assert_eq!(download_status(&enclosure), "done, 4.0 MB");
+    }
+}
@@ -0,0 +1,255 @@
+//! What changed between two configurations, so a reload (`presser ctl
+//! reload-config`) rebuilds and reschedules only what it has to
+//!
+//! Sections and feeds are compared by their serialized settings; changes
+//! are reported by setting name, never by value, so API keys stay out of
+//! the log.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use presser_config::Config;
+use serde::Serialize;
+use serde_json::Value;
+
+/// `[ai]` settings the AI client is built with
+const AI_CLIENT_KEYS: &[&str] = &[
+    "provider",
+    "api_key",
+    "model",
+    "endpoint",
+    "system_prompt",
+    "max_tokens",
+    "temperature",
+    "enable_cache",
+];
+
+/// `[global]` settings the AI client is built with
+const AI_CLIENT_GLOBAL_KEYS: &[&str] = &["slow_request_secs"];
+
+/// `[content]` settings the AI client is built with
+const AI_CLIENT_CONTENT_KEYS: &[&str] = &["max_prompt_bytes", "truncation"];
+
+/// Differences between an old and a new configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// Changed sections (`ai`, `scheduler`, ...) with the names of their
+    /// changed settings (none for sections that are lists, like `filters`)
+    pub sections: BTreeMap<String, Vec<String>>,
+    /// URLs of feeds only in the new configuration
+    pub feeds_added: Vec<String>,
+    /// URLs of feeds only in the old configuration
+    pub feeds_removed: Vec<String>,
+    /// URLs of feeds in both whose settings changed, with those settings
+    pub feeds_changed: BTreeMap<String, Vec<String>>,
+}
+
+impl ConfigDiff {
+    /// What changed from `old` to `new`
+    pub fn between(old: &Config, new: &Config) -> Self {
+        let mut diff = ConfigDiff::default();
+        let (old_value, new_value) = (to_value(old), to_value(new));
+        let sections: BTreeSet<&String> = object_keys(&old_value)
+            .chain(object_keys(&new_value))
+            .filter(|key| *key != "feeds")
+            .collect();
+        for section in sections {
+            let (old_section, new_section) = (&old_value[section], &new_value[section]);
+            if old_section != new_section {
+                diff.sections
+                    .insert(section.clone(), changed_keys(old_section, new_section));
+            }
+        }
+
+        let old_urls: BTreeSet<&String> = old.feeds.keys().collect();
+        let new_urls: BTreeSet<&String> = new.feeds.keys().collect();
+        diff.feeds_added = new_urls
+            .difference(&old_urls)
+            .map(|url| url.to_string())
+            .collect();
+        diff.feeds_removed = old_urls
+            .difference(&new_urls)
+            .map(|url| url.to_string())
+            .collect();
+        for url in old_urls.intersection(&new_urls) {
+            let (old_feed, new_feed) = (to_value(&old.feeds[*url]), to_value(&new.feeds[*url]));
+            if old_feed != new_feed {
+                diff.feeds_changed
+                    .insert(url.to_string(), changed_keys(&old_feed, &new_feed));
+            }
+        }
+        diff
+    }
+
+    /// Whether the configurations are the same
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+            && self.feeds_added.is_empty()
+            && self.feeds_removed.is_empty()
+            && self.feeds_changed.is_empty()
+    }
+
+    /// Whether any setting of `section` changed
+    pub fn section_changed(&self, section: &str) -> bool {
+        self.sections.contains_key(section)
+    }
+
+    /// Whether `key` of `section` changed
+    pub fn changed(&self, section: &str, key: &str) -> bool {
+        self.sections
+            .get(section)
+            .is_some_and(|keys| keys.iter().any(|k| k == key))
+    }
+
+    /// Whether the AI client has to be built again: its provider, model,
+    /// prompt, request, or prompt size settings changed
+    pub fn rebuilds_ai(&self) -> bool {
+        AI_CLIENT_KEYS.iter().any(|key| self.changed("ai", key))
+            || AI_CLIENT_GLOBAL_KEYS
+                .iter()
+                .any(|key| self.changed("global", key))
+            || AI_CLIENT_CONTENT_KEYS
+                .iter()
+                .any(|key| self.changed("content", key))
+    }
+
+    /// Whether the database has to be opened again (its path, pool, or key
+    /// changed)
+    pub fn reopens_database(&self) -> bool {
+        self.section_changed("database")
+    }
+
+    /// Whether scheduler concurrency changed, which only a restarted daemon
+    /// picks up
+    pub fn changes_concurrency(&self) -> bool {
+        self.changed("global", "max_concurrent_fetches") || self.changed("scheduler", "groups")
+    }
+
+    /// One line per change, for the log
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (section, keys) in &self.sections {
+            lines.push(match keys.is_empty() {
+                true => format!("[{}] changed", section),
+                false => format!("[{}] changed: {}", section, keys.join(", ")),
+            });
+        }
+        lines.extend(
+            self.feeds_added
+                .iter()
+                .map(|url| format!("feed added: {}", url)),
+        );
+        lines.extend(
+            self.feeds_removed
+                .iter()
+                .map(|url| format!("feed removed: {}", url)),
+        );
+        for (url, keys) in &self.feeds_changed {
+            lines.push(format!("feed changed: {} ({})", url, keys.join(", ")));
+        }
+        lines
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn object_keys(value: &Value) -> impl Iterator<Item = &String> {
+    value
+        .as_object()
+        .into_iter()
+        .flat_map(|object| object.keys())
+}
+
+/// Keys of two objects whose values differ, sorted; none if either isn't
+/// an object
+fn changed_keys(old: &Value, new: &Value) -> Vec<String> {
+    if !(old.is_object() && new.is_object()) {
+        return Vec::new();
+    }
+    let keys: BTreeSet<&String> = object_keys(old).chain(object_keys(new)).collect();
+    keys.into_iter()
+        .filter(|key| old.get(key.as_str()) != new.get(key.as_str()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::test_config;
+    use presser_config::{AiProvider, FeedConfig, FilterRule};
+
+    fn feed(url: &str) -> FeedConfig {
+        FeedConfig::new(url, "Feed")
+    }
+
+    #[test]
+    fn test_config_diff() {
+        let mut old = test_config();
+        old.feeds
+            .insert("https://a.com/feed".into(), feed("https://a.com/feed"));
+        old.feeds
+            .insert("https://b.com/feed".into(), feed("https://b.com/feed"));
+        assert!(ConfigDiff::between(&old, &old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.feeds.remove("https://a.com/feed");
+        new.feeds
+            .insert("https://c.com/feed".into(), feed("https://c.com/feed"));
+        let b = new.feeds.get_mut("https://b.com/feed").unwrap();
+        b.update_interval = Some("0 0 * * * *".into());
+        new.ai.temperature = 0.2;
+        new.scheduler.default_interval = "0 0 */2 * * *".into();
+
+        let diff = ConfigDiff::between(&old, &new);
+        assert_eq!(diff.feeds_added, ["https://c.com/feed"]);
+        assert_eq!(diff.feeds_removed, ["https://a.com/feed"]);
+        assert_eq!(
+            diff.describe(),
+            [
+                "[ai] changed: temperature",
+                "[scheduler] changed: default_interval",
+                "feed added: https://c.com/feed",
+                "feed removed: https://a.com/feed",
+                "feed changed: https://b.com/feed (update_interval)",
+            ]
+        );
+        assert!(diff.rebuilds_ai());
+        assert!(!diff.reopens_database());
+        assert!(!diff.changes_concurrency());
+    }
+
+    #[test]
+    fn test_ai_rebuild() {
+        let old = test_config();
+        let mut new = old.clone();
+        // The budget is the engine's, not the client's
+        new.ai.daily_token_budget = Some(10_000);
+        new.content.max_html_bytes = 4096;
+        let diff = ConfigDiff::between(&old, &new);
+        assert!(diff.section_changed("ai") && !diff.rebuilds_ai());
+        new.content.max_prompt_bytes = 4096;
+        assert!(ConfigDiff::between(&old, &new).rebuilds_ai());
+        new.content = old.content.clone();
+
+        new.ai.provider = AiProvider::Anthropic;
+        new.ai.api_key = Some("sk-secret".into());
+        let diff = ConfigDiff::between(&old, &new);
+        assert!(diff.rebuilds_ai());
+        // Names of settings only
+        assert_eq!(
+            diff.describe(),
+            ["[ai] changed: api_key, daily_token_budget, provider"]
+        );
+
+        new.filters.push(FilterRule {
+            name: "short".into(),
+            rule: "word_count > 100".into(),
+            folders: Vec::new(),
+            tags: Vec::new(),
+        });
+        let diff = ConfigDiff::between(&old, &new);
+        assert!(diff.describe().contains(&"[filters] changed".to_string()));
+    }
+}
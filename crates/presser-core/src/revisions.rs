@@ -0,0 +1,219 @@
+//! Entry revisions: when an update brings new text for an entry already
+//! stored (a correction, a major edit), the text it replaces is kept with a
+//! line diff against the new one, and the AI can be asked what changed
+//! (`[ai] describe_changes`)
+//!
+//! Changes to whitespace alone don't count. The diff is a longest common
+//! subsequence of lines; texts too long for that are diffed as a whole.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Utc;
+use presser_ai::AiClient;
+use presser_db::{Database, Entry, EntryRevision};
+
+use crate::ai_queue::{self, Priority};
+use crate::update::{AiBudget, UpdateReport};
+
+/// Most line pairs compared when diffing; longer texts are shown as
+/// removed whole and added whole
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// A line diff between two texts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineDiff {
+    /// Changed lines, `-` removed and `+` added, in text order
+    pub text: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Diff `old` against `new` line by line, leaving out unchanged lines
+pub fn line_diff(old: &str, new: &str) -> LineDiff {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut diff = LineDiff::default();
+    let push = |diff: &mut LineDiff, sign: char, line: &str| {
+        diff.text.push(sign);
+        diff.text.push_str(line);
+        diff.text.push('\n');
+        match sign {
+            '+' => diff.added += 1,
+            _ => diff.removed += 1,
+        }
+    };
+
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        old.iter().for_each(|line| push(&mut diff, '-', line));
+        new.iter().for_each(|line| push(&mut diff, '+', line));
+        return diff;
+    }
+
+    // common[i][j]: longest common subsequence of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut common = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i * width + j] = if old[i] == new[j] {
+                common[(i + 1) * width + j + 1] + 1
+            } else {
+                common[(i + 1) * width + j].max(common[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len()
+            && (j == new.len() || common[(i + 1) * width + j] >= common[i * width + j + 1])
+        {
+            push(&mut diff, '-', old[i]);
+            i += 1;
+        } else {
+            push(&mut diff, '+', new[j]);
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// Text of `stored` that `fetched` replaces, with the new text, if it
+/// changed by more than whitespace
+///
+/// Extracted text is compared with extracted text and feed descriptions
+/// with feed descriptions, so an entry extracted after it was stored isn't
+/// taken for an edited one.
+pub fn changed_text(stored: &Entry, fetched: &Entry) -> Option<(String, String)> {
+    let (old, new) = match (&stored.content_text, &fetched.content_text) {
+        (Some(old), Some(new)) => (old, new),
+        (_, None) => (stored.summary.as_ref()?, fetched.summary.as_ref()?),
+        (None, Some(_)) => return None,
+    };
+    let words = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (words(old) != words(new)).then(|| (old.clone(), new.clone()))
+}
+
+/// Store a revision for each changed entry (`(entry_id, old text, new
+/// text)`), asking the AI what changed when `describe` is set and the
+/// daily token budget allows
+///
+/// A failed description is reported as an error; the revision is stored
+/// without one.
+pub(crate) async fn record_revisions(
+    ai: &Arc<AiClient>,
+    db: &Database,
+    budget: &AiBudget,
+    changed: Vec<(String, String, String)>,
+    describe: bool,
+    report: &mut UpdateReport,
+) -> Result<()> {
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let today = Utc::now().date_naive();
+    let mut spent = db.get_ai_usage(today).await?.tokens.max(0) as u64;
+    let ids: Vec<String> = changed.iter().map(|(id, _, _)| id.clone()).collect();
+    let priorities = match describe {
+        true => ai_queue::entry_priorities(db, &ids).await?,
+        false => Default::default(),
+    };
+    for (entry_id, old, new) in changed {
+        let diff = line_diff(&old, &new);
+        let mut revision = EntryRevision {
+            entry_id,
+            previous_text: old,
+            diff: diff.text,
+            lines_added: diff.added as i64,
+            lines_removed: diff.removed as i64,
+            created_at: Utc::now(),
+            ..Default::default()
+        };
+        if describe && !budget.exhausted(spent) {
+            let priority = priorities
+                .get(&revision.entry_id)
+                .copied()
+                .unwrap_or_else(Priority::lowest);
+            let slot = budget.acquire(priority).await;
+            budget.pace().await;
+            let described = ai.describe_changes(&revision.diff).await;
+            drop(slot);
+            match described {
+                Ok(summary) => {
+                    let tokens = summary.tokens.map_or(0, u64::from);
+                    if !summary.cached {
+                        db.record_ai_usage(today, tokens as i64).await?;
+                        report.tokens += tokens;
+                        spent += tokens;
+                    }
+                    revision.change_summary = Some(summary.text);
+                    revision.model = Some(summary.model);
+                    revision.tokens = summary.tokens.map(i64::from);
+                }
+                Err(e) => report.errors.push(format!(
+                    "Describing the changes to {} failed: {:#}",
+                    revision.entry_id, e
+                )),
+            }
+        }
+        db.add_revision(&revision).await?;
+        report.revised += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let old = "Title\nThe mayor said 12 people\nwere hurt.\nMore soon.";
+        let new =
+            "Title\nThe mayor said 15 people\nwere hurt.\nMore soon.\nCorrection: 15, not 12.";
+        let diff = line_diff(old, new);
+        assert_eq!(
+            diff.text,
+            "-The mayor said 12 people\n+The mayor said 15 people\n+Correction: 15, not 12.\n"
+        );
+        assert_eq!((diff.added, diff.removed), (2, 1));
+
+        assert_eq!(line_diff("same", "same"), LineDiff::default());
+        let diff = line_diff("", "new");
+        assert_eq!(
+            (diff.text.as_str(), diff.added, diff.removed),
+            ("+new\n", 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_changed_text() {
+        let entry = |content: Option<&str>, summary: Option<&str>| Entry {
+            content_text: content.map(str::to_string),
+            summary: summary.map(str::to_string),
+            ..Default::default()
+        };
+        let stored = entry(Some("Twelve  people\nwere hurt."), Some("Short"));
+        assert_eq!(
+            changed_text(&stored, &entry(Some("Twelve people were hurt."), None)),
+            None
+        );
+        assert_eq!(
+            changed_text(&stored, &entry(Some("Fifteen people were hurt."), None)),
+            Some((
+                "Twelve  people\nwere hurt.".to_string(),
+                "Fifteen people were hurt.".to_string()
+            ))
+        );
+        // Text extracted later isn't compared with a feed description
+        let extracted = entry(Some("Full article"), Some("Short"));
+        assert_eq!(changed_text(&extracted, &entry(None, Some("Short"))), None);
+        assert!(changed_text(&extracted, &entry(None, Some("Shorter"))).is_some());
+        assert_eq!(
+            changed_text(&entry(None, Some("Short")), &entry(Some("Full"), None)),
+            None
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Entry ranking: AI relevance, feed priority, recency, and social signals
+//! (Hacker News points, Reddit score) combined into one rank per entry,
+//! which orders digests and the TUI's top view
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use presser_config::{Config, ScoringConfig};
+use presser_db::{Database, EntryFilter, EntryRank};
+
+/// Relevance assumed for entries the AI hasn't scored
+const NEUTRAL_RELEVANCE: f64 = 0.5;
+
+/// Labels feeds put before a post's points or score, e.g. hnrss.org's
+/// "Points: 42"
+const SOCIAL_LABELS: [&str; 2] = ["points:", "score:"];
+
+/// What goes into an entry's rank
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signals {
+    /// AI relevance score in 0..=1 (`Entry::score`), if scored
+    pub relevance: Option<f64>,
+    /// The feed's `priority` (1.0 unless configured)
+    pub priority: f64,
+    /// Hours since the entry was published
+    pub age_hours: f64,
+    /// Hacker News points or Reddit score
+    pub social: Option<i64>,
+}
+
+/// Weighted mean of relevance and (when known) the social signal, times
+/// the feed's priority, halved every `half_life_hours`
+///
+/// Entries without a social signal aren't held against those with one.
+pub fn rank(config: &ScoringConfig, signals: &Signals) -> f64 {
+    let relevance = signals
+        .relevance
+        .unwrap_or(NEUTRAL_RELEVANCE)
+        .clamp(0.0, 1.0);
+    let mut weighted = config.relevance_weight * relevance;
+    let mut weights = config.relevance_weight;
+    if let Some(points) = signals.social {
+        let saturation = f64::from(config.social_saturation).ln_1p();
+        let social = ((points.max(0) as f64).ln_1p() / saturation).min(1.0);
+        weighted += config.social_weight * social;
+        weights += config.social_weight;
+    }
+    let quality = if weights > 0.0 {
+        weighted / weights
+    } else {
+        NEUTRAL_RELEVANCE
+    };
+    let decay = 0.5_f64.powf(signals.age_hours.max(0.0) / config.half_life_hours);
+    signals.priority * quality * decay
+}
+
+/// Points or score a feed's description gives for a post ("Points: 42",
+/// "Score: 7"), if any
+pub fn social_score(description: &str) -> Option<i64> {
+    let lower = description.to_lowercase();
+    SOCIAL_LABELS.iter().find_map(|label| {
+        let at = lower.find(label)? + label.len();
+        let digits: String = lower[at..]
+            .trim_start()
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    })
+}
+
+/// Rank every entry published in the last `[scoring].window_days` as of
+/// `now`, replacing the stored ranks, and return how many were ranked
+pub async fn rank_entries(db: &Database, config: &Config, now: DateTime<Utc>) -> Result<usize> {
+    let scoring = &config.scoring;
+    let since = now - Duration::days(i64::from(scoring.window_days));
+    let entries = db.query_entries(&EntryFilter::new().since(since)).await?;
+    let priorities: HashMap<String, f64> = db
+        .get_all_feeds()
+        .await?
+        .into_iter()
+        .filter_map(|feed| {
+            let priority = config.feeds.get(&feed.url)?.priority?;
+            Some((feed.id, priority))
+        })
+        .collect();
+
+    let ranks: Vec<EntryRank> = entries
+        .into_iter()
+        .map(|entry| {
+            let social = entry.summary.as_deref().and_then(social_score);
+            let published = entry.published.unwrap_or(entry.created_at);
+            let signals = Signals {
+                relevance: entry.score,
+                priority: priorities.get(&entry.feed_id).copied().unwrap_or(1.0),
+                age_hours: (now - published).num_seconds() as f64 / 3600.0,
+                social,
+            };
+            EntryRank {
+                rank: rank(scoring, &signals),
+                entry_id: entry.id,
+                social_score: social,
+            }
+        })
+        .collect();
+    db.replace_entry_ranks(&ranks).await?;
+    Ok(ranks.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_config::FeedConfig;
+    use presser_db::{fixtures, EntryOrder};
+
+    #[test]
+    fn test_rank() {
+        let config = ScoringConfig::default();
+        let signals = Signals {
+            relevance: None,
+            priority: 1.0,
+            age_hours: 0.0,
+            social: None,
+        };
+        assert_eq!(rank(&config, &signals), 0.5);
+        let day_old = Signals {
+            age_hours: 24.0,
+            ..signals.clone()
+        };
+        assert_eq!(rank(&config, &day_old), 0.25);
+        let boosted = Signals {
+            priority: 2.0,
+            ..day_old
+        };
+        assert_eq!(rank(&config, &boosted), 0.5);
+        let popular = Signals {
+            social: Some(10_000),
+            ..signals.clone()
+        };
+        assert_eq!(rank(&config, &popular), 0.75);
+        let relevant = Signals {
+            relevance: Some(1.0),
+            social: Some(0),
+            ..signals
+        };
+        assert_eq!(rank(&config, &relevant), 0.5);
+
+        assert_eq!(
+            social_score("<p>Article URL: https://x.io</p><p>Points: 42</p>"),
+            Some(42)
+        );
+        assert_eq!(social_score("submitted by /u/ferris, score:7"), Some(7));
+        assert_eq!(social_score("Points: many"), None);
+        assert_eq!(social_score("No signals here"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rank_entries() {
+        let (db, fixtures) = fixtures::seeded().await.unwrap();
+        let mut config = crate::engine::tests::test_config();
+        let mut rust_blog = FeedConfig::new(&fixtures.feeds[0].url, "Rust Blog");
+        rust_blog.priority = Some(10.0);
+        config.feeds.insert(rust_blog.url.clone(), rust_blog);
+
+        // `rust-old` is outside the two-week window
+        assert_eq!(rank_entries(&db, &config, Utc::now()).await.unwrap(), 4);
+        assert_eq!(db.get_entry_rank("rust-old").await.unwrap(), None);
+        let ids: Vec<String> = db
+            .query_entries(&EntryFilter::new().unread().order(EntryOrder::Rank))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, ["rust-async", "news-election", "news-weather"]);
+    }
+}
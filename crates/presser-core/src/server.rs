@@ -0,0 +1,594 @@
+//! HTTP server (`presser serve`)
+//!
+//! Serves read-only views of the database to other programs. `/health` and
+//! `/metrics` (Prometheus) are always available; `/feed.atom` (the summaries
+//! feed) only with `--feeds-out`, so no content is published unless asked for.
+//!
+//! With `--api`, `/api` serves the shared feeds and entries to the users
+//! created with `presser users add`, each authenticated by their token
+//! (`Authorization: Bearer ...`) and with read, starred, and tag state of
+//! their own. With `--ingest`, the same tokens let `POST /ingest` push
+//! articles (JSON `url`, `title`, `content`) into the inbox feed, where
+//! they wait to be summarized like fetched entries.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::{FromRequestParts, Path, Query, State};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use presser_db::{Database, DatabaseError, Entry, Feed, User, UserEntryFilter};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ingest::Ingested;
+use crate::metrics::METRICS;
+use crate::Engine;
+
+/// What `presser serve` exposes
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Address to listen on
+    pub listen: SocketAddr,
+
+    /// Serve the summaries feed at `/feed.atom`
+    pub feeds_out: bool,
+
+    /// Days of entries in the summaries feed unless `?days=` overrides it
+    pub days: u32,
+
+    /// Serve the per-user API under `/api`
+    pub api: bool,
+
+    /// Accept pushed articles at `/ingest`
+    pub ingest: bool,
+}
+
+struct ServerState {
+    engine: Arc<Engine>,
+    options: ServerOptions,
+}
+
+/// Query parameters of `/feed.atom`
+#[derive(Debug, Default, Deserialize)]
+struct FeedQuery {
+    days: Option<u32>,
+    tag: Option<String>,
+}
+
+/// Most entries `/api/entries` returns at once
+const MAX_API_ENTRIES: i64 = 500;
+
+/// Query parameters of `/api/entries`
+#[derive(Debug, Default, Deserialize)]
+struct EntriesQuery {
+    #[serde(default)]
+    unread: bool,
+    #[serde(default)]
+    starred: bool,
+    feed: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Body of `PATCH /api/entries/{id}`; fields left out are unchanged
+#[derive(Debug, Default, Deserialize)]
+struct EntryUpdate {
+    read: Option<bool>,
+    starred: Option<bool>,
+}
+
+/// An entry as a user sees it
+#[derive(Debug, Serialize)]
+struct ApiEntry {
+    id: String,
+    feed_id: String,
+    title: String,
+    url: String,
+    author: Option<String>,
+    published: Option<DateTime<Utc>>,
+    summary: Option<String>,
+    read: bool,
+    starred: bool,
+    tags: Vec<String>,
+}
+
+impl ApiEntry {
+    fn new(entry: Entry, tags: Vec<String>) -> Self {
+        Self {
+            id: entry.id,
+            feed_id: entry.feed_id,
+            title: entry.title,
+            url: entry.url,
+            author: entry.author,
+            published: entry.published,
+            summary: entry.summary,
+            read: entry.read,
+            starred: entry.starred,
+            tags,
+        }
+    }
+}
+
+/// A new API token: 32 random bytes, hex-encoded
+pub fn new_token() -> String {
+    rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// What is stored of a token: its SHA-256, hex-encoded
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// The user a request's bearer token belongs to; requests without a valid
+/// token are refused with 401
+struct ApiUser(User);
+
+impl FromRequestParts<Arc<ServerState>> for ApiUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<ServerState>,
+    ) -> Result<Self, Response> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(str::trim);
+        let Some(token) = token else {
+            return Err(unauthorized());
+        };
+        match state
+            .engine
+            .database()
+            .get_user_by_token(&hash_token(token))
+            .await
+        {
+            Ok(Some(user)) => Ok(ApiUser(user)),
+            Ok(None) => Err(unauthorized()),
+            Err(e) => Err(api_error(e)),
+        }
+    }
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        "Missing or unknown token\n",
+    )
+        .into_response()
+}
+
+/// Response for a failed database call
+fn api_error(e: DatabaseError) -> Response {
+    if e.is_not_found() {
+        return (StatusCode::NOT_FOUND, format!("{}\n", e)).into_response();
+    }
+    if e.is_invalid() {
+        return (StatusCode::BAD_REQUEST, format!("{}\n", e)).into_response();
+    }
+    tracing::error!("API request failed: {:#}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Request failed\n").into_response()
+}
+
+/// Routes for `options`
+fn router(engine: Arc<Engine>, options: ServerOptions) -> Router {
+    let mut router = Router::new()
+        .route("/health", get(health))
+        .route("/metrics", get(metrics));
+    if options.feeds_out {
+        router = router.route("/feed.atom", get(summaries_feed));
+    }
+    if options.api {
+        router = router
+            .route("/api/me", get(api_me))
+            .route("/api/feeds", get(api_feeds))
+            .route("/api/entries", get(api_entries))
+            .route("/api/entries/{id}", get(api_entry).patch(api_update_entry))
+            .route(
+                "/api/entries/{id}/tags/{tag}",
+                put(api_add_tag).delete(api_remove_tag),
+            );
+    }
+    if options.ingest {
+        router = router.route("/ingest", post(ingest));
+    }
+    router.with_state(Arc::new(ServerState { engine, options }))
+}
+
+/// Listen on `options.listen` until Ctrl-C
+pub async fn serve(engine: Engine, options: ServerOptions) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(options.listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", options.listen))?;
+    println!("Listening on http://{}", options.listen);
+    if options.feeds_out {
+        println!("Summaries feed: http://{}/feed.atom", options.listen);
+    }
+    if options.api {
+        println!("API: http://{}/api", options.listen);
+    }
+    if options.ingest {
+        println!("Ingest: POST http://{}/ingest", options.listen);
+    }
+    axum::serve(listener, router(Arc::new(engine), options))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        .context("HTTP server failed")
+}
+
+async fn health() -> &'static str {
+    "ok\n"
+}
+
+async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        METRICS.render_prometheus(),
+    )
+}
+
+async fn summaries_feed(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+) -> Response {
+    // Behind a proxy the Host header is the public name, which is what readers need
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(String::from)
+        .unwrap_or_else(|| state.options.listen.to_string());
+    let self_url = format!("http://{}/feed.atom", host);
+    let days = query.days.unwrap_or(state.options.days);
+
+    match state
+        .engine
+        .summaries_feed(days, query.tag.as_deref(), Some(&self_url))
+        .await
+    {
+        Ok(feed) => (
+            [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+            feed,
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to build summaries feed: {:#}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to build feed\n").into_response()
+        }
+    }
+}
+
+async fn api_me(ApiUser(user): ApiUser) -> Json<User> {
+    Json(user)
+}
+
+async fn api_feeds(
+    State(state): State<Arc<ServerState>>,
+    _: ApiUser,
+) -> Result<Json<Vec<Feed>>, Response> {
+    let feeds = state
+        .engine
+        .database()
+        .get_all_feeds()
+        .await
+        .map_err(api_error)?;
+    Ok(Json(feeds))
+}
+
+async fn api_entries(
+    State(state): State<Arc<ServerState>>,
+    ApiUser(user): ApiUser,
+    Query(query): Query<EntriesQuery>,
+) -> Result<Json<Vec<ApiEntry>>, Response> {
+    let db = state.engine.database();
+    let filter = UserEntryFilter {
+        unread: query.unread,
+        starred: query.starred,
+        feed_id: query.feed,
+        limit: query.limit.unwrap_or(100).clamp(1, MAX_API_ENTRIES),
+    };
+    let entries = db
+        .get_user_entries(user.id, &filter)
+        .await
+        .map_err(api_error)?;
+    let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+    let mut tags = db
+        .get_user_tags_for_entries(user.id, &ids)
+        .await
+        .map_err(api_error)?;
+    let entries = entries
+        .into_iter()
+        .map(|entry| {
+            let entry_tags = tags.remove(&entry.id).unwrap_or_default();
+            ApiEntry::new(entry, entry_tags)
+        })
+        .collect();
+    Ok(Json(entries))
+}
+
+async fn api_entry(
+    State(state): State<Arc<ServerState>>,
+    ApiUser(user): ApiUser,
+    Path(id): Path<String>,
+) -> Result<Json<ApiEntry>, Response> {
+    user_entry(&state, &user, &id).await.map(Json)
+}
+
+async fn api_update_entry(
+    State(state): State<Arc<ServerState>>,
+    ApiUser(user): ApiUser,
+    Path(id): Path<String>,
+    Json(update): Json<EntryUpdate>,
+) -> Result<Json<ApiEntry>, Response> {
+    let db = state.engine.database();
+    require_entry(db, &user, &id).await?;
+    db.set_user_state(user.id, &id, update.read, update.starred)
+        .await
+        .map_err(api_error)?;
+    user_entry(&state, &user, &id).await.map(Json)
+}
+
+async fn api_add_tag(
+    State(state): State<Arc<ServerState>>,
+    ApiUser(user): ApiUser,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<Json<ApiEntry>, Response> {
+    let db = state.engine.database();
+    require_entry(db, &user, &id).await?;
+    db.add_user_tag(user.id, &id, &tag)
+        .await
+        .map_err(api_error)?;
+    user_entry(&state, &user, &id).await.map(Json)
+}
+
+async fn api_remove_tag(
+    State(state): State<Arc<ServerState>>,
+    ApiUser(user): ApiUser,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<Json<ApiEntry>, Response> {
+    let db = state.engine.database();
+    require_entry(db, &user, &id).await?;
+    db.remove_user_tag(user.id, &id, &tag)
+        .await
+        .map_err(api_error)?;
+    user_entry(&state, &user, &id).await.map(Json)
+}
+
+async fn ingest(
+    State(state): State<Arc<ServerState>>,
+    ApiUser(user): ApiUser,
+    Json(item): Json<Ingested>,
+) -> Result<(StatusCode, Json<ApiEntry>), Response> {
+    if let Err(e) = item.check() {
+        return Err((StatusCode::BAD_REQUEST, format!("{:#}\n", e)).into_response());
+    }
+    match state.engine.ingest(&item).await {
+        Ok(entry) => {
+            tracing::info!("{} pushed {}", user.name, entry.url);
+            Ok((StatusCode::CREATED, Json(ApiEntry::new(entry, Vec::new()))))
+        }
+        Err(e) => match e.downcast::<DatabaseError>() {
+            Ok(e) => Err(api_error(e)),
+            Err(e) => {
+                tracing::warn!("Failed to ingest {}: {:#}", item.url, e);
+                Err((StatusCode::BAD_GATEWAY, format!("{:#}\n", e)).into_response())
+            }
+        },
+    }
+}
+
+/// 404 unless entry `id` exists, before writing state that refers to it
+async fn require_entry(db: &Database, user: &User, id: &str) -> Result<(), Response> {
+    db.get_user_entry(user.id, id)
+        .await
+        .map(drop)
+        .map_err(api_error)
+}
+
+/// An entry with `user`'s state and tags
+async fn user_entry(state: &ServerState, user: &User, id: &str) -> Result<ApiEntry, Response> {
+    let db = state.engine.database();
+    let entry = db.get_user_entry(user.id, id).await.map_err(api_error)?;
+    let ids = [entry.id.clone()];
+    let mut tags = db
+        .get_user_tags_for_entries(user.id, &ids)
+        .await
+        .map_err(api_error)?;
+    let entry_tags = tags.remove(&entry.id).unwrap_or_default();
+    Ok(ApiEntry::new(entry, entry_tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tests::test_config;
+    use axum::body::Body;
+    use axum::http::Request;
+    use presser_db::fixtures;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_feed_route() {
+        let engine = Engine::with_config(test_config()).await.unwrap();
+        fixtures::seed(engine.database()).await.unwrap();
+        let engine = Arc::new(engine);
+        let options = |feeds_out| ServerOptions {
+            listen: "127.0.0.1:8080".parse().unwrap(),
+            feeds_out,
+            days: 30,
+            api: false,
+            ingest: false,
+        };
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+
+        let response = router(engine.clone(), options(false))
+            .oneshot(get("/feed.atom"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = router(engine.clone(), options(false))
+            .oneshot(get("/metrics"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = router(engine, options(true))
+            .oneshot(get("/feed.atom?tag=rust"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("<summary>Async closures are now stable.</summary>"));
+        assert!(body.contains("<link rel=\"self\" href=\"http://127.0.0.1:8080/feed.atom\"/>"));
+        assert!(!body.contains("news-election"));
+    }
+
+    #[tokio::test]
+    async fn test_api_users() {
+        let engine = Engine::with_config(test_config()).await.unwrap();
+        fixtures::seed(engine.database()).await.unwrap();
+        let alice_token = new_token();
+        let bob_token = new_token();
+        let db = engine.database();
+        db.create_user("alice", &hash_token(&alice_token))
+            .await
+            .unwrap();
+        db.create_user("bob", &hash_token(&bob_token))
+            .await
+            .unwrap();
+        let app = router(
+            Arc::new(engine),
+            ServerOptions {
+                listen: "127.0.0.1:8080".parse().unwrap(),
+                feeds_out: false,
+                days: 30,
+                api: true,
+                ingest: true,
+            },
+        );
+        let send = |request: Request<Body>| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default(),
+                )
+            }
+        };
+        let request = |method: &str, uri: &str, token: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let ids = |value: &serde_json::Value| -> Vec<String> {
+            value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|e| e["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let (status, _) = send(request("GET", "/api/me", "wrong", "")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, me) = send(request("GET", "/api/me", &bob_token, "")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(me["name"], "bob");
+
+        let body = r#"{"read": true, "starred": true}"#;
+        let (status, entry) = send(request(
+            "PATCH",
+            "/api/entries/rust-async",
+            &alice_token,
+            body,
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            (entry["read"].clone(), entry["starred"].clone()),
+            (true.into(), true.into())
+        );
+        let (_, entry) = send(request(
+            "PUT",
+            "/api/entries/rust-async/tags/later",
+            &alice_token,
+            "",
+        ))
+        .await;
+        assert!(entry["tags"].as_array().unwrap().contains(&"later".into()));
+        let (status, _) = send(request("PATCH", "/api/entries/missing", &alice_token, body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(request(
+            "PUT",
+            "/api/entries/missing/tags/later",
+            &alice_token,
+            "",
+        ))
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(request(
+            "PUT",
+            "/api/entries/rust-async/tags/%20",
+            &alice_token,
+            "",
+        ))
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Alice's state is hers alone
+        let (_, alice) = send(request("GET", "/api/entries?unread=true", &alice_token, "")).await;
+        let (_, bob) = send(request("GET", "/api/entries?unread=true", &bob_token, "")).await;
+        assert!(!ids(&alice).contains(&"rust-async".to_string()));
+        assert!(ids(&bob).contains(&"rust-async".to_string()));
+        let (_, entry) = send(request("GET", "/api/entries/rust-async", &bob_token, "")).await;
+        assert_eq!(entry["starred"], false);
+        assert!(!entry["tags"].as_array().unwrap().contains(&"later".into()));
+        let (_, starred) = send(request(
+            "GET",
+            "/api/entries?starred=true",
+            &alice_token,
+            "",
+        ))
+        .await;
+        assert_eq!(ids(&starred), ["rust-async"]);
+
+        // Pushed articles land in the inbox, queued for summarization
+        let pushed = r#"{"url": "https://example.com/pushed", "title": "Pushed", "content": "<p>Pushed text</p>"}"#;
+        let (status, entry) = send(request("POST", "/ingest", &bob_token, pushed)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(entry["feed_id"], crate::ingest::INBOX_FEED_ID);
+        let (_, unread) = send(request("GET", "/api/entries?unread=true", &alice_token, "")).await;
+        assert!(ids(&unread).contains(&"https://example.com/pushed".to_string()));
+        let (status, _) = send(request("POST", "/ingest", "wrong", pushed)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let bad = r#"{"url": "file:///etc/passwd", "content": "x"}"#;
+        let (status, _) = send(request("POST", "/ingest", &bob_token, bad)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,12 +1,21 @@
 //! Scheduled tasks for presser
 
-use std::sync::Arc;
 use anyhow::Result;
 use async_trait::async_trait;
 use presser_scheduler::Task;
+use std::sync::Arc;
 
 use crate::Engine;
 
+/// Scheduler group of feed updates (`[scheduler.groups]`)
+pub const FETCH_GROUP: &str = "fetches";
+
+/// Scheduler group of tasks spending most of their time on AI requests
+pub const AI_GROUP: &str = "ai-heavy";
+
+/// Scheduler group of database maintenance
+pub const MAINTENANCE_GROUP: &str = "maintenance";
+
 /// Task that updates a single feed
 pub struct FeedUpdateTask {
     engine: Arc<Engine>,
@@ -23,10 +32,103 @@ impl FeedUpdateTask {
 #[async_trait]
 impl Task for FeedUpdateTask {
     async fn execute(&self) -> Result<()> {
-        self.engine.update_feed(&self.feed_id).await
+        let report = self.engine.update_feed(&self.feed_id).await?;
+        if report.offline {
+            tracing::info!("Offline, update of {} deferred", self.feed_id);
+        }
+        Ok(())
     }
 
     fn name(&self) -> &str {
         &self.feed_id
     }
+
+    fn needs_network(&self) -> bool {
+        true
+    }
+
+    fn group(&self) -> &str {
+        FETCH_GROUP
+    }
+}
+
+/// Task that prunes entries per the retention policy and old update log
+/// records, VACUUMs, then runs schema maintenance (hot indices, ANALYZE, WAL
+/// truncation)
+pub struct MaintenanceTask {
+    engine: Arc<Engine>,
+}
+
+impl MaintenanceTask {
+    /// Create a new maintenance task
+    pub fn new(engine: Arc<Engine>) -> Self {
+        Self { engine }
+    }
+}
+
+#[async_trait]
+impl Task for MaintenanceTask {
+    async fn execute(&self) -> Result<()> {
+        self.engine.prune().await?;
+        self.engine.prune_run_log().await?;
+        let db = self.engine.database();
+        db.vacuum().await?;
+        let report = db.maintenance().await?;
+        if !report.indices_created.is_empty() {
+            tracing::warn!(
+                "Recreated missing indices: {}",
+                report.indices_created.join(", ")
+            );
+        }
+        if report.wal_busy {
+            tracing::warn!("WAL checkpoint blocked by readers; WAL not truncated");
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "maintenance"
+    }
+
+    fn group(&self) -> &str {
+        MAINTENANCE_GROUP
+    }
+}
+
+/// Task that summarizes entries left in the summary queue (deferred by the
+/// daily token budget or interrupted)
+pub struct SummaryQueueTask {
+    engine: Arc<Engine>,
+}
+
+impl SummaryQueueTask {
+    /// Create a new summary queue task
+    pub fn new(engine: Arc<Engine>) -> Self {
+        Self { engine }
+    }
+}
+
+#[async_trait]
+impl Task for SummaryQueueTask {
+    async fn execute(&self) -> Result<()> {
+        if self.engine.database().summary_queue_len().await? == 0 {
+            return Ok(());
+        }
+        let report = self.engine.drain_summary_queue(None).await?;
+        tracing::info!(
+            "Summary queue: {} summarized, {} deferred, {} failed",
+            report.summarized,
+            report.deferred,
+            report.errors.len()
+        );
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "summary-queue"
+    }
+
+    fn group(&self) -> &str {
+        AI_GROUP
+    }
 }
@@ -0,0 +1,24 @@
+//! Text helpers shared by the CLI, the TUI, and the notifiers
+
+/// `text` cut to at most `max` characters, ending in an ellipsis when cut
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("abc", 2), "a…");
+        assert_eq!(truncate("äöü", 2), "ä…");
+        assert_eq!(truncate("abc", 0), "…");
+    }
+}
@@ -0,0 +1,327 @@
+//! Entry translation (a feed's `translate_to`): entries whose text is in
+//! another language are translated by the AI provider before they are
+//! summarized, and the translation is stored beside the original
+//!
+//! Languages are detected offline: by script for the languages with their
+//! own (Cyrillic, Greek, Arabic, CJK, ...), and by counting common function
+//! words for those written in Latin script. Text whose language can't be
+//! told is left alone rather than sent off on a guess.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Utc;
+use presser_ai::AiClient;
+use presser_db::{Database, Translation};
+
+use crate::ai_queue::{self, Priority};
+use crate::update::{AiBudget, UpdateReport};
+
+/// Characters of text looked at to detect its language
+const SAMPLE_CHARS: usize = 2000;
+
+/// Function words a Latin-script text needs before its language is trusted
+const MIN_FUNCTION_WORDS: usize = 3;
+
+/// Languages presser can detect, as ISO 639-1 codes with their English names
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("cs", "Czech"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("zh", "Chinese"),
+];
+
+/// Frequent short words of the Latin-script languages, chosen to overlap
+/// as little as possible between them
+const FUNCTION_WORDS: &[(&str, &[&str])] = &[
+    (
+        "cs",
+        &[
+            "je", "se", "na", "že", "jsou", "jako", "také", "ale", "který", "aby",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "und", "das", "ist", "nicht", "mit", "sich", "auch", "ein", "eine", "dem",
+        ],
+    ),
+    (
+        "en",
+        &[
+            "the", "and", "of", "to", "is", "that", "with", "for", "this", "are", "was", "it",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "que", "y", "del", "por", "una", "para", "con", "es", "se",
+        ],
+    ),
+    (
+        "fi",
+        &[
+            "ja", "on", "että", "ei", "se", "oli", "ovat", "mutta", "kun", "myös",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "les", "et", "des", "est", "une", "du", "que", "pour", "dans", "pas", "sur",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "che", "di", "della", "per", "non", "sono", "gli", "una", "anche", "nel", "è",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "niet", "zijn", "op", "voor", "met", "ook", "dat",
+        ],
+    ),
+    (
+        "pl",
+        &[
+            "i", "w", "nie", "się", "jest", "na", "że", "to", "do", "jak", "ale", "czy",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "que", "não", "uma", "para", "com", "do", "da", "em", "são", "mais",
+        ],
+    ),
+    (
+        "sv",
+        &[
+            "och", "att", "det", "som", "en", "är", "inte", "på", "för", "med", "har", "av",
+        ],
+    ),
+    (
+        "tr",
+        &[
+            "ve", "bir", "bu", "için", "ile", "da", "de", "çok", "daha", "olarak", "gibi", "ama",
+        ],
+    ),
+];
+
+/// English name of the language with ISO 639-1 `code`
+pub fn language_name(code: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
+/// ISO 639-1 code of the language `text` is written in, if it can be told
+pub fn detect(text: &str) -> Option<&'static str> {
+    let sample: String = text.chars().take(SAMPLE_CHARS).collect();
+    detect_script(&sample).or_else(|| detect_latin(&sample))
+}
+
+/// Language told by its own script, when most letters are in one
+fn detect_script(text: &str) -> Option<&'static str> {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    let in_range = |ranges: &[(u32, u32)]| {
+        letters
+            .iter()
+            .filter(|&&c| {
+                ranges
+                    .iter()
+                    .any(|&(lo, hi)| (lo..=hi).contains(&(c as u32)))
+            })
+            .count()
+    };
+    let kana = in_range(&[(0x3040, 0x30FF)]);
+    let scripts = [
+        ("ja", kana),
+        ("zh", in_range(&[(0x4E00, 0x9FFF), (0x3400, 0x4DBF)])),
+        ("ko", in_range(&[(0xAC00, 0xD7AF), (0x1100, 0x11FF)])),
+        ("ru", in_range(&[(0x0400, 0x04FF)])),
+        ("el", in_range(&[(0x0370, 0x03FF)])),
+        ("ar", in_range(&[(0x0600, 0x06FF)])),
+        ("he", in_range(&[(0x0590, 0x05FF)])),
+        ("hi", in_range(&[(0x0900, 0x097F)])),
+        ("th", in_range(&[(0x0E00, 0x0E7F)])),
+    ];
+    let (code, count) = scripts.into_iter().max_by_key(|(_, count)| *count)?;
+    if count * 2 < letters.len() || count == 0 {
+        return None;
+    }
+    Some(match code {
+        // Japanese mixes kanji with kana; Chinese has no kana
+        "zh" if kana > 0 => "ja",
+        "ru" if text.chars().any(|c| "іїєґІЇЄҐ".contains(c)) => "uk",
+        code => code,
+    })
+}
+
+/// Latin-script language with the most function words, if clearly ahead
+fn detect_latin(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut counts: Vec<(&'static str, usize)> = FUNCTION_WORDS
+        .iter()
+        .map(|(code, function_words)| {
+            let count = words.iter().filter(|w| function_words.contains(w)).count();
+            (*code, count)
+        })
+        .collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let (code, best) = counts[0];
+    let runner_up = counts.get(1).map_or(0, |(_, count)| *count);
+    (best >= MIN_FUNCTION_WORDS && best > runner_up).then_some(code)
+}
+
+/// Translate the text of entries (`(entry_id, text)`) into `language` where
+/// it is in another one, storing each translation, and return the entries
+/// with the text to summarize: the translation, or the original
+///
+/// Failed translations are reported as errors and leave the original text;
+/// once the daily token budget runs out the rest stay untranslated.
+pub(crate) async fn translate_entries(
+    ai: &Arc<AiClient>,
+    db: &Database,
+    budget: &AiBudget,
+    entries: Vec<(String, String)>,
+    language: &str,
+    report: &mut UpdateReport,
+) -> Result<Vec<(String, String)>> {
+    let target = language_name(language).unwrap_or(language);
+    let today = Utc::now().date_naive();
+    let mut spent = db.get_ai_usage(today).await?.tokens.max(0) as u64;
+    let ids: Vec<String> = entries.iter().map(|(id, _)| id.clone()).collect();
+    let priorities = ai_queue::entry_priorities(db, &ids).await?;
+    let mut texts = Vec::with_capacity(entries.len());
+    for (entry_id, text) in entries {
+        let Some(source) = detect(&text).filter(|source| *source != language) else {
+            texts.push((entry_id, text));
+            continue;
+        };
+        if budget.exhausted(spent) {
+            tracing::info!(
+                "Not translating {}: the daily token budget is spent",
+                entry_id
+            );
+            texts.push((entry_id, text));
+            continue;
+        }
+        let priority = priorities
+            .get(&entry_id)
+            .copied()
+            .unwrap_or_else(Priority::lowest);
+        let slot = budget.acquire(priority).await;
+        budget.pace().await;
+        let translated = ai.translate(&text, target).await;
+        drop(slot);
+        match translated {
+            Ok(translation) => {
+                let tokens = translation.tokens.map_or(0, u64::from);
+                if !translation.cached {
+                    db.record_ai_usage(today, tokens as i64).await?;
+                    report.tokens += tokens;
+                    spent += tokens;
+                }
+                db.set_translation(&Translation {
+                    entry_id: entry_id.clone(),
+                    language: language.to_string(),
+                    source_language: source.to_string(),
+                    content_text: translation.text.clone(),
+                    model: translation.model,
+                    tokens: translation.tokens.map(i64::from),
+                    created_at: Utc::now(),
+                })
+                .await?;
+                report.translated += 1;
+                texts.push((entry_id, translation.text));
+            }
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("Translation of {} failed: {:#}", entry_id, e));
+                texts.push((entry_id, text));
+            }
+        }
+    }
+    Ok(texts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let cases = [
+            (
+                "The committee said that the new line is expected to open in spring, \
+                 and that it was built for the city.",
+                Some("en"),
+            ),
+            (
+                "Die Stadt hat sich entschieden, die neue Linie nicht vor dem Frühling \
+                 zu eröffnen, und das ist auch gut so.",
+                Some("de"),
+            ),
+            (
+                "Le conseil a décidé que la nouvelle ligne ne sera pas ouverte avant le \
+                 printemps, et les travaux sont en cours dans la ville.",
+                Some("fr"),
+            ),
+            (
+                "El ayuntamiento dijo que la nueva línea se abrirá en primavera y que \
+                 los trabajos para la obra siguen en marcha.",
+                Some("es"),
+            ),
+            (
+                "Городской совет решил открыть новую линию метро весной.",
+                Some("ru"),
+            ),
+            (
+                "Міська рада вирішила відкрити нову лінію метро навесні.",
+                Some("uk"),
+            ),
+            ("市议会决定在春天开通新的地铁线路。", Some("zh")),
+            (
+                "市議会は春に新しい地下鉄路線を開通させることを決めました。",
+                Some("ja"),
+            ),
+            (
+                "시의회는 봄에 새 지하철 노선을 개통하기로 결정했다.",
+                Some("ko"),
+            ),
+            // Too little to go on
+            ("Rust 1.80", None),
+            ("", None),
+        ];
+        for (text, language) in cases {
+            assert_eq!(detect(text), language, "{}", text);
+        }
+        assert_eq!(language_name("de"), Some("German"));
+        assert_eq!(language_name("xx"), None);
+    }
+}
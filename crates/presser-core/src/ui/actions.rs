@@ -0,0 +1,308 @@
+//! Changes made to entries from the TUI
+//!
+//! The TUI shows a change before writing it and keeps the last one so it can
+//! be undone; a [`Change`] knows how to do both, and how to reverse itself.
+
+use anyhow::{Context, Result};
+use presser_db::{Database, Entry, TagSource};
+
+use super::app::{App, Page};
+use super::keys::Action;
+use crate::mute;
+
+/// One change to one entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Read(bool),
+    Starred(bool),
+    /// Hidden from every view ("not interested")
+    Hidden(bool),
+    /// A user tag added (or removed)
+    Tag {
+        name: String,
+        added: bool,
+    },
+}
+
+impl Change {
+    /// The change that reverts this one
+    pub fn inverse(&self) -> Change {
+        match self {
+            Change::Read(read) => Change::Read(!read),
+            Change::Starred(starred) => Change::Starred(!starred),
+            Change::Hidden(hidden) => Change::Hidden(!hidden),
+            Change::Tag { name, added } => Change::Tag {
+                name: name.clone(),
+                added: !added,
+            },
+        }
+    }
+
+    /// Apply to a displayed entry and its tags
+    pub fn apply(&self, entry: &mut Entry, tags: &mut Vec<String>) {
+        match self {
+            Change::Read(read) => entry.read = *read,
+            Change::Starred(starred) => entry.starred = *starred,
+            Change::Hidden(hidden) => entry.hidden = *hidden,
+            Change::Tag { name, added: true } => {
+                if !tags.contains(name) {
+                    tags.push(name.clone());
+                    tags.sort();
+                }
+            }
+            Change::Tag { name, added: false } => tags.retain(|t| t != name),
+        }
+    }
+
+    /// Write to the database
+    pub async fn persist(&self, db: &Database, entry_id: &str) -> Result<()> {
+        let result = match self {
+            Change::Read(true) => db.mark_read(entry_id).await,
+            Change::Read(false) => db.mark_unread(entry_id).await,
+            Change::Starred(true) => db.star_entry(entry_id).await,
+            Change::Starred(false) => db.unstar_entry(entry_id).await,
+            Change::Hidden(true) => db.hide_entry(entry_id).await,
+            Change::Hidden(false) => db.unhide_entry(entry_id).await,
+            Change::Tag { name, added: true } => db.add_tag(entry_id, name, TagSource::User).await,
+            Change::Tag { name, added: false } => db.remove_tag(entry_id, name).await,
+        };
+        Ok(result?)
+    }
+
+    /// What was done, for the status line
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Read(true) => "Marked read".into(),
+            Change::Read(false) => "Marked unread".into(),
+            Change::Starred(true) => "Starred".into(),
+            Change::Starred(false) => "Unstarred".into(),
+            Change::Hidden(true) => "Hidden".into(),
+            Change::Hidden(false) => "Unhidden".into(),
+            Change::Tag { name, added: true } => format!("Tagged '{}'", name),
+            Change::Tag { name, added: false } => format!("Removed tag '{}'", name),
+        }
+    }
+}
+
+/// The last change, with what is needed to put a hidden entry back
+#[derive(Debug, Clone)]
+pub struct Undo {
+    pub change: Change,
+    /// The entry as it was before the change
+    pub entry: Entry,
+    /// Its position in the entry list
+    pub index: Option<usize>,
+}
+
+impl App {
+    /// Actions on the selected entry (or the one being read)
+    pub(super) async fn handle_entry_action(&mut self, action: Action) {
+        if action == Action::Undo {
+            return self.undo().await;
+        }
+        let Some(entry) = self.target_entry() else {
+            return;
+        };
+        let entry_id = entry.id.clone();
+        let change = match action {
+            Action::ToggleRead => Change::Read(!entry.read),
+            Action::Star => Change::Starred(!entry.starred),
+            Action::Hide => Change::Hidden(true),
+            Action::Tag => return self.open_palette_with("tag "),
+            Action::Untag => return self.open_palette_with("untag "),
+            Action::Mute => {
+                let topics = mute::suggestions(entry);
+                self.open_palette_with("mute ");
+                self.mute_topics = topics;
+                return;
+            }
+            _ => return,
+        };
+        self.change_entry(&entry_id, change).await;
+    }
+
+    /// The entry being read, or the one selected in the list
+    pub(super) fn target_entry(&self) -> Option<&Entry> {
+        match self.page {
+            Page::Reader => self.current_entry.as_ref(),
+            Page::Entries | Page::Search => self
+                .entry_state
+                .selected()
+                .and_then(|i| self.entries.get(i)),
+            Page::Feeds | Page::Dashboard | Page::Downloads => None,
+        }
+    }
+
+    /// Make a change to an entry, remembering it for undo
+    pub(super) async fn change_entry(&mut self, entry_id: &str, change: Change) {
+        let Some(before) = self
+            .current_entry
+            .iter()
+            .chain(&self.entries)
+            .find(|e| e.id == entry_id)
+        else {
+            return;
+        };
+        let undo = Undo {
+            change,
+            entry: before.clone(),
+            index: self.entries.iter().position(|e| e.id == entry_id),
+        };
+        match self
+            .write_change(&undo.entry, &undo.change, undo.index)
+            .await
+        {
+            Ok(()) => {
+                let hint = self
+                    .keymap
+                    .key(Action::Undo)
+                    .map(|k| format!(" ({} to undo)", k))
+                    .unwrap_or_default();
+                self.status = Some(format!(" {}{}", undo.change.describe(), hint));
+                self.last_change = Some(undo);
+            }
+            Err(e) => self.status = Some(format!(" {:#}", e)),
+        }
+    }
+
+    /// Make a change to the entry being read or the selected one
+    pub(super) async fn change_target(&mut self, change: Change) -> Result<()> {
+        let entry_id = self
+            .target_entry()
+            .map(|e| e.id.clone())
+            .context("No entry selected")?;
+        self.change_entry(&entry_id, change).await;
+        Ok(())
+    }
+
+    /// Revert the last change
+    async fn undo(&mut self) {
+        let Some(undo) = self.last_change.take() else {
+            self.status = Some(" Nothing to undo".into());
+            return;
+        };
+        let inverse = undo.change.inverse();
+        match self.write_change(&undo.entry, &inverse, undo.index).await {
+            Ok(()) => self.status = Some(format!(" Undone: {}", undo.change.describe())),
+            Err(e) => {
+                self.status = Some(format!(" {:#}", e));
+                self.last_change = Some(undo);
+            }
+        }
+    }
+
+    /// Show a change right away, then write it, putting the screen back if
+    /// the write fails
+    async fn write_change(
+        &mut self,
+        entry: &Entry,
+        change: &Change,
+        index: Option<usize>,
+    ) -> Result<()> {
+        self.show_change(entry, change, index);
+        let result = change.persist(self.engine.database(), &entry.id).await;
+        if result.is_err() {
+            self.show_change(entry, &change.inverse(), index);
+        }
+        result
+    }
+
+    /// Apply a change to the entry list and reader. `entry` is the entry as it
+    /// was before, used to put a hidden entry back at `index`.
+    fn show_change(&mut self, entry: &Entry, change: &Change, index: Option<usize>) {
+        match change {
+            Change::Hidden(true) => {
+                self.entries.retain(|e| e.id != entry.id);
+                if self
+                    .current_entry
+                    .as_ref()
+                    .is_some_and(|e| e.id == entry.id)
+                {
+                    self.current_entry = None;
+                    self.page = self.list_page();
+                }
+                let len = self.entries.len();
+                self.entry_state.select(
+                    self.entry_state
+                        .selected()
+                        .map(|i| i.min(len.saturating_sub(1)))
+                        .filter(|_| len > 0),
+                );
+            }
+            Change::Hidden(false) => {
+                if let Some(index) =
+                    index.filter(|_| !self.entries.iter().any(|e| e.id == entry.id))
+                {
+                    let index = index.min(self.entries.len());
+                    self.entries.insert(index, entry.clone());
+                    self.entry_state.select(Some(index));
+                }
+            }
+            change => {
+                let tags = self.entry_tags.entry(entry.id.clone()).or_default();
+                for shown in self
+                    .entries
+                    .iter_mut()
+                    .chain(self.current_entry.as_mut())
+                    .filter(|e| e.id == entry.id)
+                {
+                    change.apply(shown, tags);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_db::Feed;
+
+    #[tokio::test]
+    async fn test_change_round_trip() {
+        let db = Database::open_in_memory().await.unwrap();
+        db.migrate().await.unwrap();
+        db.upsert_feed(&Feed {
+            id: "blog".into(),
+            url: "https://example.com/feed".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let mut entry = Entry {
+            id: "a".into(),
+            feed_id: "blog".into(),
+            ..Default::default()
+        };
+        db.upsert_entry(&entry).await.unwrap();
+        let mut tags = Vec::new();
+
+        for change in [
+            Change::Read(true),
+            Change::Starred(true),
+            Change::Tag {
+                name: "later".into(),
+                added: true,
+            },
+        ] {
+            change.apply(&mut entry, &mut tags);
+            change.persist(&db, "a").await.unwrap();
+        }
+        let stored = db.get_entry("a").await.unwrap().unwrap();
+        assert!(stored.read && stored.starred && entry.read && entry.starred);
+        assert_eq!(db.get_tags_for_entry("a").await.unwrap(), tags);
+
+        let untag = Change::Tag {
+            name: "later".into(),
+            added: true,
+        }
+        .inverse();
+        assert_eq!(untag.describe(), "Removed tag 'later'");
+        untag.apply(&mut entry, &mut tags);
+        untag.persist(&db, "a").await.unwrap();
+        Change::Hidden(true).persist(&db, "a").await.unwrap();
+        assert!(tags.is_empty());
+        assert!(db.get_tags_for_entry("a").await.unwrap().is_empty());
+        assert!(db.get_entry("a").await.unwrap().unwrap().hidden);
+    }
+}
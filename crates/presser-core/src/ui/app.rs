@@ -1,99 +1,297 @@
 //! TUI application
 
-use std::io;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use crossterm::event::{self, Event, KeyEvent, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    widgets::{List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+use presser_ai::SummaryStyle;
+use presser_db::{
+    Enclosure, Entry, EntryDiscussion, EntryRevision, Feed, FolderCount, MuteKind, SmartFolder,
+    TagCount, Translation,
 };
-use presser_db::{Entry, Feed};
-
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::actions::Undo;
+use super::dashboard::Dashboard;
+use super::downloads::Downloads;
+use super::feed_form::Dialog;
+use super::images::{ImageState, Picture, Protocol, ShownImage};
+use super::keys::{Action, Keymap};
+use super::palette::{self, Prompt, PromptKind};
+use super::reader::{LaidOut, PendingSummary, Position, ReaderConfig};
+use super::refresh::RefreshProgress;
+use super::related::Related;
+use super::search::{self, SearchResults};
+use super::sidebar::{self, Source, View};
+use super::theme::Theme;
+use crate::control::{self, ControlEndpoint, ControlRequest, ControlResponse};
+use crate::update::UpdateProgress;
 use crate::Engine;
 
+/// Rows moved by page up/down in lists
+const PAGE_ITEMS: usize = 10;
+
+/// How long a toast stays in the status bar
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// `ui_state` keys of the list and entry open at quit
+const SOURCE_STATE: &str = "source";
+const ENTRY_STATE: &str = "entry";
+
+/// Frames of the progress spinner, advanced every loop iteration
+pub(super) const SPINNER: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 /// Current page/view in the TUI
-#[derive(Clone, Copy, PartialEq)]
-enum Page {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Page {
     /// Feed list - shows all feeds with unread/total counts
     Feeds,
     /// Entry list - shows articles for selected feed
     Entries,
+    /// Search results - entries from any feed, with snippets
+    Search,
     /// Reader - shows article content
     Reader,
+    /// Dashboard - today's numbers, next runs, and a digest preview
+    Dashboard,
+    /// Downloads - podcast episodes downloading and downloaded
+    Downloads,
 }
 
-/// Reader display configuration
-struct ReaderConfig {
-    /// Left/right margin for body text
-    margin: u16,
-}
-
-impl Default for ReaderConfig {
-    fn default() -> Self {
-        Self { margin: 2 }
-    }
+/// A notice shown in the status bar for a few seconds
+struct Toast {
+    text: String,
+    error: bool,
+    pub(super) shown_at: Instant,
 }
 
 pub struct App {
-    engine: Arc<Engine>,
-    feeds: Vec<Feed>,
-    entries: Vec<Entry>,
-    feed_state: ListState,
-    entry_state: ListState,
-    page: Page,
-    should_quit: bool,
+    pub(super) engine: Arc<Engine>,
+    pub(super) feeds: Vec<Feed>,
+    pub(super) folders: Vec<FolderCount>,
+    pub(super) tags: Vec<TagCount>,
+    /// Saved searches, listed last
+    pub(super) smart_folders: Vec<SmartFolder>,
+    /// Rows of the feeds page, filtered by `feed_filter`
+    pub(super) sidebar: Vec<sidebar::Item>,
+    pub(super) unread_counts: HashMap<String, i64>,
+    pub(super) entries: Vec<Entry>,
+    /// What the entry list shows, and which of its entries
+    pub(super) source: Option<Source>,
+    pub(super) view: View,
+    /// Title of the entry list
+    pub(super) list_title: String,
+    pub(super) feed_state: ListState,
+    pub(super) entry_state: ListState,
+    pub(super) page: Page,
+    pub(super) should_quit: bool,
     // Reader state
-    current_entry: Option<Entry>,
-    current_feed_title: String,
-    scroll_offset: u16,
-    reader_config: ReaderConfig,
+    pub(super) current_entry: Option<Entry>,
+    pub(super) current_feed_title: String,
+    pub(super) scroll_offset: u16,
+    /// Scroll offset of the article's last screen, as last drawn
+    pub(super) reader_max_scroll: u16,
+    /// Reading position of the entry being read
+    pub(super) position: Option<Position>,
+    pub(super) reader_config: ReaderConfig,
+    /// The current entry's HTML content, rendered
+    pub(super) article: Option<LaidOut>,
+    /// Graphics protocol for inline images, if the terminal has one
+    pub(super) image_protocol: Option<Protocol>,
+    /// Images of the article being read, by URL
+    pub(super) images: HashMap<String, ImageState>,
+    pub(super) image_results: mpsc::UnboundedReceiver<(String, Result<Picture>)>,
+    pub(super) image_sender: mpsc::UnboundedSender<(String, Result<Picture>)>,
+    /// Images placed by the last frame, and those drawn on screen
+    pub(super) shown_images: Vec<ShownImage>,
+    pub(super) drawn_images: Vec<ShownImage>,
+    /// One-off message shown in place of the help bar until the next key
+    pub(super) status: Option<String>,
+    pub(super) keymap: Keymap,
+    pub(super) theme: Theme,
+    pub(super) prompt: Option<Prompt>,
+    /// Last confirmed find, for next/previous match
+    pub(super) search: Option<String>,
+    pub(super) results: Option<SearchResults>,
+    /// Palette filters on the feed and entry lists
+    pub(super) feed_filter: Option<String>,
+    pub(super) entry_filter: Option<String>,
+    pub(super) show_help: bool,
+    /// Tags of the listed entries
+    pub(super) entry_tags: HashMap<String, Vec<String>>,
+    /// Discussions of the listed entries
+    pub(super) entry_discussions: HashMap<String, EntryDiscussion>,
+    /// Latest revisions of the listed entries whose text changed
+    pub(super) entry_revisions: HashMap<String, EntryRevision>,
+    /// Other feeds that carried the listed entries, by entry
+    pub(super) entry_sources: HashMap<String, Vec<String>>,
+    /// Last entry change, for undo
+    pub(super) last_change: Option<Undo>,
+    /// AI summary of the entry being read
+    pub(super) summary: Option<String>,
+    /// Translation of the entry being read, if its feed has `translate_to`
+    pub(super) translation: Option<Translation>,
+    /// Whether the reader shows a translated entry's original text
+    pub(super) show_original: bool,
+    /// Entries related to the one being read (None: it has no embedding)
+    pub(super) related: Option<Vec<Related>>,
+    /// Whether the reader shows the related panel
+    pub(super) show_related: bool,
+    /// Topics suggested by the mute action, numbered for `:mute N`
+    pub(super) mute_topics: Vec<(MuteKind, String)>,
+    /// Style of the next on-demand summary (`None`: the configured prompt)
+    pub(super) summary_style: Option<SummaryStyle>,
+    pub(super) pending_summary: Option<PendingSummary>,
+    /// Loop iterations, for the spinner
+    pub(super) tick: usize,
+    /// Update events from background refreshes
+    pub(super) progress: mpsc::UnboundedReceiver<UpdateProgress>,
+    pub(super) progress_sender: mpsc::UnboundedSender<UpdateProgress>,
+    pub(super) refresh_tasks: Vec<JoinHandle<Result<()>>>,
+    /// Feeds being refreshed right now
+    pub(super) refreshing: HashSet<String>,
+    pub(super) refresh: RefreshProgress,
+    toasts: Vec<Toast>,
+    /// What the dashboard shows, loaded when it is opened
+    pub(super) dashboard: Option<Dashboard>,
+    /// Page the dashboard was opened from
+    pub(super) dashboard_origin: Page,
+    pub(super) dashboard_scroll: u16,
+    pub(super) downloads: Downloads,
+    /// Page the downloads view was opened from
+    pub(super) downloads_origin: Page,
+    /// Episode to play once the terminal can be handed to the player
+    pub(super) play_request: Option<Enclosure>,
+    pub(super) dialog: Option<Dialog>,
 }
 
 impl App {
     pub async fn new(engine: Arc<Engine>) -> Result<Self> {
-        let feeds = engine.database().get_all_feeds().await?;
-        let mut feed_state = ListState::default();
-        if !feeds.is_empty() {
-            feed_state.select(Some(0));
-        }
+        let keymap = Keymap::from_config(&engine.config().ui)?;
+        let theme = Theme::from_config(&engine.config().ui.theme)?;
+        let (progress_sender, progress) = mpsc::unbounded_channel();
+        let (image_sender, image_results) = mpsc::unbounded_channel();
+        let image_protocol = Protocol::from_mode(engine.config().ui.images);
 
-        Ok(Self {
+        let mut app = Self {
             engine,
-            feeds,
+            feeds: Vec::new(),
+            folders: Vec::new(),
+            tags: Vec::new(),
+            smart_folders: Vec::new(),
+            sidebar: Vec::new(),
+            unread_counts: HashMap::new(),
             entries: Vec::new(),
-            feed_state,
+            source: None,
+            view: View::default(),
+            list_title: String::new(),
+            feed_state: ListState::default(),
             entry_state: ListState::default(),
             page: Page::Feeds,
             should_quit: false,
             current_entry: None,
             current_feed_title: String::new(),
             scroll_offset: 0,
+            reader_max_scroll: 0,
+            position: None,
             reader_config: ReaderConfig::default(),
-        })
+            article: None,
+            image_protocol,
+            images: HashMap::new(),
+            image_results,
+            image_sender,
+            shown_images: Vec::new(),
+            drawn_images: Vec::new(),
+            status: None,
+            keymap,
+            theme,
+            prompt: None,
+            search: None,
+            results: None,
+            feed_filter: None,
+            entry_filter: None,
+            show_help: false,
+            entry_tags: HashMap::new(),
+            entry_discussions: HashMap::new(),
+            entry_revisions: HashMap::new(),
+            entry_sources: HashMap::new(),
+            last_change: None,
+            summary: None,
+            translation: None,
+            show_original: false,
+            summary_style: None,
+            pending_summary: None,
+            tick: 0,
+            progress,
+            progress_sender,
+            refresh_tasks: Vec::new(),
+            refreshing: HashSet::new(),
+            refresh: RefreshProgress::default(),
+            toasts: Vec::new(),
+            dashboard: None,
+            dashboard_origin: Page::Feeds,
+            dashboard_scroll: 0,
+            downloads: Downloads::new(),
+            downloads_origin: Page::Feeds,
+            play_request: None,
+            dialog: None,
+            related: None,
+            show_related: true,
+            mute_topics: Vec::new(),
+        };
+        app.load_feeds().await?;
+        app.restore_state().await?;
+        Ok(app)
     }
 
-    async fn load_entries(&mut self) -> Result<()> {
-        if let Some(idx) = self.feed_state.selected() {
-            if let Some(feed) = self.feeds.get(idx) {
-                self.entries = self.engine.database()
-                    .get_entries_for_feed(&feed.id, 100).await?;
-                self.entry_state = ListState::default();
-                if !self.entries.is_empty() {
-                    self.entry_state.select(Some(0));
-                }
-            }
-        }
+    /// Reload feeds, folders, tags, and smart folders with their counts, and
+    /// rebuild the sidebar
+    pub(super) async fn load_feeds(&mut self) -> Result<()> {
+        let db = self.engine.database();
+        self.feeds = db.get_all_feeds().await?;
+        self.folders = db.get_folder_counts().await?;
+        self.tags = db.get_all_tags_with_counts().await?;
+        self.smart_folders = db.get_smart_folders().await?;
+        self.unread_counts = db.get_unread_counts().await?;
+        self.sidebar = sidebar::build(
+            &self.feeds,
+            &self.folders,
+            &self.tags,
+            &self.smart_folders,
+            self.feed_filter.as_deref(),
+        );
+        let len = self.sidebar.len();
+        self.feed_state.select(
+            self.feed_state
+                .selected()
+                .map_or(Some(0), |i| Some(i.min(len.saturating_sub(1))))
+                .filter(|_| len > 0),
+        );
         Ok(())
     }
 
+    /// Title of a feed, or its ID if it is gone
+    pub(super) fn feed_title<'a>(&'a self, feed_id: &'a str) -> &'a str {
+        self.feeds
+            .iter()
+            .find(|f| f.id == feed_id)
+            .map_or(feed_id, |f| f.title.as_str())
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
@@ -102,607 +300,384 @@ impl App {
         let mut terminal = Terminal::new(backend)?;
 
         while !self.should_quit {
+            self.tick = self.tick.wrapping_add(1);
+            self.toasts
+                .retain(|t| t.shown_at.elapsed() < TOAST_DURATION);
+            self.poll_summary().await;
+            self.poll_refresh().await?;
+            self.poll_images();
+            self.poll_downloads().await;
+            self.poll_dialog().await;
+            self.track_position().await;
             terminal.draw(|f| self.render(f))?;
+            if let Some(position) = self.position.as_mut().filter(|_| self.page == Page::Reader) {
+                position.scroll = self.scroll_offset;
+                position.max_scroll = self.reader_max_scroll;
+            }
+            if self.shown_images != self.drawn_images {
+                self.draw_images(&mut terminal)?;
+            }
 
             if event::poll(std::time::Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
-                        self.handle_key(key.code).await?;
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        self.handle_key(key).await?;
                     }
+                    // The screen is cleared, images and all
+                    Event::Resize(..) => self.drawn_images.clear(),
+                    _ => {}
                 }
             }
+            if let Some(enclosure) = self.play_request.take() {
+                self.play(&mut terminal, enclosure).await?;
+            }
         }
 
+        if let Some(clear) = self.image_protocol.and_then(Protocol::clear) {
+            write!(terminal.backend_mut(), "{}", clear)?;
+        }
         disable_raw_mode()?;
         execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        self.save_position().await;
+        self.save_state().await
+    }
+
+    /// Remember the open list and entry (or the selected sidebar row) for
+    /// the next start
+    async fn save_state(&self) -> Result<()> {
+        let page = match self.page {
+            Page::Dashboard => self.dashboard_origin,
+            Page::Downloads => self.downloads_origin,
+            page => page,
+        };
+        let open = matches!(page, Page::Entries | Page::Reader) && self.results.is_none();
+        let (source, entry) = if open {
+            let entry = match page {
+                Page::Reader => self.current_entry.as_ref(),
+                _ => self
+                    .entry_state
+                    .selected()
+                    .and_then(|i| self.entries.get(i)),
+            };
+            (self.source.clone(), entry.map(|e| e.id.as_str()))
+        } else {
+            (
+                self.selected_item().and_then(|item| self.item_source(item)),
+                None,
+            )
+        };
+        let db = self.engine.database();
+        db.set_ui_state(SOURCE_STATE, source.map(|s| s.key()).as_deref())
+            .await?;
+        db.set_ui_state(ENTRY_STATE, entry).await?;
         Ok(())
     }
 
-    fn render(&mut self, frame: &mut Frame) {
+    /// Select the list and entry that were open when the TUI last quit
+    async fn restore_state(&mut self) -> Result<()> {
+        let db = self.engine.database();
+        let Some(source) = db
+            .get_ui_state(SOURCE_STATE)
+            .await?
+            .as_deref()
+            .and_then(Source::from_key)
+        else {
+            return Ok(());
+        };
+        let Some(row) = self
+            .sidebar
+            .iter()
+            .position(|item| self.item_source(*item).as_ref() == Some(&source))
+        else {
+            return Ok(());
+        };
+        self.feed_state.select(Some(row));
+        let Some(entry_id) = db.get_ui_state(ENTRY_STATE).await? else {
+            return Ok(());
+        };
+        self.open_selected_feed().await?;
+        if let Some(i) = self.entries.iter().position(|e| e.id == entry_id) {
+            self.entry_state.select(Some(i));
+        }
+        Ok(())
+    }
+
+    pub(super) fn render(&mut self, frame: &mut Frame) {
+        self.shown_images.clear();
+        frame.render_widget(Block::default().style(self.theme.base()), frame.size());
         match self.page {
             Page::Feeds => self.render_feeds_page(frame),
             Page::Entries => self.render_entries_page(frame),
+            Page::Search => self.render_search_page(frame),
             Page::Reader => self.render_reader(frame),
+            Page::Dashboard => self.render_dashboard(frame),
+            Page::Downloads => self.render_downloads(frame),
+        }
+        if self.dialog.is_some() {
+            self.render_dialog(frame);
+        }
+        if let Some(prompt) = self
+            .prompt
+            .as_ref()
+            .filter(|p| p.kind == PromptKind::Palette)
+        {
+            self.render_palette(frame, prompt);
+        }
+        if self.show_help {
+            self.render_help(frame);
         }
     }
 
-    fn render_feeds_page(&mut self, frame: &mut Frame) {
-        use ratatui::text::{Line, Span};
-
-        let area = frame.size();
-
-        // Layout: title + list + help bar
-        let chunks = Layout::new(
-            Direction::Vertical,
-            [
-                Constraint::Length(1),
-                Constraint::Min(0),
-                Constraint::Length(1),
-            ],
-        ).split(area);
-
-        // Title bar
-        let title = Paragraph::new(Line::from(vec![
-            Span::styled(" Presser ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::styled(format!("({} feeds)", self.feeds.len()), Style::default().fg(Color::DarkGray)),
-        ]));
-        frame.render_widget(title, chunks[0]);
-
-        // Feed list with unread/total counts
-        let feed_items: Vec<ListItem> = self.feeds.iter()
-            .map(|f| {
-                // TODO: Track unread count properly - for now show entry_count
-                let count_str = format!("({})", f.entry_count);
-                let line = Line::from(vec![
-                    Span::styled(
-                        if f.enabled { " " } else { "×" },
-                        if f.enabled { Style::default() } else { Style::default().fg(Color::DarkGray) },
-                    ),
-                    Span::styled(&f.title, Style::default().fg(Color::White)),
-                    Span::raw(" "),
-                    Span::styled(count_str, Style::default().fg(Color::DarkGray)),
-                ]);
-                ListItem::new(line)
-            })
-            .collect();
-
-        let feed_list = List::new(feed_items)
-            .highlight_style(Style::default().bg(Color::Rgb(40, 40, 40)).add_modifier(Modifier::BOLD))
-            .highlight_symbol("▶ ");
-
-        frame.render_stateful_widget(feed_list, chunks[1], &mut self.feed_state);
-
-        // Help bar
-        let help = Paragraph::new(Line::from(vec![
-            Span::styled(" Enter", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" open ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" r", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" refresh ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" q", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" quit ", Style::default().fg(Color::Black)),
-        ])).style(Style::default().bg(Color::Rgb(80, 80, 80)));
-
-        frame.render_widget(help, chunks[2]);
-    }
-
-    fn render_entries_page(&mut self, frame: &mut Frame) {
-        use ratatui::text::{Line, Span};
-
-        let area = frame.size();
-
-        // Layout: title + list + help bar
-        let chunks = Layout::new(
-            Direction::Vertical,
-            [
-                Constraint::Length(1),
-                Constraint::Min(0),
-                Constraint::Length(1),
-            ],
-        ).split(area);
-
-        // Title bar showing current feed
-        let title = Paragraph::new(Line::from(vec![
-            Span::styled(" ◀ ", Style::default().fg(Color::DarkGray)),
-            Span::styled(&self.current_feed_title, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::styled(format!(" ({} articles)", self.entries.len()), Style::default().fg(Color::DarkGray)),
-        ]));
-        frame.render_widget(title, chunks[0]);
-
-        // Entry list
-        let entry_items: Vec<ListItem> = self.entries.iter()
-            .map(|e| {
-                let read_marker = if e.read { " " } else { "●" };
-                let date_str = e.published
-                    .map(|d| d.format("%m/%d").to_string())
-                    .unwrap_or_default();
-                let line = Line::from(vec![
-                    Span::styled(
-                        read_marker,
-                        if e.read { Style::default().fg(Color::DarkGray) } else { Style::default().fg(Color::Green) },
-                    ),
-                    Span::raw(" "),
-                    Span::styled(date_str, Style::default().fg(Color::DarkGray)),
-                    Span::raw(" "),
-                    Span::styled(
-                        &e.title,
-                        if e.read { Style::default().fg(Color::DarkGray) } else { Style::default().fg(Color::White) },
-                    ),
-                ]);
-                ListItem::new(line)
-            })
-            .collect();
-
-        let entry_list = List::new(entry_items)
-            .highlight_style(Style::default().bg(Color::Rgb(40, 40, 40)).add_modifier(Modifier::BOLD))
-            .highlight_symbol("▶ ");
-
-        frame.render_stateful_widget(entry_list, chunks[1], &mut self.entry_state);
-
-        // Help bar
-        let help = Paragraph::new(Line::from(vec![
-            Span::styled(" Enter", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" read ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" r", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" refresh ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" Esc", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" back ", Style::default().fg(Color::Black)),
-        ])).style(Style::default().bg(Color::Rgb(80, 80, 80)));
-
-        frame.render_widget(help, chunks[2]);
+    /// Help bar showing the keys currently bound to `items`
+    pub(super) fn help_bar(&self, items: &[(Action, &str)]) -> Paragraph<'static> {
+        let mut spans = Vec::new();
+        if self.engine.is_offline() {
+            spans.push(Span::styled(" offline ", self.theme.error()));
+        }
+        for (action, label) in items {
+            let Some(key) = self.keymap.key(*action) else {
+                continue;
+            };
+            if !spans.is_empty() {
+                spans.push(Span::styled("│", Style::default().fg(self.theme.dim)));
+            }
+            spans.push(Span::styled(
+                format!(" {}", key),
+                Style::default()
+                    .fg(self.theme.bar_fg)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                format!(" {} ", label),
+                Style::default().fg(self.theme.bar_fg),
+            ));
+        }
+        Paragraph::new(Line::from(spans)).style(self.theme.bar())
     }
 
-    fn render_reader(&self, frame: &mut Frame) {
-        use ratatui::text::{Line, Span};
-
-        let Some(entry) = &self.current_entry else {
-            return;
-        };
-
-        let area = frame.size();
-        let margin = self.reader_config.margin;
-
-        let help_height = 1u16;
-
-        // Layout: scrollable content + help bar at bottom
-        let chunks = Layout::new(
-            Direction::Vertical,
-            [
-                Constraint::Min(0),
-                Constraint::Length(help_height),
-            ],
-        ).split(area);
-
-        // Content area with margins
-        let content_area = if margin > 0 && area.width > margin * 2 {
-            let inner = Layout::new(
-                Direction::Horizontal,
-                [
-                    Constraint::Length(margin),
-                    Constraint::Min(0),
-                    Constraint::Length(margin),
-                ],
-            ).split(chunks[0]);
-            inner[1]
-        } else {
-            chunks[0]
-        };
-
-        let available_width = content_area.width as usize;
-        const LABEL_WIDTH: usize = 8;
-        let value_width = if available_width > LABEL_WIDTH {
-            available_width - LABEL_WIDTH
-        } else {
-            available_width.max(20)
-        };
-
-        let meta_label_style = Style::default().fg(Color::Cyan);
-        let meta_value_style = Style::default().fg(Color::Yellow);
-        let indent = " ".repeat(LABEL_WIDTH);
-
-        let date_str = entry.published
-            .map(|d| d.format("%a, %d %b %Y %H:%M:%S %z").to_string())
-            .unwrap_or_else(|| "Unknown".to_string());
-
-        let author_str = entry.author.as_deref().unwrap_or("Unknown");
-
-        let mut all_lines = Vec::with_capacity(10);
-
-        for (i, line) in textwrap::wrap(&self.current_feed_title, value_width).into_iter().enumerate() {
-            all_lines.push(if i == 0 {
-                Line::from(vec![
-                    Span::styled("Feed:   ", meta_label_style),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
-            } else {
-                Line::from(vec![
-                    Span::raw(indent.as_str()),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
-            });
+    /// The prompt being typed, else the pending status message, else `help`
+    pub(super) fn bottom_bar(&self, help: Paragraph<'static>) -> Paragraph<'static> {
+        if let Some(prompt) = &self.prompt {
+            let sigil = match prompt.kind {
+                PromptKind::Find => "Find: ",
+                PromptKind::Search => "/",
+                PromptKind::Palette => ":",
+            };
+            return Paragraph::new(format!("{}{}█", sigil, prompt.text));
         }
-
-        for (i, line) in textwrap::wrap(&entry.title, value_width).into_iter().enumerate() {
-            all_lines.push(if i == 0 {
-                Line::from(vec![
-                    Span::styled("Title:  ", meta_label_style),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
-            } else {
-                Line::from(vec![
-                    Span::raw(indent.as_str()),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
-            });
+        if let Some(status) = self.status_bar() {
+            return status;
         }
-
-        for (i, line) in textwrap::wrap(author_str, value_width).into_iter().enumerate() {
-            all_lines.push(if i == 0 {
-                Line::from(vec![
-                    Span::styled("Author: ", meta_label_style),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
+        if let Some(toast) = self.toasts.last() {
+            let style = if toast.error {
+                self.theme.error()
             } else {
-                Line::from(vec![
-                    Span::raw(indent.as_str()),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
-            });
+                self.theme.bar()
+            };
+            return Paragraph::new(format!(" {}", toast.text)).style(style);
         }
-
-        for (i, line) in textwrap::wrap(&date_str, value_width).into_iter().enumerate() {
-            all_lines.push(if i == 0 {
-                Line::from(vec![
-                    Span::styled("Date:   ", meta_label_style),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
-            } else {
-                Line::from(vec![
-                    Span::raw(indent.as_str()),
-                    Span::styled(line.into_owned(), meta_value_style),
-                ])
-            });
+        if self.refresh.total > 0 {
+            let mut feeds: Vec<&str> = self
+                .feeds
+                .iter()
+                .filter(|f| self.refreshing.contains(&f.id))
+                .map(|f| f.title.as_str())
+                .collect();
+            feeds.truncate(3);
+            return Paragraph::new(format!(
+                " {} Refreshing {}/{} feeds: {}",
+                SPINNER[self.tick % SPINNER.len()],
+                self.refresh.done,
+                self.refresh.total,
+                feeds.join(", ")
+            ))
+            .style(self.theme.bar());
         }
+        help
+    }
 
-        for (i, line) in textwrap::wrap(&entry.url, value_width).into_iter().enumerate() {
-            all_lines.push(if i == 0 {
+    /// Key bindings of the current page and the palette commands
+    fn render_help(&self, frame: &mut Frame) {
+        let key_style = Style::default()
+            .fg(self.theme.accent)
+            .add_modifier(Modifier::BOLD);
+        let mut lines: Vec<Line> = self
+            .keymap
+            .help(self.page)
+            .into_iter()
+            .map(|(keys, description)| {
                 Line::from(vec![
-                    Span::styled("Link:   ", meta_label_style),
-                    Span::styled(line.into_owned(), Style::default().fg(Color::Blue)),
+                    Span::styled(format!(" {:<22}", keys), key_style),
+                    Span::raw(description),
                 ])
-            } else {
-                Line::from(vec![
-                    Span::raw(indent.as_str()),
-                    Span::styled(line.into_owned(), Style::default().fg(Color::Blue)),
-                ])
-            });
-        }
-
-        all_lines.push(Line::from(""));
-
-        // Content - prefer content_text, fall back to summary
-        let content = entry.content_text.as_deref()
-            .or(entry.summary.as_deref())
-            .unwrap_or("[No content available]");
-
-        let styled_content = self.style_content(content);
-        all_lines.extend(styled_content.lines);
-
-        let paragraph = Paragraph::new(all_lines)
-            .wrap(Wrap { trim: false })
-            .scroll((self.scroll_offset, 0));
-
-        frame.render_widget(paragraph, content_area);
-
-        // Help bar at bottom with colored background
-        let help = Paragraph::new(Line::from(vec![
-            Span::styled(" Esc", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" back ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" j/k", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" scroll ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" n", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" next ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" r", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" read ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" m", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" random ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" o", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" open ", Style::default().fg(Color::Black)),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
-            Span::styled(" u", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD)),
-            Span::styled(" toggle ", Style::default().fg(Color::Black)),
-        ])).style(Style::default().bg(Color::Rgb(80, 80, 80)));
-
-        frame.render_widget(help, chunks[1]);
-    }
-
-    /// Style plain text content for better readability
-    fn style_content<'a>(&self, content: &'a str) -> ratatui::text::Text<'a> {
-        use ratatui::text::{Line, Span};
-
-        let body_style = Style::default().fg(Color::White);
-
-        let lines: Vec<Line> = content
-            .lines()
-            .map(|line| {
-                let trimmed = line.trim();
-                if trimmed.is_empty() {
-                    Line::from("")
-                } else if trimmed.starts_with('#') {
-                    // Headers
-                    Line::from(Span::styled(
-                        line,
-                        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
-                    ))
-                } else if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("• ") {
-                    // Bullet points
-                    Line::from(Span::styled(line, body_style))
-                } else if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
-                    // URLs
-                    Line::from(Span::styled(line, Style::default().fg(Color::Blue)))
-                } else {
-                    // Normal body text
-                    Line::from(Span::styled(line, body_style))
-                }
             })
             .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            " Commands (:)",
+            Style::default().fg(self.theme.highlight),
+        )));
+        for (name, args, description) in palette::COMMANDS {
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {:<22}", format!("{} {}", name, args)), key_style),
+                Span::raw(*description),
+            ]));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            " Search filters (/)",
+            Style::default().fg(self.theme.highlight),
+        )));
+        for (syntax, description) in search::FILTERS {
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {:<22}", syntax), key_style),
+                Span::raw(*description),
+            ]));
+        }
 
-        ratatui::text::Text::from(lines)
+        let area = centered(frame.size(), 72, lines.len() as u16 + 2);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).style(self.theme.base()).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme.border))
+                    .title(" Keys (any key closes) "),
+            ),
+            area,
+        );
     }
 
-    async fn handle_key(&mut self, key: KeyCode) -> Result<()> {
-        match self.page {
-            Page::Feeds => self.handle_feeds_key(key).await?,
-            Page::Entries => self.handle_entries_key(key).await?,
-            Page::Reader => self.handle_reader_key(key).await?,
-        }
-        Ok(())
+    /// The pending status message, styled like the help bar
+    fn status_bar(&self) -> Option<Paragraph<'static>> {
+        self.status
+            .as_ref()
+            .map(|status| Paragraph::new(status.clone()).style(self.theme.bar()))
     }
 
-    async fn handle_feeds_key(&mut self, key: KeyCode) -> Result<()> {
-        match key {
-            KeyCode::Char('q') => self.should_quit = true,
-            KeyCode::Up | KeyCode::Char('k') => {
-                let len = self.feeds.len();
-                if len > 0 {
-                    let i = self.feed_state.selected().unwrap_or(0);
-                    let new_i = if i == 0 { len - 1 } else { i - 1 };
-                    self.feed_state.select(Some(new_i));
-                }
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                let len = self.feeds.len();
-                if len > 0 {
-                    let i = self.feed_state.selected().unwrap_or(0);
-                    let new_i = if i >= len - 1 { 0 } else { i + 1 };
-                    self.feed_state.select(Some(new_i));
-                }
-            }
-            KeyCode::Enter => {
-                // Open feed -> go to entries page
-                if let Some(idx) = self.feed_state.selected() {
-                    if let Some(feed) = self.feeds.get(idx) {
-                        self.current_feed_title = feed.title.clone();
-                        self.load_entries().await?;
-                        self.page = Page::Entries;
-                    }
-                }
-            }
-            KeyCode::Char('r') => self.refresh_current_feed().await?,
-            _ => {}
+    async fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        self.status = None;
+        if let Some(pending) = self.pending_summary.take() {
+            // Any key cancels a summary in progress
+            pending.task.abort();
+            self.engine
+                .database()
+                .dequeue_summaries(&[pending.entry_id])
+                .await?;
+            self.status = Some(" Summary cancelled".into());
+            return Ok(());
         }
-        Ok(())
-    }
-
-    async fn handle_entries_key(&mut self, key: KeyCode) -> Result<()> {
-        match key {
-            KeyCode::Esc | KeyCode::Backspace => {
-                // Go back to feeds
-                self.page = Page::Feeds;
-                self.entries.clear();
-            }
-            KeyCode::Char('q') => {
-                // Go back to feeds (not quit)
-                self.page = Page::Feeds;
-                self.entries.clear();
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                let len = self.entries.len();
-                if len > 0 {
-                    let i = self.entry_state.selected().unwrap_or(0);
-                    let new_i = if i == 0 { len - 1 } else { i - 1 };
-                    self.entry_state.select(Some(new_i));
-                }
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                let len = self.entries.len();
-                if len > 0 {
-                    let i = self.entry_state.selected().unwrap_or(0);
-                    let new_i = if i >= len - 1 { 0 } else { i + 1 };
-                    self.entry_state.select(Some(new_i));
-                }
-            }
-            KeyCode::Enter => {
-                if let Some(idx) = self.entry_state.selected() {
-                    if let Some(entry) = self.entries.get(idx) {
-                        let entry_id = entry.id.clone();
-                        let needs_mark = !entry.read;
-                        if needs_mark {
-                            self.mark_entry_as_read(&entry_id).await?;
-                        }
-                        self.current_entry = Some(self.entries[idx].clone());
-                        self.scroll_offset = 0;
-                        self.page = Page::Reader;
-                    }
-                }
-            }
-            KeyCode::Char('r') => self.refresh_current_feed().await?,
-            _ => {}
+        if self.show_help {
+            // Any key closes the help overlay
+            self.show_help = false;
+            return Ok(());
         }
-        Ok(())
-    }
-
-    async fn handle_reader_key(&mut self, key: KeyCode) -> Result<()> {
-        match key {
-            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => {
-                // Go back to entries
-                self.page = Page::Entries;
-                self.current_entry = None;
-                self.scroll_offset = 0;
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.scroll_offset = self.scroll_offset.saturating_sub(1);
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.scroll_offset = self.scroll_offset.saturating_add(1);
-            }
-            KeyCode::PageUp => {
-                self.scroll_offset = self.scroll_offset.saturating_sub(20);
-            }
-            KeyCode::PageDown => {
-                self.scroll_offset = self.scroll_offset.saturating_add(20);
-            }
-            KeyCode::Char('g') => {
-                self.scroll_offset = 0;
-            }
-            KeyCode::Char('G') => {
-                self.scroll_offset = u16::MAX;
-            }
-            KeyCode::Char('o') => {
-                if let Some(entry) = &self.current_entry {
-                    let _ = open::that(&entry.url);
-                }
-            }
-            KeyCode::Char('u') => {
-                if let Some(entry) = &self.current_entry {
-                    let entry_id = entry.id.clone();
-                    let was_read = entry.read;
-                    if was_read {
-                        self.engine.database().mark_unread(&entry_id).await?;
-                    } else {
-                        self.engine.database().mark_read(&entry_id).await?;
-                    }
-                    if let Some(e) = self.current_entry.as_mut() {
-                        e.read = !was_read;
-                    }
-                    if let Some(list_entry) = self.entries.iter_mut().find(|e| e.id == entry_id) {
-                        list_entry.read = !was_read;
-                    }
-                }
-            }
-            KeyCode::Char('r') => {
-                if let Some(entry) = &self.current_entry {
-                    let entry_id = entry.id.clone();
-                    if !entry.read {
-                        self.mark_entry_as_read(&entry_id).await?;
-                    }
+        if self.prompt.is_some() {
+            return self.handle_prompt_key(key).await;
+        }
+        if self.dialog.is_some() {
+            return self.handle_dialog_key(key).await;
+        }
+        let Some(action) = self.keymap.action(self.page, key) else {
+            return Ok(());
+        };
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::Help => self.show_help = true,
+            Action::Palette => self.open_prompt(PromptKind::Palette),
+            Action::Find => self.open_prompt(PromptKind::Find),
+            Action::View => self.cycle_view().await?,
+            Action::Dashboard => self.open_dashboard().await,
+            Action::Downloads => self.open_downloads().await,
+            Action::Play => self.play_episode().await,
+            Action::Download => self.download_episode().await,
+            Action::ContinueReading => self.continue_reading().await?,
+            Action::Search => {
+                let query = self
+                    .results
+                    .as_ref()
+                    .filter(|_| self.page == Page::Search)
+                    .and_then(|r| r.query.clone());
+                self.open_prompt(PromptKind::Search);
+                if let (Some(prompt), Some(query)) = (self.prompt.as_mut(), query) {
+                    prompt.text = query;
                 }
             }
-            KeyCode::Char('n') => {
-                self.load_next_unread_in_feed().await?;
-            }
-            KeyCode::Char('m') => {
-                self.load_random_unread().await?;
-            }
-            _ => {}
+            _ => match self.page {
+                Page::Feeds => self.handle_feeds_action(action).await?,
+                Page::Entries | Page::Search => self.handle_entries_action(action).await?,
+                Page::Reader => self.handle_reader_action(action).await?,
+                Page::Dashboard => self.handle_dashboard_action(action),
+                Page::Downloads => self.handle_downloads_action(action),
+            },
         }
         Ok(())
     }
 
-    async fn load_next_unread_in_feed(&mut self) -> Result<()> {
-        if let Some(current) = &self.current_entry {
-            let current_id = current.id.clone();
-            let mut found_current = false;
-            let mut next_id: Option<String> = None;
-
-            for entry in &self.entries {
-                if found_current && !entry.read {
-                    next_id = Some(entry.id.clone());
-                    break;
+    /// Load the config again after changing it, have a running daemon do the
+    /// same, and reload the sidebar
+    pub(super) async fn reload_config(&mut self) -> Result<()> {
+        self.engine = Arc::new(Engine::new().await?);
+        let endpoint = ControlEndpoint::from_config(&self.engine.config().daemon);
+        if control::is_running(&endpoint).await {
+            match control::send(&endpoint, &ControlRequest::ReloadConfig).await {
+                Ok(ControlResponse::Error { message }) => {
+                    self.toast(format!("The daemon failed to reload: {}", message), true)
                 }
-                if entry.id == current_id {
-                    found_current = true;
-                }
-            }
-
-            if let Some(id) = next_id {
-                self.load_entry_by_id(&id).await?;
+                Err(e) => self.toast(format!("The daemon failed to reload: {:#}", e), true),
+                Ok(_) => {}
             }
         }
-        Ok(())
+        self.load_feeds().await
     }
 
-    async fn load_random_unread(&mut self) -> Result<()> {
-        let unread_entries = self.engine.database().get_unread_entries(1000).await?;
-
-        if unread_entries.is_empty() {
-            return Ok(());
-        }
-
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        if let Some(entry) = unread_entries.choose(&mut rng) {
-            let entry_id = entry.id.clone();
-            let feed_id = entry.feed_id.clone();
-
-            let feed = self.engine.database().get_feed(&feed_id).await?;
-            if let Some(feed) = feed {
-                self.current_feed_title = feed.title;
-                self.entries = self.engine.database()
-                    .get_entries_for_feed(&feed.id, 100).await?;
-                self.load_entry_by_id(&entry_id).await?;
-            }
-        }
-        Ok(())
+    pub(super) fn toast(&mut self, text: String, error: bool) {
+        self.toasts.push(Toast {
+            text,
+            error,
+            shown_at: Instant::now(),
+        });
     }
+}
 
-    async fn load_entry_by_id(&mut self, entry_id: &str) -> Result<()> {
-        if let Some(entry) = self.engine.database().get_entry(entry_id).await? {
-            if !entry.read {
-                self.mark_entry_as_read(&entry.id).await?;
-            }
-            self.current_entry = Some(entry);
-            self.scroll_offset = 0;
-        }
-        Ok(())
+/// Move a list selection; up/down wrap around, the others stop at the ends
+pub(super) fn move_selection(state: &mut ListState, len: usize, action: Action) {
+    if len == 0 {
+        return;
     }
-
-    async fn mark_entry_as_read(&mut self, entry_id: &str) -> Result<()> {
-        self.engine.database().mark_read(entry_id).await?;
-
-        if let Some(entry) = self.current_entry.as_mut() {
-            if entry.id == entry_id {
-                entry.read = true;
+    let i = state.selected().unwrap_or(0);
+    let new_i = match action {
+        Action::Up => {
+            if i == 0 {
+                len - 1
+            } else {
+                i - 1
             }
         }
-
-        if let Some(list_entry) = self.entries.iter_mut().find(|e| e.id == entry_id) {
-            list_entry.read = true;
-        }
-
-        Ok(())
-    }
-
-    async fn refresh_current_feed(&mut self) -> Result<()> {
-        if let Some(idx) = self.feed_state.selected() {
-            if let Some(feed) = self.feeds.get(idx) {
-                self.engine.update_feed(&feed.id).await?;
-                // Reload feeds to get updated counts
-                self.feeds = self.engine.database().get_all_feeds().await?;
-                // If on entries page, reload entries too
-                if self.page == Page::Entries {
-                    self.load_entries().await?;
-                }
+        Action::Down => {
+            if i >= len - 1 {
+                0
+            } else {
+                i + 1
             }
         }
-        Ok(())
-    }
+        Action::Top => 0,
+        Action::Bottom => len - 1,
+        Action::PageUp => i.saturating_sub(PAGE_ITEMS),
+        Action::PageDown => (i + PAGE_ITEMS).min(len - 1),
+        _ => return,
+    };
+    state.select(Some(new_i));
+}
+
+/// A `width` x `height` area in the middle of `area`
+pub(super) fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }
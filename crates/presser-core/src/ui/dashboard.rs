@@ -0,0 +1,472 @@
+//! The dashboard: today's activity, unread counts, AI spend, failing feeds,
+//! upcoming scheduled runs, and a preview of the digest
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use presser_db::{Feed, FolderCount};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use super::app::{App, Page};
+use super::keys::Action;
+use super::theme::Theme;
+use crate::control::{self, ControlEndpoint, ControlRequest, ControlResponse};
+use crate::daemon;
+use crate::digest::{self, Digest, DigestGrouping};
+use crate::text::truncate;
+use crate::Engine;
+
+/// Upcoming runs listed
+const MAX_RUNS: usize = 8;
+/// Digest entries previewed per section
+const MAX_ITEMS: usize = 3;
+/// Digest sections previewed
+const MAX_SECTIONS: usize = 6;
+
+/// A feed whose last fetch failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failing {
+    pub title: String,
+    pub error: String,
+    /// Share of recorded fetches that failed
+    pub failure_rate: Option<f64>,
+}
+
+/// A scheduled task's next run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    /// Feed title, or the task ID for other tasks
+    pub name: String,
+    pub at: DateTime<Utc>,
+    pub paused: bool,
+    /// Came due while the daemon was offline
+    pub deferred: bool,
+}
+
+/// Everything the dashboard shows, loaded when it is opened
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    /// Entries stored and summaries written today
+    pub new_entries: i64,
+    pub new_summaries: i64,
+    pub unread: i64,
+    pub total: i64,
+    pub folders: Vec<FolderCount>,
+    /// AI tokens and requests spent today, and the daily budget
+    pub tokens: i64,
+    pub requests: i64,
+    pub budget: Option<u64>,
+    pub failing: Vec<Failing>,
+    /// Soonest runs first
+    pub runs: Vec<Run>,
+    /// Whether the runs come from a running daemon rather than the config
+    pub daemon: bool,
+    /// The digest `presser digest` would generate now
+    pub digest: Digest,
+}
+
+impl Dashboard {
+    pub async fn load(engine: &Engine, feeds: &[Feed]) -> Result<Dashboard> {
+        let db = engine.database();
+        let config = engine.config();
+        let today = db.get_daily_counts(1).await?.pop();
+        let stats = db.get_stats().await?;
+        let usage = db.get_ai_usage(Utc::now().date_naive()).await?;
+        let feed_stats = db.get_feed_stats().await?;
+
+        let failing = feeds
+            .iter()
+            .filter(|f| f.enabled)
+            .filter_map(|f| {
+                let error = f.last_error.as_ref()?;
+                Some(Failing {
+                    title: f.title.clone(),
+                    error: error.lines().next().unwrap_or_default().to_string(),
+                    failure_rate: feed_stats
+                        .iter()
+                        .find(|s| s.feed_id == f.id)
+                        .and_then(|s| s.failure_rate()),
+                })
+            })
+            .collect();
+
+        // The running daemon knows about pauses and reloads; without one,
+        // show what it would schedule
+        let endpoint = ControlEndpoint::from_config(&config.daemon);
+        let status = if control::is_running(&endpoint).await {
+            match control::send(&endpoint, &ControlRequest::Status).await {
+                Ok(ControlResponse::Status(status)) => Some(status.tasks),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let daemon = status.is_some();
+        let mut tasks = match status {
+            Some(tasks) => tasks,
+            None => daemon::preview(config, feeds)?,
+        };
+        tasks.sort_by_key(|t| t.next_run);
+        let runs = tasks
+            .into_iter()
+            .take(MAX_RUNS)
+            .map(|task| Run {
+                name: feeds
+                    .iter()
+                    .find(|f| f.id == task.id)
+                    .map_or(task.id, |f| f.title.clone()),
+                at: task.next_run,
+                paused: task.paused,
+                deferred: task.deferred,
+            })
+            .collect();
+
+        Ok(Dashboard {
+            new_entries: today.as_ref().map_or(0, |d| d.entries),
+            new_summaries: today.as_ref().map_or(0, |d| d.summaries),
+            unread: stats.unread_entries,
+            total: stats.total_entries,
+            folders: db.get_folder_counts().await?,
+            tokens: usage.tokens,
+            requests: usage.requests,
+            budget: config.ai.daily_token_budget,
+            failing,
+            runs,
+            daemon,
+            digest: digest::build(db, 1, DigestGrouping::Feed).await?,
+        })
+    }
+
+    /// The dashboard's lines, with long titles and errors cut to fit `width`
+    pub fn lines(&self, width: usize, theme: &Theme) -> Vec<Line<'static>> {
+        let heading = Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD);
+        let label = Style::default().fg(theme.text);
+        let value = Style::default().fg(theme.highlight);
+        let dim = Style::default().fg(theme.dim);
+        let mut lines = Vec::new();
+        let section = |lines: &mut Vec<Line<'static>>, title: &str, note: String| {
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(vec![
+                Span::styled(title.to_string(), heading),
+                Span::styled(note, dim),
+            ]));
+        };
+        let row = |name: String, value_text: String, note: String| {
+            Line::from(vec![
+                Span::styled(format!("  {:<20} ", truncate(&name, 20)), label),
+                Span::styled(value_text, value),
+                Span::styled(note, dim),
+            ])
+        };
+
+        section(&mut lines, "Today", String::new());
+        lines.push(row(
+            "New entries".into(),
+            self.new_entries.to_string(),
+            format!(" ({} summarized)", self.new_summaries),
+        ));
+        lines.push(row(
+            "Unread".into(),
+            self.unread.to_string(),
+            format!(" of {}", self.total),
+        ));
+        let budget = self
+            .budget
+            .map(|b| format!(" of {} budget", b))
+            .unwrap_or_default();
+        lines.push(row(
+            "AI tokens".into(),
+            self.tokens.to_string(),
+            format!("{} ({} requests)", budget, self.requests),
+        ));
+
+        section(&mut lines, "Unread by folder", String::new());
+        for folder in &self.folders {
+            lines.push(row(
+                folder.name.clone().unwrap_or_else(|| "(unfiled)".into()),
+                folder.unread.to_string(),
+                format!(" of {}", folder.total),
+            ));
+        }
+
+        section(
+            &mut lines,
+            "Failing feeds",
+            format!(" ({})", self.failing.len()),
+        );
+        if self.failing.is_empty() {
+            lines.push(Line::from(Span::styled("  None", dim)));
+        }
+        for feed in &self.failing {
+            let rate = feed
+                .failure_rate
+                .map(|r| format!(" ({:.0}% of fetches failed)", r * 100.0))
+                .unwrap_or_default();
+            let room = width.saturating_sub(23 + rate.chars().count());
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<20} ", truncate(&feed.title, 20)), label),
+                Span::styled(
+                    truncate(&feed.error, room),
+                    Style::default().fg(theme.error_bg),
+                ),
+                Span::styled(rate, dim),
+            ]));
+        }
+
+        let source = if self.daemon {
+            " (daemon)"
+        } else {
+            " (daemon not running: from the config)"
+        };
+        section(&mut lines, "Next runs", source.into());
+        if self.runs.is_empty() {
+            lines.push(Line::from(Span::styled("  Nothing scheduled", dim)));
+        }
+        for run in &self.runs {
+            lines.push(row(
+                run.name.clone(),
+                run.at.with_timezone(&Local).format("%a %H:%M").to_string(),
+                match (run.paused, run.deferred) {
+                    (true, _) => " paused",
+                    (_, true) => " waiting for the network",
+                    _ => "",
+                }
+                .into(),
+            ));
+        }
+
+        let digest = &self.digest;
+        section(
+            &mut lines,
+            "Digest preview",
+            format!(
+                " ({} entries from {} feeds, {} summarized)",
+                digest.entry_count, digest.feed_count, digest.summarized_count
+            ),
+        );
+        if digest.sections.is_empty() {
+            lines.push(Line::from(Span::styled("  Nothing new", dim)));
+        }
+        for digest_section in digest.sections.iter().take(MAX_SECTIONS) {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "  {}",
+                    truncate(&digest_section.heading, width.saturating_sub(2))
+                ),
+                Style::default().fg(theme.tag),
+            )));
+            for item in digest_section.items.iter().take(MAX_ITEMS) {
+                let marker = if item.summary.is_some() { "✦" } else { "•" };
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("    {} ", marker),
+                        Style::default().fg(theme.summary),
+                    ),
+                    Span::styled(truncate(&item.title, width.saturating_sub(6)), label),
+                ]));
+            }
+            if digest_section.items.len() > MAX_ITEMS {
+                let more = digest_section.items.len() - MAX_ITEMS;
+                lines.push(Line::from(Span::styled(
+                    format!("      … {} more", more),
+                    dim,
+                )));
+            }
+        }
+        if digest.sections.len() > MAX_SECTIONS {
+            let more = digest.sections.len() - MAX_SECTIONS;
+            lines.push(Line::from(Span::styled(
+                format!("  … {} more sections", more),
+                dim,
+            )));
+        }
+        lines
+    }
+}
+
+impl App {
+    pub(super) fn render_dashboard(&mut self, frame: &mut Frame) {
+        let area = frame.size();
+        let chunks = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ],
+        )
+        .split(area);
+
+        let title = Paragraph::new(Line::from(vec![
+            Span::styled(" ◀ ", Style::default().fg(self.theme.dim)),
+            Span::styled(
+                "Dashboard",
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+        frame.render_widget(title, chunks[0]);
+
+        let content_area = chunks[1].inner(&ratatui::layout::Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+        let lines = self
+            .dashboard
+            .as_ref()
+            .map(|d| d.lines(content_area.width as usize, &self.theme))
+            .unwrap_or_default();
+        let max_scroll = lines.len().saturating_sub(content_area.height as usize);
+        self.dashboard_scroll = self.dashboard_scroll.min(max_scroll as u16);
+        frame.render_widget(
+            Paragraph::new(lines).scroll((self.dashboard_scroll, 0)),
+            content_area,
+        );
+
+        let help = self.help_bar(&[
+            (Action::Back, "back"),
+            (Action::Down, "scroll"),
+            (Action::RefreshAll, "refresh all"),
+            (Action::Dashboard, "reload"),
+            (Action::Help, "help"),
+        ]);
+        frame.render_widget(self.bottom_bar(help), chunks[2]);
+    }
+
+    pub(super) fn handle_dashboard_action(&mut self, action: Action) {
+        match action {
+            Action::Back => {
+                self.page = self.dashboard_origin;
+                self.dashboard = None;
+            }
+            Action::RefreshAll => self.refresh_all(),
+            Action::Up => self.dashboard_scroll = self.dashboard_scroll.saturating_sub(1),
+            Action::Down => self.dashboard_scroll = self.dashboard_scroll.saturating_add(1),
+            Action::PageUp => self.dashboard_scroll = self.dashboard_scroll.saturating_sub(20),
+            Action::PageDown => self.dashboard_scroll = self.dashboard_scroll.saturating_add(20),
+            Action::Top => self.dashboard_scroll = 0,
+            Action::Bottom => self.dashboard_scroll = u16::MAX,
+            _ => {}
+        }
+    }
+
+    /// Open the dashboard, or load it again when it is open
+    pub(super) async fn open_dashboard(&mut self) {
+        match Dashboard::load(&self.engine, &self.feeds).await {
+            Ok(dashboard) => {
+                if self.page != Page::Dashboard {
+                    self.dashboard_origin = self.page;
+                    self.dashboard_scroll = 0;
+                    self.page = Page::Dashboard;
+                }
+                self.dashboard = Some(dashboard);
+            }
+            Err(e) => self.toast(format!("Failed to load the dashboard: {:#}", e), true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::{DigestItem, DigestSection};
+
+    #[test]
+    fn test_lines() {
+        let item = |title: &str, summary: Option<&str>| DigestItem {
+            id: title.into(),
+            title: title.into(),
+            url: String::new(),
+            feed_title: "Blog".into(),
+            published: Utc::now(),
+            summary: summary.map(Into::into),
+            description: None,
+            tags: Vec::new(),
+            discussion: None,
+            comments: None,
+            changes: None,
+        };
+        let dashboard = Dashboard {
+            new_entries: 12,
+            new_summaries: 4,
+            unread: 30,
+            total: 200,
+            folders: vec![FolderCount {
+                id: Some(1),
+                name: Some("News".into()),
+                feeds: 2,
+                unread: 3,
+                total: 9,
+            }],
+            tokens: 1500,
+            requests: 6,
+            budget: Some(50_000),
+            failing: vec![Failing {
+                title: "Kernel".into(),
+                error: "HTTP 503 Service Unavailable".into(),
+                failure_rate: Some(0.25),
+            }],
+            runs: vec![Run {
+                name: "Lobsters".into(),
+                at: Utc::now(),
+                paused: true,
+                deferred: false,
+            }],
+            daemon: false,
+            digest: Digest {
+                title: "Digest".into(),
+                generated_at: Utc::now(),
+                days: 1,
+                entry_count: 5,
+                feed_count: 1,
+                summarized_count: 1,
+                synthesis: None,
+                coverage: Vec::new(),
+                sections: vec![DigestSection {
+                    heading: "Blog".into(),
+                    items: vec![
+                        item("One", Some("Summary")),
+                        item("Two", None),
+                        item("Three", None),
+                        item("Four", None),
+                        item("Five", None),
+                    ],
+                }],
+            },
+        };
+        let text: Vec<String> = dashboard
+            .lines(60, &Theme::default())
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        let has = |wanted: &str| text.iter().any(|line| line.trim_end() == wanted);
+
+        assert!(has("  New entries          12 (4 summarized)"));
+        assert!(has(
+            "  AI tokens            1500 of 50000 budget (6 requests)"
+        ));
+        assert!(has("  News                 3 of 9"));
+        assert!(has("Failing feeds (1)"));
+        assert!(has(
+            "  Kernel               HTTP 503 Ser… (25% of fetches failed)"
+        ));
+        assert!(text
+            .iter()
+            .any(|line| line.starts_with("  Lobsters") && line.ends_with(" paused")));
+        assert!(has("Next runs (daemon not running: from the config)"));
+        assert!(has("Digest preview (5 entries from 1 feeds, 1 summarized)"));
+        assert!(has("    ✦ One"));
+        assert!(has("    • Three"));
+        assert!(!has("    • Four"));
+        assert!(has("      … 2 more"));
+        assert!(text.iter().all(|line| line.chars().count() <= 60));
+    }
+}
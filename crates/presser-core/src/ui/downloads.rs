@@ -0,0 +1,416 @@
+//! The downloads view: podcast episodes downloading and downloaded, and
+//! handing the terminal to a player to play one
+
+use std::io::{self, Stdout, Write};
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use presser_db::{Database, Enclosure, EnclosureDownload};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use tokio::sync::mpsc;
+
+use super::app::{move_selection, App, Page};
+use super::images::Protocol;
+use super::keys::Action;
+use super::theme::Theme;
+use crate::podcasts;
+use crate::Engine;
+
+/// Most episodes shown
+const LIMIT: i64 = 200;
+
+/// A finished download: its entry's title, and the file or why it failed
+pub type Finished = (String, Result<PathBuf>);
+
+/// Podcast episodes whose download was started, reloaded while the view is
+/// open, and the downloads running in the background
+pub struct Downloads {
+    pub items: Vec<EnclosureDownload>,
+    pub state: ListState,
+    /// When the items were last loaded
+    loaded: Instant,
+    results: mpsc::UnboundedReceiver<Finished>,
+    sender: mpsc::UnboundedSender<Finished>,
+}
+
+impl Downloads {
+    pub fn new() -> Downloads {
+        let (sender, results) = mpsc::unbounded_channel();
+        Downloads {
+            items: Vec::new(),
+            state: ListState::default(),
+            loaded: Instant::now(),
+            results,
+            sender,
+        }
+    }
+
+    /// Load the episodes again, keeping the selection on the list
+    pub async fn load(&mut self, db: &Database) -> Result<()> {
+        self.loaded = Instant::now();
+        self.items = db.get_downloads(LIMIT).await?;
+        let selected = match self.items.len() {
+            0 => None,
+            len => Some(self.state.selected().unwrap_or(0).min(len - 1)),
+        };
+        self.state.select(selected);
+        Ok(())
+    }
+
+    /// Whether the episodes were loaded [`podcasts::PROGRESS_INTERVAL`] ago,
+    /// so the open view should load them again for its progress to move
+    pub fn stale(&self) -> bool {
+        self.loaded.elapsed() >= podcasts::PROGRESS_INTERVAL
+    }
+
+    pub fn selected(&self) -> Option<&EnclosureDownload> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+
+    /// Download `enclosure` in the background; [`Downloads::finished`]
+    /// reports it when it is done
+    pub fn start(&self, engine: Arc<Engine>, enclosure: Enclosure, title: String) {
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let result = engine.download_enclosure(enclosure.id).await;
+            let _ = sender.send((title, result));
+        });
+    }
+
+    /// Downloads finished since the last call
+    pub fn finished(&mut self) -> Vec<Finished> {
+        std::iter::from_fn(|| self.results.try_recv().ok()).collect()
+    }
+}
+
+/// List line of an episode: its title, feed, download state, and where it
+/// was left
+pub fn line(download: &EnclosureDownload, theme: &Theme) -> Line<'static> {
+    let enclosure = &download.enclosure;
+    let dim = Style::default().fg(theme.dim);
+    let status_style = if enclosure.download_error.is_some() {
+        Style::default().fg(theme.error_fg).bg(theme.error_bg)
+    } else if enclosure.local_path.is_some() {
+        dim
+    } else {
+        Style::default().fg(theme.highlight)
+    };
+    let mut spans = vec![
+        Span::styled(
+            download.entry_title.clone(),
+            Style::default().fg(theme.text),
+        ),
+        Span::styled(format!("  {}  ", download.feed_title), dim),
+        Span::styled(podcasts::download_status(enclosure), status_style),
+    ];
+    if enclosure.position_secs > 0 {
+        spans.push(Span::styled(
+            format!(
+                "  played to {}",
+                podcasts::format_position(enclosure.position_secs)
+            ),
+            dim,
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Leave the alternate screen to `command` until it exits; returns how it
+/// exited and how long it ran
+pub async fn run_player(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    images: Option<Protocol>,
+    command: &[String],
+) -> Result<(io::Result<ExitStatus>, Duration)> {
+    if let Some(clear) = images.and_then(Protocol::clear) {
+        write!(terminal.backend_mut(), "{}", clear)?;
+    }
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    let started = Instant::now();
+    let status = match tokio::process::Command::new(&command[0])
+        .args(&command[1..])
+        .spawn()
+    {
+        Ok(mut child) => loop {
+            // Ctrl-C stops the player, not presser
+            tokio::select! {
+                status = child.wait() => break status,
+                _ = tokio::signal::ctrl_c() => {}
+            }
+        },
+        Err(e) => Err(e),
+    };
+    let ran = started.elapsed();
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok((status, ran))
+}
+
+impl App {
+    pub(super) fn render_downloads(&mut self, frame: &mut Frame) {
+        let area = frame.size();
+        let chunks = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ],
+        )
+        .split(area);
+
+        let title = Paragraph::new(Line::from(vec![
+            Span::styled(" ◀ ", Style::default().fg(self.theme.dim)),
+            Span::styled(
+                "Downloads",
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" ({})", self.downloads.items.len()),
+                Style::default().fg(self.theme.dim),
+            ),
+        ]));
+        frame.render_widget(title, chunks[0]);
+
+        if self.downloads.items.is_empty() {
+            let key = self
+                .keymap
+                .key(Action::Download)
+                .map(|k| k.to_string())
+                .unwrap_or_else(|| "download".into());
+            let empty = Paragraph::new(format!(
+                "No downloads yet. Press {} on an entry with a podcast episode to download it.",
+                key
+            ))
+            .style(Style::default().fg(self.theme.dim))
+            .wrap(Wrap { trim: true });
+            frame.render_widget(
+                empty,
+                chunks[1].inner(&ratatui::layout::Margin {
+                    horizontal: 1,
+                    vertical: 1,
+                }),
+            );
+        } else {
+            let items: Vec<ListItem> = self
+                .downloads
+                .items
+                .iter()
+                .map(|download| ListItem::new(line(download, &self.theme)))
+                .collect();
+            let list = List::new(items)
+                .highlight_style(self.theme.selection())
+                .highlight_symbol("▶ ");
+            frame.render_stateful_widget(list, chunks[1], &mut self.downloads.state);
+        }
+
+        let help = self.help_bar(&[
+            (Action::Back, "back"),
+            (Action::Play, "play"),
+            (Action::Download, "download again"),
+            (Action::Help, "help"),
+        ]);
+        frame.render_widget(self.bottom_bar(help), chunks[2]);
+    }
+
+    pub(super) fn handle_downloads_action(&mut self, action: Action) {
+        match action {
+            Action::Back => {
+                self.page = self.downloads_origin;
+                self.downloads.items.clear();
+            }
+            Action::RefreshAll => self.refresh_all(),
+            action => move_selection(
+                &mut self.downloads.state,
+                self.downloads.items.len(),
+                action,
+            ),
+        }
+    }
+
+    /// Open the downloads view
+    pub(super) async fn open_downloads(&mut self) {
+        if self.page != Page::Downloads {
+            self.downloads_origin = self.page;
+            self.page = Page::Downloads;
+            self.downloads.state.select(Some(0));
+        }
+        self.load_downloads().await;
+    }
+
+    async fn load_downloads(&mut self) {
+        if let Err(e) = self.downloads.load(self.engine.database()).await {
+            self.toast(format!("Failed to load the downloads: {:#}", e), true);
+        }
+    }
+
+    /// Report finished downloads, and reload the downloads view while it is
+    /// open so its progress moves
+    pub(super) async fn poll_downloads(&mut self) {
+        for (title, result) in self.downloads.finished() {
+            match result {
+                Ok(_) => self.toast(format!("Downloaded {}", title), false),
+                Err(e) => self.toast(format!("Failed to download {}: {:#}", title, e), true),
+            }
+        }
+        if self.page == Page::Downloads && self.downloads.stale() {
+            self.load_downloads().await;
+        }
+    }
+
+    /// The podcast episode of the selected download, or of the entry being
+    /// read or selected, with the entry's title
+    async fn target_episode(&mut self) -> Option<(Enclosure, String)> {
+        if self.page == Page::Downloads {
+            let download = self.downloads.selected()?;
+            return Some((download.enclosure.clone(), download.entry_title.clone()));
+        }
+        let entry = self.target_entry()?;
+        let (entry_id, title) = (entry.id.clone(), entry.title.clone());
+        match self
+            .engine
+            .database()
+            .get_enclosures_for_entry(&entry_id)
+            .await
+        {
+            Ok(enclosures) => match podcasts::audio_enclosure(&enclosures) {
+                Some(enclosure) => Some((enclosure.clone(), title)),
+                None => {
+                    self.status = Some(" The entry has no podcast episode".into());
+                    None
+                }
+            },
+            Err(e) => {
+                self.toast(
+                    format!("Failed to load the entry's enclosures: {:#}", e),
+                    true,
+                );
+                None
+            }
+        }
+    }
+
+    pub(super) async fn play_episode(&mut self) {
+        if let Some((enclosure, _)) = self.target_episode().await {
+            self.play_request = Some(enclosure);
+        }
+    }
+
+    /// Download the target episode in the background; the downloads view
+    /// shows its progress
+    pub(super) async fn download_episode(&mut self) {
+        let Some((enclosure, title)) = self.target_episode().await else {
+            return;
+        };
+        self.downloads.start(self.engine.clone(), enclosure, title);
+        self.status = Some(" Downloading; the downloads view shows its progress".into());
+    }
+
+    /// Hand the terminal to the player for `enclosure`, starting where it
+    /// was left, and save where it stopped
+    pub(super) async fn play(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        enclosure: Enclosure,
+    ) -> Result<()> {
+        let file = podcasts::playable(&enclosure);
+        let Some(command) = self
+            .engine
+            .config()
+            .podcasts
+            .player_command(&file, enclosure.position_secs)
+        else {
+            self.toast("No player configured ([podcasts] player)".into(), true);
+            return Ok(());
+        };
+
+        let (status, ran) = run_player(terminal, self.image_protocol, &command).await?;
+        self.drawn_images.clear();
+
+        match status {
+            Err(e) => {
+                self.toast(format!("Failed to run {}: {}", command[0], e), true);
+                return Ok(());
+            }
+            Ok(status) if !status.success() => {
+                self.toast(format!("{} exited with {}", command[0], status), true);
+            }
+            Ok(_) => {}
+        }
+        let position = podcasts::stopped_at(enclosure.position_secs, ran);
+        match self
+            .engine
+            .database()
+            .set_play_position(enclosure.id, position)
+            .await
+        {
+            Ok(()) if position > 0 => {
+                self.toast(
+                    format!("Stopped at {}", podcasts::format_position(position)),
+                    false,
+                );
+            }
+            Ok(()) => {}
+            Err(e) => self.toast(format!("Failed to save the play position: {:#}", e), true),
+        }
+        if self.page == Page::Downloads {
+            self.load_downloads().await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        let theme = Theme::default();
+        let mut download = EnclosureDownload {
+            enclosure: Enclosure {
+                url: "https://example.com/ep1.mp3".into(),
+                length: Some(2_000_000),
+                downloaded_bytes: 500_000,
+                ..Default::default()
+            },
+            entry_title: "Episode 1".into(),
+            feed_title: "The Show".into(),
+        };
+        let text = |line: Line| {
+            line.spans
+                .iter()
+                .map(|s| s.content.to_string())
+                .collect::<String>()
+        };
+        let downloading = line(&download, &theme);
+        assert!(text(downloading.clone()).starts_with("Episode 1  The Show  25%"));
+        assert_eq!(downloading.spans[2].style.fg, Some(theme.highlight));
+
+        download.enclosure.download_error = Some("HTTP 404".into());
+        download.enclosure.position_secs = 95;
+        let failed = line(&download, &theme);
+        assert!(
+            text(failed.clone()).ends_with("failed: HTTP 404  played to 1:35"),
+            "{}",
+            text(failed.clone())
+        );
+        assert_eq!(failed.spans[2].style.bg, Some(theme.error_bg));
+    }
+}
@@ -0,0 +1,304 @@
+//! The entry list: the entries of a feed, folder, or tag in the current view
+
+use anyhow::Result;
+use presser_db::Entry;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use super::app::{move_selection, App, Page};
+use super::keys::Action;
+use super::sidebar::view_label;
+use super::theme::Theme;
+use crate::digest::comments_label;
+
+impl App {
+    /// Load the entries of the open feed, folder, or tag in the current view
+    pub(super) async fn load_entries(&mut self) -> Result<()> {
+        let Some(source) = &self.source else {
+            return Ok(());
+        };
+        let filter = self.view.apply(source.filter()).limit(100);
+        self.entries = self.engine.database().query_entries(&filter).await?;
+        if let Some(filter) = &self.entry_filter {
+            let filter = filter.to_lowercase();
+            self.entries
+                .retain(|e| e.title.to_lowercase().contains(&filter));
+        }
+        self.entry_state = ListState::default();
+        if !self.entries.is_empty() {
+            self.entry_state.select(Some(0));
+        }
+        self.load_tags().await
+    }
+
+    /// Load the tags, discussions, revisions, and other sources of the
+    /// listed entries
+    pub(super) async fn load_tags(&mut self) -> Result<()> {
+        let ids: Vec<String> = self.entries.iter().map(|e| e.id.clone()).collect();
+        self.entry_tags = self.engine.database().get_tags_for_entries(&ids).await?;
+        self.entry_discussions = self
+            .engine
+            .database()
+            .get_discussions_for_entries(&ids)
+            .await?;
+        self.entry_revisions = self.engine.database().get_latest_revisions(&ids).await?;
+        self.entry_sources = self.engine.database().get_other_sources(&ids).await?;
+        Ok(())
+    }
+
+    /// Latest comment count of a listed entry
+    pub(super) fn comments(&self, entry_id: &str) -> Option<i64> {
+        self.entry_discussions
+            .get(entry_id)
+            .and_then(|d| d.comments)
+    }
+
+    pub(super) fn render_entries_page(&mut self, frame: &mut Frame) {
+        let area = frame.size();
+
+        // Layout: title + list + help bar
+        let chunks = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ],
+        )
+        .split(area);
+
+        // Title bar showing current feed
+        let title = Paragraph::new(Line::from(vec![
+            Span::styled(" ◀ ", Style::default().fg(self.theme.dim)),
+            Span::styled(
+                &self.list_title,
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" ({} articles)", self.entries.len()),
+                Style::default().fg(self.theme.dim),
+            ),
+            Span::styled(
+                view_label(self.view),
+                Style::default().fg(self.theme.highlight),
+            ),
+        ]));
+        frame.render_widget(title, chunks[0]);
+
+        // Entry list
+        let entry_items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|e| {
+                ListItem::new(Line::from(entry_spans(
+                    e,
+                    self.entry_tags.get(&e.id),
+                    self.comments(&e.id),
+                    self.entry_revisions.contains_key(&e.id),
+                    &self.theme,
+                )))
+            })
+            .collect();
+
+        let entry_list = List::new(entry_items)
+            .highlight_style(self.theme.selection())
+            .highlight_symbol("▶ ");
+
+        frame.render_stateful_widget(entry_list, chunks[1], &mut self.entry_state);
+
+        // Help bar
+        let help = self.help_bar(&[
+            (Action::Open, "read"),
+            (Action::ToggleRead, "toggle"),
+            (Action::Star, "star"),
+            (Action::Hide, "hide"),
+            (Action::Tag, "tag"),
+            (Action::Undo, "undo"),
+            (Action::Refresh, "refresh"),
+            (Action::View, "view"),
+            (Action::Save, "save"),
+            (Action::Search, "search"),
+            (Action::Help, "help"),
+            (Action::Back, "back"),
+        ]);
+
+        frame.render_widget(self.bottom_bar(help), chunks[2]);
+    }
+
+    pub(super) async fn handle_entries_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Back if self.page == Page::Search => self.close_search().await?,
+            Action::Refresh if self.page == Page::Search => self.rerun_search().await?,
+            Action::Back => {
+                // Go back to feeds
+                self.page = Page::Feeds;
+                self.entries.clear();
+                self.source = None;
+                self.entry_filter = None;
+                self.load_feeds().await?;
+            }
+            Action::Open => self.open_selected_entry().await?,
+            Action::Refresh => self.refresh_current_feed(),
+            Action::RefreshAll => self.refresh_all(),
+            Action::SearchNext => self.search_step(true),
+            Action::SearchPrev => self.search_step(false),
+            Action::OpenBrowser => {
+                if let Some(entry) = self
+                    .entry_state
+                    .selected()
+                    .and_then(|i| self.entries.get(i))
+                {
+                    let _ = open::that(&entry.url);
+                }
+            }
+            Action::Save => {
+                if let Some(entry) = self
+                    .entry_state
+                    .selected()
+                    .and_then(|i| self.entries.get(i))
+                {
+                    let entry = entry.clone();
+                    self.save_entry(&entry).await;
+                }
+            }
+            Action::ToggleRead
+            | Action::Star
+            | Action::Hide
+            | Action::Tag
+            | Action::Untag
+            | Action::Undo => self.handle_entry_action(action).await,
+            Action::Summarize | Action::SummaryStyle => {
+                self.open_selected_entry().await?;
+                self.handle_reader_action(action).await?;
+            }
+            action => move_selection(&mut self.entry_state, self.entries.len(), action),
+        }
+        Ok(())
+    }
+
+    /// Show the next of all, unread, starred, or top unread entries
+    pub(super) async fn cycle_view(&mut self) -> Result<()> {
+        if self.page == Page::Search {
+            self.status = Some(" Add is:unread or is:starred to the search (/) instead".into());
+            return Ok(());
+        }
+        self.view = self.view.next();
+        if self.page == Page::Entries {
+            self.load_entries().await?;
+        }
+        self.status = Some(format!(" Showing {} entries", self.view.label()));
+        Ok(())
+    }
+
+    /// The list the reader goes back to
+    pub(super) fn list_page(&self) -> Page {
+        if self.results.is_some() {
+            Page::Search
+        } else {
+            Page::Entries
+        }
+    }
+
+    /// Open the selected entry in the reader, marking it read
+    pub(super) async fn open_selected_entry(&mut self) -> Result<()> {
+        if let Some(idx) = self.entry_state.selected() {
+            if let Some(entry) = self.entries.get(idx) {
+                let entry_id = entry.id.clone();
+                let needs_mark = !entry.read;
+                if needs_mark {
+                    self.mark_entry_as_read(&entry_id).await?;
+                }
+                self.current_feed_title = self.feed_title(&self.entries[idx].feed_id).to_string();
+                self.current_entry = Some(self.entries[idx].clone());
+                self.scroll_offset = 0;
+                self.page = Page::Reader;
+                self.load_summary().await?;
+                self.load_related().await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send an entry to the default read-it-later service, reporting in the status line
+    pub(super) async fn save_entry(&mut self, entry: &Entry) {
+        self.status = Some(
+            match crate::integrations::save_entry(&self.engine, entry, None).await {
+                Ok(service) => format!(" Saved to {}", service),
+                Err(e) => format!(" {:#}", e),
+            },
+        );
+    }
+
+    pub(super) async fn mark_entry_as_read(&mut self, entry_id: &str) -> Result<()> {
+        self.engine.database().mark_read(entry_id).await?;
+
+        if let Some(entry) = self.current_entry.as_mut() {
+            if entry.id == entry_id {
+                entry.read = true;
+            }
+        }
+
+        if let Some(list_entry) = self.entries.iter_mut().find(|e| e.id == entry_id) {
+            list_entry.read = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read/star markers, date, title, tags, comment count, and "updated"
+/// badge of a listed entry
+pub(super) fn entry_spans<'a>(
+    e: &'a Entry,
+    tags: Option<&Vec<String>>,
+    comments: Option<i64>,
+    updated: bool,
+    theme: &Theme,
+) -> Vec<ratatui::text::Span<'a>> {
+    let read_marker = if e.read { " " } else { "●" };
+    let star_marker = if e.starred { "★" } else { " " };
+    let tags = tags
+        .map(|tags| tags.iter().map(|t| format!(" #{}", t)).collect::<String>())
+        .unwrap_or_default();
+    let date_str = e
+        .published
+        .map(|d| d.format("%m/%d").to_string())
+        .unwrap_or_default();
+    vec![
+        Span::styled(
+            read_marker,
+            if e.read {
+                Style::default().fg(theme.dim)
+            } else {
+                Style::default().fg(theme.unread)
+            },
+        ),
+        Span::styled(star_marker, Style::default().fg(theme.highlight)),
+        Span::styled(date_str, Style::default().fg(theme.dim)),
+        Span::raw(" "),
+        Span::styled(
+            &e.title,
+            if e.read {
+                Style::default().fg(theme.dim)
+            } else {
+                Style::default().fg(theme.text)
+            },
+        ),
+        Span::styled(tags, Style::default().fg(theme.tag)),
+        Span::styled(
+            comments
+                .map(|n| format!("  {}", comments_label(n)))
+                .unwrap_or_default(),
+            Style::default().fg(theme.dim),
+        ),
+        Span::styled(
+            if updated { "  updated" } else { "" },
+            Style::default().fg(theme.highlight),
+        ),
+    ]
+}
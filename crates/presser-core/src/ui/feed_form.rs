@@ -0,0 +1,600 @@
+//! The add and edit feed dialogs: a form over a feed's settings, and the
+//! probe that turns a typed URL into a feed (or the feeds a page advertises)
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use presser_config::{Config, FeedConfig};
+use presser_db::Feed;
+use presser_feeds::{FeedFetcher, FeedProbe};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use tokio::task::JoinHandle;
+
+use super::app::{centered, App, SPINNER};
+use super::sidebar;
+use super::theme::Theme;
+
+/// A field of the form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Url,
+    Name,
+    Tags,
+    Interval,
+    Ai,
+}
+
+/// What a key did to the form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormEvent {
+    Submit,
+    Cancel,
+    Edited,
+}
+
+/// Settings typed into the add or edit dialog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedForm {
+    /// ID of the feed being edited (None when adding)
+    pub feed_id: Option<String>,
+    pub url: String,
+    /// Title (when adding, empty takes the feed's own)
+    pub name: String,
+    /// Comma-separated
+    pub tags: String,
+    /// Cron expression (empty: the global schedule)
+    pub interval: String,
+    pub ai: bool,
+    focus: usize,
+    /// Why the last submit failed
+    pub error: Option<String>,
+}
+
+impl FeedForm {
+    /// An empty form for a new feed
+    pub fn add() -> FeedForm {
+        FeedForm {
+            feed_id: None,
+            url: String::new(),
+            name: String::new(),
+            tags: String::new(),
+            interval: String::new(),
+            ai: true,
+            focus: 0,
+            error: None,
+        }
+    }
+
+    /// A form holding a feed's settings
+    pub fn edit(feed_id: &str, config: &FeedConfig) -> FeedForm {
+        FeedForm {
+            feed_id: Some(feed_id.to_string()),
+            url: config.url.clone(),
+            name: config.name.clone(),
+            tags: config.tags.join(", "),
+            interval: config.update_interval.clone().unwrap_or_default(),
+            ai: config.enable_ai,
+            focus: 0,
+            error: None,
+        }
+    }
+
+    /// Fields in order (a feed's URL can't be edited here)
+    fn fields(&self) -> &'static [Field] {
+        match self.feed_id {
+            None => &[
+                Field::Url,
+                Field::Name,
+                Field::Tags,
+                Field::Interval,
+                Field::Ai,
+            ],
+            Some(_) => &[Field::Name, Field::Tags, Field::Interval, Field::Ai],
+        }
+    }
+
+    pub fn focused(&self) -> Field {
+        self.fields()[self.focus]
+    }
+
+    fn text_mut(&mut self, field: Field) -> Option<&mut String> {
+        match field {
+            Field::Url => Some(&mut self.url),
+            Field::Name => Some(&mut self.name),
+            Field::Tags => Some(&mut self.tags),
+            Field::Interval => Some(&mut self.interval),
+            Field::Ai => None,
+        }
+    }
+
+    /// Tab and arrows move between fields, typing edits the focused one,
+    /// Space toggles AI, Enter submits, and Esc cancels
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormEvent {
+        let count = self.fields().len();
+        let field = self.focused();
+        match key.code {
+            KeyCode::Esc => return FormEvent::Cancel,
+            KeyCode::Enter => return FormEvent::Submit,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % count,
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + count - 1) % count,
+            KeyCode::Char(' ') if field == Field::Ai => self.ai = !self.ai,
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(text) = self.text_mut(field) {
+                    text.clear();
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(text) = self.text_mut(field) {
+                    text.push(c);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(text) = self.text_mut(field) {
+                    text.pop();
+                }
+            }
+            _ => {}
+        }
+        FormEvent::Edited
+    }
+
+    /// `base` with the form's settings; `title` names the feed when the
+    /// name was left empty
+    ///
+    /// The URL stays `base`'s: a typed page URL leads to the feed's own.
+    pub fn apply(&self, mut base: FeedConfig, title: &str) -> FeedConfig {
+        base.name = match self.name.trim() {
+            "" => title.trim().to_string(),
+            name => name.to_string(),
+        };
+        base.tags = self
+            .tags
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect();
+        base.update_interval = Some(self.interval.trim())
+            .filter(|i| !i.is_empty())
+            .map(str::to_string);
+        base.enable_ai = self.ai;
+        base
+    }
+
+    /// The form's rows, with a cursor in the focused field
+    pub fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let label = Style::default().fg(theme.accent);
+        let focused = label.add_modifier(Modifier::BOLD);
+        let value = Style::default().fg(theme.text);
+        let hint = Style::default().fg(theme.dim);
+        let mut lines = Vec::new();
+        if self.feed_id.is_some() {
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {:<10}", "URL"), label),
+                Span::styled(self.url.clone(), hint),
+            ]));
+        }
+        for (i, field) in self.fields().iter().enumerate() {
+            let (name, text, placeholder) = match field {
+                Field::Url => ("URL", self.url.as_str(), "feed or web page"),
+                Field::Name => ("Name", self.name.as_str(), "the feed's own title"),
+                Field::Tags => ("Tags", self.tags.as_str(), "comma-separated"),
+                Field::Interval => ("Interval", self.interval.as_str(), "cron, default schedule"),
+                Field::Ai => (
+                    "AI",
+                    if self.ai {
+                        "[x] summarize"
+                    } else {
+                        "[ ] summarize"
+                    },
+                    "",
+                ),
+            };
+            let is_focused = i == self.focus;
+            let mut spans = vec![
+                Span::styled(
+                    format!("{}{:<10}", if is_focused { "▶" } else { " " }, name),
+                    if is_focused { focused } else { label },
+                ),
+                Span::styled(text.to_string(), value),
+            ];
+            if is_focused && *field != Field::Ai {
+                spans.push(Span::styled("█", value));
+            }
+            if text.is_empty() {
+                spans.push(Span::styled(format!(" {}", placeholder), hint));
+            }
+            lines.push(Line::from(spans));
+        }
+        if let Some(error) = &self.error {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!(" {}", error),
+                Style::default().fg(theme.error_bg),
+            )));
+        }
+        lines
+    }
+}
+
+/// What a typed URL turned out to be
+#[derive(Debug)]
+pub enum Probed {
+    /// A readable feed (with metadata)
+    Feed(Box<FeedProbe>),
+    /// A web page advertising several feeds, to pick from
+    Candidates(Vec<String>),
+}
+
+/// Probe `url`, following autodiscovery when it is a web page: one
+/// advertised feed is probed right away, several are left to pick from
+pub async fn probe(fetcher: &FeedFetcher, url: &str) -> Result<Probed> {
+    let probe = fetcher.probe(url).await?;
+    if probe.is_valid_feed() {
+        return usable(probe);
+    }
+    if !probe.is_html() {
+        bail!(
+            "Not a feed: {}",
+            probe.error.as_deref().unwrap_or("no feed found at the URL")
+        );
+    }
+    let mut candidates = fetcher.discover(url).await?;
+    match candidates.len() {
+        0 => bail!("{} is a web page that does not advertise any feeds", url),
+        1 => usable(fetcher.probe(&candidates.remove(0)).await?),
+        _ => Ok(Probed::Candidates(candidates)),
+    }
+}
+
+fn usable(probe: FeedProbe) -> Result<Probed> {
+    if probe.metadata.is_none() {
+        bail!(
+            "{} can't be read: {}",
+            probe.url,
+            probe.error.as_deref().unwrap_or("no feed found")
+        );
+    }
+    Ok(Probed::Feed(Box::new(probe)))
+}
+
+/// A feed dialog open over the feeds page
+pub(super) enum Dialog {
+    /// Typing a feed's settings
+    Form(FeedForm),
+    /// Probing the URL typed into the add form
+    Probing {
+        form: FeedForm,
+        task: JoinHandle<Result<Probed>>,
+    },
+    /// Picking one of the feeds a web page advertises
+    Pick {
+        form: FeedForm,
+        candidates: Vec<String>,
+        selected: usize,
+    },
+    /// Waiting for `y` to delete a feed
+    ConfirmDelete(Feed),
+}
+
+impl App {
+    /// The open feed dialog, centered over the page
+    pub(super) fn render_dialog(&self, frame: &mut Frame) {
+        let hint = Style::default().fg(self.theme.dim);
+        let (title, mut lines, keys) = match &self.dialog {
+            Some(Dialog::Form(form)) => {
+                let title = if form.feed_id.is_some() {
+                    " Edit feed "
+                } else {
+                    " Add feed "
+                };
+                (
+                    title,
+                    form.lines(&self.theme),
+                    " Tab: next field · Space: toggle AI · Enter: save · Esc: cancel",
+                )
+            }
+            Some(Dialog::Probing { form, .. }) => {
+                let mut lines = form.lines(&self.theme);
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        " {} Probing {}",
+                        SPINNER[self.tick % SPINNER.len()],
+                        form.url.trim()
+                    ),
+                    Style::default().fg(self.theme.highlight),
+                )));
+                (" Add feed ", lines, " Esc: stop")
+            }
+            Some(Dialog::Pick {
+                form,
+                candidates,
+                selected,
+            }) => {
+                let mut lines = vec![
+                    Line::from(format!(" {} advertises several feeds:", form.url.trim())),
+                    Line::from(""),
+                ];
+                for (i, url) in candidates.iter().enumerate() {
+                    let style = if i == *selected {
+                        self.theme.selection()
+                    } else {
+                        self.theme.base()
+                    };
+                    lines.push(Line::from(Span::styled(
+                        format!(" {} {}", if i == *selected { "▶" } else { " " }, url),
+                        style,
+                    )));
+                }
+                (" Pick a feed ", lines, " Enter: add · Esc: back")
+            }
+            Some(Dialog::ConfirmDelete(feed)) => {
+                let lines = vec![
+                    Line::from(format!(
+                        " Delete {} and its {} entries?",
+                        feed.title, feed.entry_count
+                    )),
+                    Line::from(Span::styled(format!(" {}", feed.url), hint)),
+                ];
+                (" Delete feed ", lines, " y: delete · any other key: keep")
+            }
+            None => return,
+        };
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(keys, hint)));
+
+        let area = centered(frame.size(), 72, lines.len() as u16 + 2);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .style(self.theme.base())
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(self.theme.border))
+                        .title(title),
+                ),
+            area,
+        );
+    }
+
+    /// Keys of the open feed dialog
+    pub(super) async fn handle_dialog_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(dialog) = self.dialog.take() else {
+            return Ok(());
+        };
+        self.dialog = match dialog {
+            Dialog::Form(mut form) => match form.handle_key(key) {
+                FormEvent::Cancel => None,
+                FormEvent::Edited => Some(Dialog::Form(form)),
+                FormEvent::Submit => self.submit_form(form).await,
+            },
+            Dialog::Probing { form, task } if key.code == KeyCode::Esc => {
+                task.abort();
+                Some(Dialog::Form(form))
+            }
+            Dialog::Pick {
+                mut form,
+                candidates,
+                selected,
+            } => match key.code {
+                KeyCode::Esc => Some(Dialog::Form(form)),
+                KeyCode::Enter => {
+                    form.url = candidates[selected].clone();
+                    Some(self.start_probe(form))
+                }
+                KeyCode::Up | KeyCode::Char('k') => Some(Dialog::Pick {
+                    form,
+                    candidates,
+                    selected: selected.saturating_sub(1),
+                }),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let selected = (selected + 1).min(candidates.len() - 1);
+                    Some(Dialog::Pick {
+                        form,
+                        candidates,
+                        selected,
+                    })
+                }
+                _ => Some(Dialog::Pick {
+                    form,
+                    candidates,
+                    selected,
+                }),
+            },
+            Dialog::ConfirmDelete(feed) => {
+                if key.code == KeyCode::Char('y') {
+                    if let Err(e) = self.delete_feed(&feed).await {
+                        self.toast(format!("Failed to delete {}: {:#}", feed.title, e), true);
+                    }
+                }
+                None
+            }
+            dialog => Some(dialog),
+        };
+        Ok(())
+    }
+
+    /// Probe the URL of a new feed, or save an edited one; the form stays
+    /// open with the error when that fails
+    async fn submit_form(&mut self, mut form: FeedForm) -> Option<Dialog> {
+        form.error = None;
+        let result = match form.feed_id.clone() {
+            None if form.url.trim().is_empty() => {
+                Err(anyhow::anyhow!("Type the URL of a feed or web page"))
+            }
+            None => return Some(self.start_probe(form)),
+            Some(feed_id) => self.edit_feed(&feed_id, &form).await,
+        };
+        match result {
+            Ok(()) => None,
+            Err(e) => {
+                form.error = Some(format!("{:#}", e));
+                Some(Dialog::Form(form))
+            }
+        }
+    }
+
+    fn start_probe(&self, form: FeedForm) -> Dialog {
+        let engine = Arc::clone(&self.engine);
+        let url = form.url.trim().to_string();
+        let task = tokio::spawn(async move { probe(engine.fetcher(), &url).await });
+        Dialog::Probing { form, task }
+    }
+
+    /// Once the probe is done, add the feed it found or list the feeds the
+    /// page advertises
+    pub(super) async fn poll_dialog(&mut self) {
+        if !matches!(&self.dialog, Some(Dialog::Probing { task, .. }) if task.is_finished()) {
+            return;
+        }
+        let Some(Dialog::Probing { mut form, task }) = self.dialog.take() else {
+            return;
+        };
+        let probed = match task.await {
+            Ok(probed) => probed,
+            Err(e) => Err(e.into()),
+        };
+        let result = match probed {
+            Ok(Probed::Feed(probe)) => self.add_feed(&form, *probe).await,
+            Ok(Probed::Candidates(candidates)) => {
+                self.dialog = Some(Dialog::Pick {
+                    form,
+                    candidates,
+                    selected: 0,
+                });
+                return;
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            form.error = Some(format!("{:#}", e));
+            self.dialog = Some(Dialog::Form(form));
+        }
+    }
+
+    /// Write a probed feed with the form's settings, then select and fetch it
+    async fn add_feed(&mut self, form: &FeedForm, probe: FeedProbe) -> Result<()> {
+        let metadata = probe.metadata.context("The feed has no metadata")?;
+        let feed_config = form.apply(FeedConfig::new(&probe.url, ""), &metadata.title);
+        let (id, _) = crate::commands::create_feed(&self.engine, &feed_config, &metadata).await?;
+        self.reload_config().await?;
+        let row = self
+            .sidebar
+            .iter()
+            .position(|item| matches!(item, sidebar::Item::Feed(i) if self.feeds[*i].id == id));
+        if row.is_some() {
+            self.feed_state.select(row);
+            self.refresh_current_feed();
+        }
+        self.toast(format!("Added {}", feed_config.name), false);
+        Ok(())
+    }
+
+    /// Write an edited feed's settings, and its title to the database
+    async fn edit_feed(&mut self, feed_id: &str, form: &FeedForm) -> Result<()> {
+        let db = self.engine.database();
+        let feed = db
+            .get_feed(feed_id)
+            .await?
+            .context("The feed no longer exists")?;
+        let feed_config = form.apply(self.feed_config(&feed), &feed.title);
+        let config_dir = Config::config_dir()?;
+        if Config::update_feed(&config_dir, &feed.url, &feed_config)?.is_none() {
+            Config::add_feed(&config_dir, feed_id, &feed_config)?;
+        }
+        if feed_config.name != feed.title {
+            db.upsert_feed(&Feed {
+                title: feed_config.name.clone(),
+                ..feed
+            })
+            .await?;
+        }
+        self.reload_config().await?;
+        self.toast(format!("Saved {}", feed_config.name), false);
+        Ok(())
+    }
+
+    /// Remove a feed from the config and, with its entries, the database
+    async fn delete_feed(&mut self, feed: &Feed) -> Result<()> {
+        Config::remove_feed(&Config::config_dir()?, &feed.url)?;
+        self.engine.database().delete_feed(&feed.id).await?;
+        self.reload_config().await?;
+        self.toast(format!("Deleted {}", feed.title), false);
+        Ok(())
+    }
+
+    /// A feed's settings, with its title as the name (a feed only in the
+    /// database gets the defaults)
+    pub(super) fn feed_config(&self, feed: &Feed) -> FeedConfig {
+        let mut config = self
+            .engine
+            .config()
+            .feeds
+            .get(&feed.url)
+            .cloned()
+            .unwrap_or_else(|| FeedConfig::new(&feed.url, &feed.title));
+        config.name = feed.title.clone();
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form() {
+        let press =
+            |form: &mut FeedForm, code| form.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        let mut form = FeedForm::add();
+        for c in "https://example.com".chars() {
+            press(&mut form, KeyCode::Char(c));
+        }
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Tab);
+        for c in " rust, ,news ".chars() {
+            press(&mut form, KeyCode::Char(c));
+        }
+        press(&mut form, KeyCode::BackTab);
+        assert_eq!(form.focused(), Field::Name);
+        press(&mut form, KeyCode::Up);
+        press(&mut form, KeyCode::Up);
+        assert_eq!(form.focused(), Field::Ai);
+        press(&mut form, KeyCode::Char(' '));
+        assert_eq!(press(&mut form, KeyCode::Enter), FormEvent::Submit);
+
+        assert_eq!(form.url, "https://example.com");
+        let config = form.apply(
+            FeedConfig::new("https://example.com/feed", ""),
+            "Example Blog",
+        );
+        assert_eq!(config.url, "https://example.com/feed");
+        assert_eq!(config.name, "Example Blog");
+        assert_eq!(config.tags, ["rust", "news"]);
+        assert_eq!(config.update_interval, None);
+        assert!(!config.enable_ai);
+
+        let mut existing = FeedConfig::new("https://example.com/feed", "Blog");
+        existing.update_interval = Some("0 0 * * * *".into());
+        existing.folder = Some("Tech".into());
+        let mut form = FeedForm::edit("blog", &existing);
+        assert_eq!(form.focused(), Field::Name);
+        form.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        for c in "My Blog".chars() {
+            press(&mut form, KeyCode::Char(c));
+        }
+        assert_eq!(press(&mut form, KeyCode::Esc), FormEvent::Cancel);
+        let config = form.apply(existing.clone(), "Blog");
+        assert_eq!(config.name, "My Blog");
+        assert_eq!(config.update_interval.as_deref(), Some("0 0 * * * *"));
+        // Settings the form doesn't show are kept
+        assert_eq!(config.folder.as_deref(), Some("Tech"));
+    }
+}
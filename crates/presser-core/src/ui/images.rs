@@ -0,0 +1,319 @@
+//! Inline images in the reader, drawn with the terminal's graphics protocol
+//!
+//! Images are downloaded when an article is opened, decoded, and drawn over
+//! the rows [`super::render`] left for them once the frame is on screen.
+
+use std::io::{Cursor, Stdout, Write};
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use presser_config::ImageMode;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
+use ratatui::Terminal;
+
+use super::app::App;
+
+/// Largest image downloaded
+pub const MAX_BYTES: usize = 8 * 1024 * 1024;
+/// Images fetched per article
+pub const MAX_IMAGES: usize = 12;
+/// Tallest an image is drawn, in rows
+const MAX_ROWS: u32 = 20;
+
+/// A terminal graphics protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Iterm,
+    Sixel,
+}
+
+impl Protocol {
+    /// The protocol for a configured mode, if images can be drawn at all
+    pub fn from_mode(mode: ImageMode) -> Option<Protocol> {
+        match mode {
+            ImageMode::Auto => Protocol::detect(|name| std::env::var(name).ok()),
+            ImageMode::Kitty => Some(Protocol::Kitty),
+            ImageMode::Iterm => Some(Protocol::Iterm),
+            ImageMode::Sixel => Some(Protocol::Sixel),
+            ImageMode::Off => None,
+        }
+    }
+
+    /// Guess the terminal's protocol from its environment variables
+    fn detect(var: impl Fn(&str) -> Option<String>) -> Option<Protocol> {
+        let term = var("TERM").unwrap_or_default();
+        let program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || program == "ghostty"
+        {
+            Some(Protocol::Kitty)
+        } else if matches!(program.as_str(), "iTerm.app" | "WezTerm")
+            || var("LC_TERMINAL").as_deref() == Some("iTerm2")
+        {
+            Some(Protocol::Iterm)
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term == "yaft-256color"
+        {
+            Some(Protocol::Sixel)
+        } else {
+            None
+        }
+    }
+
+    /// Escape sequence removing every image drawn so far, for protocols
+    /// whose images outlive the text under them
+    pub fn clear(self) -> Option<&'static str> {
+        match self {
+            Protocol::Kitty => Some("\x1b_Ga=d,q=2\x1b\\"),
+            Protocol::Iterm | Protocol::Sixel => None,
+        }
+    }
+}
+
+/// Size of a terminal cell in pixels, guessed when the terminal won't say
+pub fn cell_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.columns > 0 && size.rows > 0 => (
+            u32::from(size.width / size.columns).max(1),
+            u32::from(size.height / size.rows).max(1),
+        ),
+        _ => (8, 16),
+    }
+}
+
+/// A downloaded image, with its escape sequence for the last size drawn
+pub struct Picture {
+    image: DynamicImage,
+    encoded: Option<((u16, u16), String)>,
+}
+
+impl Picture {
+    pub fn decode(bytes: &[u8]) -> Result<Picture> {
+        let image = image::load_from_memory(bytes).context("Unsupported image")?;
+        if image.width() == 0 || image.height() == 0 {
+            bail!("Empty image");
+        }
+        Ok(Picture {
+            image,
+            encoded: None,
+        })
+    }
+
+    /// Columns and rows to draw the image in: its own size, shrunk to fit
+    /// `max_cols` and [`MAX_ROWS`]
+    pub fn cells(&self, max_cols: u16, cell: (u32, u32)) -> (u16, u16) {
+        let (width, height) = (self.image.width(), self.image.height());
+        let mut cols = (width / cell.0).clamp(1, u32::from(max_cols.max(1)));
+        let mut rows = (cols * cell.0 * height / width).div_ceil(cell.1).max(1);
+        if rows > MAX_ROWS {
+            rows = MAX_ROWS;
+            cols = (rows * cell.1 * width / height / cell.0).max(1);
+        }
+        (cols as u16, rows as u16)
+    }
+
+    /// Escape sequence drawing the image at the cursor, `cols` by `rows`
+    pub fn escape(
+        &mut self,
+        protocol: Protocol,
+        (cols, rows): (u16, u16),
+        cell: (u32, u32),
+    ) -> Result<&str> {
+        if self
+            .encoded
+            .as_ref()
+            .is_none_or(|(size, _)| *size != (cols, rows))
+        {
+            let image = self.image.resize(
+                u32::from(cols) * cell.0,
+                u32::from(rows) * cell.1,
+                FilterType::Triangle,
+            );
+            let escape = match protocol {
+                Protocol::Kitty => kitty(&image),
+                Protocol::Iterm => iterm(&image, cols, rows)?,
+                Protocol::Sixel => sixel(&image)?,
+            };
+            self.encoded = Some(((cols, rows), escape));
+        }
+        Ok(self.encoded.as_ref().map_or("", |(_, escape)| escape))
+    }
+}
+
+/// Kitty graphics: raw RGBA in base64 chunks, replies suppressed
+fn kitty(image: &DynamicImage) -> String {
+    let rgba = image.to_rgba8();
+    let data = STANDARD.encode(rgba.as_raw());
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut escape = String::with_capacity(data.len() + chunks.len() * 32);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            escape.push_str(&format!(
+                "\x1b_Ga=T,f=32,s={},v={},C=1,q=2,m={};{}\x1b\\",
+                rgba.width(),
+                rgba.height(),
+                more,
+                chunk
+            ));
+        } else {
+            escape.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    escape
+}
+
+/// iTerm2 inline image: a PNG in base64
+fn iterm(image: &DynamicImage, cols: u16, rows: u16) -> Result<String> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .context("Failed to encode image")?;
+    Ok(format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        cols,
+        rows,
+        STANDARD.encode(&png)
+    ))
+}
+
+fn sixel(image: &DynamicImage) -> Result<String> {
+    let rgb = image.to_rgb8();
+    icy_sixel::sixel_string(
+        rgb.as_raw(),
+        rgb.width() as i32,
+        rgb.height() as i32,
+        icy_sixel::PixelFormat::RGB888,
+        icy_sixel::DiffusionMethod::Stucki,
+        icy_sixel::MethodForLargest::Auto,
+        icy_sixel::MethodForRep::Auto,
+        icy_sixel::Quality::HIGH,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to encode sixel image: {}", e))
+}
+
+/// An image of the article being read
+pub(super) enum ImageState {
+    Loading,
+    Ready(Picture),
+    Failed,
+}
+
+/// An inline image on screen
+#[derive(Clone, PartialEq, Eq)]
+pub(super) struct ShownImage {
+    pub(super) url: String,
+    pub(super) area: Rect,
+}
+
+impl App {
+    /// Take in downloaded images, laying the article out again to make
+    /// room for them
+    pub(super) fn poll_images(&mut self) {
+        while let Ok((url, picture)) = self.image_results.try_recv() {
+            let state = match picture {
+                Ok(picture) => {
+                    self.article = None;
+                    ImageState::Ready(picture)
+                }
+                Err(e) => {
+                    tracing::debug!("Failed to load image {}: {:#}", url, e);
+                    ImageState::Failed
+                }
+            };
+            if self.images.contains_key(&url) {
+                self.images.insert(url, state);
+            }
+        }
+    }
+
+    /// Draw the images placed by the last frame, removing those drawn before
+    pub(super) fn draw_images(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    ) -> Result<()> {
+        let Some(protocol) = self.image_protocol else {
+            return Ok(());
+        };
+        match protocol.clear() {
+            Some(clear) => write!(terminal.backend_mut(), "{}", clear)?,
+            None if !self.drawn_images.is_empty() => {
+                // Images are only removed by painting over them
+                terminal.clear()?;
+                terminal.draw(|f| self.render(f))?;
+            }
+            None => {}
+        }
+        let cell = cell_size();
+        for shown in &self.shown_images {
+            let Some(ImageState::Ready(picture)) = self.images.get_mut(&shown.url) else {
+                continue;
+            };
+            match picture.escape(protocol, (shown.area.width, shown.area.height), cell) {
+                Ok(escape) => {
+                    queue!(terminal.backend_mut(), MoveTo(shown.area.x, shown.area.y))?;
+                    write!(terminal.backend_mut(), "{}", escape)?;
+                }
+                Err(e) => tracing::debug!("Failed to draw image {}: {:#}", shown.url, e),
+            }
+        }
+        terminal.backend_mut().flush()?;
+        self.drawn_images = self.shown_images.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_and_size() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(
+            Protocol::detect(env(&[("TERM", "xterm-kitty")])),
+            Some(Protocol::Kitty)
+        );
+        assert_eq!(
+            Protocol::detect(env(&[("TERM_PROGRAM", "iTerm.app")])),
+            Some(Protocol::Iterm)
+        );
+        assert_eq!(
+            Protocol::detect(env(&[("TERM", "foot")])),
+            Some(Protocol::Sixel)
+        );
+        assert_eq!(Protocol::detect(env(&[("TERM", "xterm-256color")])), None);
+        assert_eq!(Protocol::from_mode(ImageMode::Off), None);
+
+        let mut png = Vec::new();
+        DynamicImage::new_rgb8(400, 100)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        let mut picture = Picture::decode(&png).unwrap();
+        // 10x20 pixel cells: 40 columns, 100 / 20 rows
+        assert_eq!(picture.cells(80, (10, 20)), (40, 5));
+        // Shrunk to fit the width
+        assert_eq!(picture.cells(20, (10, 20)), (20, 3));
+        let escape = picture.escape(Protocol::Kitty, (20, 3), (10, 20)).unwrap();
+        assert!(escape.starts_with("\x1b_Ga=T,f=32,s=200,v=50,"));
+        assert!(Picture::decode(b"not an image").is_err());
+    }
+}
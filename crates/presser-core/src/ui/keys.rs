@@ -0,0 +1,577 @@
+//! Key bindings: TUI actions, their default keys, and `[ui.keys]` overrides
+
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use presser_config::UiConfig;
+
+use super::app::Page;
+
+/// Something a key can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Top,
+    Bottom,
+    PageUp,
+    PageDown,
+    Open,
+    Back,
+    Quit,
+    Refresh,
+    RefreshAll,
+    View,
+    Search,
+    Find,
+    SearchNext,
+    SearchPrev,
+    Palette,
+    Help,
+    NextUnread,
+    Random,
+    OpenBrowser,
+    OpenLink,
+    ToggleRead,
+    MarkRead,
+    Star,
+    Hide,
+    Tag,
+    Untag,
+    Undo,
+    Summarize,
+    SummaryStyle,
+    Save,
+    Dashboard,
+    ContinueReading,
+    AddFeed,
+    EditFeed,
+    DeleteFeed,
+    Related,
+    OpenRelated,
+    Mute,
+    Translation,
+    Play,
+    Download,
+    Downloads,
+}
+
+const LISTS: &[Page] = &[Page::Feeds, Page::Entries, Page::Search];
+const ALL: &[Page] = &[
+    Page::Feeds,
+    Page::Entries,
+    Page::Search,
+    Page::Reader,
+    Page::Dashboard,
+    Page::Downloads,
+];
+/// Pages with a current entry
+const ENTRY: &[Page] = &[Page::Entries, Page::Search, Page::Reader];
+/// Pages with a current podcast episode
+const EPISODE: &[Page] = &[Page::Entries, Page::Search, Page::Reader, Page::Downloads];
+/// Pages with somewhere to go back to
+const BACK: &[Page] = &[
+    Page::Entries,
+    Page::Search,
+    Page::Reader,
+    Page::Dashboard,
+    Page::Downloads,
+];
+
+/// An action's config name, help text, pages, and default keys
+type ActionInfo = (
+    Action,
+    &'static str,
+    &'static str,
+    &'static [Page],
+    &'static [&'static str],
+);
+
+/// Every action. When a key is bound to several actions on the same page,
+/// the first wins.
+const ACTIONS: &[ActionInfo] = &[
+    (Action::Up, "up", "Move up / scroll up", ALL, &["k", "Up"]),
+    (
+        Action::Down,
+        "down",
+        "Move down / scroll down",
+        ALL,
+        &["j", "Down"],
+    ),
+    (Action::Top, "top", "Go to the top", ALL, &["g", "Home"]),
+    (
+        Action::Bottom,
+        "bottom",
+        "Go to the bottom",
+        ALL,
+        &["G", "End"],
+    ),
+    (
+        Action::PageUp,
+        "page_up",
+        "Page up",
+        ALL,
+        &["PageUp", "Ctrl-u"],
+    ),
+    (
+        Action::PageDown,
+        "page_down",
+        "Page down",
+        ALL,
+        &["PageDown", "Ctrl-d"],
+    ),
+    (
+        Action::Open,
+        "open",
+        "Open the selected feed or entry",
+        LISTS,
+        &["Enter", "l", "Right"],
+    ),
+    (
+        Action::Back,
+        "back",
+        "Back to the previous pane",
+        BACK,
+        &["Esc", "Backspace", "h", "Left", "q"],
+    ),
+    (Action::Quit, "quit", "Quit", ALL, &["q", "Ctrl-c"]),
+    (
+        Action::Refresh,
+        "refresh",
+        "Refresh the selected feed",
+        LISTS,
+        &["r"],
+    ),
+    (
+        Action::RefreshAll,
+        "refresh_all",
+        "Refresh every feed",
+        ALL,
+        &["R"],
+    ),
+    (
+        Action::View,
+        "view",
+        "Cycle entry lists through all, unread only, starred only, and top unread",
+        LISTS,
+        &["v"],
+    ),
+    (
+        Action::Search,
+        "search",
+        "Search all entries (edits the search in results)",
+        ALL,
+        &["/"],
+    ),
+    (Action::Find, "find", "Find in the list", LISTS, &["Ctrl-f"]),
+    (
+        Action::SearchNext,
+        "search_next",
+        "Next find match",
+        LISTS,
+        &["n"],
+    ),
+    (
+        Action::SearchPrev,
+        "search_prev",
+        "Previous find match",
+        LISTS,
+        &["N"],
+    ),
+    (Action::Palette, "palette", "Command palette", ALL, &[":"]),
+    (Action::Help, "help", "Show key bindings", ALL, &["?"]),
+    (
+        Action::NextUnread,
+        "next_unread",
+        "Next unread entry in the feed",
+        &[Page::Reader],
+        &["n"],
+    ),
+    (
+        Action::Random,
+        "random",
+        "Random unread entry",
+        &[Page::Reader],
+        &["m"],
+    ),
+    (
+        Action::OpenBrowser,
+        "open_browser",
+        "Open the entry in the browser",
+        ENTRY,
+        &["o"],
+    ),
+    (
+        Action::OpenLink,
+        "open_link",
+        "Open a link of the article by number",
+        &[Page::Reader],
+        &["L"],
+    ),
+    (
+        Action::ToggleRead,
+        "toggle_read",
+        "Toggle read/unread",
+        ENTRY,
+        &["u"],
+    ),
+    (
+        Action::MarkRead,
+        "mark_read",
+        "Mark read",
+        &[Page::Reader],
+        &["r"],
+    ),
+    (Action::Star, "star", "Star/unstar", ENTRY, &["f"]),
+    (Action::Hide, "hide", "Hide (not interested)", ENTRY, &["x"]),
+    (Action::Tag, "tag", "Add a tag", ENTRY, &["t"]),
+    (Action::Untag, "untag", "Remove a tag", ENTRY, &["T"]),
+    (
+        Action::Mute,
+        "mute",
+        "Mute a topic of the entry (pick a suggested keyword)",
+        ENTRY,
+        &["M"],
+    ),
+    (
+        Action::Undo,
+        "undo",
+        "Undo the last entry change",
+        ENTRY,
+        &["U"],
+    ),
+    (
+        Action::Summarize,
+        "summarize",
+        "Summarize (again) with AI; any key cancels",
+        ENTRY,
+        &["s"],
+    ),
+    (
+        Action::SummaryStyle,
+        "summary_style",
+        "Next summary style (bullets, TL;DR, deep dive) and summarize",
+        ENTRY,
+        &["S"],
+    ),
+    (
+        Action::Save,
+        "save",
+        "Send to the read-it-later service",
+        ENTRY,
+        &["w"],
+    ),
+    (
+        Action::Dashboard,
+        "dashboard",
+        "Dashboard: today's numbers, next runs, and a digest preview",
+        ALL,
+        &["D"],
+    ),
+    (
+        Action::ContinueReading,
+        "continue_reading",
+        "Continue the entry last left partly read",
+        ALL,
+        &["c"],
+    ),
+    (
+        Action::AddFeed,
+        "add_feed",
+        "Add a feed (or a web page advertising one)",
+        &[Page::Feeds],
+        &["a"],
+    ),
+    (
+        Action::EditFeed,
+        "edit_feed",
+        "Edit the selected feed's name, tags, schedule, and AI",
+        &[Page::Feeds],
+        &["e"],
+    ),
+    (
+        Action::DeleteFeed,
+        "delete_feed",
+        "Delete the selected feed and its entries",
+        &[Page::Feeds],
+        &["d"],
+    ),
+    (
+        Action::Related,
+        "related",
+        "Show or hide entries related to this one (by embedding)",
+        &[Page::Reader],
+        &["p"],
+    ),
+    (
+        Action::OpenRelated,
+        "open_related",
+        "Open a related entry by number",
+        &[Page::Reader],
+        &["P"],
+    ),
+    (
+        Action::Translation,
+        "translation",
+        "Switch between a translated entry and its original",
+        &[Page::Reader],
+        &["i"],
+    ),
+    (
+        Action::Play,
+        "play",
+        "Play the entry's podcast episode from where it was left",
+        EPISODE,
+        &["A"],
+    ),
+    (
+        Action::Download,
+        "download",
+        "Download the entry's podcast episode (again)",
+        EPISODE,
+        &["d"],
+    ),
+    (
+        Action::Downloads,
+        "downloads",
+        "Downloads: podcast episodes downloading and downloaded",
+        ALL,
+        &["W"],
+    ),
+];
+
+/// A key with its modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Shift is already part of a character ('G', '?'), and terminals
+        // disagree on whether they report it
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    /// Parse a key as written in `[ui.keys]`: a character, a key name
+    /// (`Enter`, `PageDown`, `F5`, ...), optionally prefixed by `Ctrl-`/`Alt-`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec;
+        while rest.chars().count() > 1 {
+            let Some((prefix, tail)) = rest.split_once('-') else {
+                break;
+            };
+            match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "c" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "m" => modifiers |= KeyModifiers::ALT,
+                _ => break,
+            }
+            rest = tail;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("Unknown key '{}'", spec),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Keys bound to each action
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Keymap {
+    /// The default bindings with `[ui.keys]` applied
+    pub fn from_config(config: &UiConfig) -> Result<Self> {
+        let mut bindings = ACTIONS
+            .iter()
+            .map(|(action, _, _, _, keys)| {
+                let keys = keys
+                    .iter()
+                    .map(|k| Key::parse(k).expect("valid default key"));
+                (*action, keys.collect())
+            })
+            .collect::<Vec<(Action, Vec<Key>)>>();
+
+        for (name, keys) in &config.keys {
+            let index = ACTIONS
+                .iter()
+                .position(|(_, n, ..)| n == name)
+                .with_context(|| format!("Unknown action '{}' in [ui.keys]", name))?;
+            bindings[index].1 = keys
+                .keys()
+                .into_iter()
+                .map(Key::parse)
+                .collect::<Result<_>>()
+                .with_context(|| format!("Invalid [ui.keys] binding for '{}'", name))?;
+        }
+        Ok(Self { bindings })
+    }
+
+    /// The action `key` triggers on `page`
+    pub fn action(&self, page: Page, key: impl Into<Key>) -> Option<Action> {
+        let key = key.into();
+        self.bindings
+            .iter()
+            .zip(ACTIONS)
+            .find(|((_, keys), (_, _, _, pages, _))| pages.contains(&page) && keys.contains(&key))
+            .map(|((action, _), _)| *action)
+    }
+
+    /// The first key bound to `action`, for the help bar
+    pub fn key(&self, action: Action) -> Option<Key> {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, keys)| keys.first().copied())
+    }
+
+    /// Bound keys and descriptions of the actions available on `page`
+    pub fn help(&self, page: Page) -> Vec<(String, &'static str)> {
+        self.bindings
+            .iter()
+            .zip(ACTIONS)
+            .filter(|((_, keys), (_, _, _, pages, _))| pages.contains(&page) && !keys.is_empty())
+            .map(|((_, keys), (_, _, description, _, _))| {
+                let keys = keys.iter().map(Key::to_string).collect::<Vec<_>>();
+                (keys.join(" "), *description)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use presser_config::KeyList;
+
+    #[test]
+    fn test_keymap() {
+        let press = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let keymap = Keymap::from_config(&UiConfig::default()).unwrap();
+        assert_eq!(keymap.action(Page::Feeds, press('q')), Some(Action::Quit));
+        assert_eq!(keymap.action(Page::Entries, press('q')), Some(Action::Back));
+        assert_eq!(
+            keymap.action(Page::Dashboard, press('q')),
+            Some(Action::Back)
+        );
+        assert_eq!(
+            keymap.action(Page::Reader, press('D')),
+            Some(Action::Dashboard)
+        );
+        assert_eq!(
+            keymap.action(Page::Entries, press('n')),
+            Some(Action::SearchNext)
+        );
+        assert_eq!(
+            keymap.action(Page::Reader, press('n')),
+            Some(Action::NextUnread)
+        );
+        let shifted = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action(Page::Reader, shifted), Some(Action::Bottom));
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action(Page::Feeds, ctrl_d), Some(Action::PageDown));
+        assert_eq!(
+            keymap.action(Page::Reader, press('/')),
+            Some(Action::Search)
+        );
+        assert_eq!(keymap.action(Page::Search, press('f')), Some(Action::Star));
+        assert_eq!(
+            keymap.action(Page::Feeds, press('d')),
+            Some(Action::DeleteFeed)
+        );
+        assert_eq!(
+            keymap.action(Page::Entries, press('d')),
+            Some(Action::Download)
+        );
+        assert_eq!(
+            keymap.action(Page::Downloads, press('A')),
+            Some(Action::Play)
+        );
+
+        let mut config = UiConfig::default();
+        config.keys.insert(
+            "palette".into(),
+            KeyList::Many(vec![";".into(), "F2".into()]),
+        );
+        config
+            .keys
+            .insert("down".into(), KeyList::One("Ctrl-n".into()));
+        let keymap = Keymap::from_config(&config).unwrap();
+        assert_eq!(
+            keymap.action(Page::Feeds, press(';')),
+            Some(Action::Palette)
+        );
+        assert_eq!(keymap.action(Page::Feeds, press(':')), None);
+        assert_eq!(keymap.action(Page::Feeds, press('j')), None);
+        assert_eq!(keymap.key(Action::Down).unwrap().to_string(), "Ctrl-n");
+        assert!(keymap
+            .help(Page::Feeds)
+            .contains(&("; F2".into(), "Command palette")));
+
+        config.keys.insert("fly".into(), KeyList::One("f".into()));
+        let error = Keymap::from_config(&config).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown action 'fly' in [ui.keys]");
+        assert!(Key::parse("Hyper-x").is_err());
+        assert_eq!(Key::parse("-").unwrap().to_string(), "-");
+    }
+}
@@ -1,6 +1,21 @@
 //! Terminal UI module
 
+mod actions;
 pub mod app;
+mod dashboard;
+mod downloads;
+mod entries;
+mod feed_form;
+mod images;
+mod keys;
+mod palette;
+mod reader;
+mod refresh;
+mod related;
+mod render;
+mod search;
+mod sidebar;
+mod theme;
 pub mod widgets;
 
 pub use app::App;
@@ -0,0 +1,632 @@
+//! The `:` command palette
+
+use anyhow::{bail, Context, Result};
+use chrono::Duration;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use presser_db::MuteKind;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, ListState, Paragraph};
+use ratatui::Frame;
+
+use super::actions::Change;
+use super::app::{App, Page};
+use super::sidebar;
+use crate::commands::parse_age;
+
+/// A parsed palette command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Open the selected item, or the Nth (1-based) item of the list
+    Open(Option<usize>),
+    /// Open the Nth (1-based) link of the article being read
+    Link(usize),
+    /// Refresh the selected feed, or every feed
+    Refresh { all: bool },
+    /// Add a user tag to the current entry
+    Tag(String),
+    /// Remove a tag from the current entry
+    Untag(String),
+    /// Show only list items containing the text; `None` clears the filter
+    Filter(Option<String>),
+    /// Jump to the entries of the feed matching the text
+    Goto(String),
+    /// Search for entries similar to the current one
+    Similar,
+    /// Open the Nth (1-based) entry of the reader's related panel
+    Related(usize),
+    /// Save the current search as a smart folder
+    SaveSearch(String),
+    /// Delete a smart folder
+    DropFolder(String),
+    /// Mute a suggested topic (by number) or any text, for a while or
+    /// until unmuted
+    Mute {
+        topic: String,
+        duration: Option<Duration>,
+    },
+    /// Show key bindings
+    Help,
+    /// Quit
+    Quit,
+}
+
+/// Palette commands: name, arguments, and description
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    ("open", "[N]", "Open the selected item, or item N"),
+    ("link", "N", "Open link N of the article"),
+    (
+        "refresh",
+        "[all]",
+        "Refresh the selected feed, or all feeds",
+    ),
+    ("tag", "NAME", "Tag the current entry"),
+    ("untag", "NAME", "Remove a tag from the current entry"),
+    (
+        "filter",
+        "[TEXT]",
+        "Filter the list by title (no text clears)",
+    ),
+    ("goto", "FEED", "Go to a feed by ID or title"),
+    ("similar", "", "Entries similar to the current one"),
+    ("related", "N", "Open related entry N"),
+    ("save", "NAME", "Save the search as a smart folder"),
+    ("drop", "NAME", "Delete a smart folder"),
+    ("mute", "N|TEXT", "Mute topic N or text (add e.g. 30d)"),
+    ("help", "", "Show key bindings"),
+    ("quit", "", "Quit"),
+];
+
+/// Parse a palette line; a command may be shortened to any unambiguous prefix
+pub fn parse(input: &str) -> Result<Command> {
+    let input = input.trim();
+    let (word, arg) = input.split_once(' ').unwrap_or((input, ""));
+    let arg = arg.trim();
+    let name = match complete(word).as_slice() {
+        [(name, ..)] => *name,
+        [] => bail!("Unknown command '{}'", word),
+        // An exact name wins over longer names it prefixes
+        matches => match matches.iter().find(|(name, ..)| *name == word) {
+            Some((name, ..)) => *name,
+            None => bail!("Ambiguous command '{}'", word),
+        },
+    };
+    let required = |arg: &str| {
+        if arg.is_empty() {
+            bail!("Usage: {} {}", name, usage(name))
+        }
+        Ok(arg.to_string())
+    };
+    Ok(match name {
+        "open" if arg.is_empty() => Command::Open(None),
+        "open" => match arg.parse() {
+            Ok(n) if n > 0 => Command::Open(Some(n)),
+            _ => bail!("Not an item number: {}", arg),
+        },
+        "link" => match arg.parse() {
+            Ok(n) if n > 0 => Command::Link(n),
+            _ => bail!("Usage: link N"),
+        },
+        "refresh" => match arg {
+            "" => Command::Refresh { all: false },
+            "all" => Command::Refresh { all: true },
+            _ => bail!("Usage: refresh [all]"),
+        },
+        "tag" => Command::Tag(required(arg)?),
+        "untag" => Command::Untag(required(arg)?),
+        "filter" => Command::Filter(Some(arg.to_string()).filter(|a| !a.is_empty())),
+        "goto" => Command::Goto(required(arg)?),
+        "similar" => Command::Similar,
+        "related" => match arg.parse() {
+            Ok(n) if n > 0 => Command::Related(n),
+            _ => bail!("Usage: related N"),
+        },
+        "save" => Command::SaveSearch(required(arg)?),
+        "drop" => Command::DropFolder(required(arg)?),
+        "mute" => {
+            let arg = required(arg)?;
+            // A trailing age (optionally after "for") limits the mute
+            let (topic, duration) = match arg.rsplit_once(' ') {
+                Some((rest, last)) => match parse_age(last) {
+                    Ok(age) => (
+                        rest.trim_end().strip_suffix(" for").unwrap_or(rest),
+                        Some(age),
+                    ),
+                    Err(_) => (arg.as_str(), None),
+                },
+                None => (arg.as_str(), None),
+            };
+            Command::Mute {
+                topic: topic.trim().to_string(),
+                duration,
+            }
+        }
+        "help" => Command::Help,
+        _ => Command::Quit,
+    })
+}
+
+/// Commands whose name starts with `prefix`
+pub fn complete(prefix: &str) -> Vec<&'static (&'static str, &'static str, &'static str)> {
+    COMMANDS
+        .iter()
+        .filter(|(name, ..)| name.starts_with(prefix))
+        .collect()
+}
+
+fn usage(name: &str) -> &'static str {
+    COMMANDS
+        .iter()
+        .find(|(n, ..)| *n == name)
+        .map_or("", |(_, usage, _)| usage)
+}
+
+/// What the bottom line is being used to type
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum PromptKind {
+    /// Jump to list items matching the text as it is typed
+    Find,
+    /// `/`: a full-text search
+    Search,
+    /// `:`: a palette command
+    Palette,
+}
+
+/// Text being typed into the bottom line
+pub(super) struct Prompt {
+    pub(super) kind: PromptKind,
+    pub(super) text: String,
+    /// Selection when the prompt opened, restored when a find is cancelled
+    origin: Option<usize>,
+}
+
+impl App {
+    /// Palette commands matching what has been typed, above the bottom bar
+    pub(super) fn render_palette(&self, frame: &mut Frame, prompt: &Prompt) {
+        let word = prompt.text.split_whitespace().next().unwrap_or("");
+        let matches = complete(word);
+        if matches.is_empty() {
+            return;
+        }
+        let mut lines: Vec<Line> = matches
+            .iter()
+            .map(|(name, args, description)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {:<8}", name),
+                        Style::default()
+                            .fg(self.theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{:<8}", args),
+                        Style::default().fg(self.theme.highlight),
+                    ),
+                    Span::raw(*description),
+                ])
+            })
+            .collect();
+        let mut title = " Commands ";
+        // The mute action's suggestions, numbered for `:mute N`
+        if matches!(matches.as_slice(), [("mute", ..)]) && !self.mute_topics.is_empty() {
+            title = " Mute ";
+            lines.extend(
+                self.mute_topics
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (kind, topic))| {
+                        Line::from(vec![
+                            Span::styled(
+                                format!(" [{}] ", i + 1),
+                                Style::default().fg(self.theme.accent),
+                            ),
+                            Span::styled(
+                                if *kind == MuteKind::Tag { "tag " } else { "" },
+                                Style::default().fg(self.theme.dim),
+                            ),
+                            Span::raw(topic.clone()),
+                        ])
+                    }),
+            );
+        }
+        let area = frame.size();
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(1));
+        let popup = Rect::new(
+            area.x,
+            area.bottom().saturating_sub(height + 1),
+            area.width.min(60),
+            height,
+        );
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(lines).style(self.theme.base()).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme.border))
+                    .title(title),
+            ),
+            popup,
+        );
+    }
+
+    pub(super) fn open_palette_with(&mut self, text: &str) {
+        self.open_prompt(PromptKind::Palette);
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.text = text.to_string();
+        }
+    }
+
+    pub(super) fn open_prompt(&mut self, kind: PromptKind) {
+        self.mute_topics.clear();
+        let origin = self.list_state().and_then(|state| state.selected());
+        self.prompt = Some(Prompt {
+            kind,
+            text: String::new(),
+            origin,
+        });
+    }
+
+    /// Keys typed into the search or palette line
+    pub(super) async fn handle_prompt_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => {
+                // Cancelling a find goes back to where it started
+                let prompt = self.prompt.take().unwrap();
+                if prompt.kind == PromptKind::Find {
+                    if let Some(state) = self.list_state() {
+                        state.select(prompt.origin);
+                    }
+                }
+            }
+            KeyCode::Enter => {
+                let prompt = self.prompt.take().unwrap();
+                match prompt.kind {
+                    PromptKind::Find => {
+                        self.search = Some(prompt.text).filter(|t| !t.is_empty());
+                    }
+                    PromptKind::Search | PromptKind::Palette if prompt.text.trim().is_empty() => {}
+                    PromptKind::Search => {
+                        let title = self
+                            .results
+                            .as_ref()
+                            .filter(|r| r.query.as_ref() == Some(&prompt.text))
+                            .map(|r| r.title.clone());
+                        if let Err(e) = self.run_search(prompt.text, title).await {
+                            self.status = Some(format!(" {:#}", e));
+                        }
+                    }
+                    PromptKind::Palette => {
+                        let result = match parse(&prompt.text) {
+                            Ok(command) => self.run_command(command).await,
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = result {
+                            self.status = Some(format!(" {:#}", e));
+                        }
+                    }
+                }
+            }
+            KeyCode::Tab if prompt.kind == PromptKind::Palette => {
+                if let [(name, ..)] = complete(prompt.text.trim()).as_slice() {
+                    prompt.text = format!("{} ", name);
+                }
+            }
+            KeyCode::Backspace => {
+                if prompt.text.pop().is_none() {
+                    self.prompt = None;
+                } else if prompt.kind == PromptKind::Find {
+                    self.search_from_origin();
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.text.push(c);
+                if prompt.kind == PromptKind::Find {
+                    self.search_from_origin();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn run_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Open(n) => {
+                if let Some(n) = n {
+                    let len = self.list_titles().len();
+                    anyhow::ensure!(n <= len, "No item {} (the list has {})", n, len);
+                    if let Some(state) = self.list_state() {
+                        state.select(Some(n - 1));
+                    }
+                }
+                match self.page {
+                    Page::Feeds => self.open_selected_feed().await?,
+                    Page::Entries | Page::Search => self.open_selected_entry().await?,
+                    Page::Reader | Page::Dashboard | Page::Downloads => {}
+                }
+            }
+            Command::Refresh { all: false } => self.refresh_current_feed(),
+            Command::Refresh { all: true } => self.refresh_all(),
+            Command::Tag(name) => {
+                self.change_target(Change::Tag { name, added: true })
+                    .await?
+            }
+            Command::Untag(name) => {
+                self.change_target(Change::Tag { name, added: false })
+                    .await?
+            }
+            Command::Filter(text) => {
+                if self.page == Page::Feeds {
+                    self.feed_filter = text;
+                    self.load_feeds().await?;
+                    self.feed_state.select(if self.sidebar.is_empty() {
+                        None
+                    } else {
+                        Some(0)
+                    });
+                } else if self.results.is_some() {
+                    anyhow::bail!("Add words or filters to the search (/) instead");
+                } else {
+                    self.entry_filter = text;
+                    self.load_entries().await?;
+                    self.page = Page::Entries;
+                }
+            }
+            Command::Goto(query) => {
+                let query_lower = query.to_lowercase();
+                let idx = self
+                    .feeds
+                    .iter()
+                    .position(|f| f.id == query)
+                    .or_else(|| {
+                        self.feeds
+                            .iter()
+                            .position(|f| f.title.to_lowercase().contains(&query_lower))
+                    })
+                    .with_context(|| format!("No feed matches '{}'", query))?;
+                if !self.sidebar.contains(&sidebar::Item::Feed(idx)) {
+                    // Filtered out of the sidebar
+                    self.feed_filter = None;
+                    self.load_feeds().await?;
+                }
+                self.feed_state.select(
+                    self.sidebar
+                        .iter()
+                        .position(|item| *item == sidebar::Item::Feed(idx)),
+                );
+                self.entry_filter = None;
+                self.current_entry = None;
+                self.open_selected_feed().await?;
+            }
+            Command::Link(n) => {
+                let links = self
+                    .article
+                    .as_ref()
+                    .filter(|_| self.page == Page::Reader)
+                    .map(|a| a.article.links.as_slice())
+                    .context("Open an entry to follow its links")?;
+                let link = links
+                    .get(n - 1)
+                    .with_context(|| format!("No link {} (the article has {})", n, links.len()))?;
+                open::that(link).with_context(|| format!("Failed to open {}", link))?;
+            }
+            Command::Similar => self.show_similar().await?,
+            Command::Related(n) => {
+                let related = self
+                    .related
+                    .as_ref()
+                    .filter(|_| self.page == Page::Reader)
+                    .context("Open an entry with an embedding to jump to related entries")?;
+                let item = related.get(n - 1).with_context(|| {
+                    format!("No related entry {} (there are {})", n, related.len())
+                })?;
+                let entry_id = item.entry_id.clone();
+                self.save_position().await;
+                self.load_entry_by_id(&entry_id).await?;
+            }
+            Command::SaveSearch(name) => {
+                let results = self
+                    .results
+                    .as_ref()
+                    .context("Search (/) first, then save the results")?;
+                let query = results
+                    .query
+                    .clone()
+                    .context("Only searches can be saved, not similar entries")?;
+                self.engine
+                    .database()
+                    .save_smart_folder(&name, &query)
+                    .await?;
+                if let Some(results) = self.results.as_mut() {
+                    results.title = name.clone();
+                }
+                self.load_feeds().await?;
+                self.status = Some(format!(" Saved smart folder '{}'", name));
+            }
+            Command::DropFolder(name) => {
+                anyhow::ensure!(
+                    self.engine.database().delete_smart_folder(&name).await?,
+                    "No smart folder '{}'",
+                    name
+                );
+                self.load_feeds().await?;
+                self.status = Some(format!(" Deleted smart folder '{}'", name));
+            }
+            Command::Mute { topic, duration } => {
+                let (kind, pattern) = match topic.parse::<usize>() {
+                    Ok(n) => self
+                        .mute_topics
+                        .get(n.wrapping_sub(1))
+                        .cloned()
+                        .with_context(|| {
+                            format!("No topic {} (press M on an entry for suggestions)", n)
+                        })?,
+                    Err(_) => (MuteKind::Keyword, topic),
+                };
+                self.engine.mute(kind, &pattern, duration).await?;
+                let until = match duration {
+                    Some(duration) => format!(
+                        "until {}",
+                        (chrono::Local::now() + duration).format("%b %-d")
+                    ),
+                    None => "until unmuted (presser unmute)".to_string(),
+                };
+                let what = if kind == MuteKind::Tag { "tag " } else { "" };
+                self.status = Some(format!(" Muted {}'{}' {}", what, pattern, until));
+            }
+            Command::Help => self.show_help = true,
+            Command::Quit => self.should_quit = true,
+        }
+        Ok(())
+    }
+
+    /// Selection state of the list on the current page
+    fn list_state(&mut self) -> Option<&mut ListState> {
+        match self.page {
+            Page::Feeds => Some(&mut self.feed_state),
+            Page::Entries | Page::Search => Some(&mut self.entry_state),
+            Page::Downloads => Some(&mut self.downloads.state),
+            Page::Reader | Page::Dashboard => None,
+        }
+    }
+
+    /// Titles of the list on the current page, for searching
+    fn list_titles(&self) -> Vec<&str> {
+        match self.page {
+            Page::Feeds => self
+                .sidebar
+                .iter()
+                .map(|item| self.item_name(*item))
+                .collect(),
+            Page::Entries | Page::Search => self.entries.iter().map(|e| e.title.as_str()).collect(),
+            Page::Downloads => self
+                .downloads
+                .items
+                .iter()
+                .map(|d| d.entry_title.as_str())
+                .collect(),
+            Page::Reader | Page::Dashboard => Vec::new(),
+        }
+    }
+
+    /// Select the first match of the query being typed, starting where the search began
+    fn search_from_origin(&mut self) {
+        let Some(prompt) = &self.prompt else {
+            return;
+        };
+        let start = prompt.origin.unwrap_or(0);
+        let found = find_match(&self.list_titles(), &prompt.text, start, true);
+        if let (Some(i), Some(state)) = (found, self.list_state()) {
+            state.select(Some(i));
+        }
+    }
+
+    /// Select the next (or previous) match of the last search
+    pub(super) fn search_step(&mut self, forward: bool) {
+        let Some(query) = self.search.clone() else {
+            return;
+        };
+        let Some(current) = self.list_state().and_then(|state| state.selected()) else {
+            return;
+        };
+        let len = self.list_titles().len();
+        let start = if forward {
+            current + 1
+        } else {
+            current + len.max(1) - 1
+        };
+        match find_match(&self.list_titles(), &query, start, forward) {
+            Some(i) => {
+                if let Some(state) = self.list_state() {
+                    state.select(Some(i));
+                }
+            }
+            None => self.status = Some(format!(" Not found: {}", query)),
+        }
+    }
+}
+
+/// Index of the first title containing `query` (ignoring case), looking from
+/// `start` forwards or backwards and wrapping around
+fn find_match(titles: &[&str], query: &str, start: usize, forward: bool) -> Option<usize> {
+    let len = titles.len();
+    let query = query.to_lowercase();
+    (0..len)
+        .map(|step| {
+            if forward {
+                (start + step) % len
+            } else {
+                (start + len * 2 - step) % len
+            }
+        })
+        .find(|&i| titles[i].to_lowercase().contains(&query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("open").unwrap(), Command::Open(None));
+        assert_eq!(parse(" o 3 ").unwrap(), Command::Open(Some(3)));
+        assert_eq!(parse("ref all").unwrap(), Command::Refresh { all: true });
+        assert_eq!(parse("tag later").unwrap(), Command::Tag("later".into()));
+        assert_eq!(parse("u later").unwrap(), Command::Untag("later".into()));
+        assert_eq!(parse("filter").unwrap(), Command::Filter(None));
+        assert_eq!(
+            parse("f rust async").unwrap(),
+            Command::Filter(Some("rust async".into()))
+        );
+        assert_eq!(
+            parse("g Rust Blog").unwrap(),
+            Command::Goto("Rust Blog".into())
+        );
+        assert_eq!(parse("q").unwrap(), Command::Quit);
+        assert_eq!(
+            parse("sa Rust news").unwrap(),
+            Command::SaveSearch("Rust news".into())
+        );
+        assert_eq!(parse("si").unwrap(), Command::Similar);
+        assert_eq!(parse("l 2").unwrap(), Command::Link(2));
+        assert_eq!(parse("rel 1").unwrap(), Command::Related(1));
+        assert_eq!(parse("link").unwrap_err().to_string(), "Usage: link N");
+        assert_eq!(
+            parse("mute world cup for 30d").unwrap(),
+            Command::Mute {
+                topic: "world cup".into(),
+                duration: Some(Duration::days(30)),
+            }
+        );
+        assert_eq!(
+            parse("mute 2 2w").unwrap(),
+            Command::Mute {
+                topic: "2".into(),
+                duration: Some(Duration::weeks(2)),
+            }
+        );
+        assert_eq!(
+            parse("mute Rust 2024").unwrap(),
+            Command::Mute {
+                topic: "Rust 2024".into(),
+                duration: None,
+            }
+        );
+
+        assert_eq!(parse("tag").unwrap_err().to_string(), "Usage: tag NAME");
+        assert_eq!(
+            parse("open 0").unwrap_err().to_string(),
+            "Not an item number: 0"
+        );
+        assert_eq!(
+            parse("zap").unwrap_err().to_string(),
+            "Unknown command 'zap'"
+        );
+        assert_eq!(complete("").len(), COMMANDS.len());
+        assert_eq!(complete("re")[0].0, "refresh");
+    }
+}
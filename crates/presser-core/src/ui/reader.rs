@@ -0,0 +1,789 @@
+//! The reader: an entry's details, summary, and article, with the related
+//! panel beside or below it
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use presser_ai::{SummaryOptions, SummaryStyle};
+use presser_db::{Entry, Summary};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::Frame;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::actions::Change;
+use super::app::{App, Page, SPINNER};
+use super::images::{self, ImageState, Picture, ShownImage};
+use super::keys::Action;
+use super::palette::PromptKind;
+use super::related;
+use super::render::{self, Article};
+use super::sidebar::Source;
+use super::theme::Theme;
+use crate::digest::comments_label;
+
+/// Width of the related panel beside the article
+const RELATED_WIDTH: u16 = 40;
+
+/// Narrowest screen with the related panel beside the article (not below it)
+const RELATED_BESIDE_MIN_WIDTH: u16 = 100;
+
+/// Width of the labels before an entry's details
+const LABEL_WIDTH: usize = 8;
+
+/// Reader display configuration
+pub(super) struct ReaderConfig {
+    /// Left/right margin for body text
+    margin: u16,
+}
+
+/// A summary being generated for the reader
+pub(super) struct PendingSummary {
+    pub(super) entry_id: String,
+    style: Option<SummaryStyle>,
+    /// Text received so far
+    text: String,
+    chunks: mpsc::UnboundedReceiver<String>,
+    pub(super) task: JoinHandle<Result<(Entry, Summary)>>,
+}
+
+/// The article being read, laid out for the reader's width
+pub(super) struct LaidOut {
+    entry_id: String,
+    width: usize,
+    pub(super) article: Article,
+}
+
+/// How far the entry being read is scrolled, saved when it is left
+pub(super) struct Position {
+    entry_id: String,
+    pub(super) scroll: u16,
+    pub(super) max_scroll: u16,
+}
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        Self { margin: 2 }
+    }
+}
+
+impl App {
+    /// Save the reading position of an entry that was left, and scroll a
+    /// newly opened one back to where it was left partly read
+    pub(super) async fn track_position(&mut self) {
+        let current = self.current_entry.as_ref().map(|e| e.id.clone());
+        if self.position.as_ref().map(|p| &p.entry_id) == current.as_ref() {
+            return;
+        }
+        self.save_position().await;
+        let Some(entry_id) = current else {
+            return;
+        };
+        match self.engine.database().get_reading_position(&entry_id).await {
+            Ok(Some(saved)) if saved.is_partial() => {
+                self.scroll_offset = saved.scroll.clamp(0, i64::from(u16::MAX)) as u16;
+            }
+            Ok(_) => {}
+            Err(e) => self.toast(format!("Failed to get the reading position: {:#}", e), true),
+        }
+        self.position = Some(Position {
+            entry_id,
+            scroll: self.scroll_offset,
+            max_scroll: 0,
+        });
+    }
+
+    pub(super) async fn save_position(&mut self) {
+        let Some(position) = self.position.take() else {
+            return;
+        };
+        let saved = self
+            .engine
+            .database()
+            .save_reading_position(
+                &position.entry_id,
+                position.scroll.into(),
+                position.max_scroll.into(),
+            )
+            .await;
+        if let Err(e) = saved {
+            self.toast(
+                format!("Failed to save the reading position: {:#}", e),
+                true,
+            );
+        }
+    }
+
+    pub(super) fn render_reader(&mut self, frame: &mut Frame) {
+        if self.current_entry.is_none() {
+            return;
+        }
+
+        let area = frame.size();
+        let margin = self.reader_config.margin;
+
+        let help_height = 1u16;
+
+        // Layout: scrollable content + help bar at bottom
+        let chunks = Layout::new(
+            Direction::Vertical,
+            [Constraint::Min(0), Constraint::Length(help_height)],
+        )
+        .split(area);
+
+        // Related entries beside the article on wide screens, else below it
+        let related_limit = self.engine.config().ui.related_entries;
+        let (article_area, related_area) =
+            if self.show_related && related_limit > 0 && self.current_entry.is_some() {
+                let split = if area.width >= RELATED_BESIDE_MIN_WIDTH {
+                    Layout::new(
+                        Direction::Horizontal,
+                        [Constraint::Min(0), Constraint::Length(RELATED_WIDTH)],
+                    )
+                    .split(chunks[0])
+                } else {
+                    let rows = self
+                        .related
+                        .as_ref()
+                        .filter(|r| !r.is_empty())
+                        .map_or(1, |r| r.len() * 2);
+                    Layout::new(
+                        Direction::Vertical,
+                        [Constraint::Min(0), Constraint::Length(rows as u16 + 2)],
+                    )
+                    .split(chunks[0])
+                };
+                (split[0], Some(split[1]))
+            } else {
+                (chunks[0], None)
+            };
+
+        // Content area with margins
+        let content_area = if margin > 0 && area.width > margin * 2 {
+            let inner = Layout::new(
+                Direction::Horizontal,
+                [
+                    Constraint::Length(margin),
+                    Constraint::Min(0),
+                    Constraint::Length(margin),
+                ],
+            )
+            .split(article_area);
+            inner[1]
+        } else {
+            article_area
+        };
+
+        let available_width = content_area.width as usize;
+        self.layout_article(available_width);
+        let Some(entry) = &self.current_entry else {
+            return;
+        };
+        let value_width = if available_width > LABEL_WIDTH {
+            available_width - LABEL_WIDTH
+        } else {
+            available_width.max(20)
+        };
+
+        let label_style = Style::default().fg(self.theme.accent);
+        let value_style = Style::default().fg(self.theme.highlight);
+        let link_style = Style::default().fg(self.theme.link);
+        let detail = |label: &str, value: &str, style: Style| {
+            details(label, value, value_width, label_style, style)
+        };
+
+        let date_str = entry
+            .published
+            .map(|d| d.format("%a, %d %b %Y %H:%M:%S %z").to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let author_str = entry.author.as_deref().unwrap_or("Unknown");
+
+        let mut all_lines = Vec::with_capacity(10);
+        all_lines.extend(detail("Feed:", &self.current_feed_title, value_style));
+        all_lines.extend(detail("Title:", &entry.title, value_style));
+        all_lines.extend(detail("Author:", author_str, value_style));
+        all_lines.extend(detail("Date:", &date_str, value_style));
+        all_lines.extend(detail("Link:", &entry.url, link_style));
+        if let Some(discussion) = self.entry_discussions.get(&entry.id) {
+            let thread = match (discussion.comments, &discussion.url) {
+                (Some(n), Some(url)) => format!("{}  {}", comments_label(n), url),
+                (Some(n), None) => comments_label(n),
+                (None, Some(url)) => url.clone(),
+                (None, None) => String::new(),
+            };
+            all_lines.extend(detail("Thread:", &thread, link_style));
+        }
+        if let Some(sources) = self.entry_sources.get(&entry.id) {
+            all_lines.extend(detail("Also:", &sources.join(", "), value_style));
+        }
+        if let Some(tags) = self.entry_tags.get(&entry.id).filter(|t| !t.is_empty()) {
+            all_lines.extend(detail(
+                "Tags:",
+                &tags.join(", "),
+                Style::default().fg(self.theme.tag),
+            ));
+        }
+        if let Some(translation) = self.translation.as_ref().filter(|t| t.entry_id == entry.id) {
+            let source = crate::translate::language_name(&translation.source_language)
+                .unwrap_or(&translation.source_language);
+            let shown = if self.show_original {
+                format!("{} original", source)
+            } else {
+                format!("translated from {}", source)
+            };
+            all_lines.extend(detail("Lang:", &shown, value_style));
+        }
+        if let Some(revision) = self.entry_revisions.get(&entry.id) {
+            let updated = format!(
+                "{}  {}",
+                revision.created_at.format("%Y-%m-%d %H:%M"),
+                revision.change_label()
+            );
+            all_lines.extend(detail("Edited:", &updated, value_style));
+        }
+        if entry.starred {
+            all_lines.push(Line::from(Span::styled(
+                "★ Starred",
+                Style::default().fg(self.theme.highlight),
+            )));
+        }
+
+        // AI summary, or the one being generated
+        let pending = self
+            .pending_summary
+            .as_ref()
+            .filter(|p| p.entry_id == entry.id);
+        let summary = pending.map(|p| p.text.as_str()).or(self.summary.as_deref());
+        if pending.is_some() || summary.is_some() {
+            let mut header = vec![Span::styled(
+                "Summary",
+                label_style.add_modifier(Modifier::BOLD),
+            )];
+            if let Some(pending) = pending {
+                header.push(Span::styled(
+                    format!(
+                        " {} {}",
+                        SPINNER[self.tick % SPINNER.len()],
+                        style_label(pending.style)
+                    ),
+                    Style::default().fg(self.theme.dim),
+                ));
+            }
+            all_lines.push(Line::from(""));
+            all_lines.push(Line::from(header));
+            for line in summary.unwrap_or_default().lines() {
+                for line in textwrap::wrap(line, available_width.max(1)) {
+                    all_lines.push(Line::from(Span::styled(
+                        line.into_owned(),
+                        Style::default().fg(self.theme.summary),
+                    )));
+                }
+            }
+        }
+
+        all_lines.push(Line::from(""));
+
+        // Content - the translation unless the original was asked for, else
+        // the rendered HTML, else content_text, else the summary.
+        // Every line is wrapped already, so lines are rows.
+        let content_start = all_lines.len();
+        let article = self.article.as_ref().filter(|a| a.entry_id == entry.id);
+        let translation = self
+            .translation
+            .as_ref()
+            .filter(|t| t.entry_id == entry.id && !self.show_original);
+        match (translation, article) {
+            (Some(translation), _) => {
+                all_lines.extend(style_content(
+                    &translation.content_text,
+                    available_width,
+                    &self.theme,
+                ));
+            }
+            (None, Some(laid_out)) => all_lines.extend(laid_out.article.lines.iter().cloned()),
+            (None, None) => {
+                let content = entry
+                    .content_text
+                    .as_deref()
+                    .or(entry.summary.as_deref())
+                    .unwrap_or("[No content available]");
+                all_lines.extend(style_content(content, available_width, &self.theme));
+            }
+        }
+
+        let height = content_area.height as usize;
+        let max_scroll = all_lines.len().saturating_sub(height);
+        let scroll = (self.scroll_offset as usize).min(max_scroll);
+        self.scroll_offset = scroll as u16;
+        self.reader_max_scroll = max_scroll.min(u16::MAX as usize) as u16;
+
+        // Images wholly in view, unless a popup is over them
+        let popup = self.show_help
+            || self
+                .prompt
+                .as_ref()
+                .is_some_and(|p| p.kind == PromptKind::Palette);
+        let shown: Vec<ShownImage> = article
+            .filter(|_| !popup)
+            .into_iter()
+            .flat_map(|laid_out| &laid_out.article.images)
+            .filter_map(|placement| {
+                let top = (content_start + placement.line).checked_sub(scroll)?;
+                (top + placement.rows as usize <= height).then(|| ShownImage {
+                    url: placement.url.clone(),
+                    area: Rect::new(
+                        content_area.x + placement.column,
+                        content_area.y + top as u16,
+                        placement.cols,
+                        placement.rows,
+                    ),
+                })
+            })
+            .collect();
+
+        let paragraph = Paragraph::new(all_lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll as u16, 0));
+
+        frame.render_widget(paragraph, content_area);
+        self.shown_images = shown;
+
+        if let Some(related_area) = related_area {
+            let lines = related::lines(
+                self.related.as_deref(),
+                |feed_id| self.feed_title(feed_id).to_string(),
+                related_area.width.saturating_sub(2) as usize,
+                &self.theme,
+            );
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.border))
+                .title(" Related ");
+            frame.render_widget(
+                Paragraph::new(lines).style(self.theme.base()).block(block),
+                related_area,
+            );
+        }
+
+        // Help bar at bottom with colored background
+        let help = self.help_bar(&[
+            (Action::Back, "back"),
+            (Action::Down, "scroll"),
+            (Action::NextUnread, "next"),
+            (Action::MarkRead, "read"),
+            (Action::Random, "random"),
+            (Action::OpenBrowser, "open"),
+            (Action::OpenLink, "link"),
+            (Action::ToggleRead, "toggle"),
+            (Action::Star, "star"),
+            (Action::Tag, "tag"),
+            (Action::Undo, "undo"),
+            (Action::Summarize, "summarize"),
+            (Action::SummaryStyle, "style"),
+            (Action::Save, "save"),
+            (Action::Related, "related"),
+            (Action::Help, "help"),
+        ]);
+
+        frame.render_widget(self.bottom_bar(help), chunks[1]);
+    }
+
+    pub(super) async fn handle_reader_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Back => {
+                // Go back to the list the entry was opened from
+                self.page = self.list_page();
+                self.current_entry = None;
+                self.summary = None;
+                self.scroll_offset = 0;
+            }
+            Action::Up => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            Action::Down => {
+                self.scroll_offset = self.scroll_offset.saturating_add(1);
+            }
+            Action::PageUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(20);
+            }
+            Action::PageDown => {
+                self.scroll_offset = self.scroll_offset.saturating_add(20);
+            }
+            Action::Top => {
+                self.scroll_offset = 0;
+            }
+            Action::Bottom => {
+                self.scroll_offset = u16::MAX;
+            }
+            Action::OpenBrowser => {
+                if let Some(entry) = &self.current_entry {
+                    let _ = open::that(&entry.url);
+                }
+            }
+            Action::MarkRead => {
+                if let Some(entry) = &self.current_entry {
+                    if !entry.read {
+                        let entry_id = entry.id.clone();
+                        self.change_entry(&entry_id, Change::Read(true)).await;
+                    }
+                }
+            }
+            Action::OpenLink => self.open_palette_with("link "),
+            Action::Related => {
+                if self.engine.config().ui.related_entries == 0 {
+                    self.status =
+                        Some(" The related panel is off ([ui] related_entries = 0)".into());
+                } else {
+                    self.show_related = !self.show_related;
+                }
+            }
+            Action::OpenRelated => self.open_palette_with("related "),
+            Action::Translation => {
+                if self.translation.is_some() {
+                    self.show_original = !self.show_original;
+                    self.scroll_offset = 0;
+                } else {
+                    self.status = Some(" Not translated (set translate_to on its feed)".into());
+                }
+            }
+            Action::RefreshAll => self.refresh_all(),
+            Action::Summarize => self.start_summary(),
+            Action::SummaryStyle => {
+                self.summary_style = next_style(self.summary_style);
+                self.start_summary();
+            }
+            Action::NextUnread => {
+                self.load_next_unread_in_feed().await?;
+            }
+            Action::Random => {
+                self.load_random_unread().await?;
+            }
+            Action::Save => {
+                if let Some(entry) = self.current_entry.clone() {
+                    self.save_entry(&entry).await;
+                }
+            }
+            action => self.handle_entry_action(action).await,
+        }
+        Ok(())
+    }
+
+    async fn load_next_unread_in_feed(&mut self) -> Result<()> {
+        if let Some(current) = &self.current_entry {
+            let current_id = current.id.clone();
+            let mut found_current = false;
+            let mut next_id: Option<String> = None;
+
+            for entry in &self.entries {
+                if found_current && !entry.read {
+                    next_id = Some(entry.id.clone());
+                    break;
+                }
+                if entry.id == current_id {
+                    found_current = true;
+                }
+            }
+
+            if let Some(id) = next_id {
+                self.load_entry_by_id(&id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn load_random_unread(&mut self) -> Result<()> {
+        let unread_entries = self.engine.database().get_unread_entries(1000).await?;
+
+        if unread_entries.is_empty() {
+            return Ok(());
+        }
+
+        use rand::seq::SliceRandom;
+        let mut rng = rand::thread_rng();
+        if let Some(entry) = unread_entries.choose(&mut rng) {
+            let entry_id = entry.id.clone();
+            let feed_id = entry.feed_id.clone();
+
+            let feed = self.engine.database().get_feed(&feed_id).await?;
+            if let Some(feed) = feed {
+                self.list_title = feed.title.clone();
+                self.current_feed_title = feed.title;
+                self.source = Some(Source::Feed(feed.id.clone()));
+                self.results = None;
+                self.entries = self
+                    .engine
+                    .database()
+                    .get_entries_for_feed(&feed.id, 100)
+                    .await?;
+                self.load_tags().await?;
+                self.load_entry_by_id(&entry_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Open the entry most recently left partly read, from its feed's list
+    pub(super) async fn continue_reading(&mut self) -> Result<()> {
+        // The entry being read counts too
+        self.save_position().await;
+        let db = self.engine.database();
+        let Some(position) = db.get_last_partial_position().await? else {
+            self.status = Some(" No partly read entries".into());
+            return Ok(());
+        };
+        let Some(entry) = db.get_entry(&position.entry_id).await? else {
+            return Ok(());
+        };
+        self.list_title = self.feed_title(&entry.feed_id).to_string();
+        self.source = Some(Source::Feed(entry.feed_id.clone()));
+        self.results = None;
+        self.entry_filter = None;
+        self.load_entries().await?;
+        if let Some(i) = self.entries.iter().position(|e| e.id == entry.id) {
+            self.entry_state.select(Some(i));
+        }
+        self.load_entry_by_id(&entry.id).await?;
+        self.page = Page::Reader;
+        Ok(())
+    }
+
+    pub(super) async fn load_entry_by_id(&mut self, entry_id: &str) -> Result<()> {
+        if let Some(entry) = self.engine.database().get_entry(entry_id).await? {
+            if !entry.read {
+                self.mark_entry_as_read(&entry.id).await?;
+            }
+            self.current_feed_title = self.feed_title(&entry.feed_id).to_string();
+            self.current_entry = Some(entry);
+            self.scroll_offset = 0;
+            self.load_summary().await?;
+            self.load_related().await;
+        }
+        Ok(())
+    }
+
+    /// Load the entries related to the one being read (the panel shows
+    /// nothing related when that fails)
+    pub(super) async fn load_related(&mut self) {
+        self.related = None;
+        let limit = self.engine.config().ui.related_entries;
+        let Some(entry) = self.current_entry.as_ref().filter(|_| limit > 0) else {
+            return;
+        };
+        match related::load(self.engine.database(), &entry.id, limit).await {
+            Ok(related) => self.related = related,
+            Err(e) => {
+                tracing::debug!("Failed to load entries related to {}: {:#}", entry.id, e);
+                self.related = Some(Vec::new());
+            }
+        }
+    }
+
+    /// Load the stored summary and translation of the entry being read
+    pub(super) async fn load_summary(&mut self) -> Result<()> {
+        let db = self.engine.database();
+        (self.summary, self.translation) = match &self.current_entry {
+            Some(entry) => (
+                db.get_summary(&entry.id).await?.map(|s| s.summary_text),
+                db.get_translation(&entry.id).await?,
+            ),
+            None => (None, None),
+        };
+        self.show_original = false;
+        Ok(())
+    }
+
+    /// Generate a new summary of the entry being read in the current style
+    fn start_summary(&mut self) {
+        let Some(entry) = &self.current_entry else {
+            return;
+        };
+        let (sender, chunks) = mpsc::unbounded_channel();
+        let engine = Arc::clone(&self.engine);
+        let entry_id = entry.id.clone();
+        let options = SummaryOptions {
+            style: self.summary_style,
+            skip_cache: true,
+        };
+        let task = tokio::spawn({
+            let entry_id = entry_id.clone();
+            async move {
+                engine
+                    .summarize_entry_streaming(&entry_id, options, Some(sender))
+                    .await
+            }
+        });
+        self.pending_summary = Some(PendingSummary {
+            entry_id,
+            style: self.summary_style,
+            text: String::new(),
+            chunks,
+            task,
+        });
+    }
+
+    /// Take in streamed summary text, and the result once generation ends
+    pub(super) async fn poll_summary(&mut self) {
+        let Some(pending) = self.pending_summary.as_mut() else {
+            return;
+        };
+        while let Ok(chunk) = pending.chunks.try_recv() {
+            pending.text.push_str(&chunk);
+        }
+        if !pending.task.is_finished() {
+            return;
+        }
+        let Some(pending) = self.pending_summary.take() else {
+            return;
+        };
+        let style = style_label(pending.style);
+        match pending.task.await {
+            Ok(Ok((_, summary))) => {
+                if self
+                    .current_entry
+                    .as_ref()
+                    .is_some_and(|e| e.id == pending.entry_id)
+                {
+                    self.summary = Some(summary.summary_text);
+                }
+                self.status = Some(format!(" Summarized ({})", style));
+            }
+            Ok(Err(e)) => self.status = Some(format!(" Summary failed: {:#}", e)),
+            Err(e) => self.status = Some(format!(" Summary failed: {}", e)),
+        }
+    }
+
+    /// Render the current entry's HTML for the reader's width, unless it is
+    /// already, and fetch its images
+    fn layout_article(&mut self, width: usize) {
+        let Some(entry) = &self.current_entry else {
+            return;
+        };
+        let Some(html) = entry
+            .content_html
+            .as_deref()
+            .filter(|h| !h.trim().is_empty())
+        else {
+            self.article = None;
+            return;
+        };
+        if self
+            .article
+            .as_ref()
+            .is_some_and(|a| a.entry_id == entry.id && a.width == width)
+        {
+            return;
+        }
+
+        let cell = images::cell_size();
+        let image_size = |url: &str| match self.images.get(url) {
+            Some(ImageState::Ready(picture)) => Some(picture.cells(width as u16, cell)),
+            _ => None,
+        };
+        let article = render::render(html, Some(&entry.url), width, &self.theme, &image_size);
+        if self.article.as_ref().is_none_or(|a| a.entry_id != entry.id) {
+            // Keep only this article's images
+            self.images.retain(|url, _| article.sources.contains(url));
+        }
+        // Offline, articles show without their images
+        if self.image_protocol.is_some() && !self.engine.is_offline() {
+            for url in article.sources.iter().take(images::MAX_IMAGES) {
+                if self.images.contains_key(url) {
+                    continue;
+                }
+                self.images.insert(url.clone(), ImageState::Loading);
+                let engine = self.engine.clone();
+                let sender = self.image_sender.clone();
+                let url = url.clone();
+                let feed_id = entry.feed_id.clone();
+                tokio::spawn(async move {
+                    let picture: Result<Picture> = async {
+                        let bytes = engine
+                            .fetch_image(&feed_id, &url, images::MAX_BYTES)
+                            .await?;
+                        tokio::task::spawn_blocking(move || Picture::decode(&bytes)).await?
+                    }
+                    .await;
+                    let _ = sender.send((url, picture));
+                });
+            }
+        }
+        self.article = Some(LaidOut {
+            entry_id: entry.id.clone(),
+            width,
+            article,
+        });
+    }
+}
+
+/// `value` wrapped to `width` beside `label`, one of an entry's details
+fn details(
+    label: &str,
+    value: &str,
+    width: usize,
+    label_style: Style,
+    style: Style,
+) -> Vec<Line<'static>> {
+    textwrap::wrap(value, width)
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let label = if i == 0 { label } else { "" };
+            Line::from(vec![
+                Span::styled(format!("{:<1$}", label, LABEL_WIDTH), label_style),
+                Span::styled(line.into_owned(), style),
+            ])
+        })
+        .collect()
+}
+
+/// Style plain text content for better readability, wrapped to `width`
+fn style_content(content: &str, width: usize, theme: &Theme) -> Vec<ratatui::text::Line<'static>> {
+    let body_style = Style::default().fg(theme.text);
+
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        let style = if trimmed.starts_with('#') {
+            // Headers
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD)
+        } else if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
+            // URLs
+            Style::default().fg(theme.link)
+        } else {
+            // Normal body text and bullet points
+            body_style
+        };
+        if trimmed.is_empty() {
+            lines.push(Line::from(""));
+            continue;
+        }
+        for part in textwrap::wrap(line, width.max(1)) {
+            lines.push(Line::from(Span::styled(part.into_owned(), style)));
+        }
+    }
+    lines
+}
+
+/// The summary style after `style`: each builtin one, then the configured prompt
+fn next_style(style: Option<SummaryStyle>) -> Option<SummaryStyle> {
+    match style {
+        None => Some(SummaryStyle::ALL[0]),
+        Some(style) => {
+            let i = SummaryStyle::ALL
+                .iter()
+                .position(|s| *s == style)
+                .unwrap_or(0);
+            SummaryStyle::ALL.get(i + 1).copied()
+        }
+    }
+}
+
+fn style_label(style: Option<SummaryStyle>) -> &'static str {
+    style.map_or("default", SummaryStyle::label)
+}
@@ -0,0 +1,178 @@
+//! Feed refreshes running in the background, and their progress
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use super::app::{App, Page};
+use super::sidebar::{self, Source};
+use crate::update::{UpdateProgress, UpdateReport};
+
+/// Counts for the background refreshes running together
+#[derive(Default)]
+pub(super) struct RefreshProgress {
+    /// Feeds to refresh
+    pub(super) total: usize,
+    /// Feeds done (or failed)
+    pub(super) done: usize,
+    new: usize,
+    failed: usize,
+    /// Feeds deferred because the network is unavailable
+    offline: usize,
+}
+
+impl App {
+    /// Refresh the selected feed, or every feed of the selected folder, in
+    /// the background
+    pub(super) fn refresh_current_feed(&mut self) {
+        let feed_ids: Vec<String> = match self.selected_item() {
+            Some(sidebar::Item::Feed(i)) => vec![self.feeds[i].id.clone()],
+            Some(sidebar::Item::Folder(i)) => self
+                .feeds
+                .iter()
+                .filter(|f| f.enabled && f.folder_id == self.folders[i].id)
+                .map(|f| f.id.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        for feed_id in feed_ids {
+            if self.refreshing.contains(&feed_id) {
+                continue;
+            }
+            let engine = Arc::clone(&self.engine);
+            let progress = self.progress_sender.clone();
+            self.refresh.total += 1;
+            self.refresh_tasks.push(tokio::spawn(async move {
+                let _ = progress.send(UpdateProgress::Started(feed_id.clone()));
+                let report = engine
+                    .update_feed(&feed_id)
+                    .await
+                    .unwrap_or_else(|e| UpdateReport::failure(&feed_id, &e));
+                let _ = progress.send(UpdateProgress::Finished(report));
+                Ok(())
+            }));
+        }
+    }
+
+    /// Refresh every enabled feed in the background
+    pub(super) fn refresh_all(&mut self) {
+        let engine = Arc::clone(&self.engine);
+        let progress = self.progress_sender.clone();
+        self.refresh.total += self.feeds.iter().filter(|f| f.enabled).count();
+        self.refresh_tasks.push(tokio::spawn(async move {
+            engine
+                .update_all_feeds_with_progress(Some(progress))
+                .await?;
+            Ok(())
+        }));
+    }
+
+    /// Take in refresh progress: update counts as entries arrive, toast
+    /// failures, and sum up once every refresh is done
+    pub(super) async fn poll_refresh(&mut self) -> Result<()> {
+        let mut arrived = HashSet::new();
+        while let Ok(event) = self.progress.try_recv() {
+            match event {
+                UpdateProgress::Started(feed_id) => {
+                    self.refreshing.insert(feed_id);
+                }
+                UpdateProgress::Finished(report) => {
+                    self.refreshing.remove(&report.feed_id);
+                    self.refresh.done += 1;
+                    self.refresh.new += report.new;
+                    if report.offline {
+                        self.refresh.offline += 1;
+                    }
+                    if report.failed {
+                        self.refresh.failed += 1;
+                        let title = self
+                            .feeds
+                            .iter()
+                            .find(|f| f.id == report.feed_id)
+                            .map_or(report.feed_id.as_str(), |f| f.title.as_str());
+                        let error = report
+                            .errors
+                            .first()
+                            .map(String::as_str)
+                            .unwrap_or("unknown error");
+                        self.toast(format!("{} failed: {}", title, error), true);
+                    }
+                    if report.new > 0 {
+                        arrived.insert(report.feed_id);
+                    }
+                }
+            }
+        }
+
+        let mut failures = Vec::new();
+        for task in std::mem::take(&mut self.refresh_tasks) {
+            if !task.is_finished() {
+                self.refresh_tasks.push(task);
+                continue;
+            }
+            match task.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => failures.push(format!("Refresh failed: {:#}", e)),
+                Err(e) => failures.push(format!("Refresh failed: {}", e)),
+            }
+        }
+        for failure in failures {
+            self.toast(failure, true);
+        }
+
+        if !arrived.is_empty() {
+            self.load_feeds().await?;
+            let shown = match &self.source {
+                Some(Source::Feed(id)) => arrived.contains(id),
+                Some(Source::Folder(name)) => self
+                    .feeds
+                    .iter()
+                    .filter(|f| arrived.contains(&f.id))
+                    .any(|f| {
+                        self.folders
+                            .iter()
+                            .any(|d| d.id == f.folder_id && d.name.as_ref() == Some(name))
+                    }),
+                Some(Source::Tag(_)) => true,
+                None => false,
+            };
+            if self.page == Page::Entries && shown {
+                let selected = self
+                    .entry_state
+                    .selected()
+                    .and_then(|i| self.entries.get(i))
+                    .map(|e| e.id.clone());
+                self.load_entries().await?;
+                if let Some(i) =
+                    selected.and_then(|id| self.entries.iter().position(|e| e.id == id))
+                {
+                    self.entry_state.select(Some(i));
+                }
+            }
+        }
+
+        if self.refresh.total > 0 && self.refresh_tasks.is_empty() && self.refreshing.is_empty() {
+            let refresh = std::mem::take(&mut self.refresh);
+            if refresh.offline == refresh.done {
+                self.toast(
+                    "Offline: refresh deferred, showing stored entries".into(),
+                    true,
+                );
+            } else {
+                let offline = match refresh.offline {
+                    0 => String::new(),
+                    n => format!(", {} deferred (offline)", n),
+                };
+                self.toast(
+                    format!(
+                        "Refreshed {} feeds: {} new entries, {} failed{}",
+                        refresh.done, refresh.new, refresh.failed, offline
+                    ),
+                    false,
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+//! The reader's related panel: stored entries closest to the one being read
+//! by embedding, from any feed
+
+use anyhow::Result;
+use presser_db::Database;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use super::theme::Theme;
+use crate::text::truncate;
+
+/// An entry related to the one being read
+#[derive(Debug, Clone, PartialEq)]
+pub struct Related {
+    pub entry_id: String,
+    pub title: String,
+    pub feed_id: String,
+    pub read: bool,
+    /// Cosine similarity to the entry being read
+    pub similarity: f32,
+}
+
+/// The `limit` entries closest to `entry_id`, closest first, or None when it
+/// has no embedding yet
+pub async fn load(db: &Database, entry_id: &str, limit: usize) -> Result<Option<Vec<Related>>> {
+    // Hidden entries are dropped after the search, so ask for a few more
+    let Some(similar) = db.similar_entries(entry_id, limit + 5).await? else {
+        return Ok(None);
+    };
+    let mut related = Vec::with_capacity(limit);
+    for item in similar {
+        if related.len() == limit {
+            break;
+        }
+        if let Some(entry) = db.get_entry(&item.entry_id).await?.filter(|e| !e.hidden) {
+            related.push(Related {
+                entry_id: entry.id,
+                title: entry.title,
+                feed_id: entry.feed_id,
+                read: entry.read,
+                similarity: item.similarity,
+            });
+        }
+    }
+    Ok(Some(related))
+}
+
+/// The panel's rows: each entry numbered for `:related N`, with its feed
+/// (named by `feed_title`) and similarity below, cut to fit `width`
+pub fn lines(
+    related: Option<&[Related]>,
+    feed_title: impl Fn(&str) -> String,
+    width: usize,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(theme.dim);
+    let Some(related) = related else {
+        return vec![Line::from(Span::styled(
+            truncate("This entry has no embedding yet", width),
+            dim,
+        ))];
+    };
+    if related.is_empty() {
+        return vec![Line::from(Span::styled("Nothing related", dim))];
+    }
+    let mut lines = Vec::new();
+    for (i, item) in related.iter().enumerate() {
+        let number = format!("[{}] ", i + 1);
+        let title = if item.read {
+            Style::default().fg(theme.dim)
+        } else {
+            Style::default().fg(theme.text).add_modifier(Modifier::BOLD)
+        };
+        let room = width.saturating_sub(number.chars().count());
+        let similarity = format!(" · {:.0}%", item.similarity * 100.0);
+        let feed_room = room.saturating_sub(similarity.chars().count());
+        lines.push(Line::from(vec![
+            Span::styled(number.clone(), Style::default().fg(theme.accent)),
+            Span::styled(truncate(&item.title, room), title),
+        ]));
+        lines.push(Line::from(vec![
+            Span::raw(" ".repeat(number.chars().count())),
+            Span::styled(
+                truncate(&feed_title(&item.feed_id), feed_room),
+                Style::default().fg(theme.tag),
+            ),
+            Span::styled(similarity, dim),
+        ]));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let related = [
+            Related {
+                entry_id: "a".into(),
+                title: "Rust 2.0 announced at last".into(),
+                feed_id: "rust-blog".into(),
+                read: false,
+                similarity: 0.912,
+            },
+            Related {
+                entry_id: "b".into(),
+                title: "Rust 2.0".into(),
+                feed_id: "lobsters".into(),
+                read: true,
+                similarity: 0.5,
+            },
+        ];
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines
+                .iter()
+                .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+                .collect()
+        };
+        let feed_title = |id: &str| match id {
+            "rust-blog" => "Rust Blog".to_string(),
+            _ => id.to_string(),
+        };
+        let theme = Theme::default();
+
+        assert_eq!(
+            text(lines(Some(&related), feed_title, 20, &theme)),
+            [
+                "[1] Rust 2.0 announ…",
+                "    Rust Blog · 91%",
+                "[2] Rust 2.0",
+                "    lobsters · 50%",
+            ]
+        );
+        assert_eq!(
+            text(lines(Some(&[]), feed_title, 20, &theme)),
+            ["Nothing related"]
+        );
+        assert_eq!(
+            text(lines(None, feed_title, 20, &theme)),
+            ["This entry has no e…"]
+        );
+    }
+}
@@ -0,0 +1,625 @@
+//! Article HTML laid out for the reader
+//!
+//! Headings, emphasis, lists, blockquotes, and code are shown markdown-style.
+//! Links are numbered, with the targets listed at the end for `:link N`.
+//! Images get rows of their own when the terminal can draw them, and an
+//! `[image: alt]` placeholder otherwise. Lines are wrapped here rather than by
+//! the paragraph widget, so each line is one row and images can be placed.
+
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use scraper::{ElementRef, Html, Node};
+use textwrap::core::display_width;
+use url::Url;
+
+use super::theme::Theme;
+
+/// Narrowest text column, however deeply indented
+const MIN_WIDTH: usize = 20;
+
+/// An article laid out one line per row
+#[derive(Debug, Default)]
+pub struct Article {
+    pub lines: Vec<Line<'static>>,
+    /// Link targets: link N is `links[N - 1]`
+    pub links: Vec<String>,
+    /// Images drawn inline, over rows left blank for them
+    pub images: Vec<Placement>,
+    /// Every image in the article, drawn or not
+    pub sources: Vec<String>,
+}
+
+/// Where an inline image goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub url: String,
+    /// First of the lines left for it
+    pub line: usize,
+    /// Column it starts at, after any indent
+    pub column: u16,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Lay out article HTML `width` columns wide
+///
+/// Relative links resolve against `base`. `image_size` gives the columns and
+/// rows an image takes if it can be drawn now, or `None` for a placeholder.
+pub fn render(
+    html: &str,
+    base: Option<&str>,
+    width: usize,
+    theme: &Theme,
+    image_size: &dyn Fn(&str) -> Option<(u16, u16)>,
+) -> Article {
+    let document = Html::parse_fragment(html);
+    let mut renderer = Renderer {
+        base: base.and_then(|b| Url::parse(b).ok()),
+        width,
+        theme: *theme,
+        image_size,
+        article: Article::default(),
+        inline: Vec::new(),
+        styles: vec![Style::default().fg(theme.text)],
+        indents: Vec::new(),
+        lists: Vec::new(),
+        pre: 0,
+        blank: false,
+        opened: 0,
+    };
+    renderer.walk(document.root_element());
+    renderer.block();
+    renderer.footnotes();
+    renderer.article
+}
+
+/// What starts the lines of a nested block
+enum Indent {
+    Quote,
+    /// A list item, whose marker goes on its first line
+    Item {
+        marker: String,
+        shown: bool,
+    },
+}
+
+struct Renderer<'a> {
+    base: Option<Url>,
+    width: usize,
+    theme: Theme,
+    image_size: &'a dyn Fn(&str) -> Option<(u16, u16)>,
+    article: Article,
+    /// Text of the block being filled, with its styles
+    inline: Vec<(String, Style)>,
+    styles: Vec<Style>,
+    indents: Vec<Indent>,
+    /// Open lists, with the next number of ordered ones
+    lists: Vec<Option<usize>>,
+    /// Inside `<pre>`: whitespace is kept
+    pre: usize,
+    /// A blank line goes before the next block
+    blank: bool,
+    /// Lines before the open quote or list item, which its first block
+    /// doesn't need a blank line after
+    opened: usize,
+}
+
+impl Renderer<'_> {
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    let style = self.style();
+                    self.inline.push((text.to_string(), style));
+                }
+                Node::Element(_) => {
+                    if let Some(element) = ElementRef::wrap(child) {
+                        self.element(element);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        let theme = self.theme;
+        match name {
+            "script" | "style" | "head" | "noscript" | "template" | "iframe" | "svg" | "button"
+            | "form" => {}
+            "br" => self.block(),
+            "hr" => {
+                self.block();
+                self.separate();
+                let (mut line, indent) = self.prefix(false);
+                let rule = "─".repeat(self.width.saturating_sub(indent).clamp(3, 40));
+                line.push(Span::styled(rule, Style::default().fg(theme.dim)));
+                self.article.lines.push(Line::from(line));
+                self.blank = true;
+            }
+            "img" => self.image(element),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph();
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                let style = Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD);
+                self.inline.push((format!("{} ", "#".repeat(level)), style));
+                self.styled(element, |_| style);
+                self.paragraph();
+            }
+            "p" | "figure" | "table" | "details" => {
+                self.paragraph();
+                self.walk(element);
+                self.paragraph();
+            }
+            "div" | "section" | "article" | "header" | "footer" | "main" | "aside" | "nav"
+            | "summary" | "tr" | "dl" | "dt" | "dd" | "caption" => {
+                self.block();
+                match name {
+                    "dt" => self.styled(element, |s| s.add_modifier(Modifier::BOLD)),
+                    _ => self.walk(element),
+                }
+                self.block();
+            }
+            "figcaption" => {
+                self.block();
+                self.styled(element, |s| s.fg(theme.dim).add_modifier(Modifier::ITALIC));
+                self.block();
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.paragraph();
+                    self.separate();
+                } else {
+                    self.block();
+                }
+                let start = element.value().attr("start").and_then(|s| s.parse().ok());
+                self.lists.push((name == "ol").then(|| start.unwrap_or(1)));
+                self.walk(element);
+                self.block();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank = true;
+                }
+            }
+            "li" => {
+                self.block();
+                let depth = self.lists.len();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ if depth > 1 => "◦ ".to_string(),
+                    _ => "• ".to_string(),
+                };
+                self.indents.push(Indent::Item {
+                    marker,
+                    shown: false,
+                });
+                self.opened = self.article.lines.len();
+                self.walk(element);
+                self.block();
+                self.indents.pop();
+            }
+            "blockquote" => {
+                self.paragraph();
+                // The blank line before the quote isn't part of it
+                self.separate();
+                self.indents.push(Indent::Quote);
+                self.opened = self.article.lines.len();
+                self.styled(element, |s| s.add_modifier(Modifier::ITALIC));
+                self.block();
+                self.indents.pop();
+                self.blank = true;
+            }
+            "pre" => {
+                self.paragraph();
+                self.pre += 1;
+                self.styled(element, |s| s.fg(theme.highlight));
+                self.block();
+                self.pre -= 1;
+                self.blank = true;
+            }
+            "a" => {
+                let href = element.value().attr("href").and_then(|h| self.resolve(h));
+                if href.is_some() {
+                    self.styled(element, |s| {
+                        s.fg(theme.link).add_modifier(Modifier::UNDERLINED)
+                    });
+                } else {
+                    self.walk(element);
+                }
+                if let Some(href) = href {
+                    let n = self.link(href);
+                    self.inline
+                        .push((format!("[{}]", n), Style::default().fg(theme.dim)));
+                }
+            }
+            "td" | "th" => {
+                if self.inline.iter().any(|(text, _)| !text.trim().is_empty()) {
+                    self.inline
+                        .push((" │ ".into(), Style::default().fg(theme.dim)));
+                }
+                match name {
+                    "th" => self.styled(element, |s| s.add_modifier(Modifier::BOLD)),
+                    _ => self.walk(element),
+                }
+            }
+            "strong" | "b" => self.styled(element, |s| s.add_modifier(Modifier::BOLD)),
+            "em" | "i" | "cite" => self.styled(element, |s| s.add_modifier(Modifier::ITALIC)),
+            "code" | "kbd" | "samp" => self.styled(element, |s| s.fg(theme.highlight)),
+            "del" | "s" | "strike" => {
+                self.styled(element, |s| s.add_modifier(Modifier::CROSSED_OUT))
+            }
+            "u" | "ins" => self.styled(element, |s| s.add_modifier(Modifier::UNDERLINED)),
+            "mark" => self.styled(element, |s| s.add_modifier(Modifier::REVERSED)),
+            _ => self.walk(element),
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    /// Walk an element's children in a style derived from the current one
+    fn styled(&mut self, element: ElementRef, style: impl Fn(Style) -> Style) {
+        self.styles.push(style(self.style()));
+        self.walk(element);
+        self.styles.pop();
+    }
+
+    /// An image: rows left for it when it can be drawn, else a placeholder
+    /// linking to it
+    fn image(&mut self, element: ElementRef) {
+        let Some(src) = element.value().attr("src").and_then(|s| self.resolve(s)) else {
+            return;
+        };
+        if !self.article.sources.contains(&src) {
+            self.article.sources.push(src.clone());
+        }
+        let Some((cols, rows)) = (self.image_size)(&src) else {
+            let alt = element
+                .value()
+                .attr("alt")
+                .map(str::trim)
+                .unwrap_or_default();
+            let style = Style::default().fg(self.theme.dim);
+            let text = match alt {
+                "" => "[image]".to_string(),
+                alt => format!("[image: {}]", alt),
+            };
+            let n = self.link(src);
+            self.inline.push((format!(" {}[{}] ", text, n), style));
+            return;
+        };
+        self.block();
+        self.separate();
+        let (_, indent) = self.prefix(false);
+        self.article.images.push(Placement {
+            url: src,
+            line: self.article.lines.len(),
+            column: indent as u16,
+            cols,
+            rows,
+        });
+        for _ in 0..rows {
+            let (line, _) = self.prefix(false);
+            self.article.lines.push(Line::from(line));
+        }
+        self.blank = true;
+    }
+
+    /// Absolute URL of a link or image, skipping in-page anchors and scripts
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return None;
+        }
+        let url = match &self.base {
+            Some(base) => base.join(href),
+            None => Url::parse(href),
+        };
+        url.ok().map(String::from)
+    }
+
+    /// Number of a link, adding it if new
+    fn link(&mut self, url: String) -> usize {
+        match self.article.links.iter().position(|l| *l == url) {
+            Some(i) => i + 1,
+            None => {
+                self.article.links.push(url);
+                self.article.links.len()
+            }
+        }
+    }
+
+    /// End a paragraph-like block: blank lines around it
+    fn paragraph(&mut self) {
+        self.block();
+        self.blank = true;
+    }
+
+    /// Lay out the text gathered so far
+    fn block(&mut self) {
+        let inline = std::mem::take(&mut self.inline);
+        if self.pre > 0 {
+            self.preformatted(inline);
+        } else {
+            self.wrap(words(inline));
+        }
+    }
+
+    /// The blank line owed before a block, if any
+    fn separate(&mut self) {
+        if std::mem::take(&mut self.blank) && self.article.lines.len() > self.opened {
+            let (line, _) = self.prefix(false);
+            self.article.lines.push(Line::from(line));
+        }
+    }
+
+    /// Spans starting a line, and their width. The first line of a list
+    /// item gets its marker.
+    fn prefix(&mut self, first: bool) -> (Vec<Span<'static>>, usize) {
+        let items = self
+            .indents
+            .iter()
+            .filter(|i| matches!(i, Indent::Item { .. }))
+            .count();
+        let mut spans = Vec::new();
+        let mut width = 0;
+        let mut item = 0;
+        for indent in &mut self.indents {
+            let text = match indent {
+                Indent::Quote => {
+                    spans.push(Span::styled("│ ", Style::default().fg(self.theme.dim)));
+                    width += 2;
+                    continue;
+                }
+                Indent::Item { marker, shown } => {
+                    item += 1;
+                    if first && !*shown && item == items {
+                        *shown = true;
+                        marker.clone()
+                    } else {
+                        " ".repeat(display_width(marker))
+                    }
+                }
+            };
+            width += display_width(&text);
+            spans.push(Span::styled(text, Style::default().fg(self.theme.accent)));
+        }
+        (spans, width)
+    }
+
+    /// Fill lines with words
+    fn wrap(&mut self, words: Vec<Vec<Span<'static>>>) {
+        if words.is_empty() {
+            return;
+        }
+        self.separate();
+        let (mut line, indent) = self.prefix(true);
+        let width = self.width.max(indent + MIN_WIDTH);
+        let mut used = indent;
+        for word in words {
+            let len: usize = word.iter().map(|s| display_width(&s.content)).sum();
+            if used > indent && used + 1 + len > width {
+                let next = self.prefix(false).0;
+                self.article
+                    .lines
+                    .push(Line::from(std::mem::replace(&mut line, next)));
+                used = indent;
+            }
+            if used > indent {
+                line.push(Span::raw(" "));
+                used += 1;
+            }
+            if used + len <= width {
+                line.extend(word);
+                used += len;
+            } else {
+                // Longer than a line: break it anywhere
+                for span in word {
+                    self.push_chars(&mut line, &mut used, indent, width, span);
+                }
+            }
+        }
+        self.article.lines.push(Line::from(line));
+    }
+
+    /// Lines of preformatted text as they are, broken only where too long
+    fn preformatted(&mut self, inline: Vec<(String, Style)>) {
+        let mut rows = vec![Vec::new()];
+        for (text, style) in inline {
+            let text = text.replace('\t', "    ");
+            for (i, part) in text.split('\n').enumerate() {
+                if i > 0 {
+                    rows.push(Vec::new());
+                }
+                if !part.is_empty() {
+                    rows.last_mut()
+                        .unwrap()
+                        .push(Span::styled(part.to_string(), style));
+                }
+            }
+        }
+        while rows.last().is_some_and(Vec::is_empty) {
+            rows.pop();
+        }
+        if rows.is_empty() {
+            return;
+        }
+        self.separate();
+        for (i, row) in rows.into_iter().enumerate() {
+            let (mut line, indent) = self.prefix(i == 0);
+            let width = self.width.max(indent + MIN_WIDTH);
+            let mut used = indent;
+            for span in row {
+                self.push_chars(&mut line, &mut used, indent, width, span);
+            }
+            self.article.lines.push(Line::from(line));
+        }
+    }
+
+    /// Add a span to a line a character at a time, starting new lines as
+    /// it fills
+    fn push_chars(
+        &mut self,
+        line: &mut Vec<Span<'static>>,
+        used: &mut usize,
+        indent: usize,
+        width: usize,
+        span: Span<'static>,
+    ) {
+        let mut chunk = String::new();
+        for c in span.content.chars() {
+            let len = display_width(c.encode_utf8(&mut [0; 4]));
+            if *used + len > width && *used > indent {
+                line.push(Span::styled(std::mem::take(&mut chunk), span.style));
+                let next = self.prefix(false).0;
+                self.article
+                    .lines
+                    .push(Line::from(std::mem::replace(line, next)));
+                *used = indent;
+            }
+            chunk.push(c);
+            *used += len;
+        }
+        if !chunk.is_empty() {
+            line.push(Span::styled(chunk, span.style));
+        }
+    }
+
+    /// The numbered list of link targets
+    fn footnotes(&mut self) {
+        if self.article.links.is_empty() {
+            return;
+        }
+        self.indents.clear();
+        self.blank = true;
+        self.separate();
+        let heading = Style::default()
+            .fg(self.theme.accent)
+            .add_modifier(Modifier::BOLD);
+        self.article
+            .lines
+            .push(Line::from(Span::styled("Links", heading)));
+        for (i, url) in self.article.links.clone().into_iter().enumerate() {
+            self.wrap(vec![
+                vec![Span::styled(
+                    format!("[{}]", i + 1),
+                    Style::default().fg(self.theme.dim),
+                )],
+                vec![Span::styled(url, Style::default().fg(self.theme.link))],
+            ]);
+        }
+    }
+}
+
+/// Words of inline text, whitespace collapsed. A word may take several
+/// styles, as in `<b>bold</b>face`.
+fn words(inline: Vec<(String, Style)>) -> Vec<Vec<Span<'static>>> {
+    let mut words = Vec::new();
+    let mut word = Vec::new();
+    for (text, style) in inline {
+        for (i, part) in text.split(char::is_whitespace).enumerate() {
+            if i > 0 && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            if !part.is_empty() {
+                word.push(Span::styled(part.to_string(), style));
+            }
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(article: &Article) -> Vec<String> {
+        article
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let html = r#"
+            <h2>Async <em>Rust</em></h2>
+            <p>Read <a href="/guide">the guide</a> and the
+               <a href="https://example.com/spec">spec</a>.</p>
+            <ul><li>one</li><li>two<ol><li>nested</li></ol></li></ul>
+            <blockquote><p>Quoted text</p></blockquote>
+            <pre>fn main() {
+    run();
+}</pre>
+            <p><img src="a.png" alt="A chart"><img src="b.png"></p>
+        "#;
+        let no_images = |_: &str| None;
+        let article = render(
+            html,
+            Some("https://ex.com/p/"),
+            30,
+            &Theme::default(),
+            &no_images,
+        );
+        assert_eq!(
+            text(&article),
+            [
+                "## Async Rust",
+                "",
+                "Read the guide[1] and the",
+                "spec[2].",
+                "",
+                "• one",
+                "• two",
+                "  1. nested",
+                "",
+                "│ Quoted text",
+                "",
+                "fn main() {",
+                "    run();",
+                "}",
+                "",
+                "[image: A chart][3] [image][4]",
+                "",
+                "Links",
+                "[1] https://ex.com/guide",
+                "[2] https://example.com/spec",
+                "[3] https://ex.com/p/a.png",
+                "[4] https://ex.com/p/b.png",
+            ]
+        );
+        assert_eq!(article.links[0], "https://ex.com/guide");
+        assert_eq!(article.sources.len(), 2);
+
+        let drawn = |url: &str| url.ends_with("a.png").then_some((10, 3));
+        let article = render(
+            r#"<p>Before</p><img src="a.png">"#,
+            Some("https://blog.example/"),
+            30,
+            &Theme::default(),
+            &drawn,
+        );
+        assert_eq!(text(&article), ["Before", "", "", "", ""]);
+        assert_eq!(
+            article.images,
+            [Placement {
+                url: "https://blog.example/a.png".into(),
+                line: 2,
+                column: 0,
+                cols: 10,
+                rows: 3,
+            }]
+        );
+    }
+}
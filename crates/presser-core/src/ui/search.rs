@@ -0,0 +1,405 @@
+//! The `/` search view: query syntax and running it
+//!
+//! A query is full-text search terms (the FTS syntax: words, `"phrases"`,
+//! `prefix*`, `OR`) mixed with `key:value` filters taking the same criteria
+//! as `presser read`. A query of filters alone lists matching entries newest
+//! first.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use presser_db::{Database, EntryFilter, SearchHit, SearchOptions, HIGHLIGHT_END, HIGHLIGHT_START};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use super::app::{App, Page};
+use super::entries::entry_spans;
+use super::keys::Action;
+
+/// Filters: syntax and description, for help
+pub const FILTERS: &[(&str, &str)] = &[
+    ("feed:ID", "Only entries from a feed"),
+    ("tag:NAME", "Only entries with a tag"),
+    ("folder:NAME", "Only entries from feeds in a folder"),
+    ("is:unread|read|starred", "Only entries in that state"),
+    ("days:N", "Only entries published in the last N days"),
+    (
+        "since:YYYY-MM-DD",
+        "Only entries published on or after a date",
+    ),
+];
+
+/// A parsed search
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    /// Full-text terms
+    pub text: String,
+    pub filter: EntryFilter,
+}
+
+/// Parse a query, separating filters from search terms
+pub fn parse(input: &str) -> Result<Query> {
+    let mut query = Query::default();
+    let mut terms = Vec::new();
+    for token in tokens(input) {
+        let Some((key, value)) = token.split_once(':').filter(|_| !token.starts_with('"')) else {
+            terms.push(token);
+            continue;
+        };
+        let filter = std::mem::take(&mut query.filter);
+        query.filter = match key {
+            "feed" => filter.feed(value),
+            "tag" => filter.tag(value),
+            "folder" => filter.folder(value),
+            "is" => match value {
+                "unread" => filter.unread(),
+                "read" => filter.read(true),
+                "starred" => filter.starred(true),
+                _ => bail!("Unknown state 'is:{}' (unread, read, or starred)", value),
+            },
+            "days" => {
+                let days: u32 = value
+                    .parse()
+                    .with_context(|| format!("Not a number of days: {}", value))?;
+                filter.since(Utc::now() - Duration::days(i64::from(days)))
+            }
+            "since" => {
+                let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .with_context(|| format!("Not a date (YYYY-MM-DD): {}", value))?;
+                filter.since(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+            }
+            // Not a filter, e.g. "c++:" or a URL
+            _ => {
+                terms.push(token);
+                filter
+            }
+        };
+    }
+    query.text = terms.join(" ");
+    Ok(query)
+}
+
+/// Run a query: ranked full-text hits with snippets, or with no search terms
+/// the newest entries matching the filters
+pub async fn run(db: &Database, query: &Query, limit: i64) -> Result<Vec<SearchHit>> {
+    if query.text.trim().is_empty() {
+        let entries = db.query_entries(&query.filter.clone().limit(limit)).await?;
+        return Ok(entries
+            .into_iter()
+            .map(|entry| SearchHit {
+                entry,
+                snippet: String::new(),
+                rank: 0.0,
+            })
+            .collect());
+    }
+    let options = SearchOptions::new(&query.text)
+        .filter(query.filter.clone())
+        .limit(limit);
+    Ok(db.search(&options).await?)
+}
+
+/// Entries similar to one, by embedding, with their similarity as the snippet
+pub async fn similar(db: &Database, entry_id: &str, limit: usize) -> Result<Vec<SearchHit>> {
+    let similar = db
+        .similar_entries(entry_id, limit)
+        .await?
+        .context("This entry has no embedding yet")?;
+    let mut hits = Vec::with_capacity(similar.len());
+    for item in similar {
+        if let Some(entry) = db.get_entry(&item.entry_id).await?.filter(|e| !e.hidden) {
+            hits.push(SearchHit {
+                entry,
+                snippet: format!("{:.0}% similar", item.similarity * 100.0),
+                rank: f64::from(item.similarity),
+            });
+        }
+    }
+    Ok(hits)
+}
+
+/// Split a snippet into plain and highlighted parts
+pub fn highlights(snippet: &str) -> Vec<(&str, bool)> {
+    let mut parts = Vec::new();
+    let mut rest = snippet;
+    while let Some((before, after)) = rest.split_once(HIGHLIGHT_START) {
+        let (term, tail) = after.split_once(HIGHLIGHT_END).unwrap_or((after, ""));
+        parts.push((before, false));
+        parts.push((term, true));
+        rest = tail;
+    }
+    parts.push((rest, false));
+    parts.retain(|(text, _)| !text.is_empty());
+    parts
+}
+
+/// Split on whitespace, keeping `"quoted phrases"` together
+fn tokens(input: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                start.get_or_insert(i);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if let Some(s) = start.take() {
+                    tokens.push(&input[s..i]);
+                }
+            }
+            _ => {
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&input[s..]);
+    }
+    tokens
+}
+
+/// Most hits shown by the search view
+const SEARCH_LIMIT: i64 = 100;
+
+/// What the search view is showing (its entries are in `App::entries`)
+pub(super) struct SearchResults {
+    /// The search as typed, or what the entries are similar to
+    pub(super) title: String,
+    /// The search to run again or save (None for similar entries)
+    pub(super) query: Option<String>,
+    /// Page to go back to
+    origin: Page,
+    /// Highlighted excerpt of each entry
+    snippets: HashMap<String, String>,
+}
+
+impl App {
+    pub(super) fn render_search_page(&mut self, frame: &mut Frame) {
+        let Some(results) = &self.results else {
+            return;
+        };
+        let area = frame.size();
+
+        // Layout: title + list + help bar
+        let chunks = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ],
+        )
+        .split(area);
+
+        let title = Paragraph::new(Line::from(vec![
+            Span::styled(" ⌕ ", Style::default().fg(self.theme.dim)),
+            Span::styled(
+                &results.title,
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" ({} results)", self.entries.len()),
+                Style::default().fg(self.theme.dim),
+            ),
+        ]));
+        frame.render_widget(title, chunks[0]);
+
+        // Each hit: the entry with its feed, then the snippet with matches highlighted
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|e| {
+                let mut spans = entry_spans(
+                    e,
+                    self.entry_tags.get(&e.id),
+                    self.comments(&e.id),
+                    self.entry_revisions.contains_key(&e.id),
+                    &self.theme,
+                );
+                spans.push(Span::styled(
+                    format!("  {}", self.feed_title(&e.feed_id)),
+                    Style::default().fg(self.theme.dim),
+                ));
+                let mut lines = vec![Line::from(spans)];
+                let snippet = results
+                    .snippets
+                    .get(&e.id)
+                    .map(|s| s.replace('\n', " "))
+                    .unwrap_or_default();
+                if !snippet.is_empty() {
+                    let mut spans = vec![Span::raw("       ")];
+                    for (text, highlighted) in highlights(&snippet) {
+                        spans.push(Span::styled(
+                            text.to_string(),
+                            if highlighted {
+                                Style::default()
+                                    .fg(self.theme.highlight)
+                                    .add_modifier(Modifier::BOLD)
+                            } else {
+                                Style::default().fg(self.theme.text)
+                            },
+                        ));
+                    }
+                    lines.push(Line::from(spans));
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selection())
+            .highlight_symbol("▶ ");
+
+        frame.render_stateful_widget(list, chunks[1], &mut self.entry_state);
+
+        let help = self.help_bar(&[
+            (Action::Open, "read"),
+            (Action::Star, "star"),
+            (Action::Tag, "tag"),
+            (Action::Search, "edit search"),
+            (Action::Refresh, "rerun"),
+            (Action::Palette, "save/similar"),
+            (Action::Help, "help"),
+            (Action::Back, "back"),
+        ]);
+
+        frame.render_widget(self.bottom_bar(help), chunks[2]);
+    }
+
+    /// Search and show the results; `title` defaults to the query
+    pub(super) async fn run_search(&mut self, input: String, title: Option<String>) -> Result<()> {
+        let query = parse(&input)?;
+        let hits = run(self.engine.database(), &query, SEARCH_LIMIT).await?;
+        self.show_results(title.unwrap_or_else(|| input.clone()), Some(input), hits)
+            .await
+    }
+
+    /// Run the search being shown again
+    pub(super) async fn rerun_search(&mut self) -> Result<()> {
+        let Some(results) = &self.results else {
+            return Ok(());
+        };
+        let query = results
+            .query
+            .clone()
+            .context("Similar entries can't be refreshed")?;
+        let title = results.title.clone();
+        let selected = self
+            .entry_state
+            .selected()
+            .and_then(|i| self.entries.get(i))
+            .map(|e| e.id.clone());
+        self.run_search(query, Some(title)).await?;
+        if let Some(i) = selected.and_then(|id| self.entries.iter().position(|e| e.id == id)) {
+            self.entry_state.select(Some(i));
+        }
+        Ok(())
+    }
+
+    /// Show entries similar to the one being read or selected
+    pub(super) async fn show_similar(&mut self) -> Result<()> {
+        let entry = self.target_entry().cloned().context("No entry selected")?;
+        let hits = similar(self.engine.database(), &entry.id, SEARCH_LIMIT as usize).await?;
+        self.show_results(format!("Similar to: {}", entry.title), None, hits)
+            .await
+    }
+
+    /// Show hits in the search view
+    async fn show_results(
+        &mut self,
+        title: String,
+        query: Option<String>,
+        hits: Vec<SearchHit>,
+    ) -> Result<()> {
+        let origin = match (&self.results, self.page) {
+            (Some(results), _) => results.origin,
+            (None, Page::Feeds) => Page::Feeds,
+            (None, _) => Page::Entries,
+        };
+        let snippets = hits
+            .iter()
+            .map(|h| (h.entry.id.clone(), h.snippet.clone()))
+            .collect();
+        self.entries = hits.into_iter().map(|h| h.entry).collect();
+        self.results = Some(SearchResults {
+            title,
+            query,
+            origin,
+            snippets,
+        });
+        self.entry_state = ListState::default();
+        if self.entries.is_empty() {
+            self.status = Some(" No matches".into());
+        } else {
+            self.entry_state.select(Some(0));
+        }
+        self.current_entry = None;
+        self.summary = None;
+        self.page = Page::Search;
+        self.load_tags().await
+    }
+
+    /// Leave the search view for the page it was opened from
+    pub(super) async fn close_search(&mut self) -> Result<()> {
+        let Some(results) = self.results.take() else {
+            return Ok(());
+        };
+        self.entries.clear();
+        if results.origin == Page::Entries {
+            self.load_entries().await?;
+            self.page = Page::Entries;
+        } else {
+            self.page = Page::Feeds;
+            self.load_feeds().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let query =
+            parse(r#"rust "async await" feed:blog tag:later is:unread days:7 c++:"#).unwrap();
+        assert_eq!(query.text, r#"rust "async await" c++:"#);
+        assert_eq!(query.filter.feed_ids, ["blog"]);
+        assert_eq!(query.filter.tags, ["later"]);
+        assert_eq!(query.filter.read, Some(false));
+        assert!(query.filter.since.is_some());
+
+        let query = parse("is:starred since:2024-03-01").unwrap();
+        assert!(query.text.is_empty());
+        assert_eq!(query.filter.starred, Some(true));
+        assert_eq!(
+            query.filter.since.unwrap().to_rfc3339(),
+            "2024-03-01T00:00:00+00:00"
+        );
+
+        assert_eq!(
+            parse("is:new").unwrap_err().to_string(),
+            "Unknown state 'is:new' (unread, read, or starred)"
+        );
+        assert!(parse("days:week").is_err());
+        assert_eq!(
+            highlights("The «async» and «await» way"),
+            [
+                ("The ", false),
+                ("async", true),
+                (" and ", false),
+                ("await", true),
+                (" way", false)
+            ]
+        );
+    }
+}
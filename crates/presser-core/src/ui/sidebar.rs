@@ -0,0 +1,472 @@
+//! The feeds sidebar: folders with their feeds, tags, and smart folders,
+//! and what an entry list shows
+
+use anyhow::Result;
+use presser_db::{EntryFilter, EntryOrder, Feed, FolderCount, SmartFolder, TagCount};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, Paragraph};
+use ratatui::Frame;
+
+use super::app::{move_selection, App, Page, SPINNER};
+use super::feed_form::{Dialog, FeedForm};
+use super::keys::Action;
+
+/// A row of the sidebar, indexing into the lists it was built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item {
+    Folder(usize),
+    Feed(usize),
+    Tag(usize),
+    SmartFolder(usize),
+}
+
+/// Rows in display order: each folder followed by its feeds, then unfiled
+/// feeds, tags, and smart folders. With a filter, only rows whose name
+/// contains it (ignoring case) are kept, plus the folders of kept feeds.
+pub fn build(
+    feeds: &[Feed],
+    folders: &[FolderCount],
+    tags: &[TagCount],
+    smart_folders: &[SmartFolder],
+    filter: Option<&str>,
+) -> Vec<Item> {
+    let filter = filter.map(str::to_lowercase);
+    let matches = |name: &str| {
+        filter
+            .as_ref()
+            .is_none_or(|f| name.to_lowercase().contains(f))
+    };
+
+    let mut items = Vec::new();
+    for (i, folder) in folders.iter().enumerate() {
+        let Some(name) = &folder.name else {
+            continue;
+        };
+        let start = items.len();
+        items.extend(
+            feeds
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.folder_id.is_some() && f.folder_id == folder.id)
+                .filter(|(_, f)| matches(&f.title))
+                .map(|(i, _)| Item::Feed(i)),
+        );
+        if items.len() > start || matches(name) {
+            items.insert(start, Item::Folder(i));
+        }
+    }
+    // Feeds whose folder isn't listed count as unfiled
+    let listed = |id: Option<i64>| folders.iter().any(|f| f.name.is_some() && f.id == id);
+    items.extend(
+        feeds
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.folder_id.is_none() || !listed(f.folder_id))
+            .filter(|(_, f)| matches(&f.title))
+            .map(|(i, _)| Item::Feed(i)),
+    );
+    items.extend(
+        tags.iter()
+            .enumerate()
+            .filter(|(_, t)| matches(&t.name))
+            .map(|(i, _)| Item::Tag(i)),
+    );
+    items.extend(
+        smart_folders
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| matches(&f.name))
+            .map(|(i, _)| Item::SmartFolder(i)),
+    );
+    items
+}
+
+/// What an entry list shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Feed(String),
+    Folder(String),
+    Tag(String),
+}
+
+impl Source {
+    /// Filter selecting the source's entries
+    pub fn filter(&self) -> EntryFilter {
+        match self {
+            Source::Feed(id) => EntryFilter::new().feed(id),
+            Source::Folder(name) => EntryFilter::new().folder(name),
+            Source::Tag(name) => EntryFilter::new().tag(name),
+        }
+    }
+
+    /// The source written as `feed:ID`, `folder:NAME`, or `tag:NAME`, for
+    /// remembering it across restarts
+    pub fn key(&self) -> String {
+        match self {
+            Source::Feed(id) => format!("feed:{}", id),
+            Source::Folder(name) => format!("folder:{}", name),
+            Source::Tag(name) => format!("tag:{}", name),
+        }
+    }
+
+    /// The source written by [`Source::key`]
+    pub fn from_key(key: &str) -> Option<Source> {
+        let (kind, name) = key.split_once(':')?;
+        match kind {
+            "feed" => Some(Source::Feed(name.into())),
+            "folder" => Some(Source::Folder(name.into())),
+            "tag" => Some(Source::Tag(name.into())),
+            _ => None,
+        }
+    }
+}
+
+/// Which entries of a source are listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum View {
+    #[default]
+    All,
+    Unread,
+    Starred,
+    /// Unread entries, highest ranked first
+    Top,
+}
+
+impl View {
+    /// The view after this one
+    pub fn next(self) -> View {
+        match self {
+            View::All => View::Unread,
+            View::Unread => View::Starred,
+            View::Starred => View::Top,
+            View::Top => View::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            View::All => "all",
+            View::Unread => "unread only",
+            View::Starred => "starred only",
+            View::Top => "top unread",
+        }
+    }
+
+    /// Narrow a filter to the view
+    pub fn apply(self, filter: EntryFilter) -> EntryFilter {
+        match self {
+            View::All => filter,
+            View::Unread => filter.unread(),
+            View::Starred => filter.starred(true),
+            View::Top => filter.unread().order(EntryOrder::Rank),
+        }
+    }
+}
+
+impl App {
+    /// The sidebar row selected on the feeds page
+    pub(super) fn selected_item(&self) -> Option<Item> {
+        self.feed_state
+            .selected()
+            .and_then(|i| self.sidebar.get(i))
+            .copied()
+    }
+
+    /// Name of a sidebar row
+    pub(super) fn item_name(&self, item: Item) -> &str {
+        match item {
+            Item::Folder(i) => self.folders[i].name.as_deref().unwrap_or_default(),
+            Item::Feed(i) => &self.feeds[i].title,
+            Item::Tag(i) => &self.tags[i].name,
+            Item::SmartFolder(i) => &self.smart_folders[i].name,
+        }
+    }
+
+    /// Entries a sidebar row lists (None for smart folders, which search)
+    pub(super) fn item_source(&self, item: Item) -> Option<Source> {
+        match item {
+            Item::Feed(i) => Some(Source::Feed(self.feeds[i].id.clone())),
+            Item::Folder(i) => Some(Source::Folder(
+                self.folders[i].name.clone().unwrap_or_default(),
+            )),
+            Item::Tag(i) => Some(Source::Tag(self.tags[i].name.clone())),
+            Item::SmartFolder(_) => None,
+        }
+    }
+
+    pub(super) fn render_feeds_page(&mut self, frame: &mut Frame) {
+        let area = frame.size();
+
+        // Layout: title + list + help bar
+        let chunks = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ],
+        )
+        .split(area);
+
+        // Title bar
+        let title = Paragraph::new(Line::from(vec![
+            Span::styled(
+                " Presser ",
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("({} feeds)", self.feeds.len()),
+                Style::default().fg(self.theme.dim),
+            ),
+            Span::styled(
+                view_label(self.view),
+                Style::default().fg(self.theme.highlight),
+            ),
+        ]));
+        frame.render_widget(title, chunks[0]);
+
+        // Sidebar: folders and their feeds with unread/total counts, then
+        // tags and smart folders
+        let count_style = Style::default().fg(self.theme.dim);
+        let feed_items: Vec<ListItem> = self
+            .sidebar
+            .iter()
+            .map(|item| {
+                ListItem::new(Line::from(match *item {
+                    Item::Folder(i) => {
+                        let folder = &self.folders[i];
+                        vec![
+                            Span::styled("▾", Style::default().fg(self.theme.accent)),
+                            Span::styled(
+                                folder.name.as_deref().unwrap_or_default(),
+                                Style::default()
+                                    .fg(self.theme.accent)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                            Span::raw(" "),
+                            Span::styled(
+                                format!("({}/{})", folder.unread, folder.total),
+                                count_style,
+                            ),
+                        ]
+                    }
+                    Item::Feed(i) => {
+                        let f = &self.feeds[i];
+                        let unread = self.unread_counts.get(&f.id).copied().unwrap_or(0);
+                        let marker = if self.refreshing.contains(&f.id) {
+                            SPINNER[self.tick % SPINNER.len()]
+                        } else if f.enabled {
+                            " "
+                        } else {
+                            "×"
+                        };
+                        vec![
+                            Span::raw(if in_folder(f, &self.folders) {
+                                "  "
+                            } else {
+                                ""
+                            }),
+                            Span::styled(
+                                marker,
+                                if f.enabled {
+                                    Style::default()
+                                } else {
+                                    count_style
+                                },
+                            ),
+                            Span::styled(&f.title, Style::default().fg(self.theme.text)),
+                            Span::raw(" "),
+                            Span::styled(format!("({}/{})", unread, f.entry_count), count_style),
+                        ]
+                    }
+                    Item::Tag(i) => {
+                        let tag = &self.tags[i];
+                        vec![
+                            Span::styled("#", Style::default().fg(self.theme.tag)),
+                            Span::styled(&tag.name, Style::default().fg(self.theme.tag)),
+                            Span::raw(" "),
+                            Span::styled(format!("({}/{})", tag.unread, tag.count), count_style),
+                        ]
+                    }
+                    Item::SmartFolder(i) => {
+                        let folder = &self.smart_folders[i];
+                        vec![
+                            Span::styled("⌕", Style::default().fg(self.theme.tag)),
+                            Span::styled(&folder.name, Style::default().fg(self.theme.tag)),
+                            Span::raw(" "),
+                            Span::styled(&folder.query, count_style),
+                        ]
+                    }
+                }))
+            })
+            .collect();
+
+        let feed_list = List::new(feed_items)
+            .highlight_style(self.theme.selection())
+            .highlight_symbol("▶ ");
+
+        frame.render_stateful_widget(feed_list, chunks[1], &mut self.feed_state);
+
+        // Help bar
+        let help = self.help_bar(&[
+            (Action::Open, "open"),
+            (Action::Refresh, "refresh"),
+            (Action::RefreshAll, "refresh all"),
+            (Action::AddFeed, "add"),
+            (Action::EditFeed, "edit"),
+            (Action::DeleteFeed, "delete"),
+            (Action::View, "view"),
+            (Action::Search, "search"),
+            (Action::ContinueReading, "continue"),
+            (Action::Palette, "command"),
+            (Action::Dashboard, "dashboard"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ]);
+
+        frame.render_widget(self.bottom_bar(help), chunks[2]);
+    }
+
+    pub(super) async fn handle_feeds_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Open => self.open_selected_feed().await?,
+            Action::Refresh => self.refresh_current_feed(),
+            Action::RefreshAll => self.refresh_all(),
+            Action::SearchNext => self.search_step(true),
+            Action::SearchPrev => self.search_step(false),
+            Action::AddFeed => self.dialog = Some(Dialog::Form(FeedForm::add())),
+            Action::EditFeed | Action::DeleteFeed => {
+                let Some(Item::Feed(i)) = self.selected_item() else {
+                    self.status = Some(" Select a feed first".into());
+                    return Ok(());
+                };
+                let feed = &self.feeds[i];
+                self.dialog = Some(match action {
+                    Action::EditFeed => {
+                        Dialog::Form(FeedForm::edit(&feed.id, &self.feed_config(feed)))
+                    }
+                    _ => Dialog::ConfirmDelete(feed.clone()),
+                });
+            }
+            action => move_selection(&mut self.feed_state, self.sidebar.len(), action),
+        }
+        Ok(())
+    }
+
+    /// Open the entries of the selected feed, folder, or tag, or run the
+    /// selected smart folder
+    pub(super) async fn open_selected_feed(&mut self) -> Result<()> {
+        let Some(item) = self.selected_item() else {
+            return Ok(());
+        };
+        let Some(source) = self.item_source(item) else {
+            let Item::SmartFolder(i) = item else {
+                return Ok(());
+            };
+            let folder = &self.smart_folders[i];
+            let (name, query) = (folder.name.clone(), folder.query.clone());
+            return self.run_search(query, Some(name)).await;
+        };
+        self.list_title = match item {
+            Item::Tag(_) => format!("#{}", self.item_name(item)),
+            _ => self.item_name(item).to_string(),
+        };
+        self.source = Some(source);
+        self.results = None;
+        self.load_entries().await?;
+        self.page = Page::Entries;
+        Ok(())
+    }
+}
+
+/// Whether a feed is filed under one of `folders`
+fn in_folder(feed: &Feed, folders: &[FolderCount]) -> bool {
+    feed.folder_id.is_some()
+        && folders
+            .iter()
+            .any(|d| d.name.is_some() && d.id == feed.folder_id)
+}
+
+/// Title suffix naming the entry view, empty when showing everything
+pub(super) fn view_label(view: View) -> String {
+    match view {
+        View::All => String::new(),
+        view => format!(" · {}", view.label()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let feed = |title: &str, folder_id| Feed {
+            id: title.to_lowercase(),
+            title: title.into(),
+            folder_id,
+            ..Default::default()
+        };
+        let feeds = [
+            feed("Blog", None),
+            feed("Lobsters", Some(1)),
+            feed("HN", Some(1)),
+            feed("Kernel", Some(2)),
+        ];
+        let folders = [
+            FolderCount {
+                id: Some(1),
+                name: Some("News".into()),
+                feeds: 2,
+                unread: 3,
+                total: 9,
+            },
+            FolderCount {
+                id: None,
+                name: None,
+                feeds: 1,
+                unread: 0,
+                total: 1,
+            },
+        ];
+        let tags = [TagCount {
+            name: "rust".into(),
+            count: 4,
+            unread: 1,
+        }];
+
+        assert_eq!(
+            build(&feeds, &folders, &tags, &[], None),
+            [
+                Item::Folder(0),
+                Item::Feed(1),
+                Item::Feed(2),
+                Item::Feed(0),
+                // Its folder has no counts (yet), so it is listed unfiled
+                Item::Feed(3),
+                Item::Tag(0),
+            ]
+        );
+        assert_eq!(
+            build(&feeds, &folders, &tags, &[], Some("hn")),
+            [Item::Folder(0), Item::Feed(2)]
+        );
+        assert_eq!(
+            build(&feeds, &folders, &tags, &[], Some("RUST")),
+            [Item::Tag(0)]
+        );
+
+        let source = Source::Tag("c++:17".into());
+        assert_eq!(Source::from_key(&source.key()), Some(source));
+        assert_eq!(Source::from_key("smart:Rust"), None);
+
+        assert_eq!(View::default().next().next().next().next(), View::All);
+        let filter = View::Unread.apply(Source::Folder("News".into()).filter());
+        assert_eq!(filter.folders, ["News"]);
+        assert_eq!(filter.read, Some(false));
+    }
+}
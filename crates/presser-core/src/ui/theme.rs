@@ -0,0 +1,257 @@
+//! TUI colors: the builtin palettes and `[ui.theme]` overrides
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use presser_config::{ThemeBase, ThemeConfig};
+use ratatui::style::{Color, Modifier, Style};
+
+/// Colors of every element of the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Body text and unread titles
+    pub text: Color,
+    /// Counts, dates, read entries, separators, and placeholders
+    pub dim: Color,
+    /// Pane titles, folders, labels, headings, and keys
+    pub accent: Color,
+    /// Values, the current view, search matches, stars, and code
+    pub highlight: Color,
+    pub link: Color,
+    /// Tags and smart folders
+    pub tag: Color,
+    /// Unread markers
+    pub unread: Color,
+    /// AI summaries
+    pub summary: Color,
+    /// Selected row (`reset` keeps the row's own colors)
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+    /// Help and status bar
+    pub bar_fg: Color,
+    pub bar_bg: Color,
+    /// Error notices in the status bar
+    pub error_fg: Color,
+    pub error_bg: Color,
+    /// Popup borders
+    pub border: Color,
+    /// Behind everything (`reset`: the terminal's own)
+    pub background: Color,
+}
+
+/// Element names as written in `[ui.theme]`
+pub const ELEMENTS: &[&str] = &[
+    "text",
+    "dim",
+    "accent",
+    "highlight",
+    "link",
+    "tag",
+    "unread",
+    "summary",
+    "selection_fg",
+    "selection_bg",
+    "bar_fg",
+    "bar_bg",
+    "error_fg",
+    "error_bg",
+    "border",
+    "background",
+];
+
+impl Theme {
+    /// A builtin palette
+    pub fn builtin(base: ThemeBase) -> Theme {
+        match base {
+            ThemeBase::Dark => Theme {
+                text: Color::White,
+                dim: Color::DarkGray,
+                accent: Color::Cyan,
+                highlight: Color::Yellow,
+                link: Color::Blue,
+                tag: Color::Magenta,
+                unread: Color::Green,
+                summary: Color::Green,
+                selection_fg: Color::Reset,
+                selection_bg: Color::Rgb(40, 40, 40),
+                bar_fg: Color::Black,
+                bar_bg: Color::Rgb(80, 80, 80),
+                error_fg: Color::White,
+                error_bg: Color::Red,
+                border: Color::Reset,
+                background: Color::Reset,
+            },
+            ThemeBase::Light => Theme {
+                text: Color::Rgb(30, 30, 30),
+                dim: Color::Rgb(128, 128, 128),
+                accent: Color::Rgb(0, 95, 175),
+                highlight: Color::Rgb(175, 95, 0),
+                link: Color::Rgb(0, 0, 215),
+                tag: Color::Rgb(135, 0, 135),
+                unread: Color::Rgb(0, 135, 0),
+                summary: Color::Rgb(0, 110, 60),
+                selection_fg: Color::Reset,
+                selection_bg: Color::Rgb(220, 220, 220),
+                bar_fg: Color::Rgb(30, 30, 30),
+                bar_bg: Color::Rgb(200, 200, 200),
+                error_fg: Color::White,
+                error_bg: Color::Rgb(200, 40, 40),
+                border: Color::Rgb(128, 128, 128),
+                background: Color::Rgb(250, 250, 250),
+            },
+            // https://ethanschoonover.com/solarized/ (dark)
+            ThemeBase::Solarized => Theme {
+                text: rgb(0x839496),
+                dim: rgb(0x586e75),
+                accent: rgb(0x2aa198),
+                highlight: rgb(0xb58900),
+                link: rgb(0x268bd2),
+                tag: rgb(0xd33682),
+                unread: rgb(0x859900),
+                summary: rgb(0x859900),
+                selection_fg: Color::Reset,
+                selection_bg: rgb(0x073642),
+                bar_fg: rgb(0x002b36),
+                bar_bg: rgb(0x93a1a1),
+                error_fg: rgb(0xfdf6e3),
+                error_bg: rgb(0xdc322f),
+                border: rgb(0x586e75),
+                background: rgb(0x002b36),
+            },
+            // https://github.com/morhetz/gruvbox (dark)
+            ThemeBase::Gruvbox => Theme {
+                text: rgb(0xebdbb2),
+                dim: rgb(0x928374),
+                accent: rgb(0x8ec07c),
+                highlight: rgb(0xfabd2f),
+                link: rgb(0x83a598),
+                tag: rgb(0xd3869b),
+                unread: rgb(0xb8bb26),
+                summary: rgb(0xb8bb26),
+                selection_fg: Color::Reset,
+                selection_bg: rgb(0x3c3836),
+                bar_fg: rgb(0x282828),
+                bar_bg: rgb(0xa89984),
+                error_fg: rgb(0xfbf1c7),
+                error_bg: rgb(0xcc241d),
+                border: rgb(0x665c54),
+                background: rgb(0x282828),
+            },
+        }
+    }
+
+    /// The configured palette with its overrides
+    pub fn from_config(config: &ThemeConfig) -> Result<Theme> {
+        let mut theme = Theme::builtin(config.base);
+        for (element, value) in &config.colors {
+            let Ok(color) = Color::from_str(value) else {
+                bail!(
+                    "Unknown color '{}' for [ui.theme] {} (a name, #rrggbb, or 0-255)",
+                    value,
+                    element
+                );
+            };
+            *theme.element(element)? = color;
+        }
+        Ok(theme)
+    }
+
+    fn element(&mut self, name: &str) -> Result<&mut Color> {
+        Ok(match name {
+            "text" => &mut self.text,
+            "dim" => &mut self.dim,
+            "accent" => &mut self.accent,
+            "highlight" => &mut self.highlight,
+            "link" => &mut self.link,
+            "tag" => &mut self.tag,
+            "unread" => &mut self.unread,
+            "summary" => &mut self.summary,
+            "selection_fg" => &mut self.selection_fg,
+            "selection_bg" => &mut self.selection_bg,
+            "bar_fg" => &mut self.bar_fg,
+            "bar_bg" => &mut self.bar_bg,
+            "error_fg" => &mut self.error_fg,
+            "error_bg" => &mut self.error_bg,
+            "border" => &mut self.border,
+            "background" => &mut self.background,
+            _ => bail!(
+                "Unknown [ui.theme] element '{}' (one of {})",
+                name,
+                ELEMENTS.join(", ")
+            ),
+        })
+    }
+
+    /// Plain text on the background, for panes and popups
+    pub fn base(&self) -> Style {
+        Style::default().fg(self.text).bg(self.background)
+    }
+
+    /// The selected row of a list
+    pub fn selection(&self) -> Style {
+        let style = Style::default()
+            .bg(self.selection_bg)
+            .add_modifier(Modifier::BOLD);
+        match self.selection_fg {
+            Color::Reset => style,
+            color => style.fg(color),
+        }
+    }
+
+    /// The help and status bar
+    pub fn bar(&self) -> Style {
+        Style::default().fg(self.bar_fg).bg(self.bar_bg)
+    }
+
+    pub fn error(&self) -> Style {
+        Style::default().fg(self.error_fg).bg(self.error_bg)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin(ThemeBase::Dark)
+    }
+}
+
+fn rgb(hex: u32) -> Color {
+    Color::Rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let config: ThemeConfig = toml::from_str(
+            r##"
+            base = "gruvbox"
+            accent = "#ff8000"
+            dim = "8"
+            selection_bg = "dark gray"
+            "##,
+        )
+        .unwrap();
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.accent, Color::Rgb(255, 128, 0));
+        assert_eq!(theme.dim, Color::Indexed(8));
+        assert_eq!(theme.selection_bg, Color::DarkGray);
+        assert_eq!(theme.background, rgb(0x282828));
+        assert_eq!(
+            Theme::from_config(&ThemeConfig::default()).unwrap(),
+            Theme::default()
+        );
+
+        let config: ThemeConfig = toml::from_str(r#"accnt = "red""#).unwrap();
+        assert!(Theme::from_config(&config)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Unknown [ui.theme] element 'accnt'"));
+        let config: ThemeConfig = toml::from_str(r#"link = "blurple""#).unwrap();
+        assert_eq!(
+            Theme::from_config(&config).unwrap_err().to_string(),
+            "Unknown color 'blurple' for [ui.theme] link (a name, #rrggbb, or 0-255)"
+        );
+    }
+}
@@ -0,0 +1,533 @@
+//! Pieces of the feed update pipeline: keyword filters, summarization, reports
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use presser_ai::{AiClient, SummaryOptions, SummaryStyle};
+use presser_config::{AiConfig, FeedConfig};
+use presser_db::{Database, RunRecord, Summary};
+use presser_feeds::FeedEntry;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+use crate::ai_queue::{self, Priority, Slot, SlotQueue};
+use crate::metrics::{self, METRICS};
+use crate::pipeline::{Pipelines, Verdict};
+
+/// Failed summaries of a queued entry before it is taken off the queue
+pub(crate) const MAX_SUMMARY_ATTEMPTS: i64 = 3;
+
+/// Outcome of updating one feed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateReport {
+    /// Feed that was updated
+    pub feed_id: String,
+
+    /// Nothing changed since the last fetch: the server answered 304 Not
+    /// Modified, or served the same body again
+    pub not_modified: bool,
+
+    /// Another process was already updating the feed, so nothing was done
+    #[serde(default)]
+    pub busy: bool,
+
+    /// The network was unavailable (or offline mode is on), so the update was
+    /// deferred
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Entries stored for the first time
+    pub new: usize,
+
+    /// New entries that got an AI summary
+    pub summarized: usize,
+
+    /// New entries translated into their feed's `translate_to` language
+    #[serde(default)]
+    pub translated: usize,
+
+    /// Entries dropped by keyword filters
+    pub skipped: usize,
+
+    /// New entries another feed already stored (see `global.duplicates`)
+    #[serde(default)]
+    pub duplicates: usize,
+
+    /// Stored entries whose text changed, kept as revisions
+    #[serde(default)]
+    pub revised: usize,
+
+    /// Entries left in the summary queue because the daily token budget ran out
+    #[serde(default)]
+    pub deferred: usize,
+
+    /// Tokens spent on new summaries (cache hits are free)
+    #[serde(default)]
+    pub tokens: u64,
+
+    /// Bytes downloaded: the feed, extracted pages, and comment feeds
+    #[serde(default)]
+    pub bytes: u64,
+
+    /// How long the update took
+    #[serde(default)]
+    pub duration_ms: u64,
+
+    /// The update itself failed (the reason is the first error)
+    pub failed: bool,
+
+    /// URL the feed was permanently redirected to (see `presser fix-urls`)
+    #[serde(default)]
+    pub moved_to: Option<String>,
+
+    /// Non-fatal errors (e.g. failed summaries)
+    pub errors: Vec<String>,
+}
+
+/// Progress of a multi-feed update, for live displays
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateProgress {
+    /// A feed's update started
+    Started(String),
+    /// A feed's update ended
+    Finished(UpdateReport),
+}
+
+impl UpdateReport {
+    /// Report for a feed whose update failed outright
+    pub fn failure(feed_id: &str, error: &anyhow::Error) -> Self {
+        Self {
+            feed_id: feed_id.to_string(),
+            failed: true,
+            errors: vec![format!("{:#}", error)],
+            ..Default::default()
+        }
+    }
+
+    /// Report for a feed left alone because the network is unavailable
+    pub fn offline(feed_id: &str) -> Self {
+        Self {
+            feed_id: feed_id.to_string(),
+            offline: true,
+            ..Default::default()
+        }
+    }
+
+    /// Row for the `runs` table (`presser log`)
+    pub(crate) fn run_record(&self, run_id: &str, started_at: DateTime<Utc>) -> RunRecord {
+        RunRecord {
+            run_id: run_id.to_string(),
+            feed_id: self.feed_id.clone(),
+            started_at,
+            duration_ms: self.duration_ms as i64,
+            new_entries: self.new as i64,
+            summarized: self.summarized as i64,
+            skipped: self.skipped as i64,
+            deferred: self.deferred as i64,
+            tokens: self.tokens as i64,
+            bytes: self.bytes as i64,
+            not_modified: self.not_modified,
+            failed: self.failed,
+            errors: (!self.errors.is_empty())
+                .then(|| serde_json::to_string(&self.errors).unwrap_or_default()),
+            ..Default::default()
+        }
+    }
+}
+
+/// ID shared by the feed updates of one run: its start time plus a random suffix
+pub(crate) fn new_run_id() -> String {
+    format!(
+        "{}-{:04x}",
+        Utc::now().format("%Y%m%dT%H%M%S"),
+        rand::random::<u16>()
+    )
+}
+
+/// Per-feed reports of an update run, ordered by feed ID
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateSummary {
+    /// One report per feed that was attempted
+    pub reports: Vec<UpdateReport>,
+
+    /// Summaries of entries queued by earlier runs, made before the feeds were updated
+    #[serde(default)]
+    pub backlog: Option<UpdateReport>,
+}
+
+impl UpdateSummary {
+    /// Feeds whose update failed
+    pub fn failed(&self) -> usize {
+        self.reports.iter().filter(|r| r.failed).count()
+    }
+
+    /// Entries stored for the first time, across all feeds
+    pub fn new_entries(&self) -> usize {
+        self.reports.iter().map(|r| r.new).sum()
+    }
+
+    /// Summaries generated, across all feeds
+    pub fn summarized(&self) -> usize {
+        self.reports.iter().map(|r| r.summarized).sum()
+    }
+
+    /// Entries dropped by keyword filters, across all feeds
+    pub fn skipped(&self) -> usize {
+        self.reports.iter().map(|r| r.skipped).sum()
+    }
+
+    /// Feeds that were permanently redirected to a new URL
+    pub fn moved(&self) -> usize {
+        self.reports.iter().filter(|r| r.moved_to.is_some()).count()
+    }
+
+    /// Summaries deferred by the daily token budget, across all feeds and the backlog
+    pub fn deferred(&self) -> usize {
+        self.reports
+            .iter()
+            .chain(&self.backlog)
+            .map(|r| r.deferred)
+            .sum()
+    }
+}
+
+/// What updating one feed would do (`presser update --dry-run`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdatePlan {
+    /// Feed that was fetched
+    pub feed_id: String,
+
+    /// Nothing changed since the last fetch (304, or the same body)
+    pub not_modified: bool,
+
+    /// Titles of entries that would be stored for the first time
+    pub new: Vec<String>,
+
+    /// New entries that would be sent to the AI provider
+    pub to_summarize: usize,
+
+    /// Entries the filters, mutes, or pipeline hooks would drop
+    pub skipped: usize,
+
+    /// Why the fetch failed
+    pub error: Option<String>,
+}
+
+/// A feed URL rewritten (or to be rewritten) after a permanent redirect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlMove {
+    /// Feed that moved
+    pub feed_id: String,
+
+    /// URL in the config and database
+    pub from: String,
+
+    /// URL the feed redirects to
+    pub to: String,
+
+    /// `feeds/*.toml` file that was rewritten (none for dry runs and feeds
+    /// missing from the config)
+    pub config_file: Option<std::path::PathBuf>,
+
+    /// Why the move was not applied
+    pub skipped: Option<String>,
+}
+
+/// Case-insensitive include/exclude keyword filter for a feed
+#[derive(Debug, Clone, Default)]
+pub struct KeywordFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl KeywordFilter {
+    /// Filter from a feed's config (keeps everything if unconfigured)
+    pub fn for_feed(config: Option<&FeedConfig>) -> Self {
+        let lower = |words: &[String]| words.iter().map(|w| w.to_lowercase()).collect();
+        config
+            .map(|c| Self {
+                include: lower(&c.include_keywords),
+                exclude: lower(&c.exclude_keywords),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether an entry passes the filter
+    pub fn matches(&self, entry: &FeedEntry) -> bool {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return true;
+        }
+        let text = [
+            Some(&entry.title),
+            entry.summary.as_ref(),
+            entry.content_text.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|s| s.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n");
+        let mentions = |words: &[String]| words.iter().any(|w| text.contains(w.as_str()));
+        (self.include.is_empty() || mentions(&self.include)) && !mentions(&self.exclude)
+    }
+}
+
+/// Summarize `(entry_id, content)` pairs in `style` (the plain summary
+/// prompt if None) and store the results
+///
+/// The requests take their turn in the engine's AI queue (see
+/// [`ai_queue`]), so entries of all feeds updating at once are summarized
+/// by score and age, newest first, within `ai.max_concurrent_requests`.
+/// Failures are recorded in the report instead of aborting the update.
+/// Entries are queued first and leave the queue once their summary is stored,
+/// dropped by a `before_summarize` hook, or after `MAX_SUMMARY_ATTEMPTS`
+/// failures. Entries the daily token budget has no room for stay queued and
+/// are counted as deferred. Requests already in flight when the budget runs
+/// out still complete, so the budget can be overshot by a few summaries.
+pub(crate) async fn summarize_entries(
+    ai: &Arc<AiClient>,
+    db: &Database,
+    pipelines: &Pipelines,
+    budget: &Arc<AiBudget>,
+    pending: Vec<(String, String)>,
+    style: Option<SummaryStyle>,
+    report: &mut UpdateReport,
+) -> Result<()> {
+    summarize_entries_with(
+        ai,
+        db,
+        pipelines,
+        budget,
+        pending,
+        SummaryOptions {
+            style,
+            ..Default::default()
+        },
+        None,
+        false,
+        report,
+    )
+    .await
+}
+
+/// [`summarize_entries`] with per-request options, sending summary text to
+/// `stream` as it is generated; `interactive` requests go ahead of the queue
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn summarize_entries_with(
+    ai: &Arc<AiClient>,
+    db: &Database,
+    pipelines: &Pipelines,
+    budget: &Arc<AiBudget>,
+    pending: Vec<(String, String)>,
+    options: SummaryOptions,
+    stream: Option<mpsc::UnboundedSender<String>>,
+    interactive: bool,
+    report: &mut UpdateReport,
+) -> Result<()> {
+    let ids: Vec<String> = pending.iter().map(|(id, _)| id.clone()).collect();
+    db.enqueue_summaries(&ids).await?;
+    let priorities = if interactive {
+        Default::default()
+    } else {
+        ai_queue::entry_priorities(db, &ids).await?
+    };
+    let today = Utc::now().date_naive();
+    let spent = Arc::new(AtomicU64::new(
+        db.get_ai_usage(today).await?.tokens.max(0) as u64
+    ));
+
+    let mut tasks = JoinSet::new();
+    let mut dropped = Vec::new();
+    for (entry_id, mut content) in pending {
+        if pipelines
+            .before_summarize(&entry_id, &mut content, report)
+            .await
+            == Verdict::Drop
+        {
+            dropped.push(entry_id);
+            continue;
+        }
+        let priority = match interactive {
+            true => Priority::interactive(),
+            false => priorities
+                .get(&entry_id)
+                .copied()
+                .unwrap_or_else(Priority::lowest),
+        };
+        let ai = Arc::clone(ai);
+        let budget = Arc::clone(budget);
+        let spent = Arc::clone(&spent);
+        let stream = stream.clone();
+        tasks.spawn(async move {
+            let _slot = budget.acquire(priority).await;
+            if budget.exhausted(spent.load(Ordering::Relaxed)) {
+                return (entry_id, String::new(), None);
+            }
+            budget.pace().await;
+            let summary = metrics::time_ai(ai.summarize_stream(&content, options, |text| {
+                if let Some(stream) = &stream {
+                    let _ = stream.send(text.to_string());
+                }
+            }))
+            .await;
+            if let Ok(summary) = &summary {
+                spent.fetch_add(summary.tokens.map_or(0, u64::from), Ordering::Relaxed);
+            }
+            (entry_id, presser_ai::content_hash(&content), Some(summary))
+        });
+    }
+    db.dequeue_summaries(&dropped).await?;
+
+    // Each summary is stored as it arrives, so an interrupted run loses at
+    // most the requests in flight
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((_, _, None)) => report.deferred += 1,
+            Ok((entry_id, content_hash, Some(Ok(summary)))) => {
+                METRICS.summaries.inc();
+                METRICS.tokens.add(summary.tokens.map_or(0, u64::from));
+                let usage = if summary.cached {
+                    METRICS.cache_hits.inc();
+                    None
+                } else {
+                    report.tokens += summary.tokens.map_or(0, u64::from);
+                    Some((today, summary.tokens.map_or(0, i64::from)))
+                };
+                let mut summary = Summary {
+                    entry_id,
+                    summary_text: summary.text,
+                    model: summary.model,
+                    tokens: summary.tokens.map(i64::from),
+                    content_hash,
+                    ..Default::default()
+                };
+                pipelines.after_summarize(&mut summary, report).await;
+                db.complete_summary(&summary, usage).await?;
+                report.summarized += 1;
+            }
+            Ok((entry_id, _, Some(Err(e)))) => {
+                METRICS.summary_errors.inc();
+                let error = format!("{:#}", e);
+                let attempts = db.record_summary_failure(&entry_id, &error).await?;
+                if attempts >= MAX_SUMMARY_ATTEMPTS {
+                    db.dequeue_summaries(std::slice::from_ref(&entry_id))
+                        .await?;
+                }
+                report
+                    .errors
+                    .push(format!("Summary of {} failed: {}", entry_id, error))
+            }
+            Err(e) => report.errors.push(format!("Summary task failed: {}", e)),
+        }
+    }
+    if report.deferred > 0 {
+        tracing::info!(
+            "Daily token budget reached; {} summaries deferred",
+            report.deferred
+        );
+    }
+    Ok(())
+}
+
+/// Limits on AI use from the `[ai]` config, shared by everything one engine
+/// summarizes or translates: the request queue, the rate limit, and the
+/// daily token budget
+pub(crate) struct AiBudget {
+    /// Tokens to spend per UTC day
+    daily_tokens: Option<u64>,
+    /// Minimum time between requests
+    interval: Option<Duration>,
+    /// Earliest start of the next request
+    next_request: Mutex<Instant>,
+    /// Slots for requests in flight
+    slots: SlotQueue,
+}
+
+impl AiBudget {
+    /// Budget from `daily_token_budget`, `requests_per_minute`, and
+    /// `max_concurrent_requests`
+    pub(crate) fn from_config(config: &AiConfig) -> Self {
+        Self {
+            daily_tokens: config.daily_token_budget,
+            interval: config
+                .requests_per_minute
+                .filter(|&n| n > 0)
+                .map(|n| Duration::from_secs(60) / n),
+            next_request: Mutex::new(Instant::now()),
+            slots: SlotQueue::new(config.max_concurrent_requests),
+        }
+    }
+
+    /// Wait for a request slot, behind waiting requests of higher priority
+    pub(crate) async fn acquire(&self, priority: Priority) -> Slot<'_> {
+        self.slots.acquire(priority).await
+    }
+
+    /// Whether `spent` tokens use up the daily budget
+    pub(crate) fn exhausted(&self, spent: u64) -> bool {
+        self.daily_tokens.is_some_and(|budget| spent >= budget)
+    }
+
+    /// Tokens left after spending `spent` today, if there is a budget
+    pub(crate) fn remaining(&self, spent: u64) -> Option<u64> {
+        self.daily_tokens.map(|budget| budget.saturating_sub(spent))
+    }
+
+    /// Wait for the next request slot under the rate limit
+    pub(crate) async fn pace(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let slot = {
+            let mut next = self.next_request.lock().await;
+            let slot = (*next).max(Instant::now());
+            *next = slot + interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_filter() {
+        let entry = |title: &str, summary: Option<&str>| FeedEntry {
+            id: title.into(),
+            title: title.into(),
+            url: String::new(),
+            published: None,
+            updated: None,
+            summary: summary.map(String::from),
+            content_html: None,
+            content_text: None,
+            author: None,
+            categories: Vec::new(),
+            discussion: None,
+            enclosures: Vec::new(),
+        };
+        let config: FeedConfig = toml::from_str(
+            r#"
+            url = "https://example.com/feed"
+            name = "Example"
+            include_keywords = ["Rust", "wasm"]
+            exclude_keywords = ["sponsored"]
+            "#,
+        )
+        .unwrap();
+
+        let filter = KeywordFilter::for_feed(Some(&config));
+        assert!(filter.matches(&entry("Rust 1.80 released", None)));
+        assert!(filter.matches(&entry("New runtime", Some("Runs WASM modules"))));
+        assert!(!filter.matches(&entry("Python 3.13", None)));
+        assert!(!filter.matches(&entry("Rust jobs", Some("Sponsored post"))));
+        assert!(KeywordFilter::for_feed(None).matches(&entry("Anything", None)));
+    }
+}
@@ -0,0 +1,65 @@
+-- Full-text search over entries and their AI summaries
+--
+-- The original entries_fts was an external-content table over `entries`,
+-- which cannot include summaries.summary_text. Replace it with a standalone
+-- FTS5 table keyed by entries.rowid and maintained from both tables.
+
+DROP TRIGGER IF EXISTS entries_fts_insert;
+DROP TRIGGER IF EXISTS entries_fts_delete;
+DROP TRIGGER IF EXISTS entries_fts_update;
+DROP TABLE IF EXISTS entries_fts;
+
+CREATE VIRTUAL TABLE entries_fts USING fts5(
+    title,
+    content_text,
+    summary,
+    summary_text,
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+-- Backfill existing rows
+INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text)
+SELECT e.rowid, e.title, e.content_text, e.summary, s.summary_text
+FROM entries e
+LEFT JOIN summaries s ON s.entry_id = e.id;
+
+-- Entry triggers (only re-index when searchable columns change)
+CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text)
+    VALUES (new.rowid, new.title, new.content_text, new.summary,
+            (SELECT summary_text FROM summaries WHERE entry_id = new.id));
+END;
+
+CREATE TRIGGER entries_fts_delete AFTER DELETE ON entries BEGIN
+    DELETE FROM entries_fts WHERE rowid = old.rowid;
+END;
+
+CREATE TRIGGER entries_fts_update AFTER UPDATE OF title, content_text, summary ON entries BEGIN
+    DELETE FROM entries_fts WHERE rowid = old.rowid;
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text)
+    VALUES (new.rowid, new.title, new.content_text, new.summary,
+            (SELECT summary_text FROM summaries WHERE entry_id = new.id));
+END;
+
+-- Summary triggers re-index the owning entry
+CREATE TRIGGER summaries_fts_insert AFTER INSERT ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text)
+    SELECT rowid, title, content_text, summary, new.summary_text
+    FROM entries WHERE id = new.entry_id;
+END;
+
+CREATE TRIGGER summaries_fts_update AFTER UPDATE OF summary_text ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text)
+    SELECT rowid, title, content_text, summary, new.summary_text
+    FROM entries WHERE id = new.entry_id;
+END;
+
+CREATE TRIGGER summaries_fts_delete AFTER DELETE ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = old.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text)
+    SELECT rowid, title, content_text, summary, NULL
+    FROM entries WHERE id = old.entry_id;
+END;
@@ -0,0 +1,6 @@
+-- Starred/bookmarked entries
+
+ALTER TABLE entries ADD COLUMN starred BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE entries ADD COLUMN starred_at DATETIME;
+
+CREATE INDEX idx_entries_starred ON entries(starred_at DESC) WHERE starred = 1;
@@ -0,0 +1,34 @@
+-- Normalized tag storage
+--
+-- `entries.categories` stays as the raw feed payload; tags are the queryable
+-- form and also hold user- and AI-assigned labels.
+
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS entry_tags (
+    entry_id TEXT NOT NULL,
+    tag_id INTEGER NOT NULL,
+    source TEXT NOT NULL DEFAULT 'user', -- feed, user, ai
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (entry_id, tag_id),
+    FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_entry_tags_tag_id ON entry_tags(tag_id);
+
+-- Backfill from the JSON categories column
+INSERT OR IGNORE INTO tags (name)
+SELECT DISTINCT trim(c.value)
+FROM entries e, json_each(e.categories) c
+WHERE e.categories IS NOT NULL AND json_valid(e.categories) AND trim(c.value) != '';
+
+INSERT OR IGNORE INTO entry_tags (entry_id, tag_id, source)
+SELECT e.id, t.id, 'feed'
+FROM entries e, json_each(e.categories) c
+JOIN tags t ON t.name = trim(c.value)
+WHERE e.categories IS NOT NULL AND json_valid(e.categories);
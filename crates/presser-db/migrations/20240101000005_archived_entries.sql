@@ -0,0 +1,23 @@
+-- Archive for entries removed by retention pruning
+--
+-- No foreign key to feeds: archived entries outlive their feed. The AI
+-- summary is copied inline because summaries cascade with the entry.
+
+CREATE TABLE IF NOT EXISTS archived_entries (
+    id TEXT PRIMARY KEY NOT NULL,
+    feed_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    author TEXT,
+    published DATETIME,
+    summary TEXT,
+    content_html TEXT,
+    content_text TEXT,
+    categories TEXT, -- JSON array
+    summary_text TEXT,
+    created_at DATETIME NOT NULL,
+    archived_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_archived_entries_feed_id ON archived_entries(feed_id);
+CREATE INDEX idx_archived_entries_published ON archived_entries(published DESC);
@@ -0,0 +1,6 @@
+-- Relevance score and indices backing EntryFilter
+
+ALTER TABLE entries ADD COLUMN score REAL;
+
+CREATE INDEX idx_entries_score ON entries(score DESC) WHERE score IS NOT NULL;
+CREATE INDEX idx_entries_read_published ON entries(read, published DESC);
@@ -0,0 +1,16 @@
+-- Entry embeddings for semantic search and related articles
+--
+-- Vectors are stored as little-endian f32 BLOBs, the layout sqlite-vec reads
+-- natively, so the optional extension needs no separate copy of the data.
+
+CREATE TABLE IF NOT EXISTS embeddings (
+    entry_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    dims INTEGER NOT NULL,
+    vector BLOB NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (entry_id, model),
+    FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_embeddings_model ON embeddings(model, dims);
@@ -0,0 +1,11 @@
+-- Per-feed HTTP fetch state for conditional GET and failure tracking
+
+CREATE TABLE IF NOT EXISTS feed_fetch_state (
+    feed_id TEXT PRIMARY KEY NOT NULL,
+    etag TEXT,
+    last_modified TEXT,
+    last_status INTEGER,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
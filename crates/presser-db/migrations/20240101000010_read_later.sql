@@ -0,0 +1,9 @@
+-- Read-later queue, ordered by position (0 is the front)
+
+CREATE TABLE IF NOT EXISTS read_later (
+    entry_id TEXT PRIMARY KEY NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+    added_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    position INTEGER NOT NULL
+);
+
+CREATE INDEX idx_read_later_position ON read_later(position);
@@ -0,0 +1,4 @@
+-- Lifetime fetch counters for per-feed failure rates
+
+ALTER TABLE feed_fetch_state ADD COLUMN total_fetches INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE feed_fetch_state ADD COLUMN total_failures INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,109 @@
+-- Notes and highlights on entries, searchable through entries_fts
+--
+-- entries_fts gains a `notes` column. Every indexed row is now built from
+-- the entries_fts_source view, so each trigger just re-indexes one entry.
+
+CREATE TABLE IF NOT EXISTS notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entry_id TEXT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+    selection_text TEXT,
+    note_text TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK (selection_text IS NOT NULL OR note_text IS NOT NULL)
+);
+
+CREATE INDEX idx_notes_entry_id ON notes(entry_id);
+
+DROP TRIGGER IF EXISTS entries_fts_insert;
+DROP TRIGGER IF EXISTS entries_fts_delete;
+DROP TRIGGER IF EXISTS entries_fts_update;
+DROP TRIGGER IF EXISTS summaries_fts_insert;
+DROP TRIGGER IF EXISTS summaries_fts_update;
+DROP TRIGGER IF EXISTS summaries_fts_delete;
+DROP TABLE IF EXISTS entries_fts;
+
+CREATE VIEW entries_fts_source AS
+SELECT e.rowid AS entry_rowid,
+       e.id AS entry_id,
+       e.title,
+       e.content_text,
+       e.summary,
+       (SELECT summary_text FROM summaries WHERE entry_id = e.id) AS summary_text,
+       (SELECT group_concat(COALESCE(selection_text, '') || ' ' || COALESCE(note_text, ''), ' ')
+        FROM notes WHERE entry_id = e.id) AS notes
+FROM entries e;
+
+CREATE VIRTUAL TABLE entries_fts USING fts5(
+    title,
+    content_text,
+    summary,
+    summary_text,
+    notes,
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+SELECT entry_rowid, title, content_text, summary, summary_text, notes FROM entries_fts_source;
+
+-- Entry triggers (only re-index when searchable columns change)
+CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.id;
+END;
+
+CREATE TRIGGER entries_fts_delete AFTER DELETE ON entries BEGIN
+    DELETE FROM entries_fts WHERE rowid = old.rowid;
+END;
+
+CREATE TRIGGER entries_fts_update AFTER UPDATE OF title, content_text, summary ON entries BEGIN
+    DELETE FROM entries_fts WHERE rowid = old.rowid;
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.id;
+END;
+
+-- Summary and note triggers re-index the owning entry
+CREATE TRIGGER summaries_fts_insert AFTER INSERT ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER summaries_fts_update AFTER UPDATE OF summary_text ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER summaries_fts_delete AFTER DELETE ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = old.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = old.entry_id;
+END;
+
+CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER notes_fts_update AFTER UPDATE OF selection_text, note_text ON notes BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = old.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = old.entry_id;
+END;
@@ -0,0 +1,5 @@
+-- Hidden entries: suppressed from every view but kept so refetches don't resurrect them
+
+ALTER TABLE entries ADD COLUMN hidden INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_entries_hidden ON entries(hidden);
@@ -0,0 +1,105 @@
+-- Keep every summary version per entry; exactly one may be current
+--
+-- summaries is rebuilt with its own id. The FTS view and every trigger that
+-- reads it are dropped first and recreated afterwards, since SQLite checks
+-- them when the new table is renamed into place.
+
+DROP TRIGGER IF EXISTS entries_fts_insert;
+DROP TRIGGER IF EXISTS entries_fts_update;
+DROP TRIGGER IF EXISTS summaries_fts_insert;
+DROP TRIGGER IF EXISTS summaries_fts_update;
+DROP TRIGGER IF EXISTS summaries_fts_delete;
+DROP TRIGGER IF EXISTS notes_fts_insert;
+DROP TRIGGER IF EXISTS notes_fts_update;
+DROP TRIGGER IF EXISTS notes_fts_delete;
+DROP VIEW IF EXISTS entries_fts_source;
+
+CREATE TABLE summaries_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entry_id TEXT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+    summary_text TEXT NOT NULL,
+    model TEXT NOT NULL,
+    style TEXT NOT NULL DEFAULT '',
+    tokens INTEGER,
+    content_hash TEXT NOT NULL,
+    is_current INTEGER NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO summaries_new (entry_id, summary_text, model, tokens, content_hash, created_at)
+SELECT entry_id, summary_text, model, tokens, content_hash, created_at FROM summaries;
+
+DROP TABLE summaries;
+ALTER TABLE summaries_new RENAME TO summaries;
+
+CREATE UNIQUE INDEX idx_summaries_current ON summaries(entry_id) WHERE is_current = 1;
+CREATE UNIQUE INDEX idx_summaries_version
+    ON summaries(entry_id, model, style, content_hash, summary_text);
+CREATE INDEX idx_summaries_content_hash ON summaries(content_hash);
+CREATE INDEX idx_summaries_created_at ON summaries(created_at DESC);
+
+CREATE VIEW entries_fts_source AS
+SELECT e.rowid AS entry_rowid,
+       e.id AS entry_id,
+       e.title,
+       e.content_text,
+       e.summary,
+       (SELECT summary_text FROM summaries WHERE entry_id = e.id AND is_current = 1) AS summary_text,
+       (SELECT group_concat(COALESCE(selection_text, '') || ' ' || COALESCE(note_text, ''), ' ')
+        FROM notes WHERE entry_id = e.id) AS notes
+FROM entries e;
+
+CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.id;
+END;
+
+CREATE TRIGGER entries_fts_update AFTER UPDATE OF title, content_text, summary ON entries BEGIN
+    DELETE FROM entries_fts WHERE rowid = old.rowid;
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.id;
+END;
+
+CREATE TRIGGER summaries_fts_insert AFTER INSERT ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER summaries_fts_update AFTER UPDATE OF summary_text, is_current ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER summaries_fts_delete AFTER DELETE ON summaries BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = old.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = old.entry_id;
+END;
+
+CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER notes_fts_update AFTER UPDATE OF selection_text, note_text ON notes BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = new.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = new.entry_id;
+END;
+
+CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+    DELETE FROM entries_fts WHERE rowid = (SELECT rowid FROM entries WHERE id = old.entry_id);
+    INSERT INTO entries_fts(rowid, title, content_text, summary, summary_text, notes)
+    SELECT entry_rowid, title, content_text, summary, summary_text, notes
+    FROM entries_fts_source WHERE entry_id = old.entry_id;
+END;
@@ -0,0 +1,15 @@
+-- Enclosures (podcast audio, attachments) and their download state
+
+CREATE TABLE IF NOT EXISTS enclosures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entry_id TEXT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    mime_type TEXT,
+    length INTEGER,
+    local_path TEXT,
+    downloaded_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (entry_id, url)
+);
+
+CREATE INDEX idx_enclosures_pending ON enclosures(entry_id) WHERE local_path IS NULL;
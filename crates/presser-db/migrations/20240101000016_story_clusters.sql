@@ -0,0 +1,25 @@
+-- Clusters of near-duplicate entries (the same story from several sources)
+
+CREATE TABLE IF NOT EXISTS story_clusters (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- An entry belongs to at most one cluster
+CREATE TABLE IF NOT EXISTS story_cluster_members (
+    entry_id TEXT PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+    cluster_id INTEGER NOT NULL REFERENCES story_clusters(id) ON DELETE CASCADE,
+    is_representative BOOLEAN NOT NULL DEFAULT 0,
+    added_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_story_cluster_members_cluster ON story_cluster_members(cluster_id);
+CREATE UNIQUE INDEX idx_story_cluster_members_representative
+    ON story_cluster_members(cluster_id) WHERE is_representative = 1;
+
+-- A cluster left with a single member is no longer a duplicate group
+CREATE TRIGGER story_cluster_members_ad AFTER DELETE ON story_cluster_members
+WHEN (SELECT COUNT(*) FROM story_cluster_members WHERE cluster_id = old.cluster_id) < 2
+BEGIN
+    DELETE FROM story_clusters WHERE id = old.cluster_id;
+END;
@@ -0,0 +1,21 @@
+-- entries.updated_at doubles as a version for compare-and-swap updates.
+-- Entry writes now stamp it with millisecond precision, every update moves
+-- it forward by at least 1 ms, and tag changes count as a change to the entry.
+
+-- Two writes within the same millisecond must still yield distinct versions
+-- (recursive triggers are off, so the bump does not re-fire this trigger)
+CREATE TRIGGER entries_version_bump AFTER UPDATE ON entries
+WHEN julianday(new.updated_at) <= julianday(old.updated_at)
+BEGIN
+    UPDATE entries
+    SET updated_at = strftime('%Y-%m-%d %H:%M:%f', julianday(old.updated_at) + 1.0 / 86400000)
+    WHERE rowid = new.rowid;
+END;
+
+CREATE TRIGGER entry_tags_touch_insert AFTER INSERT ON entry_tags BEGIN
+    UPDATE entries SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.entry_id;
+END;
+
+CREATE TRIGGER entry_tags_touch_delete AFTER DELETE ON entry_tags BEGIN
+    UPDATE entries SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = old.entry_id;
+END;
@@ -0,0 +1,9 @@
+-- Short-lived claims on feeds being updated, so the daemon and CLI processes
+-- sharing a database never fetch the same feed at the same time
+
+CREATE TABLE IF NOT EXISTS feed_leases (
+    feed_id TEXT PRIMARY KEY NOT NULL,
+    holder TEXT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
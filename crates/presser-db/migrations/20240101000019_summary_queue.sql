@@ -0,0 +1,20 @@
+-- Entries waiting for an AI summary. Entries are queued before they are
+-- summarized and leave the queue once summarized, so whatever the rate limit
+-- or daily token budget defers (or a crash interrupts) is picked up later
+
+CREATE TABLE IF NOT EXISTS summary_queue (
+    entry_id TEXT PRIMARY KEY NOT NULL,
+    enqueued_at DATETIME NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_summary_queue_enqueued ON summary_queue(enqueued_at);
+
+-- AI requests and tokens spent per UTC day, for the daily token budget
+CREATE TABLE IF NOT EXISTS ai_usage (
+    day TEXT PRIMARY KEY NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    tokens INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,4 @@
+-- New address of a feed whose fetches were permanently redirected, kept until
+-- `presser fix-urls` (or auto_fix_urls) moves the feed there
+
+ALTER TABLE feed_fetch_state ADD COLUMN moved_to TEXT;
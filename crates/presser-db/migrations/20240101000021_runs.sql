@@ -0,0 +1,21 @@
+-- One row per feed per update run, for `presser log`. Rows outlive their feed
+-- so the log still explains what happened to a removed feed
+
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id TEXT NOT NULL,
+    feed_id TEXT NOT NULL,
+    started_at DATETIME NOT NULL,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    new_entries INTEGER NOT NULL DEFAULT 0,
+    summarized INTEGER NOT NULL DEFAULT 0,
+    skipped INTEGER NOT NULL DEFAULT 0,
+    deferred INTEGER NOT NULL DEFAULT 0,
+    tokens INTEGER NOT NULL DEFAULT 0,
+    not_modified BOOLEAN NOT NULL DEFAULT 0,
+    failed BOOLEAN NOT NULL DEFAULT 0,
+    errors TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_runs_started ON runs(started_at);
+CREATE INDEX IF NOT EXISTS idx_runs_feed ON runs(feed_id, started_at);
@@ -0,0 +1,9 @@
+-- Saved searches shown as folders in the TUI. `query` is the search as typed,
+-- filters included, and is run again each time the folder is opened
+
+CREATE TABLE IF NOT EXISTS smart_folders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    query TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,19 @@
+-- TUI state kept across restarts: named values (the last open list and
+-- entry), and how far each entry was scrolled in the reader
+
+CREATE TABLE IF NOT EXISTS ui_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- `max_scroll` is the scroll offset of the article's last screen when it was
+-- saved; an entry is partly read while 0 < scroll < max_scroll
+CREATE TABLE IF NOT EXISTS reading_positions (
+    entry_id TEXT PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+    scroll INTEGER NOT NULL,
+    max_scroll INTEGER NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_reading_positions_updated ON reading_positions(updated_at);
@@ -0,0 +1,4 @@
+-- SHA-256 of the last feed body that was processed, so a feed that sends no
+-- validators (or ignores them) but serves the same document is skipped
+
+ALTER TABLE feed_fetch_state ADD COLUMN content_hash TEXT;
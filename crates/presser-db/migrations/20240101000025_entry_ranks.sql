@@ -0,0 +1,12 @@
+-- Combined rank per recent entry (AI relevance, feed priority, recency, and
+-- social signals), rewritten after each update run. Orders digests and the
+-- TUI's top view
+
+CREATE TABLE IF NOT EXISTS entry_ranks (
+    entry_id TEXT PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+    rank REAL NOT NULL,
+    social_score INTEGER,
+    ranked_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_entry_ranks_rank ON entry_ranks(rank DESC);
@@ -0,0 +1,10 @@
+-- Mute rules: fetched entries matching one are dropped until it expires
+-- (`expires_at` NULL: until removed). `kind` is keyword, regex, or tag
+
+CREATE TABLE IF NOT EXISTS mute_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    expires_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,14 @@
+-- Archived article pages: the HTML of each entry's page, stored compressed
+-- in the archive directory under its SHA-256 `hash` (pages with the same
+-- content share a file). No foreign key, so an entry's page stays findable
+-- after retention prunes the entry
+
+CREATE TABLE IF NOT EXISTS archived_pages (
+    entry_id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    archived_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_archived_pages_hash ON archived_pages(hash);
@@ -0,0 +1,19 @@
+-- Discussions of entries: the comments page and comment feed a feed gives
+-- for an entry, and its latest comment count. Counts are also appended to
+-- comment_counts whenever they change, so their growth can be followed
+
+CREATE TABLE IF NOT EXISTS entry_discussions (
+    entry_id TEXT PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+    url TEXT,
+    comment_feed TEXT,
+    comments INTEGER,
+    checked_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS comment_counts (
+    entry_id TEXT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+    comments INTEGER NOT NULL,
+    recorded_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_counts_entry ON comment_counts(entry_id, recorded_at);
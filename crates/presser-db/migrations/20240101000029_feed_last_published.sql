@@ -0,0 +1,4 @@
+-- Newest publication date a feed has listed, whatever its filters kept, so
+-- feeds that stopped publishing can be told from ones that are just filtered
+
+ALTER TABLE feed_fetch_state ADD COLUMN last_published DATETIME;
@@ -0,0 +1,12 @@
+-- Machine translations of entries from feeds with translate_to. The entry
+-- keeps its original text; the translation is stored beside it
+
+CREATE TABLE IF NOT EXISTS entry_translations (
+    entry_id TEXT PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    source_language TEXT NOT NULL,
+    content_text TEXT NOT NULL,
+    model TEXT NOT NULL,
+    tokens INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
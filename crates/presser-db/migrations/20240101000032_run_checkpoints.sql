@@ -0,0 +1,12 @@
+-- Feed updates in progress past their checkpoint: entries stored, fetch
+-- state saved, summaries queued. A row is removed once the update is logged
+-- in runs, so one left behind belongs to an update that was interrupted
+
+CREATE TABLE IF NOT EXISTS run_checkpoints (
+    feed_id TEXT PRIMARY KEY NOT NULL,
+    run_id TEXT NOT NULL,
+    started_at DATETIME NOT NULL,
+    new_entries INTEGER NOT NULL DEFAULT 0,
+    checkpointed_at DATETIME NOT NULL,
+    FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- User agent a feed's last successful fetch needed after the configured one
+-- was refused with 403, so feeds that block Presser can be listed and
+-- fetched with it directly next time
+
+ALTER TABLE feed_fetch_state ADD COLUMN alternate_user_agent TEXT;
@@ -0,0 +1,18 @@
+-- Earlier versions of entries whose text changed after they were stored
+-- (corrections, major edits): the text replaced, a line diff against the
+-- new one, and an AI description of the change when one was asked for
+
+CREATE TABLE IF NOT EXISTS entry_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entry_id TEXT NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+    previous_text TEXT NOT NULL,
+    diff TEXT NOT NULL,
+    lines_added INTEGER NOT NULL DEFAULT 0,
+    lines_removed INTEGER NOT NULL DEFAULT 0,
+    change_summary TEXT,
+    model TEXT,
+    tokens INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_entry_revisions_entry ON entry_revisions(entry_id, created_at);
@@ -0,0 +1,5 @@
+-- Bytes downloaded per feed: by each update (feed, extracted pages, comment
+-- feeds) and in total, including images shown in the reader
+
+ALTER TABLE runs ADD COLUMN bytes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE feed_fetch_state ADD COLUMN total_bytes INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- Download progress and playback position of enclosures, for the TUI's
+-- downloads view and its player
+
+ALTER TABLE enclosures ADD COLUMN downloaded_bytes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE enclosures ADD COLUMN download_started_at DATETIME;
+ALTER TABLE enclosures ADD COLUMN download_error TEXT;
+ALTER TABLE enclosures ADD COLUMN position_secs INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_enclosures_downloads ON enclosures(download_started_at)
+    WHERE download_started_at IS NOT NULL;
@@ -0,0 +1,13 @@
+-- What identifies an entry across feeds: its address without tracking
+-- parameters and a hash of its text, for finding the posts mirror feeds
+-- (planets, aggregators) repeat. Entries stored before this have none.
+
+CREATE TABLE IF NOT EXISTS entry_fingerprints (
+    entry_id TEXT PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+    canonical_url TEXT NOT NULL,
+    text_hash TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_entry_fingerprints_url ON entry_fingerprints(canonical_url);
+CREATE INDEX IF NOT EXISTS idx_entry_fingerprints_text ON entry_fingerprints(text_hash)
+    WHERE text_hash IS NOT NULL;
@@ -0,0 +1,12 @@
+-- Tokens obtained for feeds behind an API (`[feed.auth]`): the access token
+-- a refresh hook last gave, and the refresh token to use next when the
+-- token endpoint rotates it
+
+CREATE TABLE IF NOT EXISTS feed_tokens (
+    feed_id TEXT PRIMARY KEY NOT NULL,
+    access_token TEXT NOT NULL,
+    refresh_token TEXT,
+    expires_at DATETIME,
+    refreshed_at DATETIME NOT NULL,
+    FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
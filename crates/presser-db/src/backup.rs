@@ -0,0 +1,186 @@
+//! Online backup, restore, and integrity verification
+//!
+//! Backups use `VACUUM INTO`, which writes a consistent, compacted snapshot
+//! from a read transaction. It is safe under WAL while other connections
+//! (e.g. the daemon) keep writing. Under SQLCipher the snapshot is
+//! encrypted with the same key as the source database.
+
+use crate::connect_options;
+use anyhow::{Context, Result};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+/// Tables a file must contain to be accepted as a Presser database
+const REQUIRED_TABLES: &[&str] = &["feeds", "entries", "summaries"];
+
+/// Write a consistent snapshot of the database to `dest`
+///
+/// Fails if `dest` already exists. The snapshot is verified after writing.
+pub async fn backup(pool: &SqlitePool, dest: &Path, key: Option<&str>) -> Result<()> {
+    if dest.exists() {
+        anyhow::bail!("Backup target already exists: {}", dest.display());
+    }
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(dest.to_string_lossy().as_ref())
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to back up database to {}", dest.display()))?;
+
+    let problems = verify_file(dest, key).await?;
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "Backup {} failed verification:\n{}",
+            dest.display(),
+            problems.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Run `PRAGMA integrity_check`, returning the problems found (empty if healthy)
+pub async fn integrity_check(pool: &SqlitePool) -> Result<Vec<String>> {
+    let rows: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await
+        .context("Failed to run integrity check")?;
+    Ok(rows.into_iter().filter(|r| r != "ok").collect())
+}
+
+/// Check that a database file is intact and looks like a Presser database
+///
+/// `key` is needed to read a SQLCipher-encrypted file.
+pub async fn verify_file(path: &Path, key: Option<&str>) -> Result<Vec<String>> {
+    if !path.is_file() {
+        anyhow::bail!("No database file at {}", path.display());
+    }
+    // Not read-only: FTS5's integrity check needs write access to run
+    let options = connect_options(path, key)?;
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let mut problems = integrity_check(&pool).await?;
+    for table in REQUIRED_TABLES {
+        let found: Option<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(table)
+                .fetch_optional(&pool)
+                .await
+                .context("Failed to inspect schema")?;
+        if found.is_none() {
+            problems.push(format!("missing table `{}`", table));
+        }
+    }
+    pool.close().await;
+    Ok(problems)
+}
+
+/// Replace the database at `target` with a verified backup
+///
+/// Nothing may have `target` open while this runs. The previous database
+/// (and its WAL) is kept next to it with a `.pre-restore` suffix.
+/// Returns the path of that saved copy, if there was one.
+pub async fn restore(backup: &Path, target: &Path, key: Option<&str>) -> Result<Option<PathBuf>> {
+    let problems = verify_file(backup, key).await?;
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "Refusing to restore {}:\n{}",
+            backup.display(),
+            problems.join("\n")
+        );
+    }
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    // Copy first so a failure leaves the current database untouched
+    let staging = with_suffix(target, ".restore-tmp");
+    tokio::fs::copy(backup, &staging)
+        .await
+        .with_context(|| format!("Failed to copy {}", backup.display()))?;
+
+    let saved = if target.exists() {
+        let saved = with_suffix(target, ".pre-restore");
+        tokio::fs::rename(target, &saved).await?;
+        Some(saved)
+    } else {
+        None
+    };
+    for sidecar in ["-wal", "-shm"] {
+        let path = with_suffix(target, sidecar);
+        if path.exists() {
+            let dest = with_suffix(target, &format!(".pre-restore{}", sidecar));
+            tokio::fs::rename(&path, dest).await?;
+        }
+    }
+
+    tokio::fs::rename(&staging, target)
+        .await
+        .with_context(|| format!("Failed to move restored database to {}", target.display()))?;
+    Ok(saved)
+}
+
+/// Append a suffix to a file name (`presser.db` + `-wal` → `presser.db-wal`)
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::{Database, Feed};
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        let (db, dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "Before".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let backup_path = dir.path().join("backups/snapshot.db");
+        db.backup(&backup_path).await.unwrap();
+        assert!(db.backup(&backup_path).await.is_err(), "must not overwrite");
+        assert!(db.integrity_check().await.unwrap().is_empty());
+
+        db.delete_feed("f1").await.unwrap();
+        db.close().await;
+
+        let target = dir.path().join("test.db");
+        let saved = Database::restore(&backup_path, &target, None)
+            .await
+            .unwrap();
+        assert!(saved.unwrap().ends_with("test.db.pre-restore"));
+
+        let restored = Database::open(&target).await.unwrap();
+        restored.migrate().await.unwrap();
+        let feed = restored.get_feed("f1").await.unwrap().unwrap();
+        assert_eq!(feed.title, "Before");
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_foreign_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let bogus = dir.path().join("notes.db");
+        let other = Database::open(&bogus).await.unwrap();
+        other.close().await;
+
+        let target = dir.path().join("presser.db");
+        let err = Database::restore(&bogus, &target, None).await.unwrap_err();
+        assert!(err.to_string().contains("missing table `entries`"));
+        assert!(!target.exists());
+    }
+}
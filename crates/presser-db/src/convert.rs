@@ -0,0 +1,190 @@
+//! Conversions between what feeds give (`presser_feeds`) and what is stored
+//! (`feeds` feature)
+//!
+//! A fetched entry is stored in pieces: the entry itself, with its
+//! categories as a JSON array, and its enclosures and discussion in their
+//! own tables. Converting an entry leaves those two out; they convert on
+//! their own, and the feed and entry they belong to are filled in by the
+//! caller:
+//!
+//! ```rust,ignore
+//! let enclosures: Vec<Enclosure> = fetched.enclosures.drain(..).map(Enclosure::from).collect();
+//! let entry = Entry { feed_id: feed_id.to_string(), ..Entry::from(fetched) };
+//! ```
+//!
+//! Publication and update times carry over as they are; when the entry was
+//! stored (`created_at`, `updated_at`) is the time of conversion.
+
+use chrono::Utc;
+use presser_feeds::{Discussion, FeedEnclosure, FeedEntry};
+
+use crate::models::{categories_json, Enclosure, Entry, EntryDiscussion};
+
+/// The entry as stored, without feed (`feed_id` is empty)
+impl From<FeedEntry> for Entry {
+    fn from(entry: FeedEntry) -> Self {
+        Entry {
+            id: entry.id,
+            title: entry.title,
+            url: entry.url,
+            author: entry.author,
+            published: entry.published,
+            updated: entry.updated,
+            summary: entry.summary,
+            content_html: entry.content_html,
+            content_text: entry.content_text,
+            categories: categories_json(&entry.categories),
+            ..Default::default()
+        }
+    }
+}
+
+/// The entry as its feed gave it, without enclosures or discussion
+impl From<&Entry> for FeedEntry {
+    fn from(entry: &Entry) -> Self {
+        FeedEntry {
+            id: entry.id.clone(),
+            title: entry.title.clone(),
+            url: entry.url.clone(),
+            published: entry.published,
+            updated: entry.updated,
+            summary: entry.summary.clone(),
+            content_html: entry.content_html.clone(),
+            content_text: entry.content_text.clone(),
+            author: entry.author.clone(),
+            categories: entry.category_list(),
+            discussion: None,
+            enclosures: Vec::new(),
+        }
+    }
+}
+
+/// The enclosure as stored, not yet downloaded (`entry_id` is empty)
+impl From<FeedEnclosure> for Enclosure {
+    fn from(enclosure: FeedEnclosure) -> Self {
+        Enclosure {
+            url: enclosure.url,
+            mime_type: enclosure.mime_type,
+            length: enclosure.length,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&Enclosure> for FeedEnclosure {
+    fn from(enclosure: &Enclosure) -> Self {
+        FeedEnclosure {
+            url: enclosure.url.clone(),
+            mime_type: enclosure.mime_type.clone(),
+            length: enclosure.length,
+        }
+    }
+}
+
+/// The discussion as stored, checked now (`entry_id` is empty)
+impl From<Discussion> for EntryDiscussion {
+    fn from(discussion: Discussion) -> Self {
+        EntryDiscussion {
+            entry_id: String::new(),
+            url: discussion.url,
+            comment_feed: discussion.comment_feed,
+            comments: discussion.comments,
+            checked_at: Utc::now(),
+        }
+    }
+}
+
+impl From<&EntryDiscussion> for Discussion {
+    fn from(discussion: &EntryDiscussion) -> Self {
+        Discussion {
+            url: discussion.url.clone(),
+            comment_feed: discussion.comment_feed.clone(),
+            comments: discussion.comments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration};
+
+    fn fetched() -> FeedEntry {
+        let published: DateTime<Utc> = "2024-05-16T15:00:00Z".parse().unwrap();
+        FeedEntry {
+            id: "post-1".into(),
+            title: "Async closures".into(),
+            url: "https://blog.example.com/post-1".into(),
+            published: Some(published),
+            updated: Some(published + Duration::hours(2)),
+            summary: Some("Teaser".into()),
+            content_html: Some("<p>Full text</p>".into()),
+            content_text: Some("Full text".into()),
+            author: Some("Ferris".into()),
+            categories: vec!["rust".into(), "async \"closures\"".into()],
+            discussion: Some(Discussion {
+                url: Some("https://news.ycombinator.com/item?id=1".into()),
+                comment_feed: None,
+                comments: Some(42),
+            }),
+            enclosures: vec![FeedEnclosure {
+                url: "https://cdn.example.com/ep.mp3".into(),
+                mime_type: Some("audio/mpeg".into()),
+                length: Some(1024),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_entry_round_trip() {
+        let mut original = fetched();
+        let discussion = original.discussion.take().unwrap();
+        let enclosures = std::mem::take(&mut original.enclosures);
+        let entry = Entry {
+            feed_id: "blog".into(),
+            ..Entry::from(original.clone())
+        };
+        assert_eq!(
+            entry.categories.as_deref(),
+            Some(r#"["rust","async \"closures\""]"#)
+        );
+        assert_eq!(entry.published, original.published);
+        assert!(!entry.read && !entry.hidden);
+
+        let back = FeedEntry::from(&entry);
+        assert_eq!(
+            serde_json::to_value(&back).unwrap(),
+            serde_json::to_value(&original).unwrap()
+        );
+
+        let stored = EntryDiscussion {
+            entry_id: entry.id.clone(),
+            ..EntryDiscussion::from(discussion.clone())
+        };
+        assert_eq!(Discussion::from(&stored), discussion);
+        for enclosure in enclosures {
+            let stored = Enclosure::from(enclosure.clone());
+            assert_eq!(
+                (stored.downloaded_bytes, stored.local_path.as_deref()),
+                (0, None)
+            );
+            assert_eq!(FeedEnclosure::from(&stored), enclosure);
+        }
+    }
+
+    #[test]
+    fn test_no_categories() {
+        let entry = Entry::from(FeedEntry {
+            categories: Vec::new(),
+            ..fetched()
+        });
+        assert_eq!(entry.categories, None);
+        assert!(FeedEntry::from(&entry).categories.is_empty());
+        // Not a JSON array: none
+        let garbled = Entry {
+            categories: Some("rust, async".into()),
+            ..entry
+        };
+        assert!(garbled.category_list().is_empty());
+    }
+}
@@ -13,6 +13,14 @@ pub enum DatabaseError {
     #[error("Duplicate record: {0}")]
     Duplicate(String),
 
+    /// Value rejected before it reached the database (an empty name)
+    #[error("Invalid value: {0}")]
+    Invalid(String),
+
+    /// Record changed since it was read (optimistic concurrency)
+    #[error("Conflicting update: {0}")]
+    Conflict(String),
+
     /// Migration failed
     #[error("Migration failed: {0}")]
     MigrationError(String),
@@ -27,5 +35,63 @@ pub enum DatabaseError {
 
     /// Generic error
     #[error(transparent)]
-    Other(#[from] anyhow::Error),
+    Other(anyhow::Error),
+}
+
+impl DatabaseError {
+    /// Build a `NotFound` error for a kind of record and its key
+    pub(crate) fn not_found(what: &str, key: impl std::fmt::Display) -> anyhow::Error {
+        Self::NotFound(format!("{} {}", what, key)).into()
+    }
+
+    /// Build an `Invalid` error
+    pub(crate) fn invalid(message: &str) -> anyhow::Error {
+        Self::Invalid(message.to_string()).into()
+    }
+
+    /// Whether this is a `NotFound` error
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(_))
+    }
+
+    /// Whether this is a `Duplicate` error
+    pub fn is_duplicate(&self) -> bool {
+        matches!(self, Self::Duplicate(_))
+    }
+
+    /// Whether this is an `Invalid` error
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, Self::Invalid(_))
+    }
+
+    /// Whether this is a `Conflict` error
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, Self::Conflict(_))
+    }
+}
+
+/// Classify errors from the query layer
+///
+/// A `DatabaseError` raised inside a query is passed through. Unique
+/// violations become `Duplicate`, and a missing row or a foreign key pointing
+/// at nothing becomes `NotFound`; the message keeps the query's context.
+impl From<anyhow::Error> for DatabaseError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<DatabaseError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let message = || format!("{:#}", err);
+        match err.downcast_ref::<sqlx::Error>() {
+            Some(sqlx::Error::RowNotFound) => Self::NotFound(message()),
+            Some(sqlx::Error::Database(db)) if db.is_unique_violation() => {
+                Self::Duplicate(message())
+            }
+            Some(sqlx::Error::Database(db)) if db.is_foreign_key_violation() => {
+                Self::NotFound(message())
+            }
+            Some(sqlx::Error::Migrate(_)) => Self::MigrationError(message()),
+            _ => Self::Other(err),
+        }
+    }
 }
@@ -0,0 +1,452 @@
+//! Entry export and import (JSON, CSV, NDJSON)
+//!
+//! Records are flat so the same shape works for all three formats. Imports
+//! skip entries that already exist, so re-importing a file is harmless.
+
+use crate::models::{Entry, Feed};
+use crate::queries::filter::{query_entries, EntryFilter};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::str::FromStr;
+
+/// Serialization format for exports and imports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// A single JSON array
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// Guess the format from a file extension
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            other => anyhow::bail!(
+                "Unknown export format '{}' (expected json, csv, or ndjson)",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        })
+    }
+}
+
+/// One exported entry with its feed and AI summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRecord {
+    pub id: String,
+    pub feed_id: String,
+    pub feed_title: String,
+    pub feed_url: String,
+    pub title: String,
+    pub url: String,
+    pub author: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub summary: Option<String>,
+    pub content_text: Option<String>,
+    /// Feed categories as a JSON array
+    pub categories: Option<String>,
+    pub read: bool,
+    pub starred: bool,
+    pub score: Option<f64>,
+    /// AI-generated summary
+    pub ai_summary: Option<String>,
+    pub ai_model: Option<String>,
+    pub ai_content_hash: Option<String>,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Entries added
+    pub imported: u64,
+
+    /// Entries skipped because they already existed
+    pub duplicates: u64,
+
+    /// Feeds created to hold imported entries
+    pub feeds_created: u64,
+}
+
+/// Write entries matching a filter, returning how many were written
+pub async fn export_entries<W: Write>(
+    pool: &SqlitePool,
+    filter: &EntryFilter,
+    format: ExportFormat,
+    writer: W,
+) -> Result<usize> {
+    let entries = query_entries(pool, filter).await?;
+    let feeds: HashMap<String, Feed> = crate::queries::get_all_feeds(pool)
+        .await?
+        .into_iter()
+        .map(|f| (f.id.clone(), f))
+        .collect();
+
+    let mut records = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let summary = crate::queries::get_summary(pool, &entry.id).await?;
+        let feed = feeds.get(&entry.feed_id);
+        records.push(ExportRecord {
+            feed_title: feed.map(|f| f.title.clone()).unwrap_or_default(),
+            feed_url: feed.map(|f| f.url.clone()).unwrap_or_default(),
+            ai_model: summary.as_ref().map(|s| s.model.clone()),
+            ai_content_hash: summary.as_ref().map(|s| s.content_hash.clone()),
+            ai_summary: summary.map(|s| s.summary_text),
+            id: entry.id,
+            feed_id: entry.feed_id,
+            title: entry.title,
+            url: entry.url,
+            author: entry.author,
+            published: entry.published,
+            summary: entry.summary,
+            content_text: entry.content_text,
+            categories: entry.categories,
+            read: entry.read,
+            starred: entry.starred,
+            score: entry.score,
+        });
+    }
+
+    write_records(&records, format, writer)?;
+    Ok(records.len())
+}
+
+/// Serialize records in the given format
+pub fn write_records<W: Write>(
+    records: &[ExportRecord],
+    format: ExportFormat,
+    mut writer: W,
+) -> Result<()> {
+    match format {
+        ExportFormat::Json => serde_json::to_writer_pretty(&mut writer, records)?,
+        ExportFormat::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut writer, record)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(&mut writer);
+            for record in records {
+                csv.serialize(record)?;
+            }
+            csv.flush()?;
+        }
+    }
+    writer.flush().context("Failed to flush export")
+}
+
+/// Parse records in the given format
+pub fn read_records<R: Read>(format: ExportFormat, reader: R) -> Result<Vec<ExportRecord>> {
+    match format {
+        ExportFormat::Json => serde_json::from_reader(reader).context("Invalid JSON export"),
+        ExportFormat::Ndjson => BufReader::new(reader)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .map(|(i, line)| {
+                serde_json::from_str(&line?)
+                    .with_context(|| format!("Invalid NDJSON on line {}", i + 1))
+            })
+            .collect(),
+        ExportFormat::Csv => csv::Reader::from_reader(reader)
+            .deserialize()
+            .enumerate()
+            .map(|(i, record)| record.with_context(|| format!("Invalid CSV record {}", i + 1)))
+            .collect(),
+    }
+}
+
+/// Import records in one transaction, skipping entries that already exist
+///
+/// Entries go to the feed with the record's feed URL if there is one;
+/// missing feeds are created (disabled) from the record's feed columns.
+pub async fn import_entries<R: Read>(
+    pool: &SqlitePool,
+    format: ExportFormat,
+    reader: R,
+) -> Result<ImportReport> {
+    let records = read_records(format, reader)?;
+    let mut report = ImportReport::default();
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    for record in records {
+        let feed_url = if record.feed_url.is_empty() {
+            format!("presser-import:{}", record.feed_id)
+        } else {
+            record.feed_url.clone()
+        };
+        let title = if record.feed_title.is_empty() {
+            &record.feed_id
+        } else {
+            &record.feed_title
+        };
+        // A feed already subscribed to under another ID takes the entries
+        let existing: Option<String> = sqlx::query_scalar("SELECT id FROM feeds WHERE url = ?")
+            .bind(&feed_url)
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to look up feed for import")?;
+        let feed_id = match existing {
+            Some(id) => id,
+            None => {
+                report.feeds_created += sqlx::query(
+                    "INSERT OR IGNORE INTO feeds (id, url, title, enabled) VALUES (?, ?, ?, 0)",
+                )
+                .bind(&record.feed_id)
+                .bind(&feed_url)
+                .bind(title)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to create feed for import")?
+                .rows_affected();
+                record.feed_id.clone()
+            }
+        };
+
+        let entry = Entry {
+            id: record.id,
+            feed_id,
+            title: record.title,
+            url: record.url,
+            author: record.author,
+            published: record.published,
+            summary: record.summary,
+            content_text: record.content_text,
+            categories: record.categories,
+            read: record.read,
+            starred: record.starred,
+            starred_at: record.starred.then(Utc::now),
+            score: record.score,
+            ..Default::default()
+        };
+        let inserted = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO entries (id, feed_id, title, url, author, published, summary,
+                                           content_text, categories, read, starred, starred_at, score)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            "#,
+        )
+        .bind(&entry.id)
+        .bind(&entry.feed_id)
+        .bind(&entry.title)
+        .bind(&entry.url)
+        .bind(&entry.author)
+        .bind(entry.published)
+        .bind(&entry.summary)
+        .bind(&entry.content_text)
+        .bind(&entry.categories)
+        .bind(entry.read)
+        .bind(entry.starred)
+        .bind(entry.starred_at)
+        .bind(entry.score)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to import entry {}", entry.id))?
+        .rows_affected();
+
+        if inserted == 0 {
+            report.duplicates += 1;
+            continue;
+        }
+        report.imported += 1;
+
+        if let Some(summary_text) = record.ai_summary {
+            sqlx::query(
+                "INSERT OR IGNORE INTO summaries (entry_id, summary_text, model, content_hash) VALUES (?, ?, ?, ?)",
+            )
+            .bind(&entry.id)
+            .bind(summary_text)
+            .bind(record.ai_model.unwrap_or_default())
+            .bind(record.ai_content_hash.unwrap_or_default())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to import summary")?;
+        }
+    }
+
+    tx.commit().await.context("Failed to commit import")?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_db;
+    use crate::Summary;
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let (source, _dir) = setup_db().await;
+        source
+            .upsert_feed(&Feed {
+                id: "f1".into(),
+                url: "https://ex.com/f".into(),
+                title: "Feed, with comma".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        for i in 0..3 {
+            source
+                .upsert_entry(&Entry {
+                    id: format!("e{}", i),
+                    feed_id: "f1".into(),
+                    title: format!("Entry \"{}\"", i),
+                    url: format!("https://ex.com/{}", i),
+                    published: Some(Utc::now()),
+                    content_text: Some("line one\nline two".into()),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+        source.star_entry("e1").await.unwrap();
+        source
+            .upsert_summary(&Summary {
+                entry_id: "e1".into(),
+                summary_text: "short".into(),
+                model: "m".into(),
+                content_hash: "h".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        for format in [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Ndjson] {
+            let mut buf = Vec::new();
+            let written = source
+                .export_entries(&EntryFilter::new(), format, &mut buf)
+                .await
+                .unwrap();
+            assert_eq!(written, 3);
+
+            let (target, _dir) = setup_db().await;
+            let report = target.import_entries(format, buf.as_slice()).await.unwrap();
+            assert_eq!(
+                report,
+                ImportReport {
+                    imported: 3,
+                    duplicates: 0,
+                    feeds_created: 1
+                },
+                "{}",
+                format
+            );
+
+            let e1 = target.get_entry("e1").await.unwrap().unwrap();
+            assert!(e1.starred);
+            assert_eq!(e1.content_text.as_deref(), Some("line one\nline two"));
+            assert_eq!(
+                target
+                    .get_summary("e1")
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .summary_text,
+                "short"
+            );
+            assert_eq!(
+                target.get_feed("f1").await.unwrap().unwrap().title,
+                "Feed, with comma"
+            );
+
+            // Re-importing only reports duplicates
+            let again = target.import_entries(format, buf.as_slice()).await.unwrap();
+            assert_eq!(
+                again,
+                ImportReport {
+                    imported: 0,
+                    duplicates: 3,
+                    feeds_created: 0
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_into_feed_with_same_url() {
+        let (source, _dir) = setup_db().await;
+        let feed = Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            ..Default::default()
+        };
+        source.upsert_feed(&feed).await.unwrap();
+        source
+            .upsert_entry(&Entry {
+                id: "e1".into(),
+                feed_id: "f1".into(),
+                url: "https://ex.com/1".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let mut buf = Vec::new();
+        source
+            .export_entries(&EntryFilter::new(), ExportFormat::Json, &mut buf)
+            .await
+            .unwrap();
+
+        // The target has the feed under another ID
+        let (target, _dir) = setup_db().await;
+        target
+            .upsert_feed(&Feed {
+                id: "mine".into(),
+                ..feed
+            })
+            .await
+            .unwrap();
+        let report = target
+            .import_entries(ExportFormat::Json, buf.as_slice())
+            .await
+            .unwrap();
+        assert_eq!((report.imported, report.feeds_created), (1, 0));
+        assert_eq!(
+            target.get_entry("e1").await.unwrap().unwrap().feed_id,
+            "mine"
+        );
+        assert!(target.get_feed("f1").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!(
+            "JSONL".parse::<ExportFormat>().unwrap(),
+            ExportFormat::Ndjson
+        );
+        assert_eq!(
+            ExportFormat::from_path("out/archive.csv".as_ref()),
+            Some(ExportFormat::Csv)
+        );
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+}
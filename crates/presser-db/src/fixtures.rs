@@ -0,0 +1,156 @@
+//! Representative seed data for tests (`test-fixtures` feature)
+//!
+//! Two feeds with five entries between them, covering read, starred,
+//! summarized, tagged, and untouched entries:
+//!
+//! | entry           | feed        | age  | state                     |
+//! |-----------------|-------------|------|---------------------------|
+//! | `rust-async`    | `rust-blog` | 1 d  | unread, summary, tag rust |
+//! | `rust-release`  | `rust-blog` | 3 d  | read, starred, summary    |
+//! | `rust-old`      | `rust-blog` | 40 d | read                      |
+//! | `news-election` | `news`      | 2 h  | unread, tag politics      |
+//! | `news-weather`  | `news`      | 5 d  | unread                    |
+
+use crate::{Database, Entry, Feed, Result, Summary, TagSource};
+use chrono::{Duration, Utc};
+
+/// Everything `seed` inserted
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    /// Seeded feeds
+    pub feeds: Vec<Feed>,
+
+    /// Seeded entries, newest first
+    pub entries: Vec<Entry>,
+
+    /// Seeded summaries
+    pub summaries: Vec<Summary>,
+}
+
+/// Open a migrated in-memory database holding the fixtures
+pub async fn seeded() -> Result<(Database, Fixtures)> {
+    let db = Database::open_in_memory().await?;
+    db.migrate().await?;
+    let fixtures = seed(&db).await?;
+    Ok((db, fixtures))
+}
+
+/// Insert the fixtures into an already migrated database
+pub async fn seed(db: &Database) -> Result<Fixtures> {
+    let now = Utc::now();
+    let feeds = vec![
+        Feed {
+            id: "rust-blog".into(),
+            url: "https://blog.rust-lang.org/feed.xml".into(),
+            title: "Rust Blog".into(),
+            site_url: Some("https://blog.rust-lang.org".into()),
+            last_successful_fetch: Some(now),
+            ..Default::default()
+        },
+        Feed {
+            id: "news".into(),
+            url: "https://news.example.com/rss".into(),
+            title: "Example News".into(),
+            ..Default::default()
+        },
+    ];
+
+    let entry = |id: &str, feed_id: &str, title: &str, age: Duration| Entry {
+        id: id.into(),
+        feed_id: feed_id.into(),
+        title: title.into(),
+        url: format!("https://example.com/{}", id),
+        published: Some(now - age),
+        summary: Some(format!("{} (feed description)", title)),
+        content_text: Some(format!("{}. Full article text.", title)),
+        created_at: now - age,
+        ..Default::default()
+    };
+    let entries = vec![
+        entry(
+            "news-election",
+            "news",
+            "Election results are in",
+            Duration::hours(2),
+        ),
+        entry(
+            "rust-async",
+            "rust-blog",
+            "Async closures stabilized",
+            Duration::days(1),
+        ),
+        entry(
+            "rust-release",
+            "rust-blog",
+            "Announcing Rust 1.80",
+            Duration::days(3),
+        ),
+        entry(
+            "news-weather",
+            "news",
+            "Storm expected this weekend",
+            Duration::days(5),
+        ),
+        entry(
+            "rust-old",
+            "rust-blog",
+            "Looking back at 2015",
+            Duration::days(40),
+        ),
+    ];
+
+    let summary = |entry_id: &str, text: &str| Summary {
+        entry_id: entry_id.into(),
+        summary_text: text.into(),
+        model: "fixture-model".into(),
+        tokens: Some(42),
+        content_hash: format!("hash-{}", entry_id),
+        ..Default::default()
+    };
+    let summaries = vec![
+        summary("rust-async", "Async closures are now stable."),
+        summary("rust-release", "Rust 1.80 ships LazyCell and LazyLock."),
+    ];
+
+    for feed in &feeds {
+        db.upsert_feed(feed).await?;
+    }
+    db.upsert_entries(&entries).await?;
+    db.upsert_summaries(&summaries).await?;
+    db.mark_read("rust-release").await?;
+    db.mark_read("rust-old").await?;
+    db.star_entry("rust-release").await?;
+    db.add_tag("rust-async", "rust", TagSource::Feed).await?;
+    db.add_tag("news-election", "politics", TagSource::Feed)
+        .await?;
+
+    Ok(Fixtures {
+        feeds,
+        entries,
+        summaries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_seeded_in_memory() {
+        let (db, fixtures) = seeded().await.unwrap();
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats.total_feeds, fixtures.feeds.len() as i64);
+        assert_eq!(stats.total_entries, fixtures.entries.len() as i64);
+        assert_eq!(stats.unread_entries, 3);
+        assert_eq!(stats.total_summaries, 2);
+        assert_eq!(
+            db.get_starred_entries(10).await.unwrap()[0].id,
+            "rust-release"
+        );
+
+        // Transactions work on the single in-memory connection
+        let unread = db.get_unread_entries(10).await.unwrap();
+        assert_eq!(unread[0].id, "news-election");
+        assert_eq!(db.search_entries("LazyLock", 10).await.unwrap().len(), 1);
+    }
+}
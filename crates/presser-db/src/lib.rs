@@ -36,22 +36,64 @@
 //! # }
 //! ```
 
-use anyhow::{Context, Result};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
-use std::path::Path;
-use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
+pub mod backup;
+#[cfg(feature = "feeds")]
+pub mod convert;
 pub mod error;
+pub mod export;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod fixtures;
 pub mod models;
 pub mod queries;
 
 pub use error::DatabaseError;
+
+/// Result type of the public database API
+pub type Result<T, E = DatabaseError> = std::result::Result<T, E>;
+pub use export::{ExportFormat, ExportRecord, ImportReport};
 pub use models::*;
+pub use queries::embeddings::SimilarEntry;
+pub use queries::filter::{EntryFilter, EntryOrder};
+pub use queries::maintenance::MaintenanceReport;
+pub use queries::retention::{PruneReport, RetentionPolicy};
+pub use queries::search::{SearchHit, SearchOptions, HIGHLIGHT_END, HIGHLIGHT_START};
+pub use queries::users::UserEntryFilter;
+
+/// Path that `open` treats as a request for an in-memory database
+pub const MEMORY_PATH: &str = ":memory:";
+
+/// How long a connection waits for another process's write lock
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 
 /// Database connection pool and operations
+#[derive(Clone)]
 pub struct Database {
     pool: SqlitePool,
+    key: Option<String>,
+}
+
+/// Connection options for a database file, keyed for SQLCipher if `key` is set
+pub(crate) fn connect_options(
+    path: &Path,
+    key: Option<&str>,
+) -> anyhow::Result<SqliteConnectOptions> {
+    let options = SqliteConnectOptions::new().filename(path);
+    match key {
+        None => Ok(options),
+        #[cfg(feature = "encryption")]
+        Some(key) => Ok(options.pragma("key", format!("'{}'", key.replace('\'', "''")))),
+        #[cfg(not(feature = "encryption"))]
+        Some(_) => {
+            anyhow::bail!("Database encryption requires building with `--features encryption`")
+        }
+    }
 }
 
 impl Database {
@@ -59,24 +101,64 @@ impl Database {
     ///
     /// Creates the database file if it doesn't exist
     pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_key(path, None).await
+    }
+
+    /// Open a private in-memory database (gone once the value is dropped)
+    ///
+    /// The pool holds a single connection that never expires, since each
+    /// SQLite in-memory connection is its own database. Run `migrate` before use.
+    pub async fn open_in_memory() -> Result<Self> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .context("Failed to open in-memory database")?;
+        Ok(Self { pool, key: None })
+    }
+
+    /// Open a database, encrypted with SQLCipher when a key is given
+    ///
+    /// A key requires the `encryption` feature. An existing plaintext
+    /// database cannot be opened with a key (and vice versa). A path of
+    /// [`MEMORY_PATH`] opens an in-memory database.
+    pub async fn open_with_key<P: AsRef<Path>>(path: P, key: Option<&str>) -> Result<Self> {
         let path = path.as_ref();
+        if path == Path::new(MEMORY_PATH) && key.is_none() {
+            return Self::open_in_memory().await;
+        }
+
+        #[cfg(feature = "sqlite-vec")]
+        queries::embeddings::register_sqlite_vec();
 
         // Create parent directory if it doesn't exist
         if let Some(parent) = path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.display()))?
+        // The daemon and CLI commands share the file; wait out each other's
+        // write transactions instead of failing with SQLITE_BUSY
+        let options = connect_options(path, key)?
             .create_if_missing(true)
-            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+            .busy_timeout(BUSY_TIMEOUT);
 
         let pool = SqlitePoolOptions::new()
             .max_connections(5)
             .connect_with(options)
             .await
-            .context("Failed to connect to database")?;
+            .context(if key.is_some() {
+                "Failed to connect to database (wrong encryption key?)"
+            } else {
+                "Failed to connect to database"
+            })?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            key: key.map(String::from),
+        })
     }
 
     /// Run database migrations
@@ -88,79 +170,928 @@ impl Database {
         sqlx::migrate!("./migrations")
             .run(&self.pool)
             .await
-            .context("Failed to run migrations")?;
+            .map_err(|e| DatabaseError::MigrationError(e.to_string()))?;
 
         Ok(())
     }
 
     /// Insert or update a feed
     pub async fn upsert_feed(&self, feed: &Feed) -> Result<()> {
-        queries::upsert_feed(&self.pool, feed).await
+        Ok(queries::upsert_feed(&self.pool, feed).await?)
     }
 
     /// Get a feed by ID
     pub async fn get_feed(&self, id: &str) -> Result<Option<Feed>> {
-        queries::get_feed(&self.pool, id).await
+        Ok(queries::get_feed(&self.pool, id).await?)
     }
 
     /// Get all feeds
     pub async fn get_all_feeds(&self) -> Result<Vec<Feed>> {
-        queries::get_all_feeds(&self.pool).await
+        Ok(queries::get_all_feeds(&self.pool).await?)
     }
 
     /// Delete a feed and all its entries
     pub async fn delete_feed(&self, id: &str) -> Result<()> {
-        queries::delete_feed(&self.pool, id).await
+        Ok(queries::delete_feed(&self.pool, id).await?)
+    }
+
+    /// Get the stored HTTP fetch state for a feed
+    pub async fn get_fetch_state(&self, feed_id: &str) -> Result<Option<FetchState>> {
+        Ok(queries::fetch_state::get_fetch_state(&self.pool, feed_id).await?)
+    }
+
+    /// Insert or replace the HTTP fetch state for a feed
+    pub async fn set_fetch_state(&self, state: &FetchState) -> Result<()> {
+        Ok(queries::fetch_state::set_fetch_state(&self.pool, state).await?)
+    }
+
+    /// Record a failed fetch, returning the new consecutive failure count
+    pub async fn record_fetch_failure(&self, feed_id: &str, status: Option<i64>) -> Result<i64> {
+        Ok(queries::fetch_state::record_fetch_failure(&self.pool, feed_id, status).await?)
+    }
+
+    /// Add to the bytes downloaded for a feed
+    pub async fn add_feed_bytes(&self, feed_id: &str, bytes: u64) -> Result<()> {
+        Ok(queries::fetch_state::add_feed_bytes(&self.pool, feed_id, bytes).await?)
+    }
+
+    /// Record the newest publication date a fetch of a feed listed
+    pub async fn record_published(&self, feed_id: &str, published: DateTime<Utc>) -> Result<()> {
+        Ok(queries::fetch_state::record_published(&self.pool, feed_id, published).await?)
+    }
+
+    /// Get every feed's failure streak and newest publication date
+    pub async fn get_feed_health(&self) -> Result<Vec<FeedHealth>> {
+        Ok(queries::fetch_state::get_feed_health(&self.pool).await?)
+    }
+
+    /// Feeds whose last fetch was permanently redirected, as `(feed_id, moved_to)`
+    pub async fn feed_moves(&self) -> Result<Vec<(String, String)>> {
+        Ok(queries::fetch_state::feed_moves(&self.pool).await?)
+    }
+
+    /// Feeds whose last fetch needed an alternate user agent after a 403, as
+    /// `(feed_id, user_agent)`
+    pub async fn feed_user_agents(&self) -> Result<Vec<(String, String)>> {
+        Ok(queries::fetch_state::feed_user_agents(&self.pool).await?)
+    }
+
+    /// Point a feed at the URL it moved to, keeping its ID and entries
+    pub async fn move_feed_url(&self, feed_id: &str, url: &str) -> Result<()> {
+        Ok(queries::fetch_state::move_feed_url(&self.pool, feed_id, url).await?)
+    }
+
+    /// Claim a feed for updating; false if another holder has a live lease
+    pub async fn acquire_lease(
+        &self,
+        feed_id: &str,
+        holder: &str,
+        ttl: chrono::Duration,
+    ) -> Result<bool> {
+        Ok(queries::leases::acquire_lease(&self.pool, feed_id, holder, ttl).await?)
+    }
+
+    /// Release a feed claimed with `acquire_lease`
+    pub async fn release_lease(&self, feed_id: &str, holder: &str) -> Result<()> {
+        Ok(queries::leases::release_lease(&self.pool, feed_id, holder).await?)
+    }
+
+    /// Queue entries for summarization; entries already queued keep their place
+    pub async fn enqueue_summaries(&self, entry_ids: &[String]) -> Result<()> {
+        Ok(queries::summary_queue::enqueue_summaries(&self.pool, entry_ids).await?)
+    }
+
+    /// Oldest entries in the summary queue
+    pub async fn queued_summaries(&self, limit: i64) -> Result<Vec<QueuedSummary>> {
+        Ok(queries::summary_queue::queued_summaries(&self.pool, limit).await?)
+    }
+
+    /// Entries of one feed waiting in the summary queue, oldest first
+    pub async fn feed_queued_summaries(&self, feed_id: &str) -> Result<Vec<QueuedSummary>> {
+        Ok(queries::summary_queue::feed_queued_summaries(&self.pool, feed_id).await?)
+    }
+
+    /// Entries waiting in the summary queue
+    pub async fn summary_queue_len(&self) -> Result<i64> {
+        Ok(queries::summary_queue::summary_queue_len(&self.pool).await?)
+    }
+
+    /// Take entries off the summary queue
+    pub async fn dequeue_summaries(&self, entry_ids: &[String]) -> Result<()> {
+        Ok(queries::summary_queue::dequeue_summaries(&self.pool, entry_ids).await?)
+    }
+
+    /// Record a failed summary of a queued entry, returning its attempt count
+    pub async fn record_summary_failure(&self, entry_id: &str, error: &str) -> Result<i64> {
+        Ok(queries::summary_queue::record_summary_failure(&self.pool, entry_id, error).await?)
+    }
+
+    /// Store a new summary as its entry's current one and take the entry
+    /// off the summary queue, adding `usage` (day and tokens) to the AI usage
+    /// unless it came from the cache, all at once
+    pub async fn complete_summary(
+        &self,
+        summary: &Summary,
+        usage: Option<(chrono::NaiveDate, i64)>,
+    ) -> Result<()> {
+        Ok(queries::summary_queue::complete_summary(&self.pool, summary, usage).await?)
+    }
+
+    /// Add one AI request and its tokens to the usage of `day` (UTC)
+    pub async fn record_ai_usage(&self, day: chrono::NaiveDate, tokens: i64) -> Result<()> {
+        Ok(queries::summary_queue::record_ai_usage(&self.pool, day, tokens).await?)
+    }
+
+    /// AI usage of `day` (UTC)
+    pub async fn get_ai_usage(&self, day: chrono::NaiveDate) -> Result<AiUsage> {
+        Ok(queries::summary_queue::get_ai_usage(&self.pool, day).await?)
+    }
+
+    /// Record that a feed's update reached its checkpoint: its entries are
+    /// stored and their summaries queued
+    pub async fn set_run_checkpoint(&self, checkpoint: &RunCheckpoint) -> Result<()> {
+        Ok(queries::runs::set_run_checkpoint(&self.pool, checkpoint).await?)
+    }
+
+    /// Remove and return a feed's checkpoint; one left behind belongs to an
+    /// interrupted update
+    pub async fn take_run_checkpoint(&self, feed_id: &str) -> Result<Option<RunCheckpoint>> {
+        Ok(queries::runs::take_run_checkpoint(&self.pool, feed_id).await?)
+    }
+
+    /// Store the outcome of updating one feed, returning its row ID
+    pub async fn record_run(&self, run: &RunRecord) -> Result<i64> {
+        Ok(queries::runs::record_run(&self.pool, run).await?)
+    }
+
+    /// Recorded feed updates, newest first
+    pub async fn get_runs(
+        &self,
+        feed_id: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<RunRecord>> {
+        Ok(queries::runs::get_runs(&self.pool, feed_id, since, limit).await?)
+    }
+
+    /// Forget feed updates that started before `before`, returning how many
+    pub async fn prune_runs(&self, before: DateTime<Utc>) -> Result<u64> {
+        Ok(queries::runs::prune_runs(&self.pool, before).await?)
     }
 
     /// Insert or update an entry
     pub async fn upsert_entry(&self, entry: &Entry) -> Result<()> {
-        queries::upsert_entry(&self.pool, entry).await
+        Ok(queries::upsert_entry(&self.pool, entry).await?)
+    }
+
+    /// Insert or overwrite an entry, including read, starred, and hidden state,
+    /// unless it changed since `entry.updated_at` was read
+    ///
+    /// Returns the new `updated_at` to use for the next write. Fails with
+    /// `DatabaseError::Conflict` when another writer got there first; tag
+    /// changes count as writes.
+    pub async fn upsert_entry_if_unchanged(&self, entry: &Entry) -> Result<DateTime<Utc>> {
+        Ok(queries::upsert_entry_if_unchanged(&self.pool, entry).await?)
+    }
+
+    /// Insert or update many entries in one transaction
+    pub async fn upsert_entries(&self, entries: &[Entry]) -> Result<()> {
+        Ok(queries::upsert_entries(&self.pool, entries).await?)
     }
 
     /// Get an entry by ID
     pub async fn get_entry(&self, id: &str) -> Result<Option<Entry>> {
-        queries::get_entry(&self.pool, id).await
+        Ok(queries::get_entry(&self.pool, id).await?)
+    }
+
+    /// The entry stored for an article URL, whatever its feed (URLs are unique)
+    pub async fn get_entry_by_url(&self, url: &str) -> Result<Option<Entry>> {
+        Ok(queries::get_entry_by_url(&self.pool, url).await?)
+    }
+
+    /// Which of the given entry IDs are already stored (one query)
+    pub async fn get_known_entry_ids(&self, ids: &[String]) -> Result<HashSet<String>> {
+        Ok(queries::get_known_entry_ids(&self.pool, ids).await?)
+    }
+
+    /// Feeds of the given entry IDs that are stored, by entry ID (one query)
+    pub async fn get_entry_feed_ids(&self, ids: &[String]) -> Result<HashMap<String, String>> {
+        Ok(queries::get_entry_feed_ids(&self.pool, ids).await?)
+    }
+
+    /// Get the stored entries among the given IDs (one query, unordered)
+    pub async fn get_entries_by_ids(&self, ids: &[String]) -> Result<Vec<Entry>> {
+        Ok(queries::get_entries_by_ids(&self.pool, ids).await?)
     }
 
     /// Get entries for a feed
     pub async fn get_entries_for_feed(&self, feed_id: &str, limit: i64) -> Result<Vec<Entry>> {
-        queries::get_entries_for_feed(&self.pool, feed_id, limit).await
+        Ok(queries::get_entries_for_feed(&self.pool, feed_id, limit).await?)
     }
 
     /// Get unread entries
     pub async fn get_unread_entries(&self, limit: i64) -> Result<Vec<Entry>> {
-        queries::get_unread_entries(&self.pool, limit).await
+        Ok(queries::get_unread_entries(&self.pool, limit).await?)
+    }
+
+    /// Get entries matching a filter
+    pub async fn query_entries(&self, filter: &EntryFilter) -> Result<Vec<Entry>> {
+        Ok(queries::filter::query_entries(&self.pool, filter).await?)
+    }
+
+    /// Count entries matching a filter
+    pub async fn count_entries(&self, filter: &EntryFilter) -> Result<i64> {
+        Ok(queries::filter::count_entries(&self.pool, filter).await?)
     }
 
     /// Mark an entry as read
     pub async fn mark_read(&self, entry_id: &str) -> Result<()> {
-        queries::mark_read(&self.pool, entry_id).await
+        Ok(queries::mark_read(&self.pool, entry_id).await?)
+    }
+
+    /// Set the read state of every entry matching a filter; returns how many changed
+    pub async fn set_read(&self, filter: &EntryFilter, read: bool) -> Result<u64> {
+        Ok(queries::filter::set_read(&self.pool, filter, read).await?)
     }
 
     /// Mark an entry as unread
     pub async fn mark_unread(&self, entry_id: &str) -> Result<()> {
-        queries::mark_unread(&self.pool, entry_id).await
+        Ok(queries::mark_unread(&self.pool, entry_id).await?)
+    }
+
+    /// Star an entry
+    pub async fn star_entry(&self, entry_id: &str) -> Result<()> {
+        Ok(queries::star_entry(&self.pool, entry_id).await?)
     }
 
-    /// Insert or update a summary
+    /// Remove the star from an entry
+    pub async fn unstar_entry(&self, entry_id: &str) -> Result<()> {
+        Ok(queries::unstar_entry(&self.pool, entry_id).await?)
+    }
+
+    /// Hide an entry from all views
+    pub async fn hide_entry(&self, entry_id: &str) -> Result<()> {
+        Ok(queries::hide_entry(&self.pool, entry_id).await?)
+    }
+
+    /// Make a hidden entry visible again
+    pub async fn unhide_entry(&self, entry_id: &str) -> Result<()> {
+        Ok(queries::unhide_entry(&self.pool, entry_id).await?)
+    }
+
+    /// Get hidden entries
+    pub async fn get_hidden_entries(&self, limit: i64) -> Result<Vec<Entry>> {
+        Ok(queries::get_hidden_entries(&self.pool, limit).await?)
+    }
+
+    /// Get starred entries, most recently starred first
+    pub async fn get_starred_entries(&self, limit: i64) -> Result<Vec<Entry>> {
+        Ok(queries::get_starred_entries(&self.pool, limit).await?)
+    }
+
+    /// Store a summary as the entry's current version
     pub async fn upsert_summary(&self, summary: &Summary) -> Result<()> {
-        queries::upsert_summary(&self.pool, summary).await
+        Ok(queries::upsert_summary(&self.pool, summary).await?)
+    }
+
+    /// Store many summaries as current versions in one transaction
+    pub async fn upsert_summaries(&self, summaries: &[Summary]) -> Result<()> {
+        Ok(queries::upsert_summaries(&self.pool, summaries).await?)
     }
 
-    /// Get summary for an entry
+    /// Get the current summary for an entry
     pub async fn get_summary(&self, entry_id: &str) -> Result<Option<Summary>> {
-        queries::get_summary(&self.pool, entry_id).await
+        Ok(queries::get_summary(&self.pool, entry_id).await?)
+    }
+
+    /// Get the current summaries of many entries in one query, keyed by entry ID
+    pub async fn get_summaries_for_entries(
+        &self,
+        entry_ids: &[String],
+    ) -> Result<HashMap<String, Summary>> {
+        Ok(queries::get_summaries_for_entries(&self.pool, entry_ids).await?)
+    }
+
+    /// Get every summary version of an entry, newest first
+    pub async fn get_summary_versions(&self, entry_id: &str) -> Result<Vec<Summary>> {
+        Ok(queries::summary_versions::get_summary_versions(&self.pool, entry_id).await?)
+    }
+
+    /// Get one summary version by ID
+    pub async fn get_summary_version(&self, id: i64) -> Result<Option<Summary>> {
+        Ok(queries::summary_versions::get_summary_version(&self.pool, id).await?)
+    }
+
+    /// Make a stored version the entry's current summary
+    pub async fn set_current_summary(&self, id: i64) -> Result<()> {
+        Ok(queries::summary_versions::set_current_summary(&self.pool, id).await?)
+    }
+
+    /// Attach a tag to an entry
+    pub async fn add_tag(&self, entry_id: &str, name: &str, source: TagSource) -> Result<()> {
+        Ok(queries::tags::add_tag(&self.pool, entry_id, name, source).await?)
+    }
+
+    /// Detach a tag from an entry
+    pub async fn remove_tag(&self, entry_id: &str, name: &str) -> Result<()> {
+        Ok(queries::tags::remove_tag(&self.pool, entry_id, name).await?)
+    }
+
+    /// Replace the feed-provided tags of an entry
+    pub async fn set_feed_tags(&self, entry_id: &str, categories: &[String]) -> Result<()> {
+        Ok(queries::tags::set_feed_tags(&self.pool, entry_id, categories).await?)
+    }
+
+    /// Get tag names for an entry
+    pub async fn get_tags_for_entry(&self, entry_id: &str) -> Result<Vec<String>> {
+        Ok(queries::tags::get_tags_for_entry(&self.pool, entry_id).await?)
+    }
+
+    /// Get tag names of many entries in one query, keyed by entry ID
+    pub async fn get_tags_for_entries(
+        &self,
+        entry_ids: &[String],
+    ) -> Result<HashMap<String, Vec<String>>> {
+        Ok(queries::tags::get_tags_for_entries(&self.pool, entry_ids).await?)
+    }
+
+    /// Get entries carrying a tag
+    pub async fn get_entries_by_tag(&self, name: &str, limit: i64) -> Result<Vec<Entry>> {
+        Ok(queries::tags::get_entries_by_tag(&self.pool, name, limit).await?)
+    }
+
+    /// Get all tags in use with entry and unread counts
+    pub async fn get_all_tags_with_counts(&self) -> Result<Vec<TagCount>> {
+        Ok(queries::tags::get_all_tags_with_counts(&self.pool).await?)
+    }
+
+    /// Store an enclosure for an entry, returning its ID
+    pub async fn upsert_enclosure(&self, enclosure: &Enclosure) -> Result<i64> {
+        Ok(queries::enclosures::upsert_enclosure(&self.pool, enclosure).await?)
+    }
+
+    /// Get the enclosures of an entry
+    pub async fn get_enclosures_for_entry(&self, entry_id: &str) -> Result<Vec<Enclosure>> {
+        Ok(queries::enclosures::get_enclosures_for_entry(&self.pool, entry_id).await?)
+    }
+
+    /// Enclosures not downloaded yet, optionally limited to a MIME prefix
+    pub async fn get_pending_downloads(
+        &self,
+        mime_prefix: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Enclosure>> {
+        Ok(queries::enclosures::get_pending_downloads(&self.pool, mime_prefix, limit).await?)
+    }
+
+    /// Record that an enclosure was downloaded
+    pub async fn mark_downloaded(&self, id: i64, local_path: &str) -> Result<()> {
+        Ok(queries::enclosures::mark_downloaded(&self.pool, id, local_path).await?)
+    }
+
+    /// Mark a downloaded enclosure as pending again
+    pub async fn clear_download(&self, id: i64) -> Result<()> {
+        Ok(queries::enclosures::clear_download(&self.pool, id).await?)
+    }
+
+    /// Get an enclosure by ID
+    pub async fn get_enclosure(&self, id: i64) -> Result<Option<Enclosure>> {
+        Ok(queries::enclosures::get_enclosure(&self.pool, id).await?)
+    }
+
+    /// Record that a download of an enclosure started
+    pub async fn start_download(&self, id: i64) -> Result<()> {
+        Ok(queries::enclosures::start_download(&self.pool, id).await?)
+    }
+
+    /// Record how many bytes of an enclosure's download were written
+    pub async fn set_download_progress(&self, id: i64, bytes: i64) -> Result<()> {
+        Ok(queries::enclosures::set_download_progress(&self.pool, id, bytes).await?)
+    }
+
+    /// Record why an enclosure's download failed
+    pub async fn fail_download(&self, id: i64, error: &str) -> Result<()> {
+        Ok(queries::enclosures::fail_download(&self.pool, id, error).await?)
+    }
+
+    /// Enclosures whose download was started, most recent first
+    pub async fn get_downloads(&self, limit: i64) -> Result<Vec<EnclosureDownload>> {
+        Ok(queries::enclosures::get_downloads(&self.pool, limit).await?)
+    }
+
+    /// Record where playback of an enclosure stopped
+    pub async fn set_play_position(&self, id: i64, position_secs: i64) -> Result<()> {
+        Ok(queries::enclosures::set_play_position(&self.pool, id, position_secs).await?)
+    }
+
+    /// Record (or replace) an entry's fingerprint
+    pub async fn set_fingerprint(&self, fingerprint: &EntryFingerprint) -> Result<()> {
+        Ok(queries::fingerprints::set_fingerprint(&self.pool, fingerprint).await?)
+    }
+
+    /// Get an entry's fingerprint
+    pub async fn get_fingerprint(&self, entry_id: &str) -> Result<Option<EntryFingerprint>> {
+        Ok(queries::fingerprints::get_fingerprint(&self.pool, entry_id).await?)
+    }
+
+    /// The first stored entry of another feed with the same canonical
+    /// address or text as the fingerprinted entry
+    pub async fn find_original(&self, fingerprint: &EntryFingerprint) -> Result<Option<String>> {
+        Ok(queries::fingerprints::find_original(&self.pool, fingerprint).await?)
+    }
+
+    /// Group an entry with its near-duplicates, returning the cluster ID
+    pub async fn merge_duplicates(
+        &self,
+        representative_id: &str,
+        duplicate_ids: &[String],
+    ) -> Result<i64> {
+        Ok(
+            queries::story_clusters::merge_duplicates(&self.pool, representative_id, duplicate_ids)
+                .await?,
+        )
+    }
+
+    /// Take an entry out of its cluster (false if it was not clustered)
+    pub async fn remove_from_cluster(&self, entry_id: &str) -> Result<bool> {
+        Ok(queries::story_clusters::remove_from_cluster(&self.pool, entry_id).await?)
+    }
+
+    /// Get the cluster an entry belongs to, if any
+    pub async fn get_cluster_for_entry(&self, entry_id: &str) -> Result<Option<StoryCluster>> {
+        Ok(queries::story_clusters::get_cluster_for_entry(&self.pool, entry_id).await?)
+    }
+
+    /// Get the entry representing a cluster
+    pub async fn get_cluster_representative(&self, cluster_id: i64) -> Result<Entry> {
+        Ok(queries::story_clusters::get_cluster_representative(&self.pool, cluster_id).await?)
+    }
+
+    /// Titles of the other feeds whose entries share a cluster with each of
+    /// the given entries, by entry ID (one query)
+    pub async fn get_other_sources(
+        &self,
+        entry_ids: &[String],
+    ) -> Result<HashMap<String, Vec<String>>> {
+        Ok(queries::story_clusters::get_other_sources(&self.pool, entry_ids).await?)
+    }
+
+    /// Get the entries of a cluster, representative first
+    pub async fn get_cluster_members(&self, cluster_id: i64) -> Result<Vec<Entry>> {
+        Ok(queries::story_clusters::get_cluster_members(&self.pool, cluster_id).await?)
+    }
+
+    /// Add a highlight and/or note to an entry, returning its ID
+    pub async fn add_note(
+        &self,
+        entry_id: &str,
+        selection_text: Option<&str>,
+        note_text: Option<&str>,
+    ) -> Result<i64> {
+        Ok(queries::notes::add_note(&self.pool, entry_id, selection_text, note_text).await?)
+    }
+
+    /// Replace the text of a note
+    pub async fn update_note(&self, id: i64, note_text: Option<&str>) -> Result<()> {
+        Ok(queries::notes::update_note(&self.pool, id, note_text).await?)
+    }
+
+    /// Delete a note
+    pub async fn delete_note(&self, id: i64) -> Result<()> {
+        Ok(queries::notes::delete_note(&self.pool, id).await?)
+    }
+
+    /// Get a note by ID
+    pub async fn get_note(&self, id: i64) -> Result<Option<Note>> {
+        Ok(queries::notes::get_note(&self.pool, id).await?)
+    }
+
+    /// Get the notes on an entry
+    pub async fn get_notes_for_entry(&self, entry_id: &str) -> Result<Vec<Note>> {
+        Ok(queries::notes::get_notes_for_entry(&self.pool, entry_id).await?)
+    }
+
+    /// Get every note, grouped by entry
+    pub async fn get_all_notes(&self) -> Result<Vec<Note>> {
+        Ok(queries::notes::get_all_notes(&self.pool).await?)
+    }
+
+    /// Append an entry to the read-later queue (false if already queued)
+    pub async fn enqueue_read_later(&self, entry_id: &str) -> Result<bool> {
+        Ok(queries::read_later::enqueue(&self.pool, entry_id).await?)
+    }
+
+    /// Remove an entry from the read-later queue (false if not queued)
+    pub async fn dequeue_read_later(&self, entry_id: &str) -> Result<bool> {
+        Ok(queries::read_later::dequeue(&self.pool, entry_id).await?)
+    }
+
+    /// Move a queued entry to a new position (0 is the front)
+    pub async fn reorder_read_later(&self, entry_id: &str, position: i64) -> Result<()> {
+        Ok(queries::read_later::reorder(&self.pool, entry_id, position).await?)
+    }
+
+    /// Get the read-later queue in order
+    pub async fn get_read_later(&self, limit: i64) -> Result<Vec<Entry>> {
+        Ok(queries::read_later::get_read_later(&self.pool, limit).await?)
+    }
+
+    /// File a feed under a folder (None unfiles it)
+    pub async fn set_feed_folder(&self, feed_id: &str, folder: Option<&str>) -> Result<()> {
+        Ok(queries::folders::set_feed_folder(&self.pool, feed_id, folder).await?)
+    }
+
+    /// Apply folder assignments keyed by feed URL and drop empty folders
+    pub async fn sync_folders(&self, assignments: &HashMap<String, Option<String>>) -> Result<()> {
+        Ok(queries::folders::sync_folders(&self.pool, assignments).await?)
+    }
+
+    /// Per-folder feed, unread, and total counts
+    pub async fn get_folder_counts(&self) -> Result<Vec<FolderCount>> {
+        Ok(queries::folders::get_folder_counts(&self.pool).await?)
+    }
+
+    /// Get entries from every feed in a folder
+    pub async fn get_entries_for_folder(&self, folder: &str, limit: i64) -> Result<Vec<Entry>> {
+        Ok(queries::folders::get_entries_for_folder(&self.pool, folder, limit).await?)
     }
 
     /// Search entries by text
     pub async fn search_entries(&self, query: &str, limit: i64) -> Result<Vec<Entry>> {
-        queries::search_entries(&self.pool, query, limit).await
+        Ok(queries::search_entries(&self.pool, query, limit).await?)
+    }
+
+    /// Search entries and summaries with snippets, narrowed by an entry filter
+    pub async fn search(&self, options: &SearchOptions) -> Result<Vec<SearchHit>> {
+        Ok(queries::search::search(&self.pool, options).await?)
+    }
+
+    /// Save a search as a smart folder, replacing one with the same name
+    pub async fn save_smart_folder(&self, name: &str, query: &str) -> Result<()> {
+        Ok(queries::smart_folders::save_smart_folder(&self.pool, name, query).await?)
+    }
+
+    /// Delete a smart folder, returning whether it existed
+    pub async fn delete_smart_folder(&self, name: &str) -> Result<bool> {
+        Ok(queries::smart_folders::delete_smart_folder(&self.pool, name).await?)
+    }
+
+    /// Get every smart folder by name
+    pub async fn get_smart_folders(&self) -> Result<Vec<SmartFolder>> {
+        Ok(queries::smart_folders::get_smart_folders(&self.pool).await?)
+    }
+
+    /// Set a TUI state value, or remove it with `None`
+    pub async fn set_ui_state(&self, key: &str, value: Option<&str>) -> Result<()> {
+        Ok(queries::ui_state::set_ui_state(&self.pool, key, value).await?)
+    }
+
+    /// Get a TUI state value
+    pub async fn get_ui_state(&self, key: &str) -> Result<Option<String>> {
+        Ok(queries::ui_state::get_ui_state(&self.pool, key).await?)
+    }
+
+    /// Remember how far an entry is scrolled in the reader, out of `max_scroll`
+    pub async fn save_reading_position(
+        &self,
+        entry_id: &str,
+        scroll: i64,
+        max_scroll: i64,
+    ) -> Result<()> {
+        Ok(
+            queries::ui_state::save_reading_position(&self.pool, entry_id, scroll, max_scroll)
+                .await?,
+        )
+    }
+
+    /// Get how far an entry was scrolled in the reader
+    pub async fn get_reading_position(&self, entry_id: &str) -> Result<Option<ReadingPosition>> {
+        Ok(queries::ui_state::get_reading_position(&self.pool, entry_id).await?)
+    }
+
+    /// The entry most recently left partly read
+    pub async fn get_last_partial_position(&self) -> Result<Option<ReadingPosition>> {
+        Ok(queries::ui_state::get_last_partial_position(&self.pool).await?)
     }
 
     /// Get database statistics
     pub async fn get_stats(&self) -> Result<DatabaseStats> {
-        queries::get_stats(&self.pool).await
+        Ok(queries::get_stats(&self.pool).await?)
+    }
+
+    /// Per-feed activity breakdown
+    pub async fn get_feed_stats(&self) -> Result<Vec<FeedStats>> {
+        Ok(queries::stats::get_feed_stats(&self.pool).await?)
+    }
+
+    /// Unread entries per feed ID in one query (feeds without unread map to 0)
+    pub async fn get_unread_counts(&self) -> Result<HashMap<String, i64>> {
+        Ok(queries::stats::get_unread_counts(&self.pool).await?)
+    }
+
+    /// New entries and summaries per day over the last `days` days
+    pub async fn get_daily_counts(&self, days: u32) -> Result<Vec<DailyCount>> {
+        Ok(queries::stats::get_daily_counts(&self.pool, days).await?)
+    }
+
+    /// Store (or replace) an entry's embedding for a model
+    pub async fn store_embedding(&self, entry_id: &str, model: &str, vector: &[f32]) -> Result<()> {
+        Ok(queries::embeddings::store_embedding(&self.pool, entry_id, model, vector).await?)
+    }
+
+    /// Get an entry's embedding for a model
+    pub async fn get_embedding(&self, entry_id: &str, model: &str) -> Result<Option<Vec<f32>>> {
+        Ok(queries::embeddings::get_embedding(&self.pool, entry_id, model).await?)
+    }
+
+    /// Entries that still need an embedding for a model
+    pub async fn get_entries_without_embedding(
+        &self,
+        model: &str,
+        limit: i64,
+    ) -> Result<Vec<Entry>> {
+        Ok(queries::embeddings::get_entries_without_embedding(&self.pool, model, limit).await?)
+    }
+
+    /// Delete all embeddings produced by a model
+    pub async fn delete_embeddings_for_model(&self, model: &str) -> Result<u64> {
+        Ok(queries::embeddings::delete_embeddings_for_model(&self.pool, model).await?)
+    }
+
+    /// Find the `k` entries most similar to a query vector
+    pub async fn nearest_entries(
+        &self,
+        model: &str,
+        query: &[f32],
+        k: usize,
+    ) -> Result<Vec<SimilarEntry>> {
+        Ok(queries::embeddings::nearest_entries(&self.pool, model, query, k).await?)
+    }
+
+    /// Find the `k` entries most similar to an entry (None if it has no embedding)
+    pub async fn similar_entries(
+        &self,
+        entry_id: &str,
+        k: usize,
+    ) -> Result<Option<Vec<SimilarEntry>>> {
+        Ok(queries::embeddings::similar_entries(&self.pool, entry_id, k).await?)
+    }
+
+    /// Add a mute rule (`expires_at` None: until removed), returning its ID
+    pub async fn add_mute_rule(
+        &self,
+        kind: MuteKind,
+        pattern: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<i64> {
+        Ok(queries::mutes::add_mute_rule(&self.pool, kind, pattern, expires_at).await?)
+    }
+
+    /// Mute rules in force at `now`, oldest first
+    pub async fn get_active_mute_rules(&self, now: DateTime<Utc>) -> Result<Vec<MuteRule>> {
+        Ok(queries::mutes::get_active_mute_rules(&self.pool, now).await?)
+    }
+
+    /// Delete a mute rule, returning whether it existed
+    pub async fn delete_mute_rule(&self, id: i64) -> Result<bool> {
+        Ok(queries::mutes::delete_mute_rule(&self.pool, id).await?)
+    }
+
+    /// Delete the mute rules that expired by `now`, returning how many
+    pub async fn delete_expired_mute_rules(&self, now: DateTime<Utc>) -> Result<u64> {
+        Ok(queries::mutes::delete_expired_mute_rules(&self.pool, now).await?)
+    }
+
+    /// Replace every stored entry rank with `ranks`
+    pub async fn replace_entry_ranks(&self, ranks: &[EntryRank]) -> Result<()> {
+        Ok(queries::ranks::replace_entry_ranks(&self.pool, ranks).await?)
+    }
+
+    /// Get an entry's rank (None if it was left out of the last ranking)
+    pub async fn get_entry_rank(&self, entry_id: &str) -> Result<Option<EntryRank>> {
+        Ok(queries::ranks::get_entry_rank(&self.pool, entry_id).await?)
+    }
+
+    /// Record (or replace) the archived page of an entry
+    pub async fn set_archived_page(&self, page: &ArchivedPage) -> Result<()> {
+        Ok(queries::archived_pages::set_archived_page(&self.pool, page).await?)
+    }
+
+    /// Get the archived page of an entry, even one pruned since
+    pub async fn get_archived_page(&self, entry_id: &str) -> Result<Option<ArchivedPage>> {
+        Ok(queries::archived_pages::get_archived_page(&self.pool, entry_id).await?)
+    }
+
+    /// Record an entry's discussion, keeping earlier values the feed no
+    /// longer gives, and log its comment count when that changed
+    pub async fn set_discussion(&self, discussion: &EntryDiscussion) -> Result<()> {
+        Ok(queries::discussions::set_discussion(&self.pool, discussion).await?)
+    }
+
+    /// Get the discussions of entries, by entry ID
+    pub async fn get_discussions_for_entries(
+        &self,
+        entry_ids: &[String],
+    ) -> Result<HashMap<String, EntryDiscussion>> {
+        Ok(queries::discussions::get_discussions_for_entries(&self.pool, entry_ids).await?)
+    }
+
+    /// Get an entry's recorded comment counts, oldest first
+    pub async fn get_comment_history(&self, entry_id: &str) -> Result<Vec<CommentCount>> {
+        Ok(queries::discussions::get_comment_history(&self.pool, entry_id).await?)
+    }
+
+    /// Get the discussions with a comment feed of a feed's entries first
+    /// seen since `since`
+    pub async fn get_comment_feeds(
+        &self,
+        feed_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<EntryDiscussion>> {
+        Ok(queries::discussions::get_comment_feeds(&self.pool, feed_id, since).await?)
+    }
+
+    /// Store the text an update replaced, returning the revision's row ID
+    pub async fn add_revision(&self, revision: &EntryRevision) -> Result<i64> {
+        Ok(queries::revisions::add_revision(&self.pool, revision).await?)
+    }
+
+    /// Get an entry's revisions, newest first
+    pub async fn get_revisions(&self, entry_id: &str) -> Result<Vec<EntryRevision>> {
+        Ok(queries::revisions::get_revisions(&self.pool, entry_id).await?)
+    }
+
+    /// Get the newest revision of each of the given entries that has one
+    pub async fn get_latest_revisions(
+        &self,
+        entry_ids: &[String],
+    ) -> Result<HashMap<String, EntryRevision>> {
+        Ok(queries::revisions::get_latest_revisions(&self.pool, entry_ids).await?)
+    }
+
+    /// Store an entry's translation, replacing an earlier one
+    pub async fn set_translation(&self, translation: &Translation) -> Result<()> {
+        Ok(queries::translations::set_translation(&self.pool, translation).await?)
+    }
+
+    /// Get an entry's translation
+    pub async fn get_translation(&self, entry_id: &str) -> Result<Option<Translation>> {
+        Ok(queries::translations::get_translation(&self.pool, entry_id).await?)
+    }
+
+    /// Store a feed's token, replacing the one it had
+    pub async fn set_feed_token(&self, token: &FeedToken) -> Result<()> {
+        Ok(queries::tokens::set_feed_token(&self.pool, token).await?)
+    }
+
+    /// Get a feed's token
+    pub async fn get_feed_token(&self, feed_id: &str) -> Result<Option<FeedToken>> {
+        Ok(queries::tokens::get_feed_token(&self.pool, feed_id).await?)
+    }
+
+    /// Forget a feed's token
+    pub async fn delete_feed_token(&self, feed_id: &str) -> Result<()> {
+        Ok(queries::tokens::delete_feed_token(&self.pool, feed_id).await?)
+    }
+
+    /// Create an API user identified by the hash of their token
+    pub async fn create_user(&self, name: &str, token_hash: &str) -> Result<User> {
+        Ok(queries::users::create_user(&self.pool, name, token_hash).await?)
+    }
+
+    /// Get all API users, by name
+    pub async fn list_users(&self) -> Result<Vec<User>> {
+        Ok(queries::users::list_users(&self.pool).await?)
+    }
+
+    /// Delete an API user and all of their state
+    pub async fn delete_user(&self, name: &str) -> Result<()> {
+        Ok(queries::users::delete_user(&self.pool, name).await?)
+    }
+
+    /// Get the API user with a token hash
+    pub async fn get_user_by_token(&self, token_hash: &str) -> Result<Option<User>> {
+        Ok(queries::users::get_user_by_token(&self.pool, token_hash).await?)
+    }
+
+    /// Get visible entries with a user's read and starred state, newest first
+    pub async fn get_user_entries(
+        &self,
+        user_id: i64,
+        filter: &UserEntryFilter,
+    ) -> Result<Vec<Entry>> {
+        Ok(queries::users::get_user_entries(&self.pool, user_id, filter).await?)
+    }
+
+    /// Get one entry with a user's read and starred state
+    pub async fn get_user_entry(&self, user_id: i64, entry_id: &str) -> Result<Entry> {
+        Ok(queries::users::get_user_entry(&self.pool, user_id, entry_id).await?)
+    }
+
+    /// Set a user's read and starred state of an entry; None keeps the
+    /// current value
+    pub async fn set_user_state(
+        &self,
+        user_id: i64,
+        entry_id: &str,
+        read: Option<bool>,
+        starred: Option<bool>,
+    ) -> Result<()> {
+        Ok(queries::users::set_user_state(&self.pool, user_id, entry_id, read, starred).await?)
+    }
+
+    /// Attach a tag to an entry for a user
+    pub async fn add_user_tag(&self, user_id: i64, entry_id: &str, name: &str) -> Result<()> {
+        Ok(queries::users::add_user_tag(&self.pool, user_id, entry_id, name).await?)
+    }
+
+    /// Detach a user's tag from an entry
+    pub async fn remove_user_tag(&self, user_id: i64, entry_id: &str, name: &str) -> Result<()> {
+        Ok(queries::users::remove_user_tag(&self.pool, user_id, entry_id, name).await?)
+    }
+
+    /// Get the tags a user sees on entries (shared feed and AI tags and
+    /// their own), keyed by entry ID
+    pub async fn get_user_tags_for_entries(
+        &self,
+        user_id: i64,
+        entry_ids: &[String],
+    ) -> Result<HashMap<String, Vec<String>>> {
+        Ok(queries::users::get_user_tags_for_entries(&self.pool, user_id, entry_ids).await?)
+    }
+
+    /// Delete or archive entries according to a retention policy
+    ///
+    /// Starred entries are never pruned. Runs in a single transaction.
+    pub async fn prune(&self, policy: &RetentionPolicy) -> Result<PruneReport> {
+        Ok(queries::retention::prune(&self.pool, policy).await?)
+    }
+
+    /// Entries `prune` would delete under `policy`, oldest first per feed
+    pub async fn prune_candidates(&self, policy: &RetentionPolicy) -> Result<Vec<Entry>> {
+        Ok(queries::retention::prune_candidates(&self.pool, policy).await?)
+    }
+
+    /// Get archived entries, most recently archived first
+    pub async fn get_archived_entries(&self, limit: i64) -> Result<Vec<ArchivedEntry>> {
+        Ok(queries::retention::get_archived_entries(&self.pool, limit).await?)
+    }
+
+    /// Reclaim free space (VACUUM); follow with `maintenance` to shrink the WAL again
+    pub async fn vacuum(&self) -> Result<()> {
+        Ok(queries::retention::vacuum(&self.pool).await?)
+    }
+
+    /// Recreate missing hot indices, run ANALYZE, and truncate the WAL
+    ///
+    /// Meant to run nightly; without checkpoints the WAL of a long-running
+    /// daemon keeps growing.
+    pub async fn maintenance(&self) -> Result<MaintenanceReport> {
+        Ok(queries::maintenance::maintenance(&self.pool).await?)
+    }
+
+    /// Write a verified, consistent snapshot of the database to `dest`
+    ///
+    /// Safe to call while other processes are writing.
+    pub async fn backup<P: AsRef<Path>>(&self, dest: P) -> Result<()> {
+        Ok(backup::backup(&self.pool, dest.as_ref(), self.key.as_deref()).await?)
+    }
+
+    /// Run SQLite's integrity check, returning any problems found
+    pub async fn integrity_check(&self) -> Result<Vec<String>> {
+        Ok(backup::integrity_check(&self.pool).await?)
+    }
+
+    /// Replace the database file at `target` with a verified backup
+    ///
+    /// The database must not be open anywhere. Returns where the previous
+    /// database was saved, if one existed. `key` is the encryption key of
+    /// the backup, if it is encrypted.
+    pub async fn restore<P: AsRef<Path>, Q: AsRef<Path>>(
+        source: P,
+        target: Q,
+        key: Option<&str>,
+    ) -> Result<Option<PathBuf>> {
+        Ok(backup::restore(source.as_ref(), target.as_ref(), key).await?)
+    }
+
+    /// Export entries matching a filter, returning how many were written
+    pub async fn export_entries<W: std::io::Write>(
+        &self,
+        filter: &EntryFilter,
+        format: ExportFormat,
+        writer: W,
+    ) -> Result<usize> {
+        Ok(export::export_entries(&self.pool, filter, format, writer).await?)
+    }
+
+    /// Import exported entries, skipping ones that already exist
+    pub async fn import_entries<R: std::io::Read>(
+        &self,
+        format: ExportFormat,
+        reader: R,
+    ) -> Result<ImportReport> {
+        Ok(export::import_entries(&self.pool, format, reader).await?)
     }
 
     /// Get a reference to the connection pool
@@ -188,7 +1119,7 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
-    async fn setup_db() -> (Database, TempDir) {
+    pub(crate) async fn setup_db() -> (Database, TempDir) {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
         let db = Database::open(&db_path).await.unwrap();
@@ -204,6 +1135,76 @@ mod tests {
         assert!(db.is_ok());
     }
 
+    #[cfg(feature = "encryption")]
+    #[tokio::test]
+    async fn test_encrypted_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("secret.db");
+        let db = Database::open_with_key(&db_path, Some("it's a secret"))
+            .await
+            .unwrap();
+        db.migrate().await.unwrap();
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "Private newsletter".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.backup(temp_dir.path().join("copy.db")).await.unwrap();
+        db.close().await;
+
+        let raw = std::fs::read(&db_path).unwrap();
+        assert!(!raw.starts_with(b"SQLite format 3"));
+        assert!(Database::open_with_key(&db_path, Some("wrong"))
+            .await
+            .is_err());
+        assert!(Database::open(&db_path).await.is_err());
+        let db = Database::open_with_key(&db_path, Some("it's a secret"))
+            .await
+            .unwrap();
+        assert!(db.get_feed("f1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_typed_errors() {
+        let (db, _dir) = setup_db().await;
+        assert!(db.mark_read("missing").await.unwrap_err().is_not_found());
+        assert!(db.delete_feed("missing").await.unwrap_err().is_not_found());
+
+        let feed = Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            ..Default::default()
+        };
+        db.upsert_feed(&feed).await.unwrap();
+        let entry = Entry {
+            id: "e1".into(),
+            feed_id: "f1".into(),
+            url: "https://ex.com/1".into(),
+            ..Default::default()
+        };
+        db.upsert_entry(&entry).await.unwrap();
+
+        // Same URL under another id violates entries.url UNIQUE
+        let clash = Entry {
+            id: "e2".into(),
+            ..entry.clone()
+        };
+        let err = db.upsert_entry(&clash).await.unwrap_err();
+        assert!(err.is_duplicate(), "{err}");
+
+        // An entry for a feed that doesn't exist
+        let orphan = Entry {
+            id: "e3".into(),
+            feed_id: "nope".into(),
+            url: "https://ex.com/3".into(),
+            ..Default::default()
+        };
+        assert!(db.upsert_entry(&orphan).await.unwrap_err().is_not_found());
+    }
+
     #[tokio::test]
     async fn test_feed_crud() {
         let (db, _dir) = setup_db().await;
@@ -268,6 +1269,17 @@ mod tests {
         let fetched = db.get_entry("entry1").await.unwrap().unwrap();
         assert_eq!(fetched.title, "Article Title");
         assert!(!fetched.read);
+        let by_url = db
+            .get_entry_by_url("https://ex.com/article")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_url.id, "entry1");
+        assert!(db
+            .get_entry_by_url("https://ex.com/other")
+            .await
+            .unwrap()
+            .is_none());
 
         // Mark read
         db.mark_read("entry1").await.unwrap();
@@ -288,6 +1300,96 @@ mod tests {
         assert_eq!(entries.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_upsert_entry_if_unchanged() {
+        let db = Database::open_in_memory().await.unwrap();
+        db.migrate().await.unwrap();
+        fixtures::seed(&db).await.unwrap();
+
+        // Two readers load the same version
+        let mut tui = db.get_entry("rust-async").await.unwrap().unwrap();
+        let mut daemon = tui.clone();
+
+        tui.read = true;
+        tui.updated_at = db.upsert_entry_if_unchanged(&tui).await.unwrap();
+        daemon.title = "Async closures stabilized (updated)".into();
+        let err = db.upsert_entry_if_unchanged(&daemon).await.unwrap_err();
+        assert!(err.is_conflict(), "{err}");
+
+        // Back-to-back swaps still get distinct versions
+        tui.starred = true;
+        let version = db.upsert_entry_if_unchanged(&tui).await.unwrap();
+        assert!(version > tui.updated_at);
+        tui.updated_at = version;
+
+        // Tag changes and plain updates invalidate the version too
+        db.add_tag("rust-async", "lang", TagSource::User)
+            .await
+            .unwrap();
+        assert!(db
+            .upsert_entry_if_unchanged(&tui)
+            .await
+            .unwrap_err()
+            .is_conflict());
+        let mut fresh = db.get_entry("rust-async").await.unwrap().unwrap();
+        assert!(fresh.read && fresh.starred);
+        db.mark_unread("rust-async").await.unwrap();
+        fresh.hidden = true;
+        assert!(db
+            .upsert_entry_if_unchanged(&fresh)
+            .await
+            .unwrap_err()
+            .is_conflict());
+
+        // New entries are simply inserted
+        let new = Entry {
+            id: "fresh".into(),
+            feed_id: "news".into(),
+            url: "https://example.com/fresh".into(),
+            read: true,
+            ..Default::default()
+        };
+        db.upsert_entry_if_unchanged(&new).await.unwrap();
+        assert!(db.get_entry("fresh").await.unwrap().unwrap().read);
+    }
+
+    #[tokio::test]
+    async fn test_starred_entries() {
+        let (db, _dir) = setup_db().await;
+
+        let feed = Feed {
+            id: "feed1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        };
+        db.upsert_feed(&feed).await.unwrap();
+
+        let entry = Entry {
+            id: "entry1".into(),
+            feed_id: "feed1".into(),
+            title: "Keep this".into(),
+            url: "https://ex.com/keep".into(),
+            ..Default::default()
+        };
+        db.upsert_entry(&entry).await.unwrap();
+        assert!(db.get_starred_entries(10).await.unwrap().is_empty());
+
+        db.star_entry("entry1").await.unwrap();
+        let starred = db.get_starred_entries(10).await.unwrap();
+        assert_eq!(starred.len(), 1);
+        assert!(starred[0].starred_at.is_some());
+
+        // Re-fetching the feed must not clear the star
+        db.upsert_entry(&entry).await.unwrap();
+        assert!(db.get_entry("entry1").await.unwrap().unwrap().starred);
+
+        db.unstar_entry("entry1").await.unwrap();
+        let fetched = db.get_entry("entry1").await.unwrap().unwrap();
+        assert!(!fetched.starred);
+        assert!(fetched.starred_at.is_none());
+    }
+
     #[tokio::test]
     async fn test_summary_operations() {
         let (db, _dir) = setup_db().await;
@@ -327,6 +1429,61 @@ mod tests {
         assert_eq!(fetched.model, "gpt-4");
     }
 
+    #[tokio::test]
+    async fn test_batch_upserts() {
+        let (db, _dir) = setup_db().await;
+
+        let feed = Feed {
+            id: "feed1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        };
+        db.upsert_feed(&feed).await.unwrap();
+
+        let mut entries: Vec<Entry> = (0..20)
+            .map(|i| Entry {
+                id: format!("entry{}", i),
+                feed_id: "feed1".into(),
+                title: format!("Article {}", i),
+                url: format!("https://ex.com/{}", i),
+                ..Default::default()
+            })
+            .collect();
+        db.upsert_entries(&entries).await.unwrap();
+        assert_eq!(
+            db.get_entries_for_feed("feed1", 100).await.unwrap().len(),
+            20
+        );
+
+        let summaries: Vec<Summary> = entries
+            .iter()
+            .map(|e| Summary {
+                entry_id: e.id.clone(),
+                summary_text: "summary".into(),
+                model: "m".into(),
+                content_hash: "h".into(),
+                ..Default::default()
+            })
+            .collect();
+        db.upsert_summaries(&summaries).await.unwrap();
+        assert_eq!(db.get_stats().await.unwrap().total_summaries, 20);
+
+        // A failing row rolls back the whole batch
+        entries.push(Entry {
+            id: "orphan".into(),
+            feed_id: "missing".into(),
+            url: "https://ex.com/orphan".into(),
+            ..Default::default()
+        });
+        entries[0].title = "Changed".into();
+        assert!(db.upsert_entries(&entries).await.is_err());
+        assert_eq!(
+            db.get_entry("entry0").await.unwrap().unwrap().title,
+            "Article 0"
+        );
+    }
+
     #[tokio::test]
     async fn test_stats() {
         let (db, _dir) = setup_db().await;
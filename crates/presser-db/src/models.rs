@@ -37,6 +37,9 @@ pub struct Feed {
     /// Whether the feed is enabled
     pub enabled: bool,
 
+    /// Folder the feed is filed under
+    pub folder_id: Option<i64>,
+
     /// Created timestamp
     pub created_at: DateTime<Utc>,
 
@@ -58,6 +61,7 @@ impl Default for Feed {
             last_error: None,
             entry_count: 0,
             enabled: true,
+            folder_id: None,
             created_at: now,
             updated_at: now,
         }
@@ -103,6 +107,18 @@ pub struct Entry {
     /// Whether this entry has been read
     pub read: bool,
 
+    /// Whether this entry is starred/bookmarked
+    pub starred: bool,
+
+    /// When the entry was starred
+    pub starred_at: Option<DateTime<Utc>>,
+
+    /// Relevance score (higher is more interesting)
+    pub score: Option<f64>,
+
+    /// Whether the entry is hidden from all views
+    pub hidden: bool,
+
     /// Created timestamp
     pub created_at: DateTime<Utc>,
 
@@ -126,15 +142,42 @@ impl Default for Entry {
             content_text: None,
             categories: None,
             read: false,
+            starred: false,
+            starred_at: None,
+            score: None,
+            hidden: false,
             created_at: now,
             updated_at: now,
         }
     }
 }
 
-/// Summary model
+impl Entry {
+    /// The feed's categories for the entry, parsed from their JSON array
+    /// (none if it doesn't parse)
+    pub fn category_list(&self) -> Vec<String> {
+        self.categories
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Categories as `Entry::categories` stores them: a JSON array, or none
+/// if there are none
+pub fn categories_json(categories: &[String]) -> Option<String> {
+    if categories.is_empty() {
+        return None;
+    }
+    serde_json::to_string(categories).ok()
+}
+
+/// Summary model (one version of an entry's summary)
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Summary {
+    /// Version identifier (0 until stored)
+    pub id: i64,
+
     /// Entry ID this summary belongs to
     pub entry_id: String,
 
@@ -144,12 +187,18 @@ pub struct Summary {
     /// AI model used
     pub model: String,
 
+    /// Prompt style the summary was generated with (empty for the default)
+    pub style: String,
+
     /// Token count
     pub tokens: Option<i64>,
 
     /// Content hash (for caching)
     pub content_hash: String,
 
+    /// Whether this is the version shown for the entry
+    pub is_current: bool,
+
     /// Created timestamp
     pub created_at: DateTime<Utc>,
 }
@@ -157,12 +206,643 @@ pub struct Summary {
 impl Default for Summary {
     fn default() -> Self {
         Self {
+            id: 0,
             entry_id: String::new(),
             summary_text: String::new(),
             model: String::new(),
+            style: String::new(),
             tokens: None,
             content_hash: String::new(),
+            is_current: true,
             created_at: Utc::now(),
         }
     }
 }
+
+/// Where a tag assignment came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum TagSource {
+    /// Category provided by the feed itself
+    Feed,
+    /// Assigned manually by the user
+    User,
+    /// Assigned by AI classification
+    Ai,
+}
+
+/// An entry's page kept in the archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct ArchivedPage {
+    /// Entry the page belongs to (which may since have been pruned)
+    pub entry_id: String,
+
+    /// URL the page was fetched from
+    pub url: String,
+
+    /// SHA-256 of the page's HTML, naming its archive file
+    pub hash: String,
+
+    /// Size of the HTML before compression, in bytes
+    pub size: i64,
+
+    /// When the page was fetched
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Where an entry is discussed, as its feed last said
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct EntryDiscussion {
+    pub entry_id: String,
+
+    /// Page with the comments (Hacker News item, Reddit thread, blog comments)
+    pub url: Option<String>,
+
+    /// Feed of the comments, counted when the feed has `track_comments`
+    pub comment_feed: Option<String>,
+
+    /// Latest comment count
+    pub comments: Option<i64>,
+
+    /// When the discussion was last seen in the feed or counted
+    pub checked_at: DateTime<Utc>,
+}
+
+/// What identifies an entry across feeds, for finding the posts mirror
+/// feeds repeat
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct EntryFingerprint {
+    pub entry_id: String,
+
+    /// Address without scheme, `www.`, fragment, or tracking parameters
+    pub canonical_url: String,
+
+    /// Hash of the normalized text, if it is long enough to compare
+    pub text_hash: Option<String>,
+}
+
+/// A token obtained for a feed behind an API (`[feed.auth]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct FeedToken {
+    pub feed_id: String,
+
+    /// Token sent with the feed's requests
+    pub access_token: String,
+
+    /// Refresh token to get the next one with, when the token endpoint
+    /// gave a new one
+    pub refresh_token: Option<String>,
+
+    /// When the access token runs out, if the token endpoint said
+    pub expires_at: Option<DateTime<Utc>>,
+
+    pub refreshed_at: DateTime<Utc>,
+}
+
+/// An entry's text translated into a feed's `translate_to` language
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Translation {
+    pub entry_id: String,
+
+    /// Language translated into (ISO 639-1 code)
+    pub language: String,
+
+    /// Language detected in the original (ISO 639-1 code)
+    pub source_language: String,
+
+    /// Translated text
+    pub content_text: String,
+
+    /// Model that translated it
+    pub model: String,
+
+    /// Tokens the translation took (if reported)
+    pub tokens: Option<i64>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// An entry's text as it was before an update changed it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct EntryRevision {
+    /// Row ID (ignored when storing)
+    pub id: i64,
+
+    pub entry_id: String,
+
+    /// Text the update replaced
+    pub previous_text: String,
+
+    /// Line diff from the previous text to the new one (`-`/`+` prefixed)
+    pub diff: String,
+
+    pub lines_added: i64,
+
+    pub lines_removed: i64,
+
+    /// What changed, in the AI's words (if it was asked)
+    pub change_summary: Option<String>,
+
+    /// Model that wrote `change_summary`
+    pub model: Option<String>,
+
+    /// Tokens `change_summary` took (if reported)
+    pub tokens: Option<i64>,
+
+    /// When the change was noticed
+    pub created_at: DateTime<Utc>,
+}
+
+impl EntryRevision {
+    /// What changed: the AI's description, or the changed line counts
+    pub fn change_label(&self) -> String {
+        match &self.change_summary {
+            Some(summary) => summary.trim().to_string(),
+            None => format!("+{} -{} lines", self.lines_added, self.lines_removed),
+        }
+    }
+}
+
+/// A user of the API, with read, starred, and tag state of their own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct User {
+    pub id: i64,
+
+    /// Unique name (case-insensitive)
+    pub name: String,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// An entry's comment count at one point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct CommentCount {
+    pub comments: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// What a mute rule's pattern is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum MuteKind {
+    /// Text in the title, description, or content (ignoring case)
+    Keyword,
+    /// Regular expression over the title, description, and content
+    Regex,
+    /// One of the entry's categories (ignoring case)
+    Tag,
+}
+
+/// A rule dropping matching entries from updates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct MuteRule {
+    /// Rule identifier
+    pub id: i64,
+
+    /// How `pattern` is matched
+    pub kind: MuteKind,
+
+    /// Keyword, regular expression, or tag
+    pub pattern: String,
+
+    /// When the rule stops applying (None: until removed)
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// When the rule was added
+    pub created_at: DateTime<Utc>,
+}
+
+/// Tag with the number of entries carrying it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TagCount {
+    /// Tag name
+    pub name: String,
+
+    /// Number of tagged entries
+    pub count: i64,
+
+    /// Tagged entries not yet read (hidden ones left out)
+    pub unread: i64,
+}
+
+/// Entry moved out of `entries` by retention pruning
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ArchivedEntry {
+    /// Original entry identifier
+    pub id: String,
+
+    /// Feed the entry came from (the feed may no longer exist)
+    pub feed_id: String,
+
+    /// Entry title
+    pub title: String,
+
+    /// Entry URL
+    pub url: String,
+
+    /// Author name
+    pub author: Option<String>,
+
+    /// Publication date
+    pub published: Option<DateTime<Utc>>,
+
+    /// Entry summary/description
+    pub summary: Option<String>,
+
+    /// Full content (HTML)
+    pub content_html: Option<String>,
+
+    /// Extracted clean text content
+    pub content_text: Option<String>,
+
+    /// Categories/tags (JSON array)
+    pub categories: Option<String>,
+
+    /// AI summary at the time of archival
+    pub summary_text: Option<String>,
+
+    /// When the entry was first stored
+    pub created_at: DateTime<Utc>,
+
+    /// When the entry was archived
+    pub archived_at: DateTime<Utc>,
+}
+
+/// HTTP fetch state remembered between runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct FetchState {
+    /// Feed this state belongs to
+    pub feed_id: String,
+
+    /// `ETag` from the last successful response
+    pub etag: Option<String>,
+
+    /// `Last-Modified` from the last successful response
+    pub last_modified: Option<String>,
+
+    /// HTTP status of the last response (None if the request never completed)
+    pub last_status: Option<i64>,
+
+    /// Failed fetches since the last success
+    pub consecutive_failures: i64,
+
+    /// Where the last successful fetch was permanently redirected to
+    pub moved_to: Option<String>,
+
+    /// SHA-256 of the last feed body whose entries were processed
+    pub content_hash: Option<String>,
+
+    /// User agent the last successful fetch needed because the configured
+    /// one got 403 Forbidden (None when the configured one worked)
+    pub alternate_user_agent: Option<String>,
+}
+
+/// How alive a feed looks: its failure streak and when it last published
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct FeedHealth {
+    /// Feed described
+    pub feed_id: String,
+
+    /// HTTP status of the last response (None if the request never completed)
+    pub last_status: Option<i64>,
+
+    /// Failed fetches since the last success
+    pub consecutive_failures: i64,
+
+    /// Newest publication date the feed has listed
+    pub last_published: Option<DateTime<Utc>>,
+
+    /// When the newest stored entry of the feed was first stored
+    pub last_stored: Option<DateTime<Utc>>,
+}
+
+impl FeedHealth {
+    /// When the feed last showed signs of life: its newest publication
+    /// date or, for feeds without dates, its newest stored entry
+    pub fn last_active(&self) -> Option<DateTime<Utc>> {
+        self.last_published.max(self.last_stored)
+    }
+}
+
+/// An entry's combined rank (higher ranks first)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct EntryRank {
+    /// Entry ranked
+    pub entry_id: String,
+
+    /// Relevance, feed priority, recency, and social signals combined
+    pub rank: f64,
+
+    /// Hacker News points or Reddit score found in the entry, if any
+    pub social_score: Option<i64>,
+}
+
+/// Folder with feed and entry counts (`name` is None for unfiled feeds)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct FolderCount {
+    /// Folder identifier (what `Feed::folder_id` points at)
+    pub id: Option<i64>,
+
+    /// Folder name
+    pub name: Option<String>,
+
+    /// Feeds in the folder
+    pub feeds: i64,
+
+    /// Unread entries across those feeds
+    pub unread: i64,
+
+    /// All entries across those feeds
+    pub total: i64,
+}
+
+/// Saved search shown as a folder in the TUI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct SmartFolder {
+    /// Folder identifier
+    pub id: i64,
+
+    /// Folder name (unique, case-insensitive)
+    pub name: String,
+
+    /// Search as typed, filters included
+    pub query: String,
+
+    /// Created timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+/// Per-feed activity breakdown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct FeedStats {
+    /// Feed identifier
+    pub feed_id: String,
+
+    /// Feed title
+    pub title: String,
+
+    /// Stored entries
+    pub entries: i64,
+
+    /// Unread entries
+    pub unread: i64,
+
+    /// Average entries per week over the last four weeks
+    pub entries_per_week: f64,
+
+    /// Average tokens of AI summaries (None if nothing was summarized)
+    pub avg_summary_tokens: Option<f64>,
+
+    /// Fetch attempts recorded
+    pub fetches: i64,
+
+    /// Failed fetch attempts recorded
+    pub failures: i64,
+
+    /// Bytes downloaded for the feed since it was added
+    pub bytes: i64,
+}
+
+impl FeedStats {
+    /// Share of fetches that failed (None before the first fetch)
+    pub fn failure_rate(&self) -> Option<f64> {
+        (self.fetches > 0).then(|| self.failures as f64 / self.fetches as f64)
+    }
+}
+
+/// Activity on a single day (UTC)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct DailyCount {
+    /// Day as `YYYY-MM-DD`
+    pub day: String,
+
+    /// Entries first stored that day
+    pub entries: i64,
+
+    /// Summaries generated that day
+    pub summaries: i64,
+}
+
+/// How far an entry was scrolled in the TUI reader
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct ReadingPosition {
+    /// Entry identifier
+    pub entry_id: String,
+
+    /// Rows scrolled past
+    pub scroll: i64,
+
+    /// Scroll offset of the article's last screen when saved
+    pub max_scroll: i64,
+
+    /// When the position was saved
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReadingPosition {
+    /// Scrolled, but not to the end
+    pub fn is_partial(&self) -> bool {
+        self.scroll > 0 && self.scroll < self.max_scroll
+    }
+}
+
+/// Highlight and/or annotation on an entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Note {
+    /// Note identifier
+    pub id: i64,
+
+    /// Entry the note is attached to
+    pub entry_id: String,
+
+    /// Highlighted passage from the entry
+    pub selection_text: Option<String>,
+
+    /// The user's own text
+    pub note_text: Option<String>,
+
+    /// Created timestamp
+    pub created_at: DateTime<Utc>,
+
+    /// Updated timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Group of near-duplicate entries covering the same story
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct StoryCluster {
+    /// Cluster identifier
+    pub id: i64,
+
+    /// Entry shown in place of the whole group
+    pub representative_id: String,
+
+    /// Visible entries in the cluster
+    pub members: i64,
+
+    /// Distinct feeds those entries come from
+    pub sources: i64,
+}
+
+/// Media file attached to an entry (e.g. podcast audio)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Enclosure {
+    /// Enclosure identifier (0 until stored)
+    pub id: i64,
+
+    /// Entry the enclosure belongs to
+    pub entry_id: String,
+
+    /// Remote URL of the file
+    pub url: String,
+
+    /// MIME type as advertised by the feed
+    pub mime_type: Option<String>,
+
+    /// Size in bytes as advertised by the feed
+    pub length: Option<i64>,
+
+    /// Where the downloaded file lives (None until downloaded)
+    pub local_path: Option<String>,
+
+    /// When the download finished
+    pub downloaded_at: Option<DateTime<Utc>>,
+
+    /// Bytes written so far by the download in progress (or the last one)
+    pub downloaded_bytes: i64,
+
+    /// When a download was last started (None if never)
+    pub download_started_at: Option<DateTime<Utc>>,
+
+    /// Why the last download failed
+    pub download_error: Option<String>,
+
+    /// Where playback stopped, in seconds from the start
+    pub position_secs: i64,
+}
+
+impl Enclosure {
+    /// Share of the file downloaded, from 0 to 1, if its length is known
+    pub fn progress(&self) -> Option<f64> {
+        if self.local_path.is_some() {
+            return Some(1.0);
+        }
+        let length = self.length.filter(|&length| length > 0)?;
+        Some((self.downloaded_bytes as f64 / length as f64).min(1.0))
+    }
+}
+
+/// Enclosure whose download was started, with its entry and feed titles
+/// (the TUI's downloads view)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct EnclosureDownload {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub enclosure: Enclosure,
+
+    pub entry_title: String,
+
+    pub feed_title: String,
+}
+
+/// Entry waiting in the summary queue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct QueuedSummary {
+    /// Entry to summarize
+    pub entry_id: String,
+
+    /// When the entry was queued
+    pub enqueued_at: DateTime<Utc>,
+
+    /// Failed summary attempts so far
+    pub attempts: i64,
+
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
+}
+
+/// A feed update past its checkpoint (entries stored, summaries queued)
+/// that has not been logged yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct RunCheckpoint {
+    pub feed_id: String,
+
+    /// Run the update belongs to
+    pub run_id: String,
+
+    /// When the update started
+    pub started_at: DateTime<Utc>,
+
+    /// Entries the update stored for the first time
+    pub new_entries: i64,
+
+    pub checkpointed_at: DateTime<Utc>,
+}
+
+/// AI requests and tokens spent on one UTC day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct AiUsage {
+    /// Summaries requested (cache hits included)
+    pub requests: i64,
+
+    /// Tokens reported by the provider
+    pub tokens: i64,
+}
+
+/// Outcome of updating one feed in one update run (`presser log`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct RunRecord {
+    /// Row ID (ignored when recording)
+    pub id: i64,
+
+    /// Shared by every feed updated in the same run
+    pub run_id: String,
+
+    /// Feed that was updated
+    pub feed_id: String,
+
+    /// When the feed's update started
+    pub started_at: DateTime<Utc>,
+
+    /// How long it took
+    pub duration_ms: i64,
+
+    /// Entries stored for the first time
+    pub new_entries: i64,
+
+    /// New entries that got an AI summary
+    pub summarized: i64,
+
+    /// Entries dropped by keyword filters or hooks
+    pub skipped: i64,
+
+    /// Summaries deferred by the daily token budget
+    pub deferred: i64,
+
+    /// Tokens spent on summaries
+    pub tokens: i64,
+
+    /// Bytes downloaded: the feed, extracted pages, and comment feeds
+    pub bytes: i64,
+
+    /// Server answered 304 Not Modified
+    pub not_modified: bool,
+
+    /// The update itself failed
+    pub failed: bool,
+
+    /// JSON array of error messages, if any
+    pub errors: Option<String>,
+}
+
+impl RunRecord {
+    /// Error messages from `errors`
+    pub fn error_list(&self) -> Vec<String> {
+        self.errors
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+}
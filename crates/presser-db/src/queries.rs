@@ -3,22 +3,73 @@
 //! Uses runtime queries to avoid requiring a database during compilation.
 
 use crate::models::{Entry, Feed, Summary};
-use crate::DatabaseStats;
+use crate::{DatabaseError, DatabaseStats};
 use anyhow::{Context, Result};
-use sqlx::{Row, SqlitePool};
+use chrono::{DateTime, Utc};
+use filter::{query_entries, EntryFilter, EntryOrder};
+use sqlx::query::Query;
+use sqlx::sqlite::{Sqlite, SqliteArguments, SqliteQueryResult};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
+
+pub mod archived_pages;
+pub mod discussions;
+pub mod embeddings;
+pub mod enclosures;
+pub mod fetch_state;
+pub mod filter;
+pub mod fingerprints;
+pub mod folders;
+pub mod leases;
+pub mod maintenance;
+pub mod mutes;
+pub mod notes;
+pub mod ranks;
+pub mod read_later;
+pub mod retention;
+pub mod revisions;
+pub mod runs;
+pub mod search;
+pub mod smart_folders;
+pub mod stats;
+pub mod story_clusters;
+pub mod summary_queue;
+pub mod summary_versions;
+pub mod tags;
+pub mod tokens;
+pub mod translations;
+pub mod ui_state;
+pub mod users;
+
+/// Query type produced by `sqlx::query` for SQLite
+type SqliteQuery<'q> = Query<'q, Sqlite, SqliteArguments<'q>>;
+
+pub use search::search_entries;
+
+/// Fail with `DatabaseError::NotFound` when a keyed update touched no rows
+pub(crate) fn expect_row(
+    result: SqliteQueryResult,
+    what: &str,
+    key: impl std::fmt::Display,
+) -> Result<()> {
+    if result.rows_affected() == 0 {
+        return Err(DatabaseError::not_found(what, key));
+    }
+    Ok(())
+}
 
 // =============================================================================
 // Feed Operations
 // =============================================================================
 
-/// Insert or update a feed
+/// Insert or update a feed (an unset folder keeps the stored one)
 pub async fn upsert_feed(pool: &SqlitePool, feed: &Feed) -> Result<()> {
     sqlx::query(
         r#"
         INSERT INTO feeds (id, url, title, description, site_url, last_fetched,
                           last_successful_fetch, last_error, entry_count, enabled,
-                          created_at, updated_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                          folder_id, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
         ON CONFLICT(id) DO UPDATE SET
             url = excluded.url,
             title = excluded.title,
@@ -29,6 +80,7 @@ pub async fn upsert_feed(pool: &SqlitePool, feed: &Feed) -> Result<()> {
             last_error = excluded.last_error,
             entry_count = excluded.entry_count,
             enabled = excluded.enabled,
+            folder_id = COALESCE(excluded.folder_id, feeds.folder_id),
             updated_at = CURRENT_TIMESTAMP
         "#,
     )
@@ -37,13 +89,14 @@ pub async fn upsert_feed(pool: &SqlitePool, feed: &Feed) -> Result<()> {
     .bind(&feed.title)
     .bind(&feed.description)
     .bind(&feed.site_url)
-    .bind(&feed.last_fetched)
-    .bind(&feed.last_successful_fetch)
+    .bind(feed.last_fetched)
+    .bind(feed.last_successful_fetch)
     .bind(&feed.last_error)
     .bind(feed.entry_count)
     .bind(feed.enabled)
-    .bind(&feed.created_at)
-    .bind(&feed.updated_at)
+    .bind(feed.folder_id)
+    .bind(feed.created_at)
+    .bind(feed.updated_at)
     .execute(pool)
     .await
     .context("Failed to upsert feed")?;
@@ -69,57 +122,137 @@ pub async fn get_all_feeds(pool: &SqlitePool) -> Result<Vec<Feed>> {
 
 /// Delete a feed (entries cascade via foreign key)
 pub async fn delete_feed(pool: &SqlitePool, id: &str) -> Result<()> {
-    sqlx::query("DELETE FROM feeds WHERE id = ?")
+    let result = sqlx::query("DELETE FROM feeds WHERE id = ?")
         .bind(id)
         .execute(pool)
         .await
         .context("Failed to delete feed")?;
-    Ok(())
+    expect_row(result, "feed", id)
 }
 
 // =============================================================================
 // Entry Operations
 // =============================================================================
 
-/// Insert or update an entry (preserves read status on update)
+/// Upsert statement for entries (preserves read, starred, and hidden status on update)
+const UPSERT_ENTRY: &str = r#"
+    INSERT INTO entries (id, feed_id, title, url, author, published, updated,
+                        summary, content_html, content_text, categories, read,
+                        starred, starred_at, score, hidden, created_at, updated_at)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+    ON CONFLICT(id) DO UPDATE SET
+        feed_id = excluded.feed_id,
+        title = excluded.title,
+        url = excluded.url,
+        author = excluded.author,
+        published = excluded.published,
+        updated = excluded.updated,
+        summary = excluded.summary,
+        content_html = excluded.content_html,
+        content_text = excluded.content_text,
+        categories = excluded.categories,
+        score = COALESCE(excluded.score, entries.score),
+        updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    "#;
+
+fn bind_entry<'q>(query: SqliteQuery<'q>, entry: &'q Entry) -> SqliteQuery<'q> {
+    query
+        .bind(&entry.id)
+        .bind(&entry.feed_id)
+        .bind(&entry.title)
+        .bind(&entry.url)
+        .bind(&entry.author)
+        .bind(entry.published)
+        .bind(entry.updated)
+        .bind(&entry.summary)
+        .bind(&entry.content_html)
+        .bind(&entry.content_text)
+        .bind(&entry.categories)
+        .bind(entry.read)
+        .bind(entry.starred)
+        .bind(entry.starred_at)
+        .bind(entry.score)
+        .bind(entry.hidden)
+        .bind(entry.created_at)
+        .bind(entry.updated_at)
+}
+
+/// Insert or update an entry (preserves read, starred, and hidden status on update)
 pub async fn upsert_entry(pool: &SqlitePool, entry: &Entry) -> Result<()> {
-    sqlx::query(
-        r#"
-        INSERT INTO entries (id, feed_id, title, url, author, published, updated,
-                            summary, content_html, content_text, categories, read,
-                            created_at, updated_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
-        ON CONFLICT(id) DO UPDATE SET
-            feed_id = excluded.feed_id,
-            title = excluded.title,
-            url = excluded.url,
-            author = excluded.author,
-            published = excluded.published,
-            updated = excluded.updated,
-            summary = excluded.summary,
-            content_html = excluded.content_html,
-            content_text = excluded.content_text,
-            categories = excluded.categories,
-            updated_at = CURRENT_TIMESTAMP
-        "#,
-    )
-    .bind(&entry.id)
-    .bind(&entry.feed_id)
-    .bind(&entry.title)
-    .bind(&entry.url)
-    .bind(&entry.author)
-    .bind(&entry.published)
-    .bind(&entry.updated)
-    .bind(&entry.summary)
-    .bind(&entry.content_html)
-    .bind(&entry.content_text)
-    .bind(&entry.categories)
-    .bind(entry.read)
-    .bind(&entry.created_at)
-    .bind(&entry.updated_at)
-    .execute(pool)
-    .await
-    .context("Failed to upsert entry")?;
+    bind_entry(sqlx::query(UPSERT_ENTRY), entry)
+        .execute(pool)
+        .await
+        .context("Failed to upsert entry")?;
+    Ok(())
+}
+
+/// Upsert statement that also writes user state, but only over the version
+/// the caller read (`updated_at` compared at millisecond precision)
+const UPSERT_ENTRY_IF_UNCHANGED: &str = r#"
+    INSERT INTO entries (id, feed_id, title, url, author, published, updated,
+                        summary, content_html, content_text, categories, read,
+                        starred, starred_at, score, hidden, created_at, updated_at)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+    ON CONFLICT(id) DO UPDATE SET
+        feed_id = excluded.feed_id,
+        title = excluded.title,
+        url = excluded.url,
+        author = excluded.author,
+        published = excluded.published,
+        updated = excluded.updated,
+        summary = excluded.summary,
+        content_html = excluded.content_html,
+        content_text = excluded.content_text,
+        categories = excluded.categories,
+        read = excluded.read,
+        starred = excluded.starred,
+        starred_at = excluded.starred_at,
+        score = excluded.score,
+        hidden = excluded.hidden,
+        updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE julianday(entries.updated_at) = julianday(excluded.updated_at)
+    "#;
+
+/// Insert an entry, or overwrite it (user state included) if nobody else
+/// changed it since `entry.updated_at` was read
+///
+/// Returns the new `updated_at`, or a `Conflict` error if the stored entry
+/// has moved on; reload it and retry.
+pub async fn upsert_entry_if_unchanged(pool: &SqlitePool, entry: &Entry) -> Result<DateTime<Utc>> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let result = bind_entry(sqlx::query(UPSERT_ENTRY_IF_UNCHANGED), entry)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update entry")?;
+    if result.rows_affected() == 0 {
+        return Err(DatabaseError::Conflict(format!(
+            "entry {} changed since {}",
+            entry.id, entry.updated_at
+        ))
+        .into());
+    }
+    let updated_at = sqlx::query_scalar("SELECT updated_at FROM entries WHERE id = ?")
+        .bind(&entry.id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to read entry version")?;
+    tx.commit().await.context("Failed to commit entry")?;
+    Ok(updated_at)
+}
+
+/// Insert or update many entries in a single transaction
+///
+/// The statement is prepared once and reused for every row, and the single
+/// commit avoids one fsync per entry; see `docs/ARCHITECTURE.md` for numbers.
+pub async fn upsert_entries(pool: &SqlitePool, entries: &[Entry]) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    for entry in entries {
+        bind_entry(sqlx::query(UPSERT_ENTRY), entry)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to upsert entry {}", entry.id))?;
+    }
+    tx.commit().await.context("Failed to commit entries")?;
     Ok(())
 }
 
@@ -132,115 +265,233 @@ pub async fn get_entry(pool: &SqlitePool, id: &str) -> Result<Option<Entry>> {
         .context("Failed to get entry")
 }
 
+pub async fn get_entry_by_url(pool: &SqlitePool, url: &str) -> Result<Option<Entry>> {
+    sqlx::query_as::<_, Entry>("SELECT * FROM entries WHERE url = ?")
+        .bind(url)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get entry by URL")
+}
+
+/// Which of the given entry IDs are already stored
+pub async fn get_known_entry_ids(pool: &SqlitePool, ids: &[String]) -> Result<HashSet<String>> {
+    let ids = serde_json::to_string(ids)?;
+    let known: Vec<String> =
+        sqlx::query_scalar("SELECT id FROM entries WHERE id IN (SELECT value FROM json_each(?))")
+            .bind(ids)
+            .fetch_all(pool)
+            .await
+            .context("Failed to look up known entries")?;
+    Ok(known.into_iter().collect())
+}
+
+/// Feeds of the given entry IDs that are stored, by entry ID
+pub async fn get_entry_feed_ids(
+    pool: &SqlitePool,
+    ids: &[String],
+) -> Result<HashMap<String, String>> {
+    let owners: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, feed_id FROM entries WHERE id IN (SELECT value FROM json_each(?))",
+    )
+    .bind(serde_json::to_string(ids)?)
+    .fetch_all(pool)
+    .await
+    .context("Failed to look up feeds of entries")?;
+    Ok(owners.into_iter().collect())
+}
+
+/// Get the stored entries among the given IDs, in no particular order
+pub async fn get_entries_by_ids(pool: &SqlitePool, ids: &[String]) -> Result<Vec<Entry>> {
+    sqlx::query_as("SELECT * FROM entries WHERE id IN (SELECT value FROM json_each(?))")
+        .bind(serde_json::to_string(ids)?)
+        .fetch_all(pool)
+        .await
+        .context("Failed to get entries")
+}
+
 /// Get entries for a feed, ordered by published date descending
 pub async fn get_entries_for_feed(
     pool: &SqlitePool,
     feed_id: &str,
     limit: i64,
 ) -> Result<Vec<Entry>> {
-    sqlx::query_as::<_, Entry>(
-        "SELECT * FROM entries WHERE feed_id = ? ORDER BY published DESC LIMIT ?",
-    )
-    .bind(feed_id)
-    .bind(limit)
-    .fetch_all(pool)
-    .await
-    .context("Failed to get entries for feed")
+    query_entries(pool, &EntryFilter::new().feed(feed_id).limit(limit)).await
 }
 
 /// Get unread entries, ordered by published date descending
 pub async fn get_unread_entries(pool: &SqlitePool, limit: i64) -> Result<Vec<Entry>> {
-    sqlx::query_as::<_, Entry>(
-        "SELECT * FROM entries WHERE read = 0 ORDER BY published DESC LIMIT ?",
-    )
-    .bind(limit)
-    .fetch_all(pool)
-    .await
-    .context("Failed to get unread entries")
+    query_entries(pool, &EntryFilter::new().unread().limit(limit)).await
 }
 
 /// Mark an entry as read
 pub async fn mark_read(pool: &SqlitePool, entry_id: &str) -> Result<()> {
-    sqlx::query("UPDATE entries SET read = 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+    let result = sqlx::query("UPDATE entries SET read = 1, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = ?")
         .bind(entry_id)
         .execute(pool)
         .await
         .context("Failed to mark entry as read")?;
-    Ok(())
+    expect_row(result, "entry", entry_id)
 }
 
 /// Mark an entry as unread
 pub async fn mark_unread(pool: &SqlitePool, entry_id: &str) -> Result<()> {
-    sqlx::query("UPDATE entries SET read = 0, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+    let result = sqlx::query("UPDATE entries SET read = 0, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = ?")
         .bind(entry_id)
         .execute(pool)
         .await
         .context("Failed to mark entry as unread")?;
-    Ok(())
+    expect_row(result, "entry", entry_id)
 }
 
-// =============================================================================
-// Summary Operations
-// =============================================================================
-
-/// Insert or update a summary
-pub async fn upsert_summary(pool: &SqlitePool, summary: &Summary) -> Result<()> {
-    sqlx::query(
+/// Star an entry (keeps the original starred_at if already starred)
+pub async fn star_entry(pool: &SqlitePool, entry_id: &str) -> Result<()> {
+    let result = sqlx::query(
         r#"
-        INSERT INTO summaries (entry_id, summary_text, model, tokens, content_hash, created_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-        ON CONFLICT(entry_id) DO UPDATE SET
-            summary_text = excluded.summary_text,
-            model = excluded.model,
-            tokens = excluded.tokens,
-            content_hash = excluded.content_hash,
-            created_at = excluded.created_at
+        UPDATE entries
+        SET starred = 1,
+            starred_at = COALESCE(starred_at, CURRENT_TIMESTAMP),
+            updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+        WHERE id = ?
         "#,
     )
-    .bind(&summary.entry_id)
-    .bind(&summary.summary_text)
-    .bind(&summary.model)
-    .bind(summary.tokens)
-    .bind(&summary.content_hash)
-    .bind(&summary.created_at)
+    .bind(entry_id)
     .execute(pool)
     .await
-    .context("Failed to upsert summary")?;
+    .context("Failed to star entry")?;
+    expect_row(result, "entry", entry_id)
+}
+
+/// Remove the star from an entry
+pub async fn unstar_entry(pool: &SqlitePool, entry_id: &str) -> Result<()> {
+    let result = sqlx::query(
+        "UPDATE entries SET starred = 0, starred_at = NULL, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = ?",
+    )
+    .bind(entry_id)
+    .execute(pool)
+    .await
+    .context("Failed to unstar entry")?;
+    expect_row(result, "entry", entry_id)
+}
+
+/// Hide an entry from all views (it stays stored so refetches don't bring it back)
+pub async fn hide_entry(pool: &SqlitePool, entry_id: &str) -> Result<()> {
+    let result = sqlx::query("UPDATE entries SET hidden = 1, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = ?")
+        .bind(entry_id)
+        .execute(pool)
+        .await
+        .context("Failed to hide entry")?;
+    expect_row(result, "entry", entry_id)
+}
+
+/// Make a hidden entry visible again
+pub async fn unhide_entry(pool: &SqlitePool, entry_id: &str) -> Result<()> {
+    let result = sqlx::query("UPDATE entries SET hidden = 0, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = ?")
+        .bind(entry_id)
+        .execute(pool)
+        .await
+        .context("Failed to unhide entry")?;
+    expect_row(result, "entry", entry_id)
+}
+
+/// Get hidden entries, newest first
+pub async fn get_hidden_entries(pool: &SqlitePool, limit: i64) -> Result<Vec<Entry>> {
+    query_entries(pool, &EntryFilter::new().hidden(true).limit(limit)).await
+}
+
+/// Get starred entries, most recently starred first
+pub async fn get_starred_entries(pool: &SqlitePool, limit: i64) -> Result<Vec<Entry>> {
+    let filter = EntryFilter::new()
+        .starred(true)
+        .order(EntryOrder::Starred)
+        .limit(limit);
+    query_entries(pool, &filter).await
+}
+
+// =============================================================================
+// Summary Operations
+// =============================================================================
+
+/// Upsert statement for summary versions (an identical version is promoted, not duplicated)
+const UPSERT_SUMMARY: &str = r#"
+    INSERT INTO summaries (entry_id, summary_text, model, style, tokens, content_hash,
+                           is_current, created_at)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7)
+    ON CONFLICT(entry_id, model, style, content_hash, summary_text) DO UPDATE SET
+        tokens = excluded.tokens,
+        is_current = 1
+    "#;
+
+fn bind_summary<'q>(query: SqliteQuery<'q>, summary: &'q Summary) -> SqliteQuery<'q> {
+    query
+        .bind(&summary.entry_id)
+        .bind(&summary.summary_text)
+        .bind(&summary.model)
+        .bind(&summary.style)
+        .bind(summary.tokens)
+        .bind(&summary.content_hash)
+        .bind(summary.created_at)
+}
+
+/// Store a summary as the entry's current version, keeping older versions
+pub(crate) async fn store_summary(conn: &mut SqliteConnection, summary: &Summary) -> Result<()> {
+    sqlx::query("UPDATE summaries SET is_current = 0 WHERE entry_id = ? AND is_current = 1")
+        .bind(&summary.entry_id)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to demote current summary")?;
+    bind_summary(sqlx::query(UPSERT_SUMMARY), summary)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to upsert summary for {}", summary.entry_id))?;
+    Ok(())
+}
+
+/// Store a summary as the entry's current version (older versions are kept)
+pub async fn upsert_summary(pool: &SqlitePool, summary: &Summary) -> Result<()> {
+    upsert_summaries(pool, std::slice::from_ref(summary)).await
+}
+
+/// Store many summaries as current versions in a single transaction
+pub async fn upsert_summaries(pool: &SqlitePool, summaries: &[Summary]) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    for summary in summaries {
+        store_summary(&mut tx, summary).await?;
+    }
+    tx.commit().await.context("Failed to commit summaries")?;
     Ok(())
 }
 
-/// Get summary for an entry
+/// Get the current summary for an entry
 pub async fn get_summary(pool: &SqlitePool, entry_id: &str) -> Result<Option<Summary>> {
-    sqlx::query_as::<_, Summary>("SELECT * FROM summaries WHERE entry_id = ?")
+    sqlx::query_as::<_, Summary>("SELECT * FROM summaries WHERE entry_id = ? AND is_current = 1")
         .bind(entry_id)
         .fetch_optional(pool)
         .await
         .context("Failed to get summary")
 }
 
-// =============================================================================
-// Search and Statistics
-// =============================================================================
-
-/// Search entries using FTS5 full-text search
-pub async fn search_entries(pool: &SqlitePool, query: &str, limit: i64) -> Result<Vec<Entry>> {
-    sqlx::query_as::<_, Entry>(
-        r#"
-        SELECT e.*
-        FROM entries e
-        JOIN entries_fts fts ON e.rowid = fts.rowid
-        WHERE entries_fts MATCH ?1
-        ORDER BY bm25(entries_fts)
-        LIMIT ?2
-        "#,
+/// Get the current summaries of many entries, keyed by entry ID
+pub async fn get_summaries_for_entries(
+    pool: &SqlitePool,
+    entry_ids: &[String],
+) -> Result<HashMap<String, Summary>> {
+    let summaries = sqlx::query_as::<_, Summary>(
+        "SELECT * FROM summaries WHERE is_current = 1 AND entry_id IN (SELECT value FROM json_each(?))",
     )
-    .bind(query)
-    .bind(limit)
+    .bind(serde_json::to_string(entry_ids)?)
     .fetch_all(pool)
     .await
-    .context("Failed to search entries")
+    .context("Failed to get summaries")?;
+    Ok(summaries
+        .into_iter()
+        .map(|s| (s.entry_id.clone(), s))
+        .collect())
 }
 
+// =============================================================================
+// Statistics
+// =============================================================================
+
 /// Get database statistics
 pub async fn get_stats(pool: &SqlitePool) -> Result<DatabaseStats> {
     let row = sqlx::query(
@@ -248,8 +499,8 @@ pub async fn get_stats(pool: &SqlitePool) -> Result<DatabaseStats> {
         SELECT
             (SELECT COUNT(*) FROM feeds) as total_feeds,
             (SELECT COUNT(*) FROM entries) as total_entries,
-            (SELECT COUNT(*) FROM entries WHERE read = 0) as unread_entries,
-            (SELECT COUNT(*) FROM summaries) as total_summaries
+            (SELECT COUNT(*) FROM entries WHERE read = 0 AND hidden = 0) as unread_entries,
+            (SELECT COUNT(*) FROM summaries WHERE is_current = 1) as total_summaries
         "#,
     )
     .fetch_one(pool)
@@ -0,0 +1,73 @@
+//! Archived article pages (`[archive]`): which stored page belongs to which
+//! entry; the compressed HTML itself lives in files named by hash
+
+use crate::models::ArchivedPage;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Record (or replace) the archived page of an entry
+pub async fn set_archived_page(pool: &SqlitePool, page: &ArchivedPage) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO archived_pages (entry_id, url, hash, size, archived_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(entry_id) DO UPDATE SET
+            url = excluded.url,
+            hash = excluded.hash,
+            size = excluded.size,
+            archived_at = excluded.archived_at
+        "#,
+    )
+    .bind(&page.entry_id)
+    .bind(&page.url)
+    .bind(&page.hash)
+    .bind(page.size)
+    .bind(page.archived_at)
+    .execute(pool)
+    .await
+    .context("Failed to record archived page")?;
+    Ok(())
+}
+
+/// Get the archived page of an entry
+pub async fn get_archived_page(pool: &SqlitePool, entry_id: &str) -> Result<Option<ArchivedPage>> {
+    sqlx::query_as::<_, ArchivedPage>("SELECT * FROM archived_pages WHERE entry_id = ?")
+        .bind(entry_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get archived page")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::ArchivedPage;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_archived_pages() {
+        let (db, _dir) = setup_db().await;
+        let mut page = ArchivedPage {
+            entry_id: "gone-entry".into(),
+            url: "https://example.com/post".into(),
+            hash: "ab12".into(),
+            size: 2048,
+            archived_at: Utc::now() - Duration::days(1),
+        };
+        // Pages are kept without a stored entry
+        db.set_archived_page(&page).await.unwrap();
+        assert_eq!(
+            db.get_archived_page("gone-entry")
+                .await
+                .unwrap()
+                .map(|p| p.hash),
+            Some("ab12".to_string())
+        );
+
+        page.hash = "cd34".into();
+        db.set_archived_page(&page).await.unwrap();
+        let stored = db.get_archived_page("gone-entry").await.unwrap().unwrap();
+        assert_eq!((stored.hash.as_str(), stored.size), ("cd34", 2048));
+        assert!(db.get_archived_page("other").await.unwrap().is_none());
+    }
+}
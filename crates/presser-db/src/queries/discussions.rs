@@ -0,0 +1,162 @@
+//! Discussions of entries (comments pages, comment feeds, comment counts)
+//! and the history of each entry's count
+
+use std::collections::HashMap;
+
+use crate::models::{CommentCount, EntryDiscussion};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Record an entry's discussion; fields left out keep their stored value,
+/// and a changed comment count is appended to the entry's history
+pub async fn set_discussion(pool: &SqlitePool, discussion: &EntryDiscussion) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let previous: Option<(Option<i64>,)> =
+        sqlx::query_as("SELECT comments FROM entry_discussions WHERE entry_id = ?")
+            .bind(&discussion.entry_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to get discussion")?;
+    sqlx::query(
+        r#"
+        INSERT INTO entry_discussions (entry_id, url, comment_feed, comments, checked_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(entry_id) DO UPDATE SET
+            url = COALESCE(excluded.url, url),
+            comment_feed = COALESCE(excluded.comment_feed, comment_feed),
+            comments = COALESCE(excluded.comments, comments),
+            checked_at = excluded.checked_at
+        "#,
+    )
+    .bind(&discussion.entry_id)
+    .bind(&discussion.url)
+    .bind(&discussion.comment_feed)
+    .bind(discussion.comments)
+    .bind(discussion.checked_at)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record discussion")?;
+    if let Some(comments) = discussion.comments {
+        if previous.and_then(|(c,)| c) != Some(comments) {
+            sqlx::query(
+                "INSERT INTO comment_counts (entry_id, comments, recorded_at) VALUES (?, ?, ?)",
+            )
+            .bind(&discussion.entry_id)
+            .bind(comments)
+            .bind(discussion.checked_at)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to record comment count")?;
+        }
+    }
+    tx.commit().await.context("Failed to commit discussion")?;
+    Ok(())
+}
+
+/// Get the discussions of entries, by entry ID
+pub async fn get_discussions_for_entries(
+    pool: &SqlitePool,
+    entry_ids: &[String],
+) -> Result<HashMap<String, EntryDiscussion>> {
+    let discussions: Vec<EntryDiscussion> = sqlx::query_as(
+        "SELECT * FROM entry_discussions WHERE entry_id IN (SELECT value FROM json_each(?))",
+    )
+    .bind(serde_json::to_string(entry_ids)?)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get discussions")?;
+    Ok(discussions
+        .into_iter()
+        .map(|d| (d.entry_id.clone(), d))
+        .collect())
+}
+
+/// Get an entry's recorded comment counts, oldest first
+pub async fn get_comment_history(pool: &SqlitePool, entry_id: &str) -> Result<Vec<CommentCount>> {
+    sqlx::query_as(
+        "SELECT comments, recorded_at FROM comment_counts WHERE entry_id = ? ORDER BY recorded_at",
+    )
+    .bind(entry_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get comment history")
+}
+
+/// Get the discussions with a comment feed of a feed's entries first seen
+/// since `since`
+pub async fn get_comment_feeds(
+    pool: &SqlitePool,
+    feed_id: &str,
+    since: DateTime<Utc>,
+) -> Result<Vec<EntryDiscussion>> {
+    sqlx::query_as(
+        r#"
+        SELECT d.* FROM entry_discussions d
+        JOIN entries e ON e.id = d.entry_id
+        WHERE e.feed_id = ? AND e.created_at >= ? AND d.comment_feed IS NOT NULL
+        ORDER BY e.created_at DESC
+        "#,
+    )
+    .bind(feed_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get comment feeds")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::seeded;
+    use crate::EntryDiscussion;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_discussions() {
+        let (db, _) = seeded().await.unwrap();
+        let now = Utc::now();
+        let mut discussion = EntryDiscussion {
+            entry_id: "rust-async".into(),
+            url: Some("https://news.ycombinator.com/item?id=1".into()),
+            comment_feed: Some("https://blog.rust-lang.org/comments.xml".into()),
+            comments: Some(10),
+            checked_at: now - Duration::hours(2),
+        };
+        db.set_discussion(&discussion).await.unwrap();
+        // Unchanged counts aren't logged again; missing fields are kept
+        discussion.checked_at = now - Duration::hours(1);
+        discussion.url = None;
+        db.set_discussion(&discussion).await.unwrap();
+        discussion.checked_at = now;
+        discussion.comments = Some(42);
+        db.set_discussion(&discussion).await.unwrap();
+
+        let ids = vec!["rust-async".to_string(), "news-weather".to_string()];
+        let stored = db.get_discussions_for_entries(&ids).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored["rust-async"].comments, Some(42));
+        assert_eq!(
+            stored["rust-async"].url.as_deref(),
+            Some("https://news.ycombinator.com/item?id=1")
+        );
+        let history: Vec<i64> = db
+            .get_comment_history("rust-async")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.comments)
+            .collect();
+        assert_eq!(history, [10, 42]);
+
+        let tracked = db
+            .get_comment_feeds("rust-blog", now - Duration::days(2))
+            .await
+            .unwrap();
+        assert_eq!(tracked.len(), 1);
+        assert!(db
+            .get_comment_feeds("rust-blog", now - Duration::hours(1))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
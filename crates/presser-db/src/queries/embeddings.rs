@@ -0,0 +1,314 @@
+//! Embedding storage and vector similarity search
+//!
+//! Similarity is a brute-force cosine scan over all vectors of one model.
+//! With the `sqlite-vec` feature the scan runs inside SQLite via
+//! `vec_distance_cosine`; otherwise vectors are decoded and scored in Rust.
+
+use crate::models::Entry;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// An entry ranked by similarity to a query vector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarEntry {
+    /// Matching entry ID
+    pub entry_id: String,
+
+    /// Cosine similarity in [-1, 1] (higher is closer)
+    pub similarity: f32,
+}
+
+/// Encode a vector as a little-endian f32 BLOB
+pub fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Decode a little-endian f32 BLOB (trailing partial values are ignored)
+pub fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Cosine similarity of two vectors (0 when lengths differ or either is zero)
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Store (or replace) an entry's embedding for a model
+pub async fn store_embedding(
+    pool: &SqlitePool,
+    entry_id: &str,
+    model: &str,
+    vector: &[f32],
+) -> Result<()> {
+    if vector.is_empty() {
+        anyhow::bail!("Refusing to store an empty embedding for {}", entry_id);
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO embeddings (entry_id, model, dims, vector)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(entry_id, model) DO UPDATE SET
+            dims = excluded.dims,
+            vector = excluded.vector,
+            created_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(entry_id)
+    .bind(model)
+    .bind(vector.len() as i64)
+    .bind(encode_vector(vector))
+    .execute(pool)
+    .await
+    .context("Failed to store embedding")?;
+    Ok(())
+}
+
+/// Get an entry's embedding for a model
+pub async fn get_embedding(
+    pool: &SqlitePool,
+    entry_id: &str,
+    model: &str,
+) -> Result<Option<Vec<f32>>> {
+    let blob: Option<Vec<u8>> =
+        sqlx::query_scalar("SELECT vector FROM embeddings WHERE entry_id = ? AND model = ?")
+            .bind(entry_id)
+            .bind(model)
+            .fetch_optional(pool)
+            .await
+            .context("Failed to get embedding")?;
+    Ok(blob.map(|b| decode_vector(&b)))
+}
+
+/// Entries that have no embedding for a model yet, newest first
+pub async fn get_entries_without_embedding(
+    pool: &SqlitePool,
+    model: &str,
+    limit: i64,
+) -> Result<Vec<Entry>> {
+    sqlx::query_as::<_, Entry>(
+        r#"
+        SELECT e.* FROM entries e
+        WHERE NOT EXISTS (SELECT 1 FROM embeddings m WHERE m.entry_id = e.id AND m.model = ?)
+        ORDER BY e.published DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(model)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get entries without embeddings")
+}
+
+/// Delete all embeddings produced by a model (e.g. after switching models)
+pub async fn delete_embeddings_for_model(pool: &SqlitePool, model: &str) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM embeddings WHERE model = ?")
+        .bind(model)
+        .execute(pool)
+        .await
+        .context("Failed to delete embeddings")?;
+    Ok(result.rows_affected())
+}
+
+/// Find the `k` entries whose embeddings are most similar to `query`
+///
+/// Only vectors from the same model and dimensionality are compared.
+pub async fn nearest_entries(
+    pool: &SqlitePool,
+    model: &str,
+    query: &[f32],
+    k: usize,
+) -> Result<Vec<SimilarEntry>> {
+    if query.is_empty() || k == 0 {
+        return Ok(Vec::new());
+    }
+
+    #[cfg(feature = "sqlite-vec")]
+    {
+        let rows: Vec<(String, f64)> = sqlx::query_as(
+            r#"
+            SELECT entry_id, 1.0 - vec_distance_cosine(vector, ?3) AS similarity
+            FROM embeddings
+            WHERE model = ?1 AND dims = ?2
+            ORDER BY similarity DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(model)
+        .bind(query.len() as i64)
+        .bind(encode_vector(query))
+        .bind(k as i64)
+        .fetch_all(pool)
+        .await
+        .context("Failed to search embeddings")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(entry_id, similarity)| SimilarEntry {
+                entry_id,
+                similarity: similarity as f32,
+            })
+            .collect())
+    }
+
+    #[cfg(not(feature = "sqlite-vec"))]
+    {
+        let rows: Vec<(String, Vec<u8>)> =
+            sqlx::query_as("SELECT entry_id, vector FROM embeddings WHERE model = ? AND dims = ?")
+                .bind(model)
+                .bind(query.len() as i64)
+                .fetch_all(pool)
+                .await
+                .context("Failed to load embeddings")?;
+
+        let mut scored: Vec<SimilarEntry> = rows
+            .into_iter()
+            .map(|(entry_id, blob)| SimilarEntry {
+                similarity: cosine_similarity(query, &decode_vector(&blob)),
+                entry_id,
+            })
+            .collect();
+        scored.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        scored.truncate(k);
+        Ok(scored)
+    }
+}
+
+/// Find the `k` entries most similar to an entry, by its newest embedding
+///
+/// Returns None when the entry has no embedding.
+pub async fn similar_entries(
+    pool: &SqlitePool,
+    entry_id: &str,
+    k: usize,
+) -> Result<Option<Vec<SimilarEntry>>> {
+    let row: Option<(String, Vec<u8>)> = sqlx::query_as(
+        "SELECT model, vector FROM embeddings WHERE entry_id = ? ORDER BY created_at DESC, rowid DESC LIMIT 1",
+    )
+    .bind(entry_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get embedding")?;
+    let Some((model, blob)) = row else {
+        return Ok(None);
+    };
+    let mut similar = nearest_entries(pool, &model, &decode_vector(&blob), k + 1).await?;
+    similar.retain(|s| s.entry_id != entry_id);
+    similar.truncate(k);
+    Ok(Some(similar))
+}
+
+/// Register sqlite-vec with every SQLite connection opened afterwards
+#[cfg(feature = "sqlite-vec")]
+pub(crate) fn register_sqlite_vec() {
+    use libsqlite3_sys::{sqlite3, sqlite3_api_routines};
+    use std::os::raw::{c_char, c_int};
+
+    type ExtensionInit = unsafe extern "C" fn(
+        *mut sqlite3,
+        *mut *const c_char,
+        *const sqlite3_api_routines,
+    ) -> c_int;
+
+    static REGISTER: std::sync::Once = std::sync::Once::new();
+    REGISTER.call_once(|| unsafe {
+        // SAFETY: sqlite3_vec_init is a standard SQLite extension entry point;
+        // the sqlite-vec crate just declares it without parameters.
+        let init = std::mem::transmute::<*const (), ExtensionInit>(
+            sqlite_vec::sqlite3_vec_init as *const (),
+        );
+        libsqlite3_sys::sqlite3_auto_extension(Some(init));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_db;
+    use crate::Feed;
+
+    #[test]
+    fn test_vector_encoding_and_cosine() {
+        let v = vec![0.5, -1.25, 3.0];
+        assert_eq!(decode_vector(&encode_vector(&v)), v);
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_nearest_entries() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        for id in ["a", "b", "c", "d"] {
+            db.upsert_entry(&Entry {
+                id: id.into(),
+                feed_id: "f1".into(),
+                title: id.into(),
+                url: format!("https://ex.com/{}", id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+
+        db.store_embedding("a", "m1", &[1.0, 0.0, 0.0])
+            .await
+            .unwrap();
+        db.store_embedding("b", "m1", &[0.9, 0.1, 0.0])
+            .await
+            .unwrap();
+        db.store_embedding("c", "m1", &[0.0, 1.0, 0.0])
+            .await
+            .unwrap();
+        // Other model and dimensionality are never compared
+        db.store_embedding("d", "m2", &[1.0, 0.0, 0.0])
+            .await
+            .unwrap();
+        db.store_embedding("d", "m1", &[1.0, 0.0]).await.unwrap();
+
+        let hits = db.nearest_entries("m1", &[1.0, 0.0, 0.0], 2).await.unwrap();
+        let ids: Vec<_> = hits.iter().map(|h| h.entry_id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert!((hits[0].similarity - 1.0).abs() < 1e-5);
+
+        assert_eq!(
+            db.get_embedding("c", "m1").await.unwrap(),
+            Some(vec![0.0, 1.0, 0.0])
+        );
+        assert!(db.get_embedding("c", "m2").await.unwrap().is_none());
+
+        let similar = db.similar_entries("a", 2).await.unwrap().unwrap();
+        let ids: Vec<_> = similar.iter().map(|h| h.entry_id.as_str()).collect();
+        assert_eq!(ids, ["b", "c"]);
+        assert!(db.similar_entries("missing", 2).await.unwrap().is_none());
+
+        let missing = db.get_entries_without_embedding("m2", 10).await.unwrap();
+        assert_eq!(missing.len(), 3);
+        assert_eq!(db.delete_embeddings_for_model("m1").await.unwrap(), 4);
+    }
+}
@@ -0,0 +1,353 @@
+//! Enclosures and download tracking
+//!
+//! Only the persistence side lives here: downloaders ask for pending
+//! enclosures, report their progress, and record where each file ended up.
+
+use crate::models::{Enclosure, EnclosureDownload};
+use crate::queries::expect_row;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Store an enclosure, refreshing its metadata if the entry already has it
+///
+/// Download state is kept. Returns the enclosure ID.
+pub async fn upsert_enclosure(pool: &SqlitePool, enclosure: &Enclosure) -> Result<i64> {
+    // Execute + SELECT rather than RETURNING: sqlx 0.7 can hand back stale
+    // rows from a cached RETURNING statement when NULLs are bound
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    sqlx::query(
+        r#"
+        INSERT INTO enclosures (entry_id, url, mime_type, length)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(entry_id, url) DO UPDATE SET
+            mime_type = COALESCE(excluded.mime_type, enclosures.mime_type),
+            length = COALESCE(excluded.length, enclosures.length)
+        "#,
+    )
+    .bind(&enclosure.entry_id)
+    .bind(&enclosure.url)
+    .bind(&enclosure.mime_type)
+    .bind(enclosure.length)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to upsert enclosure")?;
+    let id = sqlx::query_scalar("SELECT id FROM enclosures WHERE entry_id = ? AND url = ?")
+        .bind(&enclosure.entry_id)
+        .bind(&enclosure.url)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to look up enclosure")?;
+    tx.commit().await.context("Failed to commit enclosure")?;
+    Ok(id)
+}
+
+/// Get the enclosures of an entry
+pub async fn get_enclosures_for_entry(pool: &SqlitePool, entry_id: &str) -> Result<Vec<Enclosure>> {
+    sqlx::query_as::<_, Enclosure>(
+        r#"
+        SELECT id, entry_id, url, mime_type, length, local_path, downloaded_at,
+               downloaded_bytes, download_started_at, download_error, position_secs
+        FROM enclosures WHERE entry_id = ? ORDER BY id
+        "#,
+    )
+    .bind(entry_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get enclosures")
+}
+
+/// Get an enclosure by ID
+pub async fn get_enclosure(pool: &SqlitePool, id: i64) -> Result<Option<Enclosure>> {
+    sqlx::query_as::<_, Enclosure>(
+        r#"
+        SELECT id, entry_id, url, mime_type, length, local_path, downloaded_at,
+               downloaded_bytes, download_started_at, download_error, position_secs
+        FROM enclosures WHERE id = ?
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get enclosure")
+}
+
+/// Enclosures not downloaded yet, newest entries first
+///
+/// `mime_prefix` narrows the list, e.g. `Some("audio/")` for podcasts.
+/// Enclosures of hidden entries are skipped.
+pub async fn get_pending_downloads(
+    pool: &SqlitePool,
+    mime_prefix: Option<&str>,
+    limit: i64,
+) -> Result<Vec<Enclosure>> {
+    sqlx::query_as::<_, Enclosure>(
+        r#"
+        SELECT n.id, n.entry_id, n.url, n.mime_type, n.length, n.local_path, n.downloaded_at,
+               n.downloaded_bytes, n.download_started_at, n.download_error, n.position_secs
+        FROM enclosures n
+        JOIN entries e ON e.id = n.entry_id
+        WHERE n.local_path IS NULL
+          AND e.hidden = 0
+          AND (?1 IS NULL OR n.mime_type LIKE ?1 || '%')
+        ORDER BY COALESCE(e.published, e.created_at) DESC, n.id
+        LIMIT ?2
+        "#,
+    )
+    .bind(mime_prefix)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get pending downloads")
+}
+
+/// Record that an enclosure was downloaded to `local_path`
+pub async fn mark_downloaded(pool: &SqlitePool, id: i64, local_path: &str) -> Result<()> {
+    let result = sqlx::query(
+        "UPDATE enclosures SET local_path = ?, downloaded_at = CURRENT_TIMESTAMP WHERE id = ?",
+    )
+    .bind(local_path)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to mark enclosure downloaded")?;
+    expect_row(result, "enclosure", id)
+}
+
+/// Forget a download (e.g. after the file was deleted) so it is pending again
+pub async fn clear_download(pool: &SqlitePool, id: i64) -> Result<()> {
+    let result = sqlx::query(
+        r#"
+        UPDATE enclosures
+        SET local_path = NULL, downloaded_at = NULL, downloaded_bytes = 0,
+            download_started_at = NULL, download_error = NULL
+        WHERE id = ?
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to clear enclosure download")?;
+    expect_row(result, "enclosure", id)
+}
+
+/// Record that a download of an enclosure started, forgetting any earlier
+/// progress or failure
+pub async fn start_download(pool: &SqlitePool, id: i64) -> Result<()> {
+    let result = sqlx::query(
+        r#"
+        UPDATE enclosures
+        SET download_started_at = CURRENT_TIMESTAMP, downloaded_bytes = 0, download_error = NULL
+        WHERE id = ?
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to start enclosure download")?;
+    expect_row(result, "enclosure", id)
+}
+
+/// Record how many bytes of an enclosure's download were written
+pub async fn set_download_progress(pool: &SqlitePool, id: i64, bytes: i64) -> Result<()> {
+    let result = sqlx::query("UPDATE enclosures SET downloaded_bytes = ? WHERE id = ?")
+        .bind(bytes)
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to record download progress")?;
+    expect_row(result, "enclosure", id)
+}
+
+/// Record why an enclosure's download failed
+pub async fn fail_download(pool: &SqlitePool, id: i64, error: &str) -> Result<()> {
+    let result = sqlx::query("UPDATE enclosures SET download_error = ? WHERE id = ?")
+        .bind(error)
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to record download failure")?;
+    expect_row(result, "enclosure", id)
+}
+
+/// Enclosures whose download was started, most recently started first,
+/// with their entry and feed titles
+pub async fn get_downloads(pool: &SqlitePool, limit: i64) -> Result<Vec<EnclosureDownload>> {
+    sqlx::query_as::<_, EnclosureDownload>(
+        r#"
+        SELECT n.id, n.entry_id, n.url, n.mime_type, n.length, n.local_path, n.downloaded_at,
+               n.downloaded_bytes, n.download_started_at, n.download_error, n.position_secs,
+               e.title AS entry_title, f.title AS feed_title
+        FROM enclosures n
+        JOIN entries e ON e.id = n.entry_id
+        JOIN feeds f ON f.id = e.feed_id
+        WHERE n.download_started_at IS NOT NULL
+        ORDER BY n.download_started_at DESC, n.id DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get downloads")
+}
+
+/// Record where playback of an enclosure stopped
+pub async fn set_play_position(pool: &SqlitePool, id: i64, position_secs: i64) -> Result<()> {
+    let result = sqlx::query("UPDATE enclosures SET position_secs = ? WHERE id = ?")
+        .bind(position_secs.max(0))
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to record play position")?;
+    expect_row(result, "enclosure", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::seeded;
+    use crate::tests::setup_db;
+    use crate::{Enclosure, Entry, Feed};
+
+    #[tokio::test]
+    async fn test_enclosure_downloads() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "pod".into(),
+            url: "https://pod.com/rss".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        for id in ["ep1", "ep2"] {
+            db.upsert_entry(&Entry {
+                id: id.into(),
+                feed_id: "pod".into(),
+                url: format!("https://pod.com/{}", id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+
+        let audio = |entry_id: &str| Enclosure {
+            entry_id: entry_id.into(),
+            url: format!("https://cdn.pod.com/{}.mp3", entry_id),
+            mime_type: Some("audio/mpeg".into()),
+            length: Some(1024),
+            ..Default::default()
+        };
+        let ep1 = db.upsert_enclosure(&audio("ep1")).await.unwrap();
+        db.upsert_enclosure(&audio("ep2")).await.unwrap();
+        db.upsert_enclosure(&Enclosure {
+            entry_id: "ep2".into(),
+            url: "https://cdn.pod.com/cover.jpg".into(),
+            mime_type: Some("image/jpeg".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(db.get_pending_downloads(None, 10).await.unwrap().len(), 3);
+        assert_eq!(
+            db.get_pending_downloads(Some("audio/"), 10)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        db.mark_downloaded(ep1, "/podcasts/ep1.mp3").await.unwrap();
+        // A refetch without a length keeps the download and the old length
+        let refetched = Enclosure {
+            length: None,
+            ..audio("ep1")
+        };
+        assert_eq!(db.upsert_enclosure(&refetched).await.unwrap(), ep1);
+        let stored = &db.get_enclosures_for_entry("ep1").await.unwrap()[0];
+        assert_eq!(stored.local_path.as_deref(), Some("/podcasts/ep1.mp3"));
+        assert_eq!(stored.length, Some(1024));
+        assert!(stored.downloaded_at.is_some());
+        assert_eq!(
+            db.get_pending_downloads(Some("audio/"), 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        db.clear_download(ep1).await.unwrap();
+        assert_eq!(
+            db.get_pending_downloads(Some("audio/"), 10)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(db
+            .mark_downloaded(999, "/x")
+            .await
+            .unwrap_err()
+            .is_not_found());
+    }
+
+    #[tokio::test]
+    async fn test_download_progress_and_position() {
+        let (db, _) = seeded().await.unwrap();
+        let id = db
+            .upsert_enclosure(&Enclosure {
+                entry_id: "rust-async".into(),
+                url: "https://cdn.example.com/episode.mp3".into(),
+                mime_type: Some("audio/mpeg".into()),
+                length: Some(400),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(db.get_downloads(10).await.unwrap().is_empty());
+
+        db.start_download(id).await.unwrap();
+        db.set_download_progress(id, 100).await.unwrap();
+        let downloads = db.get_downloads(10).await.unwrap();
+        assert_eq!(downloads.len(), 1);
+        assert_eq!(downloads[0].entry_title, "Async closures stabilized");
+        assert_eq!(downloads[0].feed_title, "Rust Blog");
+        assert_eq!(downloads[0].enclosure.progress(), Some(0.25));
+
+        db.fail_download(id, "connection reset").await.unwrap();
+        let failed = db.get_enclosure(id).await.unwrap().unwrap();
+        assert_eq!(failed.download_error.as_deref(), Some("connection reset"));
+        // Starting again forgets the failure
+        db.start_download(id).await.unwrap();
+        db.mark_downloaded(id, "/podcasts/episode.mp3")
+            .await
+            .unwrap();
+        let done = db.get_enclosure(id).await.unwrap().unwrap();
+        assert_eq!(
+            (done.download_error.as_deref(), done.progress()),
+            (None, Some(1.0))
+        );
+
+        db.set_play_position(id, 754).await.unwrap();
+        // Positions survive refetches of the feed
+        db.upsert_enclosure(&Enclosure {
+            entry_id: "rust-async".into(),
+            url: "https://cdn.example.com/episode.mp3".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            db.get_enclosures_for_entry("rust-async").await.unwrap()[0].position_secs,
+            754
+        );
+
+        db.clear_download(id).await.unwrap();
+        assert!(db.get_downloads(10).await.unwrap().is_empty());
+        assert!(db.get_enclosure(999).await.unwrap().is_none());
+        assert!(db
+            .set_play_position(999, 1)
+            .await
+            .unwrap_err()
+            .is_not_found());
+    }
+}
@@ -0,0 +1,303 @@
+//! Per-feed fetch state (conditional GET validators, failure streaks,
+//! newest publication dates)
+
+use crate::models::{FeedHealth, FetchState};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Get the stored fetch state for a feed
+pub async fn get_fetch_state(pool: &SqlitePool, feed_id: &str) -> Result<Option<FetchState>> {
+    sqlx::query_as::<_, FetchState>(
+        r#"
+        SELECT feed_id, etag, last_modified, last_status, consecutive_failures, moved_to,
+               content_hash, alternate_user_agent
+        FROM feed_fetch_state WHERE feed_id = ?
+        "#,
+    )
+    .bind(feed_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get fetch state")
+}
+
+/// Record a completed fetch, replacing the stored state for the feed
+pub async fn set_fetch_state(pool: &SqlitePool, state: &FetchState) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO feed_fetch_state (feed_id, etag, last_modified, last_status,
+                                      consecutive_failures, moved_to, content_hash,
+                                      alternate_user_agent, total_fetches)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1)
+        ON CONFLICT(feed_id) DO UPDATE SET
+            etag = excluded.etag,
+            last_modified = excluded.last_modified,
+            last_status = excluded.last_status,
+            consecutive_failures = excluded.consecutive_failures,
+            moved_to = excluded.moved_to,
+            content_hash = excluded.content_hash,
+            alternate_user_agent = excluded.alternate_user_agent,
+            total_fetches = total_fetches + 1,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(&state.feed_id)
+    .bind(&state.etag)
+    .bind(&state.last_modified)
+    .bind(state.last_status)
+    .bind(state.consecutive_failures)
+    .bind(&state.moved_to)
+    .bind(&state.content_hash)
+    .bind(&state.alternate_user_agent)
+    .execute(pool)
+    .await
+    .context("Failed to set fetch state")?;
+    Ok(())
+}
+
+/// Record a failed fetch, keeping validators and bumping the failure streak
+///
+/// Returns the new consecutive failure count.
+pub async fn record_fetch_failure(
+    pool: &SqlitePool,
+    feed_id: &str,
+    status: Option<i64>,
+) -> Result<i64> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    sqlx::query(
+        r#"
+        INSERT INTO feed_fetch_state (feed_id, last_status, consecutive_failures,
+                                      total_fetches, total_failures)
+        VALUES (?1, ?2, 1, 1, 1)
+        ON CONFLICT(feed_id) DO UPDATE SET
+            last_status = excluded.last_status,
+            consecutive_failures = consecutive_failures + 1,
+            total_fetches = total_fetches + 1,
+            total_failures = total_failures + 1,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(feed_id)
+    .bind(status)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record fetch failure")?;
+    let failures =
+        sqlx::query_scalar("SELECT consecutive_failures FROM feed_fetch_state WHERE feed_id = ?")
+            .bind(feed_id)
+            .fetch_one(&mut *tx)
+            .await
+            .context("Failed to read failure count")?;
+    tx.commit()
+        .await
+        .context("Failed to commit fetch failure")?;
+    Ok(failures)
+}
+
+/// Add to the bytes downloaded for a feed
+pub async fn add_feed_bytes(pool: &SqlitePool, feed_id: &str, bytes: u64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO feed_fetch_state (feed_id, total_bytes) VALUES (?1, ?2)
+        ON CONFLICT(feed_id) DO UPDATE SET total_bytes = total_bytes + excluded.total_bytes
+        "#,
+    )
+    .bind(feed_id)
+    .bind(bytes as i64)
+    .execute(pool)
+    .await
+    .context("Failed to add feed bytes")?;
+    Ok(())
+}
+
+/// Record the newest publication date a fetch of a feed listed, keeping a
+/// later one already stored
+pub async fn record_published(
+    pool: &SqlitePool,
+    feed_id: &str,
+    published: DateTime<Utc>,
+) -> Result<()> {
+    let stored: Option<Option<DateTime<Utc>>> =
+        sqlx::query_scalar("SELECT last_published FROM feed_fetch_state WHERE feed_id = ?")
+            .bind(feed_id)
+            .fetch_optional(pool)
+            .await
+            .context("Failed to get last published date")?;
+    if stored.flatten().is_some_and(|stored| stored >= published) {
+        return Ok(());
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO feed_fetch_state (feed_id, last_published) VALUES (?, ?)
+        ON CONFLICT(feed_id) DO UPDATE SET last_published = excluded.last_published
+        "#,
+    )
+    .bind(feed_id)
+    .bind(published)
+    .execute(pool)
+    .await
+    .context("Failed to record last published date")?;
+    Ok(())
+}
+
+/// Get the health of every feed, ordered by feed ID
+pub async fn get_feed_health(pool: &SqlitePool) -> Result<Vec<FeedHealth>> {
+    sqlx::query_as(
+        r#"
+        SELECT f.id AS feed_id, s.last_status,
+               COALESCE(s.consecutive_failures, 0) AS consecutive_failures,
+               s.last_published,
+               (SELECT MAX(created_at) FROM entries WHERE feed_id = f.id) AS last_stored
+        FROM feeds f
+        LEFT JOIN feed_fetch_state s ON s.feed_id = f.id
+        ORDER BY f.id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get feed health")
+}
+
+/// Feeds whose last successful fetch was permanently redirected, as
+/// `(feed_id, moved_to)` pairs ordered by feed ID
+pub async fn feed_moves(pool: &SqlitePool) -> Result<Vec<(String, String)>> {
+    sqlx::query_as(
+        "SELECT feed_id, moved_to FROM feed_fetch_state WHERE moved_to IS NOT NULL ORDER BY feed_id",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to list feed moves")
+}
+
+/// Feeds whose last successful fetch needed an alternate user agent, as
+/// `(feed_id, user_agent)` pairs ordered by feed ID
+pub async fn feed_user_agents(pool: &SqlitePool) -> Result<Vec<(String, String)>> {
+    sqlx::query_as(
+        r#"
+        SELECT feed_id, alternate_user_agent FROM feed_fetch_state
+        WHERE alternate_user_agent IS NOT NULL ORDER BY feed_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to list feed user agents")
+}
+
+/// Point a feed at the URL it moved to, keeping its ID (and so its entries),
+/// and forget the recorded move
+pub async fn move_feed_url(pool: &SqlitePool, feed_id: &str, url: &str) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let result =
+        sqlx::query("UPDATE feeds SET url = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(url)
+            .bind(feed_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to move feed")?;
+    crate::queries::expect_row(result, "Feed", feed_id)?;
+    sqlx::query("UPDATE feed_fetch_state SET moved_to = NULL WHERE feed_id = ?")
+        .bind(feed_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear feed move")?;
+    tx.commit().await.context("Failed to commit feed move")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::seeded;
+    use crate::tests::setup_db;
+    use crate::Feed;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_fetch_state() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert!(db.get_fetch_state("f1").await.unwrap().is_none());
+
+        let state = FetchState {
+            feed_id: "f1".into(),
+            etag: Some("\"v1\"".into()),
+            last_modified: Some("Mon, 01 Jan 2024 00:00:00 GMT".into()),
+            last_status: Some(200),
+            consecutive_failures: 0,
+            moved_to: Some("https://new.ex.com/f".into()),
+            content_hash: Some("abc123".into()),
+            alternate_user_agent: Some("Mozilla/5.0".into()),
+        };
+        db.set_fetch_state(&state).await.unwrap();
+        assert_eq!(db.get_fetch_state("f1").await.unwrap(), Some(state.clone()));
+
+        assert_eq!(db.record_fetch_failure("f1", Some(503)).await.unwrap(), 1);
+        assert_eq!(db.record_fetch_failure("f1", None).await.unwrap(), 2);
+        let failed = db.get_fetch_state("f1").await.unwrap().unwrap();
+        assert_eq!(failed.etag, state.etag);
+        assert_eq!(failed.content_hash, state.content_hash);
+        assert_eq!(failed.alternate_user_agent, state.alternate_user_agent);
+        assert_eq!(failed.last_status, None);
+        assert_eq!(failed.consecutive_failures, 2);
+
+        assert_eq!(
+            db.feed_moves().await.unwrap(),
+            [("f1".to_string(), "https://new.ex.com/f".to_string())]
+        );
+        db.move_feed_url("f1", "https://new.ex.com/f")
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_feed("f1").await.unwrap().unwrap().url,
+            "https://new.ex.com/f"
+        );
+        assert_eq!(
+            db.get_fetch_state("f1").await.unwrap().unwrap().moved_to,
+            None
+        );
+        assert!(db.feed_moves().await.unwrap().is_empty());
+        assert_eq!(
+            db.feed_user_agents().await.unwrap(),
+            [("f1".to_string(), "Mozilla/5.0".to_string())]
+        );
+        assert!(db
+            .move_feed_url("missing", "https://ex.com/g")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_feed_health() {
+        let (db, _) = seeded().await.unwrap();
+        let health = db.get_feed_health().await.unwrap();
+        let rust = health.iter().find(|h| h.feed_id == "rust-blog").unwrap();
+        assert_eq!(rust.consecutive_failures, 0);
+        assert_eq!(rust.last_published, None);
+        assert!(rust.last_active().is_some());
+
+        let now = Utc::now();
+        db.record_published("rust-blog", now - Duration::days(3))
+            .await
+            .unwrap();
+        // An older date doesn't replace a newer one
+        db.record_published("rust-blog", now - Duration::days(30))
+            .await
+            .unwrap();
+        db.record_fetch_failure("news", Some(410)).await.unwrap();
+        let health = db.get_feed_health().await.unwrap();
+        let rust = health.iter().find(|h| h.feed_id == "rust-blog").unwrap();
+        assert_eq!(rust.last_published, Some(now - Duration::days(3)));
+        let news = health.iter().find(|h| h.feed_id == "news").unwrap();
+        assert_eq!(
+            (news.last_status, news.consecutive_failures),
+            (Some(410), 1)
+        );
+    }
+}
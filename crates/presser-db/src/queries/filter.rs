@@ -0,0 +1,425 @@
+//! Composable entry filtering
+//!
+//! `EntryFilter` compiles to a single SELECT so every listing (feed view,
+//! unread, starred, tag, digest) shares one code path and the same indices.
+
+use crate::models::Entry;
+use crate::queries::search::build_match_query;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+/// Result ordering for filtered entries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryOrder {
+    /// Most recently published first
+    #[default]
+    Newest,
+    /// Oldest published first
+    Oldest,
+    /// Most recently starred first
+    Starred,
+    /// Highest score first
+    Score,
+    /// Highest rank first, then unranked entries newest first
+    Rank,
+}
+
+/// Criteria for selecting entries; unset fields don't constrain the result,
+/// except that hidden entries are left out unless `hidden` is set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntryFilter {
+    /// Only entries from these feeds
+    pub feed_ids: Vec<String>,
+
+    /// Only entries from feeds in these folders
+    pub folders: Vec<String>,
+
+    /// Only entries carrying at least one of these tags
+    pub tags: Vec<String>,
+
+    /// Read state
+    pub read: Option<bool>,
+
+    /// Starred state
+    pub starred: Option<bool>,
+
+    /// Hidden state (unset behaves like `Some(false)`)
+    pub hidden: Option<bool>,
+
+    /// Published at or after
+    pub since: Option<DateTime<Utc>>,
+
+    /// Published before
+    pub until: Option<DateTime<Utc>>,
+
+    /// Whether an AI summary exists
+    pub has_summary: Option<bool>,
+
+    /// Full-text query (same syntax as search)
+    pub text: Option<String>,
+
+    /// Minimum relevance score
+    pub min_score: Option<f64>,
+
+    /// Result ordering
+    pub order: EntryOrder,
+
+    /// Maximum number of entries
+    pub limit: Option<i64>,
+
+    /// Number of entries to skip
+    pub offset: Option<i64>,
+}
+
+impl EntryFilter {
+    /// Filter matching every entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to a feed (may be called repeatedly)
+    pub fn feed(mut self, feed_id: impl Into<String>) -> Self {
+        self.feed_ids.push(feed_id.into());
+        self
+    }
+
+    /// Restrict to feeds in a folder (may be called repeatedly)
+    pub fn folder(mut self, name: impl Into<String>) -> Self {
+        self.folders.push(name.into());
+        self
+    }
+
+    /// Restrict to entries carrying a tag (may be called repeatedly)
+    pub fn tag(mut self, name: impl Into<String>) -> Self {
+        self.tags.push(name.into());
+        self
+    }
+
+    /// Restrict by read state
+    pub fn read(mut self, read: bool) -> Self {
+        self.read = Some(read);
+        self
+    }
+
+    /// Only unread entries
+    pub fn unread(self) -> Self {
+        self.read(false)
+    }
+
+    /// Restrict by starred state
+    pub fn starred(mut self, starred: bool) -> Self {
+        self.starred = Some(starred);
+        self
+    }
+
+    /// Restrict by hidden state (`true` lists only hidden entries)
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = Some(hidden);
+        self
+    }
+
+    /// Only entries published at or after a time
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only entries published before a time
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Restrict by whether an AI summary exists
+    pub fn has_summary(mut self, has_summary: bool) -> Self {
+        self.has_summary = Some(has_summary);
+        self
+    }
+
+    /// Only entries matching a full-text query
+    pub fn text(mut self, query: impl Into<String>) -> Self {
+        self.text = Some(query.into());
+        self
+    }
+
+    /// Only entries scored at least this high
+    pub fn min_score(mut self, score: f64) -> Self {
+        self.min_score = Some(score);
+        self
+    }
+
+    /// Set the result ordering
+    pub fn order(mut self, order: EntryOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Set the maximum number of entries
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first N entries
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Append the WHERE clause (without ORDER/LIMIT) to a query
+    pub(crate) fn push_where<'a>(&'a self, qb: &mut QueryBuilder<'a, Sqlite>) {
+        qb.push(" WHERE 1 = 1");
+
+        if !self.feed_ids.is_empty() {
+            qb.push(" AND e.feed_id IN (");
+            let mut ids = qb.separated(", ");
+            for id in &self.feed_ids {
+                ids.push_bind(id);
+            }
+            qb.push(")");
+        }
+        if !self.folders.is_empty() {
+            qb.push(
+                " AND e.feed_id IN (SELECT f.id FROM feeds f \
+                 JOIN folders d ON d.id = f.folder_id WHERE d.name IN (",
+            );
+            let mut names = qb.separated(", ");
+            for name in &self.folders {
+                names.push_bind(name.trim());
+            }
+            qb.push("))");
+        }
+        if !self.tags.is_empty() {
+            qb.push(
+                " AND e.id IN (SELECT et.entry_id FROM entry_tags et \
+                 JOIN tags t ON t.id = et.tag_id WHERE t.name IN (",
+            );
+            let mut names = qb.separated(", ");
+            for name in &self.tags {
+                names.push_bind(name.trim());
+            }
+            qb.push("))");
+        }
+        if let Some(read) = self.read {
+            qb.push(" AND e.read = ").push_bind(read);
+        }
+        if let Some(starred) = self.starred {
+            qb.push(" AND e.starred = ").push_bind(starred);
+        }
+        qb.push(" AND e.hidden = ")
+            .push_bind(self.hidden.unwrap_or(false));
+        if let Some(since) = self.since {
+            qb.push(" AND e.published >= ").push_bind(since);
+        }
+        if let Some(until) = self.until {
+            qb.push(" AND e.published < ").push_bind(until);
+        }
+        if let Some(has_summary) = self.has_summary {
+            qb.push(if has_summary {
+                " AND EXISTS"
+            } else {
+                " AND NOT EXISTS"
+            })
+            .push(" (SELECT 1 FROM summaries s WHERE s.entry_id = e.id)");
+        }
+        if let Some(text) = &self.text {
+            match build_match_query(text) {
+                Some(match_query) => {
+                    qb.push(
+                        " AND e.rowid IN (SELECT rowid FROM entries_fts WHERE entries_fts MATCH ",
+                    )
+                    .push_bind(match_query)
+                    .push(")");
+                }
+                // Nothing searchable (e.g. only punctuation) matches nothing
+                None => {
+                    qb.push(" AND 0");
+                }
+            }
+        }
+        if let Some(min_score) = self.min_score {
+            qb.push(" AND e.score >= ").push_bind(min_score);
+        }
+    }
+}
+
+/// Fetch entries matching a filter
+pub async fn query_entries(pool: &SqlitePool, filter: &EntryFilter) -> Result<Vec<Entry>> {
+    let mut qb = QueryBuilder::new("SELECT e.* FROM entries e");
+    filter.push_where(&mut qb);
+
+    qb.push(match filter.order {
+        EntryOrder::Newest => " ORDER BY e.published DESC",
+        EntryOrder::Oldest => " ORDER BY e.published ASC",
+        EntryOrder::Starred => " ORDER BY e.starred_at DESC",
+        EntryOrder::Score => " ORDER BY e.score DESC, e.published DESC",
+        EntryOrder::Rank => {
+            " ORDER BY (SELECT r.rank FROM entry_ranks r WHERE r.entry_id = e.id) DESC, \
+             e.published DESC"
+        }
+    });
+    // SQLite requires a LIMIT for OFFSET; -1 means unbounded
+    qb.push(" LIMIT ").push_bind(filter.limit.unwrap_or(-1));
+    if let Some(offset) = filter.offset {
+        qb.push(" OFFSET ").push_bind(offset);
+    }
+
+    qb.build_query_as::<Entry>()
+        .fetch_all(pool)
+        .await
+        .context("Failed to query entries")
+}
+
+/// Count entries matching a filter (ordering, limit, and offset are ignored)
+pub async fn count_entries(pool: &SqlitePool, filter: &EntryFilter) -> Result<i64> {
+    let mut qb = QueryBuilder::new("SELECT COUNT(*) FROM entries e");
+    filter.push_where(&mut qb);
+    qb.build_query_scalar::<i64>()
+        .fetch_one(pool)
+        .await
+        .context("Failed to count entries")
+}
+
+/// Set the read state of every entry matching a filter (ordering, limit, and offset are ignored)
+///
+/// Returns the number of entries whose state changed.
+pub async fn set_read(pool: &SqlitePool, filter: &EntryFilter, read: bool) -> Result<u64> {
+    let mut qb = QueryBuilder::new("UPDATE entries SET read = ");
+    qb.push_bind(read)
+        .push(", updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE read != ")
+        .push_bind(read)
+        .push(" AND id IN (SELECT e.id FROM entries e");
+    filter.push_where(&mut qb);
+    qb.push(")");
+    let result = qb
+        .build()
+        .execute(pool)
+        .await
+        .context("Failed to update read state")?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_db;
+    use crate::{Feed, Summary, TagSource};
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_entry_filter() {
+        let (db, _dir) = setup_db().await;
+        for feed_id in ["f1", "f2"] {
+            db.upsert_feed(&Feed {
+                id: feed_id.into(),
+                url: format!("https://ex.com/{}", feed_id),
+                title: feed_id.into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+        let now = Utc::now();
+        for (i, (feed_id, days_ago, score)) in [("f1", 1, 0.9), ("f1", 5, 0.2), ("f2", 2, 0.5)]
+            .into_iter()
+            .enumerate()
+        {
+            db.upsert_entry(&Entry {
+                id: format!("e{}", i),
+                feed_id: feed_id.into(),
+                title: format!("Entry {} about rust", i),
+                url: format!("https://ex.com/e{}", i),
+                published: Some(now - Duration::days(days_ago)),
+                score: Some(score),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+        db.mark_read("e1").await.unwrap();
+        db.star_entry("e2").await.unwrap();
+        db.add_tag("e2", "Lang", TagSource::User).await.unwrap();
+        db.upsert_summary(&Summary {
+            entry_id: "e0".into(),
+            summary_text: "s".into(),
+            model: "m".into(),
+            content_hash: "h".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let ids = |entries: Vec<Entry>| entries.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        let query = |filter: EntryFilter| {
+            let db = &db;
+            async move { ids(db.query_entries(&filter).await.unwrap()) }
+        };
+
+        assert_eq!(query(EntryFilter::new()).await, ["e0", "e2", "e1"]);
+        assert_eq!(
+            query(EntryFilter::new().order(EntryOrder::Oldest).limit(1)).await,
+            ["e1"]
+        );
+        assert_eq!(query(EntryFilter::new().feed("f1").unread()).await, ["e0"]);
+        assert_eq!(query(EntryFilter::new().starred(true)).await, ["e2"]);
+        assert_eq!(query(EntryFilter::new().tag("lang")).await, ["e2"]);
+        assert_eq!(
+            query(EntryFilter::new().has_summary(false)).await,
+            ["e2", "e1"]
+        );
+        assert_eq!(
+            query(EntryFilter::new().since(now - Duration::days(3))).await,
+            ["e0", "e2"]
+        );
+        assert_eq!(
+            query(EntryFilter::new().until(now - Duration::days(3))).await,
+            ["e1"]
+        );
+        assert_eq!(
+            query(EntryFilter::new().min_score(0.4).order(EntryOrder::Score)).await,
+            ["e0", "e2"]
+        );
+        assert_eq!(
+            query(EntryFilter::new().text("rust").offset(2)).await,
+            ["e1"]
+        );
+        assert!(query(EntryFilter::new().text("python")).await.is_empty());
+        assert_eq!(
+            db.count_entries(&EntryFilter::new().unread())
+                .await
+                .unwrap(),
+            2
+        );
+
+        // Hidden entries drop out of every view until unhidden, even after a refetch
+        db.hide_entry("e0").await.unwrap();
+        let refetched = db.get_entry("e0").await.unwrap().unwrap();
+        db.upsert_entry(&Entry {
+            hidden: false,
+            ..refetched
+        })
+        .await
+        .unwrap();
+        assert_eq!(query(EntryFilter::new()).await, ["e2", "e1"]);
+        assert_eq!(query(EntryFilter::new().hidden(true)).await, ["e0"]);
+        assert!(db.search_entries("Entry 0", 10).await.unwrap().is_empty());
+        assert_eq!(db.get_stats().await.unwrap().unread_entries, 1);
+        db.unhide_entry("e0").await.unwrap();
+        assert!(db.get_hidden_entries(10).await.unwrap().is_empty());
+
+        let older = EntryFilter::new().until(now - Duration::days(1) - Duration::hours(1));
+        assert_eq!(db.set_read(&older, true).await.unwrap(), 1);
+        assert_eq!(db.set_read(&older, true).await.unwrap(), 0);
+        assert_eq!(query(EntryFilter::new().unread()).await, ["e0"]);
+        assert_eq!(
+            db.set_read(&EntryFilter::new().feed("f1"), false)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(query(EntryFilter::new().unread()).await, ["e0", "e1"]);
+    }
+}
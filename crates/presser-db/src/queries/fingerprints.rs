@@ -0,0 +1,122 @@
+//! Fingerprints of stored entries, and the lookup that finds an entry's
+//! original in another feed
+
+use crate::models::EntryFingerprint;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Record (or replace) an entry's fingerprint
+pub async fn set_fingerprint(pool: &SqlitePool, fingerprint: &EntryFingerprint) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO entry_fingerprints (entry_id, canonical_url, text_hash)
+        VALUES (?, ?, ?)
+        ON CONFLICT(entry_id) DO UPDATE SET
+            canonical_url = excluded.canonical_url,
+            text_hash = excluded.text_hash
+        "#,
+    )
+    .bind(&fingerprint.entry_id)
+    .bind(&fingerprint.canonical_url)
+    .bind(&fingerprint.text_hash)
+    .execute(pool)
+    .await
+    .context("Failed to record entry fingerprint")?;
+    Ok(())
+}
+
+/// Get an entry's fingerprint
+pub async fn get_fingerprint(
+    pool: &SqlitePool,
+    entry_id: &str,
+) -> Result<Option<EntryFingerprint>> {
+    sqlx::query_as("SELECT * FROM entry_fingerprints WHERE entry_id = ?")
+        .bind(entry_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get entry fingerprint")
+}
+
+/// ID of the first stored entry of another feed than the fingerprinted
+/// entry's with the same canonical address or text, if any
+pub async fn find_original(
+    pool: &SqlitePool,
+    fingerprint: &EntryFingerprint,
+) -> Result<Option<String>> {
+    sqlx::query_scalar(
+        r#"
+        SELECT f.entry_id FROM entry_fingerprints f
+        JOIN entries e ON e.id = f.entry_id
+        WHERE f.entry_id != ?1
+          AND e.feed_id != (SELECT feed_id FROM entries WHERE id = ?1)
+          AND (f.canonical_url = ?2 OR (?3 IS NOT NULL AND f.text_hash = ?3))
+        ORDER BY e.created_at, e.id
+        LIMIT 1
+        "#,
+    )
+    .bind(&fingerprint.entry_id)
+    .bind(&fingerprint.canonical_url)
+    .bind(&fingerprint.text_hash)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to look up duplicate entries")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::seeded;
+    use crate::EntryFingerprint;
+
+    fn fingerprint(entry_id: &str, url: &str, text_hash: Option<&str>) -> EntryFingerprint {
+        EntryFingerprint {
+            entry_id: entry_id.into(),
+            canonical_url: url.into(),
+            text_hash: text_hash.map(Into::into),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_original() {
+        let (db, _) = seeded().await.unwrap();
+        let async_post = fingerprint("rust-async", "blog.rust-lang.org/async", Some("a1"));
+        db.set_fingerprint(&async_post).await.unwrap();
+        db.set_fingerprint(&fingerprint(
+            "rust-release",
+            "blog.rust-lang.org/1.80",
+            Some("r1"),
+        ))
+        .await
+        .unwrap();
+        assert_eq!(
+            db.get_fingerprint("rust-async").await.unwrap(),
+            Some(async_post.clone())
+        );
+        // Alone, or only matched by its own feed's entries
+        assert_eq!(db.find_original(&async_post).await.unwrap(), None);
+        let same_feed = fingerprint("rust-old", "blog.rust-lang.org/async", None);
+        db.set_fingerprint(&same_feed).await.unwrap();
+        assert_eq!(db.find_original(&same_feed).await.unwrap(), None);
+
+        // A mirror's copy, by address or by text
+        let mirrored = fingerprint("news-election", "blog.rust-lang.org/async", None);
+        db.set_fingerprint(&mirrored).await.unwrap();
+        assert_eq!(
+            db.find_original(&mirrored).await.unwrap().as_deref(),
+            Some("rust-old")
+        );
+        let copied = fingerprint("news-weather", "news.example.com/weather", Some("r1"));
+        assert_eq!(
+            db.find_original(&copied).await.unwrap().as_deref(),
+            Some("rust-release")
+        );
+        let unrelated = fingerprint("news-weather", "news.example.com/weather", Some("w1"));
+        assert_eq!(db.find_original(&unrelated).await.unwrap(), None);
+
+        let owners = db
+            .get_entry_feed_ids(&["rust-async".into(), "planet-async".into()])
+            .await
+            .unwrap();
+        assert_eq!(owners.len(), 1);
+        assert_eq!(owners["rust-async"], "rust-blog");
+    }
+}
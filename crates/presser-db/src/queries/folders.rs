@@ -0,0 +1,196 @@
+//! Feed folders
+//!
+//! Folder names are case-insensitive. The config file is the source of
+//! truth: `sync_folders` applies its assignments and drops empty folders.
+
+use crate::models::{Entry, FolderCount};
+use crate::queries::expect_row;
+use crate::queries::filter::{query_entries, EntryFilter};
+use anyhow::{Context, Result};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::collections::HashMap;
+
+/// Get or create a folder, returning its ID
+async fn ensure_folder(tx: &mut Transaction<'_, Sqlite>, name: &str) -> Result<i64> {
+    sqlx::query("INSERT OR IGNORE INTO folders (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to create folder")?;
+    sqlx::query_scalar("SELECT id FROM folders WHERE name = ?")
+        .bind(name)
+        .fetch_one(&mut **tx)
+        .await
+        .context("Failed to look up folder")
+}
+
+/// File a feed under a folder (None moves it out of any folder)
+pub async fn set_feed_folder(pool: &SqlitePool, feed_id: &str, folder: Option<&str>) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let folder_id = match folder.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(name) => Some(ensure_folder(&mut tx, name).await?),
+        None => None,
+    };
+    let result =
+        sqlx::query("UPDATE feeds SET folder_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(folder_id)
+            .bind(feed_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to set feed folder")?;
+    expect_row(result, "feed", feed_id)?;
+    tx.commit().await.context("Failed to commit feed folder")?;
+    Ok(())
+}
+
+/// Apply folder assignments keyed by feed URL, then drop empty folders
+///
+/// Feeds whose URL is not in `assignments` keep their current folder.
+pub async fn sync_folders(
+    pool: &SqlitePool,
+    assignments: &HashMap<String, Option<String>>,
+) -> Result<()> {
+    // Sorted so that the first spelling of a folder name is stable
+    let mut assignments: Vec<_> = assignments.iter().collect();
+    assignments.sort();
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    for (url, folder) in assignments {
+        let folder_id = match folder.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+            Some(name) => Some(ensure_folder(&mut tx, name).await?),
+            None => None,
+        };
+        sqlx::query("UPDATE feeds SET folder_id = ? WHERE url = ? AND folder_id IS NOT ?")
+            .bind(folder_id)
+            .bind(url)
+            .bind(folder_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to sync feed folder")?;
+    }
+    sqlx::query("DELETE FROM folders WHERE id NOT IN (SELECT folder_id FROM feeds WHERE folder_id IS NOT NULL)")
+        .execute(&mut *tx)
+        .await
+        .context("Failed to remove empty folders")?;
+    tx.commit().await.context("Failed to commit folder sync")?;
+    Ok(())
+}
+
+/// Per-folder feed, unread, and total counts; unfiled feeds come last
+pub async fn get_folder_counts(pool: &SqlitePool) -> Result<Vec<FolderCount>> {
+    sqlx::query_as::<_, FolderCount>(
+        r#"
+        SELECT d.id AS id,
+               d.name AS name,
+               COUNT(DISTINCT f.id) AS feeds,
+               COUNT(e.id) FILTER (WHERE e.read = 0 AND e.hidden = 0) AS unread,
+               COUNT(e.id) AS total
+        FROM feeds f
+        LEFT JOIN folders d ON d.id = f.folder_id
+        LEFT JOIN entries e ON e.feed_id = f.id
+        GROUP BY f.folder_id
+        ORDER BY d.name IS NULL, d.name COLLATE NOCASE
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get folder counts")
+}
+
+/// Get entries from every feed in a folder, newest first
+pub async fn get_entries_for_folder(
+    pool: &SqlitePool,
+    folder: &str,
+    limit: i64,
+) -> Result<Vec<Entry>> {
+    query_entries(pool, &EntryFilter::new().folder(folder).limit(limit)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_db;
+    use crate::Feed;
+
+    #[tokio::test]
+    async fn test_folders() {
+        let (db, _dir) = setup_db().await;
+        for id in ["a", "b", "c"] {
+            db.upsert_feed(&Feed {
+                id: id.into(),
+                url: format!("https://{}.com/feed", id),
+                title: id.into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+            db.upsert_entry(&Entry {
+                id: format!("{}1", id),
+                feed_id: id.into(),
+                title: "t".into(),
+                url: format!("https://{}.com/1", id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+        db.mark_read("b1").await.unwrap();
+
+        let assignments = HashMap::from([
+            ("https://a.com/feed".to_string(), Some("Tech".to_string())),
+            ("https://b.com/feed".to_string(), Some("Tech".to_string())),
+        ]);
+        db.sync_folders(&assignments).await.unwrap();
+
+        let counts = db.get_folder_counts().await.unwrap();
+        assert_eq!(counts.len(), 2);
+        assert!(counts[0].id.is_some());
+        assert_eq!(
+            counts[0],
+            FolderCount {
+                id: counts[0].id,
+                name: Some("Tech".into()),
+                feeds: 2,
+                unread: 1,
+                total: 2
+            }
+        );
+        assert_eq!(
+            counts[1],
+            FolderCount {
+                id: None,
+                name: None,
+                feeds: 1,
+                unread: 1,
+                total: 1
+            }
+        );
+        assert_eq!(
+            db.get_entries_for_folder("TECH", 10).await.unwrap().len(),
+            2
+        );
+
+        // A refresh via upsert_feed keeps the folder
+        let feed = db.get_feed("a").await.unwrap().unwrap();
+        db.upsert_feed(&Feed {
+            folder_id: None,
+            ..feed
+        })
+        .await
+        .unwrap();
+        assert!(db.get_feed("a").await.unwrap().unwrap().folder_id.is_some());
+
+        // Moving every feed out drops the folder
+        db.set_feed_folder("a", Some("News")).await.unwrap();
+        let cleared = HashMap::from([("https://b.com/feed".to_string(), None)]);
+        db.sync_folders(&cleared).await.unwrap();
+        let names: Vec<_> = db
+            .get_folder_counts()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, [Some("News".to_string()), None]);
+    }
+}
@@ -0,0 +1,84 @@
+//! Feed update leases shared by every process using the database
+//!
+//! A lease is taken before a feed is fetched and released afterwards. It
+//! expires on its own, so a crashed process cannot block a feed forever.
+
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+
+/// Claim `feed_id` for `holder` for `ttl`
+///
+/// Succeeds if the feed is unclaimed, its lease expired, or `holder` already
+/// holds it; returns false if another holder has a live lease.
+pub async fn acquire_lease(
+    pool: &SqlitePool,
+    feed_id: &str,
+    holder: &str,
+    ttl: Duration,
+) -> Result<bool> {
+    let now = Utc::now();
+    let result = sqlx::query(
+        r#"
+        INSERT INTO feed_leases (feed_id, holder, expires_at)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(feed_id) DO UPDATE SET
+            holder = excluded.holder,
+            expires_at = excluded.expires_at
+        WHERE feed_leases.holder = excluded.holder OR feed_leases.expires_at <= ?4
+        "#,
+    )
+    .bind(feed_id)
+    .bind(holder)
+    .bind(now + ttl)
+    .bind(now)
+    .execute(pool)
+    .await
+    .context("Failed to acquire feed lease")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Give up `holder`'s lease on `feed_id` (no-op if it holds none)
+pub async fn release_lease(pool: &SqlitePool, feed_id: &str, holder: &str) -> Result<()> {
+    sqlx::query("DELETE FROM feed_leases WHERE feed_id = ? AND holder = ?")
+        .bind(feed_id)
+        .bind(holder)
+        .execute(pool)
+        .await
+        .context("Failed to release feed lease")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::Feed;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_leases() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let minute = Duration::minutes(1);
+
+        assert!(db.acquire_lease("f1", "cli", minute).await.unwrap());
+        assert!(db.acquire_lease("f1", "cli", minute).await.unwrap());
+        assert!(!db.acquire_lease("f1", "daemon", minute).await.unwrap());
+
+        // Only the holder can release
+        db.release_lease("f1", "daemon").await.unwrap();
+        assert!(!db.acquire_lease("f1", "daemon", minute).await.unwrap());
+        db.release_lease("f1", "cli").await.unwrap();
+        assert!(db.acquire_lease("f1", "daemon", -minute).await.unwrap());
+
+        // An expired lease can be taken over
+        assert!(db.acquire_lease("f1", "cli", minute).await.unwrap());
+    }
+}
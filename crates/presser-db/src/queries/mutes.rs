@@ -0,0 +1,102 @@
+//! Mute rules (`presser mute`), dropping matching entries from updates
+//! until they expire
+
+use crate::models::{MuteKind, MuteRule};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Add a mute rule, returning its ID
+pub async fn add_mute_rule(
+    pool: &SqlitePool,
+    kind: MuteKind,
+    pattern: &str,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<i64> {
+    let result = sqlx::query("INSERT INTO mute_rules (kind, pattern, expires_at) VALUES (?, ?, ?)")
+        .bind(kind)
+        .bind(pattern)
+        .bind(expires_at)
+        .execute(pool)
+        .await
+        .context("Failed to add mute rule")?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Mute rules in force at `now`, oldest first
+pub async fn get_active_mute_rules(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<MuteRule>> {
+    sqlx::query_as::<_, MuteRule>(
+        "SELECT * FROM mute_rules WHERE expires_at IS NULL OR expires_at > ? ORDER BY id",
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get mute rules")
+}
+
+/// Delete a mute rule, returning whether it existed
+pub async fn delete_mute_rule(pool: &SqlitePool, id: i64) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM mute_rules WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to delete mute rule")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete the rules that expired by `now`, returning how many
+pub async fn delete_expired_mute_rules(pool: &SqlitePool, now: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM mute_rules WHERE expires_at <= ?")
+        .bind(now)
+        .execute(pool)
+        .await
+        .context("Failed to delete expired mute rules")?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::MuteKind;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_mute_rules_expire() {
+        let (db, _dir) = setup_db().await;
+        let now = Utc::now();
+        let cup = db
+            .add_mute_rule(
+                MuteKind::Keyword,
+                "world cup",
+                Some(now + Duration::days(30)),
+            )
+            .await
+            .unwrap();
+        db.add_mute_rule(MuteKind::Tag, "crypto", Some(now - Duration::hours(1)))
+            .await
+            .unwrap();
+        let forever = db
+            .add_mute_rule(MuteKind::Regex, r"(?i)\bnft\b", None)
+            .await
+            .unwrap();
+
+        let active = db.get_active_mute_rules(now).await.unwrap();
+        let ids: Vec<i64> = active.iter().map(|r| r.id).collect();
+        assert_eq!(ids, [cup, forever]);
+        assert_eq!(active[0].kind, MuteKind::Keyword);
+        assert_eq!(active[0].pattern, "world cup");
+        assert_eq!(active[1].expires_at, None);
+
+        assert_eq!(db.delete_expired_mute_rules(now).await.unwrap(), 1);
+        assert!(db.delete_mute_rule(cup).await.unwrap());
+        assert!(!db.delete_mute_rule(cup).await.unwrap());
+        let ids: Vec<i64> = db
+            .get_active_mute_rules(now + Duration::days(365))
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, [forever]);
+    }
+}
@@ -0,0 +1,134 @@
+//! Notes and highlights on entries
+//!
+//! A note holds a highlighted passage, free text, or both. Notes are
+//! indexed with their entry, so `search` finds entries by their notes.
+
+use crate::models::Note;
+use crate::queries::expect_row;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Add a note to an entry, returning its ID
+pub async fn add_note(
+    pool: &SqlitePool,
+    entry_id: &str,
+    selection_text: Option<&str>,
+    note_text: Option<&str>,
+) -> Result<i64> {
+    if selection_text.is_none() && note_text.is_none() {
+        anyhow::bail!("A note needs a selection, text, or both");
+    }
+    let result =
+        sqlx::query("INSERT INTO notes (entry_id, selection_text, note_text) VALUES (?, ?, ?)")
+            .bind(entry_id)
+            .bind(selection_text)
+            .bind(note_text)
+            .execute(pool)
+            .await
+            .context("Failed to add note")?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Replace the text of a note (the highlighted selection is kept)
+pub async fn update_note(pool: &SqlitePool, id: i64, note_text: Option<&str>) -> Result<()> {
+    let result =
+        sqlx::query("UPDATE notes SET note_text = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(note_text)
+            .bind(id)
+            .execute(pool)
+            .await
+            .context("Failed to update note")?;
+    expect_row(result, "note", id)
+}
+
+/// Delete a note
+pub async fn delete_note(pool: &SqlitePool, id: i64) -> Result<()> {
+    let result = sqlx::query("DELETE FROM notes WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to delete note")?;
+    expect_row(result, "note", id)
+}
+
+/// Get a note by ID
+pub async fn get_note(pool: &SqlitePool, id: i64) -> Result<Option<Note>> {
+    sqlx::query_as::<_, Note>("SELECT * FROM notes WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get note")
+}
+
+/// Get the notes on an entry, oldest first
+pub async fn get_notes_for_entry(pool: &SqlitePool, entry_id: &str) -> Result<Vec<Note>> {
+    sqlx::query_as::<_, Note>("SELECT * FROM notes WHERE entry_id = ? ORDER BY created_at, id")
+        .bind(entry_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to get notes for entry")
+}
+
+/// Get every note, grouped by entry (for export)
+pub async fn get_all_notes(pool: &SqlitePool) -> Result<Vec<Note>> {
+    sqlx::query_as::<_, Note>("SELECT * FROM notes ORDER BY entry_id, created_at, id")
+        .fetch_all(pool)
+        .await
+        .context("Failed to get notes")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed};
+
+    #[tokio::test]
+    async fn test_notes_crud_and_search() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f".into(),
+            url: "https://f.com".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.upsert_entry(&Entry {
+            id: "e".into(),
+            feed_id: "f".into(),
+            title: "Compilers".into(),
+            url: "https://f.com/e".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        assert!(db.add_note("e", None, None).await.is_err());
+        let id = db
+            .add_note("e", Some("register allocation"), Some("revisit"))
+            .await
+            .unwrap();
+        db.add_note("e", None, Some("second thought"))
+            .await
+            .unwrap();
+        assert_eq!(db.get_notes_for_entry("e").await.unwrap().len(), 2);
+        assert_eq!(db.search_entries("allocation", 10).await.unwrap().len(), 1);
+
+        db.update_note(id, Some("done")).await.unwrap();
+        let note = db.get_note(id).await.unwrap().unwrap();
+        assert_eq!(note.selection_text.as_deref(), Some("register allocation"));
+        assert_eq!(note.note_text.as_deref(), Some("done"));
+        assert!(db.search_entries("revisit", 10).await.unwrap().is_empty());
+        assert_eq!(db.search_entries("done", 10).await.unwrap().len(), 1);
+
+        db.delete_note(id).await.unwrap();
+        assert!(db
+            .search_entries("allocation", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(db.get_all_notes().await.unwrap().len(), 1);
+
+        db.delete_feed("f").await.unwrap();
+        assert!(db.get_all_notes().await.unwrap().is_empty());
+    }
+}
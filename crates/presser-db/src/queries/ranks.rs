@@ -0,0 +1,77 @@
+//! Entry ranks (see `presser-core`'s scoring module for how they are made)
+
+use crate::models::EntryRank;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Replace every stored rank with `ranks` in one transaction; entries left
+/// out lose theirs
+pub async fn replace_entry_ranks(pool: &SqlitePool, ranks: &[EntryRank]) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    sqlx::query("DELETE FROM entry_ranks")
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear entry ranks")?;
+    for rank in ranks {
+        sqlx::query("INSERT INTO entry_ranks (entry_id, rank, social_score) VALUES (?, ?, ?)")
+            .bind(&rank.entry_id)
+            .bind(rank.rank)
+            .bind(rank.social_score)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to store rank of entry {}", rank.entry_id))?;
+    }
+    tx.commit().await.context("Failed to commit entry ranks")?;
+    Ok(())
+}
+
+/// Get an entry's rank
+pub async fn get_entry_rank(pool: &SqlitePool, entry_id: &str) -> Result<Option<EntryRank>> {
+    sqlx::query_as::<_, EntryRank>(
+        "SELECT entry_id, rank, social_score FROM entry_ranks WHERE entry_id = ?",
+    )
+    .bind(entry_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get entry rank")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures;
+    use crate::{EntryFilter, EntryOrder, EntryRank};
+
+    #[tokio::test]
+    async fn test_replace_and_order_by_rank() {
+        let (db, _) = fixtures::seeded().await.unwrap();
+        let rank = |id: &str, rank, social_score| EntryRank {
+            entry_id: id.into(),
+            rank,
+            social_score,
+        };
+        db.replace_entry_ranks(&[rank("news-weather", 0.2, None)])
+            .await
+            .unwrap();
+        db.replace_entry_ranks(&[
+            rank("rust-async", 0.9, Some(120)),
+            rank("news-election", 0.4, None),
+        ])
+        .await
+        .unwrap();
+
+        assert_eq!(db.get_entry_rank("news-weather").await.unwrap(), None);
+        assert_eq!(
+            db.get_entry_rank("rust-async").await.unwrap(),
+            Some(rank("rust-async", 0.9, Some(120)))
+        );
+        let ids: Vec<String> = db
+            .query_entries(&EntryFilter::new().unread().order(EntryOrder::Rank))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        // Unranked entries come last, newest first
+        assert_eq!(ids, ["rust-async", "news-election", "news-weather"]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Read-later queue
+//!
+//! Positions are kept contiguous from 0; entries deleted through their feed
+//! leave gaps that the next dequeue or reorder closes.
+
+use crate::models::Entry;
+use crate::DatabaseError;
+use anyhow::{Context, Result};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+
+/// Renumber the queue to 0..n in its current order
+async fn renumber(tx: &mut Transaction<'_, Sqlite>) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE read_later SET position = r.rn
+        FROM (SELECT entry_id, ROW_NUMBER() OVER (ORDER BY position) - 1 AS rn FROM read_later) r
+        WHERE read_later.entry_id = r.entry_id
+        "#,
+    )
+    .execute(&mut **tx)
+    .await
+    .context("Failed to renumber read-later queue")?;
+    Ok(())
+}
+
+/// Append an entry to the back of the queue (false if already queued)
+pub async fn enqueue(pool: &SqlitePool, entry_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO read_later (entry_id, position)
+        SELECT ?, COALESCE(MAX(position) + 1, 0) FROM read_later
+        "#,
+    )
+    .bind(entry_id)
+    .execute(pool)
+    .await
+    .context("Failed to add entry to read later")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove an entry from the queue (false if it was not queued)
+pub async fn dequeue(pool: &SqlitePool, entry_id: &str) -> Result<bool> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let removed = sqlx::query("DELETE FROM read_later WHERE entry_id = ?")
+        .bind(entry_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to remove entry from read later")?
+        .rows_affected()
+        > 0;
+    if removed {
+        renumber(&mut tx).await?;
+    }
+    tx.commit().await.context("Failed to commit read later")?;
+    Ok(removed)
+}
+
+/// Move a queued entry to `position`, clamped to the back of the queue
+pub async fn reorder(pool: &SqlitePool, entry_id: &str, position: i64) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    renumber(&mut tx).await?;
+
+    let current: Option<i64> =
+        sqlx::query_scalar("SELECT position FROM read_later WHERE entry_id = ?")
+            .bind(entry_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to look up read-later position")?;
+    let Some(current) = current else {
+        return Err(DatabaseError::not_found("read-later entry", entry_id));
+    };
+    let len: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM read_later")
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to count read-later queue")?;
+    let target = position.clamp(0, len - 1);
+
+    // Shift the entries between the old and new slot by one towards the gap
+    let shift = if target < current {
+        "UPDATE read_later SET position = position + 1 WHERE position >= ?1 AND position < ?2"
+    } else {
+        "UPDATE read_later SET position = position - 1 WHERE position > ?2 AND position <= ?1"
+    };
+    sqlx::query(shift)
+        .bind(target)
+        .bind(current)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to shift read-later queue")?;
+    sqlx::query("UPDATE read_later SET position = ? WHERE entry_id = ?")
+        .bind(target)
+        .bind(entry_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to move read-later entry")?;
+
+    tx.commit().await.context("Failed to commit read later")?;
+    Ok(())
+}
+
+/// Get queued entries, front of the queue first
+pub async fn get_read_later(pool: &SqlitePool, limit: i64) -> Result<Vec<Entry>> {
+    sqlx::query_as::<_, Entry>(
+        r#"
+        SELECT e.* FROM read_later r
+        JOIN entries e ON e.id = r.entry_id
+        ORDER BY r.position
+        LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get read-later entries")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed};
+
+    #[tokio::test]
+    async fn test_read_later_queue() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f".into(),
+            url: "https://f.com".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        for id in ["a", "b", "c", "d"] {
+            db.upsert_entry(&Entry {
+                id: id.into(),
+                feed_id: "f".into(),
+                url: format!("https://f.com/{}", id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+            assert!(db.enqueue_read_later(id).await.unwrap());
+        }
+        assert!(!db.enqueue_read_later("a").await.unwrap());
+
+        let ids = |entries: Vec<Entry>| entries.into_iter().map(|e| e.id).collect::<Vec<_>>();
+        db.reorder_read_later("d", 0).await.unwrap();
+        assert_eq!(
+            ids(db.get_read_later(10).await.unwrap()),
+            ["d", "a", "b", "c"]
+        );
+        db.reorder_read_later("a", 99).await.unwrap();
+        assert_eq!(
+            ids(db.get_read_later(10).await.unwrap()),
+            ["d", "b", "c", "a"]
+        );
+
+        assert!(db.dequeue_read_later("b").await.unwrap());
+        assert!(!db.dequeue_read_later("b").await.unwrap());
+        db.reorder_read_later("a", 1).await.unwrap();
+        assert_eq!(ids(db.get_read_later(10).await.unwrap()), ["d", "a", "c"]);
+        assert!(db.reorder_read_later("b", 0).await.is_err());
+
+        // Queue membership is independent of the read flag
+        db.mark_read("d").await.unwrap();
+        assert_eq!(db.get_read_later(10).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,245 @@
+//! Retention pruning, archival, and space reclamation
+
+use crate::models::{ArchivedEntry, Entry};
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// Which entries to prune (starred, read-later, and annotated entries are always kept)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Prune entries published (or first seen) more than this many days ago
+    pub max_age_days: Option<u32>,
+
+    /// Keep only the newest N entries of each feed
+    pub max_entries_per_feed: Option<u32>,
+
+    /// Copy pruned entries into `archived_entries` before deleting them
+    pub archive: bool,
+}
+
+impl RetentionPolicy {
+    /// Whether the policy would never prune anything
+    pub fn is_noop(&self) -> bool {
+        self.max_age_days.is_none() && self.max_entries_per_feed.is_none()
+    }
+}
+
+/// Outcome of a prune run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PruneReport {
+    /// Entries removed from `entries`
+    pub deleted: u64,
+
+    /// Entries copied to `archived_entries` (subset of `deleted`)
+    pub archived: u64,
+}
+
+/// Entry ids matched by a policy; ?1 is the age cutoff, ?2 the per-feed limit
+const PRUNE_CANDIDATES: &str = r#"
+    SELECT id FROM (
+        SELECT id, starred,
+               COALESCE(published, created_at) AS ts,
+               ROW_NUMBER() OVER (
+                   PARTITION BY feed_id
+                   ORDER BY julianday(COALESCE(published, created_at)) DESC
+               ) AS rn
+        FROM entries
+    )
+    WHERE starred = 0
+      AND id NOT IN (SELECT entry_id FROM read_later)
+      AND id NOT IN (SELECT entry_id FROM notes)
+      AND ((?1 IS NOT NULL AND julianday(ts) < julianday(?1))
+           OR (?2 IS NOT NULL AND rn > ?2))
+"#;
+
+/// Prune entries according to a policy in a single transaction
+pub async fn prune(pool: &SqlitePool, policy: &RetentionPolicy) -> Result<PruneReport> {
+    if policy.is_noop() {
+        return Ok(PruneReport::default());
+    }
+
+    let cutoff = policy
+        .max_age_days
+        .map(|days| Utc::now() - Duration::days(i64::from(days)));
+    let per_feed = policy.max_entries_per_feed.map(i64::from);
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let mut report = PruneReport::default();
+
+    if policy.archive {
+        report.archived = sqlx::query(&format!(
+            r#"
+            INSERT OR REPLACE INTO archived_entries
+                (id, feed_id, title, url, author, published, summary, content_html,
+                 content_text, categories, summary_text, created_at)
+            SELECT e.id, e.feed_id, e.title, e.url, e.author, e.published, e.summary,
+                   e.content_html, e.content_text, e.categories, s.summary_text, e.created_at
+            FROM entries e
+            LEFT JOIN summaries s ON s.entry_id = e.id AND s.is_current = 1
+            WHERE e.id IN ({})
+            "#,
+            PRUNE_CANDIDATES
+        ))
+        .bind(cutoff)
+        .bind(per_feed)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to archive entries")?
+        .rows_affected();
+    }
+
+    report.deleted = sqlx::query(&format!(
+        "DELETE FROM entries WHERE id IN ({})",
+        PRUNE_CANDIDATES
+    ))
+    .bind(cutoff)
+    .bind(per_feed)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to prune entries")?
+    .rows_affected();
+
+    tx.commit().await.context("Failed to commit prune")?;
+    Ok(report)
+}
+
+/// Entries a policy would prune, without deleting anything (oldest first)
+pub async fn prune_candidates(pool: &SqlitePool, policy: &RetentionPolicy) -> Result<Vec<Entry>> {
+    if policy.is_noop() {
+        return Ok(Vec::new());
+    }
+    let cutoff = policy
+        .max_age_days
+        .map(|days| Utc::now() - Duration::days(i64::from(days)));
+    sqlx::query_as::<_, Entry>(&format!(
+        "SELECT * FROM entries WHERE id IN ({}) \
+         ORDER BY feed_id, julianday(COALESCE(published, created_at))",
+        PRUNE_CANDIDATES
+    ))
+    .bind(cutoff)
+    .bind(policy.max_entries_per_feed.map(i64::from))
+    .fetch_all(pool)
+    .await
+    .context("Failed to find prune candidates")
+}
+
+/// Get archived entries, most recently archived first
+pub async fn get_archived_entries(pool: &SqlitePool, limit: i64) -> Result<Vec<ArchivedEntry>> {
+    sqlx::query_as::<_, ArchivedEntry>(
+        "SELECT * FROM archived_entries ORDER BY archived_at DESC, published DESC LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get archived entries")
+}
+
+/// Rebuild the database file
+///
+/// Cannot run inside a transaction; expect it to take a while on large databases.
+/// Under WAL the rebuilt pages pass through the WAL, so checkpoint afterwards.
+pub async fn vacuum(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("VACUUM")
+        .execute(pool)
+        .await
+        .context("Failed to vacuum database")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed, Summary};
+
+    async fn seed(db: &crate::Database) {
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        for (i, days_ago) in [1, 10, 40, 50].into_iter().enumerate() {
+            db.upsert_entry(&Entry {
+                id: format!("e{}", i),
+                feed_id: "f1".into(),
+                title: format!("Entry {}", i),
+                url: format!("https://ex.com/{}", i),
+                published: Some(Utc::now() - Duration::days(days_ago)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_by_age_keeps_starred() {
+        let (db, _dir) = setup_db().await;
+        seed(&db).await;
+        db.star_entry("e3").await.unwrap();
+
+        let policy = RetentionPolicy {
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+        let candidates = db.prune_candidates(&policy).await.unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].id, "e2");
+        let report = db.prune(&policy).await.unwrap();
+        assert_eq!(
+            report,
+            PruneReport {
+                deleted: 1,
+                archived: 0
+            }
+        );
+        assert!(db.get_entry("e2").await.unwrap().is_none());
+        assert!(db.get_entry("e3").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prune_per_feed_with_archive() {
+        let (db, _dir) = setup_db().await;
+        seed(&db).await;
+        db.upsert_summary(&Summary {
+            entry_id: "e3".into(),
+            summary_text: "old news".into(),
+            model: "m".into(),
+            content_hash: "h".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let policy = RetentionPolicy {
+            max_entries_per_feed: Some(2),
+            archive: true,
+            ..Default::default()
+        };
+        let report = db.prune(&policy).await.unwrap();
+        assert_eq!(
+            report,
+            PruneReport {
+                deleted: 2,
+                archived: 2
+            }
+        );
+        assert_eq!(db.get_entries_for_feed("f1", 10).await.unwrap().len(), 2);
+
+        let archived = db.get_archived_entries(10).await.unwrap();
+        assert_eq!(archived.len(), 2);
+        let e3 = archived.iter().find(|a| a.id == "e3").unwrap();
+        assert_eq!(e3.summary_text.as_deref(), Some("old news"));
+
+        assert_eq!(
+            db.prune(&RetentionPolicy::default()).await.unwrap(),
+            PruneReport::default()
+        );
+        db.vacuum().await.unwrap();
+    }
+}
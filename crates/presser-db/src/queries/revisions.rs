@@ -0,0 +1,111 @@
+//! Earlier versions of entries whose text changed after they were stored
+
+use std::collections::HashMap;
+
+use crate::models::EntryRevision;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Store the text an update replaced, returning the revision's row ID
+pub async fn add_revision(pool: &SqlitePool, revision: &EntryRevision) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO entry_revisions (entry_id, previous_text, diff, lines_added, lines_removed,
+                                     change_summary, model, tokens, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&revision.entry_id)
+    .bind(&revision.previous_text)
+    .bind(&revision.diff)
+    .bind(revision.lines_added)
+    .bind(revision.lines_removed)
+    .bind(&revision.change_summary)
+    .bind(&revision.model)
+    .bind(revision.tokens)
+    .bind(revision.created_at)
+    .execute(pool)
+    .await
+    .context("Failed to store revision")?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Get an entry's revisions, newest first
+pub async fn get_revisions(pool: &SqlitePool, entry_id: &str) -> Result<Vec<EntryRevision>> {
+    sqlx::query_as("SELECT * FROM entry_revisions WHERE entry_id = ? ORDER BY id DESC")
+        .bind(entry_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to get revisions")
+}
+
+/// Get the newest revision of each of the given entries that has one
+pub async fn get_latest_revisions(
+    pool: &SqlitePool,
+    entry_ids: &[String],
+) -> Result<HashMap<String, EntryRevision>> {
+    let revisions: Vec<EntryRevision> = sqlx::query_as(
+        r#"
+        SELECT * FROM entry_revisions r
+        WHERE entry_id IN (SELECT value FROM json_each(?))
+          AND id = (SELECT MAX(id) FROM entry_revisions WHERE entry_id = r.entry_id)
+        "#,
+    )
+    .bind(serde_json::to_string(entry_ids)?)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get latest revisions")?;
+    Ok(revisions
+        .into_iter()
+        .map(|r| (r.entry_id.clone(), r))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::seeded;
+    use crate::EntryRevision;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn test_revisions() {
+        let (db, _) = seeded().await.unwrap();
+        let ids = vec!["news-weather".to_string(), "rust-async".to_string()];
+        assert!(db.get_latest_revisions(&ids).await.unwrap().is_empty());
+
+        let first = EntryRevision {
+            entry_id: "news-weather".into(),
+            previous_text: "Rain".into(),
+            diff: "-Rain\n+Rain and wind\n".into(),
+            lines_added: 1,
+            lines_removed: 1,
+            created_at: Utc::now(),
+            ..Default::default()
+        };
+        db.add_revision(&first).await.unwrap();
+        let second = EntryRevision {
+            previous_text: "Rain and wind".into(),
+            diff: "-Rain and wind\n+Storm warning\n".into(),
+            change_summary: Some("Upgraded to a storm warning".into()),
+            model: Some("gpt-4".into()),
+            tokens: Some(20),
+            ..first.clone()
+        };
+        let id = db.add_revision(&second).await.unwrap();
+
+        let revisions = db.get_revisions("news-weather").await.unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].id, id);
+        assert_eq!(revisions[1].change_label(), "+1 -1 lines");
+        let latest = db.get_latest_revisions(&ids).await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(
+            latest["news-weather"].change_label(),
+            "Upgraded to a storm warning"
+        );
+
+        // Revisions go with their entries
+        db.delete_feed("news").await.unwrap();
+        assert!(db.get_revisions("news-weather").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+//! Per-feed update run log
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::models::{RunCheckpoint, RunRecord};
+
+/// Store the outcome of updating one feed, returning its row ID
+pub async fn record_run(pool: &SqlitePool, run: &RunRecord) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO runs (run_id, feed_id, started_at, duration_ms, new_entries, summarized,
+                          skipped, deferred, tokens, bytes, not_modified, failed, errors)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&run.run_id)
+    .bind(&run.feed_id)
+    .bind(run.started_at)
+    .bind(run.duration_ms)
+    .bind(run.new_entries)
+    .bind(run.summarized)
+    .bind(run.skipped)
+    .bind(run.deferred)
+    .bind(run.tokens)
+    .bind(run.bytes)
+    .bind(run.not_modified)
+    .bind(run.failed)
+    .bind(&run.errors)
+    .execute(pool)
+    .await
+    .context("Failed to record run")?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Recorded feed updates, newest first, optionally for one feed and since a time
+pub async fn get_runs(
+    pool: &SqlitePool,
+    feed_id: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<RunRecord>> {
+    sqlx::query_as::<_, RunRecord>(
+        r#"
+        SELECT id, run_id, feed_id, started_at, duration_ms, new_entries, summarized,
+               skipped, deferred, tokens, bytes, not_modified, failed, errors
+        FROM runs
+        WHERE (?1 IS NULL OR feed_id = ?1)
+          AND (?2 IS NULL OR julianday(started_at) >= julianday(?2))
+        ORDER BY julianday(started_at) DESC, id DESC
+        LIMIT ?3
+        "#,
+    )
+    .bind(feed_id)
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get runs")
+}
+
+/// Forget feed updates that started before `before`, returning how many
+pub async fn prune_runs(pool: &SqlitePool, before: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM runs WHERE julianday(started_at) < julianday(?)")
+        .bind(before)
+        .execute(pool)
+        .await
+        .context("Failed to prune runs")?;
+    Ok(result.rows_affected())
+}
+
+/// Record that a feed's update reached its checkpoint
+pub async fn set_run_checkpoint(pool: &SqlitePool, checkpoint: &RunCheckpoint) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO run_checkpoints (feed_id, run_id, started_at, new_entries, checkpointed_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(feed_id) DO UPDATE SET
+            run_id = excluded.run_id,
+            started_at = excluded.started_at,
+            new_entries = excluded.new_entries,
+            checkpointed_at = excluded.checkpointed_at
+        "#,
+    )
+    .bind(&checkpoint.feed_id)
+    .bind(&checkpoint.run_id)
+    .bind(checkpoint.started_at)
+    .bind(checkpoint.new_entries)
+    .bind(checkpoint.checkpointed_at)
+    .execute(pool)
+    .await
+    .context("Failed to record run checkpoint")?;
+    Ok(())
+}
+
+/// Remove and return a feed's checkpoint, if one was left
+pub async fn take_run_checkpoint(
+    pool: &SqlitePool,
+    feed_id: &str,
+) -> Result<Option<RunCheckpoint>> {
+    sqlx::query_as("DELETE FROM run_checkpoints WHERE feed_id = ? RETURNING *")
+        .bind(feed_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to take run checkpoint")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::RunRecord;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_runs() {
+        let (db, _dir) = setup_db().await;
+        let now = Utc::now();
+        let run = |run_id: &str, feed_id: &str, days_ago: i64| RunRecord {
+            run_id: run_id.into(),
+            feed_id: feed_id.into(),
+            started_at: now - Duration::days(days_ago),
+            new_entries: 2,
+            bytes: 4096,
+            ..Default::default()
+        };
+        db.record_run(&run("r1", "a", 10)).await.unwrap();
+        db.record_run(&run("r2", "a", 1)).await.unwrap();
+        db.record_run(&RunRecord {
+            failed: true,
+            errors: Some(serde_json::to_string(&["HTTP 503"]).unwrap()),
+            ..run("r2", "b", 1)
+        })
+        .await
+        .unwrap();
+
+        let all = db.get_runs(None, None, 10).await.unwrap();
+        let order: Vec<_> = all
+            .iter()
+            .map(|r| (r.run_id.as_str(), r.feed_id.as_str()))
+            .collect();
+        assert_eq!(order, [("r2", "b"), ("r2", "a"), ("r1", "a")]);
+        assert_eq!(all[0].error_list(), ["HTTP 503"]);
+        assert!(all[1].error_list().is_empty());
+
+        let recent = db
+            .get_runs(Some("a"), Some(now - Duration::days(7)), 10)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(
+            (
+                recent[0].run_id.as_str(),
+                recent[0].new_entries,
+                recent[0].bytes
+            ),
+            ("r2", 2, 4096)
+        );
+
+        assert_eq!(db.prune_runs(now - Duration::days(7)).await.unwrap(), 1);
+        assert_eq!(db.get_runs(None, None, 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_checkpoints() {
+        let (db, _) = crate::fixtures::seeded().await.unwrap();
+        assert_eq!(db.take_run_checkpoint("news").await.unwrap(), None);
+
+        let checkpoint = crate::RunCheckpoint {
+            feed_id: "news".into(),
+            run_id: "r1".into(),
+            started_at: Utc::now(),
+            new_entries: 3,
+            checkpointed_at: Utc::now(),
+        };
+        db.set_run_checkpoint(&checkpoint).await.unwrap();
+        assert_eq!(
+            db.take_run_checkpoint("news").await.unwrap(),
+            Some(checkpoint)
+        );
+        assert_eq!(db.take_run_checkpoint("news").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,315 @@
+//! Full-text search over entries, AI summaries, and notes (FTS5)
+
+use crate::models::Entry;
+use crate::queries::filter::EntryFilter;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Row, SqlitePool};
+
+/// Default marker placed before a matched term in snippets
+pub const HIGHLIGHT_START: &str = "«";
+
+/// Default marker placed after a matched term in snippets
+pub const HIGHLIGHT_END: &str = "»";
+
+/// Search parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchOptions {
+    /// User query: words, "quoted phrases", `prefix*` terms, and `OR`
+    pub query: String,
+
+    /// Further criteria on the matching entries (its ordering and limit are
+    /// not used)
+    #[serde(default)]
+    pub filter: EntryFilter,
+
+    /// Maximum number of hits
+    pub limit: i64,
+
+    /// Marker inserted before highlighted terms
+    pub highlight_start: String,
+
+    /// Marker inserted after highlighted terms
+    pub highlight_end: String,
+}
+
+impl SearchOptions {
+    /// Search all feeds with default limit and highlight markers
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            filter: EntryFilter::new(),
+            limit: 50,
+            highlight_start: HIGHLIGHT_START.to_string(),
+            highlight_end: HIGHLIGHT_END.to_string(),
+        }
+    }
+
+    /// Restrict the search to a single feed
+    pub fn in_feed(mut self, feed_id: impl Into<String>) -> Self {
+        self.filter = self.filter.feed(feed_id);
+        self
+    }
+
+    /// Only return entries that also match a filter
+    pub fn filter(mut self, filter: EntryFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the maximum number of hits
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// A matching entry with its best snippet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// The matching entry
+    pub entry: Entry,
+
+    /// Excerpt around the match with highlight markers
+    pub snippet: String,
+
+    /// BM25 rank (lower is better)
+    pub rank: f64,
+}
+
+/// Convert user input into a safe FTS5 MATCH expression
+///
+/// Every term is quoted so punctuation can't break the FTS5 grammar.
+/// `"phrases"` stay phrases, a trailing `*` becomes a prefix query, and a
+/// bare `OR` between terms is kept as the operator. Returns None if the
+/// input has no searchable terms.
+pub fn build_match_query(input: &str) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let (raw, quoted) = if c == '"' {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            (phrase, true)
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            (word, false)
+        };
+
+        if !quoted && raw == "OR" {
+            if terms.last().is_some_and(|t| t != "OR") {
+                terms.push("OR".to_string());
+            }
+            continue;
+        }
+
+        let (body, prefix) = match raw.strip_suffix('*') {
+            Some(body) if !quoted => (body, true),
+            _ => (raw.as_str(), false),
+        };
+        let body = body.trim();
+        if body.is_empty() {
+            continue;
+        }
+
+        let escaped = format!("\"{}\"", body.replace('"', "\"\""));
+        terms.push(if prefix {
+            format!("{}*", escaped)
+        } else {
+            escaped
+        });
+    }
+
+    while terms.last().is_some_and(|t| t == "OR") {
+        terms.pop();
+    }
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Search entries, summaries, and notes, returning ranked hits with snippets
+pub async fn search(pool: &SqlitePool, options: &SearchOptions) -> Result<Vec<SearchHit>> {
+    let Some(match_query) = build_match_query(&options.query) else {
+        return Ok(Vec::new());
+    };
+
+    let mut qb = QueryBuilder::new("SELECT e.*, snippet(entries_fts, -1, ");
+    qb.push_bind(&options.highlight_start)
+        .push(", ")
+        .push_bind(&options.highlight_end)
+        .push(
+            ", '…', 16) AS snippet, bm25(entries_fts) AS rank \
+             FROM entries_fts JOIN entries e ON e.rowid = entries_fts.rowid",
+        );
+    options.filter.push_where(&mut qb);
+    qb.push(" AND entries_fts MATCH ")
+        .push_bind(match_query)
+        .push(" ORDER BY rank LIMIT ")
+        .push_bind(options.limit);
+
+    let rows = qb
+        .build()
+        .fetch_all(pool)
+        .await
+        .context("Failed to search entries")?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(SearchHit {
+                entry: Entry::from_row(row)?,
+                snippet: row.try_get("snippet")?,
+                rank: row.try_get("rank")?,
+            })
+        })
+        .collect()
+}
+
+/// Search entries, returning just the entries in rank order
+pub async fn search_entries(pool: &SqlitePool, query: &str, limit: i64) -> Result<Vec<Entry>> {
+    let hits = search(pool, &SearchOptions::new(query).limit(limit)).await?;
+    Ok(hits.into_iter().map(|hit| hit.entry).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed, Summary};
+
+    #[test]
+    fn test_build_match_query() {
+        assert_eq!(
+            build_match_query("rust async").as_deref(),
+            Some(r#""rust" "async""#)
+        );
+        assert_eq!(
+            build_match_query(r#""async await" tok*"#).as_deref(),
+            Some(r#""async await" "tok"*"#)
+        );
+        assert_eq!(
+            build_match_query("rust OR go").as_deref(),
+            Some(r#""rust" OR "go""#)
+        );
+        assert_eq!(
+            build_match_query("OR rust OR").as_deref(),
+            Some(r#""rust""#)
+        );
+        assert_eq!(
+            build_match_query("c++ (beta)").as_deref(),
+            Some(r#""c++" "(beta)""#)
+        );
+        assert_eq!(build_match_query("  * \"\" "), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_phrases_prefix_and_scope() {
+        let (db, _dir) = setup_db().await;
+        for feed_id in ["f1", "f2"] {
+            db.upsert_feed(&Feed {
+                id: feed_id.into(),
+                url: format!("https://ex.com/{}", feed_id),
+                title: feed_id.into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+        db.upsert_entry(&Entry {
+            id: "e1".into(),
+            feed_id: "f1".into(),
+            title: "Tokio internals".into(),
+            url: "https://ex.com/e1".into(),
+            content_text: Some("The async await model in Rust".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.upsert_entry(&Entry {
+            id: "e2".into(),
+            feed_id: "f2".into(),
+            title: "Await considered".into(),
+            url: "https://ex.com/e2".into(),
+            content_text: Some("Python async and then await".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let hits = db
+            .search(&SearchOptions::new("\"async await\""))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.id, "e1");
+        assert_eq!(hits[0].snippet, "The «async await» model in Rust");
+
+        let hits = db.search(&SearchOptions::new("tok*")).await.unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let hits = db
+            .search(&SearchOptions::new("await").in_feed("f2"))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.id, "e2");
+
+        db.mark_read("e2").await.unwrap();
+        let hits = db
+            .search(&SearchOptions::new("async").filter(EntryFilter::new().unread()))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.id, "e1");
+    }
+
+    #[tokio::test]
+    async fn test_search_summary_text() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.upsert_entry(&Entry {
+            id: "e1".into(),
+            feed_id: "f1".into(),
+            title: "Untitled".into(),
+            url: "https://ex.com/e1".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        assert!(db.search_entries("quantum", 10).await.unwrap().is_empty());
+
+        db.upsert_summary(&Summary {
+            entry_id: "e1".into(),
+            summary_text: "A quantum computing milestone".into(),
+            model: "m".into(),
+            content_hash: "h".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(db.search_entries("quantum", 10).await.unwrap().len(), 1);
+
+        // Reading an entry must not drop it from the index
+        db.mark_read("e1").await.unwrap();
+        assert_eq!(db.search_entries("quantum", 10).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,74 @@
+//! Smart folders: saved searches
+//!
+//! Only the query text is stored; parsing and running it is up to the
+//! caller, so a folder always shows the current matches.
+
+use crate::models::SmartFolder;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Save a search under a name, replacing the query of a folder with that name
+pub async fn save_smart_folder(pool: &SqlitePool, name: &str, query: &str) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        anyhow::bail!("A smart folder needs a name");
+    }
+    sqlx::query(
+        "INSERT INTO smart_folders (name, query) VALUES (?, ?) \
+         ON CONFLICT(name) DO UPDATE SET query = excluded.query",
+    )
+    .bind(name)
+    .bind(query.trim())
+    .execute(pool)
+    .await
+    .context("Failed to save smart folder")?;
+    Ok(())
+}
+
+/// Delete a smart folder, returning whether it existed
+pub async fn delete_smart_folder(pool: &SqlitePool, name: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM smart_folders WHERE name = ?")
+        .bind(name.trim())
+        .execute(pool)
+        .await
+        .context("Failed to delete smart folder")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get every smart folder by name
+pub async fn get_smart_folders(pool: &SqlitePool) -> Result<Vec<SmartFolder>> {
+    sqlx::query_as::<_, SmartFolder>("SELECT * FROM smart_folders ORDER BY name")
+        .fetch_all(pool)
+        .await
+        .context("Failed to get smart folders")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+
+    #[tokio::test]
+    async fn test_smart_folders() {
+        let (db, _dir) = setup_db().await;
+        db.save_smart_folder("Rust", "rust is:unread")
+            .await
+            .unwrap();
+        db.save_smart_folder("AI", "llm OR gpt").await.unwrap();
+        // Same name in another case replaces the query
+        db.save_smart_folder(" rust ", "rust tag:later")
+            .await
+            .unwrap();
+
+        let folders = db.get_smart_folders().await.unwrap();
+        let names: Vec<_> = folders
+            .iter()
+            .map(|f| (f.name.as_str(), f.query.as_str()))
+            .collect();
+        assert_eq!(names, [("AI", "llm OR gpt"), ("Rust", "rust tag:later")]);
+
+        assert!(db.save_smart_folder("  ", "x").await.is_err());
+        assert!(db.delete_smart_folder("RUST").await.unwrap());
+        assert!(!db.delete_smart_folder("Rust").await.unwrap());
+        assert_eq!(db.get_smart_folders().await.unwrap().len(), 1);
+    }
+}
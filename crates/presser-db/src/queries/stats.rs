@@ -0,0 +1,166 @@
+//! Per-feed and time-series statistics
+
+use crate::models::{DailyCount, FeedStats};
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// Per-feed breakdown, busiest feeds first
+pub async fn get_feed_stats(pool: &SqlitePool) -> Result<Vec<FeedStats>> {
+    sqlx::query_as::<_, FeedStats>(
+        r#"
+        SELECT f.id AS feed_id,
+               f.title AS title,
+               COUNT(e.id) AS entries,
+               COUNT(e.id) FILTER (WHERE e.read = 0 AND e.hidden = 0) AS unread,
+               COUNT(e.id) FILTER (
+                   WHERE julianday(COALESCE(e.published, e.created_at)) >= julianday('now', '-28 days')
+               ) / 4.0 AS entries_per_week,
+               AVG(s.tokens) AS avg_summary_tokens,
+               COALESCE(fs.total_fetches, 0) AS fetches,
+               COALESCE(fs.total_failures, 0) AS failures,
+               COALESCE(fs.total_bytes, 0) AS bytes
+        FROM feeds f
+        LEFT JOIN entries e ON e.feed_id = f.id
+        LEFT JOIN summaries s ON s.entry_id = e.id AND s.is_current = 1
+        LEFT JOIN feed_fetch_state fs ON fs.feed_id = f.id
+        GROUP BY f.id
+        ORDER BY entries_per_week DESC, f.title COLLATE NOCASE
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get feed stats")
+}
+
+/// Unread (and not hidden) entries per feed ID; every feed is present
+pub async fn get_unread_counts(pool: &SqlitePool) -> Result<HashMap<String, i64>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT f.id, COUNT(e.id)
+        FROM feeds f
+        LEFT JOIN entries e ON e.feed_id = f.id AND e.read = 0 AND e.hidden = 0
+        GROUP BY f.id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get unread counts")?;
+    Ok(rows.into_iter().collect())
+}
+
+/// New entries and summaries per day over the last `days` days, oldest first
+///
+/// Days without activity are included with zero counts.
+pub async fn get_daily_counts(pool: &SqlitePool, days: u32) -> Result<Vec<DailyCount>> {
+    sqlx::query_as::<_, DailyCount>(
+        r#"
+        WITH RECURSIVE days(day) AS (
+            SELECT date('now', '-' || (?1 - 1) || ' days')
+            UNION ALL
+            SELECT date(day, '+1 day') FROM days WHERE day < date('now')
+        )
+        SELECT d.day AS day,
+               (SELECT COUNT(*) FROM entries e WHERE date(e.created_at) = d.day) AS entries,
+               (SELECT COUNT(*) FROM summaries s WHERE date(s.created_at) = d.day) AS summaries
+        FROM days d
+        ORDER BY d.day
+        "#,
+    )
+    .bind(days.max(1))
+    .fetch_all(pool)
+    .await
+    .context("Failed to get daily counts")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed, Summary};
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_feed_stats_and_daily_counts() {
+        let (db, _dir) = setup_db().await;
+        for id in ["busy", "quiet"] {
+            db.upsert_feed(&Feed {
+                id: id.into(),
+                url: format!("https://{}.com", id),
+                title: id.into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+        let now = Utc::now();
+        for i in 0..8 {
+            let entry = Entry {
+                id: format!("b{}", i),
+                feed_id: "busy".into(),
+                url: format!("https://busy.com/{}", i),
+                published: Some(now - Duration::days(i)),
+                created_at: now - Duration::days(i),
+                ..Default::default()
+            };
+            db.upsert_entry(&entry).await.unwrap();
+        }
+        db.mark_read("b0").await.unwrap();
+        for (id, tokens) in [("b0", 100), ("b1", 200)] {
+            let summary = Summary {
+                entry_id: id.into(),
+                tokens: Some(tokens),
+                ..Default::default()
+            };
+            db.upsert_summary(&summary).await.unwrap();
+        }
+        db.record_fetch_failure("busy", Some(500)).await.unwrap();
+        db.set_fetch_state(&crate::FetchState {
+            feed_id: "busy".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.add_feed_bytes("busy", 30_000).await.unwrap();
+        db.add_feed_bytes("busy", 2_000).await.unwrap();
+
+        let stats = db.get_feed_stats().await.unwrap();
+        let busy = &stats[0];
+        assert_eq!(busy.feed_id, "busy");
+        assert_eq!((busy.entries, busy.unread), (8, 7));
+        assert_eq!(busy.entries_per_week, 2.0);
+        assert_eq!(busy.avg_summary_tokens, Some(150.0));
+        assert_eq!(busy.failure_rate(), Some(0.5));
+        assert_eq!(busy.bytes, 32_000);
+        assert_eq!(stats[1].bytes, 0);
+        assert_eq!(stats[1].failure_rate(), None);
+        assert_eq!(stats[1].avg_summary_tokens, None);
+
+        let daily = db.get_daily_counts(7).await.unwrap();
+        assert_eq!(daily.len(), 7);
+        assert_eq!(
+            daily.last().unwrap().day,
+            now.format("%Y-%m-%d").to_string()
+        );
+        assert_eq!(daily.iter().map(|d| d.entries).sum::<i64>(), 7);
+        assert_eq!(daily.last().unwrap().summaries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unread_counts() {
+        let (db, _) = crate::fixtures::seeded().await.unwrap();
+        db.upsert_feed(&Feed {
+            id: "empty".into(),
+            url: "https://empty.com".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.hide_entry("news-weather").await.unwrap();
+
+        let counts = db.get_unread_counts().await.unwrap();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts["rust-blog"], 1);
+        assert_eq!(counts["news"], 1);
+        assert_eq!(counts["empty"], 0);
+    }
+}
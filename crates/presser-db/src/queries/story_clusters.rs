@@ -0,0 +1,312 @@
+//! Story clusters: near-duplicate entries grouped under one representative
+//!
+//! Grouping decisions come from the AI dedup service; this module only
+//! stores them. Hidden entries stay in their cluster but are not counted or
+//! listed. When the representative is removed or hidden, the oldest visible
+//! member stands in for it.
+
+use crate::models::{Entry, StoryCluster};
+use crate::DatabaseError;
+use anyhow::{bail, Context, Result};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// Member order: representative first, then the earliest report
+const MEMBER_ORDER: &str = "m.is_representative DESC, COALESCE(e.published, e.created_at), e.id";
+
+/// Group `representative_id` with its duplicates, returning the cluster ID
+///
+/// Clusters that any of the entries already belong to are merged into one,
+/// so repeated dedup passes converge instead of fragmenting.
+pub async fn merge_duplicates(
+    pool: &SqlitePool,
+    representative_id: &str,
+    duplicate_ids: &[String],
+) -> Result<i64> {
+    let mut ids = vec![representative_id];
+    for id in duplicate_ids {
+        if !ids.contains(&id.as_str()) {
+            ids.push(id);
+        }
+    }
+    if ids.len() < 2 {
+        bail!(
+            "A story cluster needs at least one duplicate of {}",
+            representative_id
+        );
+    }
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let mut existing = Vec::new();
+    for id in &ids {
+        let cluster: Option<i64> =
+            sqlx::query_scalar("SELECT cluster_id FROM story_cluster_members WHERE entry_id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await
+                .context("Failed to look up story cluster")?;
+        existing.extend(cluster);
+    }
+    existing.sort_unstable();
+    existing.dedup();
+
+    let target = match existing.first() {
+        Some(&id) => id,
+        None => sqlx::query("INSERT INTO story_clusters DEFAULT VALUES")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to create story cluster")?
+            .last_insert_rowid(),
+    };
+    for &other in existing.iter().skip(1) {
+        sqlx::query("UPDATE story_cluster_members SET cluster_id = ?1, is_representative = 0 WHERE cluster_id = ?2")
+            .bind(target)
+            .bind(other)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to merge story clusters")?;
+        sqlx::query("DELETE FROM story_clusters WHERE id = ?")
+            .bind(other)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete merged story cluster")?;
+    }
+
+    // Clear first: the partial unique index allows one representative per cluster
+    sqlx::query("UPDATE story_cluster_members SET is_representative = 0 WHERE cluster_id = ?")
+        .bind(target)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to reset story cluster representative")?;
+    for id in &ids {
+        sqlx::query(
+            r#"
+            INSERT INTO story_cluster_members (entry_id, cluster_id, is_representative)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(entry_id) DO UPDATE SET
+                cluster_id = excluded.cluster_id,
+                is_representative = excluded.is_representative
+            "#,
+        )
+        .bind(id)
+        .bind(target)
+        .bind(*id == representative_id)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to add {} to story cluster", id))?;
+    }
+
+    tx.commit()
+        .await
+        .context("Failed to commit story cluster")?;
+    Ok(target)
+}
+
+/// Take an entry out of its cluster (false if it was not clustered)
+///
+/// A cluster left with a single member is dissolved.
+pub async fn remove_from_cluster(pool: &SqlitePool, entry_id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM story_cluster_members WHERE entry_id = ?")
+        .bind(entry_id)
+        .execute(pool)
+        .await
+        .context("Failed to remove entry from story cluster")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the cluster an entry belongs to (None if unclustered or all hidden)
+pub async fn get_cluster_for_entry(
+    pool: &SqlitePool,
+    entry_id: &str,
+) -> Result<Option<StoryCluster>> {
+    let sql = format!(
+        r#"
+        WITH visible AS (
+            SELECT m.cluster_id, e.id, e.feed_id,
+                   ROW_NUMBER() OVER (ORDER BY {}) AS rank
+            FROM story_cluster_members m
+            JOIN entries e ON e.id = m.entry_id
+            WHERE e.hidden = 0
+              AND m.cluster_id = (SELECT cluster_id FROM story_cluster_members WHERE entry_id = ?)
+        )
+        SELECT cluster_id AS id,
+               MAX(CASE WHEN rank = 1 THEN id END) AS representative_id,
+               COUNT(*) AS members,
+               COUNT(DISTINCT feed_id) AS sources
+        FROM visible
+        GROUP BY cluster_id
+        "#,
+        MEMBER_ORDER
+    );
+    sqlx::query_as::<_, StoryCluster>(&sql)
+        .bind(entry_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get story cluster")
+}
+
+/// Get the entry representing a cluster
+pub async fn get_cluster_representative(pool: &SqlitePool, cluster_id: i64) -> Result<Entry> {
+    get_cluster_members_limited(pool, cluster_id, 1)
+        .await?
+        .pop()
+        .ok_or_else(|| DatabaseError::not_found("story cluster", cluster_id))
+}
+
+/// Get the visible entries of a cluster, representative first
+pub async fn get_cluster_members(pool: &SqlitePool, cluster_id: i64) -> Result<Vec<Entry>> {
+    get_cluster_members_limited(pool, cluster_id, -1).await
+}
+
+async fn get_cluster_members_limited(
+    pool: &SqlitePool,
+    cluster_id: i64,
+    limit: i64,
+) -> Result<Vec<Entry>> {
+    let sql = format!(
+        r#"
+        SELECT e.* FROM story_cluster_members m
+        JOIN entries e ON e.id = m.entry_id
+        WHERE m.cluster_id = ? AND e.hidden = 0
+        ORDER BY {}
+        LIMIT ?
+        "#,
+        MEMBER_ORDER
+    );
+    sqlx::query_as::<_, Entry>(&sql)
+        .bind(cluster_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .context("Failed to get story cluster members")
+}
+
+/// Titles of the other feeds whose entries (hidden ones too) share a
+/// cluster with each of the given entries, by entry ID
+pub async fn get_other_sources(
+    pool: &SqlitePool,
+    entry_ids: &[String],
+) -> Result<HashMap<String, Vec<String>>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT m.entry_id, f.title
+        FROM story_cluster_members m
+        JOIN entries own ON own.id = m.entry_id
+        JOIN story_cluster_members o ON o.cluster_id = m.cluster_id AND o.entry_id != m.entry_id
+        JOIN entries e ON e.id = o.entry_id
+        JOIN feeds f ON f.id = e.feed_id
+        WHERE m.entry_id IN (SELECT value FROM json_each(?)) AND e.feed_id != own.feed_id
+        ORDER BY m.entry_id, f.title
+        "#,
+    )
+    .bind(serde_json::to_string(entry_ids)?)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get other sources of entries")?;
+    let mut sources: HashMap<String, Vec<String>> = HashMap::new();
+    for (entry_id, title) in rows {
+        sources.entry(entry_id).or_default().push(title);
+    }
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::seeded;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_story_clusters() {
+        let (db, _) = seeded().await.unwrap();
+        let a = db
+            .merge_duplicates("rust-release", &ids(&["news-election"]))
+            .await
+            .unwrap();
+        let b = db
+            .merge_duplicates("rust-async", &ids(&["news-weather"]))
+            .await
+            .unwrap();
+        assert_ne!(a, b);
+
+        // Overlapping groups collapse into the older cluster
+        let merged = db
+            .merge_duplicates("news-weather", &ids(&["rust-release", "news-weather"]))
+            .await
+            .unwrap();
+        assert_eq!(merged, a);
+        let cluster = db
+            .get_cluster_for_entry("rust-async")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cluster.id, a);
+        assert_eq!(cluster.representative_id, "news-weather");
+        assert_eq!((cluster.members, cluster.sources), (4, 2));
+        let sources = db
+            .get_other_sources(&ids(&["rust-async", "news-weather", "rust-old"]))
+            .await
+            .unwrap();
+        assert_eq!(sources["rust-async"], ["Example News"]);
+        assert_eq!(sources["news-weather"], ["Rust Blog"]);
+        assert!(!sources.contains_key("rust-old"));
+        let members = db.get_cluster_members(a).await.unwrap();
+        let order: Vec<_> = members.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(
+            order,
+            [
+                "news-weather",
+                "rust-release",
+                "rust-async",
+                "news-election"
+            ]
+        );
+
+        // Without its representative the earliest report stands in
+        assert!(db.remove_from_cluster("news-weather").await.unwrap());
+        assert!(!db.remove_from_cluster("news-weather").await.unwrap());
+        assert_eq!(
+            db.get_cluster_representative(a).await.unwrap().id,
+            "rust-release"
+        );
+        db.hide_entry("rust-release").await.unwrap();
+        assert_eq!(
+            db.get_cluster_representative(a).await.unwrap().id,
+            "rust-async"
+        );
+        assert_eq!(
+            db.get_cluster_for_entry("news-election")
+                .await
+                .unwrap()
+                .unwrap()
+                .members,
+            2
+        );
+
+        // Dissolved once a single member is left
+        db.remove_from_cluster("rust-async").await.unwrap();
+        db.remove_from_cluster("news-election").await.unwrap();
+        assert!(db
+            .get_cluster_for_entry("rust-release")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(db
+            .get_cluster_representative(a)
+            .await
+            .unwrap_err()
+            .is_not_found());
+
+        assert!(db
+            .merge_duplicates("rust-old", &ids(&["rust-old"]))
+            .await
+            .is_err());
+        let err = db
+            .merge_duplicates("rust-old", &ids(&["missing"]))
+            .await
+            .unwrap_err();
+        assert!(err.is_not_found(), "{err}");
+    }
+}
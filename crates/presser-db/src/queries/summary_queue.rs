@@ -0,0 +1,251 @@
+//! Summary queue and daily AI usage
+//!
+//! New entries are queued before they are summarized and removed once a
+//! summary is stored (or they have failed too often), so entries deferred by
+//! the token budget or interrupted by a crash are summarized on a later run.
+//! A summary, its removal from the queue, and the tokens it took are written
+//! together, so a crash can't leave tokens charged for a summary that is
+//! then requested again.
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use sqlx::{Sqlite, SqlitePool};
+
+use crate::models::{AiUsage, QueuedSummary, Summary};
+
+/// Queue entries for summarization; entries already queued keep their place
+pub async fn enqueue_summaries(pool: &SqlitePool, entry_ids: &[String]) -> Result<()> {
+    if entry_ids.is_empty() {
+        return Ok(());
+    }
+    let now = Utc::now();
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    for entry_id in entry_ids {
+        sqlx::query("INSERT OR IGNORE INTO summary_queue (entry_id, enqueued_at) VALUES (?, ?)")
+            .bind(entry_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to queue summary")?;
+    }
+    tx.commit().await.context("Failed to commit transaction")?;
+    Ok(())
+}
+
+/// Oldest queued entries first
+pub async fn queued_summaries(pool: &SqlitePool, limit: i64) -> Result<Vec<QueuedSummary>> {
+    sqlx::query_as::<_, QueuedSummary>(
+        r#"
+        SELECT entry_id, enqueued_at, attempts, last_error
+        FROM summary_queue
+        ORDER BY enqueued_at, entry_id
+        LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get queued summaries")
+}
+
+/// Queued entries of one feed, oldest first
+pub async fn feed_queued_summaries(pool: &SqlitePool, feed_id: &str) -> Result<Vec<QueuedSummary>> {
+    sqlx::query_as::<_, QueuedSummary>(
+        r#"
+        SELECT q.entry_id, q.enqueued_at, q.attempts, q.last_error
+        FROM summary_queue q
+        JOIN entries e ON e.id = q.entry_id
+        WHERE e.feed_id = ?
+        ORDER BY q.enqueued_at, q.entry_id
+        "#,
+    )
+    .bind(feed_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get queued summaries of feed")
+}
+
+/// Entries waiting in the queue
+pub async fn summary_queue_len(pool: &SqlitePool) -> Result<i64> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM summary_queue")
+        .fetch_one(pool)
+        .await
+        .context("Failed to count queued summaries")
+}
+
+/// Take entries off the queue (no-op for entries that are not queued)
+pub async fn dequeue_summaries(pool: &SqlitePool, entry_ids: &[String]) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    for entry_id in entry_ids {
+        sqlx::query("DELETE FROM summary_queue WHERE entry_id = ?")
+            .bind(entry_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to dequeue summary")?;
+    }
+    tx.commit().await.context("Failed to commit transaction")?;
+    Ok(())
+}
+
+/// Record a failed attempt on a queued entry, returning its attempt count (0 if not queued)
+pub async fn record_summary_failure(pool: &SqlitePool, entry_id: &str, error: &str) -> Result<i64> {
+    let attempts = sqlx::query_scalar(
+        r#"
+        UPDATE summary_queue SET attempts = attempts + 1, last_error = ?
+        WHERE entry_id = ?
+        RETURNING attempts
+        "#,
+    )
+    .bind(error)
+    .bind(entry_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to record summary failure")?;
+    Ok(attempts.unwrap_or(0))
+}
+
+/// Store a new summary as its entry's current one and take the entry off
+/// the queue, adding `usage` (day and tokens) to the AI usage unless it came
+/// from the cache, all in one transaction
+pub async fn complete_summary(
+    pool: &SqlitePool,
+    summary: &Summary,
+    usage: Option<(NaiveDate, i64)>,
+) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    crate::queries::store_summary(&mut tx, summary).await?;
+    sqlx::query("DELETE FROM summary_queue WHERE entry_id = ?")
+        .bind(&summary.entry_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to dequeue summary")?;
+    if let Some((day, tokens)) = usage {
+        add_usage(&mut *tx, day, tokens).await?;
+    }
+    tx.commit().await.context("Failed to commit summary")?;
+    Ok(())
+}
+
+/// Add one request and `tokens` to the usage of `day`
+pub async fn record_ai_usage(pool: &SqlitePool, day: NaiveDate, tokens: i64) -> Result<()> {
+    add_usage(pool, day, tokens).await
+}
+
+async fn add_usage<'e>(
+    executor: impl sqlx::Executor<'e, Database = Sqlite>,
+    day: NaiveDate,
+    tokens: i64,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO ai_usage (day, requests, tokens) VALUES (?1, 1, ?2)
+        ON CONFLICT(day) DO UPDATE SET
+            requests = requests + 1,
+            tokens = tokens + excluded.tokens
+        "#,
+    )
+    .bind(day.to_string())
+    .bind(tokens)
+    .execute(executor)
+    .await
+    .context("Failed to record AI usage")?;
+    Ok(())
+}
+
+/// Usage of `day` (zero if nothing was recorded)
+pub async fn get_ai_usage(pool: &SqlitePool, day: NaiveDate) -> Result<AiUsage> {
+    let usage = sqlx::query_as::<_, AiUsage>("SELECT requests, tokens FROM ai_usage WHERE day = ?")
+        .bind(day.to_string())
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get AI usage")?;
+    Ok(usage.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures;
+    use crate::tests::setup_db;
+    use chrono::NaiveDate;
+
+    #[tokio::test]
+    async fn test_summary_queue() {
+        let (db, _dir) = setup_db().await;
+        fixtures::seed(&db).await.unwrap();
+        let ids = |queued: Vec<crate::QueuedSummary>| -> Vec<String> {
+            queued.into_iter().map(|q| q.entry_id).collect()
+        };
+
+        db.enqueue_summaries(&["news-weather".into(), "rust-old".into()])
+            .await
+            .unwrap();
+        db.enqueue_summaries(&["news-weather".into()])
+            .await
+            .unwrap();
+        assert_eq!(db.summary_queue_len().await.unwrap(), 2);
+        assert_eq!(ids(db.queued_summaries(1).await.unwrap()), ["news-weather"]);
+
+        assert_eq!(
+            db.record_summary_failure("rust-old", "timeout")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.record_summary_failure("missing", "timeout")
+                .await
+                .unwrap(),
+            0
+        );
+        let queued = db.queued_summaries(10).await.unwrap();
+        assert_eq!(queued[1].last_error.as_deref(), Some("timeout"));
+
+        db.dequeue_summaries(&["news-weather".into(), "missing".into()])
+            .await
+            .unwrap();
+        assert_eq!(ids(db.queued_summaries(10).await.unwrap()), ["rust-old"]);
+
+        // Deleting the entry (here with its feed) takes it off the queue
+        db.delete_feed("rust-blog").await.unwrap();
+        assert_eq!(db.summary_queue_len().await.unwrap(), 0);
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        db.record_ai_usage(day, 120).await.unwrap();
+        db.record_ai_usage(day, 30).await.unwrap();
+        let usage = db.get_ai_usage(day).await.unwrap();
+        assert_eq!((usage.requests, usage.tokens), (2, 150));
+
+        // A completed summary leaves the queue and is charged once
+        db.enqueue_summaries(&["news-weather".into()])
+            .await
+            .unwrap();
+        let summary = crate::Summary {
+            entry_id: "news-weather".into(),
+            summary_text: "Rain".into(),
+            model: "gpt-4".into(),
+            tokens: Some(50),
+            content_hash: "abc".into(),
+            ..Default::default()
+        };
+        db.complete_summary(&summary, Some((day, 50)))
+            .await
+            .unwrap();
+        assert_eq!(db.summary_queue_len().await.unwrap(), 0);
+        assert_eq!(db.get_ai_usage(day).await.unwrap().tokens, 200);
+        assert_eq!(
+            db.get_summary("news-weather")
+                .await
+                .unwrap()
+                .unwrap()
+                .summary_text,
+            "Rain"
+        );
+        assert_eq!(
+            db.get_ai_usage(day.succ_opt().unwrap())
+                .await
+                .unwrap()
+                .tokens,
+            0
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Summary history: every stored version per entry, one marked current
+
+use crate::models::Summary;
+use crate::DatabaseError;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Get every summary version of an entry, newest first
+pub async fn get_summary_versions(pool: &SqlitePool, entry_id: &str) -> Result<Vec<Summary>> {
+    sqlx::query_as::<_, Summary>(
+        "SELECT * FROM summaries WHERE entry_id = ? ORDER BY created_at DESC, id DESC",
+    )
+    .bind(entry_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get summary versions")
+}
+
+/// Get one summary version by ID (e.g. to compare two versions)
+pub async fn get_summary_version(pool: &SqlitePool, id: i64) -> Result<Option<Summary>> {
+    sqlx::query_as::<_, Summary>("SELECT * FROM summaries WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get summary version")
+}
+
+/// Make a stored version the entry's current summary
+pub async fn set_current_summary(pool: &SqlitePool, id: i64) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    let entry_id: Option<String> =
+        sqlx::query_scalar("SELECT entry_id FROM summaries WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to look up summary version")?;
+    let Some(entry_id) = entry_id else {
+        return Err(DatabaseError::not_found("summary version", id));
+    };
+
+    sqlx::query(
+        "UPDATE summaries SET is_current = 0 WHERE entry_id = ? AND is_current = 1 AND id != ?",
+    )
+    .bind(&entry_id)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to demote current summary")?;
+    sqlx::query("UPDATE summaries SET is_current = 1 WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to promote summary")?;
+    tx.commit()
+        .await
+        .context("Failed to commit summary promotion")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed, Summary};
+
+    #[tokio::test]
+    async fn test_summary_versions() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f".into(),
+            url: "https://f.com".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.upsert_entry(&Entry {
+            id: "e".into(),
+            feed_id: "f".into(),
+            url: "https://f.com/e".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let version = |text: &str, model: &str, style: &str| Summary {
+            entry_id: "e".into(),
+            summary_text: text.into(),
+            model: model.into(),
+            style: style.into(),
+            content_hash: "h".into(),
+            ..Default::default()
+        };
+        db.upsert_summary(&version("brief", "small", ""))
+            .await
+            .unwrap();
+        db.upsert_summary(&version("detailed", "large", "bullets"))
+            .await
+            .unwrap();
+        // Storing an identical version again only promotes it
+        db.upsert_summary(&version("brief", "small", ""))
+            .await
+            .unwrap();
+
+        let versions = db.get_summary_versions("e").await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions.iter().filter(|s| s.is_current).count(), 1);
+        assert_eq!(
+            db.get_summary("e").await.unwrap().unwrap().summary_text,
+            "brief"
+        );
+
+        let detailed = versions.iter().find(|s| s.model == "large").unwrap();
+        db.set_current_summary(detailed.id).await.unwrap();
+        assert_eq!(db.get_summary("e").await.unwrap().unwrap().style, "bullets");
+        assert_eq!(db.search_entries("detailed", 10).await.unwrap().len(), 1);
+        assert!(db.search_entries("brief", 10).await.unwrap().is_empty());
+        assert_eq!(db.get_stats().await.unwrap().total_summaries, 1);
+
+        assert!(db.set_current_summary(9999).await.is_err());
+        assert!(
+            db.get_summary_version(detailed.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .is_current
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Tag queries
+//!
+//! Tag names are case-insensitive; the first spelling seen is kept.
+
+use crate::models::{Entry, TagCount, TagSource};
+use crate::queries::filter::{query_entries, EntryFilter};
+use crate::DatabaseError;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// Attach a tag to an entry, creating the tag if needed
+pub async fn add_tag(
+    pool: &SqlitePool,
+    entry_id: &str,
+    name: &str,
+    source: TagSource,
+) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DatabaseError::invalid("Tag name cannot be empty"));
+    }
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to create tag")?;
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO entry_tags (entry_id, tag_id, source)
+        SELECT ?1, id, ?2 FROM tags WHERE name = ?3
+        "#,
+    )
+    .bind(entry_id)
+    .bind(source)
+    .bind(name)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to add tag")?;
+    tx.commit().await.context("Failed to commit tag")?;
+    Ok(())
+}
+
+/// Detach a tag from an entry (the tag itself is kept)
+pub async fn remove_tag(pool: &SqlitePool, entry_id: &str, name: &str) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM entry_tags WHERE entry_id = ? AND tag_id = (SELECT id FROM tags WHERE name = ?)",
+    )
+    .bind(entry_id)
+    .bind(name.trim())
+    .execute(pool)
+    .await
+    .context("Failed to remove tag")?;
+    Ok(())
+}
+
+/// Replace the feed-provided tags of an entry, leaving user and AI tags alone
+pub async fn set_feed_tags(pool: &SqlitePool, entry_id: &str, categories: &[String]) -> Result<()> {
+    sqlx::query("DELETE FROM entry_tags WHERE entry_id = ? AND source = ?")
+        .bind(entry_id)
+        .bind(TagSource::Feed)
+        .execute(pool)
+        .await
+        .context("Failed to clear feed tags")?;
+    for category in categories.iter().filter(|c| !c.trim().is_empty()) {
+        add_tag(pool, entry_id, category, TagSource::Feed).await?;
+    }
+    Ok(())
+}
+
+/// Get tag names for an entry, alphabetically
+pub async fn get_tags_for_entry(pool: &SqlitePool, entry_id: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar(
+        r#"
+        SELECT t.name FROM tags t
+        JOIN entry_tags et ON et.tag_id = t.id
+        WHERE et.entry_id = ?
+        ORDER BY t.name
+        "#,
+    )
+    .bind(entry_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get tags for entry")
+}
+
+/// Get tag names of many entries, keyed by entry ID (untagged entries are absent)
+pub async fn get_tags_for_entries(
+    pool: &SqlitePool,
+    entry_ids: &[String],
+) -> Result<HashMap<String, Vec<String>>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT et.entry_id, t.name FROM tags t
+        JOIN entry_tags et ON et.tag_id = t.id
+        WHERE et.entry_id IN (SELECT value FROM json_each(?))
+        ORDER BY t.name
+        "#,
+    )
+    .bind(serde_json::to_string(entry_ids)?)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get tags for entries")?;
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (entry_id, name) in rows {
+        tags.entry(entry_id).or_default().push(name);
+    }
+    Ok(tags)
+}
+
+/// Get entries carrying a tag, ordered by published date descending
+pub async fn get_entries_by_tag(pool: &SqlitePool, name: &str, limit: i64) -> Result<Vec<Entry>> {
+    query_entries(pool, &EntryFilter::new().tag(name).limit(limit)).await
+}
+
+/// Get all tags in use with their entry and unread counts, most used first
+pub async fn get_all_tags_with_counts(pool: &SqlitePool) -> Result<Vec<TagCount>> {
+    sqlx::query_as::<_, TagCount>(
+        r#"
+        SELECT t.name,
+               COUNT(et.entry_id) AS count,
+               COUNT(e.id) FILTER (WHERE e.read = 0 AND e.hidden = 0) AS unread
+        FROM tags t
+        JOIN entry_tags et ON et.tag_id = t.id
+        JOIN entries e ON e.id = et.entry_id
+        GROUP BY t.id
+        ORDER BY count DESC, t.name
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get tag counts")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed, TagSource};
+
+    #[tokio::test]
+    async fn test_tags() {
+        let (db, _dir) = setup_db().await;
+        db.upsert_feed(&Feed {
+            id: "f1".into(),
+            url: "https://ex.com/f".into(),
+            title: "F".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        for id in ["e1", "e2"] {
+            db.upsert_entry(&Entry {
+                id: id.into(),
+                feed_id: "f1".into(),
+                title: id.into(),
+                url: format!("https://ex.com/{}", id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+
+        db.set_feed_tags("e1", &["Rust".into(), "Async".into()])
+            .await
+            .unwrap();
+        db.set_feed_tags("e2", &["rust".into()]).await.unwrap();
+        db.add_tag("e1", "to-read", TagSource::User).await.unwrap();
+        db.mark_read("e2").await.unwrap();
+
+        let counts = db.get_all_tags_with_counts().await.unwrap();
+        assert_eq!(counts[0].name, "Rust");
+        assert_eq!(counts[0].count, 2);
+        assert_eq!(counts[0].unread, 1);
+        assert_eq!(db.get_entries_by_tag("RUST", 10).await.unwrap().len(), 2);
+
+        // Refreshing feed categories keeps user tags
+        db.set_feed_tags("e1", &["Rust".into()]).await.unwrap();
+        assert_eq!(
+            db.get_tags_for_entry("e1").await.unwrap(),
+            vec!["Rust", "to-read"]
+        );
+
+        db.remove_tag("e1", "to-read").await.unwrap();
+        assert_eq!(db.get_tags_for_entry("e1").await.unwrap(), vec!["Rust"]);
+    }
+}
@@ -0,0 +1,87 @@
+//! Access tokens obtained for feeds behind an API
+
+use crate::models::FeedToken;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Store a feed's token, replacing the one it had
+pub async fn set_feed_token(pool: &SqlitePool, token: &FeedToken) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO feed_tokens (feed_id, access_token, refresh_token, expires_at, refreshed_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(feed_id) DO UPDATE SET
+            access_token = excluded.access_token,
+            refresh_token = excluded.refresh_token,
+            expires_at = excluded.expires_at,
+            refreshed_at = excluded.refreshed_at
+        "#,
+    )
+    .bind(&token.feed_id)
+    .bind(&token.access_token)
+    .bind(&token.refresh_token)
+    .bind(token.expires_at)
+    .bind(token.refreshed_at)
+    .execute(pool)
+    .await
+    .context("Failed to store feed token")?;
+    Ok(())
+}
+
+/// Get a feed's token
+pub async fn get_feed_token(pool: &SqlitePool, feed_id: &str) -> Result<Option<FeedToken>> {
+    sqlx::query_as("SELECT * FROM feed_tokens WHERE feed_id = ?")
+        .bind(feed_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get feed token")
+}
+
+/// Forget a feed's token
+pub async fn delete_feed_token(pool: &SqlitePool, feed_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM feed_tokens WHERE feed_id = ?")
+        .bind(feed_id)
+        .execute(pool)
+        .await
+        .context("Failed to delete feed token")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::seeded;
+    use crate::FeedToken;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_feed_tokens() {
+        let (db, _) = seeded().await.unwrap();
+        assert!(db.get_feed_token("news").await.unwrap().is_none());
+
+        let now = Utc::now();
+        let mut token = FeedToken {
+            feed_id: "news".into(),
+            access_token: "first".into(),
+            refresh_token: Some("r1".into()),
+            expires_at: Some(now + Duration::hours(1)),
+            refreshed_at: now,
+        };
+        db.set_feed_token(&token).await.unwrap();
+        // Rotated on refresh
+        token.access_token = "second".into();
+        token.refresh_token = Some("r2".into());
+        db.set_feed_token(&token).await.unwrap();
+        assert_eq!(
+            db.get_feed_token("news").await.unwrap(),
+            Some(token.clone())
+        );
+
+        db.delete_feed_token("news").await.unwrap();
+        assert!(db.get_feed_token("news").await.unwrap().is_none());
+
+        // Tokens go with their feeds
+        db.set_feed_token(&token).await.unwrap();
+        db.delete_feed("news").await.unwrap();
+        assert!(db.get_feed_token("news").await.unwrap().is_none());
+    }
+}
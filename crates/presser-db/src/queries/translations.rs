@@ -0,0 +1,77 @@
+//! Translations of entries' text
+
+use crate::models::Translation;
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+
+/// Store an entry's translation, replacing an earlier one
+pub async fn set_translation(pool: &SqlitePool, translation: &Translation) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO entry_translations (entry_id, language, source_language, content_text,
+                                        model, tokens, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(entry_id) DO UPDATE SET
+            language = excluded.language,
+            source_language = excluded.source_language,
+            content_text = excluded.content_text,
+            model = excluded.model,
+            tokens = excluded.tokens,
+            created_at = excluded.created_at
+        "#,
+    )
+    .bind(&translation.entry_id)
+    .bind(&translation.language)
+    .bind(&translation.source_language)
+    .bind(&translation.content_text)
+    .bind(&translation.model)
+    .bind(translation.tokens)
+    .bind(translation.created_at)
+    .execute(pool)
+    .await
+    .context("Failed to store translation")?;
+    Ok(())
+}
+
+/// Get an entry's translation
+pub async fn get_translation(pool: &SqlitePool, entry_id: &str) -> Result<Option<Translation>> {
+    sqlx::query_as("SELECT * FROM entry_translations WHERE entry_id = ?")
+        .bind(entry_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get translation")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::seeded;
+    use crate::Translation;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn test_translations() {
+        let (db, _) = seeded().await.unwrap();
+        assert!(db.get_translation("news-weather").await.unwrap().is_none());
+
+        let mut translation = Translation {
+            entry_id: "news-weather".into(),
+            language: "en".into(),
+            source_language: "de".into(),
+            content_text: "Rain tomorrow".into(),
+            model: "gpt-4".into(),
+            tokens: Some(12),
+            created_at: Utc::now(),
+        };
+        db.set_translation(&translation).await.unwrap();
+        translation.content_text = "Rain tomorrow, sun on Sunday".into();
+        db.set_translation(&translation).await.unwrap();
+        assert_eq!(
+            db.get_translation("news-weather").await.unwrap(),
+            Some(translation)
+        );
+
+        // Translations go with their entries
+        db.delete_feed("news").await.unwrap();
+        assert!(db.get_translation("news-weather").await.unwrap().is_none());
+    }
+}
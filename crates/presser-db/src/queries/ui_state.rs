@@ -0,0 +1,153 @@
+//! TUI state kept across restarts
+//!
+//! Named values hold what was open when the TUI quit; reading positions
+//! hold how far each entry was scrolled, so it opens where it was left.
+
+use crate::models::ReadingPosition;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+/// Set a named value, or remove it with `None`
+pub async fn set_ui_state(pool: &SqlitePool, key: &str, value: Option<&str>) -> Result<()> {
+    match value {
+        Some(value) => sqlx::query(
+            "INSERT INTO ui_state (key, value, updated_at) VALUES (?, ?, ?) \
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        )
+        .bind(key)
+        .bind(value)
+        .bind(Utc::now())
+        .execute(pool)
+        .await,
+        None => sqlx::query("DELETE FROM ui_state WHERE key = ?")
+            .bind(key)
+            .execute(pool)
+            .await,
+    }
+    .context("Failed to save TUI state")?;
+    Ok(())
+}
+
+/// Get a named value
+pub async fn get_ui_state(pool: &SqlitePool, key: &str) -> Result<Option<String>> {
+    sqlx::query_scalar("SELECT value FROM ui_state WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get TUI state")
+}
+
+/// Remember how far an entry is scrolled, out of `max_scroll`
+pub async fn save_reading_position(
+    pool: &SqlitePool,
+    entry_id: &str,
+    scroll: i64,
+    max_scroll: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO reading_positions (entry_id, scroll, max_scroll, updated_at) \
+         VALUES (?, ?, ?, ?) \
+         ON CONFLICT(entry_id) DO UPDATE SET scroll = excluded.scroll, \
+         max_scroll = excluded.max_scroll, updated_at = excluded.updated_at",
+    )
+    .bind(entry_id)
+    .bind(scroll)
+    .bind(max_scroll)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .context("Failed to save reading position")?;
+    Ok(())
+}
+
+/// Get how far an entry was scrolled
+pub async fn get_reading_position(
+    pool: &SqlitePool,
+    entry_id: &str,
+) -> Result<Option<ReadingPosition>> {
+    sqlx::query_as::<_, ReadingPosition>("SELECT * FROM reading_positions WHERE entry_id = ?")
+        .bind(entry_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get reading position")
+}
+
+/// The entry most recently left partly read (scrolled, but not to the end),
+/// skipping hidden entries
+pub async fn get_last_partial_position(pool: &SqlitePool) -> Result<Option<ReadingPosition>> {
+    sqlx::query_as::<_, ReadingPosition>(
+        "SELECT p.* FROM reading_positions p JOIN entries e ON e.id = p.entry_id \
+         WHERE p.scroll > 0 AND p.scroll < p.max_scroll AND e.hidden = 0 \
+         ORDER BY p.updated_at DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get the last reading position")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_db;
+    use crate::{Entry, Feed};
+
+    #[tokio::test]
+    async fn test_ui_state() {
+        let (db, _dir) = setup_db().await;
+        db.set_ui_state("source", Some("feed:blog")).await.unwrap();
+        db.set_ui_state("source", Some("tag:rust")).await.unwrap();
+        assert_eq!(
+            db.get_ui_state("source").await.unwrap().as_deref(),
+            Some("tag:rust")
+        );
+        db.set_ui_state("source", None).await.unwrap();
+        assert_eq!(db.get_ui_state("source").await.unwrap(), None);
+
+        db.upsert_feed(&Feed {
+            id: "blog".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        for id in ["a", "b", "c"] {
+            db.upsert_entry(&Entry {
+                id: id.into(),
+                feed_id: "blog".into(),
+                url: format!("https://example.com/{}", id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+        db.save_reading_position("a", 10, 40).await.unwrap();
+        db.save_reading_position("b", 12, 30).await.unwrap();
+        // Read to the end, so not partly read
+        db.save_reading_position("c", 25, 25).await.unwrap();
+        assert_eq!(
+            db.get_last_partial_position()
+                .await
+                .unwrap()
+                .unwrap()
+                .entry_id,
+            "b"
+        );
+        db.save_reading_position("a", 11, 40).await.unwrap();
+        let last = db.get_last_partial_position().await.unwrap().unwrap();
+        assert_eq!((last.entry_id.as_str(), last.scroll), ("a", 11));
+        assert_eq!(
+            db.get_reading_position("c").await.unwrap().unwrap().scroll,
+            25
+        );
+
+        db.hide_entry("a").await.unwrap();
+        assert_eq!(
+            db.get_last_partial_position()
+                .await
+                .unwrap()
+                .unwrap()
+                .entry_id,
+            "b"
+        );
+        assert!(db.get_reading_position("x").await.unwrap().is_none());
+    }
+}
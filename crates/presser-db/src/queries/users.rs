@@ -0,0 +1,318 @@
+//! API users and their own read, starred, and tag state
+//!
+//! Feeds and entries are shared. A user's state lives in `user_entry_state`
+//! and `user_tags`; entries they never touched are unread and unstarred.
+//! Feed and AI tags are shared too, while manual tags are per user.
+
+use std::collections::HashMap;
+
+use crate::models::{Entry, TagSource, User};
+use crate::DatabaseError;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+/// Entry columns with the read and starred state of user `?1`
+const USER_ENTRY_COLUMNS: &str = r#"
+    e.id, e.feed_id, e.title, e.url, e.author, e.published, e.updated, e.summary,
+    e.content_html, e.content_text, e.categories, e.score, e.hidden, e.created_at, e.updated_at,
+    COALESCE(s.read, 0) AS read, COALESCE(s.starred, 0) AS starred, s.starred_at
+"#;
+
+/// Which of a user's entries to list
+#[derive(Debug, Clone, Default)]
+pub struct UserEntryFilter {
+    /// Only entries the user hasn't read
+    pub unread: bool,
+
+    /// Only entries the user starred
+    pub starred: bool,
+
+    /// Only entries of this feed
+    pub feed_id: Option<String>,
+
+    pub limit: i64,
+}
+
+/// Create a user identified by the hash of their token
+pub async fn create_user(pool: &SqlitePool, name: &str, token_hash: &str) -> Result<User> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DatabaseError::invalid("User name cannot be empty"));
+    }
+    sqlx::query_as("INSERT INTO users (name, token_hash, created_at) VALUES (?, ?, ?) RETURNING id, name, created_at")
+        .bind(name)
+        .bind(token_hash)
+        .bind(Utc::now())
+        .fetch_one(pool)
+        .await
+        .with_context(|| format!("Failed to create user {}", name))
+}
+
+/// Get all users, by name
+pub async fn list_users(pool: &SqlitePool) -> Result<Vec<User>> {
+    sqlx::query_as("SELECT id, name, created_at FROM users ORDER BY name")
+        .fetch_all(pool)
+        .await
+        .context("Failed to list users")
+}
+
+/// Delete a user and all of their state
+pub async fn delete_user(pool: &SqlitePool, name: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM users WHERE name = ?")
+        .bind(name.trim())
+        .execute(pool)
+        .await
+        .context("Failed to delete user")?;
+    crate::queries::expect_row(result, "user", name)
+}
+
+/// Get the user with a token hash
+pub async fn get_user_by_token(pool: &SqlitePool, token_hash: &str) -> Result<Option<User>> {
+    sqlx::query_as("SELECT id, name, created_at FROM users WHERE token_hash = ?")
+        .bind(token_hash)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get user")
+}
+
+/// Get visible entries with a user's state, newest first
+pub async fn get_user_entries(
+    pool: &SqlitePool,
+    user_id: i64,
+    filter: &UserEntryFilter,
+) -> Result<Vec<Entry>> {
+    let sql = format!(
+        r#"
+        SELECT {} FROM entries e
+        LEFT JOIN user_entry_state s ON s.entry_id = e.id AND s.user_id = ?1
+        WHERE e.hidden = 0
+          AND (?2 = 0 OR COALESCE(s.read, 0) = 0)
+          AND (?3 = 0 OR COALESCE(s.starred, 0) = 1)
+          AND (?4 IS NULL OR e.feed_id = ?4)
+        ORDER BY COALESCE(e.published, e.created_at) DESC
+        LIMIT ?5
+        "#,
+        USER_ENTRY_COLUMNS
+    );
+    sqlx::query_as(&sql)
+        .bind(user_id)
+        .bind(filter.unread)
+        .bind(filter.starred)
+        .bind(&filter.feed_id)
+        .bind(filter.limit)
+        .fetch_all(pool)
+        .await
+        .context("Failed to get user entries")
+}
+
+/// Get one entry with a user's state
+pub async fn get_user_entry(pool: &SqlitePool, user_id: i64, entry_id: &str) -> Result<Entry> {
+    let sql = format!(
+        r#"
+        SELECT {} FROM entries e
+        LEFT JOIN user_entry_state s ON s.entry_id = e.id AND s.user_id = ?1
+        WHERE e.id = ?2
+        "#,
+        USER_ENTRY_COLUMNS
+    );
+    sqlx::query_as(&sql)
+        .bind(user_id)
+        .bind(entry_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to get user entry")?
+        .ok_or_else(|| DatabaseError::not_found("entry", entry_id))
+}
+
+/// Set a user's read and starred state of an entry; None keeps the current value
+pub async fn set_user_state(
+    pool: &SqlitePool,
+    user_id: i64,
+    entry_id: &str,
+    read: Option<bool>,
+    starred: Option<bool>,
+) -> Result<()> {
+    let now = Utc::now();
+    sqlx::query(
+        r#"
+        INSERT INTO user_entry_state (user_id, entry_id, read, starred, starred_at, updated_at)
+        VALUES (?1, ?2, COALESCE(?3, 0), COALESCE(?4, 0), CASE WHEN ?4 THEN ?5 END, ?5)
+        ON CONFLICT(user_id, entry_id) DO UPDATE SET
+            read = COALESCE(?3, read),
+            starred_at = CASE
+                WHEN ?4 IS NULL THEN starred_at
+                WHEN ?4 AND starred THEN starred_at
+                WHEN ?4 THEN ?5
+            END,
+            starred = COALESCE(?4, starred),
+            updated_at = ?5
+        "#,
+    )
+    .bind(user_id)
+    .bind(entry_id)
+    .bind(read)
+    .bind(starred)
+    .bind(now)
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to update entry {}", entry_id))?;
+    Ok(())
+}
+
+/// Attach a tag to an entry for a user, creating the tag if needed
+pub async fn add_user_tag(
+    pool: &SqlitePool,
+    user_id: i64,
+    entry_id: &str,
+    name: &str,
+) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DatabaseError::invalid("Tag name cannot be empty"));
+    }
+
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+    sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to create tag")?;
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO user_tags (user_id, entry_id, tag_id, created_at)
+        SELECT ?1, ?2, id, ?3 FROM tags WHERE name = ?4
+        "#,
+    )
+    .bind(user_id)
+    .bind(entry_id)
+    .bind(Utc::now())
+    .bind(name)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to add tag")?;
+    tx.commit().await.context("Failed to commit tag")?;
+    Ok(())
+}
+
+/// Detach a user's tag from an entry
+pub async fn remove_user_tag(
+    pool: &SqlitePool,
+    user_id: i64,
+    entry_id: &str,
+    name: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM user_tags
+        WHERE user_id = ? AND entry_id = ? AND tag_id = (SELECT id FROM tags WHERE name = ?)
+        "#,
+    )
+    .bind(user_id)
+    .bind(entry_id)
+    .bind(name.trim())
+    .execute(pool)
+    .await
+    .context("Failed to remove tag")?;
+    Ok(())
+}
+
+/// Get the tags a user sees on entries, keyed by entry ID: the shared feed
+/// and AI tags and the user's own, alphabetically
+pub async fn get_user_tags_for_entries(
+    pool: &SqlitePool,
+    user_id: i64,
+    entry_ids: &[String],
+) -> Result<HashMap<String, Vec<String>>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT et.entry_id, t.name FROM tags t
+        JOIN entry_tags et ON et.tag_id = t.id
+        WHERE et.source != ?1 AND et.entry_id IN (SELECT value FROM json_each(?3))
+        UNION
+        SELECT ut.entry_id, t.name FROM tags t
+        JOIN user_tags ut ON ut.tag_id = t.id
+        WHERE ut.user_id = ?2 AND ut.entry_id IN (SELECT value FROM json_each(?3))
+        ORDER BY 2
+        "#,
+    )
+    .bind(TagSource::User)
+    .bind(user_id)
+    .bind(serde_json::to_string(entry_ids)?)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get user tags")?;
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (entry_id, name) in rows {
+        tags.entry(entry_id).or_default().push(name);
+    }
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserEntryFilter;
+    use crate::fixtures::seeded;
+
+    #[tokio::test]
+    async fn test_user_state() {
+        let (db, _) = seeded().await.unwrap();
+        let alice = db.create_user("alice", "hash-a").await.unwrap();
+        let bob = db.create_user("bob", "hash-b").await.unwrap();
+        assert!(db
+            .create_user("Alice", "hash-c")
+            .await
+            .unwrap_err()
+            .is_duplicate());
+        assert_eq!(
+            db.get_user_by_token("hash-b").await.unwrap(),
+            Some(bob.clone())
+        );
+        assert_eq!(db.get_user_by_token("nope").await.unwrap(), None);
+
+        db.set_user_state(alice.id, "rust-async", Some(true), Some(true))
+            .await
+            .unwrap();
+        db.set_user_state(alice.id, "rust-async", None, Some(true))
+            .await
+            .unwrap();
+        db.add_user_tag(alice.id, "rust-async", "later")
+            .await
+            .unwrap();
+        assert!(db
+            .set_user_state(alice.id, "missing", Some(true), None)
+            .await
+            .unwrap_err()
+            .is_not_found());
+
+        let unread = UserEntryFilter {
+            unread: true,
+            limit: 50,
+            ..Default::default()
+        };
+        let alice_unread = db.get_user_entries(alice.id, &unread).await.unwrap();
+        let bob_unread = db.get_user_entries(bob.id, &unread).await.unwrap();
+        assert_eq!(bob_unread.len(), alice_unread.len() + 1);
+        assert!(alice_unread.iter().all(|e| e.id != "rust-async"));
+
+        let entry = db.get_user_entry(alice.id, "rust-async").await.unwrap();
+        assert!(entry.read && entry.starred && entry.starred_at.is_some());
+        let entry = db.get_user_entry(bob.id, "rust-async").await.unwrap();
+        assert!(!entry.read && !entry.starred);
+
+        let ids = vec!["rust-async".to_string()];
+        let alice_tags = db.get_user_tags_for_entries(alice.id, &ids).await.unwrap();
+        let bob_tags = db.get_user_tags_for_entries(bob.id, &ids).await.unwrap();
+        assert!(alice_tags["rust-async"].contains(&"later".to_string()));
+        assert!(!bob_tags
+            .get("rust-async")
+            .is_some_and(|t| t.contains(&"later".to_string())));
+
+        // The local state is untouched
+        assert!(!db.get_entry("rust-async").await.unwrap().unwrap().read);
+
+        db.delete_user("alice").await.unwrap();
+        assert!(db.delete_user("alice").await.unwrap_err().is_not_found());
+        assert_eq!(db.list_users().await.unwrap(), [bob]);
+    }
+}
@@ -0,0 +1,276 @@
+//! Source adapters for feeds that need more than a plain RSS/Atom fetch
+//!
+//! An adapter recognises a user-facing address (e.g. `bsky:alice.bsky.social`
+//! or `https://x.com/someone`), maps it to a fetchable feed URL, and may
+//! adjust the request, parse the body itself, and clean up the parsed
+//! entries. Adapters are registered in an [`AdapterRegistry`] under URL
+//! patterns; the fetcher dispatches every address through it, so the builtin
+//! adapters and ones registered by downstream code (say, for an internal
+//! feed with its own format) plug in the same way.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::{FeedEntry, FeedMetadata, FeedParser};
+
+pub mod arxiv;
+pub mod bluesky;
+pub mod github;
+pub mod nitter;
+pub mod oai;
+pub mod papers;
+
+pub use arxiv::ArxivAdapter;
+pub use bluesky::BlueskyAdapter;
+pub use github::GithubAdapter;
+pub use nitter::NitterAdapter;
+pub use oai::OaiAdapter;
+pub use papers::is_paper_feed;
+
+/// Maximum length of a title derived from post text
+const DERIVED_TITLE_LEN: usize = 80;
+
+/// Custom handling for the feeds whose addresses match a registered pattern
+///
+/// Every step but [`name`](Self::name) has a default that behaves like a
+/// plain feed, so an adapter overrides only what its source needs.
+pub trait SourceAdapter: Send + Sync {
+    /// Names the adapter in logs
+    fn name(&self) -> &str;
+
+    /// URL to fetch for `address`, or None when this adapter does not handle
+    /// it after all (the next matching adapter is tried)
+    fn resolve(&self, address: &str) -> Option<String> {
+        Some(address.to_string())
+    }
+
+    /// Adjust the request before it is sent, e.g. with credentials
+    fn prepare(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request
+    }
+
+    /// Parse a fetched body into the feed and its entries
+    fn parse(&self, parser: &FeedParser, body: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+        Ok(parser.parse(body)?)
+    }
+
+    /// Clean up parsed entries (titles, canonical links)
+    fn post_process(&self, _entries: &mut [FeedEntry]) {}
+}
+
+/// Adapters keyed by URL pattern
+///
+/// Patterns match whole addresses, ignoring ASCII case; `*` stands for any
+/// run of characters (`https://intranet.example.com/*`). An adapter
+/// registered later is tried before those registered earlier, so it can
+/// take over addresses a builtin adapter handles.
+#[derive(Clone, Default)]
+pub struct AdapterRegistry {
+    adapters: Vec<(String, Arc<dyn SourceAdapter>)>,
+}
+
+impl AdapterRegistry {
+    /// Registry without any adapters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The builtin arXiv, Bluesky, GitHub, OAI-PMH, and X adapters, X through
+    /// `nitter_instance`
+    pub fn builtin(nitter_instance: &str) -> Self {
+        let mut registry = Self::new();
+        registry.register_all(arxiv::PATTERNS, Arc::new(ArxivAdapter));
+        registry.register_all(bluesky::PATTERNS, Arc::new(BlueskyAdapter));
+        registry.register_all(github::PATTERNS, Arc::new(GithubAdapter));
+        registry.register_all(
+            nitter::PATTERNS,
+            Arc::new(NitterAdapter::new(nitter_instance)),
+        );
+        registry.register_all(oai::PATTERNS, Arc::new(OaiAdapter));
+        registry
+    }
+
+    /// Handle addresses matching `pattern` with `adapter`
+    pub fn register(&mut self, pattern: &str, adapter: Arc<dyn SourceAdapter>) {
+        self.adapters.push((pattern.to_ascii_lowercase(), adapter));
+    }
+
+    /// Handle addresses matching any of `patterns` with `adapter`
+    pub fn register_all(&mut self, patterns: &[&str], adapter: Arc<dyn SourceAdapter>) {
+        for pattern in patterns {
+            self.register(pattern, adapter.clone());
+        }
+    }
+
+    /// The adapter handling `address` and the URL it fetches, if any
+    pub fn resolve(&self, address: &str) -> Option<(String, Arc<dyn SourceAdapter>)> {
+        let lowered = address.trim().to_ascii_lowercase();
+        self.adapters
+            .iter()
+            .rev()
+            .filter(|(pattern, _)| matches(pattern, &lowered))
+            .find_map(|(_, adapter)| Some((adapter.resolve(address)?, adapter.clone())))
+    }
+}
+
+/// Whether `text` matches a pattern where `*` stands for any characters
+fn matches(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut text) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match text.find(part) {
+            Some(at) => text = &text[at + part.len()..],
+            None => return false,
+        }
+    }
+    text.len() >= last.len() && text.ends_with(last)
+}
+
+/// Give entries without a title one derived from their text
+pub(crate) fn fill_missing_titles(entries: &mut [FeedEntry]) {
+    for entry in entries.iter_mut() {
+        if entry.title.trim().is_empty() {
+            entry.title = derive_title(entry);
+        }
+    }
+}
+
+/// Build a title from the first line of a post's text
+fn derive_title(entry: &FeedEntry) -> String {
+    let text = entry
+        .content_text
+        .as_deref()
+        .or(entry.summary.as_deref())
+        .unwrap_or_default();
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default();
+    if first_line.chars().count() <= DERIVED_TITLE_LEN {
+        return first_line.to_string();
+    }
+    let truncated: String = first_line.chars().take(DERIVED_TITLE_LEN - 1).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, text: &str) -> FeedEntry {
+        FeedEntry {
+            id: "1".into(),
+            title: title.into(),
+            url: "https://nitter.net/rustlang/status/42#m".into(),
+            published: None,
+            updated: None,
+            summary: None,
+            content_html: None,
+            content_text: Some(text.into()),
+            author: None,
+            categories: Vec::new(),
+            discussion: None,
+            enclosures: Vec::new(),
+        }
+    }
+
+    struct Intranet;
+
+    impl SourceAdapter for Intranet {
+        fn name(&self) -> &str {
+            "intranet"
+        }
+
+        fn resolve(&self, address: &str) -> Option<String> {
+            Some(format!("{}?format=rss", address))
+        }
+    }
+
+    #[test]
+    fn test_registry_dispatch() {
+        let mut registry = AdapterRegistry::builtin("nitter.net");
+        let resolve = |registry: &AdapterRegistry, address: &str| {
+            registry
+                .resolve(address)
+                .map(|(url, adapter)| (adapter.name().to_string(), url))
+        };
+        assert_eq!(
+            resolve(&registry, "bsky:alice.bsky.social"),
+            Some((
+                "bluesky".into(),
+                "https://bsky.app/profile/alice.bsky.social/rss".into()
+            ))
+        );
+        assert_eq!(
+            resolve(&registry, "https://X.com/rustlang"),
+            Some(("nitter".into(), "https://nitter.net/rustlang/rss".into()))
+        );
+        // Matches the pattern, but the adapter declines a post URL
+        assert_eq!(resolve(&registry, "https://x.com/rustlang/status/1"), None);
+        assert_eq!(
+            resolve(&registry, "github:tokio-rs/tokio"),
+            Some((
+                "github".into(),
+                "https://api.github.com/repos/tokio-rs/tokio/releases?per_page=30".into()
+            ))
+        );
+        assert_eq!(
+            resolve(&registry, "arxiv:cs.LG")
+                .map(|(name, _)| name)
+                .as_deref(),
+            Some("arxiv")
+        );
+        assert_eq!(
+            resolve(&registry, "oai:https://repo.example.org/oai")
+                .map(|(name, _)| name)
+                .as_deref(),
+            Some("oai")
+        );
+        assert!(is_paper_feed("arxiv:author:Yann LeCun"));
+        assert!(!is_paper_feed("github:tokio-rs/tokio"));
+        assert_eq!(resolve(&registry, "https://example.com/feed.xml"), None);
+
+        registry.register("https://*.corp.example/*/feed", Arc::new(Intranet));
+        assert_eq!(
+            resolve(&registry, "https://news.corp.example/teams/feed"),
+            Some((
+                "intranet".into(),
+                "https://news.corp.example/teams/feed?format=rss".into()
+            ))
+        );
+        assert_eq!(
+            resolve(&registry, "https://news.corp.example/teams/feed.xml"),
+            None
+        );
+
+        // Registered later, so ahead of the builtin X adapter
+        registry.register("https://x.com/*", Arc::new(Intranet));
+        assert_eq!(
+            resolve(&registry, "https://x.com/rustlang")
+                .map(|(name, _)| name)
+                .as_deref(),
+            Some("intranet")
+        );
+    }
+
+    #[test]
+    fn test_post_process_derives_titles() {
+        let adapter = NitterAdapter::new("nitter.net");
+        let long = "a".repeat(200);
+        let mut entries = vec![entry("", "\n  Hello world\nsecond line"), entry("", &long)];
+        adapter.post_process(&mut entries);
+
+        assert_eq!(entries[0].title, "Hello world");
+        assert_eq!(entries[0].url, "https://x.com/rustlang/status/42");
+        assert_eq!(entries[1].title.chars().count(), DERIVED_TITLE_LEN);
+        assert!(entries[1].title.ends_with('…'));
+    }
+}
@@ -0,0 +1,281 @@
+//! arXiv categories and authors, through the arXiv API
+//!
+//! The API lists the newest submissions matching a search as Atom with
+//! arXiv's own elements (comments, DOIs); entries are keyed by the abstract
+//! page without its version, so a revised paper stays one entry.
+
+use anyhow::Result;
+use url::Url;
+
+use super::papers::{self, Paper};
+use super::SourceAdapter;
+use crate::{FeedEntry, FeedMetadata, FeedParser};
+
+/// Query endpoint of the API
+pub const API_URL: &str = "https://export.arxiv.org/api/query";
+
+/// Papers asked for per fetch
+const MAX_RESULTS: u32 = 50;
+
+/// Addresses the adapter is registered for
+pub const PATTERNS: &[&str] = &["arxiv:*", "http*://arxiv.org/list/*"];
+
+/// What an arXiv address follows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// New submissions to any of these categories (`cs.LG`)
+    Categories(Vec<String>),
+    /// Papers by an author
+    Author(String),
+}
+
+/// Follows arXiv categories and authors through the arXiv API
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArxivAdapter;
+
+impl SourceAdapter for ArxivAdapter {
+    fn name(&self) -> &str {
+        "arxiv"
+    }
+
+    fn resolve(&self, address: &str) -> Option<String> {
+        detect(address).map(|query| feed_url(&query))
+    }
+
+    fn parse(&self, _parser: &FeedParser, body: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+        parse(body)
+    }
+}
+
+/// What an arXiv address follows
+///
+/// Accepts `arxiv:<category>` (several separated by commas),
+/// `arxiv:author:<name>`, and `https://arxiv.org/list/<category>/...` pages.
+pub fn detect(url: &str) -> Option<Query> {
+    let trimmed = url.trim();
+    let categories = match trimmed.strip_prefix("arxiv:") {
+        Some(rest) => {
+            if let Some(author) = rest.strip_prefix("author:") {
+                let author = papers::normalize(author);
+                return (!author.is_empty()).then_some(Query::Author(author));
+            }
+            rest.to_string()
+        }
+        None => {
+            let parsed = Url::parse(trimmed).ok()?;
+            if parsed.host_str()?.trim_start_matches("www.") != "arxiv.org" {
+                return None;
+            }
+            let mut segments = parsed.path_segments()?;
+            if segments.next()? != "list" {
+                return None;
+            }
+            segments.next()?.to_string()
+        }
+    };
+    let categories: Vec<String> = categories
+        .split(',')
+        .map(|c| c.trim().to_string())
+        .collect();
+    let valid = |category: &String| {
+        !category.is_empty()
+            && category
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    };
+    categories
+        .iter()
+        .all(valid)
+        .then_some(Query::Categories(categories))
+}
+
+/// API URL listing the newest papers `query` matches
+pub fn feed_url(query: &Query) -> String {
+    let search = match query {
+        Query::Categories(categories) => categories
+            .iter()
+            .map(|category| format!("cat:{}", category))
+            .collect::<Vec<_>>()
+            .join(" OR "),
+        Query::Author(author) => format!("au:\"{}\"", author),
+    };
+    let max_results = MAX_RESULTS.to_string();
+    let params = [
+        ("search_query", search.as_str()),
+        ("sortBy", "submittedDate"),
+        ("sortOrder", "descending"),
+        ("max_results", max_results.as_str()),
+    ];
+    Url::parse_with_params(API_URL, params)
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+/// Parse an API response
+pub fn parse(body: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+    let root = papers::read_tree(body)?;
+    if root.name != "feed" {
+        anyhow::bail!("Unexpected arXiv API response: <{}>", root.name);
+    }
+    // "ArXiv Query: search_query=cat:cs.LG&id_list=&start=0&max_results=50"
+    let search = root
+        .child_text("title")
+        .and_then(|title| {
+            let query = title.split_once("search_query=")?.1;
+            Some(query.split('&').next().unwrap_or_default().to_string())
+        })
+        .filter(|query| !query.is_empty());
+    let mut metadata = FeedMetadata {
+        title: search.map_or_else(|| "arXiv".to_string(), |query| format!("arXiv {}", query)),
+        description: None,
+        url: String::new(),
+        site_url: Some("https://arxiv.org".to_string()),
+        last_updated: None,
+    };
+    let mut entries = Vec::new();
+    for entry in root.children("entry") {
+        let id = entry.child_text("id").unwrap_or_default();
+        // Errors come as an entry
+        if id.contains("/api/errors") {
+            anyhow::bail!(
+                "arXiv API error: {}",
+                entry.child_text("summary").unwrap_or(id)
+            );
+        }
+        let url = abs_url(&id);
+        let has_pdf = entry
+            .children("link")
+            .any(|link| link.attribute("title") == Some("pdf"));
+        let mut subjects: Vec<String> = Vec::new();
+        for term in entry
+            .children("category")
+            .filter_map(|c| c.attribute("term"))
+        {
+            if !subjects.iter().any(|s| s == term) {
+                subjects.push(term.to_string());
+            }
+        }
+        let paper = Paper {
+            title: entry.child_text("title").unwrap_or_default(),
+            authors: entry
+                .children("author")
+                .filter_map(|a| a.child_text("name"))
+                .collect(),
+            abstract_text: entry.child_text("summary"),
+            published: entry
+                .child_text("published")
+                .and_then(|d| papers::parse_date(&d)),
+            updated: entry
+                .child_text("updated")
+                .and_then(|d| papers::parse_date(&d)),
+            subjects,
+            pdf: has_pdf.then(|| url.replacen("/abs/", "/pdf/", 1)),
+            doi: entry.child_text("doi"),
+            note: entry.child_text("comment"),
+            url,
+        };
+        let entry = paper.into_entry();
+        metadata.last_updated = metadata.last_updated.max(entry.updated.or(entry.published));
+        entries.push(entry);
+    }
+    Ok((metadata, entries))
+}
+
+/// Abstract page of an entry ID, over HTTPS and without the version
+/// (`http://arxiv.org/abs/2405.12345v2` is `https://arxiv.org/abs/2405.12345`)
+fn abs_url(id: &str) -> String {
+    let id = id.replacen("http://", "https://", 1);
+    match id.rsplit_once('v') {
+        Some((unversioned, version))
+            if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            unversioned.to_string()
+        }
+        _ => id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let lg = Query::Categories(vec!["cs.LG".into()]);
+        assert_eq!(detect("arxiv:cs.LG"), Some(lg.clone()));
+        assert_eq!(detect("https://arxiv.org/list/cs.LG/recent"), Some(lg));
+        assert_eq!(
+            detect("arxiv:cs.LG, stat.ML"),
+            Some(Query::Categories(vec!["cs.LG".into(), "stat.ML".into()]))
+        );
+        assert_eq!(
+            detect("arxiv:author:Yann  LeCun"),
+            Some(Query::Author("Yann LeCun".into()))
+        );
+        assert_eq!(detect("arxiv:cs LG"), None);
+        assert_eq!(detect("https://arxiv.org/abs/2405.12345"), None);
+        assert_eq!(
+            feed_url(&Query::Categories(vec!["cs.LG".into(), "stat.ML".into()])),
+            "https://export.arxiv.org/api/query?search_query=cat%3Acs.LG+OR+cat%3Astat.ML\
+             &sortBy=submittedDate&sortOrder=descending&max_results=50"
+        );
+        assert_eq!(
+            abs_url("http://arxiv.org/abs/hep-th/9901001v1"),
+            "https://arxiv.org/abs/hep-th/9901001"
+        );
+        assert_eq!(
+            abs_url("http://arxiv.org/abs/solv-int/9901001"),
+            "https://arxiv.org/abs/solv-int/9901001"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:arxiv="http://arxiv.org/schemas/atom">
+  <title type="html">ArXiv Query: search_query=cat:cs.LG&amp;id_list=&amp;start=0&amp;max_results=50</title>
+  <entry>
+    <id>http://arxiv.org/abs/2405.12345v2</id>
+    <updated>2024-05-22T10:00:00Z</updated>
+    <published>2024-05-20T17:59:59Z</published>
+    <title>Sparse Attention
+      at Scale</title>
+    <summary>  We reduce attention cost by 40%
+      on long contexts.
+    </summary>
+    <author><name>Ada Lovelace</name></author>
+    <author><name>Alan Turing</name><arxiv:affiliation>Manchester</arxiv:affiliation></author>
+    <arxiv:doi>10.1234/abc</arxiv:doi>
+    <arxiv:comment>12 pages, code at https://example.com/code</arxiv:comment>
+    <link href="http://arxiv.org/abs/2405.12345v2" rel="alternate" type="text/html"/>
+    <link title="pdf" href="http://arxiv.org/pdf/2405.12345v2" rel="related" type="application/pdf"/>
+    <arxiv:primary_category term="cs.LG" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="cs.LG" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="cs.CL" scheme="http://arxiv.org/schemas/atom"/>
+  </entry>
+</feed>"#;
+        let (metadata, entries) = parse(body).unwrap();
+        assert_eq!(metadata.title, "arXiv cat:cs.LG");
+        let entry = &entries[0];
+        assert_eq!(entry.id, "https://arxiv.org/abs/2405.12345");
+        assert_eq!(entry.title, "Sparse Attention at Scale");
+        assert_eq!(entry.author.as_deref(), Some("Ada Lovelace, Alan Turing"));
+        assert!(entry
+            .content_text
+            .as_deref()
+            .unwrap()
+            .starts_with("We reduce attention cost by 40% on long contexts.\n\n"));
+        assert_eq!(entry.categories, vec!["cs.LG", "cs.CL"]);
+        assert_eq!(entry.enclosures[0].url, "https://arxiv.org/pdf/2405.12345");
+        assert!(entry.updated.is_some());
+        assert_eq!(metadata.last_updated, entry.updated);
+
+        let error = br#"<feed xmlns="http://www.w3.org/2005/Atom"><entry>
+            <id>http://arxiv.org/api/errors#incorrect_id_format_for_1234</id>
+            <summary>incorrect id format for 1234</summary></entry></feed>"#;
+        assert!(parse(error)
+            .unwrap_err()
+            .to_string()
+            .contains("incorrect id format"));
+    }
+}
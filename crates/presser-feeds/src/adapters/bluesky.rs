@@ -0,0 +1,86 @@
+//! Bluesky profiles via the public per-profile RSS endpoint
+
+use url::Url;
+
+use super::SourceAdapter;
+use crate::FeedEntry;
+
+/// Addresses the adapter is registered for
+pub const PATTERNS: &[&str] = &["bsky:*", "bluesky:*", "http*://bsky.app/profile/*"];
+
+/// Follows Bluesky profiles through their RSS endpoint
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlueskyAdapter;
+
+impl SourceAdapter for BlueskyAdapter {
+    fn name(&self) -> &str {
+        "bluesky"
+    }
+
+    fn resolve(&self, address: &str) -> Option<String> {
+        detect(address).map(|actor| feed_url(&actor))
+    }
+
+    fn post_process(&self, entries: &mut [FeedEntry]) {
+        super::fill_missing_titles(entries);
+    }
+}
+
+/// Extract the actor (handle or DID) from a Bluesky address
+///
+/// Accepts `bsky:<actor>`, `bluesky:<actor>`, and `https://bsky.app/profile/<actor>`.
+pub fn detect(url: &str) -> Option<String> {
+    let trimmed = url.trim();
+    for prefix in ["bsky:", "bluesky:"] {
+        if let Some(actor) = trimmed.strip_prefix(prefix) {
+            return clean_actor(actor);
+        }
+    }
+
+    let parsed = Url::parse(trimmed).ok()?;
+    if parsed.host_str()? != "bsky.app" {
+        return None;
+    }
+    let mut segments = parsed.path_segments()?;
+    match (segments.next(), segments.next()) {
+        (Some("profile"), Some(actor)) => clean_actor(actor),
+        _ => None,
+    }
+}
+
+/// RSS URL for a profile's posts
+pub fn feed_url(actor: &str) -> String {
+    format!("https://bsky.app/profile/{}/rss", actor)
+}
+
+fn clean_actor(actor: &str) -> Option<String> {
+    let actor = actor.trim().trim_start_matches('@').trim_matches('/');
+    (!actor.is_empty()).then(|| actor.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect("bsky:@alice.bsky.social").as_deref(),
+            Some("alice.bsky.social")
+        );
+        assert_eq!(
+            detect("https://bsky.app/profile/did:plc:abc123").as_deref(),
+            Some("did:plc:abc123")
+        );
+        assert_eq!(detect("https://bsky.app/"), None);
+        assert_eq!(detect("https://example.com/profile/x"), None);
+    }
+
+    #[test]
+    fn test_feed_url() {
+        assert_eq!(
+            feed_url("alice.bsky.social"),
+            "https://bsky.app/profile/alice.bsky.social/rss"
+        );
+    }
+}
@@ -0,0 +1,448 @@
+//! GitHub repositories' releases and issues, and the notifications inbox,
+//! through the REST API
+//!
+//! Release notes and issue bodies come as the Markdown they were written
+//! in, kept as the entries' text. The API answers anonymous requests for
+//! public repositories (60 an hour); a token sent with the feed's
+//! `[feed.auth]` raises the limit and is needed for private repositories
+//! and notifications. Discussions are only in the GraphQL API, so they
+//! aren't followed.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use url::Url;
+
+use super::SourceAdapter;
+use crate::{Discussion, FeedEntry, FeedMetadata, FeedParser};
+
+/// REST API root
+pub const API_BASE: &str = "https://api.github.com";
+
+/// API version requested
+const API_VERSION: &str = "2022-11-28";
+
+/// Items asked for per fetch
+const PER_PAGE: u32 = 30;
+
+/// Addresses the adapter is registered for
+pub const PATTERNS: &[&str] = &[
+    "github:*",
+    "http*://github.com/*/releases",
+    "http*://github.com/*/issues",
+    "http*://github.com/notifications",
+];
+
+/// What a GitHub address follows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Releases of a repository (`owner/name`)
+    Releases(String),
+    /// Issues (not pull requests) of a repository, newest first
+    Issues(String),
+    /// The token owner's notifications
+    Notifications,
+}
+
+/// Follows GitHub releases, issues, and notifications through the REST API
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GithubAdapter;
+
+impl SourceAdapter for GithubAdapter {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    fn resolve(&self, address: &str) -> Option<String> {
+        detect(address).map(|target| feed_url(&target))
+    }
+
+    fn prepare(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", API_VERSION)
+    }
+
+    fn parse(&self, _parser: &FeedParser, body: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+        parse(body)
+    }
+}
+
+/// What a GitHub address follows
+///
+/// Accepts `github:<owner>/<repo>` (releases), `github:<owner>/<repo>/releases`,
+/// `github:<owner>/<repo>/issues`, `github:notifications`, and the matching
+/// `https://github.com/...` pages.
+pub fn detect(url: &str) -> Option<Target> {
+    let trimmed = url.trim();
+    let path = match trimmed.strip_prefix("github:") {
+        Some(path) => path.to_string(),
+        None => {
+            let parsed = Url::parse(trimmed).ok()?;
+            if parsed.host_str()? != "github.com" {
+                return None;
+            }
+            parsed.path().to_string()
+        }
+    };
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        ["notifications"] => Some(Target::Notifications),
+        [owner, repo] | [owner, repo, "releases"] => {
+            Some(Target::Releases(format!("{}/{}", owner, repo)))
+        }
+        [owner, repo, "issues"] => Some(Target::Issues(format!("{}/{}", owner, repo))),
+        _ => None,
+    }
+}
+
+/// API URL listing what `target` follows
+pub fn feed_url(target: &Target) -> String {
+    match target {
+        Target::Releases(repo) => {
+            format!("{}/repos/{}/releases?per_page={}", API_BASE, repo, PER_PAGE)
+        }
+        Target::Issues(repo) => format!(
+            "{}/repos/{}/issues?state=all&sort=created&per_page={}",
+            API_BASE, repo, PER_PAGE
+        ),
+        Target::Notifications => format!("{}/notifications?per_page={}", API_BASE, PER_PAGE),
+    }
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    html_url: String,
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    created_at: Option<DateTime<Utc>>,
+    published_at: Option<DateTime<Utc>>,
+    author: Option<User>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+    html_url: String,
+    title: String,
+    body: Option<String>,
+    user: Option<User>,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+    comments: Option<i64>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    /// Set on pull requests, which the issues endpoint lists too
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Subject {
+    title: String,
+    url: Option<String>,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    full_name: String,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct Notification {
+    id: String,
+    reason: String,
+    updated_at: DateTime<Utc>,
+    subject: Subject,
+    repository: Repository,
+}
+
+/// One item of a listing; which one is told by the fields it has
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Item {
+    Notification(Notification),
+    Release(Release),
+    Issue(Issue),
+}
+
+/// Parse a releases, issues, or notifications listing
+pub fn parse(body: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+    let items: Vec<Item> =
+        serde_json::from_slice(body).context("Unexpected GitHub API response")?;
+    let mut metadata = FeedMetadata {
+        title: "GitHub".to_string(),
+        description: None,
+        url: String::new(),
+        site_url: None,
+        last_updated: None,
+    };
+    let mut entries = Vec::new();
+    for item in items {
+        let entry = match item {
+            Item::Release(release) if release.draft => continue,
+            Item::Issue(issue) if issue.pull_request.is_some() => continue,
+            Item::Release(release) => {
+                // .../<owner>/<repo>/releases/tag/<tag>
+                if let Some((releases, _)) = release.html_url.split_once("/releases/") {
+                    let repo = releases.trim_start_matches("https://github.com/");
+                    metadata.title = format!("{} releases", repo);
+                    metadata.site_url = Some(format!("{}/releases", releases));
+                }
+                release_entry(release)
+            }
+            Item::Issue(issue) => {
+                if let Some((repo_url, _)) = issue.html_url.rsplit_once("/issues/") {
+                    metadata.title = format!(
+                        "{} issues",
+                        repo_url.trim_start_matches("https://github.com/")
+                    );
+                    metadata.site_url = Some(format!("{}/issues", repo_url));
+                }
+                issue_entry(issue)
+            }
+            Item::Notification(notification) => {
+                metadata.title = "GitHub notifications".to_string();
+                metadata.site_url = Some("https://github.com/notifications".to_string());
+                notification_entry(notification)
+            }
+        };
+        metadata.last_updated = metadata.last_updated.max(entry.updated.or(entry.published));
+        entries.push(entry);
+    }
+    Ok((metadata, entries))
+}
+
+fn release_entry(release: Release) -> FeedEntry {
+    let repo_name = release
+        .html_url
+        .split_once("/releases/")
+        .and_then(|(repo, _)| repo.rsplit('/').next())
+        .unwrap_or_default()
+        .to_string();
+    let name = release
+        .name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| release.tag_name.clone());
+    // "v1.38.0" alone says little in a digest of many projects
+    let title = match name.to_lowercase().contains(&repo_name.to_lowercase()) {
+        true => name,
+        false => format!("{} {}", repo_name, name),
+    };
+    FeedEntry {
+        id: release.html_url.clone(),
+        title,
+        url: release.html_url,
+        published: release.published_at.or(release.created_at),
+        updated: None,
+        summary: None,
+        content_html: None,
+        content_text: release.body.filter(|body| !body.trim().is_empty()),
+        author: release.author.map(|user| user.login),
+        categories: if release.prerelease {
+            vec!["prerelease".to_string()]
+        } else {
+            Vec::new()
+        },
+        discussion: None,
+        enclosures: Vec::new(),
+    }
+}
+
+fn issue_entry(issue: Issue) -> FeedEntry {
+    FeedEntry {
+        id: issue.html_url.clone(),
+        title: format!("#{} {}", issue.number, issue.title),
+        url: issue.html_url.clone(),
+        published: Some(issue.created_at),
+        updated: issue.updated_at,
+        summary: None,
+        content_html: None,
+        content_text: issue.body.filter(|body| !body.trim().is_empty()),
+        author: issue.user.map(|user| user.login),
+        categories: issue.labels.into_iter().map(|label| label.name).collect(),
+        discussion: Some(Discussion {
+            url: Some(issue.html_url),
+            comment_feed: None,
+            comments: issue.comments,
+        }),
+        enclosures: Vec::new(),
+    }
+}
+
+fn notification_entry(notification: Notification) -> FeedEntry {
+    let url = notification
+        .subject
+        .url
+        .as_deref()
+        .and_then(web_url)
+        .unwrap_or_else(|| notification.repository.html_url.clone());
+    FeedEntry {
+        // A thread comes back with each new activity, as a new entry
+        id: format!(
+            "github-notification-{}-{}",
+            notification.id,
+            notification.updated_at.timestamp()
+        ),
+        title: notification.subject.title,
+        url,
+        published: Some(notification.updated_at),
+        updated: None,
+        summary: Some(format!(
+            "{} in {} ({})",
+            notification.subject.kind,
+            notification.repository.full_name,
+            notification.reason.replace('_', " ")
+        )),
+        content_html: None,
+        content_text: None,
+        author: None,
+        categories: vec![notification.subject.kind, notification.reason],
+        discussion: None,
+        enclosures: Vec::new(),
+    }
+}
+
+/// Web page of an issue, pull request, or commit API URL
+pub fn web_url(api_url: &str) -> Option<String> {
+    let path = api_url.strip_prefix(API_BASE)?.strip_prefix("/repos/")?;
+    let segments: Vec<&str> = path.split('/').collect();
+    let [owner, repo, kind, id, ..] = segments.as_slice() else {
+        return None;
+    };
+    let kind = match *kind {
+        "issues" => "issues",
+        "pulls" => "pull",
+        "commits" => "commit",
+        _ => return None,
+    };
+    Some(format!(
+        "https://github.com/{}/{}/{}/{}",
+        owner, repo, kind, id
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let tokio = Target::Releases("tokio-rs/tokio".into());
+        assert_eq!(detect("github:tokio-rs/tokio"), Some(tokio.clone()));
+        assert_eq!(
+            detect("https://github.com/tokio-rs/tokio/releases"),
+            Some(tokio)
+        );
+        assert_eq!(
+            detect("github:rust-lang/rust/issues"),
+            Some(Target::Issues("rust-lang/rust".into()))
+        );
+        assert_eq!(
+            detect("https://github.com/notifications"),
+            Some(Target::Notifications)
+        );
+        assert_eq!(detect("https://github.com/tokio-rs/tokio/pulls"), None);
+        assert_eq!(detect("https://gitlab.com/a/b/releases"), None);
+        assert_eq!(
+            feed_url(&Target::Releases("tokio-rs/tokio".into())),
+            "https://api.github.com/repos/tokio-rs/tokio/releases?per_page=30"
+        );
+    }
+
+    #[test]
+    fn test_parse_releases() {
+        let body = br#"[
+            {"html_url": "https://github.com/tokio-rs/tokio/releases/tag/tokio-1.38.0",
+             "tag_name": "tokio-1.38.0", "name": "Tokio v1.38.0", "body": "Added `JoinSet::spawn_blocking`",
+             "draft": false, "prerelease": false, "published_at": "2024-05-30T12:00:00Z",
+             "author": {"login": "Darksonn"}},
+            {"html_url": "https://github.com/tokio-rs/tokio/releases/tag/v1.39.0-rc1",
+             "tag_name": "v1.39.0-rc1", "name": "", "body": null, "prerelease": true,
+             "created_at": "2024-06-01T12:00:00Z", "published_at": null},
+            {"html_url": "https://github.com/tokio-rs/tokio/releases/tag/untagged-1",
+             "tag_name": "v2.0.0", "name": "v2.0.0", "draft": true}
+        ]"#;
+        let (metadata, entries) = parse(body).unwrap();
+        assert_eq!(metadata.title, "tokio-rs/tokio releases");
+        assert_eq!(
+            metadata.site_url.as_deref(),
+            Some("https://github.com/tokio-rs/tokio/releases")
+        );
+        // Drafts left out
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Tokio v1.38.0");
+        assert_eq!(
+            entries[0].content_text.as_deref(),
+            Some("Added `JoinSet::spawn_blocking`")
+        );
+        assert_eq!(entries[0].author.as_deref(), Some("Darksonn"));
+        assert_eq!(entries[1].title, "tokio v1.39.0-rc1");
+        assert_eq!(entries[1].content_text, None);
+        assert_eq!(entries[1].categories, vec!["prerelease"]);
+        assert_eq!(metadata.last_updated, entries[1].published);
+    }
+
+    #[test]
+    fn test_parse_issues() {
+        let body = br#"[
+            {"number": 12, "html_url": "https://github.com/owner/repo/issues/12", "title": "Crash on empty feed",
+             "body": "Steps to reproduce", "user": {"login": "alice"}, "created_at": "2024-05-30T12:00:00Z",
+             "updated_at": "2024-05-31T12:00:00Z", "comments": 3, "labels": [{"name": "bug"}]},
+            {"number": 13, "html_url": "https://github.com/owner/repo/pull/13", "title": "Fix crash",
+             "created_at": "2024-05-31T12:00:00Z", "pull_request": {"url": "https://api.github.com/repos/owner/repo/pulls/13"}}
+        ]"#;
+        let (metadata, entries) = parse(body).unwrap();
+        assert_eq!(metadata.title, "owner/repo issues");
+        // Pull requests left out
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "#12 Crash on empty feed");
+        assert_eq!(entries[0].categories, vec!["bug"]);
+        assert_eq!(
+            entries[0].discussion.as_ref().and_then(|d| d.comments),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_parse_notifications() {
+        let body = br#"[
+            {"id": "1", "reason": "review_requested", "updated_at": "2024-05-30T12:00:00Z",
+             "subject": {"title": "Fix crash", "url": "https://api.github.com/repos/owner/repo/pulls/13", "type": "PullRequest"},
+             "repository": {"full_name": "owner/repo", "html_url": "https://github.com/owner/repo"}},
+            {"id": "2", "reason": "subscribed", "updated_at": "2024-05-30T13:00:00Z",
+             "subject": {"title": "v1.0", "url": "https://api.github.com/repos/owner/repo/releases/99", "type": "Release"},
+             "repository": {"full_name": "owner/repo", "html_url": "https://github.com/owner/repo"}}
+        ]"#;
+        let (metadata, entries) = parse(body).unwrap();
+        assert_eq!(metadata.title, "GitHub notifications");
+        assert_eq!(entries[0].url, "https://github.com/owner/repo/pull/13");
+        assert_eq!(
+            entries[0].summary.as_deref(),
+            Some("PullRequest in owner/repo (review requested)")
+        );
+        assert_eq!(entries[0].id, "github-notification-1-1717070400");
+        // No web page for the subject: the repository's
+        assert_eq!(entries[1].url, "https://github.com/owner/repo");
+        assert!(parse(b"[]").unwrap().1.is_empty());
+        assert!(parse(br#"{"message": "Bad credentials"}"#).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! X/Twitter accounts via Nitter-style RSS bridges
+
+use url::Url;
+
+use super::SourceAdapter;
+use crate::FeedEntry;
+
+/// Hosts that identify an X/Twitter profile URL
+const X_HOSTS: &[&str] = &[
+    "x.com",
+    "twitter.com",
+    "www.twitter.com",
+    "mobile.twitter.com",
+    "www.x.com",
+];
+
+/// Default bridge instance
+pub const DEFAULT_INSTANCE: &str = "nitter.net";
+
+/// Addresses the adapter is registered for
+pub const PATTERNS: &[&str] = &[
+    "x:*",
+    "twitter:*",
+    "http*://x.com/*",
+    "http*://*twitter.com/*",
+    "http*://www.x.com/*",
+];
+
+/// Follows X accounts through a Nitter-style bridge instance
+#[derive(Debug, Clone)]
+pub struct NitterAdapter {
+    instance: String,
+}
+
+impl NitterAdapter {
+    /// Adapter fetching from `instance` (a host, or a base URL)
+    pub fn new(instance: impl Into<String>) -> Self {
+        Self {
+            instance: instance.into(),
+        }
+    }
+}
+
+impl SourceAdapter for NitterAdapter {
+    fn name(&self) -> &str {
+        "nitter"
+    }
+
+    fn resolve(&self, address: &str) -> Option<String> {
+        detect(address).map(|user| feed_url(&self.instance, &user))
+    }
+
+    fn post_process(&self, entries: &mut [FeedEntry]) {
+        for entry in entries.iter_mut() {
+            entry.url = canonical_post_url(&entry.url);
+        }
+        super::fill_missing_titles(entries);
+    }
+}
+
+/// Extract the account name from an X address
+///
+/// Accepts `x:<user>`, `twitter:<user>`, and profile URLs on x.com/twitter.com.
+pub fn detect(url: &str) -> Option<String> {
+    let trimmed = url.trim();
+    for prefix in ["x:", "twitter:"] {
+        if let Some(user) = trimmed.strip_prefix(prefix) {
+            return clean_user(user);
+        }
+    }
+
+    let parsed = Url::parse(trimmed).ok()?;
+    if !X_HOSTS.contains(&parsed.host_str()?) {
+        return None;
+    }
+    let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+    match (segments.next(), segments.next()) {
+        (Some(user), None) => clean_user(user),
+        _ => None,
+    }
+}
+
+/// RSS URL for an account on the given bridge instance
+pub fn feed_url(instance: &str, user: &str) -> String {
+    let instance = instance.trim_end_matches('/');
+    if instance.contains("://") {
+        format!("{}/{}/rss", instance, user)
+    } else {
+        format!("https://{}/{}/rss", instance, user)
+    }
+}
+
+/// Rewrite a bridge post link to the canonical x.com URL
+pub fn canonical_post_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) if parsed.path().contains("/status/") => {
+            format!("https://x.com{}", parsed.path())
+        }
+        _ => url.to_string(),
+    }
+}
+
+fn clean_user(user: &str) -> Option<String> {
+    let user = user.trim().trim_start_matches('@').trim_matches('/');
+    let valid = !user.is_empty() && user.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| user.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect("x:@rustlang").as_deref(), Some("rustlang"));
+        assert_eq!(
+            detect("https://twitter.com/rustlang/").as_deref(),
+            Some("rustlang")
+        );
+        assert_eq!(detect("https://x.com/rustlang/status/1"), None);
+        assert_eq!(detect("https://example.com/rustlang"), None);
+    }
+
+    #[test]
+    fn test_feed_url() {
+        assert_eq!(
+            feed_url("nitter.net", "rustlang"),
+            "https://nitter.net/rustlang/rss"
+        );
+        assert_eq!(
+            feed_url("http://localhost:8080/", "a"),
+            "http://localhost:8080/a/rss"
+        );
+    }
+}
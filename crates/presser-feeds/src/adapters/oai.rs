@@ -0,0 +1,280 @@
+//! Repositories that speak OAI-PMH (institutional repositories, preprint
+//! servers, journals), through their Dublin Core records
+//!
+//! Each fetch lists the records added or changed in the last
+//! [`WINDOW_DAYS`] days; a set to follow goes in the base URL
+//! (`oai:https://repo.example.org/oai?set=physics`). Only the first page
+//! of a listing is read, so a busy repository's older records may be
+//! missed.
+
+use anyhow::Result;
+use chrono::Utc;
+use url::Url;
+
+use super::papers::{self, Element, Paper};
+use super::SourceAdapter;
+use crate::{FeedEntry, FeedMetadata, FeedParser};
+
+/// Days of records listed per fetch
+pub const WINDOW_DAYS: i64 = 14;
+
+/// Addresses the adapter is registered for
+pub const PATTERNS: &[&str] = &["oai:*"];
+
+/// Follows OAI-PMH repositories
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OaiAdapter;
+
+impl SourceAdapter for OaiAdapter {
+    fn name(&self) -> &str {
+        "oai"
+    }
+
+    fn resolve(&self, address: &str) -> Option<String> {
+        detect(address).map(|base| feed_url(&base))
+    }
+
+    fn parse(&self, _parser: &FeedParser, body: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+        parse(body)
+    }
+}
+
+/// Base URL of an `oai:<base URL>` address
+pub fn detect(url: &str) -> Option<Url> {
+    let base = Url::parse(url.trim().strip_prefix("oai:")?.trim()).ok()?;
+    matches!(base.scheme(), "http" | "https").then_some(base)
+}
+
+/// URL listing the Dublin Core records of the last [`WINDOW_DAYS`] days
+pub fn feed_url(base: &Url) -> String {
+    let from = (Utc::now() - chrono::Duration::days(WINDOW_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+    let mut url = base.clone();
+    url.query_pairs_mut()
+        .append_pair("verb", "ListRecords")
+        .append_pair("metadataPrefix", "oai_dc")
+        .append_pair("from", &from);
+    url.to_string()
+}
+
+/// Parse a `ListRecords` response
+pub fn parse(body: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+    let root = papers::read_tree(body)?;
+    if root.name != "OAI-PMH" {
+        anyhow::bail!("Unexpected OAI-PMH response: <{}>", root.name);
+    }
+    // The repository's base URL
+    let host = root
+        .child_text("request")
+        .and_then(|request| Url::parse(&request).ok())
+        .and_then(|url| {
+            Some((
+                url.origin().ascii_serialization(),
+                url.host_str()?.to_string(),
+            ))
+        });
+    let metadata = |last_updated| FeedMetadata {
+        title: host.as_ref().map_or_else(
+            || "OAI-PMH".to_string(),
+            |(_, host)| format!("{} papers", host),
+        ),
+        description: None,
+        url: String::new(),
+        site_url: host.as_ref().map(|(origin, _)| origin.clone()),
+        last_updated,
+    };
+    if let Some(error) = root.child("error") {
+        return match error.attribute("code") {
+            // Nothing new in the window
+            Some("noRecordsMatch") => Ok((metadata(None), Vec::new())),
+            code => anyhow::bail!(
+                "OAI-PMH error {}: {}",
+                code.unwrap_or("unknown"),
+                error.text
+            ),
+        };
+    }
+
+    let mut records = Vec::new();
+    root.descendants("record", &mut records);
+    let entries: Vec<FeedEntry> = records.into_iter().filter_map(record_entry).collect();
+    let last_updated = entries
+        .iter()
+        .filter_map(|e| e.updated.or(e.published))
+        .max();
+    Ok((metadata(last_updated), entries))
+}
+
+/// Entry of a record, unless it was deleted or has no link to a page
+fn record_entry(record: &Element) -> Option<FeedEntry> {
+    let header = record.child("header")?;
+    if header.attribute("status") == Some("deleted") {
+        return None;
+    }
+    let mut found = Vec::new();
+    record.descendants("dc", &mut found);
+    let dc = found.first()?;
+
+    let identifiers = dc.child_texts("identifier");
+    let doi = identifiers.iter().find_map(|id| {
+        let doi = id
+            .strip_prefix("doi:")
+            .or_else(|| id.strip_prefix("https://doi.org/"))
+            .or_else(|| id.strip_prefix("http://dx.doi.org/"))
+            .unwrap_or(id);
+        doi.starts_with("10.").then(|| doi.to_string())
+    });
+    let is_link = |id: &&String| id.starts_with("http://") || id.starts_with("https://");
+    let pdf = identifiers
+        .iter()
+        .filter(is_link)
+        .find(|id| id.to_ascii_lowercase().ends_with(".pdf"))
+        .cloned();
+    let url = identifiers
+        .iter()
+        .filter(is_link)
+        .find(|id| Some(*id) != pdf.as_ref() && !id.contains("doi.org/"))
+        .cloned()
+        .or_else(|| doi.as_ref().map(|doi| format!("https://doi.org/{}", doi)))
+        .or_else(|| pdf.clone())?;
+    // The abstract, then notes like "Comment: 12 pages"
+    let mut descriptions = dc.child_texts("description").into_iter();
+    let abstract_text = descriptions.next();
+    let notes: Vec<String> = descriptions
+        .map(|note| {
+            let trimmed = note
+                .strip_prefix("Comments:")
+                .or_else(|| note.strip_prefix("Comment:"));
+            trimmed.map_or(note.clone(), |rest| rest.trim().to_string())
+        })
+        .collect();
+    let published = dc
+        .child_texts("date")
+        .iter()
+        .find_map(|date| papers::parse_date(date));
+    let updated = header
+        .child_text("datestamp")
+        .and_then(|d| papers::parse_date(&d));
+
+    let paper = Paper {
+        url,
+        title: dc.child_text("title").unwrap_or_default(),
+        authors: dc
+            .child_texts("creator")
+            .iter()
+            .map(|name| display_name(name))
+            .collect(),
+        abstract_text,
+        published: published.or(updated),
+        updated,
+        subjects: dc.child_texts("subject"),
+        pdf,
+        doi,
+        note: Some(notes.join("; ")).filter(|note| !note.is_empty()),
+    };
+    Some(paper.into_entry())
+}
+
+/// "Jane Doe" for "Doe, Jane", as Dublin Core creators are usually written
+fn display_name(creator: &str) -> String {
+    match creator.split_once(", ") {
+        Some((family, given)) if !given.contains(',') => format!("{} {}", given, family),
+        _ => creator.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let base = detect("oai:https://export.arxiv.org/oai2?set=cs").unwrap();
+        let url = feed_url(&base);
+        assert!(
+            url.starts_with(
+                "https://export.arxiv.org/oai2?set=cs&verb=ListRecords&metadataPrefix=oai_dc&from="
+            ),
+            "{}",
+            url
+        );
+        assert!(detect("oai:arXiv.org:2405.12345").is_none());
+        assert!(detect("https://export.arxiv.org/oai2").is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<OAI-PMH xmlns="http://www.openarchives.org/OAI/2.0/">
+  <responseDate>2024-05-23T00:00:00Z</responseDate>
+  <request verb="ListRecords" metadataPrefix="oai_dc">https://repo.example.org/oai</request>
+  <ListRecords>
+    <record>
+      <header>
+        <identifier>oai:repo.example.org:123</identifier>
+        <datestamp>2024-05-21</datestamp>
+      </header>
+      <metadata>
+        <oai_dc:dc xmlns:oai_dc="http://www.openarchives.org/OAI/2.0/oai_dc/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+          <dc:title>Graph Neural Networks for Weather</dc:title>
+          <dc:creator>Doe, Jane</dc:creator>
+          <dc:creator>Roe, Richard</dc:creator>
+          <dc:subject>Computer Science - Learning</dc:subject>
+          <dc:description>We forecast weather with GNNs.</dc:description>
+          <dc:description>Comment: 9 pages</dc:description>
+          <dc:date>2024-05-20</dc:date>
+          <dc:identifier>https://repo.example.org/record/123</dc:identifier>
+          <dc:identifier>https://repo.example.org/record/123/files/paper.pdf</dc:identifier>
+          <dc:identifier>doi:10.5555/123</dc:identifier>
+        </oai_dc:dc>
+      </metadata>
+    </record>
+    <record>
+      <header status="deleted">
+        <identifier>oai:repo.example.org:99</identifier>
+        <datestamp>2024-05-22</datestamp>
+      </header>
+    </record>
+    <resumptionToken cursor="0">token</resumptionToken>
+  </ListRecords>
+</OAI-PMH>"#;
+        let (metadata, entries) = parse(body).unwrap();
+        assert_eq!(metadata.title, "repo.example.org papers");
+        assert_eq!(
+            metadata.site_url.as_deref(),
+            Some("https://repo.example.org")
+        );
+        // Deleted records left out
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.url, "https://repo.example.org/record/123");
+        assert_eq!(entry.author.as_deref(), Some("Jane Doe, Richard Roe"));
+        assert_eq!(
+            entry.content_text.as_deref(),
+            Some("We forecast weather with GNNs.\n\nAuthors: Jane Doe, Richard Roe\nComments: 9 pages")
+        );
+        assert_eq!(
+            entry.enclosures[0].url,
+            "https://repo.example.org/record/123/files/paper.pdf"
+        );
+        assert!(entry
+            .content_html
+            .as_deref()
+            .unwrap()
+            .contains("https://doi.org/10.5555/123"));
+        assert_eq!(
+            entry.published.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-05-20T00:00:00+00:00")
+        );
+
+        let empty = br#"<OAI-PMH><request>https://repo.example.org/oai</request>
+            <error code="noRecordsMatch">No records</error></OAI-PMH>"#;
+        assert!(parse(empty).unwrap().1.is_empty());
+        let error = br#"<OAI-PMH><error code="badArgument">Illegal set</error></OAI-PMH>"#;
+        assert!(parse(error)
+            .unwrap_err()
+            .to_string()
+            .contains("Illegal set"));
+    }
+}
@@ -0,0 +1,323 @@
+//! Research papers, as the arXiv and OAI-PMH adapters map them to entries
+//!
+//! An entry's text is the paper's abstract followed by its authors and any
+//! comments, which is what gets summarized; its HTML adds links to the PDF
+//! and DOI, and the PDF is its enclosure. Feeds of papers are summarized in
+//! the paper style unless their config says otherwise (see [`is_paper_feed`]).
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::{arxiv, oai};
+use crate::{FeedEnclosure, FeedEntry};
+
+/// Authors named in an entry's byline before "et al."
+const AUTHORS_SHOWN: usize = 3;
+
+/// Whether `address` is a feed of papers (arXiv or OAI-PMH)
+pub fn is_paper_feed(address: &str) -> bool {
+    arxiv::detect(address).is_some() || oai::detect(address).is_some()
+}
+
+/// A paper as listed by a repository
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Paper {
+    /// Abstract page, also the entry's ID
+    pub url: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub abstract_text: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub updated: Option<DateTime<Utc>>,
+    pub subjects: Vec<String>,
+    pub pdf: Option<String>,
+    pub doi: Option<String>,
+    /// Comments (page counts, venues, code links)
+    pub note: Option<String>,
+}
+
+impl Paper {
+    pub fn into_entry(self) -> FeedEntry {
+        let mut text = self.abstract_text.clone().unwrap_or_default();
+        let mut html = String::new();
+        if !self.authors.is_empty() {
+            let authors = self.authors.join(", ");
+            text.push_str(&format!("\n\nAuthors: {}", authors));
+            html.push_str(&format!("<p><em>{}</em></p>", escape(&authors)));
+        }
+        if let Some(abstract_text) = &self.abstract_text {
+            html.push_str(&format!("<p>{}</p>", escape(abstract_text)));
+        }
+        if let Some(note) = &self.note {
+            text.push_str(&format!("\nComments: {}", note));
+            html.push_str(&format!("<p>Comments: {}</p>", escape(note)));
+        }
+        let links: Vec<String> = [
+            self.pdf.as_deref().map(|pdf| ("PDF", pdf.to_string())),
+            self.doi
+                .as_deref()
+                .map(|doi| ("DOI", format!("https://doi.org/{}", doi))),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|(label, href)| format!("<a href=\"{}\">{}</a>", escape(&href), label))
+        .collect();
+        if !links.is_empty() {
+            html.push_str(&format!("<p>{}</p>", links.join(" · ")));
+        }
+        let text = text.trim().to_string();
+        FeedEntry {
+            id: self.url.clone(),
+            title: self.title,
+            url: self.url,
+            published: self.published,
+            updated: self
+                .updated
+                .filter(|updated| Some(*updated) != self.published),
+            summary: None,
+            content_html: Some(html).filter(|html| !html.is_empty()),
+            content_text: Some(text).filter(|text| !text.is_empty()),
+            author: byline(&self.authors),
+            categories: self.subjects,
+            discussion: None,
+            enclosures: self
+                .pdf
+                .map(|url| FeedEnclosure {
+                    url,
+                    mime_type: Some("application/pdf".to_string()),
+                    length: None,
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// "A, B, C", or "A et al." for more authors than that
+fn byline(authors: &[String]) -> Option<String> {
+    match authors {
+        [] => None,
+        [first, ..] if authors.len() > AUTHORS_SHOWN => Some(format!("{} et al.", first)),
+        _ => Some(authors.join(", ")),
+    }
+}
+
+/// Text with its runs of whitespace (line breaks in titles and abstracts)
+/// made single spaces
+pub(crate) fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A timestamp or a date (taken as midnight UTC)
+pub(crate) fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            Some(
+                NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .ok()?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc(),
+            )
+        })
+}
+
+/// An XML element, read whole: names are local (no namespace prefix) and
+/// text is normalized
+#[derive(Debug, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub text: String,
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    /// Value of the attribute named `name`
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Children named `name`
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// First child named `name`
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Text of the first child named `name`, if it has any
+    pub fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name)
+            .map(|child| child.text.clone())
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Texts of the children named `name` that have any
+    pub fn child_texts(&self, name: &str) -> Vec<String> {
+        self.children(name)
+            .map(|child| child.text.clone())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+
+    /// Elements named `name` at any depth below this one, in document order
+    pub fn descendants<'a>(&'a self, name: &str, found: &mut Vec<&'a Element>) {
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            }
+            child.descendants(name, found);
+        }
+    }
+}
+
+/// Read a document into its root element
+pub(crate) fn read_tree(body: &[u8]) -> Result<Element> {
+    let mut reader = Reader::from_reader(body);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    // The open elements, innermost last
+    let mut open: Vec<Element> = vec![Element::default()];
+    loop {
+        match reader.read_event_into(&mut buf).context("Invalid XML")? {
+            Event::Start(start) => open.push(element(&reader, &start)?),
+            Event::Empty(start) => {
+                let element = element(&reader, &start)?;
+                open.last_mut()
+                    .context("Unbalanced XML")?
+                    .children
+                    .push(element);
+            }
+            Event::Text(text) => {
+                let text = text.unescape().context("Invalid XML text")?;
+                push_text(&mut open, &text);
+            }
+            Event::CData(text) => push_text(&mut open, &String::from_utf8_lossy(&text)),
+            Event::End(_) => {
+                let mut closed = open.pop().context("Unbalanced XML")?;
+                closed.text = normalize(&closed.text);
+                open.last_mut()
+                    .context("Unbalanced XML")?
+                    .children
+                    .push(closed);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    let document = open
+        .pop()
+        .filter(|_| open.is_empty())
+        .context("Unbalanced XML")?;
+    document
+        .children
+        .into_iter()
+        .next()
+        .context("Empty XML document")
+}
+
+fn element(reader: &Reader<&[u8]>, start: &BytesStart) -> Result<Element> {
+    let attributes = crate::xml::attributes(reader, start)
+        .context("Invalid XML attribute")?
+        .into_iter()
+        .map(|(key, value)| (local_name(&key).to_string(), value))
+        .collect();
+    Ok(Element {
+        name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+        attributes,
+        ..Default::default()
+    })
+}
+
+/// A name without its namespace prefix
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+fn push_text(open: &mut [Element], text: &str) {
+    if let Some(current) = open.last_mut() {
+        current.text.push(' ');
+        current.text.push_str(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_entry() {
+        let paper = Paper {
+            url: "https://arxiv.org/abs/2405.12345".into(),
+            title: "Sparse attention".into(),
+            authors: vec![
+                "Ada Lovelace".into(),
+                "Alan Turing".into(),
+                "Grace Hopper".into(),
+                "Edsger Dijkstra".into(),
+            ],
+            abstract_text: Some("We show that x < y.".into()),
+            pdf: Some("https://arxiv.org/pdf/2405.12345".into()),
+            doi: Some("10.1234/abc".into()),
+            note: Some("12 pages".into()),
+            ..Default::default()
+        };
+        let entry = paper.into_entry();
+        assert_eq!(entry.id, "https://arxiv.org/abs/2405.12345");
+        assert_eq!(entry.author.as_deref(), Some("Ada Lovelace et al."));
+        assert_eq!(
+            entry.content_text.as_deref(),
+            Some("We show that x < y.\n\nAuthors: Ada Lovelace, Alan Turing, Grace Hopper, Edsger Dijkstra\nComments: 12 pages")
+        );
+        let html = entry.content_html.unwrap();
+        assert!(html.contains("<p>We show that x &lt; y.</p>"), "{}", html);
+        assert!(
+            html.contains("<a href=\"https://doi.org/10.1234/abc\">DOI</a>"),
+            "{}",
+            html
+        );
+        assert_eq!(
+            entry.enclosures[0].mime_type.as_deref(),
+            Some("application/pdf")
+        );
+        assert_eq!(byline(&["A".into(), "B".into()]).as_deref(), Some("A, B"));
+    }
+
+    #[test]
+    fn test_read_tree() {
+        let root = read_tree(
+            br#"<?xml version="1.0"?>
+            <feed xmlns:arxiv="http://arxiv.org/schemas/atom">
+              <title>A
+                 title &amp; more</title>
+              <arxiv:comment><![CDATA[12 pages]]></arxiv:comment>
+              <link rel="alternate" href="https://arxiv.org/abs/1"/>
+            </feed>"#,
+        )
+        .unwrap();
+        assert_eq!(root.name, "feed");
+        assert_eq!(root.child_text("title").as_deref(), Some("A title & more"));
+        assert_eq!(root.child_text("comment").as_deref(), Some("12 pages"));
+        assert_eq!(
+            root.child("link").and_then(|l| l.attribute("href")),
+            Some("https://arxiv.org/abs/1")
+        );
+        assert!(read_tree(b"<feed><entry></feed>").is_err());
+        assert_eq!(
+            parse_date("2024-05-20").map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-05-20T00:00:00+00:00")
+        );
+    }
+}
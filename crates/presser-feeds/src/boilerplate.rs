@@ -0,0 +1,313 @@
+//! Boilerplate stripping: cookie banners, subscription nags, and "related
+//! articles" boxes removed from article pages before readability runs
+//!
+//! Readability keeps any block that reads enough like prose, and these often
+//! do. Three heuristics pick what to drop: selectors for the widgets of
+//! common CMSes (WordPress, Substack, Medium, Ghost) and consent managers,
+//! short elements whose class or ID names them (`cookie-notice`,
+//! `newsletter-signup`), and short blocks opening with a nag ("Subscribe
+//! to…", "We use cookies…"). Configured selectors are removed on top.
+
+use anyhow::{anyhow, Result};
+use scraper::{ElementRef, Html, Selector};
+
+/// Widgets of common CMSes and consent managers
+const BUILTIN_SELECTORS: &[&str] = &[
+    // WordPress (Jetpack, YARPP, Cookie Notice)
+    ".sharedaddy",
+    ".jp-relatedposts",
+    ".yarpp-related",
+    ".wp-block-jetpack-subscriptions",
+    // Substack
+    ".subscription-widget-wrap",
+    ".subscribe-widget",
+    "p.button-wrapper",
+    ".share-dialog",
+    // Ghost
+    ".kg-signup-card",
+    ".gh-post-upgrade-cta",
+    ".read-next",
+    ".gh-read-next",
+    // Consent managers (OneTrust, Cookiebot, Osano, cookieconsent)
+    "#onetrust-consent-sdk",
+    "#CybotCookiebotDialog",
+    ".osano-cm-window",
+    ".cc-window",
+];
+
+/// Class and ID words (split on `-` and `_`) naming a widget
+const WIDGET_WORDS: &[&str] = &[
+    "cookie",
+    "cookies",
+    "consent",
+    "gdpr",
+    "paywall",
+    "subscribe",
+    "subscription",
+    "subscriptions",
+    "newsletter",
+    "signup",
+    "related",
+    "relatedposts",
+    "promo",
+    "upsell",
+    "cta",
+    "share",
+    "sharing",
+];
+
+/// Most characters of text in an element dropped for its class or ID;
+/// longer ones are more likely wrappers around the article
+const MAX_WIDGET_CHARS: usize = 1000;
+
+/// Openings of nag blocks, lowercase
+const NAG_OPENINGS: &[&str] = &[
+    "subscribe to",
+    "subscribe now",
+    "subscribe for",
+    "sign up for",
+    "sign up to",
+    "thanks for reading",
+    "we use cookies",
+    "this site uses cookies",
+    "this website uses cookies",
+    "accept all cookies",
+    "member-only story",
+    "share this",
+    "you've read all your free",
+];
+
+/// Openings of headings whose section (the element after them) goes too
+const SECTION_OPENINGS: &[&str] = &[
+    "related",
+    "you might also like",
+    "you may also like",
+    "more from",
+    "recommended from",
+    "read next",
+    "read more",
+];
+
+/// Most characters of text in a block dropped for its opening
+const MAX_NAG_CHARS: usize = 300;
+
+/// Blocks checked for nag openings
+const NAG_BLOCKS: &str = "body p, body h2, body h3, body h4, body h5, body h6, body aside";
+
+/// Elements never removed, whatever their class
+const STRUCTURAL: &[&str] = &["html", "head", "body", "main", "article"];
+
+/// What to strip from article pages
+#[derive(Debug, Clone)]
+pub struct Boilerplate {
+    heuristics: bool,
+    selectors: Vec<Selector>,
+}
+
+impl Boilerplate {
+    /// Strip the elements matching `selectors`, and with `heuristics` the
+    /// builtin widgets, widget-named elements, and nag blocks
+    pub fn new(heuristics: bool, selectors: &[String]) -> Result<Self> {
+        let builtin = if heuristics { BUILTIN_SELECTORS } else { &[] };
+        let selectors = builtin
+            .iter()
+            .copied()
+            .chain(selectors.iter().map(String::as_str))
+            .map(|s| Selector::parse(s).map_err(|e| anyhow!("Invalid selector '{}': {}", s, e)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            heuristics,
+            selectors,
+        })
+    }
+
+    /// `html` without its boilerplate (unchanged when there is none)
+    pub fn strip(&self, html: &str) -> String {
+        if !self.heuristics && self.selectors.is_empty() {
+            return html.to_string();
+        }
+        let mut document = Html::parse_document(html);
+        let mut doomed = Vec::new();
+        for selector in &self.selectors {
+            doomed.extend(
+                document
+                    .select(selector)
+                    .filter(|e| !STRUCTURAL.contains(&e.value().name()))
+                    .map(|e| e.id()),
+            );
+        }
+        if self.heuristics {
+            let body = Selector::parse("body *").expect("valid selector");
+            doomed.extend(
+                document
+                    .select(&body)
+                    .filter(|e| named_as_widget(e) && text_len(e) <= MAX_WIDGET_CHARS)
+                    .map(|e| e.id()),
+            );
+            let blocks = Selector::parse(NAG_BLOCKS).expect("valid selector");
+            for block in document.select(&blocks) {
+                let text = block.text().collect::<String>();
+                let opening = text.trim().to_lowercase();
+                if opening.chars().count() > MAX_NAG_CHARS {
+                    continue;
+                }
+                if NAG_OPENINGS.iter().any(|nag| opening.starts_with(nag)) {
+                    doomed.push(block.id());
+                } else if is_heading(&block)
+                    && SECTION_OPENINGS.iter().any(|s| opening.starts_with(s))
+                {
+                    // Only when links follow: "Related work" over prose stays
+                    let next = block.next_siblings().find_map(ElementRef::wrap);
+                    if let Some(links) = next.filter(is_link_list) {
+                        doomed.extend([block.id(), links.id()]);
+                    }
+                }
+            }
+        }
+        if doomed.is_empty() {
+            return html.to_string();
+        }
+        for id in doomed {
+            if let Some(mut node) = document.tree.get_mut(id) {
+                node.detach();
+            }
+        }
+        document.html()
+    }
+}
+
+impl Default for Boilerplate {
+    fn default() -> Self {
+        Self::new(true, &[]).expect("builtin selectors are valid")
+    }
+}
+
+/// Whether a word of the element's class or ID names a widget
+fn named_as_widget(element: &ElementRef) -> bool {
+    let value = element.value();
+    if STRUCTURAL.contains(&value.name()) {
+        return false;
+    }
+    value
+        .classes()
+        .chain(value.id())
+        .flat_map(|name| name.split(['-', '_']))
+        .any(|word| WIDGET_WORDS.contains(&word.to_ascii_lowercase().as_str()))
+}
+
+fn is_heading(element: &ElementRef) -> bool {
+    matches!(element.value().name(), "h2" | "h3" | "h4" | "h5" | "h6")
+}
+
+/// Whether most of the element's text is link text
+fn is_link_list(element: &ElementRef) -> bool {
+    let links = Selector::parse("a").expect("valid selector");
+    let linked: usize = element.select(&links).map(|a| text_len(&a)).sum();
+    linked > 0 && linked * 2 >= text_len(element)
+}
+
+fn text_len(element: &ElementRef) -> usize {
+    element.text().map(|t| t.trim().chars().count()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContentExtractor;
+
+    /// Article pages with the text that must survive and the boilerplate
+    /// that must not
+    const FIXTURES: &[(&str, &str, &[&str], &[&str])] = &[
+        (
+            "wordpress",
+            include_str!("../fixtures/boilerplate/wordpress.html"),
+            &[
+                "closed the line in 1962",
+                "a budget the transport committee",
+            ],
+            &[
+                "Share this",
+                "The last ferry to Millbank",
+                "Subscribe to City Notes",
+                "We use cookies",
+            ],
+        ),
+        (
+            "substack",
+            include_str!("../fixtures/boilerplate/substack.html"),
+            &[
+                "lend packets of seeds",
+                "outperform anything sold commercially",
+            ],
+            &[
+                "reader-supported publication",
+                "Subscribe now",
+                "Thanks for reading",
+            ],
+        ),
+        (
+            "medium",
+            include_str!("../fixtures/boilerplate/medium.html"),
+            &["eleven thousand tests", "untangling the shared fixtures"],
+            &[
+                "Member-only story",
+                "Sign up to discover",
+                "Flaky tests are a scheduling problem",
+                "Ten habits",
+            ],
+        ),
+        (
+            "ghost",
+            include_str!("../fixtures/boilerplate/ghost.html"),
+            &["below ten degrees", "keep the changing huts open"],
+            &[
+                "Sign up for more",
+                "paying subscribers only",
+                "A summer of open water",
+                "uses cookies",
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_strip_cms_fixtures() {
+        let boilerplate = Boilerplate::default();
+        let extractor = ContentExtractor::new();
+        for (cms, html, kept, stripped) in FIXTURES {
+            let cleaned = boilerplate.strip(html);
+            let text = extractor.extract(html, "https://example.com/post").unwrap();
+            for phrase in *kept {
+                assert!(cleaned.contains(phrase), "{}: lost {:?}", cms, phrase);
+                assert!(
+                    text.contains(phrase),
+                    "{}: extraction lost {:?}",
+                    cms,
+                    phrase
+                );
+            }
+            for phrase in *stripped {
+                assert!(!cleaned.contains(phrase), "{}: kept {:?}", cms, phrase);
+                assert!(
+                    !text.contains(phrase),
+                    "{}: extraction kept {:?}",
+                    cms,
+                    phrase
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_strip_selectors() {
+        let html = "<html><body><article class=\"share-wrapper\"><p>Body text</p>\
+                    <div class=\"ad-slot\">Buy now</div><div class=\"sharing\">Share</div></article></body></html>";
+        let custom = Boilerplate::new(false, &[".ad-slot".to_string()]).unwrap();
+        let cleaned = custom.strip(html);
+        assert!(!cleaned.contains("Buy now"));
+        // Heuristics off: widget-named elements stay
+        assert!(cleaned.contains("Share"));
+        // Structural elements stay even when their class names a widget
+        assert!(Boilerplate::default().strip(html).contains("Body text"));
+        assert!(Boilerplate::new(true, &["p[".to_string()]).is_err());
+    }
+}
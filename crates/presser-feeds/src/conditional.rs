@@ -0,0 +1,204 @@
+//! Conditional GET support (ETag / Last-Modified)
+
+use crate::{FeedEntry, FeedMetadata};
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// HTTP cache validators remembered from a previous fetch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    /// `ETag` response header
+    pub etag: Option<String>,
+
+    /// `Last-Modified` response header
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Read validators from response headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: get(ETAG),
+            last_modified: get(LAST_MODIFIED),
+        }
+    }
+
+    /// Whether there is nothing to revalidate with
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Add `If-None-Match` / `If-Modified-Since` headers to a request
+    pub fn apply(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// Whether a redirect from `from` to `to` means the feed has moved for good
+///
+/// That is a 301 or 308, or any redirect that only moves between `www.` and
+/// the bare domain or from `http` to `https` (which sites rarely undo, even
+/// when they answer with a temporary status).
+pub fn is_permanent_move(status: StatusCode, from: &Url, to: &Url) -> bool {
+    if matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+    ) {
+        return true;
+    }
+    let host = |url: &Url| {
+        let host = url.host_str().unwrap_or_default();
+        host.strip_prefix("www.").unwrap_or(host).to_string()
+    };
+    let upgrade =
+        from.scheme() == to.scheme() || (from.scheme() == "http" && to.scheme() == "https");
+    upgrade && host(from) == host(to) && from.path() == to.path() && from.query() == to.query()
+}
+
+/// SHA-256 of a feed body, for noticing a feed served unchanged by a server
+/// that doesn't answer 304
+pub fn content_hash(body: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(body))
+}
+
+/// Outcome of a conditional fetch
+#[derive(Debug, Clone)]
+pub enum ConditionalFetch {
+    /// Server answered 304; the stored copy is current
+    NotModified {
+        /// Where the feed has permanently moved, if the request was redirected there
+        moved_to: Option<String>,
+    },
+
+    /// Fresh feed content
+    Modified {
+        /// HTTP status of the response
+        status: u16,
+        /// Parsed feed metadata
+        metadata: Box<FeedMetadata>,
+        /// Parsed entries
+        entries: Vec<FeedEntry>,
+        /// Validators to send on the next fetch
+        validators: Validators,
+        /// Hash of the response body (see [`content_hash`])
+        content_hash: String,
+        /// Where the feed has permanently moved, if the request was redirected there
+        moved_to: Option<String>,
+    },
+}
+
+impl ConditionalFetch {
+    /// Where the feed has permanently moved, if anywhere
+    pub fn moved_to(&self) -> Option<&str> {
+        match self {
+            Self::NotModified { moved_to } | Self::Modified { moved_to, .. } => moved_to.as_deref(),
+        }
+    }
+
+    /// Whether there is nothing new since the fetch whose body hashed to
+    /// `previous`: a 304, or the same body again
+    pub fn is_unchanged(&self, previous: Option<&str>) -> bool {
+        match self {
+            Self::NotModified { .. } => true,
+            Self::Modified { content_hash, .. } => previous == Some(content_hash.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_validators_round_trip() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        let validators = Validators::from_headers(&headers);
+        assert_eq!(validators.etag.as_deref(), Some("\"abc\""));
+        assert!(validators.last_modified.is_none());
+        assert!(!validators.is_empty());
+
+        let request = validators
+            .apply(reqwest::Client::new().get("https://example.com/feed"))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[IF_NONE_MATCH], "\"abc\"");
+        assert!(request.headers().get(IF_MODIFIED_SINCE).is_none());
+    }
+
+    #[test]
+    fn test_is_unchanged() {
+        let hash = content_hash(b"<rss/>");
+        let modified = ConditionalFetch::Modified {
+            status: 200,
+            metadata: Box::new(FeedMetadata {
+                title: "Feed".into(),
+                description: None,
+                url: String::new(),
+                site_url: None,
+                last_updated: None,
+            }),
+            entries: Vec::new(),
+            validators: Validators::default(),
+            content_hash: hash.clone(),
+            moved_to: None,
+        };
+        assert!(modified.is_unchanged(Some(&hash)));
+        assert!(!modified.is_unchanged(Some(&content_hash(b"<rss></rss>"))));
+        assert!(!modified.is_unchanged(None));
+        assert!(ConditionalFetch::NotModified { moved_to: None }.is_unchanged(None));
+    }
+
+    #[test]
+    fn test_is_permanent_move() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        let feed = url("http://www.example.com/feed.xml");
+        let found = StatusCode::FOUND;
+        assert!(is_permanent_move(
+            StatusCode::MOVED_PERMANENTLY,
+            &feed,
+            &url("https://blog.example.org/rss")
+        ));
+        assert!(is_permanent_move(
+            found,
+            &feed,
+            &url("https://example.com/feed.xml")
+        ));
+        assert!(is_permanent_move(
+            found,
+            &url("https://example.com/feed.xml"),
+            &url("https://www.example.com/feed.xml")
+        ));
+        assert!(!is_permanent_move(
+            found,
+            &feed,
+            &url("http://www.example.com/feed.xml?page=2")
+        ));
+        assert!(!is_permanent_move(
+            found,
+            &url("https://example.com/feed.xml"),
+            &url("http://example.com/feed.xml")
+        ));
+        assert!(!is_permanent_move(
+            StatusCode::TEMPORARY_REDIRECT,
+            &feed,
+            &url("http://cdn.example.com/feed.xml")
+        ));
+    }
+}
@@ -0,0 +1,372 @@
+//! Cookies servers set, sent back with later requests for feeds and
+//! article pages alike, and with a file kept across restarts
+//!
+//! Enough of RFC 6265 for sites that gate content on a session cookie:
+//! `Domain`, `Path`, `Secure`, `Expires`, and `Max-Age` are honored, while
+//! `HttpOnly` and `SameSite` mean nothing without a browser. Cookies without
+//! an expiry are saved too, since a restarted daemon is the same session to
+//! the site. Cookies set on the way through redirects the HTTP client
+//! follows by itself (page fetches) are not seen; feed fetches follow their
+//! redirects one by one and see them all.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::{HeaderMap, SET_COOKIE};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Longest a cookie is kept, as browsers cap it
+const MAX_AGE_SECS: i64 = 400 * 24 * 3600;
+
+/// One stored cookie
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Cookie {
+    name: String,
+    value: String,
+    /// Host that set it, or the domain its `Domain` attribute names
+    /// (lowercase, without a leading dot)
+    domain: String,
+    /// Sent to `domain` alone, not its subdomains (no `Domain` attribute)
+    host_only: bool,
+    path: String,
+    /// Sent over HTTPS only
+    secure: bool,
+    /// `None` for a session cookie
+    expires: Option<DateTime<Utc>>,
+}
+
+impl Cookie {
+    /// Cookie of a `Set-Cookie` header received from `url`; `None` if the
+    /// header is malformed or names a domain `url` may not set cookies for
+    fn parse(header: &str, url: &Url) -> Option<Cookie> {
+        let host = url.host_str()?.to_lowercase();
+        let mut attributes = header.split(';');
+        let (name, value) = attributes.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            secure: false,
+            expires: None,
+        };
+        let mut max_age = None;
+        for attribute in attributes {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (attribute.trim(), ""),
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_lowercase();
+                    // A host sets cookies for itself and its parent domains,
+                    // never for a whole top-level domain
+                    if !domain_matches(&host, &domain) || !domain.contains('.') {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => cookie.expires = parse_expires(value),
+                _ => {}
+            }
+        }
+        // Max-Age wins over Expires
+        if let Some(secs) = max_age {
+            cookie.expires = Some(Utc::now() + Duration::seconds(secs.min(MAX_AGE_SECS)));
+        }
+        Some(cookie)
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Whether the cookie goes with a request to `url`
+    fn matches(&self, url: &Url, now: DateTime<Utc>) -> bool {
+        let Some(host) = url.host_str().map(str::to_lowercase) else {
+            return false;
+        };
+        let domain = match self.host_only {
+            true => host == self.domain,
+            false => domain_matches(&host, &self.domain),
+        };
+        domain
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired(now)
+    }
+
+    /// Whether `other` replaces this cookie
+    fn same_slot(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Whether a request for `path` is under a cookie's `cookie_path`
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || path
+            .strip_prefix(cookie_path)
+            .is_some_and(|rest| cookie_path.ends_with('/') || rest.starts_with('/'))
+}
+
+/// Path of a cookie without a `Path` attribute: the directory of `url`
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => url.path()[..end].to_string(),
+    }
+}
+
+/// Time of an `Expires` attribute, as `Wed, 21 Oct 2026 07:28:00 GMT` or
+/// with dashes (`21-Oct-2026`)
+fn parse_expires(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(&value.replace('-', " "))
+        .ok()
+        .map(|expires| expires.with_timezone(&Utc))
+}
+
+/// Cookies kept between requests (see the module docs), in memory or saved
+/// to a file as they change
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    path: Option<PathBuf>,
+    cookies: Mutex<Vec<Cookie>>,
+}
+
+impl CookieJar {
+    /// A jar kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A jar saved to `path`, starting with the cookies saved there (none
+    /// if it doesn't exist yet)
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let cookies: Vec<Cookie> = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .with_context(|| format!("Failed to parse {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let now = Utc::now();
+        Ok(Self {
+            path: Some(path),
+            cookies: Mutex::new(cookies.into_iter().filter(|c| !c.is_expired(now)).collect()),
+        })
+    }
+
+    /// File the jar is saved to, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Number of cookies kept
+    pub fn len(&self) -> usize {
+        self.cookies.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `Cookie` header for a request to `url`, if any cookie goes with it
+    pub fn header_for(&self, url: &Url) -> Option<String> {
+        let now = Utc::now();
+        let cookies = self.cookies.lock().unwrap();
+        let mut matching: Vec<&Cookie> = cookies.iter().filter(|c| c.matches(url, now)).collect();
+        if matching.is_empty() {
+            return None;
+        }
+        // Longer paths first, as browsers send them
+        matching.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        let pairs: Vec<String> = matching
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect();
+        Some(pairs.join("; "))
+    }
+
+    /// Keep the cookies a response from `url` sets, saving the jar if they
+    /// changed it; a cookie set to expire is removed
+    pub fn store(&self, url: &Url, headers: &HeaderMap) -> Result<()> {
+        let set: Vec<Cookie> = headers
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|header| Cookie::parse(header, url))
+            .collect();
+        if set.is_empty() {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let mut cookies = self.cookies.lock().unwrap();
+        let mut changed = false;
+        for cookie in set {
+            let existing = cookies.iter().position(|c| c.same_slot(&cookie));
+            match (existing, cookie.is_expired(now)) {
+                (Some(i), true) => {
+                    cookies.remove(i);
+                    changed = true;
+                }
+                (Some(i), false) if cookies[i] != cookie => {
+                    cookies[i] = cookie;
+                    changed = true;
+                }
+                (None, false) => {
+                    cookies.push(cookie);
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+        match &self.path {
+            Some(path) if changed => {
+                cookies.retain(|c| !c.is_expired(now));
+                save(path, &cookies)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Write `cookies` to `path`, readable by the user alone: they may be
+/// logins
+fn save(path: &Path, cookies: &[Cookie]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    // Write beside the final name first, so a crash never leaves half a jar
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, serde_json::to_string_pretty(cookies)?)
+        .with_context(|| format!("Failed to write {}", partial.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&partial, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    fn set_cookies(headers: &[&str]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for header in headers {
+            map.append(SET_COOKIE, HeaderValue::from_str(header).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_matching() {
+        let jar = CookieJar::new();
+        let login = url("https://www.example.com/account/login");
+        jar.store(
+            &login,
+            &set_cookies(&[
+                "session=abc123; Path=/; Secure; HttpOnly",
+                "prefs=dark; Domain=.example.com; Path=/; Max-Age=3600",
+                "step=2",
+                "tracker=x; Domain=other.com",
+                "tld=x; Domain=com",
+            ]),
+        )
+        .unwrap();
+        assert_eq!(jar.len(), 3);
+
+        assert_eq!(
+            jar.header_for(&url("https://www.example.com/account/settings"))
+                .as_deref(),
+            Some("step=2; session=abc123; prefs=dark")
+        );
+        assert_eq!(
+            jar.header_for(&url("https://www.example.com/feed.xml"))
+                .as_deref(),
+            Some("session=abc123; prefs=dark")
+        );
+        // Secure cookies stay off plain HTTP, host-only ones off other hosts
+        assert_eq!(
+            jar.header_for(&url("http://www.example.com/feed.xml"))
+                .as_deref(),
+            Some("prefs=dark")
+        );
+        assert_eq!(
+            jar.header_for(&url("https://blog.example.com/")).as_deref(),
+            Some("prefs=dark")
+        );
+        assert_eq!(jar.header_for(&url("https://notexample.com/")), None);
+        assert_eq!(
+            jar.header_for(&url("https://www.example.com/accounting"))
+                .as_deref(),
+            Some("session=abc123; prefs=dark")
+        );
+
+        // Expiring a cookie removes it
+        jar.store(
+            &login,
+            &set_cookies(&["session=; Path=/; Expires=Thu, 01-Jan-1970 00:00:00 GMT"]),
+        )
+        .unwrap();
+        assert_eq!(jar.len(), 2);
+    }
+
+    #[test]
+    fn test_saved_across_restarts() {
+        let dir = std::env::temp_dir().join(format!("presser-cookies-{}", std::process::id()));
+        let path = dir.join("cookies.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let jar = CookieJar::open(&path).unwrap();
+        assert!(jar.is_empty());
+        let site = url("https://paper.example.com/");
+        jar.store(
+            &site,
+            &set_cookies(&["session=abc123", "gone=1; Max-Age=0"]),
+        )
+        .unwrap();
+        assert!(path.exists());
+
+        let reopened = CookieJar::open(&path).unwrap();
+        assert_eq!(
+            reopened.header_for(&site).as_deref(),
+            Some("session=abc123")
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
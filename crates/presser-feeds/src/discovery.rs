@@ -0,0 +1,82 @@
+//! Feed autodiscovery
+//!
+//! Websites advertise their feeds with `<link rel="alternate">` tags in the
+//! page head; this finds them so users can add a site by its homepage URL.
+
+use scraper::{Html, Selector};
+use url::Url;
+
+/// Content types of advertised feeds, preferred first
+const FEED_TYPES: &[&str] = &[
+    "application/atom+xml",
+    "application/rss+xml",
+    "application/feed+json",
+    "application/json",
+    "application/rdf+xml",
+];
+
+/// Find feeds advertised in an HTML page
+///
+/// Atom comes before RSS before JSON Feed; within a type, page order is kept.
+/// Comment feeds are skipped when the page also has other feeds.
+pub fn find_feed_links(html: &str, base: &Url) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel][type][href]").expect("static selector");
+
+    let mut links: Vec<(usize, bool, Url)> = Vec::new();
+    for link in document.select(&selector) {
+        let element = link.value();
+        let rel = element.attr("rel").unwrap_or_default().to_lowercase();
+        if !rel.split_whitespace().any(|r| r == "alternate") {
+            continue;
+        }
+        let content_type = element.attr("type").unwrap_or_default().to_lowercase();
+        let Some(rank) = FEED_TYPES.iter().position(|t| content_type.trim() == *t) else {
+            continue;
+        };
+        let Some(href) = element.attr("href").and_then(|h| base.join(h.trim()).ok()) else {
+            continue;
+        };
+        let title = element.attr("title").unwrap_or_default().to_lowercase();
+        let comments = title.contains("comments") || href.path().contains("/comments/");
+        if !links.iter().any(|(_, _, url)| *url == href) {
+            links.push((rank, comments, href));
+        }
+    }
+
+    if links.iter().any(|(_, comments, _)| !comments) {
+        links.retain(|(_, comments, _)| !comments);
+    }
+    links.sort_by_key(|(rank, _, _)| *rank);
+    links.into_iter().map(|(_, _, url)| url).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_feed_links() {
+        let html = r#"<html><head>
+            <link rel="stylesheet" type="text/css" href="/style.css">
+            <link rel="alternate" type="application/rss+xml" title="Blog" href="/feed.xml">
+            <link rel="alternate" type="application/rss+xml" title="Comments Feed" href="/comments/feed/">
+            <link rel="alternate" type="application/atom+xml" href="https://example.com/atom.xml">
+            <link rel="alternate" type="text/html" hreflang="de" href="/de/">
+        </head></html>"#;
+        let base = Url::parse("https://example.com/blog/").unwrap();
+
+        let links: Vec<String> = find_feed_links(html, &base)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            links,
+            [
+                "https://example.com/atom.xml",
+                "https://example.com/feed.xml"
+            ]
+        );
+        assert!(find_feed_links("<p>No feeds here</p>", &base).is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+//! Discussions of entries: where their comments are and how many there are
+//!
+//! Feeds say this in several ways, all read here: RSS `<comments>` (the
+//! comments page, as Hacker News feeds use it), `<slash:comments>` (the
+//! count, from WordPress), `<wfw:commentRss>` (a feed of the comments), Atom
+//! `<link rel="replies">` with `thr:count` (Blogger), and in descriptions:
+//! "# Comments: 342" (hnrss) or a "[comments]" link (Reddit).
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// Labels a description puts before a comment count, lowercase
+const COUNT_LABELS: &[&str] = &["# comments:", "comments:"];
+
+/// Where an entry is discussed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Discussion {
+    /// Page with the comments
+    pub url: Option<String>,
+
+    /// Feed of the comments
+    pub comment_feed: Option<String>,
+
+    /// Number of comments when the feed was fetched
+    pub comments: Option<i64>,
+}
+
+impl Discussion {
+    /// Whether the feed said nothing about a discussion
+    pub fn is_empty(&self) -> bool {
+        self.url.is_none() && self.comment_feed.is_none() && self.comments.is_none()
+    }
+
+    /// Fill what is missing from `other`
+    fn or(self, other: Discussion) -> Discussion {
+        Discussion {
+            url: self.url.or(other.url),
+            comment_feed: self.comment_feed.or(other.comment_feed),
+            comments: self.comments.or(other.comments),
+        }
+    }
+}
+
+/// Which part of a discussion an element holds
+#[derive(Clone, Copy)]
+enum Field {
+    Url,
+    Feed,
+    Count,
+}
+
+/// Discussions of a feed's items (RSS) or entries (Atom), in document order;
+/// empty when the document can't be read
+pub(crate) fn scan(content: &[u8]) -> Vec<Discussion> {
+    let mut reader = Reader::from_reader(content);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut found = Vec::new();
+    let mut current: Option<Discussion> = None;
+    let mut field = None;
+    loop {
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(event) => event,
+            Err(_) => return Vec::new(),
+        };
+        match event {
+            Event::Start(element) => match element.name().as_ref() {
+                b"item" | b"entry" => current = Some(Discussion::default()),
+                b"comments" => field = Some(Field::Url),
+                b"wfw:commentRss" | b"wfw:commentrss" => field = Some(Field::Feed),
+                b"slash:comments" | b"thr:total" => field = Some(Field::Count),
+                b"link" => replies_link(&reader, &element, current.as_mut()),
+                _ => {}
+            },
+            Event::Empty(element) if element.name().as_ref() == b"link" => {
+                replies_link(&reader, &element, current.as_mut());
+            }
+            Event::Text(text) => {
+                if let (Some(discussion), Some(field)) = (current.as_mut(), field) {
+                    let text = text.unescape().map(|t| t.trim().to_string());
+                    set(discussion, field, text.unwrap_or_default());
+                }
+            }
+            Event::CData(text) => {
+                if let (Some(discussion), Some(field)) = (current.as_mut(), field) {
+                    let text = String::from_utf8_lossy(&text).trim().to_string();
+                    set(discussion, field, text);
+                }
+            }
+            Event::End(element) => match element.name().as_ref() {
+                b"item" | b"entry" => found.extend(current.take()),
+                _ => field = None,
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    found
+}
+
+fn set(discussion: &mut Discussion, field: Field, text: String) {
+    if text.is_empty() {
+        return;
+    }
+    match field {
+        Field::Url => discussion.url = Some(text),
+        Field::Feed => discussion.comment_feed = Some(text),
+        Field::Count => discussion.comments = text.parse().ok(),
+    }
+}
+
+/// Read an Atom `<link rel="replies">`: a feed or page of the comments,
+/// with their count
+fn replies_link(reader: &Reader<&[u8]>, element: &BytesStart, discussion: Option<&mut Discussion>) {
+    let Some(discussion) = discussion else {
+        return;
+    };
+    let attrs = match crate::xml::attributes(reader, element) {
+        Ok(attrs) => attrs,
+        Err(e) => {
+            tracing::warn!("Skipping a link with malformed attributes: {}", e);
+            return;
+        }
+    };
+    let attr = |name: &str| {
+        attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, v)| v.clone())
+    };
+    if attr("rel").as_deref() != Some("replies") {
+        return;
+    }
+    let href = attr("href");
+    if attr("type").is_some_and(|t| t.contains("xml")) {
+        discussion.comment_feed = discussion.comment_feed.take().or(href);
+    } else {
+        discussion.url = discussion.url.take().or(href);
+    }
+    if let Some(count) = attr("thr:count").and_then(|c| c.parse().ok()) {
+        discussion.comments = Some(count);
+    }
+}
+
+/// What an entry's description says about its discussion: a count after
+/// "Comments:" and a link titled "[comments]"
+pub(crate) fn from_description(html: &str) -> Discussion {
+    let fragment = Html::parse_fragment(html);
+    let text = fragment.root_element().text().collect::<String>();
+    let lower = text.to_lowercase();
+    let comments = COUNT_LABELS.iter().find_map(|label| {
+        let at = lower.find(label)? + label.len();
+        let digits: String = lower[at..]
+            .trim_start()
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    });
+    let links = Selector::parse("a[href]").expect("valid selector");
+    let url = fragment
+        .select(&links)
+        .find(|a| {
+            a.text()
+                .collect::<String>()
+                .trim()
+                .eq_ignore_ascii_case("[comments]")
+        })
+        .and_then(|a| a.value().attr("href"))
+        .map(str::to_string);
+    Discussion {
+        url,
+        comment_feed: None,
+        comments,
+    }
+}
+
+/// An entry's discussion from its feed item and description, if any
+pub(crate) fn merge(scanned: Option<Discussion>, description: Option<&str>) -> Option<Discussion> {
+    let described = description.map(from_description).unwrap_or_default();
+    let discussion = scanned.unwrap_or_default().or(described);
+    (!discussion.is_empty()).then_some(discussion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FeedParser;
+
+    #[test]
+    fn test_parse_discussions() {
+        let rss = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:slash="http://purl.org/rss/1.0/modules/slash/"
+     xmlns:wfw="http://wellformedweb.org/CommentAPI/">
+  <channel>
+    <title>Mixed</title>
+    <link>https://example.com</link>
+    <item>
+      <title>Show HN: A tiny database</title>
+      <link>https://tiny.example.com/</link>
+      <comments>https://news.ycombinator.com/item?id=42</comments>
+      <description><![CDATA[<p>Points: 342</p><p># Comments: 120</p>]]></description>
+    </item>
+    <item>
+      <title>A blog post</title>
+      <link>https://example.com/post</link>
+      <slash:comments>7</slash:comments>
+      <wfw:commentRss>https://example.com/post/feed/</wfw:commentRss>
+    </item>
+    <item>
+      <title>Nothing to discuss</title>
+      <link>https://example.com/quiet</link>
+    </item>
+    <item>
+      <title>Reddit link</title>
+      <link>https://example.com/linked</link>
+      <description>submitted by u/someone &lt;a href="https://www.reddit.com/r/rust/comments/abc/"&gt;[comments]&lt;/a&gt;</description>
+    </item>
+  </channel>
+</rss>"#;
+        let (_, entries) = FeedParser::new().parse(rss.as_bytes()).unwrap();
+        let discussions: Vec<Option<Discussion>> =
+            entries.into_iter().map(|e| e.discussion).collect();
+        assert_eq!(
+            discussions,
+            [
+                Some(Discussion {
+                    url: Some("https://news.ycombinator.com/item?id=42".into()),
+                    comment_feed: None,
+                    comments: Some(120),
+                }),
+                Some(Discussion {
+                    url: None,
+                    comment_feed: Some("https://example.com/post/feed/".into()),
+                    comments: Some(7),
+                }),
+                None,
+                Some(Discussion {
+                    url: Some("https://www.reddit.com/r/rust/comments/abc/".into()),
+                    comment_feed: None,
+                    comments: None,
+                }),
+            ]
+        );
+
+        let atom = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:thr="http://purl.org/syndication/thread/1.0">
+  <title>Blogger</title>
+  <entry>
+    <id>tag:blogger.com,1999:post-1</id>
+    <title>Atom post</title>
+    <link rel="alternate" type="text/html" href="https://blog.example.com/post.html"/>
+    <link rel="replies" type="application/atom+xml" href="https://blog.example.com/feeds/1/comments/default" thr:count="12"/>
+    <link rel="replies" type="text/html" href="https://blog.example.com/post.html?m=1&amp;c=1#comments"/>
+  </entry>
+</feed>"#;
+        assert_eq!(
+            scan(atom.as_bytes()),
+            [Discussion {
+                url: Some("https://blog.example.com/post.html?m=1&c=1#comments".into()),
+                comment_feed: Some("https://blog.example.com/feeds/1/comments/default".into()),
+                comments: Some(12),
+            }]
+        );
+    }
+}
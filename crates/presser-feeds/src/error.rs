@@ -29,6 +29,10 @@ pub enum FeedError {
     #[error("Request timeout for: {0}")]
     Timeout(String),
 
+    /// Redirect chain too long (or looping)
+    #[error("Too many redirects for: {0}")]
+    TooManyRedirects(String),
+
     /// Generic error
     #[error(transparent)]
     Other(#[from] anyhow::Error),
@@ -1,30 +1,67 @@
 //! Content extraction using readability algorithms
 
+use crate::boilerplate::Boilerplate;
 use crate::FeedError;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 use url::Url;
 
+/// How article pages are turned into HTML before readability runs
+pub use presser_config::ExtractionBackend;
+
+/// Main content of an article page
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Article {
+    /// Page title
+    pub title: String,
+
+    /// Article text
+    pub text: String,
+}
+
 /// Content extractor that extracts main article content from HTML
-pub struct ContentExtractor;
+///
+/// Pages are stripped of boilerplate (see [`Boilerplate`]) before
+/// readability runs.
+pub struct ContentExtractor {
+    boilerplate: Boilerplate,
+}
 
 impl ContentExtractor {
-    /// Create a new content extractor
+    /// Create a new content extractor with the builtin boilerplate heuristics
     pub fn new() -> Self {
-        Self
+        Self {
+            boilerplate: Boilerplate::default(),
+        }
+    }
+
+    /// Also strip the elements matching `selectors`; `heuristics` false
+    /// leaves only those
+    pub fn with_boilerplate(mut self, heuristics: bool, selectors: &[String]) -> Result<Self> {
+        self.boilerplate = Boilerplate::new(heuristics, selectors)?;
+        Ok(self)
     }
 
     /// Extract main content from HTML
     pub fn extract(&self, html: &str, url: &str) -> Result<String, FeedError> {
-        let parsed_url = Url::parse(url)
-            .map_err(|e| FeedError::InvalidUrl(e.to_string()))?;
+        Ok(self.extract_article(html, url)?.text)
+    }
+
+    /// Extract the title and main content from HTML
+    pub fn extract_article(&self, html: &str, url: &str) -> Result<Article, FeedError> {
+        let parsed_url = Url::parse(url).map_err(|e| FeedError::InvalidUrl(e.to_string()))?;
 
+        let html = self.boilerplate.strip(html);
         let mut cursor = Cursor::new(html.as_bytes());
 
         let product = readability::extractor::extract(&mut cursor, &parsed_url)
             .map_err(|e| FeedError::ExtractionError(e.to_string()))?;
 
-        Ok(product.text)
+        Ok(Article {
+            title: product.title,
+            text: product.text,
+        })
     }
 
     /// Convert HTML to plain text with improved formatting
@@ -97,7 +134,7 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(FeedError::InvalidUrl(_)) => {},
+            Err(FeedError::InvalidUrl(_)) => {}
             _ => panic!("Expected InvalidUrl error"),
         }
     }
@@ -0,0 +1,44 @@
+//! Headless-browser page rendering for JavaScript-heavy sites
+//!
+//! Only compiled with the `headless` feature. A Chromium instance is launched
+//! per render, so this backend is meant for the few feeds that need it.
+
+use crate::FeedError;
+use chromiumoxide::browser::{Browser, BrowserConfig};
+use futures::StreamExt;
+use std::time::Duration;
+
+/// Render a page in headless Chromium and return the resulting DOM as HTML
+pub async fn render(url: &str, timeout: Duration) -> Result<String, FeedError> {
+    let config = BrowserConfig::builder()
+        .request_timeout(timeout)
+        .build()
+        .map_err(FeedError::ExtractionError)?;
+
+    let (mut browser, mut handler) = Browser::launch(config)
+        .await
+        .map_err(|e| FeedError::ExtractionError(format!("Failed to launch browser: {}", e)))?;
+
+    let handler_task = tokio::spawn(async move {
+        while let Some(event) = handler.next().await {
+            if event.is_err() {
+                break;
+            }
+        }
+    });
+
+    let result = async {
+        let page = browser.new_page(url).await?;
+        page.wait_for_navigation().await?;
+        page.content().await
+    }
+    .await
+    .map_err(|e| FeedError::ExtractionError(format!("Headless render failed for {}: {}", url, e)));
+
+    if let Err(e) = browser.close().await {
+        tracing::debug!("Failed to close headless browser: {}", e);
+    }
+    handler_task.abort();
+
+    result
+}
@@ -0,0 +1,146 @@
+//! Favicon discovery and local caching
+//!
+//! Icons are resolved from the site's HTML (`<link rel="icon">`,
+//! `apple-touch-icon`) with `/favicon.ico` as the last resort, and cached
+//! on disk under a name derived from the site host.
+
+use scraper::{Html, Selector};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// File extensions an icon may be cached under
+const ICON_EXTENSIONS: &[&str] = &["png", "ico", "svg", "jpg", "gif", "webp"];
+
+/// Default icon cache directory (`~/.cache/presser/icons`)
+pub fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("presser")
+        .join("icons")
+}
+
+/// Find icon candidates declared in an HTML page, most preferred first
+///
+/// Explicit `icon`/`shortcut icon` links come before `apple-touch-icon`,
+/// and `/favicon.ico` on the page's origin is always appended last.
+pub fn find_icon_links(html: &str, base: &Url) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel][href]").expect("static selector");
+
+    let mut icons = Vec::new();
+    let mut touch_icons = Vec::new();
+    for link in document.select(&selector) {
+        let rel = link.value().attr("rel").unwrap_or_default().to_lowercase();
+        let Some(href) = link
+            .value()
+            .attr("href")
+            .and_then(|h| base.join(h.trim()).ok())
+        else {
+            continue;
+        };
+        let rels: Vec<&str> = rel.split_whitespace().collect();
+        if rels.contains(&"icon") {
+            icons.push(href);
+        } else if rels.iter().any(|r| r.starts_with("apple-touch-icon")) {
+            touch_icons.push(href);
+        }
+    }
+
+    icons.extend(touch_icons);
+    if let Ok(fallback) = base.join("/favicon.ico") {
+        if !icons.contains(&fallback) {
+            icons.push(fallback);
+        }
+    }
+    icons
+}
+
+/// Cache file stem for a site (hash of its host)
+pub fn cache_key(site: &Url) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(site.host_str().unwrap_or_default().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Look up an already cached icon for a site
+pub fn cached_icon(cache_dir: &Path, site: &Url) -> Option<PathBuf> {
+    let key = cache_key(site);
+    ICON_EXTENSIONS
+        .iter()
+        .map(|ext| cache_dir.join(format!("{}.{}", key, ext)))
+        .find(|path| path.is_file())
+}
+
+/// Pick a file extension from a response content type, falling back to the URL path
+pub fn icon_extension(content_type: Option<&str>, url: &Url) -> &'static str {
+    let mime = content_type.unwrap_or_default().to_lowercase();
+    let from_mime = match mime.split(';').next().unwrap_or_default().trim() {
+        "image/png" => Some("png"),
+        "image/svg+xml" => Some("svg"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        "image/x-icon" | "image/vnd.microsoft.icon" => Some("ico"),
+        _ => None,
+    };
+    from_mime
+        .or_else(|| {
+            let path = url.path().to_lowercase();
+            ICON_EXTENSIONS
+                .iter()
+                .copied()
+                .find(|ext| path.ends_with(&format!(".{}", ext)))
+        })
+        .unwrap_or("ico")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_icon_links_prefers_rel_icon() {
+        let html = r#"<html><head>
+            <link rel="apple-touch-icon" href="/touch.png">
+            <link rel="shortcut icon" href="static/fav.png">
+            <link rel="stylesheet" href="/style.css">
+        </head></html>"#;
+        let base = Url::parse("https://example.com/blog/").unwrap();
+
+        let links = find_icon_links(html, &base);
+        assert_eq!(links[0].as_str(), "https://example.com/blog/static/fav.png");
+        assert_eq!(links[1].as_str(), "https://example.com/touch.png");
+        assert_eq!(links[2].as_str(), "https://example.com/favicon.ico");
+        assert_eq!(links.len(), 3);
+    }
+
+    #[test]
+    fn test_find_icon_links_fallback_only() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let links = find_icon_links("<html></html>", &base);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].as_str(), "https://example.com/favicon.ico");
+    }
+
+    #[test]
+    fn test_icon_extension() {
+        let url = Url::parse("https://example.com/icon").unwrap();
+        assert_eq!(
+            icon_extension(Some("image/png; charset=binary"), &url),
+            "png"
+        );
+        let svg = Url::parse("https://example.com/logo.svg").unwrap();
+        assert_eq!(icon_extension(None, &svg), "svg");
+        assert_eq!(icon_extension(None, &url), "ico");
+    }
+
+    #[test]
+    fn test_cache_key_per_host() {
+        let a = Url::parse("https://example.com/a").unwrap();
+        let b = Url::parse("https://example.com/b").unwrap();
+        let c = Url::parse("https://other.org/").unwrap();
+        assert_eq!(cache_key(&a), cache_key(&b));
+        assert_ne!(cache_key(&a), cache_key(&c));
+    }
+}
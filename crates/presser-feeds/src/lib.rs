@@ -29,21 +29,67 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-
+use tokio::io::AsyncWriteExt;
+use url::Url;
+
+pub mod adapters;
+pub mod boilerplate;
+pub mod conditional;
+pub mod cookies;
+pub mod discovery;
+pub mod discussion;
 pub mod error;
 pub mod extractor;
+#[cfg(feature = "headless")]
+pub mod headless;
+pub mod icon;
+pub mod network;
+pub mod opml;
 pub mod parser;
-
+pub mod probe;
+pub mod readers;
+pub mod telemetry;
+pub mod user_agent;
+mod xml;
+
+pub use adapters::{AdapterRegistry, SourceAdapter};
+pub use boilerplate::Boilerplate;
+pub use conditional::{ConditionalFetch, Validators};
+pub use cookies::CookieJar;
+pub use discussion::Discussion;
 pub use error::FeedError;
-pub use extractor::ContentExtractor;
+pub use extractor::{Article, ContentExtractor, ExtractionBackend};
+pub use network::Connectivity;
 pub use parser::FeedParser;
+pub use probe::{FeedFormat, FeedProbe};
+pub use readers::{ReaderExport, ReaderItem, ReaderService};
+pub use telemetry::{ByteMeter, RequestObserver, RequestOutcome, RequestRecord};
+
+/// Redirects followed when fetching a feed
+const MAX_REDIRECTS: usize = 10;
+
+/// Longest a media download may take
+pub const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60 * 60);
 
 /// Feed fetcher that handles HTTP requests and parsing
 pub struct FeedFetcher {
     client: reqwest::Client,
+    /// Client that leaves redirects to the caller, for noticing moved feeds
+    direct_client: reqwest::Client,
     parser: FeedParser,
     extractor: ContentExtractor,
+    icon_dir: PathBuf,
+    timeout: Duration,
+    adapters: AdapterRegistry,
+    connectivity: Connectivity,
+    observer: Option<RequestObserver>,
+    /// Sent with feed requests unless a fetch names another
+    user_agent: String,
+    cookies: Option<Arc<CookieJar>>,
 }
 
 /// Represents a single feed entry/article
@@ -78,6 +124,54 @@ pub struct FeedEntry {
 
     /// Categories/tags
     pub categories: Vec<String>,
+
+    /// Comments page, comment feed, and comment count, when the feed gives any
+    #[serde(default)]
+    pub discussion: Option<Discussion>,
+
+    /// Attached media files (podcast audio, video)
+    #[serde(default)]
+    pub enclosures: Vec<FeedEnclosure>,
+}
+
+/// Media file attached to an entry: an RSS `<enclosure>`, a Media RSS
+/// `<media:content>`, or an Atom `<link rel="enclosure">`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedEnclosure {
+    /// Address of the file
+    pub url: String,
+
+    /// MIME type, as the feed gives it
+    pub mime_type: Option<String>,
+
+    /// Size in bytes, as the feed gives it
+    pub length: Option<i64>,
+}
+
+impl FeedEnclosure {
+    /// Whether the file is audio, by its MIME type or else its extension
+    pub fn is_audio(&self) -> bool {
+        is_audio(self.mime_type.as_deref(), &self.url)
+    }
+}
+
+/// Whether a file with this MIME type and address is audio, by its MIME
+/// type or, when the feed gives none, its extension
+pub fn is_audio(mime_type: Option<&str>, url: &str) -> bool {
+    const EXTENSIONS: &[&str] = &[
+        ".mp3", ".m4a", ".aac", ".ogg", ".oga", ".opus", ".flac", ".wav",
+    ];
+    match mime_type {
+        Some(mime) if !mime.is_empty() => mime.starts_with("audio/"),
+        _ => {
+            let path = url
+                .split(['?', '#'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+            EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+        }
+    }
 }
 
 /// Feed metadata
@@ -109,53 +203,394 @@ impl FeedFetcher {
     pub fn with_timeout(timeout: Duration) -> Result<Self> {
         let client = reqwest::Client::builder()
             .timeout(timeout)
-            .user_agent(format!("Presser/{}", env!("CARGO_PKG_VERSION")))
+            .user_agent(user_agent::default_user_agent())
+            .build()
+            .context("Failed to create HTTP client")?;
+        let direct_client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(user_agent::default_user_agent())
+            .redirect(reqwest::redirect::Policy::none())
             .build()
             .context("Failed to create HTTP client")?;
 
         Ok(Self {
             client,
+            direct_client,
             parser: FeedParser::new(),
             extractor: ContentExtractor::new(),
+            icon_dir: icon::default_cache_dir(),
+            timeout,
+            adapters: AdapterRegistry::builtin(adapters::nitter::DEFAULT_INSTANCE),
+            connectivity: Connectivity::default(),
+            observer: None,
+            user_agent: user_agent::default_user_agent(),
+            cookies: None,
         })
     }
 
+    /// Send `user_agent` with feed requests (see [`user_agent::resolve`] for
+    /// the presets)
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent::resolve(user_agent);
+        self
+    }
+
+    /// User agent sent with feed requests
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// Check connectivity against `address` (`host:port`; empty turns
+    /// offline detection off)
+    pub fn with_connectivity_check(mut self, address: &str) -> Self {
+        self.connectivity = Connectivity::new(address);
+        self
+    }
+
+    /// Whether the network is reachable, to tell a fetch that failed because
+    /// the machine is offline (see [`network::is_connection_error`]) from one
+    /// whose server is down
+    pub async fn is_online(&self) -> bool {
+        self.connectivity.is_online().await
+    }
+
+    /// Report every request the fetcher makes to `observer`
+    pub fn with_request_observer(mut self, observer: RequestObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Send cookies from `jar` with every request, and keep those
+    /// responses set
+    pub fn with_cookie_jar(mut self, jar: Arc<CookieJar>) -> Self {
+        self.cookies = Some(jar);
+        self
+    }
+
+    /// Send a request, reporting it to the observer and passing cookies
+    /// through the jar
+    async fn send(
+        &self,
+        url: &str,
+        mut request: reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        let Some(jar) = &self.cookies else {
+            return telemetry::send(self.observer.as_ref(), url, request).await;
+        };
+        if let Some(cookies) = Url::parse(url).ok().and_then(|url| jar.header_for(&url)) {
+            request = request.header(reqwest::header::COOKIE, cookies);
+        }
+        let response = telemetry::send(self.observer.as_ref(), url, request).await?;
+        if let Err(e) = jar.store(response.url(), response.headers()) {
+            tracing::warn!("Failed to save cookies: {:#}", e);
+        }
+        Ok(response)
+    }
+
+    /// Extract article pages with `extractor` (e.g. one with extra
+    /// boilerplate selectors)
+    pub fn with_extractor(mut self, extractor: ContentExtractor) -> Self {
+        self.extractor = extractor;
+        self
+    }
+
+    /// Use a custom Nitter-style bridge for X/Twitter accounts
+    pub fn with_nitter_instance(self, instance: impl Into<String>) -> Self {
+        let adapter = Arc::new(adapters::NitterAdapter::new(instance));
+        self.with_adapters(adapters::nitter::PATTERNS, adapter)
+    }
+
+    /// Handle addresses matching any of `patterns` with `adapter`, ahead of
+    /// the adapters registered before (see [`AdapterRegistry`])
+    pub fn with_adapters(mut self, patterns: &[&str], adapter: Arc<dyn SourceAdapter>) -> Self {
+        self.register_adapter(patterns, adapter);
+        self
+    }
+
+    /// Like [`with_adapters`](Self::with_adapters), on a fetcher already built
+    pub fn register_adapter(&mut self, patterns: &[&str], adapter: Arc<dyn SourceAdapter>) {
+        self.adapters.register_all(patterns, adapter);
+    }
+
+    /// Map an address to the URL that is actually fetched and the adapter
+    /// handling it
+    ///
+    /// Addresses no adapter handles are fetched as-is.
+    pub fn resolve_url(&self, url: &str) -> (String, Option<Arc<dyn SourceAdapter>>) {
+        match self.adapters.resolve(url) {
+            Some((fetch_url, adapter)) => (fetch_url, Some(adapter)),
+            None => (url.to_string(), None),
+        }
+    }
+
+    /// Let `adapter` adjust a request
+    fn prepare(
+        adapter: Option<&Arc<dyn SourceAdapter>>,
+        request: reqwest::RequestBuilder,
+    ) -> reqwest::RequestBuilder {
+        match adapter {
+            Some(adapter) => adapter.prepare(request),
+            None => request,
+        }
+    }
+
+    /// Parse a fetched body, with `adapter` if there is one
+    fn parse(
+        &self,
+        adapter: Option<&Arc<dyn SourceAdapter>>,
+        body: &[u8],
+    ) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
+        match adapter {
+            Some(adapter) => adapter
+                .parse(&self.parser, body)
+                .with_context(|| format!("{} adapter failed to parse the feed", adapter.name())),
+            None => Ok(self.parser.parse(body)?),
+        }
+    }
+
+    /// Use a custom directory for cached site icons
+    pub fn with_icon_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.icon_dir = dir.into();
+        self
+    }
+
     /// Fetch and parse a feed from the given URL
     ///
     /// Returns the feed metadata and list of entries
     pub async fn fetch(&self, url: &str) -> Result<(FeedMetadata, Vec<FeedEntry>)> {
-        tracing::info!("Fetching feed: {}", url);
+        match self.fetch_conditional(url, &Validators::default()).await? {
+            ConditionalFetch::Modified {
+                metadata, entries, ..
+            } => Ok((*metadata, entries)),
+            ConditionalFetch::NotModified { .. } => {
+                anyhow::bail!(
+                    "Unexpected 304 Not Modified for unconditional fetch of {}",
+                    url
+                )
+            }
+        }
+    }
 
-        let response = self.client
-            .get(url)
-            .send()
+    /// Fetch a feed, revalidating with validators from a previous fetch
+    ///
+    /// Returns `NotModified` when the server answers 304, so callers can skip
+    /// parsing and storing entirely. Redirects are followed here rather than by
+    /// the HTTP client so a feed that moved for good (see
+    /// [`conditional::is_permanent_move`]) is reported with its new URL.
+    pub async fn fetch_conditional(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<ConditionalFetch> {
+        self.fetch_conditional_as(url, validators, &self.user_agent, None)
             .await
-            .map_err(|e| {
+    }
+
+    /// [`fetch_conditional`](Self::fetch_conditional), sending `user_agent`
+    /// instead of the fetcher's own, and `authorization` (a header name and
+    /// value) if given
+    ///
+    /// The authorization header goes only to the feed's own origin, not to
+    /// hosts it redirects to.
+    pub async fn fetch_conditional_as(
+        &self,
+        url: &str,
+        validators: &Validators,
+        user_agent: &str,
+        authorization: Option<(&str, &str)>,
+    ) -> Result<ConditionalFetch> {
+        tracing::info!("Fetching feed: {}", url);
+
+        let (fetch_url, adapter) = self.resolve_url(url);
+        let mut current = Url::parse(&fetch_url)
+            .map_err(|e| FeedError::InvalidUrl(format!("{}: {}", fetch_url, e)))?;
+        // Addresses an adapter rewrites (like a social account's bridge feed)
+        // are kept, wherever the fetched URL moves
+        let mut permanent = fetch_url == url;
+        let mut moved_to = None;
+        let mut redirects = 0;
+        let origin = current.origin();
+        let response = loop {
+            let mut request = self
+                .direct_client
+                .get(current.clone())
+                .header(reqwest::header::USER_AGENT, user_agent);
+            if let Some((name, value)) = authorization.filter(|_| current.origin() == origin) {
+                request = request.header(name, value);
+            }
+            let request = Self::prepare(adapter.as_ref(), validators.apply(request));
+            let response = self.send(current.as_str(), request).await.map_err(|e| {
                 if e.is_timeout() {
                     FeedError::Timeout(url.to_string())
                 } else {
                     FeedError::HttpError(e)
                 }
             })?;
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|location| current.join(location).ok());
+            let Some(next) = location
+                .filter(|_| status.is_redirection() && status != reqwest::StatusCode::NOT_MODIFIED)
+            else {
+                break response;
+            };
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                return Err(FeedError::TooManyRedirects(url.to_string()).into());
+            }
+            permanent &= conditional::is_permanent_move(status, &current, &next);
+            if permanent {
+                moved_to = Some(next.to_string());
+            }
+            current = next;
+        };
 
         let status = response.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            tracing::debug!("Feed not modified: {}", url);
+            return Ok(ConditionalFetch::NotModified { moved_to });
+        }
         if !status.is_success() {
             return Err(FeedError::HttpStatus {
                 url: url.to_string(),
                 status: status.as_u16(),
-            }.into());
+            }
+            .into());
         }
 
-        let bytes = response.bytes().await
-            .map_err(FeedError::HttpError)?;
+        let new_validators = Validators::from_headers(response.headers());
+        let bytes = response.bytes().await.map_err(FeedError::HttpError)?;
+        telemetry::count_bytes(bytes.len());
 
-        let (mut metadata, entries) = self.parser.parse(&bytes)?;
+        let content_hash = conditional::content_hash(&bytes);
+        let (mut metadata, mut entries) = self.parse(adapter.as_ref(), &bytes)?;
 
         if metadata.url.is_empty() {
             metadata.url = url.to_string();
         }
+        if let Some(adapter) = &adapter {
+            adapter.post_process(&mut entries);
+        }
 
-        Ok((metadata, entries))
+        Ok(ConditionalFetch::Modified {
+            status: status.as_u16(),
+            metadata: Box::new(metadata),
+            entries,
+            validators: new_validators,
+            content_hash,
+            moved_to,
+        })
+    }
+
+    /// Probe a URL and return a structured health diagnosis
+    ///
+    /// Network and parse failures are reported in the probe rather than as
+    /// errors; only an unparseable URL is an error.
+    pub async fn probe(&self, url: &str) -> Result<FeedProbe> {
+        Url::parse(url).map_err(|e| FeedError::InvalidUrl(format!("{}: {}", url, e)))?;
+
+        let mut probe = FeedProbe {
+            url: url.to_string(),
+            ..Default::default()
+        };
+
+        let (fetch_url, adapter) = self.resolve_url(url);
+        let request = Self::prepare(adapter.as_ref(), self.client.get(&fetch_url));
+        let response = match self.send(&fetch_url, request).await {
+            Ok(response) => response,
+            Err(e) => {
+                probe.error = Some(if e.is_timeout() {
+                    FeedError::Timeout(url.to_string()).to_string()
+                } else {
+                    FeedError::HttpError(e).to_string()
+                });
+                return Ok(probe);
+            }
+        };
+
+        probe.reachable = true;
+        probe.status = Some(response.status().as_u16());
+        if response.url().as_str() != fetch_url {
+            probe.redirect_target = Some(response.url().to_string());
+        }
+        probe.content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        if !response.status().is_success() {
+            probe.error = Some(
+                FeedError::HttpStatus {
+                    url: url.to_string(),
+                    status: response.status().as_u16(),
+                }
+                .to_string(),
+            );
+            return Ok(probe);
+        }
+
+        let bytes = match response.bytes().await {
+            Ok(bytes) => {
+                telemetry::count_bytes(bytes.len());
+                bytes
+            }
+            Err(e) => {
+                probe.error = Some(FeedError::HttpError(e).to_string());
+                return Ok(probe);
+            }
+        };
+
+        probe.format = self.parser.detect_format(&bytes);
+        match self.parse(adapter.as_ref(), &bytes) {
+            Ok((metadata, entries)) => {
+                let dates: Vec<_> = entries
+                    .iter()
+                    .filter_map(|e| e.published.or(e.updated))
+                    .collect();
+                probe.entry_count = entries.len();
+                probe.last_published = dates.iter().max().copied();
+                probe.avg_interval_hours = probe::average_interval_hours(&dates);
+                probe.metadata = Some(metadata);
+            }
+            Err(e) => {
+                probe.error = Some(if probe.is_html() {
+                    "URL is an HTML page, not a feed".to_string()
+                } else {
+                    format!("{:#}", e)
+                });
+            }
+        }
+
+        Ok(probe)
+    }
+
+    /// Find the feeds a web page advertises (see [`discovery::find_feed_links`])
+    pub async fn discover(&self, page_url: &str) -> Result<Vec<String>> {
+        let page = Url::parse(page_url)
+            .map_err(|e| FeedError::InvalidUrl(format!("{}: {}", page_url, e)))?;
+        let response = self
+            .send(page.as_str(), self.client.get(page.as_str()))
+            .await
+            .map_err(FeedError::HttpError)?;
+        if !response.status().is_success() {
+            return Err(FeedError::HttpStatus {
+                url: page_url.to_string(),
+                status: response.status().as_u16(),
+            }
+            .into());
+        }
+        // Relative links resolve against the page reached after redirects
+        let base = response.url().clone();
+        let html = response.text().await.map_err(FeedError::HttpError)?;
+        telemetry::count_bytes(html.len());
+        Ok(discovery::find_feed_links(&html, &base)
+            .into_iter()
+            .map(String::from)
+            .collect())
     }
 
     /// Fetch and parse a feed, extracting full content for each entry
@@ -175,11 +610,172 @@ impl FeedFetcher {
 
     /// Extract full article content from a URL
     pub async fn extract_content(&self, url: &str) -> Result<String> {
-        tracing::debug!("Extracting content from: {}", url);
+        self.extract_content_with(url, ExtractionBackend::Readability)
+            .await
+    }
+
+    /// Extract full article content from a URL using the given backend
+    pub async fn extract_content_with(
+        &self,
+        url: &str,
+        backend: ExtractionBackend,
+    ) -> Result<String> {
+        Ok(self.extract_article_with(url, backend).await?.text)
+    }
+
+    /// Extract the title and full content of an article page using the given backend
+    pub async fn extract_article_with(
+        &self,
+        url: &str,
+        backend: ExtractionBackend,
+    ) -> Result<Article> {
+        tracing::debug!("Extracting content from: {} ({:?})", url, backend);
+
+        let html = match backend {
+            ExtractionBackend::Readability => self.fetch_html(url).await?,
+            ExtractionBackend::Headless => self.render_headless(url).await?,
+        };
+
+        Ok(self.extractor.extract_article(&html, url)?)
+    }
+
+    #[cfg(feature = "headless")]
+    async fn render_headless(&self, url: &str) -> Result<String> {
+        Ok(headless::render(url, self.timeout).await?)
+    }
+
+    #[cfg(not(feature = "headless"))]
+    async fn render_headless(&self, url: &str) -> Result<String> {
+        Err(FeedError::ExtractionError(format!(
+            "Headless extraction for {} requires building with the `headless` feature",
+            url
+        ))
+        .into())
+    }
+
+    /// Download a page as served, e.g. to archive it
+    pub async fn fetch_page(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .send(url, self.client.get(url))
+            .await
+            .map_err(FeedError::HttpError)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FeedError::HttpStatus {
+                url: url.to_string(),
+                status: status.as_u16(),
+            }
+            .into());
+        }
+
+        let bytes = response.bytes().await.map_err(FeedError::HttpError)?;
+        telemetry::count_bytes(bytes.len());
+        Ok(bytes.to_vec())
+    }
+
+    /// Count the entries of a comment feed (`wfw:commentRss`, Atom
+    /// `rel="replies"`)
+    ///
+    /// Feeds listing only the latest comments undercount long threads.
+    pub async fn count_comments(&self, url: &str) -> Result<i64> {
+        let body = self.fetch_page(url).await?;
+        let (_, entries) = self.parser.parse(&body)?;
+        Ok(entries.len() as i64)
+    }
+
+    /// Fetch a page's served HTML
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let response = self
+            .send(url, self.client.get(url))
+            .await
+            .map_err(FeedError::HttpError)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FeedError::HttpStatus {
+                url: url.to_string(),
+                status: status.as_u16(),
+            }
+            .into());
+        }
+
+        let html = response.text().await.map_err(FeedError::HttpError)?;
+        telemetry::count_bytes(html.len());
+        Ok(html)
+    }
 
-        let response = self.client
-            .get(url)
-            .send()
+    /// Resolve, download, and cache the icon for a site
+    ///
+    /// Returns the cached file path. Candidates are tried in order: icons
+    /// declared in the site's HTML, then `/favicon.ico`.
+    pub async fn fetch_icon(&self, site_url: &str) -> Result<PathBuf> {
+        let site = Url::parse(site_url)
+            .map_err(|e| FeedError::InvalidUrl(format!("{}: {}", site_url, e)))?;
+
+        if let Some(path) = icon::cached_icon(&self.icon_dir, &site) {
+            return Ok(path);
+        }
+
+        let html = match self
+            .send(site.as_str(), self.client.get(site.as_str()))
+            .await
+        {
+            Ok(response) if response.status().is_success() => {
+                let html = response.text().await.unwrap_or_default();
+                telemetry::count_bytes(html.len());
+                html
+            }
+            _ => String::new(),
+        };
+
+        for candidate in icon::find_icon_links(&html, &site) {
+            tracing::debug!("Trying icon candidate: {}", candidate);
+            let response = match self
+                .send(candidate.as_str(), self.client.get(candidate.as_str()))
+                .await
+            {
+                Ok(r) if r.status().is_success() => r,
+                _ => continue,
+            };
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            if content_type
+                .as_deref()
+                .is_some_and(|ct| ct.starts_with("text/html"))
+            {
+                continue;
+            }
+            let bytes = response.bytes().await.map_err(FeedError::HttpError)?;
+            telemetry::count_bytes(bytes.len());
+            if bytes.is_empty() {
+                continue;
+            }
+
+            let ext = icon::icon_extension(content_type.as_deref(), &candidate);
+            tokio::fs::create_dir_all(&self.icon_dir)
+                .await
+                .with_context(|| format!("Failed to create {}", self.icon_dir.display()))?;
+            let path = self
+                .icon_dir
+                .join(format!("{}.{}", icon::cache_key(&site), ext));
+            tokio::fs::write(&path, &bytes)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            return Ok(path);
+        }
+
+        Err(FeedError::ExtractionError(format!("No icon found for {}", site_url)).into())
+    }
+
+    /// Download an image, refusing anything larger than `max_bytes` or not
+    /// served as an image
+    pub async fn fetch_image(&self, url: &str, max_bytes: usize) -> Result<Vec<u8>> {
+        let response = self
+            .send(url, self.client.get(url))
             .await
             .map_err(FeedError::HttpError)?;
 
@@ -188,13 +784,84 @@ impl FeedFetcher {
             return Err(FeedError::HttpStatus {
                 url: url.to_string(),
                 status: status.as_u16(),
-            }.into());
+            }
+            .into());
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if !content_type.is_empty() && !content_type.starts_with("image/") {
+            anyhow::bail!("Not an image ({}): {}", content_type, url);
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len > max_bytes as u64)
+        {
+            anyhow::bail!("Image larger than {} bytes: {}", max_bytes, url);
         }
 
-        let html = response.text().await
+        let bytes = response.bytes().await.map_err(FeedError::HttpError)?;
+        telemetry::count_bytes(bytes.len());
+        if bytes.len() > max_bytes {
+            anyhow::bail!("Image larger than {} bytes: {}", max_bytes, url);
+        }
+        Ok(bytes.to_vec())
+    }
+
+    /// Download a media file (e.g. a podcast episode) to `path`, returning
+    /// the bytes written
+    ///
+    /// `progress` follows the bytes written so far. Downloads may take far
+    /// longer than feed requests, so only [`DOWNLOAD_TIMEOUT`] bounds them.
+    pub async fn download(&self, url: &str, path: &Path, progress: &AtomicU64) -> Result<u64> {
+        let mut response = self
+            .send(url, self.client.get(url).timeout(DOWNLOAD_TIMEOUT))
+            .await
             .map_err(FeedError::HttpError)?;
 
-        Ok(self.extractor.extract(&html, url)?)
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FeedError::HttpStatus {
+                url: url.to_string(),
+                status: status.as_u16(),
+            }
+            .into());
+        }
+
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut written = 0u64;
+        while let Some(chunk) = response.chunk().await.map_err(FeedError::HttpError)? {
+            file.write_all(&chunk)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            telemetry::count_bytes(chunk.len());
+            written += chunk.len() as u64;
+            progress.store(written, Ordering::Relaxed);
+        }
+        file.flush()
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(written)
+    }
+
+    /// Path of an already cached icon for a site, without any network access
+    pub fn icon_path(&self, site_url: &str) -> Option<PathBuf> {
+        let site = Url::parse(site_url).ok()?;
+        icon::cached_icon(&self.icon_dir, &site)
+    }
+
+    /// Directory where site icons are cached
+    pub fn icon_dir(&self) -> &Path {
+        &self.icon_dir
+    }
+
+    /// Request timeout used for fetches and page renders
+    pub fn timeout(&self) -> Duration {
+        self.timeout
     }
 
     /// Get a reference to the HTTP client
@@ -203,7 +870,6 @@ impl FeedFetcher {
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,5 +880,69 @@ mod tests {
         assert!(fetcher.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_cookie_jar() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/login")
+            .with_header("set-cookie", "session=abc123; Path=/")
+            .create_async()
+            .await;
+        let page = server
+            .mock("GET", "/article")
+            .match_header("cookie", "session=abc123")
+            .with_body("<p>Subscribers only</p>")
+            .create_async()
+            .await;
+
+        let jar = Arc::new(CookieJar::new());
+        let fetcher = FeedFetcher::new().unwrap().with_cookie_jar(jar.clone());
+        fetcher
+            .fetch_page(&format!("{}/login", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(jar.len(), 1);
+        fetcher
+            .fetch_page(&format!("{}/article", server.url()))
+            .await
+            .unwrap();
+        page.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_authorization() {
+        let mut origin = mockito::Server::new_async().await;
+        let mut mirror = mockito::Server::new_async().await;
+        let moved = origin
+            .mock("GET", "/notifications.atom")
+            .match_header("authorization", "Bearer abc")
+            .with_status(302)
+            .with_header("location", &format!("{}/feed.atom", mirror.url()))
+            .create_async()
+            .await;
+        // Not sent to another host
+        let feed = mirror
+            .mock("GET", "/feed.atom")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_body(r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Inbox</title></feed>"#)
+            .create_async()
+            .await;
+
+        let fetcher = FeedFetcher::new().unwrap();
+        let url = format!("{}/notifications.atom", origin.url());
+        let fetched = fetcher
+            .fetch_conditional_as(
+                &url,
+                &Validators::default(),
+                "presser",
+                Some(("Authorization", "Bearer abc")),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(fetched, ConditionalFetch::Modified { .. }));
+        moved.assert_async().await;
+        feed.assert_async().await;
+    }
+
     // TODO: Add more tests with mock HTTP responses
 }
@@ -0,0 +1,109 @@
+//! Telling "the network is down" apart from "this feed is down"
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::net::TcpStream;
+
+use crate::FeedError;
+
+/// Address connected to when no other is configured
+pub const DEFAULT_CHECK_ADDRESS: &str = "1.1.1.1:443";
+
+/// How long a check's answer is reused, so a run of failing fetches
+/// checks once
+const CACHE_FOR: Duration = Duration::from_secs(10);
+
+/// Time allowed for the check's connection
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Checks whether the network is up by opening a TCP connection to a
+/// well-known address
+#[derive(Debug)]
+pub struct Connectivity {
+    /// `host:port` to connect to (None: never offline)
+    address: Option<String>,
+    last: Mutex<Option<(Instant, bool)>>,
+}
+
+impl Connectivity {
+    /// Check against `address` (`host:port`; empty turns detection off)
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: Some(address.trim())
+                .filter(|a| !a.is_empty())
+                .map(str::to_string),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Whether the check address accepts a connection
+    pub async fn is_online(&self) -> bool {
+        let Some(address) = &self.address else {
+            return true;
+        };
+        if let Some((at, online)) = *self.last.lock().unwrap() {
+            if at.elapsed() < CACHE_FOR {
+                return online;
+            }
+        }
+        let connect = TcpStream::connect(address.as_str());
+        let online = matches!(
+            tokio::time::timeout(CHECK_TIMEOUT, connect).await,
+            Ok(Ok(_))
+        );
+        *self.last.lock().unwrap() = Some((Instant::now(), online));
+        online
+    }
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHECK_ADDRESS)
+    }
+}
+
+/// Whether a fetch failed without reaching the server (DNS, a refused or
+/// unreachable connection, a timeout), as every fetch does when offline
+pub fn is_connection_error(error: &anyhow::Error) -> bool {
+    let unreachable = |e: &reqwest::Error| e.is_connect() || e.is_timeout();
+    match error.downcast_ref::<FeedError>() {
+        Some(FeedError::Timeout(_)) => true,
+        Some(FeedError::HttpError(e)) => unreachable(e),
+        Some(_) => false,
+        None => error
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(unreachable),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connectivity() {
+        assert!(Connectivity::new("").is_online().await);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        assert!(Connectivity::new(&address).is_online().await);
+        drop(listener);
+        let connectivity = Connectivity::new(&address);
+        assert!(!connectivity.is_online().await);
+
+        // Nothing listens on the port any more, so the request can't connect
+        let error = reqwest::get(format!("http://{}/feed", address))
+            .await
+            .unwrap_err();
+        assert!(is_connection_error(&FeedError::HttpError(error).into()));
+        assert!(is_connection_error(
+            &FeedError::Timeout("https://example.com".into()).into()
+        ));
+        let status = FeedError::HttpStatus {
+            url: "https://example.com".into(),
+            status: 503,
+        };
+        assert!(!is_connection_error(&status.into()));
+    }
+}
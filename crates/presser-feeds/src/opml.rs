@@ -0,0 +1,230 @@
+//! OPML subscription lists
+//!
+//! Other readers export their subscriptions as OPML, with feeds nested in
+//! folder outlines. Presser folders are flat, so a feed lands in the
+//! innermost folder it sits in; `category` entries become tags. Export files
+//! each feed under its folder and lists its tags in `category`.
+
+use crate::FeedError;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// A feed listed in an OPML document
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OpmlFeed {
+    /// Feed URL (`xmlUrl`)
+    pub url: String,
+
+    /// Feed title (`title`, else `text`)
+    pub title: Option<String>,
+
+    /// Website URL (`htmlUrl`)
+    pub site_url: Option<String>,
+
+    /// Innermost enclosing folder outline
+    pub folder: Option<String>,
+
+    /// `category` entries
+    pub tags: Vec<String>,
+}
+
+/// A parsed OPML document
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Opml {
+    /// Document title from `<head>`
+    pub title: Option<String>,
+
+    /// Feeds in document order
+    pub feeds: Vec<OpmlFeed>,
+}
+
+/// Parse an OPML document
+pub fn parse(xml: &str) -> Result<Opml, FeedError> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut opml = Opml::default();
+    // One item per open outline: the folder name, or None for a feed outline
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut in_title = false;
+    loop {
+        match reader.read_event().map_err(parse_error)? {
+            Event::Start(element) if element.name().as_ref() == b"outline" => {
+                let attrs = attributes(&reader, &element)?;
+                match feed_from(&attrs, &folders) {
+                    Some(feed) => {
+                        opml.feeds.push(feed);
+                        folders.push(None);
+                    }
+                    None => folders.push(attr(&attrs, "title").or_else(|| attr(&attrs, "text"))),
+                }
+            }
+            Event::Empty(element) if element.name().as_ref() == b"outline" => {
+                opml.feeds
+                    .extend(feed_from(&attributes(&reader, &element)?, &folders));
+            }
+            Event::End(element) if element.name().as_ref() == b"outline" => {
+                folders.pop();
+            }
+            Event::Start(element) if element.name().as_ref() == b"title" => in_title = true,
+            Event::End(element) if element.name().as_ref() == b"title" => in_title = false,
+            Event::Text(text) if in_title => {
+                opml.title = Some(text.unescape().map_err(parse_error)?.into_owned());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(opml)
+}
+
+/// Render feeds as an OPML 2.0 document
+pub fn write(title: &str, feeds: &[OpmlFeed]) -> String {
+    let mut out =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str(&format!(
+        "  <head>\n    <title>{}</title>\n  </head>\n  <body>\n",
+        escape(title)
+    ));
+
+    let mut folders: Vec<&str> = Vec::new();
+    for feed in feeds {
+        if let Some(folder) = feed.folder.as_deref() {
+            if !folders.contains(&folder) {
+                folders.push(folder);
+            }
+        }
+    }
+    for folder in folders {
+        out.push_str(&format!(
+            "    <outline text=\"{0}\" title=\"{0}\">\n",
+            escape(folder)
+        ));
+        for feed in feeds.iter().filter(|f| f.folder.as_deref() == Some(folder)) {
+            out.push_str(&format!("      {}\n", feed_outline(feed)));
+        }
+        out.push_str("    </outline>\n");
+    }
+    for feed in feeds.iter().filter(|f| f.folder.is_none()) {
+        out.push_str(&format!("    {}\n", feed_outline(feed)));
+    }
+
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+fn feed_outline(feed: &OpmlFeed) -> String {
+    let title = escape(feed.title.as_deref().unwrap_or(&feed.url)).into_owned();
+    let mut outline = format!(
+        "<outline type=\"rss\" text=\"{0}\" title=\"{0}\" xmlUrl=\"{1}\"",
+        title,
+        escape(&feed.url)
+    );
+    if let Some(site_url) = &feed.site_url {
+        outline.push_str(&format!(" htmlUrl=\"{}\"", escape(site_url)));
+    }
+    if !feed.tags.is_empty() {
+        outline.push_str(&format!(" category=\"{}\"", escape(&feed.tags.join(","))));
+    }
+    outline.push_str("/>");
+    outline
+}
+
+/// Feed described by an outline's attributes (None for a folder)
+fn feed_from(attrs: &[(String, String)], folders: &[Option<String>]) -> Option<OpmlFeed> {
+    let url = attr(attrs, "xmlurl")?;
+    let mut tags: Vec<String> = Vec::new();
+    // `category` is a comma-separated list of slash-delimited paths
+    for category in attr(attrs, "category").unwrap_or_default().split(',') {
+        tags.extend(
+            category
+                .split('/')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(String::from),
+        );
+    }
+    let mut seen = std::collections::HashSet::new();
+    tags.retain(|tag| seen.insert(tag.clone()));
+
+    Some(OpmlFeed {
+        url,
+        title: attr(attrs, "title").or_else(|| attr(attrs, "text")),
+        site_url: attr(attrs, "htmlurl"),
+        folder: folders.iter().rev().flatten().next().cloned(),
+        tags,
+    })
+}
+
+/// Non-empty attribute value (names compared case-insensitively; exporters disagree on `xmlUrl`)
+fn attr(attrs: &[(String, String)], name: &str) -> Option<String> {
+    attrs
+        .iter()
+        .find(|(key, value)| key.eq_ignore_ascii_case(name) && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+fn attributes(
+    reader: &Reader<&[u8]>,
+    element: &BytesStart,
+) -> Result<Vec<(String, String)>, FeedError> {
+    crate::xml::attributes(reader, element).map_err(parse_error)
+}
+
+fn parse_error(e: impl std::fmt::Display) -> FeedError {
+    FeedError::ParseError(format!("Invalid OPML: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_round_trip() {
+        let xml = r#"<?xml version="1.0"?>
+<opml version="1.0">
+  <head><title>My &amp; Feeds</title></head>
+  <body>
+    <outline text="Tech">
+      <outline text="Rust">
+        <outline text="Rust Blog" xmlUrl="https://blog.rust-lang.org/feed.xml" htmlUrl="https://blog.rust-lang.org/"/>
+      </outline>
+      <outline title="HN" xmlurl="https://hnrss.org/frontpage" category="/news/daily,Tech"/>
+    </outline>
+    <outline text="Unfiled" xmlUrl="https://example.com/feed"/>
+  </body>
+</opml>"#;
+        let opml = parse(xml).unwrap();
+
+        assert_eq!(opml.title.as_deref(), Some("My & Feeds"));
+        assert_eq!(
+            opml.feeds,
+            [
+                OpmlFeed {
+                    url: "https://blog.rust-lang.org/feed.xml".into(),
+                    title: Some("Rust Blog".into()),
+                    site_url: Some("https://blog.rust-lang.org/".into()),
+                    folder: Some("Rust".into()),
+                    tags: Vec::new(),
+                },
+                OpmlFeed {
+                    url: "https://hnrss.org/frontpage".into(),
+                    title: Some("HN".into()),
+                    site_url: None,
+                    folder: Some("Tech".into()),
+                    tags: vec!["news".into(), "daily".into(), "Tech".into()],
+                },
+                OpmlFeed {
+                    url: "https://example.com/feed".into(),
+                    title: Some("Unfiled".into()),
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let written = write("Presser & co", &opml.feeds);
+        assert!(written.contains("<title>Presser &amp; co</title>"));
+        assert_eq!(parse(&written).unwrap().feeds, opml.feeds);
+    }
+}
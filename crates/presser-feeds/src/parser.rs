@@ -1,6 +1,8 @@
 //! Feed parsing implementation
 
-use crate::{ContentExtractor, FeedEntry, FeedError, FeedMetadata};
+use crate::{
+    discussion, ContentExtractor, FeedEnclosure, FeedEntry, FeedError, FeedFormat, FeedMetadata,
+};
 use anyhow::Result;
 use feed_rs::parser;
 use sha2::{Digest, Sha256};
@@ -18,56 +20,129 @@ impl FeedParser {
 
     /// Parse feed XML/content into metadata and entries
     pub fn parse(&self, content: &[u8]) -> Result<(FeedMetadata, Vec<FeedEntry>), FeedError> {
-        let feed = parser::parse(content)
-            .map_err(|e| FeedError::ParseError(e.to_string()))?;
+        let feed = parser::parse(content).map_err(|e| FeedError::ParseError(e.to_string()))?;
 
         let metadata = FeedMetadata {
             title: feed.title.map(|t| t.content).unwrap_or_default(),
             description: feed.description.map(|t| t.content),
-            url: feed.links.first().map(|l| l.href.clone()).unwrap_or_default(),
-            site_url: feed.links.iter()
+            url: feed
+                .links
+                .first()
+                .map(|l| l.href.clone())
+                .unwrap_or_default(),
+            site_url: feed
+                .links
+                .iter()
                 .find(|l| l.rel.as_deref() == Some("alternate"))
                 .map(|l| l.href.clone()),
             last_updated: feed.updated,
         };
 
         let extractor = ContentExtractor::new();
-        let entries = feed.entries.into_iter().map(|entry| {
-            let id = if entry.id.is_empty() {
-                // Generate stable ID from URL, title, and published date
-                let url = entry.links.first().map(|l| l.href.as_str()).unwrap_or("");
-                let title = entry.title.as_ref().map(|t| t.content.as_str()).unwrap_or("");
-                let published = entry.published.map(|d| d.to_rfc3339()).unwrap_or_default();
-                let mut hasher = Sha256::new();
-                hasher.update(format!("{}|{}|{}", url, title, published).as_bytes());
-                format!("{:x}", hasher.finalize())
-            } else {
-                entry.id
-            };
-
-            let summary_html = entry.summary.map(|t| t.content);
-            let content_html = entry.content.and_then(|c| c.body);
-
-            let content_text = content_html.as_ref()
-                .or(summary_html.as_ref())
-                .map(|html| extractor.html_to_text(html, FEED_TEXT_WIDTH));
-
-            FeedEntry {
-                id,
-                title: entry.title.map(|t| t.content).unwrap_or_default(),
-                url: entry.links.first().map(|l| l.href.clone()).unwrap_or_default(),
-                published: entry.published,
-                updated: entry.updated,
-                summary: summary_html,
-                content_html,
-                content_text,
-                author: entry.authors.first().map(|p| p.name.clone()),
-                categories: entry.categories.iter().map(|c| c.term.clone()).collect(),
-            }
-        }).collect();
+        let mut entries: Vec<FeedEntry> = feed
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let enclosures = enclosures(&entry);
+                let id = if entry.id.is_empty() {
+                    // Generate stable ID from URL, title, and published date
+                    let url = entry.links.first().map(|l| l.href.as_str()).unwrap_or("");
+                    let title = entry
+                        .title
+                        .as_ref()
+                        .map(|t| t.content.as_str())
+                        .unwrap_or("");
+                    let published = entry.published.map(|d| d.to_rfc3339()).unwrap_or_default();
+                    let mut hasher = Sha256::new();
+                    hasher.update(format!("{}|{}|{}", url, title, published).as_bytes());
+                    format!("{:x}", hasher.finalize())
+                } else {
+                    entry.id
+                };
+
+                let summary_html = entry.summary.map(|t| t.content);
+                let content_html = entry.content.and_then(|c| c.body);
+
+                let content_text = content_html
+                    .as_ref()
+                    .or(summary_html.as_ref())
+                    .map(|html| extractor.html_to_text(html, FEED_TEXT_WIDTH));
+
+                FeedEntry {
+                    id,
+                    title: entry.title.map(|t| t.content).unwrap_or_default(),
+                    url: entry
+                        .links
+                        .first()
+                        .map(|l| l.href.clone())
+                        .unwrap_or_default(),
+                    published: entry.published,
+                    updated: entry.updated,
+                    summary: summary_html,
+                    content_html,
+                    content_text,
+                    author: entry.authors.first().map(|p| p.name.clone()),
+                    categories: entry.categories.iter().map(|c| c.term.clone()).collect(),
+                    discussion: None,
+                    enclosures,
+                }
+            })
+            .collect();
+
+        // feed-rs drops comment elements, so they are read separately
+        let mut scanned = discussion::scan(content);
+        if scanned.len() != entries.len() {
+            scanned.clear();
+        }
+        let mut scanned = scanned.into_iter();
+        for entry in &mut entries {
+            entry.discussion = discussion::merge(scanned.next(), entry.summary.as_deref());
+        }
 
         Ok((metadata, entries))
     }
+
+    /// Detect the syndication format of the content, if it is a feed at all
+    pub fn detect_format(&self, content: &[u8]) -> Option<FeedFormat> {
+        parser::parse(content)
+            .ok()
+            .map(|feed| feed.feed_type.into())
+    }
+}
+
+/// Media files attached to an entry, each address once
+///
+/// feed-rs reads RSS `<enclosure>` and `<media:content>` into media objects;
+/// Atom enclosures stay links.
+fn enclosures(entry: &feed_rs::model::Entry) -> Vec<FeedEnclosure> {
+    let media = entry
+        .media
+        .iter()
+        .flat_map(|object| &object.content)
+        .filter_map(|content| {
+            Some(FeedEnclosure {
+                url: content.url.as_ref()?.to_string(),
+                mime_type: content.content_type.as_ref().map(|mime| mime.to_string()),
+                length: content.size.and_then(|size| i64::try_from(size).ok()),
+            })
+        });
+    let links = entry
+        .links
+        .iter()
+        .filter(|link| link.rel.as_deref() == Some("enclosure"))
+        .map(|link| FeedEnclosure {
+            url: link.href.clone(),
+            mime_type: link.media_type.clone(),
+            length: link.length.and_then(|length| i64::try_from(length).ok()),
+        });
+
+    let mut enclosures: Vec<FeedEnclosure> = Vec::new();
+    for enclosure in media.chain(links) {
+        if !enclosures.iter().any(|e| e.url == enclosure.url) {
+            enclosures.push(enclosure);
+        }
+    }
+    enclosures
 }
 
 impl Default for FeedParser {
@@ -116,6 +191,8 @@ mod tests {
         assert_eq!(entries[0].title, "Test Entry");
         assert_eq!(entries[0].url, "https://example.com/entry1");
         assert_eq!(entries[0].id, "entry-1");
+        assert_eq!(parser.detect_format(rss.as_bytes()), Some(FeedFormat::Rss2));
+        assert_eq!(parser.detect_format(b"<html></html>"), None);
     }
 
     #[test]
@@ -153,6 +230,55 @@ mod tests {
         assert_eq!(entries[0].author, Some("Test Author".to_string()));
     }
 
+    #[test]
+    fn test_parse_enclosures() {
+        let rss = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Podcast</title>
+    <item>
+      <title>Episode 1</title>
+      <guid>ep-1</guid>
+      <enclosure url="https://cdn.example.com/ep1.mp3" type="audio/mpeg" length="1234567"/>
+    </item>
+    <item>
+      <title>Episode 2</title>
+      <guid>ep-2</guid>
+    </item>
+  </channel>
+</rss>"#;
+        let (_, entries) = FeedParser::new().parse(rss.as_bytes()).unwrap();
+        assert_eq!(
+            entries[0].enclosures,
+            [FeedEnclosure {
+                url: "https://cdn.example.com/ep1.mp3".into(),
+                mime_type: Some("audio/mpeg".into()),
+                length: Some(1234567),
+            }]
+        );
+        assert!(entries[0].enclosures[0].is_audio());
+        assert!(entries[1].enclosures.is_empty());
+
+        let atom = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Podcast</title>
+  <entry>
+    <title>Episode</title>
+    <id>atom-ep</id>
+    <updated>2024-01-01T12:00:00Z</updated>
+    <link href="https://example.com/ep"/>
+    <link rel="enclosure" href="https://cdn.example.com/ep.ogg" type="audio/ogg" length="42"/>
+  </entry>
+</feed>"#;
+        let (_, entries) = FeedParser::new().parse(atom.as_bytes()).unwrap();
+        assert_eq!(entries[0].enclosures.len(), 1);
+        assert_eq!(
+            entries[0].enclosures[0].url,
+            "https://cdn.example.com/ep.ogg"
+        );
+        assert_eq!(entries[0].enclosures[0].length, Some(42));
+    }
+
     #[test]
     fn test_parse_missing_fields() {
         let minimal_rss = r#"<?xml version="1.0" encoding="UTF-8"?>
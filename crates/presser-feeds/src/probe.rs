@@ -0,0 +1,174 @@
+//! Feed health probing
+//!
+//! A probe fetches a URL once and reports a structured diagnosis instead of
+//! failing on the first problem, so callers can explain *why* a feed is broken.
+
+use crate::FeedMetadata;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Syndication format detected by the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    Atom,
+    Json,
+    Rss0,
+    Rss1,
+    Rss2,
+}
+
+impl From<feed_rs::model::FeedType> for FeedFormat {
+    fn from(feed_type: feed_rs::model::FeedType) -> Self {
+        use feed_rs::model::FeedType;
+        match feed_type {
+            FeedType::Atom => Self::Atom,
+            FeedType::JSON => Self::Json,
+            FeedType::RSS0 => Self::Rss0,
+            FeedType::RSS1 => Self::Rss1,
+            FeedType::RSS2 => Self::Rss2,
+        }
+    }
+}
+
+impl fmt::Display for FeedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Atom => "Atom",
+            Self::Json => "JSON Feed",
+            Self::Rss0 => "RSS 0.9x",
+            Self::Rss1 => "RSS 1.0",
+            Self::Rss2 => "RSS 2.0",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Structured diagnosis of a feed URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedProbe {
+    /// URL that was probed
+    pub url: String,
+
+    /// Whether the server answered at all
+    pub reachable: bool,
+
+    /// HTTP status of the final response
+    pub status: Option<u16>,
+
+    /// Final URL when the request was redirected
+    pub redirect_target: Option<String>,
+
+    /// Response content type
+    pub content_type: Option<String>,
+
+    /// Detected feed format (None if the body is not a feed)
+    pub format: Option<FeedFormat>,
+
+    /// Parsed feed metadata
+    pub metadata: Option<FeedMetadata>,
+
+    /// Number of entries in the feed
+    pub entry_count: usize,
+
+    /// Most recent entry publication date
+    pub last_published: Option<DateTime<Utc>>,
+
+    /// Average hours between consecutive entries
+    pub avg_interval_hours: Option<f64>,
+
+    /// First problem encountered, if any
+    pub error: Option<String>,
+}
+
+impl FeedProbe {
+    /// Whether the URL serves a parseable feed
+    pub fn is_valid_feed(&self) -> bool {
+        self.format.is_some()
+    }
+
+    /// Whether the body looks like an HTML page rather than a feed
+    pub fn is_html(&self) -> bool {
+        self.content_type
+            .as_deref()
+            .is_some_and(|ct| ct.to_lowercase().contains("text/html"))
+    }
+}
+
+/// Average interval in hours between publication dates (None with fewer than two dates)
+pub fn average_interval_hours(dates: &[DateTime<Utc>]) -> Option<f64> {
+    let (first, last) = (dates.iter().min()?, dates.iter().max()?);
+    if dates.len() < 2 {
+        return None;
+    }
+    let span = (*last - *first).num_seconds() as f64 / 3600.0;
+    Some(span / (dates.len() - 1) as f64)
+}
+
+impl fmt::Display for FeedProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "URL:        {}", self.url)?;
+        match self.status {
+            Some(status) => writeln!(f, "Reachable:  yes (HTTP {})", status)?,
+            None => writeln!(
+                f,
+                "Reachable:  {}",
+                if self.reachable { "yes" } else { "no" }
+            )?,
+        }
+        if let Some(target) = &self.redirect_target {
+            writeln!(f, "Redirects:  {}", target)?;
+        }
+        match self.format {
+            Some(format) => writeln!(f, "Format:     {}", format)?,
+            None => writeln!(f, "Format:     not a feed")?,
+        }
+        if let Some(title) = self.metadata.as_ref().map(|m| m.title.as_str()) {
+            writeln!(f, "Title:      {}", title)?;
+        }
+        writeln!(f, "Entries:    {}", self.entry_count)?;
+        if let Some(date) = self.last_published {
+            writeln!(f, "Latest:     {}", date.format("%Y-%m-%d %H:%M UTC"))?;
+        }
+        if let Some(hours) = self.avg_interval_hours {
+            if hours >= 48.0 {
+                writeln!(f, "Frequency:  every {:.1} days", hours / 24.0)?;
+            } else {
+                writeln!(f, "Frequency:  every {:.1} hours", hours)?;
+            }
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "Problem:    {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_average_interval() {
+        let dates = vec![
+            Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+        ];
+        assert_eq!(average_interval_hours(&dates), Some(24.0));
+        assert_eq!(average_interval_hours(&dates[..1]), None);
+        assert_eq!(average_interval_hours(&[]), None);
+    }
+
+    #[test]
+    fn test_probe_flags() {
+        let probe = FeedProbe {
+            content_type: Some("text/html; charset=utf-8".into()),
+            ..Default::default()
+        };
+        assert!(probe.is_html());
+        assert!(!probe.is_valid_feed());
+    }
+}
@@ -0,0 +1,220 @@
+//! Feedly cloud API (`/v3`, developer access token)
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{base, file_under, get_json, ReaderExport, ReaderItem, PAGE_SIZE};
+use crate::opml::OpmlFeed;
+use crate::FeedError;
+
+/// Public API endpoint
+pub const DEFAULT_URL: &str = "https://cloud.feedly.com";
+
+/// Prefix of subscription IDs and origin stream IDs, followed by the feed URL
+const FEED_PREFIX: &str = "feed/";
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Subscription {
+    id: String,
+    title: Option<String>,
+    website: Option<String>,
+    #[serde(default)]
+    categories: Vec<Label>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    label: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Stream {
+    #[serde(default)]
+    items: Vec<Item>,
+    continuation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    title: Option<String>,
+    /// Milliseconds since the epoch
+    published: Option<i64>,
+    #[serde(default)]
+    alternate: Vec<Link>,
+    #[serde(rename = "originId")]
+    origin_id: Option<String>,
+    origin: Option<Origin>,
+    #[serde(default)]
+    unread: bool,
+    #[serde(default)]
+    tags: Vec<Tag>,
+    content: Option<Content>,
+    summary: Option<Content>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Origin {
+    #[serde(rename = "streamId")]
+    stream_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tag {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    content: String,
+}
+
+pub(super) async fn export(
+    client: &reqwest::Client,
+    url: &str,
+    token: &str,
+    limit: usize,
+) -> Result<ReaderExport, FeedError> {
+    let api = format!("{}/v3", base(url));
+    let auth = format!("OAuth {}", token);
+    let profile: Profile = get_json(
+        client
+            .get(format!("{}/profile", api))
+            .header("Authorization", &auth),
+    )
+    .await?;
+    let subscriptions: Vec<Subscription> = get_json(
+        client
+            .get(format!("{}/subscriptions", api))
+            .header("Authorization", &auth),
+    )
+    .await?;
+
+    let mut items = Vec::new();
+    for stream in ["tag/global.saved", "category/global.all"] {
+        let stream_id = format!("user/{}/{}", profile.id, stream);
+        let mut continuation: Option<String> = None;
+        let mut fetched = 0;
+        while fetched < limit {
+            let mut request = client
+                .get(format!("{}/streams/contents", api))
+                .header("Authorization", &auth)
+                .query(&[
+                    ("streamId", stream_id.as_str()),
+                    ("count", &PAGE_SIZE.min(limit - fetched).to_string()),
+                ]);
+            if let Some(c) = &continuation {
+                request = request.query(&[("continuation", c)]);
+            }
+            let page: Stream = get_json(request).await?;
+            fetched += page.items.len();
+            let done = page.items.is_empty() || page.continuation.is_none();
+            items.extend(page.items);
+            if done {
+                break;
+            }
+            continuation = page.continuation;
+        }
+    }
+    Ok(convert(subscriptions, items))
+}
+
+fn convert(subscriptions: Vec<Subscription>, items: Vec<Item>) -> ReaderExport {
+    let feeds = subscriptions
+        .into_iter()
+        .filter_map(|s| {
+            let url = s.id.strip_prefix(FEED_PREFIX)?.to_string();
+            let (folder, tags) = file_under(s.categories.iter().map(|c| c.label.as_str()));
+            Some(OpmlFeed {
+                folder,
+                tags,
+                title: s.title.or_else(|| Some(url.clone())),
+                site_url: s.website,
+                url,
+            })
+        })
+        .collect();
+    let items = items
+        .into_iter()
+        .filter_map(|item| {
+            let feed_url = item
+                .origin?
+                .stream_id
+                .strip_prefix(FEED_PREFIX)?
+                .to_string();
+            // Feedly's origin ID is the publisher's GUID, often the article URL
+            let url = item
+                .alternate
+                .into_iter()
+                .next()
+                .map(|l| l.href)
+                .or(item.origin_id.filter(|id| id.starts_with("http")))?;
+            Some(ReaderItem {
+                feed_url,
+                url,
+                title: item.title.unwrap_or_default(),
+                published: item
+                    .published
+                    .and_then(DateTime::<Utc>::from_timestamp_millis),
+                content_html: item.content.or(item.summary).map(|c| c.content),
+                read: !item.unread,
+                starred: item
+                    .tags
+                    .iter()
+                    .any(|t| t.id.ends_with("/tag/global.saved")),
+            })
+        })
+        .collect();
+    ReaderExport { feeds, items }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let subscriptions: Vec<Subscription> = serde_json::from_str(
+            r#"[{"id": "feed/https://blog.rust-lang.org/feed.xml", "title": "Rust Blog",
+                 "website": "https://blog.rust-lang.org/",
+                 "categories": [{"id": "user/u1/category/tech", "label": "Tech"}]}]"#,
+        )
+        .unwrap();
+        let stream: Stream = serde_json::from_str(
+            r#"{"items": [
+                {"id": "e1", "title": "A", "published": 1710504000000, "unread": false,
+                 "originId": "https://blog.rust-lang.org/a",
+                 "origin": {"streamId": "feed/https://blog.rust-lang.org/feed.xml"},
+                 "tags": [{"id": "user/u1/tag/global.saved"}]},
+                {"id": "e2", "title": "B", "unread": true,
+                 "alternate": [{"href": "https://blog.rust-lang.org/b", "type": "text/html"}],
+                 "origin": {"streamId": "feed/https://blog.rust-lang.org/feed.xml"}}]}"#,
+        )
+        .unwrap();
+
+        let export = convert(subscriptions, stream.items);
+        assert_eq!(export.feeds[0].url, "https://blog.rust-lang.org/feed.xml");
+        assert_eq!(export.feeds[0].folder.as_deref(), Some("Tech"));
+        let flags: Vec<_> = export
+            .items
+            .iter()
+            .map(|i| (i.url.as_str(), i.read, i.starred))
+            .collect();
+        assert_eq!(
+            flags,
+            [
+                ("https://blog.rust-lang.org/a", true, true),
+                ("https://blog.rust-lang.org/b", false, false)
+            ]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! FreshRSS, through its Google Reader compatible API (`/api/greader.php`)
+//!
+//! The API password is the one set under Profile > API management, not the
+//! login password.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{base, file_under, get_json, ReaderExport, ReaderItem, PAGE_SIZE};
+use crate::opml::OpmlFeed;
+use crate::FeedError;
+
+/// Stream of every entry in every subscription
+const READING_LIST: &str = "user/-/state/com.google/reading-list";
+
+/// Stream (and item category) of starred entries
+const STARRED: &str = "user/-/state/com.google/starred";
+
+/// Suffixes of the categories marking an item read or starred
+const READ_STATE: &str = "/state/com.google/read";
+const STARRED_STATE: &str = "/state/com.google/starred";
+
+#[derive(Debug, Deserialize)]
+struct SubscriptionList {
+    subscriptions: Vec<Subscription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Subscription {
+    id: String,
+    title: String,
+    url: String,
+    html_url: Option<String>,
+    #[serde(default)]
+    categories: Vec<Label>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    label: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Stream {
+    #[serde(default)]
+    items: Vec<Item>,
+    continuation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    #[serde(default)]
+    title: String,
+    published: Option<i64>,
+    #[serde(default)]
+    canonical: Vec<Link>,
+    #[serde(default)]
+    alternate: Vec<Link>,
+    #[serde(default)]
+    categories: Vec<String>,
+    origin: Origin,
+    summary: Option<Content>,
+    content: Option<Content>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Origin {
+    stream_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    content: String,
+}
+
+pub(super) async fn export(
+    client: &reqwest::Client,
+    url: &str,
+    user: &str,
+    password: &str,
+    limit: usize,
+) -> Result<ReaderExport, FeedError> {
+    let api = format!("{}/api/greader.php", base(url));
+    let auth = login(client, &api, user, password).await?;
+
+    let list: SubscriptionList = get_json(
+        client
+            .get(format!("{}/reader/api/0/subscription/list", api))
+            .header("Authorization", &auth)
+            .query(&[("output", "json")]),
+    )
+    .await?;
+
+    let mut items = Vec::new();
+    for stream in [STARRED, READING_LIST] {
+        let mut continuation: Option<String> = None;
+        let mut fetched = 0;
+        while fetched < limit {
+            let mut request = client
+                .get(format!("{}/reader/api/0/stream/contents/{}", api, stream))
+                .header("Authorization", &auth)
+                .query(&[
+                    ("output", "json"),
+                    ("n", &PAGE_SIZE.min(limit - fetched).to_string()),
+                ]);
+            if let Some(c) = &continuation {
+                request = request.query(&[("c", c)]);
+            }
+            let page: Stream = get_json(request).await?;
+            fetched += page.items.len();
+            let done = page.items.is_empty() || page.continuation.is_none();
+            items.extend(page.items);
+            if done {
+                break;
+            }
+            continuation = page.continuation;
+        }
+    }
+    Ok(convert(list.subscriptions, items))
+}
+
+/// `ClientLogin` for the `Authorization` header value
+async fn login(
+    client: &reqwest::Client,
+    api: &str,
+    user: &str,
+    password: &str,
+) -> Result<String, FeedError> {
+    let url = format!("{}/accounts/ClientLogin", api);
+    let response = client
+        .post(&url)
+        .form(&[("Email", user), ("Passwd", password)])
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(FeedError::HttpStatus {
+            url,
+            status: status.as_u16(),
+        });
+    }
+    let body = response.text().await?;
+    body.lines()
+        .find_map(|line| line.strip_prefix("Auth="))
+        .map(|token| format!("GoogleLogin auth={}", token.trim()))
+        .ok_or_else(|| FeedError::Other(anyhow::anyhow!("FreshRSS login returned no token")))
+}
+
+fn convert(subscriptions: Vec<Subscription>, items: Vec<Item>) -> ReaderExport {
+    let feed_urls: HashMap<&str, &str> = subscriptions
+        .iter()
+        .map(|s| (s.id.as_str(), s.url.as_str()))
+        .collect();
+    let items = items
+        .into_iter()
+        .filter_map(|item| {
+            let url = item
+                .canonical
+                .first()
+                .or(item.alternate.first())?
+                .href
+                .clone();
+            let feed_url = feed_urls.get(item.origin.stream_id.as_str())?.to_string();
+            Some(ReaderItem {
+                feed_url,
+                url,
+                read: item.categories.iter().any(|c| c.ends_with(READ_STATE)),
+                starred: item.categories.iter().any(|c| c.ends_with(STARRED_STATE)),
+                title: item.title,
+                published: item
+                    .published
+                    .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0)),
+                content_html: item.content.or(item.summary).map(|c| c.content),
+            })
+        })
+        .collect();
+    let feeds = subscriptions
+        .into_iter()
+        .map(|s| {
+            let (folder, tags) = file_under(s.categories.iter().map(|c| c.label.as_str()));
+            OpmlFeed {
+                folder,
+                tags,
+                url: s.url,
+                title: Some(s.title),
+                site_url: s.html_url.filter(|u| !u.is_empty()),
+            }
+        })
+        .collect();
+    ReaderExport { feeds, items }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let list: SubscriptionList = serde_json::from_str(
+            r#"{"subscriptions": [{"id": "feed/3", "title": "Rust Blog",
+                "url": "https://blog.rust-lang.org/feed.xml", "htmlUrl": "https://blog.rust-lang.org/",
+                "categories": [{"id": "user/-/label/Tech", "label": "Tech"}]}]}"#,
+        )
+        .unwrap();
+        let stream: Stream = serde_json::from_str(
+            r#"{"items": [
+                {"id": "tag:google.com,2005:reader/item/1", "title": "A", "published": 1710504000,
+                 "canonical": [{"href": "https://blog.rust-lang.org/a"}],
+                 "categories": ["user/-/state/com.google/reading-list", "user/-/state/com.google/read",
+                                "user/-/state/com.google/starred"],
+                 "origin": {"streamId": "feed/3"}, "summary": {"content": "<p>A</p>"}},
+                {"id": "tag:google.com,2005:reader/item/2", "title": "Gone", "published": 1710504000,
+                 "alternate": [{"href": "https://elsewhere.example/b"}], "categories": [],
+                 "origin": {"streamId": "feed/99"}}]}"#,
+        )
+        .unwrap();
+
+        let export = convert(list.subscriptions, stream.items);
+        assert_eq!(export.feeds[0].folder.as_deref(), Some("Tech"));
+        assert_eq!(export.items.len(), 1);
+        let item = &export.items[0];
+        assert!(item.read && item.starred);
+        assert_eq!(item.feed_url, "https://blog.rust-lang.org/feed.xml");
+        assert_eq!(item.content_html.as_deref(), Some("<p>A</p>"));
+        assert_eq!(
+            item.published.unwrap().to_rfc3339(),
+            "2024-03-15T12:00:00+00:00"
+        );
+    }
+}
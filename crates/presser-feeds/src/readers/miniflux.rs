@@ -0,0 +1,149 @@
+//! Miniflux (`/v1` REST API, `X-Auth-Token` authentication)
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{base, file_under, get_json, ReaderExport, ReaderItem, PAGE_SIZE};
+use crate::opml::OpmlFeed;
+use crate::FeedError;
+
+#[derive(Debug, Deserialize)]
+struct Feed {
+    feed_url: String,
+    title: String,
+    site_url: Option<String>,
+    category: Option<Category>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Category {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntryPage {
+    total: usize,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Entry {
+    url: String,
+    title: String,
+    status: String,
+    starred: bool,
+    published_at: Option<DateTime<Utc>>,
+    content: Option<String>,
+    feed: EntryFeed,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntryFeed {
+    feed_url: String,
+}
+
+pub(super) async fn export(
+    client: &reqwest::Client,
+    url: &str,
+    token: &str,
+    limit: usize,
+) -> Result<ReaderExport, FeedError> {
+    let api = format!("{}/v1", base(url));
+    let feeds: Vec<Feed> = get_json(
+        client
+            .get(format!("{}/feeds", api))
+            .header("X-Auth-Token", token),
+    )
+    .await?;
+
+    let mut entries = Vec::new();
+    for starred_only in [true, false] {
+        let mut offset = 0;
+        while offset < limit {
+            let mut request = client
+                .get(format!("{}/entries", api))
+                .header("X-Auth-Token", token)
+                .query(&[
+                    ("order", "published_at"),
+                    ("direction", "desc"),
+                    ("limit", &PAGE_SIZE.min(limit - offset).to_string()),
+                    ("offset", &offset.to_string()),
+                ]);
+            if starred_only {
+                request = request.query(&[("starred", "true")]);
+            }
+            let page: EntryPage = get_json(request).await?;
+            let count = page.entries.len();
+            entries.extend(page.entries);
+            offset += count;
+            if count == 0 || offset >= page.total {
+                break;
+            }
+        }
+    }
+    Ok(convert(feeds, entries))
+}
+
+fn convert(feeds: Vec<Feed>, entries: Vec<Entry>) -> ReaderExport {
+    ReaderExport {
+        feeds: feeds
+            .into_iter()
+            .map(|feed| {
+                let (folder, tags) = file_under(feed.category.as_ref().map(|c| c.title.as_str()));
+                OpmlFeed {
+                    folder,
+                    tags,
+                    url: feed.feed_url,
+                    title: Some(feed.title),
+                    site_url: feed.site_url.filter(|s| !s.is_empty()),
+                }
+            })
+            .collect(),
+        items: entries
+            .into_iter()
+            .filter(|entry| entry.status != "removed")
+            .map(|entry| ReaderItem {
+                feed_url: entry.feed.feed_url,
+                read: entry.status == "read",
+                url: entry.url,
+                title: entry.title,
+                published: entry.published_at,
+                content_html: entry.content.filter(|c| !c.is_empty()),
+                starred: entry.starred,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let feeds: Vec<Feed> = serde_json::from_str(
+            r#"[{"id": 1, "feed_url": "https://blog.rust-lang.org/feed.xml", "title": "Rust Blog",
+                 "site_url": "https://blog.rust-lang.org/", "category": {"id": 2, "title": "Tech"}}]"#,
+        )
+        .unwrap();
+        let page: EntryPage = serde_json::from_str(
+            r#"{"total": 2, "entries": [
+                {"id": 10, "url": "https://blog.rust-lang.org/a", "title": "A", "status": "read",
+                 "starred": true, "published_at": "2024-03-15T12:00:00Z", "content": "<p>A</p>",
+                 "feed": {"feed_url": "https://blog.rust-lang.org/feed.xml"}},
+                {"id": 11, "url": "https://blog.rust-lang.org/b", "title": "B", "status": "removed",
+                 "starred": false, "published_at": "2024-03-16T12:00:00Z", "content": "",
+                 "feed": {"feed_url": "https://blog.rust-lang.org/feed.xml"}}]}"#,
+        )
+        .unwrap();
+
+        let export = convert(feeds, page.entries);
+        assert_eq!(export.feeds[0].folder.as_deref(), Some("Tech"));
+        assert_eq!(export.items.len(), 1);
+        assert!(export.items[0].read && export.items[0].starred);
+        assert_eq!(
+            export.items[0].feed_url,
+            "https://blog.rust-lang.org/feed.xml"
+        );
+    }
+}
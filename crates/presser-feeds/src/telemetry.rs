@@ -0,0 +1,145 @@
+//! Outbound request telemetry: every HTTP request the fetcher makes is
+//! reported to an optional observer, which records latencies and logs slow
+//! requests, and the response bodies it downloads are counted by the
+//! [`ByteMeter`] measuring the calling task
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How a request ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    /// The server answered with this status
+    Status(u16),
+    /// The request hit the fetcher's timeout
+    TimedOut,
+    /// Connection, TLS, or other transport failure
+    Failed,
+}
+
+/// One finished request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestRecord<'a> {
+    pub url: &'a str,
+    /// Time until the response headers arrived (or the request failed)
+    pub duration: Duration,
+    pub outcome: RequestOutcome,
+}
+
+/// Called after each request (see [`crate::FeedFetcher::with_request_observer`])
+pub type RequestObserver = Arc<dyn Fn(&RequestRecord) + Send + Sync>;
+
+/// Send `request`, reporting it to `observer`
+pub(crate) async fn send(
+    observer: Option<&RequestObserver>,
+    url: &str,
+    request: reqwest::RequestBuilder,
+) -> reqwest::Result<reqwest::Response> {
+    let started = Instant::now();
+    let result = request.send().await;
+    if let Some(observer) = observer {
+        observer(&RequestRecord {
+            url,
+            duration: started.elapsed(),
+            outcome: match &result {
+                Ok(response) => RequestOutcome::Status(response.status().as_u16()),
+                Err(e) if e.is_timeout() => RequestOutcome::TimedOut,
+                Err(_) => RequestOutcome::Failed,
+            },
+        });
+    }
+    result
+}
+
+tokio::task_local! {
+    static METER: ByteMeter;
+}
+
+/// Counts the response body bytes downloaded by a task, e.g. one feed's
+/// update with its extracted pages and comment feeds
+///
+/// Bytes downloaded by tasks spawned from the measured one aren't counted.
+#[derive(Debug, Clone, Default)]
+pub struct ByteMeter(Arc<AtomicU64>);
+
+impl ByteMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `future`, counting what the fetcher downloads meanwhile
+    pub async fn measure<F: Future>(&self, future: F) -> F::Output {
+        METER.scope(self.clone(), future).await
+    }
+
+    /// Bytes counted so far
+    pub fn bytes(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Count a downloaded body against the current task's meter, if any
+pub(crate) fn count_bytes(len: usize) {
+    let _ = METER.try_with(|meter| meter.0.fetch_add(len as u64, Ordering::Relaxed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_send_reports_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/feed")
+            .with_status(404)
+            .create_async()
+            .await;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let observer: RequestObserver = {
+            let seen = seen.clone();
+            Arc::new(move |record: &RequestRecord| {
+                seen.lock()
+                    .unwrap()
+                    .push((record.url.to_string(), record.outcome))
+            })
+        };
+
+        let client = reqwest::Client::new();
+        let url = format!("{}/feed", server.url());
+        let response = send(Some(&observer), &url, client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 404);
+        let _ = send(
+            Some(&observer),
+            "http://127.0.0.1:1/",
+            client.get("http://127.0.0.1:1/"),
+        )
+        .await;
+
+        mock.assert_async().await;
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                (url, RequestOutcome::Status(404)),
+                ("http://127.0.0.1:1/".to_string(), RequestOutcome::Failed),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_byte_meter() {
+        let meter = ByteMeter::new();
+        meter
+            .measure(async {
+                count_bytes(100);
+                meter.measure(async { count_bytes(20) }).await;
+            })
+            .await;
+        // Outside any measured task
+        count_bytes(5);
+        assert_eq!(meter.bytes(), 120);
+    }
+}
@@ -0,0 +1,77 @@
+//! User agents sent with feed requests
+//!
+//! Some sites refuse the default `Presser/x.y` agent but serve a browser, so
+//! a feed answering 403 can be retried once with the other of the two.
+
+use crate::FeedError;
+
+/// Preset name for [`BROWSER`] in config
+pub const BROWSER_PRESET: &str = "browser";
+
+/// A current desktop browser's user agent
+pub const BROWSER: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+    (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+/// Presser's own user agent (`Presser/<version>`)
+pub fn default_user_agent() -> String {
+    format!("Presser/{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// User agent a configured value stands for: `browser` for [`BROWSER`], an
+/// empty value for [`default_user_agent`], anything else as-is
+pub fn resolve(configured: &str) -> String {
+    match configured.trim() {
+        "" => default_user_agent(),
+        preset if preset.eq_ignore_ascii_case(BROWSER_PRESET) => BROWSER.to_string(),
+        user_agent => user_agent.to_string(),
+    }
+}
+
+/// User agent to retry with after `sent` was refused: the browser's, or
+/// Presser's own when the browser's was refused
+pub fn alternate(sent: &str) -> String {
+    if sent == BROWSER {
+        default_user_agent()
+    } else {
+        BROWSER.to_string()
+    }
+}
+
+/// Whether a fetch failed because the server answered 403 Forbidden
+pub fn is_forbidden(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<FeedError>(),
+        Some(FeedError::HttpStatus { status: 403, .. })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve("browser"), BROWSER);
+        assert_eq!(resolve("Browser"), BROWSER);
+        assert_eq!(resolve(""), default_user_agent());
+        assert_eq!(resolve("MyReader/2.0"), "MyReader/2.0");
+    }
+
+    #[test]
+    fn test_alternate() {
+        assert_eq!(alternate(&default_user_agent()), BROWSER);
+        assert_eq!(alternate("MyReader/2.0"), BROWSER);
+        assert_eq!(alternate(BROWSER), default_user_agent());
+    }
+
+    #[test]
+    fn test_is_forbidden() {
+        let status = |status| FeedError::HttpStatus {
+            url: "https://example.com/feed".into(),
+            status,
+        };
+        assert!(is_forbidden(&status(403).into()));
+        assert!(!is_forbidden(&status(404).into()));
+        assert!(!is_forbidden(&anyhow::anyhow!("HTTP 403")));
+    }
+}
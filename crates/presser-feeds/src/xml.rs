@@ -0,0 +1,55 @@
+//! Reading XML attributes the same way in every quick-xml parser
+//!
+//! feed-rs turns on quick-xml's `encoding` feature, under which attribute
+//! values can only be unescaped after decoding them the way the reader
+//! decodes the document.
+
+use quick_xml::events::BytesStart;
+use quick_xml::Reader;
+
+/// An element's attributes as `(name, value)` pairs: names as written,
+/// prefix included, and values decoded and unescaped
+pub(crate) fn attributes<R>(
+    reader: &Reader<R>,
+    element: &BytesStart,
+) -> quick_xml::Result<Vec<(String, String)>> {
+    element
+        .attributes()
+        .map(|attribute| {
+            let attribute = attribute?;
+            let value = attribute.decode_and_unescape_value(reader)?.into_owned();
+            Ok((
+                String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+                value,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+
+    #[test]
+    fn test_attributes() {
+        let mut reader = Reader::from_str(
+            r#"<link thr:count="3" title="Q&amp;A" href='a?b=1&amp;c=2'/><a x="&bogus;"/>"#,
+        );
+        let Ok(Event::Empty(element)) = reader.read_event() else {
+            panic!("expected an empty element");
+        };
+        assert_eq!(
+            attributes(&reader, &element).unwrap(),
+            vec![
+                ("thr:count".to_string(), "3".to_string()),
+                ("title".to_string(), "Q&A".to_string()),
+                ("href".to_string(), "a?b=1&c=2".to_string()),
+            ]
+        );
+        let Ok(Event::Empty(element)) = reader.read_event() else {
+            panic!("expected an empty element");
+        };
+        assert!(attributes(&reader, &element).is_err());
+    }
+}
@@ -8,7 +8,8 @@
 //!
 //! - Cron-based scheduling
 //! - Per-feed custom schedules
-//! - Concurrent task execution with limits
+//! - Concurrent task execution with limits, per named group of tasks
+//! - Pausing and resuming individual tasks
 //! - Task cancellation and cleanup
 //!
 //! # Example
@@ -42,7 +43,9 @@
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, RwLock, Semaphore};
@@ -52,7 +55,7 @@ pub mod error;
 pub mod task;
 
 pub use error::SchedulerError;
-pub use task::Task;
+pub use task::{Task, DEFAULT_GROUP};
 
 /// Scheduler for managing periodic tasks
 pub struct Scheduler {
@@ -70,6 +73,12 @@ pub struct Scheduler {
 
     /// Concurrency limiter
     semaphore: Arc<Semaphore>,
+
+    /// Limiters of the groups with a limit of their own
+    groups: HashMap<String, Arc<Semaphore>>,
+
+    /// Whether tasks that need the network are held back
+    offline: AtomicBool,
 }
 
 /// A scheduled task with its cron schedule
@@ -86,10 +95,46 @@ struct ScheduledTask {
     /// Next execution time
     next_run: DateTime<Utc>,
 
+    /// Skipped by the scheduler until resumed
+    paused: bool,
+
+    /// Came due while offline; runs as soon as the scheduler is back online
+    deferred: bool,
+
     /// Task execution function
     executor: Arc<dyn Task>,
 }
 
+/// Snapshot of a scheduled task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskStatus {
+    /// Task ID
+    pub id: String,
+
+    /// Last execution time
+    pub last_run: Option<DateTime<Utc>>,
+
+    /// Next execution time (still advanced while paused)
+    pub next_run: DateTime<Utc>,
+
+    /// Whether the task is paused
+    pub paused: bool,
+
+    /// Whether the task came due while offline and waits for the network
+    #[serde(default)]
+    pub deferred: bool,
+}
+
+/// When a cron expression next fires, without scheduling anything (None if
+/// it never does again)
+pub fn next_run(schedule: &str) -> Result<Option<DateTime<Utc>>> {
+    Ok(parse(schedule)?.upcoming(Utc).next())
+}
+
+fn parse(schedule: &str) -> Result<cron::Schedule> {
+    schedule.parse().context("Failed to parse cron expression")
+}
+
 impl Scheduler {
     /// Create a new scheduler with the given concurrency limit
     pub fn new(max_concurrent: usize) -> Result<Self> {
@@ -106,9 +151,26 @@ impl Scheduler {
             running: Arc::new(RwLock::new(false)),
             shutdown_tx,
             semaphore,
+            groups: HashMap::new(),
+            offline: AtomicBool::new(false),
         })
     }
 
+    /// Run the tasks of `group` (see [`Task::group`]) at most `max_concurrent`
+    /// at a time, whatever tasks of other groups are running
+    ///
+    /// Tasks of groups without a limit of their own share the one given to
+    /// [`Scheduler::new`].
+    pub fn with_group(mut self, group: impl Into<String>, max_concurrent: usize) -> Result<Self> {
+        let group = group.into();
+        if max_concurrent == 0 {
+            anyhow::bail!("max_concurrent of group {} must be greater than 0", group);
+        }
+        self.groups
+            .insert(group, Arc::new(Semaphore::new(max_concurrent)));
+        Ok(self)
+    }
+
     /// Add a task to the scheduler
     ///
     /// # Arguments
@@ -124,11 +186,7 @@ impl Scheduler {
     ) -> Result<()> {
         let id = id.into();
 
-        // Parse cron schedule
-        let schedule: cron::Schedule = schedule
-            .parse()
-            .context("Failed to parse cron expression")?;
-
+        let schedule = parse(schedule)?;
         let next_run = schedule
             .upcoming(Utc)
             .next()
@@ -139,6 +197,8 @@ impl Scheduler {
             schedule,
             last_run: None,
             next_run,
+            paused: false,
+            deferred: false,
             executor,
         };
 
@@ -158,6 +218,95 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Swap a scheduled task's implementation, and its cron schedule when
+    /// `schedule` is given, keeping when it last ran and whether it is paused
+    /// or deferred
+    ///
+    /// A new schedule takes effect from now: the task next runs when it
+    /// next fires.
+    pub async fn replace(
+        &self,
+        id: &str,
+        schedule: Option<&str>,
+        executor: Arc<dyn Task>,
+    ) -> Result<(), SchedulerError> {
+        let schedule = schedule
+            .map(|schedule| {
+                let parsed = parse(schedule)
+                    .map_err(|_| SchedulerError::InvalidCron(schedule.to_string()))?;
+                let next_run = parsed
+                    .upcoming(Utc)
+                    .next()
+                    .context("Failed to calculate next run time")?;
+                Ok::<_, SchedulerError>((parsed, next_run))
+            })
+            .transpose()?;
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(id)
+            .ok_or_else(|| SchedulerError::TaskNotFound(id.to_string()))?;
+        task.executor = executor;
+        if let Some((schedule, next_run)) = schedule {
+            task.schedule = schedule;
+            task.next_run = next_run;
+            tracing::info!("Rescheduled task: {}", id);
+        }
+        Ok(())
+    }
+
+    /// Stop running a task on schedule until [`resume`](Self::resume) is called
+    pub async fn pause(&self, id: &str) -> Result<(), SchedulerError> {
+        self.set_paused(id, true).await
+    }
+
+    /// Run a paused task on schedule again
+    pub async fn resume(&self, id: &str) -> Result<(), SchedulerError> {
+        self.set_paused(id, false).await
+    }
+
+    async fn set_paused(&self, id: &str, paused: bool) -> Result<(), SchedulerError> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(id)
+            .ok_or_else(|| SchedulerError::TaskNotFound(id.to_string()))?;
+        task.paused = paused;
+        tracing::info!("{} task: {}", if paused { "Paused" } else { "Resumed" }, id);
+        Ok(())
+    }
+
+    /// Status of every scheduled task, ordered by ID
+    pub async fn tasks(&self) -> Vec<TaskStatus> {
+        let tasks = self.tasks.read().await;
+        let mut statuses: Vec<TaskStatus> = tasks
+            .values()
+            .map(|task| TaskStatus {
+                id: task.id.clone(),
+                last_run: task.last_run,
+                next_run: task.next_run,
+                paused: task.paused,
+                deferred: task.deferred,
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.id.cmp(&b.id));
+        statuses
+    }
+
+    /// Hold back tasks that need the network (see [`Task::needs_network`])
+    /// while `offline`; a task that comes due meanwhile runs once the
+    /// scheduler is back online, instead of failing now
+    pub fn set_offline(&self, offline: bool) {
+        if self.offline.swap(offline, Ordering::Relaxed) != offline {
+            tracing::info!(
+                "Scheduler is {}",
+                if offline { "offline" } else { "back online" }
+            );
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
     /// Start the scheduler
     ///
     /// This will begin executing tasks according to their schedules
@@ -191,6 +340,7 @@ impl Scheduler {
     /// Process one scheduler tick
     async fn tick(&self) {
         let now = Utc::now();
+        let offline = self.is_offline();
 
         // Collect tasks to run while holding lock briefly
         let tasks_to_run: Vec<_> = {
@@ -198,19 +348,26 @@ impl Scheduler {
             tasks
                 .values_mut()
                 .filter_map(|task| {
-                    if task.next_run <= now {
-                        let executor = task.executor.clone();
-                        let id = task.id.clone();
-
-                        task.last_run = Some(now);
+                    let due = task.next_run <= now;
+                    if due {
                         if let Some(next) = task.schedule.upcoming(Utc).next() {
                             task.next_run = next;
                         }
-
-                        Some((id, executor))
-                    } else {
-                        None
                     }
+                    if task.paused || !(due || task.deferred) {
+                        return None;
+                    }
+                    if offline && task.executor.needs_network() {
+                        if !task.deferred {
+                            tracing::debug!("Offline, deferring task: {}", task.id);
+                        }
+                        task.deferred = true;
+                        return None;
+                    }
+
+                    task.deferred = false;
+                    task.last_run = Some(now);
+                    Some((task.id.clone(), task.executor.clone()))
                 })
                 .collect()
         };
@@ -219,10 +376,16 @@ impl Scheduler {
         // Spawn tasks outside the lock
         let mut new_handles = Vec::new();
         for (id, executor) in tasks_to_run {
-            let permit = match self.semaphore.clone().try_acquire_owned() {
+            let group = executor.group();
+            let semaphore = self.groups.get(group).unwrap_or(&self.semaphore);
+            let permit = match semaphore.clone().try_acquire_owned() {
                 Ok(p) => p,
                 Err(_) => {
-                    tracing::debug!("Concurrency limit reached, skipping task: {}", id);
+                    tracing::debug!(
+                        "Concurrency limit of {} reached, skipping task: {}",
+                        group,
+                        id
+                    );
                     continue;
                 }
             };
@@ -333,6 +496,234 @@ mod tests {
         assert_eq!(scheduler.task_count().await, 1);
     }
 
+    #[test]
+    fn test_next_run() {
+        let next = next_run("0 0 * * * *").unwrap().unwrap();
+        assert!(next > Utc::now());
+        assert!(next <= Utc::now() + chrono::Duration::hours(1));
+        assert!(next_run("every hour").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        struct NoopTask;
+
+        #[async_trait::async_trait]
+        impl Task for NoopTask {
+            async fn execute(&self) -> Result<()> {
+                Ok(())
+            }
+            fn name(&self) -> &str {
+                "noop"
+            }
+        }
+
+        let scheduler = Scheduler::new(2).unwrap();
+        for id in ["b", "a"] {
+            scheduler
+                .schedule(id, "0 0 * * * *", Arc::new(NoopTask))
+                .await
+                .unwrap();
+        }
+
+        scheduler.pause("b").await.unwrap();
+        let tasks = scheduler.tasks().await;
+        let ids: Vec<_> = tasks.iter().map(|t| (t.id.as_str(), t.paused)).collect();
+        assert_eq!(ids, [("a", false), ("b", true)]);
+
+        scheduler.resume("b").await.unwrap();
+        assert!(!scheduler.tasks().await[1].paused);
+        assert!(matches!(
+            scheduler.pause("missing").await,
+            Err(SchedulerError::TaskNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replace() {
+        struct NoopTask;
+
+        #[async_trait::async_trait]
+        impl Task for NoopTask {
+            async fn execute(&self) -> Result<()> {
+                Ok(())
+            }
+            fn name(&self) -> &str {
+                "noop"
+            }
+        }
+
+        let scheduler = Scheduler::new(2).unwrap();
+        scheduler
+            .schedule("a", "0 0 0 1 1 *", Arc::new(NoopTask))
+            .await
+            .unwrap();
+        scheduler.pause("a").await.unwrap();
+        let before = scheduler.tasks().await[0].clone();
+
+        // Same schedule: only the implementation changes
+        scheduler
+            .replace("a", None, Arc::new(NoopTask))
+            .await
+            .unwrap();
+        assert_eq!(scheduler.tasks().await[0], before);
+
+        scheduler
+            .replace("a", Some("0 * * * * *"), Arc::new(NoopTask))
+            .await
+            .unwrap();
+        let after = &scheduler.tasks().await[0];
+        assert!(after.paused);
+        assert!(after.next_run < before.next_run);
+
+        assert!(matches!(
+            scheduler
+                .replace("a", Some("not cron"), Arc::new(NoopTask))
+                .await,
+            Err(SchedulerError::InvalidCron(_))
+        ));
+        assert!(matches!(
+            scheduler.replace("missing", None, Arc::new(NoopTask)).await,
+            Err(SchedulerError::TaskNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_offline_deferral() {
+        use std::sync::atomic::AtomicUsize;
+
+        struct FetchTask {
+            count: Arc<AtomicUsize>,
+        }
+
+        #[async_trait::async_trait]
+        impl Task for FetchTask {
+            async fn execute(&self) -> Result<()> {
+                self.count.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+            fn name(&self) -> &str {
+                "fetch"
+            }
+            fn needs_network(&self) -> bool {
+                true
+            }
+        }
+
+        let scheduler = Scheduler::new(2).unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        scheduler
+            .schedule(
+                "fetch",
+                "0 0 0 1 1 *",
+                Arc::new(FetchTask {
+                    count: count.clone(),
+                }),
+            )
+            .await
+            .unwrap();
+        let due = Utc::now() - chrono::Duration::seconds(1);
+        scheduler
+            .tasks
+            .write()
+            .await
+            .get_mut("fetch")
+            .unwrap()
+            .next_run = due;
+
+        scheduler.set_offline(true);
+        scheduler.tick().await;
+        let status = &scheduler.tasks().await[0];
+        assert!(status.deferred);
+        assert!(status.next_run > Utc::now());
+
+        // Still offline: nothing runs
+        scheduler.tick().await;
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        // Back online, the deferred task runs without waiting for its next run
+        scheduler.set_offline(false);
+        scheduler.tick().await;
+        for handle in scheduler.handles.write().await.drain(..) {
+            handle.await.unwrap();
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(!scheduler.tasks().await[0].deferred);
+    }
+
+    #[tokio::test]
+    async fn test_groups() {
+        use std::sync::atomic::AtomicUsize;
+
+        /// Runs until released, in a group
+        struct GroupTask {
+            group: &'static str,
+            started: Arc<AtomicUsize>,
+            release: Arc<tokio::sync::Notify>,
+        }
+
+        #[async_trait::async_trait]
+        impl Task for GroupTask {
+            async fn execute(&self) -> Result<()> {
+                self.started.fetch_add(1, Ordering::SeqCst);
+                self.release.notified().await;
+                Ok(())
+            }
+            fn name(&self) -> &str {
+                self.group
+            }
+            fn group(&self) -> &str {
+                self.group
+            }
+        }
+
+        assert!(Scheduler::new(1).unwrap().with_group("fetches", 0).is_err());
+        let scheduler = Scheduler::new(1)
+            .unwrap()
+            .with_group("fetches", 2)
+            .unwrap()
+            .with_group("ai-heavy", 1)
+            .unwrap();
+        let started = Arc::new(AtomicUsize::new(0));
+        let release = Arc::new(tokio::sync::Notify::new());
+        let tasks = [
+            ("digest", "ai-heavy"),
+            ("summaries", "ai-heavy"),
+            ("feed-1", "fetches"),
+            ("feed-2", "fetches"),
+            ("feed-3", "fetches"),
+            ("other", DEFAULT_GROUP),
+        ];
+        for (id, group) in tasks {
+            let task = GroupTask {
+                group,
+                started: started.clone(),
+                release: release.clone(),
+            };
+            scheduler
+                .schedule(id, "0 0 0 1 1 *", Arc::new(task))
+                .await
+                .unwrap();
+            scheduler.tasks.write().await.get_mut(id).unwrap().next_run =
+                Utc::now() - chrono::Duration::seconds(1);
+        }
+
+        // One AI task and two fetches fit beside each other, and the
+        // ungrouped task gets the scheduler's own slot
+        scheduler.tick().await;
+        let running = scheduler.handles.read().await.len();
+        assert_eq!(running, 4);
+
+        while started.load(Ordering::SeqCst) < running {
+            tokio::task::yield_now().await;
+        }
+        release.notify_waiters();
+        for handle in scheduler.handles.write().await.drain(..) {
+            handle.await.unwrap();
+        }
+        assert_eq!(started.load(Ordering::SeqCst), 4);
+    }
+
     #[tokio::test]
     async fn test_shutdown() {
         let scheduler = Scheduler::new(2).unwrap();
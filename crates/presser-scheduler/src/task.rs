@@ -3,6 +3,9 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
+/// Group of tasks that don't name one
+pub const DEFAULT_GROUP: &str = "default";
+
 /// Trait for executable tasks
 #[async_trait]
 pub trait Task: Send + Sync {
@@ -11,6 +14,19 @@ pub trait Task: Send + Sync {
 
     /// Get the task name/description
     fn name(&self) -> &str;
+
+    /// Whether the task needs the network, and so waits while the scheduler
+    /// is offline
+    fn needs_network(&self) -> bool {
+        false
+    }
+
+    /// Concurrency group the task runs in (see
+    /// [`crate::Scheduler::with_group`]); tasks of groups without a limit of
+    /// their own share the scheduler's
+    fn group(&self) -> &str {
+        DEFAULT_GROUP
+    }
 }
 
 /// Example task implementation for feed updates